@@ -49,6 +49,14 @@ require("pinnacle").setup(function(Pinnacle)
         end
     end)
 
+    -- mod_key + alt + shift + c = Close window, killing it if it doesn't close
+    Input.keybind({ mod_key, "alt", "shift" }, "c", function()
+        local focused = Window.get_focused()
+        if focused then
+            focused:close_or_kill(3)
+        end
+    end)
+
     -- mod_key + alt + Return = Spawn `terminal`
     Input.keybind({ mod_key }, key.Return, function()
         Process.spawn(terminal)
@@ -81,6 +89,90 @@ require("pinnacle").setup(function(Pinnacle)
         end
     end)
 
+    -- mod_key + g = Group the focused window with the next one
+    Input.keybind({ mod_key }, "g", function()
+        local focused = Window.get_focused()
+        if focused then
+            focused:add_to_group()
+        end
+    end)
+
+    -- mod_key + shift + g = Remove the focused window from its group
+    Input.keybind({ mod_key, "shift" }, "g", function()
+        local focused = Window.get_focused()
+        if focused then
+            focused:remove_from_group()
+        end
+    end)
+
+    -- mod_key + tab = Cycle forward through the focused window's group
+    Input.keybind({ mod_key }, key.Tab, function()
+        local focused = Window.get_focused()
+        if focused then
+            focused:cycle_group_forward()
+        end
+    end)
+
+    -- mod_key + shift + tab = Cycle backward through the focused window's group
+    Input.keybind({ mod_key, "shift" }, key.Tab, function()
+        local focused = Window.get_focused()
+        if focused then
+            focused:cycle_group_backward()
+        end
+    end)
+
+    -- mod_key + i = Open the next window right after the focused one
+    Input.keybind({ mod_key }, "i", function()
+        local focused = Window.get_focused()
+        if focused then
+            focused:mark_insert_position()
+        end
+    end)
+
+    -- mod_key + j and mod_key + k focus the next and previous tiled window
+    Input.keybind({ mod_key }, "j", function()
+        Window.cycle_focus_forward()
+    end)
+
+    Input.keybind({ mod_key }, "k", function()
+        Window.cycle_focus_backward()
+    end)
+
+    -- alt + tab and alt + shift + tab open the window switcher and cycle through windows
+    -- in the order they were focused. Releasing alt focuses the selected window.
+    Input.keybind({ "alt" }, key.Tab, function()
+        Window.cycle_switcher_forward()
+    end)
+
+    Input.keybind({ "alt", "shift" }, key.Tab, function()
+        Window.cycle_switcher_backward()
+    end)
+
+    -- mod_key + u jumps to a window that wants attention
+    Input.keybind({ mod_key }, "u", function()
+        Window.focus_urgent()
+    end)
+
+    -- mod_key + arrow keys focus the nearest window in that direction
+    for keysym, direction in pairs({
+        [key.Left] = "left",
+        [key.Right] = "right",
+        [key.Up] = "up",
+        [key.Down] = "down",
+    }) do
+        Input.keybind({ mod_key }, keysym, function()
+            Window.move_focus(direction)
+        end)
+
+        -- mod_key + shift + arrow keys move the focused window in that direction
+        Input.keybind({ mod_key, "shift" }, keysym, function()
+            local focused = Window.get_focused()
+            if focused then
+                focused:move_in_direction(direction)
+            end
+        end)
+    end
+
     ----------------------
     -- Tags and Outputs --
     ----------------------
@@ -91,11 +183,18 @@ require("pinnacle").setup(function(Pinnacle)
     --
     -- `Output.setup` allows you to declare things like mode, scale, and tags for outputs.
     -- Here we give all outputs tags 1 through 5.
+    -- Remember which tags were active and which tags apps' windows were on
+    -- across restarts and config reloads
+    Pinnacle.set_session_persistence(true)
+
     Output.setup({
         -- "*" matches all outputs
         ["*"] = { tags = tag_names },
     })
 
+    -- Bring back the tags that were active last time
+    Pinnacle.restore_session()
+
     -- If you want to declare output locations as well, you can use `Output.setup_locs`.
     -- This will additionally allow you to recalculate output locations on signals like
     -- output connect, disconnect, and resize.
@@ -133,6 +232,27 @@ require("pinnacle").setup(function(Pinnacle)
         end)
     end
 
+    -- mod_key + grave = Flip back to the previously active tags
+    Input.keybind({ mod_key }, key.grave, function()
+        Tag.switch_to_previous()
+    end)
+
+    -- mod_key + scroll up/down = Switch to the previous or next tag
+    Input.scrollbind({ mod_key }, "up", function()
+        Tag.switch_left()
+    end)
+    Input.scrollbind({ mod_key }, "down", function()
+        Tag.switch_right()
+    end)
+
+    -- 3-finger swipe left/right = Switch to the next or previous tag
+    Input.swipebind({}, 3, "left", function()
+        Tag.switch_right()
+    end)
+    Input.swipebind({}, 3, "right", function()
+        Tag.switch_left()
+    end)
+
     --------------------
     -- Layouts        --
     --------------------
@@ -186,6 +306,7 @@ require("pinnacle").setup(function(Pinnacle)
         Layout.builtins.corner({ corner_loc = "bottom_right" }),
         Layout.builtins.fair(),
         Layout.builtins.fair({ direction = "horizontal" }),
+        Layout.builtins.monocle(),
     })
 
     -- Set the cycling layout manager as the layout manager that will be used.
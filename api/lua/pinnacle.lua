@@ -26,6 +26,10 @@ local pinnacle = {
     layout = require("pinnacle.layout"),
     ---@type Render
     render = require("pinnacle.render"),
+    ---@type Screenshot
+    screenshot = require("pinnacle.screenshot"),
+    ---@type Debug
+    debug = require("pinnacle.debug"),
 }
 
 ---Quit Pinnacle.
@@ -38,6 +42,39 @@ function pinnacle.reload_config()
     client.unary_request(pinnacle_service.ReloadConfig, {})
 end
 
+---Set whether tags and the tags windows are on are saved across restarts and config reloads.
+---
+---Tags are saved by name for each output, and windows by app id. While enabled, new windows
+---are put on the tags their app was last on unless a window rule places them.
+---Use `Pinnacle.restore_session` to bring back the saved tags.
+---
+---### Example
+---```lua
+---Pinnacle.set_session_persistence(true)
+---```
+---
+---@param enabled boolean
+function pinnacle.set_session_persistence(enabled)
+    client.unary_request(pinnacle_service.SetSessionPersistence, { enabled = enabled })
+end
+
+---Restore the saved session.
+---
+---Every output activates the tags that were active on it, and open windows move back to the
+---tags their app was last on. Call this after adding tags so there's something to restore.
+---
+---### Example
+---```lua
+---Pinnacle.set_session_persistence(true)
+---
+---Output.setup({ ["*"] = { tags = { "1", "2", "3" } } })
+---
+---Pinnacle.restore_session()
+---```
+function pinnacle.restore_session()
+    client.unary_request(pinnacle_service.RestoreSession, {})
+end
+
 ---Setup a Pinnacle config.
 ---
 ---You must pass in a function that takes in the `Pinnacle` table. This table is how you'll access the other config modules.
@@ -0,0 +1,73 @@
+-- This Source Code Form is subject to the terms of the Mozilla Public
+-- License, v. 2.0. If a copy of the MPL was not distributed with this
+-- file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+local client = require("pinnacle.grpc.client")
+local debug_service = require("pinnacle.grpc.defs").pinnacle.debug.v0alpha1.DebugService
+
+local set_or_toggle = {
+    SET = 1,
+    [true] = 1,
+    UNSET = 2,
+    [false] = 2,
+    TOGGLE = 3,
+}
+
+---Debugging utilities.
+---
+---@class Debug
+local debug = {}
+
+---Show or hide the performance overlay.
+---
+---The overlay is drawn in the top-left corner of every output and shows the output's FPS,
+---its 50th and 99th percentile frame times, the percentage of the output that was damaged
+---in the last frame, and whether the last frame was scanned out directly or composited.
+---
+---@param enabled boolean
+function debug.set_perf_overlay(enabled)
+    client.unary_request(debug_service.SetPerfOverlay, { set_or_toggle = set_or_toggle[enabled] })
+end
+
+---Toggle the performance overlay.
+---
+---#### Example
+---```lua
+---Input.keybind({ "super", "shift" }, "p", function()
+---    Debug.toggle_perf_overlay()
+---end)
+---```
+function debug.toggle_perf_overlay()
+    client.unary_request(debug_service.SetPerfOverlay, { set_or_toggle = set_or_toggle.TOGGLE })
+end
+
+---Enable or disable damage visualization.
+---
+---While enabled, regions of outputs that were redrawn flash red and every render element
+---is outlined in yellow. This is useful for finding clients or parts of Pinnacle that redraw
+---more than they need to.
+---
+---@param enabled boolean
+function debug.set_damage_visualization(enabled)
+    client.unary_request(
+        debug_service.SetDamageVisualization,
+        { set_or_toggle = set_or_toggle[enabled] }
+    )
+end
+
+---Toggle damage visualization.
+---
+---#### Example
+---```lua
+---Input.keybind({ "super", "shift" }, "d", function()
+---    Debug.toggle_damage_visualization()
+---end)
+---```
+function debug.toggle_damage_visualization()
+    client.unary_request(
+        debug_service.SetDamageVisualization,
+        { set_or_toggle = set_or_toggle.TOGGLE }
+    )
+end
+
+return debug
@@ -86,7 +86,8 @@ function client.unary_request(grpc_request_args, data)
     -- TODO: check response headers for errors
     local _ = stream:get_headers()
 
-    local response_body = stream:get_next_chunk()
+    -- Large responses like screenshots are split into multiple chunks
+    local response_body = stream:get_body_as_string()
 
     local trailers = stream:get_headers()
     if trailers then -- idk if im big dummy or not but there are never any trailers
@@ -26,6 +26,11 @@ local defs = {}
 ---@class pinnacle.v0alpha1.PingResponse
 ---@field payload string
 
+---@class pinnacle.v0alpha1.SetSessionPersistenceRequest
+---@field enabled boolean?
+
+---@class pinnacle.v0alpha1.RestoreSessionRequest
+
 ---@enum pinnacle.v0alpha1.SetOrToggle
 local pinnacle_v0alpha1_SetOrToggle = {
     SET_OR_TOGGLE_UNSPECIFIED = 0,
@@ -54,10 +59,59 @@ local pinnacle_output_v0alpha1_Transform = {
     TRANSFORM_FLIPPED_270 = 8,
 }
 
+---@enum pinnacle.output.v0alpha1.Vrr
+local pinnacle_output_v0alpha1_Vrr = {
+    VRR_UNSPECIFIED = 0,
+    VRR_OFF = 1,
+    VRR_ON = 2,
+    VRR_ON_DEMAND = 3,
+}
+
+---@enum pinnacle.output.v0alpha1.ColorFilter
+local pinnacle_output_v0alpha1_ColorFilter = {
+    COLOR_FILTER_UNSPECIFIED = 0,
+    COLOR_FILTER_NONE = 1,
+    COLOR_FILTER_GRAYSCALE = 2,
+    COLOR_FILTER_INVERT = 3,
+    COLOR_FILTER_PROTANOPIA = 4,
+    COLOR_FILTER_DEUTERANOPIA = 5,
+    COLOR_FILTER_TRITANOPIA = 6,
+    COLOR_FILTER_PROTANOPIA_CORRECTION = 7,
+    COLOR_FILTER_DEUTERANOPIA_CORRECTION = 8,
+    COLOR_FILTER_TRITANOPIA_CORRECTION = 9,
+}
+
+---@enum pinnacle.output.v0alpha1.WallpaperMode
+local pinnacle_output_v0alpha1_WallpaperMode = {
+    WALLPAPER_MODE_UNSPECIFIED = 0,
+    WALLPAPER_MODE_FILL = 1,
+    WALLPAPER_MODE_FIT = 2,
+    WALLPAPER_MODE_STRETCH = 3,
+}
+
+---@enum pinnacle.output.v0alpha1.Alignment
+local pinnacle_output_v0alpha1_Alignment = {
+    ALIGNMENT_UNSPECIFIED = 0,
+    ALIGNMENT_TOP_ALIGN_LEFT = 1,
+    ALIGNMENT_TOP_ALIGN_CENTER = 2,
+    ALIGNMENT_TOP_ALIGN_RIGHT = 3,
+    ALIGNMENT_BOTTOM_ALIGN_LEFT = 4,
+    ALIGNMENT_BOTTOM_ALIGN_CENTER = 5,
+    ALIGNMENT_BOTTOM_ALIGN_RIGHT = 6,
+    ALIGNMENT_LEFT_ALIGN_TOP = 7,
+    ALIGNMENT_LEFT_ALIGN_CENTER = 8,
+    ALIGNMENT_LEFT_ALIGN_BOTTOM = 9,
+    ALIGNMENT_RIGHT_ALIGN_TOP = 10,
+    ALIGNMENT_RIGHT_ALIGN_CENTER = 11,
+    ALIGNMENT_RIGHT_ALIGN_BOTTOM = 12,
+}
+
 ---@class pinnacle.output.v0alpha1.SetLocationRequest
 ---@field output_name string?
 ---@field x integer?
 ---@field y integer?
+---@field relative_to string?
+---@field alignment pinnacle.output.v0alpha1.Alignment?
 
 ---@class pinnacle.output.v0alpha1.SetModeRequest
 ---@field output_name string?
@@ -65,6 +119,35 @@ local pinnacle_output_v0alpha1_Transform = {
 ---@field pixel_height integer?
 ---@field refresh_rate_millihz integer?
 
+---@class pinnacle.output.v0alpha1.Modeline
+---@field clock number?
+---@field hdisplay integer?
+---@field hsync_start integer?
+---@field hsync_end integer?
+---@field htotal integer?
+---@field vdisplay integer?
+---@field vsync_start integer?
+---@field vsync_end integer?
+---@field vtotal integer?
+---@field hsync_pos boolean?
+---@field vsync_pos boolean?
+
+---@class pinnacle.output.v0alpha1.SetModelineRequest
+---@field output_name string?
+---@field modeline pinnacle.output.v0alpha1.Modeline?
+
+---@class pinnacle.output.v0alpha1.CvtMode
+---@field pixel_width integer?
+---@field pixel_height integer?
+---@field refresh_rate_millihz integer?
+---@field reduced_blanking boolean?
+
+---@class pinnacle.output.v0alpha1.AddCustomModeRequest
+---@field output_name string?
+---@field modeline pinnacle.output.v0alpha1.Modeline?
+---@field modeline_string string?
+---@field cvt pinnacle.output.v0alpha1.CvtMode?
+
 ---@class pinnacle.output.v0alpha1.SetScaleRequest
 ---@field output_name string?
 ---@field absolute number?
@@ -74,6 +157,67 @@ local pinnacle_output_v0alpha1_Transform = {
 ---@field output_name string?
 ---@field transform pinnacle.output.v0alpha1.Transform
 
+---@class pinnacle.output.v0alpha1.SetVrrRequest
+---@field output_name string?
+---@field vrr pinnacle.output.v0alpha1.Vrr?
+
+---@class pinnacle.output.v0alpha1.SetMaxRenderTimeRequest
+---@field output_name string?
+---@field millis integer?
+---@field adaptive table?
+
+---@class pinnacle.output.v0alpha1.SetMaxFpsRequest
+---@field output_name string?
+---@field max_fps integer?
+
+---@class pinnacle.output.v0alpha1.SetColorFilterRequest
+---@field output_name string?
+---@field color_filter pinnacle.output.v0alpha1.ColorFilter?
+
+---@class pinnacle.output.v0alpha1.SetNightLightRequest.FixedSchedule
+---@field from_minute integer?
+---@field to_minute integer?
+
+---@class pinnacle.output.v0alpha1.SetNightLightRequest.Location
+---@field latitude number?
+---@field longitude number?
+
+---@class pinnacle.output.v0alpha1.SetNightLightRequest
+---@field output_name string?
+---@field temperature integer?
+---@field fixed pinnacle.output.v0alpha1.SetNightLightRequest.FixedSchedule?
+---@field location pinnacle.output.v0alpha1.SetNightLightRequest.Location?
+
+---@class pinnacle.output.v0alpha1.SetWallpaperRequest.Color
+---@field r number?
+---@field g number?
+---@field b number?
+
+---@class pinnacle.output.v0alpha1.SetWallpaperRequest.Image
+---@field path string?
+---@field mode pinnacle.output.v0alpha1.WallpaperMode?
+
+---@class pinnacle.output.v0alpha1.SetWallpaperRequest
+---@field output_name string?
+---@field color pinnacle.output.v0alpha1.SetWallpaperRequest.Color?
+---@field image pinnacle.output.v0alpha1.SetWallpaperRequest.Image?
+
+---@class pinnacle.output.v0alpha1.SetPoweredRequest
+---@field output_name string?
+---@field powered boolean?
+
+---@class pinnacle.output.v0alpha1.SetEnabledRequest
+---@field output_name string?
+---@field enabled boolean?
+
+---@class pinnacle.output.v0alpha1.SetPrimaryRequest
+---@field output_name string?
+
+---@class pinnacle.output.v0alpha1.SaveProfileRequest
+
+---@class pinnacle.output.v0alpha1.ClearProfilesRequest
+---@field all boolean?
+
 ---@class pinnacle.output.v0alpha1.GetRequest
 
 ---@class pinnacle.output.v0alpha1.GetResponse
@@ -99,12 +243,20 @@ local pinnacle_output_v0alpha1_Transform = {
 ---@field scale number?
 ---@field transform pinnacle.output.v0alpha1.Transform?
 ---@field serial integer?
+---@field vrr pinnacle.output.v0alpha1.Vrr?
+---@field enabled boolean?
+---@field powered boolean?
+---@field primary boolean?
 
 -- Window
 
 ---@class pinnacle.window.v0alpha1.CloseRequest
 ---@field window_id integer?
 
+---@class pinnacle.window.v0alpha1.CloseOrKillRequest
+---@field window_id integer?
+---@field timeout_ms integer?
+
 ---@class pinnacle.window.v0alpha1.SetGeometryRequest
 ---@field window_id integer?
 ---@field geometry pinnacle.v0alpha1.Geometry?
@@ -134,15 +286,145 @@ local pinnacle_output_v0alpha1_Transform = {
 ---@field tag_id integer?
 ---@field set_or_toggle pinnacle.v0alpha1.SetOrToggle?
 
+---@class pinnacle.window.v0alpha1.SetTagsRequest
+---@field window_id integer?
+---@field tag_ids integer[]?
+
 ---@class pinnacle.window.v0alpha1.RaiseRequest
 ---@field window_id integer?
 
+---@class pinnacle.window.v0alpha1.LowerRequest
+---@field window_id integer?
+
+---@class pinnacle.window.v0alpha1.SetClickToRaiseRequest
+---@field click_to_raise boolean?
+
+---@class pinnacle.window.v0alpha1.SetFocusFollowsMouseRequest
+---@field enabled boolean?
+---@field delay_ms integer?
+
+---@enum pinnacle.window.v0alpha1.GroupMode
+local pinnacle_window_v0alpha1_GroupMode = {
+    GROUP_MODE_UNSPECIFIED = 0,
+    GROUP_MODE_TABBED = 1,
+    GROUP_MODE_STACKED = 2,
+}
+
+---@enum pinnacle.window.v0alpha1.InsertPosition
+local pinnacle_window_v0alpha1_InsertPosition = {
+    INSERT_POSITION_UNSPECIFIED = 0,
+    INSERT_POSITION_END = 1,
+    INSERT_POSITION_AFTER_FOCUSED = 2,
+    INSERT_POSITION_MASTER = 3,
+}
+
+---@class pinnacle.window.v0alpha1.AddToGroupRequest
+---@field window_id integer?
+---@field target_window_id integer?
+
+---@class pinnacle.window.v0alpha1.RemoveFromGroupRequest
+---@field window_id integer?
+
+---@class pinnacle.window.v0alpha1.SetGroupModeRequest
+---@field window_id integer?
+---@field mode pinnacle.window.v0alpha1.GroupMode?
+
+---@class pinnacle.window.v0alpha1.CycleGroupRequest
+---@field window_id integer?
+---@field backward boolean?
+
 ---@class pinnacle.window.v0alpha1.MoveGrabRequest
 ---@field button integer?
 
 ---@class pinnacle.window.v0alpha1.ResizeGrabRequest
 ---@field button integer?
 
+---@class pinnacle.window.v0alpha1.SetInsertPositionRequest
+---@field position pinnacle.window.v0alpha1.InsertPosition?
+
+---@class pinnacle.window.v0alpha1.MarkInsertPositionRequest
+---@field window_id integer?
+
+---@class pinnacle.window.v0alpha1.CycleFocusRequest
+---@field backward boolean?
+
+---@enum pinnacle.window.v0alpha1.Direction
+local pinnacle_window_v0alpha1_Direction = {
+    DIRECTION_UNSPECIFIED = 0,
+    DIRECTION_LEFT = 1,
+    DIRECTION_RIGHT = 2,
+    DIRECTION_UP = 3,
+    DIRECTION_DOWN = 4,
+}
+
+---@class pinnacle.window.v0alpha1.MoveFocusRequest
+---@field direction pinnacle.window.v0alpha1.Direction?
+
+---@class pinnacle.window.v0alpha1.MoveInDirectionRequest
+---@field window_id integer?
+---@field direction pinnacle.window.v0alpha1.Direction?
+
+---@class pinnacle.window.v0alpha1.FocusUrgentRequest
+
+---@class pinnacle.window.v0alpha1.CycleWindowSwitcherRequest
+---@field backward boolean?
+
+---@class pinnacle.window.v0alpha1.SetWindowSwitcherThumbnailsRequest
+---@field thumbnails boolean?
+
+---@class pinnacle.window.v0alpha1.SetUnresponsiveOverlayRequest
+---@field enabled boolean?
+
+---@enum pinnacle.window.v0alpha1.WindowLayer
+local pinnacle_window_v0alpha1_WindowLayer = {
+    WINDOW_LAYER_UNSPECIFIED = 0,
+    WINDOW_LAYER_NORMAL = 1,
+    WINDOW_LAYER_ABOVE = 2,
+    WINDOW_LAYER_BELOW = 3,
+}
+
+---@class pinnacle.window.v0alpha1.SetLayerRequest
+---@field window_id integer?
+---@field layer pinnacle.window.v0alpha1.WindowLayer?
+
+---@class pinnacle.window.v0alpha1.SetFakeFullscreenRequest
+---@field window_id integer?
+---@field set_or_toggle pinnacle.v0alpha1.SetOrToggle?
+
+---@enum pinnacle.window.v0alpha1.PipCorner
+local pinnacle_window_v0alpha1_PipCorner = {
+    PIP_CORNER_UNSPECIFIED = 0,
+    PIP_CORNER_TOP_LEFT = 1,
+    PIP_CORNER_TOP_RIGHT = 2,
+    PIP_CORNER_BOTTOM_LEFT = 3,
+    PIP_CORNER_BOTTOM_RIGHT = 4,
+}
+
+---@class pinnacle.window.v0alpha1.SetPipPlacementRequest
+---@field corner pinnacle.window.v0alpha1.PipCorner?
+---@field width integer?
+---@field height integer?
+
+---@class pinnacle.window.v0alpha1.SetSnapThresholdRequest
+---@field threshold integer?
+
+---@class pinnacle.window.v0alpha1.RememberedGeometry
+---@field app_id string?
+---@field geometry pinnacle.v0alpha1.Geometry?
+
+---@class pinnacle.window.v0alpha1.GetRememberedGeometriesRequest
+
+---@class pinnacle.window.v0alpha1.GetRememberedGeometriesResponse
+---@field geometries pinnacle.window.v0alpha1.RememberedGeometry[]?
+
+---@class pinnacle.window.v0alpha1.ForgetGeometryRequest
+---@field app_id string?
+
+---@class pinnacle.window.v0alpha1.ResizeTileRequest
+---@field window_id integer?
+---@field width_delta integer?
+---@field height_delta integer?
+
 ---@class pinnacle.window.v0alpha1.GetRequest
 
 ---@class pinnacle.window.v0alpha1.GetResponse
@@ -159,6 +441,8 @@ local pinnacle_output_v0alpha1_Transform = {
 ---@field floating boolean?
 ---@field fullscreen_or_maximized pinnacle.window.v0alpha1.FullscreenOrMaximized?
 ---@field tag_ids integer[]?
+---@field layer pinnacle.window.v0alpha1.WindowLayer?
+---@field urgent boolean?
 
 ---@enum pinnacle.window.v0alpha1.FullscreenOrMaximized
 local pinnacle_window_v0alpha1_FullscreenOrMaximized = {
@@ -172,12 +456,39 @@ local pinnacle_window_v0alpha1_FullscreenOrMaximized = {
 ---@field cond pinnacle.window.v0alpha1.WindowRuleCondition?
 ---@field rule pinnacle.window.v0alpha1.WindowRule?
 
+---@class pinnacle.window.v0alpha1.AddWindowRuleResponse
+---@field rule_id integer?
+
+---@class pinnacle.window.v0alpha1.RemoveWindowRuleRequest
+---@field rule_id integer?
+
 ---@class pinnacle.window.v0alpha1.WindowRuleCondition
 ---@field any pinnacle.window.v0alpha1.WindowRuleCondition[]?
 ---@field all pinnacle.window.v0alpha1.WindowRuleCondition[]?
 ---@field classes string[]?
 ---@field titles string[]?
 ---@field tags integer[]?
+---@field class_regexes string[]?
+---@field title_regexes string[]?
+---@field x11_classes string[]?
+---@field x11_roles string[]?
+---@field outputs string[]?
+---@field floating boolean?
+---@field none pinnacle.window.v0alpha1.WindowRuleCondition[]?
+
+---@enum pinnacle.window.v0alpha1.FloatingAnchor
+local pinnacle_window_v0alpha1_FloatingAnchor = {
+    FLOATING_ANCHOR_UNSPECIFIED = 0,
+    FLOATING_ANCHOR_CENTER = 1,
+    FLOATING_ANCHOR_TOP = 2,
+    FLOATING_ANCHOR_BOTTOM = 3,
+    FLOATING_ANCHOR_LEFT = 4,
+    FLOATING_ANCHOR_RIGHT = 5,
+    FLOATING_ANCHOR_TOP_LEFT = 6,
+    FLOATING_ANCHOR_TOP_RIGHT = 7,
+    FLOATING_ANCHOR_BOTTOM_LEFT = 8,
+    FLOATING_ANCHOR_BOTTOM_RIGHT = 9,
+}
 
 ---@class pinnacle.window.v0alpha1.WindowRule
 ---@field output string?
@@ -188,6 +499,15 @@ local pinnacle_window_v0alpha1_FullscreenOrMaximized = {
 ---@field y integer?
 ---@field width integer?
 ---@field height integer?
+---@field animations boolean?
+---@field corner_radius number?
+---@field blur boolean?
+---@field dim boolean?
+---@field layer pinnacle.window.v0alpha1.WindowLayer?
+---@field pip boolean?
+---@field fake_fullscreen boolean?
+---@field follow boolean?
+---@field anchor pinnacle.window.v0alpha1.FloatingAnchor?
 
 -- Tag
 
@@ -198,6 +518,52 @@ local pinnacle_window_v0alpha1_FullscreenOrMaximized = {
 ---@class pinnacle.tag.v0alpha1.SwitchToRequest
 ---@field tag_id integer?
 
+---@class pinnacle.tag.v0alpha1.SwitchToPreviousRequest
+---@field output_name string?
+---@field index integer?
+
+---@class pinnacle.tag.v0alpha1.SwitchToRelativeRequest
+---@field output_name string?
+---@field offset integer?
+
+---@class pinnacle.tag.v0alpha1.GetHistoryRequest
+---@field output_name string?
+
+---@class pinnacle.tag.v0alpha1.GetHistoryResponse.TagSet
+---@field tag_ids integer[]?
+
+---@class pinnacle.tag.v0alpha1.GetHistoryResponse
+---@field entries pinnacle.tag.v0alpha1.GetHistoryResponse.TagSet[]?
+
+---@class pinnacle.tag.v0alpha1.SetGapsRequest
+---@field tag_id integer?
+---@field inner integer?
+---@field outer integer?
+
+---@class pinnacle.tag.v0alpha1.SetCornerRadiusRequest
+---@field tag_id integer?
+---@field radius number?
+
+---@class pinnacle.tag.v0alpha1.SetLayoutRequest
+---@field tag_id integer?
+---@field layout string?
+
+---@class pinnacle.tag.v0alpha1.SetNameRequest
+---@field tag_id integer?
+---@field name string?
+
+---@class pinnacle.tag.v0alpha1.MoveToOutputRequest
+---@field tag_id integer?
+---@field output_name string?
+
+---@class pinnacle.tag.v0alpha1.ReorderRequest
+---@field tag_id integer?
+---@field index integer?
+---@field offset integer?
+
+---@class pinnacle.tag.v0alpha1.SetDynamicRequest
+---@field enabled boolean?
+
 ---@class pinnacle.tag.v0alpha1.AddRequest
 ---@field output_name string?
 ---@field tag_names string[]?
@@ -207,6 +573,7 @@ local pinnacle_window_v0alpha1_FullscreenOrMaximized = {
 
 ---@class pinnacle.tag.v0alpha1.RemoveRequest
 ---@field tag_ids integer[]?
+---@field fallback_tag_id integer?
 
 ---@class pinnacle.tag.v0alpha1.GetRequest
 
@@ -221,6 +588,9 @@ local pinnacle_window_v0alpha1_FullscreenOrMaximized = {
 ---@field name string?
 ---@field output_name string?
 ---@field window_ids integer[]?
+---@field layout string?
+---@field urgent boolean?
+---@field hidden boolean?
 
 -- Input
 
@@ -237,9 +607,26 @@ local pinnacle_input_v0alpha1_Modifier = {
 ---@field modifiers pinnacle.input.v0alpha1.Modifier[]?
 ---@field raw_code integer?
 ---@field xkb_name string?
+---@field group string?
+---@field description string?
+---@field on_release boolean?
+---@field repeat boolean?
+---@field priority integer?
 
 ---@class pinnacle.input.v0alpha1.SetKeybindResponse
 
+---@class pinnacle.input.v0alpha1.ListKeybindsRequest
+
+---@class pinnacle.input.v0alpha1.ListKeybindsResponse.Keybind
+---@field modifiers pinnacle.input.v0alpha1.Modifier[]?
+---@field raw_code integer?
+---@field xkb_name string?
+---@field group string?
+---@field description string?
+
+---@class pinnacle.input.v0alpha1.ListKeybindsResponse
+---@field keybinds pinnacle.input.v0alpha1.ListKeybindsResponse.Keybind[]?
+
 ---@enum pinnacle.input.v0alpha1.SetMousebindRequest.MouseEdge
 local pinnacle_input_v0alpha1_SetMousebindRequest_MouseEdge = {
     MOUSE_EDGE_UNSPECIFIED = 0,
@@ -253,17 +640,128 @@ local pinnacle_input_v0alpha1_SetMousebindRequest_MouseEdge = {
 
 ---@class pinnacle.input.v0alpha1.SetMousebindResponse
 
+---@enum pinnacle.input.v0alpha1.SetScrollbindRequest.ScrollDirection
+local pinnacle_input_v0alpha1_SetScrollbindRequest_ScrollDirection = {
+    SCROLL_DIRECTION_UNSPECIFIED = 0,
+    SCROLL_DIRECTION_UP = 1,
+    SCROLL_DIRECTION_DOWN = 2,
+    SCROLL_DIRECTION_LEFT = 3,
+    SCROLL_DIRECTION_RIGHT = 4,
+}
+
+---@class pinnacle.input.v0alpha1.SetScrollbindRequest
+---@field modifiers pinnacle.input.v0alpha1.Modifier[]?
+---@field direction pinnacle.input.v0alpha1.SetScrollbindRequest.ScrollDirection?
+
+---@class pinnacle.input.v0alpha1.SetScrollbindResponse
+
+---@enum pinnacle.input.v0alpha1.SetSwipebindRequest.SwipeDirection
+local pinnacle_input_v0alpha1_SetSwipebindRequest_SwipeDirection = {
+    SWIPE_DIRECTION_UNSPECIFIED = 0,
+    SWIPE_DIRECTION_UP = 1,
+    SWIPE_DIRECTION_DOWN = 2,
+    SWIPE_DIRECTION_LEFT = 3,
+    SWIPE_DIRECTION_RIGHT = 4,
+}
+
+---@class pinnacle.input.v0alpha1.SetSwipebindRequest
+---@field modifiers pinnacle.input.v0alpha1.Modifier[]?
+---@field fingers integer?
+---@field direction pinnacle.input.v0alpha1.SetSwipebindRequest.SwipeDirection?
+
+---@class pinnacle.input.v0alpha1.SetSwipebindResponse
+
 ---@class SetXkbConfigRequest
 ---@field rules string?
 ---@field variant string?
 ---@field layout string?
 ---@field model string?
 ---@field options string?
+---@field compose_key string?
+
+---@class pinnacle.input.v0alpha1.SwitchXkbLayoutRequest
+---@field index integer?
+---@field offset integer?
+
+---@class pinnacle.input.v0alpha1.GetXkbLayoutsRequest
+
+---@class pinnacle.input.v0alpha1.GetXkbLayoutsResponse
+---@field names string[]?
+---@field active_index integer?
+
+---@class pinnacle.input.v0alpha1.SetPerWindowXkbLayoutRequest
+---@field enabled boolean?
 
 ---@class SetRepeatRateRequest
 ---@field rate integer?
 ---@field delay integer?
 
+---@class pinnacle.input.v0alpha1.SetLockKeysRequest
+---@field num_lock boolean?
+---@field caps_lock boolean?
+
+---@class pinnacle.input.v0alpha1.SetStickyKeysRequest
+---@field enabled boolean?
+
+---@class pinnacle.input.v0alpha1.SetSlowKeysRequest
+---@field delay_ms integer?
+
+---@class pinnacle.input.v0alpha1.SetMouseKeysRequest
+---@field enabled boolean?
+
+---@class pinnacle.input.v0alpha1.RemapKey
+---@field keycode integer?
+---@field keysym integer?
+---@field xkb_name string?
+
+---@class pinnacle.input.v0alpha1.SetKeyRemapRequest
+---@field from pinnacle.input.v0alpha1.RemapKey?
+---@field to pinnacle.input.v0alpha1.RemapKey?
+---@field filter pinnacle.input.v0alpha1.DeviceFilter?
+
+---@enum pinnacle.input.v0alpha1.LidSwitchPolicy
+local pinnacle_input_v0alpha1_LidSwitchPolicy = {
+    LID_SWITCH_POLICY_UNSPECIFIED = 0,
+    LID_SWITCH_POLICY_IGNORE = 1,
+    LID_SWITCH_POLICY_CLAMSHELL = 2,
+}
+
+---@class pinnacle.input.v0alpha1.SetLidSwitchPolicyRequest
+---@field policy pinnacle.input.v0alpha1.LidSwitchPolicy?
+
+---@class pinnacle.input.v0alpha1.WarpPointerRequest.Location
+---@field x number?
+---@field y number?
+
+---@class pinnacle.input.v0alpha1.WarpPointerRequest
+---@field location pinnacle.input.v0alpha1.WarpPointerRequest.Location?
+---@field window_id integer?
+---@field output_name string?
+
+---@class pinnacle.input.v0alpha1.SetWarpPointerOnOutputFocusRequest
+---@field enabled boolean?
+
+---@class pinnacle.input.v0alpha1.SetCursorThemeRequest
+---@field theme string?
+---@field size integer?
+
+---@enum pinnacle.input.v0alpha1.DeviceType
+local pinnacle_input_v0alpha1_DeviceType = {
+    DEVICE_TYPE_UNSPECIFIED = 0,
+    DEVICE_TYPE_TOUCHPAD = 1,
+    DEVICE_TYPE_TRACKPOINT = 2,
+    DEVICE_TYPE_MOUSE = 3,
+    DEVICE_TYPE_TABLET = 4,
+    DEVICE_TYPE_TOUCHSCREEN = 5,
+    DEVICE_TYPE_KEYBOARD = 6,
+}
+
+---@class pinnacle.input.v0alpha1.DeviceFilter
+---@field name string?
+---@field vendor_id integer?
+---@field product_id integer?
+---@field device_type pinnacle.input.v0alpha1.DeviceType?
+
 ---@enum pinnacle.input.v0alpha1.SetLibinputSettingRequest.AccelProfile
 local pinnacle_input_v0alpha1_SetLibinputSettingRequest_AccelProfile = {
     ACCEL_PROFILE_UNSPECIFIED = 0,
@@ -314,6 +812,44 @@ local pinnacle_input_v0alpha1_SetLibinputSettingRequest_TapButtonMap = {
 ---@field tap_drag boolean?
 ---@field tap_drag_lock boolean?
 ---@field tap boolean?
+---@field filter pinnacle.input.v0alpha1.DeviceFilter?
+
+---@class pinnacle.input.v0alpha1.Device
+---@field name string?
+---@field vendor_id integer?
+---@field product_id integer?
+---@field device_type pinnacle.input.v0alpha1.DeviceType?
+
+---@class pinnacle.input.v0alpha1.GetDevicesRequest
+---@field filter pinnacle.input.v0alpha1.DeviceFilter?
+
+---@class pinnacle.input.v0alpha1.GetDevicesResponse
+---@field devices pinnacle.input.v0alpha1.Device[]?
+
+---@class pinnacle.input.v0alpha1.SetScrollFactorRequest
+---@field filter pinnacle.input.v0alpha1.DeviceFilter?
+---@field factor number?
+
+---@class pinnacle.input.v0alpha1.SetTouchOutputRequest
+---@field filter pinnacle.input.v0alpha1.DeviceFilter?
+---@field output_name string?
+
+---@class pinnacle.input.v0alpha1.SetTabletSettingRequest.PressureCurve
+---@field x1 number?
+---@field y1 number?
+---@field x2 number?
+---@field y2 number?
+
+---@class pinnacle.input.v0alpha1.SetTabletSettingRequest.ButtonRemap
+---@field from integer?
+---@field to integer?
+
+---@class pinnacle.input.v0alpha1.SetTabletSettingRequest
+---@field filter pinnacle.input.v0alpha1.DeviceFilter?
+---@field output_name string?
+---@field region pinnacle.v0alpha1.Geometry?
+---@field pressure_curve pinnacle.input.v0alpha1.SetTabletSettingRequest.PressureCurve?
+---@field button_remap pinnacle.input.v0alpha1.SetTabletSettingRequest.ButtonRemap?
 
 -- Process
 
@@ -321,6 +857,9 @@ local pinnacle_input_v0alpha1_SetLibinputSettingRequest_TapButtonMap = {
 ---@field args string[]?
 ---@field once boolean?
 ---@field has_callback boolean?
+---@field tag_ids integer[]?
+---@field output string?
+---@field follow boolean?
 
 ---@class pinnacle.process.v0alpha1.SpawnResponse
 ---@field stdout string?
@@ -334,10 +873,19 @@ local pinnacle_input_v0alpha1_SetLibinputSettingRequest_TapButtonMap = {
 
 -- Layout
 
+---@enum pinnacle.layout.v0alpha1.FallbackLayout
+local pinnacle_layout_v0alpha1_FallbackLayout = {
+    FALLBACK_LAYOUT_UNSPECIFIED = 0,
+    FALLBACK_LAYOUT_MASTER_STACK = 1,
+    FALLBACK_LAYOUT_DWINDLE = 2,
+    FALLBACK_LAYOUT_GRID = 3,
+}
+
 ---@class pinnacle.layout.v0alpha1.LayoutRequest.Geometries
 ---@field request_id integer?
 ---@field output_name string?
 ---@field geometries pinnacle.v0alpha1.Geometry[]?
+---@field layout_name string?
 
 ---@class pinnacle.layout.v0alpha1.LayoutRequest.ExplicitLayout
 ---@field output_name string?
@@ -346,6 +894,17 @@ local pinnacle_input_v0alpha1_SetLibinputSettingRequest_TapButtonMap = {
 ---@field geometries pinnacle.layout.v0alpha1.LayoutRequest.Geometries?
 ---@field layout pinnacle.layout.v0alpha1.LayoutRequest.ExplicitLayout?
 
+---@class pinnacle.layout.v0alpha1.SetFallbackLayoutRequest
+---@field layout pinnacle.layout.v0alpha1.FallbackLayout?
+
+---@class pinnacle.layout.v0alpha1.SetMonocleIndicatorRequest
+---@field set_or_toggle pinnacle.v0alpha1.SetOrToggle?
+
+---@class pinnacle.layout.v0alpha1.TileResize
+---@field window_id integer?
+---@field width_delta integer?
+---@field height_delta integer?
+
 ---@class pinnacle.layout.v0alpha1.LayoutResponse
 ---@field request_id integer?
 ---@field output_name string?
@@ -353,6 +912,8 @@ local pinnacle_input_v0alpha1_SetLibinputSettingRequest_TapButtonMap = {
 ---@field tag_ids integer[]?
 ---@field output_width integer?
 ---@field output_height integer?
+---@field layout string?
+---@field resize pinnacle.layout.v0alpha1.TileResize?
 
 -- Render
 
@@ -369,6 +930,56 @@ local pinnacle_render_v0alpha1_Filter = {
 ---@class pinnacle.render.v0alpha1.SetDownscaleFilterRequest
 ---@field filter pinnacle.render.v0alpha1.Filter?
 
+---@class pinnacle.render.v0alpha1.SetAnimationsEnabledRequest
+---@field enabled boolean?
+
+---@class pinnacle.render.v0alpha1.SetCornerRadiusRequest
+---@field radius number?
+
+---@class pinnacle.render.v0alpha1.SetBlurRequest
+---@field passes integer?
+---@field offset number?
+---@field layer_namespaces string[]?
+
+---@class pinnacle.render.v0alpha1.SetDimInactiveRequest
+---@field dim number?
+
+-- Screenshot
+
+---@class pinnacle.screenshot.v0alpha1.ScreenshotRequest
+---@field output_name string?
+---@field region pinnacle.v0alpha1.Geometry?
+---@field window_id integer?
+---@field include_cursor boolean?
+---@field path string?
+
+---@class pinnacle.screenshot.v0alpha1.WindowThumbnailRequest
+---@field window_id integer?
+---@field max_width integer?
+---@field max_height integer?
+
+---@class pinnacle.screenshot.v0alpha1.StartRecordingRequest
+---@field output_name string?
+---@field region pinnacle.v0alpha1.Geometry?
+---@field path string?
+---@field framerate integer?
+---@field include_cursor boolean?
+
+---@class pinnacle.screenshot.v0alpha1.StopRecordingRequest
+
+---@class pinnacle.screenshot.v0alpha1.ScreenshotResponse
+---@field png string?
+---@field width integer?
+---@field height integer?
+
+-- Debug
+
+---@class pinnacle.debug.v0alpha1.SetPerfOverlayRequest
+---@field set_or_toggle pinnacle.v0alpha1.SetOrToggle?
+
+---@class pinnacle.debug.v0alpha1.SetDamageVisualizationRequest
+---@field set_or_toggle pinnacle.v0alpha1.SetOrToggle?
+
 -- Signal
 
 ---@enum pinnacle.signal.v0alpha1.StreamControl
@@ -418,6 +1029,13 @@ local pinnacle_signal_v0alpha1_StreamControl = {
 ---@class pinnacle.signal.v0alpha1.WindowPointerLeaveResponse
 ---@field window_id integer?
 
+---@class pinnacle.signal.v0alpha1.WindowUrgentRequest
+---@field control pinnacle.signal.v0alpha1.StreamControl?
+
+---@class pinnacle.signal.v0alpha1.WindowUrgentResponse
+---@field window_id integer?
+---@field urgent boolean?
+
 ---@class pinnacle.signal.v0alpha1.TagActiveRequest
 ---@field control pinnacle.signal.v0alpha1.StreamControl?
 
@@ -425,6 +1043,34 @@ local pinnacle_signal_v0alpha1_StreamControl = {
 ---@field tag_id integer?
 ---@field active boolean?
 
+---@class pinnacle.signal.v0alpha1.TagUrgentRequest
+---@field control pinnacle.signal.v0alpha1.StreamControl?
+
+---@class pinnacle.signal.v0alpha1.TagUrgentResponse
+---@field tag_id integer?
+---@field urgent boolean?
+
+---@class pinnacle.signal.v0alpha1.TagOccupiedRequest
+---@field control pinnacle.signal.v0alpha1.StreamControl?
+
+---@class pinnacle.signal.v0alpha1.TagOccupiedResponse
+---@field tag_id integer?
+---@field occupied boolean?
+
+---@class pinnacle.signal.v0alpha1.TagRenamedRequest
+---@field control pinnacle.signal.v0alpha1.StreamControl?
+
+---@class pinnacle.signal.v0alpha1.TagRenamedResponse
+---@field tag_id integer?
+---@field name string?
+
+---@class pinnacle.signal.v0alpha1.InputXkbLayoutRequest
+---@field control pinnacle.signal.v0alpha1.StreamControl?
+
+---@class pinnacle.signal.v0alpha1.InputXkbLayoutResponse
+---@field index integer?
+---@field name string?
+
 defs.pinnacle = {
     v0alpha1 = {
         SetOrToggle = util.bijective_table(pinnacle_v0alpha1_SetOrToggle),
@@ -450,11 +1096,29 @@ defs.pinnacle = {
                 request = "pinnacle.v0alpha1.PingRequest",
                 response = "pinnacle.v0alpha1.PingResponse",
             },
+            ---@type GrpcRequestArgs
+            SetSessionPersistence = {
+                service = "pinnacle.v0alpha1.PinnacleService",
+                method = "SetSessionPersistence",
+                request = "pinnacle.v0alpha1.SetSessionPersistenceRequest",
+                response = "google.protobuf.Empty",
+            },
+            ---@type GrpcRequestArgs
+            RestoreSession = {
+                service = "pinnacle.v0alpha1.PinnacleService",
+                method = "RestoreSession",
+                request = "pinnacle.v0alpha1.RestoreSessionRequest",
+                response = "google.protobuf.Empty",
+            },
         },
     },
     output = {
         v0alpha1 = {
             Transform = util.bijective_table(pinnacle_output_v0alpha1_Transform),
+            Vrr = util.bijective_table(pinnacle_output_v0alpha1_Vrr),
+            ColorFilter = util.bijective_table(pinnacle_output_v0alpha1_ColorFilter),
+            WallpaperMode = util.bijective_table(pinnacle_output_v0alpha1_WallpaperMode),
+            Alignment = util.bijective_table(pinnacle_output_v0alpha1_Alignment),
             OutputService = {
                 ---@type GrpcRequestArgs
                 SetLocation = {
@@ -471,6 +1135,20 @@ defs.pinnacle = {
                     response = "google.protobuf.Empty",
                 },
                 ---@type GrpcRequestArgs
+                SetModeline = {
+                    service = "pinnacle.output.v0alpha1.OutputService",
+                    method = "SetModeline",
+                    request = "pinnacle.output.v0alpha1.SetModelineRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                AddCustomMode = {
+                    service = "pinnacle.output.v0alpha1.OutputService",
+                    method = "AddCustomMode",
+                    request = "pinnacle.output.v0alpha1.AddCustomModeRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
                 SetScale = {
                     service = "pinnacle.output.v0alpha1.OutputService",
                     method = "SetScale",
@@ -485,27 +1163,110 @@ defs.pinnacle = {
                     response = "google.protobuf.Empty",
                 },
                 ---@type GrpcRequestArgs
-                Get = {
+                SetVrr = {
                     service = "pinnacle.output.v0alpha1.OutputService",
-                    method = "Get",
-                    request = "pinnacle.output.v0alpha1.GetRequest",
-                    response = "pinnacle.output.v0alpha1.GetResponse",
+                    method = "SetVrr",
+                    request = "pinnacle.output.v0alpha1.SetVrrRequest",
+                    response = "google.protobuf.Empty",
                 },
                 ---@type GrpcRequestArgs
-                GetProperties = {
+                SetMaxRenderTime = {
                     service = "pinnacle.output.v0alpha1.OutputService",
-                    method = "GetProperties",
-                    request = "pinnacle.output.v0alpha1.GetPropertiesRequest",
-                    response = "pinnacle.output.v0alpha1.GetPropertiesResponse",
+                    method = "SetMaxRenderTime",
+                    request = "pinnacle.output.v0alpha1.SetMaxRenderTimeRequest",
+                    response = "google.protobuf.Empty",
                 },
-            },
-        },
-    },
+                ---@type GrpcRequestArgs
+                SetMaxFps = {
+                    service = "pinnacle.output.v0alpha1.OutputService",
+                    method = "SetMaxFps",
+                    request = "pinnacle.output.v0alpha1.SetMaxFpsRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SetColorFilter = {
+                    service = "pinnacle.output.v0alpha1.OutputService",
+                    method = "SetColorFilter",
+                    request = "pinnacle.output.v0alpha1.SetColorFilterRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SetNightLight = {
+                    service = "pinnacle.output.v0alpha1.OutputService",
+                    method = "SetNightLight",
+                    request = "pinnacle.output.v0alpha1.SetNightLightRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SetWallpaper = {
+                    service = "pinnacle.output.v0alpha1.OutputService",
+                    method = "SetWallpaper",
+                    request = "pinnacle.output.v0alpha1.SetWallpaperRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SetPowered = {
+                    service = "pinnacle.output.v0alpha1.OutputService",
+                    method = "SetPowered",
+                    request = "pinnacle.output.v0alpha1.SetPoweredRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SetEnabled = {
+                    service = "pinnacle.output.v0alpha1.OutputService",
+                    method = "SetEnabled",
+                    request = "pinnacle.output.v0alpha1.SetEnabledRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SetPrimary = {
+                    service = "pinnacle.output.v0alpha1.OutputService",
+                    method = "SetPrimary",
+                    request = "pinnacle.output.v0alpha1.SetPrimaryRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SaveProfile = {
+                    service = "pinnacle.output.v0alpha1.OutputService",
+                    method = "SaveProfile",
+                    request = "pinnacle.output.v0alpha1.SaveProfileRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                ClearProfiles = {
+                    service = "pinnacle.output.v0alpha1.OutputService",
+                    method = "ClearProfiles",
+                    request = "pinnacle.output.v0alpha1.ClearProfilesRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                Get = {
+                    service = "pinnacle.output.v0alpha1.OutputService",
+                    method = "Get",
+                    request = "pinnacle.output.v0alpha1.GetRequest",
+                    response = "pinnacle.output.v0alpha1.GetResponse",
+                },
+                ---@type GrpcRequestArgs
+                GetProperties = {
+                    service = "pinnacle.output.v0alpha1.OutputService",
+                    method = "GetProperties",
+                    request = "pinnacle.output.v0alpha1.GetPropertiesRequest",
+                    response = "pinnacle.output.v0alpha1.GetPropertiesResponse",
+                },
+            },
+        },
+    },
     window = {
         v0alpha1 = {
             FullscreenOrMaximized = util.bijective_table(
                 pinnacle_window_v0alpha1_FullscreenOrMaximized
             ),
+            GroupMode = util.bijective_table(pinnacle_window_v0alpha1_GroupMode),
+            InsertPosition = util.bijective_table(pinnacle_window_v0alpha1_InsertPosition),
+            WindowLayer = util.bijective_table(pinnacle_window_v0alpha1_WindowLayer),
+            PipCorner = util.bijective_table(pinnacle_window_v0alpha1_PipCorner),
+            Direction = util.bijective_table(pinnacle_window_v0alpha1_Direction),
+            FloatingAnchor = util.bijective_table(pinnacle_window_v0alpha1_FloatingAnchor),
             WindowService = {
                 ---@type GrpcRequestArgs
                 Close = {
@@ -515,6 +1276,13 @@ defs.pinnacle = {
                     response = "google.protobuf.Empty",
                 },
                 ---@type GrpcRequestArgs
+                CloseOrKill = {
+                    service = "pinnacle.window.v0alpha1.WindowService",
+                    method = "CloseOrKill",
+                    request = "pinnacle.window.v0alpha1.CloseOrKillRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
                 SetGeometry = {
                     service = "pinnacle.window.v0alpha1.WindowService",
                     method = "SetGeometry",
@@ -564,6 +1332,13 @@ defs.pinnacle = {
                     response = "google.protobuf.Empty",
                 },
                 ---@type GrpcRequestArgs
+                SetTags = {
+                    service = "pinnacle.window.v0alpha1.WindowService",
+                    method = "SetTags",
+                    request = "pinnacle.window.v0alpha1.SetTagsRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
                 Raise = {
                     service = "pinnacle.window.v0alpha1.WindowService",
                     method = "Raise",
@@ -571,6 +1346,55 @@ defs.pinnacle = {
                     response = "google.protobuf.Empty",
                 },
                 ---@type GrpcRequestArgs
+                Lower = {
+                    service = "pinnacle.window.v0alpha1.WindowService",
+                    method = "Lower",
+                    request = "pinnacle.window.v0alpha1.LowerRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SetClickToRaise = {
+                    service = "pinnacle.window.v0alpha1.WindowService",
+                    method = "SetClickToRaise",
+                    request = "pinnacle.window.v0alpha1.SetClickToRaiseRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SetFocusFollowsMouse = {
+                    service = "pinnacle.window.v0alpha1.WindowService",
+                    method = "SetFocusFollowsMouse",
+                    request = "pinnacle.window.v0alpha1.SetFocusFollowsMouseRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                AddToGroup = {
+                    service = "pinnacle.window.v0alpha1.WindowService",
+                    method = "AddToGroup",
+                    request = "pinnacle.window.v0alpha1.AddToGroupRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                RemoveFromGroup = {
+                    service = "pinnacle.window.v0alpha1.WindowService",
+                    method = "RemoveFromGroup",
+                    request = "pinnacle.window.v0alpha1.RemoveFromGroupRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SetGroupMode = {
+                    service = "pinnacle.window.v0alpha1.WindowService",
+                    method = "SetGroupMode",
+                    request = "pinnacle.window.v0alpha1.SetGroupModeRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                CycleGroup = {
+                    service = "pinnacle.window.v0alpha1.WindowService",
+                    method = "CycleGroup",
+                    request = "pinnacle.window.v0alpha1.CycleGroupRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
                 MoveGrab = {
                     service = "pinnacle.window.v0alpha1.WindowService",
                     method = "MoveGrab",
@@ -585,6 +1409,118 @@ defs.pinnacle = {
                     response = "google.protobuf.Empty",
                 },
                 ---@type GrpcRequestArgs
+                ResizeTile = {
+                    service = "pinnacle.window.v0alpha1.WindowService",
+                    method = "ResizeTile",
+                    request = "pinnacle.window.v0alpha1.ResizeTileRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SetInsertPosition = {
+                    service = "pinnacle.window.v0alpha1.WindowService",
+                    method = "SetInsertPosition",
+                    request = "pinnacle.window.v0alpha1.SetInsertPositionRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                MarkInsertPosition = {
+                    service = "pinnacle.window.v0alpha1.WindowService",
+                    method = "MarkInsertPosition",
+                    request = "pinnacle.window.v0alpha1.MarkInsertPositionRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                CycleFocus = {
+                    service = "pinnacle.window.v0alpha1.WindowService",
+                    method = "CycleFocus",
+                    request = "pinnacle.window.v0alpha1.CycleFocusRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                MoveFocus = {
+                    service = "pinnacle.window.v0alpha1.WindowService",
+                    method = "MoveFocus",
+                    request = "pinnacle.window.v0alpha1.MoveFocusRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                MoveInDirection = {
+                    service = "pinnacle.window.v0alpha1.WindowService",
+                    method = "MoveInDirection",
+                    request = "pinnacle.window.v0alpha1.MoveInDirectionRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                FocusUrgent = {
+                    service = "pinnacle.window.v0alpha1.WindowService",
+                    method = "FocusUrgent",
+                    request = "pinnacle.window.v0alpha1.FocusUrgentRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                CycleWindowSwitcher = {
+                    service = "pinnacle.window.v0alpha1.WindowService",
+                    method = "CycleWindowSwitcher",
+                    request = "pinnacle.window.v0alpha1.CycleWindowSwitcherRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SetWindowSwitcherThumbnails = {
+                    service = "pinnacle.window.v0alpha1.WindowService",
+                    method = "SetWindowSwitcherThumbnails",
+                    request = "pinnacle.window.v0alpha1.SetWindowSwitcherThumbnailsRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SetUnresponsiveOverlay = {
+                    service = "pinnacle.window.v0alpha1.WindowService",
+                    method = "SetUnresponsiveOverlay",
+                    request = "pinnacle.window.v0alpha1.SetUnresponsiveOverlayRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SetLayer = {
+                    service = "pinnacle.window.v0alpha1.WindowService",
+                    method = "SetLayer",
+                    request = "pinnacle.window.v0alpha1.SetLayerRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SetFakeFullscreen = {
+                    service = "pinnacle.window.v0alpha1.WindowService",
+                    method = "SetFakeFullscreen",
+                    request = "pinnacle.window.v0alpha1.SetFakeFullscreenRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SetPipPlacement = {
+                    service = "pinnacle.window.v0alpha1.WindowService",
+                    method = "SetPipPlacement",
+                    request = "pinnacle.window.v0alpha1.SetPipPlacementRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SetSnapThreshold = {
+                    service = "pinnacle.window.v0alpha1.WindowService",
+                    method = "SetSnapThreshold",
+                    request = "pinnacle.window.v0alpha1.SetSnapThresholdRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                GetRememberedGeometries = {
+                    service = "pinnacle.window.v0alpha1.WindowService",
+                    method = "GetRememberedGeometries",
+                    request = "pinnacle.window.v0alpha1.GetRememberedGeometriesRequest",
+                    response = "pinnacle.window.v0alpha1.GetRememberedGeometriesResponse",
+                },
+                ---@type GrpcRequestArgs
+                ForgetGeometry = {
+                    service = "pinnacle.window.v0alpha1.WindowService",
+                    method = "ForgetGeometry",
+                    request = "pinnacle.window.v0alpha1.ForgetGeometryRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
                 Get = {
                     service = "pinnacle.window.v0alpha1.WindowService",
                     method = "Get",
@@ -603,6 +1539,13 @@ defs.pinnacle = {
                     service = "pinnacle.window.v0alpha1.WindowService",
                     method = "AddWindowRule",
                     request = "pinnacle.window.v0alpha1.AddWindowRuleRequest",
+                    response = "pinnacle.window.v0alpha1.AddWindowRuleResponse",
+                },
+                ---@type GrpcRequestArgs
+                RemoveWindowRule = {
+                    service = "pinnacle.window.v0alpha1.WindowService",
+                    method = "RemoveWindowRule",
+                    request = "pinnacle.window.v0alpha1.RemoveWindowRuleRequest",
                     response = "google.protobuf.Empty",
                 },
             },
@@ -626,6 +1569,76 @@ defs.pinnacle = {
                     response = "google.protobuf.Empty",
                 },
                 ---@type GrpcRequestArgs
+                SwitchToPrevious = {
+                    service = "pinnacle.tag.v0alpha1.TagService",
+                    method = "SwitchToPrevious",
+                    request = "pinnacle.tag.v0alpha1.SwitchToPreviousRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SwitchToRelative = {
+                    service = "pinnacle.tag.v0alpha1.TagService",
+                    method = "SwitchToRelative",
+                    request = "pinnacle.tag.v0alpha1.SwitchToRelativeRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                GetHistory = {
+                    service = "pinnacle.tag.v0alpha1.TagService",
+                    method = "GetHistory",
+                    request = "pinnacle.tag.v0alpha1.GetHistoryRequest",
+                    response = "pinnacle.tag.v0alpha1.GetHistoryResponse",
+                },
+                ---@type GrpcRequestArgs
+                SetGaps = {
+                    service = "pinnacle.tag.v0alpha1.TagService",
+                    method = "SetGaps",
+                    request = "pinnacle.tag.v0alpha1.SetGapsRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SetLayout = {
+                    service = "pinnacle.tag.v0alpha1.TagService",
+                    method = "SetLayout",
+                    request = "pinnacle.tag.v0alpha1.SetLayoutRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SetCornerRadius = {
+                    service = "pinnacle.tag.v0alpha1.TagService",
+                    method = "SetCornerRadius",
+                    request = "pinnacle.tag.v0alpha1.SetCornerRadiusRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SetName = {
+                    service = "pinnacle.tag.v0alpha1.TagService",
+                    method = "SetName",
+                    request = "pinnacle.tag.v0alpha1.SetNameRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                MoveToOutput = {
+                    service = "pinnacle.tag.v0alpha1.TagService",
+                    method = "MoveToOutput",
+                    request = "pinnacle.tag.v0alpha1.MoveToOutputRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                Reorder = {
+                    service = "pinnacle.tag.v0alpha1.TagService",
+                    method = "Reorder",
+                    request = "pinnacle.tag.v0alpha1.ReorderRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SetDynamic = {
+                    service = "pinnacle.tag.v0alpha1.TagService",
+                    method = "SetDynamic",
+                    request = "pinnacle.tag.v0alpha1.SetDynamicRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
                 Add = {
                     service = "pinnacle.tag.v0alpha1.TagService",
                     method = "Add",
@@ -659,11 +1672,23 @@ defs.pinnacle = {
     input = {
         v0alpha1 = {
             Modifier = util.bijective_table(pinnacle_input_v0alpha1_Modifier),
+            LidSwitchPolicy = util.bijective_table(pinnacle_input_v0alpha1_LidSwitchPolicy),
+            DeviceType = util.bijective_table(pinnacle_input_v0alpha1_DeviceType),
             SetMousebindRequest = {
                 MouseEdge = util.bijective_table(
                     pinnacle_input_v0alpha1_SetMousebindRequest_MouseEdge
                 ),
             },
+            SetScrollbindRequest = {
+                ScrollDirection = util.bijective_table(
+                    pinnacle_input_v0alpha1_SetScrollbindRequest_ScrollDirection
+                ),
+            },
+            SetSwipebindRequest = {
+                SwipeDirection = util.bijective_table(
+                    pinnacle_input_v0alpha1_SetSwipebindRequest_SwipeDirection
+                ),
+            },
             SetLibinputSettingRequest = {
                 AccelProfile = util.bijective_table(
                     pinnacle_input_v0alpha1_SetLibinputSettingRequest_AccelProfile
@@ -687,6 +1712,13 @@ defs.pinnacle = {
                     response = "pinnacle.input.v0alpha1.SetKeybindResponse",
                 },
                 ---@type GrpcRequestArgs
+                ListKeybinds = {
+                    service = "pinnacle.input.v0alpha1.InputService",
+                    method = "ListKeybinds",
+                    request = "pinnacle.input.v0alpha1.ListKeybindsRequest",
+                    response = "pinnacle.input.v0alpha1.ListKeybindsResponse",
+                },
+                ---@type GrpcRequestArgs
                 SetMousebind = {
                     service = "pinnacle.input.v0alpha1.InputService",
                     method = "SetMousebind",
@@ -694,6 +1726,20 @@ defs.pinnacle = {
                     response = "pinnacle.input.v0alpha1.SetMousebindResponse",
                 },
                 ---@type GrpcRequestArgs
+                SetScrollbind = {
+                    service = "pinnacle.input.v0alpha1.InputService",
+                    method = "SetScrollbind",
+                    request = "pinnacle.input.v0alpha1.SetScrollbindRequest",
+                    response = "pinnacle.input.v0alpha1.SetScrollbindResponse",
+                },
+                ---@type GrpcRequestArgs
+                SetSwipebind = {
+                    service = "pinnacle.input.v0alpha1.InputService",
+                    method = "SetSwipebind",
+                    request = "pinnacle.input.v0alpha1.SetSwipebindRequest",
+                    response = "pinnacle.input.v0alpha1.SetSwipebindResponse",
+                },
+                ---@type GrpcRequestArgs
                 SetXkbConfig = {
                     service = "pinnacle.input.v0alpha1.InputService",
                     method = "SetXkbConfig",
@@ -701,6 +1747,27 @@ defs.pinnacle = {
                     response = "google.protobuf.Empty",
                 },
                 ---@type GrpcRequestArgs
+                SwitchXkbLayout = {
+                    service = "pinnacle.input.v0alpha1.InputService",
+                    method = "SwitchXkbLayout",
+                    request = "pinnacle.input.v0alpha1.SwitchXkbLayoutRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                GetXkbLayouts = {
+                    service = "pinnacle.input.v0alpha1.InputService",
+                    method = "GetXkbLayouts",
+                    request = "pinnacle.input.v0alpha1.GetXkbLayoutsRequest",
+                    response = "pinnacle.input.v0alpha1.GetXkbLayoutsResponse",
+                },
+                ---@type GrpcRequestArgs
+                SetPerWindowXkbLayout = {
+                    service = "pinnacle.input.v0alpha1.InputService",
+                    method = "SetPerWindowXkbLayout",
+                    request = "pinnacle.input.v0alpha1.SetPerWindowXkbLayoutRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
                 SetRepeatRate = {
                     service = "pinnacle.input.v0alpha1.InputService",
                     method = "SetRepeatRate",
@@ -708,12 +1775,103 @@ defs.pinnacle = {
                     response = "google.protobuf.Empty",
                 },
                 ---@type GrpcRequestArgs
+                SetLockKeys = {
+                    service = "pinnacle.input.v0alpha1.InputService",
+                    method = "SetLockKeys",
+                    request = "pinnacle.input.v0alpha1.SetLockKeysRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SetStickyKeys = {
+                    service = "pinnacle.input.v0alpha1.InputService",
+                    method = "SetStickyKeys",
+                    request = "pinnacle.input.v0alpha1.SetStickyKeysRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SetSlowKeys = {
+                    service = "pinnacle.input.v0alpha1.InputService",
+                    method = "SetSlowKeys",
+                    request = "pinnacle.input.v0alpha1.SetSlowKeysRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SetMouseKeys = {
+                    service = "pinnacle.input.v0alpha1.InputService",
+                    method = "SetMouseKeys",
+                    request = "pinnacle.input.v0alpha1.SetMouseKeysRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SetKeyRemap = {
+                    service = "pinnacle.input.v0alpha1.InputService",
+                    method = "SetKeyRemap",
+                    request = "pinnacle.input.v0alpha1.SetKeyRemapRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                WarpPointer = {
+                    service = "pinnacle.input.v0alpha1.InputService",
+                    method = "WarpPointer",
+                    request = "pinnacle.input.v0alpha1.WarpPointerRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SetWarpPointerOnOutputFocus = {
+                    service = "pinnacle.input.v0alpha1.InputService",
+                    method = "SetWarpPointerOnOutputFocus",
+                    request = "pinnacle.input.v0alpha1.SetWarpPointerOnOutputFocusRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SetCursorTheme = {
+                    service = "pinnacle.input.v0alpha1.InputService",
+                    method = "SetCursorTheme",
+                    request = "pinnacle.input.v0alpha1.SetCursorThemeRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SetLidSwitchPolicy = {
+                    service = "pinnacle.input.v0alpha1.InputService",
+                    method = "SetLidSwitchPolicy",
+                    request = "pinnacle.input.v0alpha1.SetLidSwitchPolicyRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
                 SetLibinputSetting = {
                     service = "pinnacle.input.v0alpha1.InputService",
                     method = "SetLibinputSetting",
                     request = "pinnacle.input.v0alpha1.SetLibinputSettingRequest",
                     response = "google.protobuf.Empty",
                 },
+                ---@type GrpcRequestArgs
+                GetDevices = {
+                    service = "pinnacle.input.v0alpha1.InputService",
+                    method = "GetDevices",
+                    request = "pinnacle.input.v0alpha1.GetDevicesRequest",
+                    response = "pinnacle.input.v0alpha1.GetDevicesResponse",
+                },
+                ---@type GrpcRequestArgs
+                SetScrollFactor = {
+                    service = "pinnacle.input.v0alpha1.InputService",
+                    method = "SetScrollFactor",
+                    request = "pinnacle.input.v0alpha1.SetScrollFactorRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SetTouchOutput = {
+                    service = "pinnacle.input.v0alpha1.InputService",
+                    method = "SetTouchOutput",
+                    request = "pinnacle.input.v0alpha1.SetTouchOutputRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SetTabletSetting = {
+                    service = "pinnacle.input.v0alpha1.InputService",
+                    method = "SetTabletSetting",
+                    request = "pinnacle.input.v0alpha1.SetTabletSettingRequest",
+                    response = "google.protobuf.Empty",
+                },
             },
         },
     },
@@ -739,6 +1897,7 @@ defs.pinnacle = {
     },
     layout = {
         v0alpha1 = {
+            FallbackLayout = util.bijective_table(pinnacle_layout_v0alpha1_FallbackLayout),
             LayoutService = {
                 ---@type GrpcRequestArgs
                 Layout = {
@@ -747,6 +1906,20 @@ defs.pinnacle = {
                     request = "pinnacle.layout.v0alpha1.LayoutRequest",
                     response = "pinnacle.layout.v0alpha1.LayoutResponse",
                 },
+                ---@type GrpcRequestArgs
+                SetFallbackLayout = {
+                    service = "pinnacle.layout.v0alpha1.LayoutService",
+                    method = "SetFallbackLayout",
+                    request = "pinnacle.layout.v0alpha1.SetFallbackLayoutRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SetMonocleIndicator = {
+                    service = "pinnacle.layout.v0alpha1.LayoutService",
+                    method = "SetMonocleIndicator",
+                    request = "pinnacle.layout.v0alpha1.SetMonocleIndicatorRequest",
+                    response = "google.protobuf.Empty",
+                },
             },
         },
     },
@@ -768,6 +1941,88 @@ defs.pinnacle = {
                     request = "pinnacle.render.v0alpha1.SetDownscaleFilterRequest",
                     response = "google.protobuf.Empty",
                 },
+                ---@type GrpcRequestArgs
+                SetAnimationsEnabled = {
+                    service = "pinnacle.render.v0alpha1.RenderService",
+                    method = "SetAnimationsEnabled",
+                    request = "pinnacle.render.v0alpha1.SetAnimationsEnabledRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SetCornerRadius = {
+                    service = "pinnacle.render.v0alpha1.RenderService",
+                    method = "SetCornerRadius",
+                    request = "pinnacle.render.v0alpha1.SetCornerRadiusRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SetBlur = {
+                    service = "pinnacle.render.v0alpha1.RenderService",
+                    method = "SetBlur",
+                    request = "pinnacle.render.v0alpha1.SetBlurRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SetDimInactive = {
+                    service = "pinnacle.render.v0alpha1.RenderService",
+                    method = "SetDimInactive",
+                    request = "pinnacle.render.v0alpha1.SetDimInactiveRequest",
+                    response = "google.protobuf.Empty",
+                },
+            },
+        },
+    },
+    screenshot = {
+        v0alpha1 = {
+            ScreenshotService = {
+                ---@type GrpcRequestArgs
+                Screenshot = {
+                    service = "pinnacle.screenshot.v0alpha1.ScreenshotService",
+                    method = "Screenshot",
+                    request = "pinnacle.screenshot.v0alpha1.ScreenshotRequest",
+                    response = "pinnacle.screenshot.v0alpha1.ScreenshotResponse",
+                },
+                ---@type GrpcRequestArgs
+                WindowThumbnail = {
+                    service = "pinnacle.screenshot.v0alpha1.ScreenshotService",
+                    method = "WindowThumbnail",
+                    request = "pinnacle.screenshot.v0alpha1.WindowThumbnailRequest",
+                    response = "pinnacle.screenshot.v0alpha1.ScreenshotResponse",
+                },
+                ---@type GrpcRequestArgs
+                StartRecording = {
+                    service = "pinnacle.screenshot.v0alpha1.ScreenshotService",
+                    method = "StartRecording",
+                    request = "pinnacle.screenshot.v0alpha1.StartRecordingRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                StopRecording = {
+                    service = "pinnacle.screenshot.v0alpha1.ScreenshotService",
+                    method = "StopRecording",
+                    request = "pinnacle.screenshot.v0alpha1.StopRecordingRequest",
+                    response = "google.protobuf.Empty",
+                },
+            },
+        },
+    },
+    debug = {
+        v0alpha1 = {
+            DebugService = {
+                ---@type GrpcRequestArgs
+                SetPerfOverlay = {
+                    service = "pinnacle.debug.v0alpha1.DebugService",
+                    method = "SetPerfOverlay",
+                    request = "pinnacle.debug.v0alpha1.SetPerfOverlayRequest",
+                    response = "google.protobuf.Empty",
+                },
+                ---@type GrpcRequestArgs
+                SetDamageVisualization = {
+                    service = "pinnacle.debug.v0alpha1.DebugService",
+                    method = "SetDamageVisualization",
+                    request = "pinnacle.debug.v0alpha1.SetDamageVisualizationRequest",
+                    response = "google.protobuf.Empty",
+                },
             },
         },
     },
@@ -819,12 +2074,47 @@ defs.pinnacle = {
                     response = "pinnacle.signal.v0alpha1.WindowPointerLeaveResponse",
                 },
                 ---@type GrpcRequestArgs
+                WindowUrgent = {
+                    service = "pinnacle.signal.v0alpha1.SignalService",
+                    method = "WindowUrgent",
+                    request = "pinnacle.signal.v0alpha1.WindowUrgentRequest",
+                    response = "pinnacle.signal.v0alpha1.WindowUrgentResponse",
+                },
+                ---@type GrpcRequestArgs
                 TagActive = {
                     service = "pinnacle.signal.v0alpha1.SignalService",
                     method = "TagActive",
                     request = "pinnacle.signal.v0alpha1.TagActiveRequest",
                     response = "pinnacle.signal.v0alpha1.TagActiveResponse",
                 },
+                ---@type GrpcRequestArgs
+                TagUrgent = {
+                    service = "pinnacle.signal.v0alpha1.SignalService",
+                    method = "TagUrgent",
+                    request = "pinnacle.signal.v0alpha1.TagUrgentRequest",
+                    response = "pinnacle.signal.v0alpha1.TagUrgentResponse",
+                },
+                ---@type GrpcRequestArgs
+                TagOccupied = {
+                    service = "pinnacle.signal.v0alpha1.SignalService",
+                    method = "TagOccupied",
+                    request = "pinnacle.signal.v0alpha1.TagOccupiedRequest",
+                    response = "pinnacle.signal.v0alpha1.TagOccupiedResponse",
+                },
+                ---@type GrpcRequestArgs
+                TagRenamed = {
+                    service = "pinnacle.signal.v0alpha1.SignalService",
+                    method = "TagRenamed",
+                    request = "pinnacle.signal.v0alpha1.TagRenamedRequest",
+                    response = "pinnacle.signal.v0alpha1.TagRenamedResponse",
+                },
+                ---@type GrpcRequestArgs
+                InputXkbLayout = {
+                    service = "pinnacle.signal.v0alpha1.SignalService",
+                    method = "InputXkbLayout",
+                    request = "pinnacle.signal.v0alpha1.InputXkbLayoutRequest",
+                    response = "pinnacle.signal.v0alpha1.InputXkbLayoutResponse",
+                },
             },
         },
     },
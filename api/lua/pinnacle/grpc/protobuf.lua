@@ -22,6 +22,8 @@ function protobuf.build_protos()
         PINNACLE_PROTO_DIR .. "/pinnacle/signal/" .. version .. "/signal.proto",
         PINNACLE_PROTO_DIR .. "/pinnacle/layout/" .. version .. "/layout.proto",
         PINNACLE_PROTO_DIR .. "/pinnacle/render/" .. version .. "/render.proto",
+        PINNACLE_PROTO_DIR .. "/pinnacle/screenshot/" .. version .. "/screenshot.proto",
+        PINNACLE_PROTO_DIR .. "/pinnacle/debug/" .. version .. "/debug.proto",
         PINNACLE_PROTO_DIR .. "/google/protobuf/empty.proto",
     }
 
@@ -15,6 +15,10 @@ local modifier_values = {
     alt = 3,
     super = 4,
 }
+local modifier_names = {}
+for name, value in pairs(modifier_values) do
+    modifier_names[value] = name
+end
 
 local mouse_button_values = {
     --- Left
@@ -31,6 +35,8 @@ local mouse_button_values = {
     [6] = 0x115,
     --- Back
     [7] = 0x116,
+    --- Task
+    [8] = 0x117,
     btn_left = 0x110,
     btn_right = 0x111,
     btn_middle = 0x112,
@@ -38,6 +44,7 @@ local mouse_button_values = {
     btn_extra = 0x114,
     btn_forward = 0x115,
     btn_back = 0x116,
+    btn_task = 0x117,
 }
 -- This alias is because I can't get @enum completion to work
 ---@alias MouseButton
@@ -48,6 +55,7 @@ local mouse_button_values = {
 ---| 5 Extra
 ---| 6 Forward
 ---| 7 Back,
+---| 8 Task
 ---| "btn_left"
 ---| "btn_right"
 ---| "btn_middle"
@@ -55,6 +63,8 @@ local mouse_button_values = {
 ---| "btn_extra"
 ---| "btn_forward"
 ---| "btn_back"
+---| "btn_task"
+---| integer Any other button, by one of the `BTN_` prefixed codes in input-event-codes.h
 
 local mouse_edge_values = {
     press = 1,
@@ -64,6 +74,37 @@ local mouse_edge_values = {
 ---| "press" Trigger on mouse button press
 ---| "release" Trigger on mouse button release
 
+local scroll_direction_values = {
+    up = 1,
+    down = 2,
+    left = 3,
+    right = 4,
+}
+---@alias ScrollDirection
+---| "up"
+---| "down"
+---| "left"
+---| "right"
+
+local swipe_direction_values = {
+    up = 1,
+    down = 2,
+    left = 3,
+    right = 4,
+}
+---@alias SwipeDirection
+---| "up"
+---| "down"
+---| "left"
+---| "right"
+
+---@class KeybindInfo
+---@field group string? What the keybind is grouped under, like "Window" or "Launchers"
+---@field description string? What the keybind does
+---@field on_release boolean? Trigger the keybind when its key is released instead of pressed
+---@field repeating boolean? Trigger the keybind repeatedly while its key is held
+---@field priority integer? Keybinds for the same key with a lower priority can't replace this one
+
 ---Input management.
 ---
 ---This module provides utilities to set key- and mousebinds as well as change keyboard settings.
@@ -74,7 +115,9 @@ local input = {
 }
 input.mouse_button_values = mouse_button_values
 
----Set a keybind. If called with an already existing keybind, it gets replaced.
+---Set a keybind. If called with an already existing keybind, it gets replaced unless the existing
+---one has a higher `priority` in its `KeybindInfo`. Whichever keybind loses prints an error naming
+---the one it conflicted with.
 ---
 ---You must provide three arguments:
 ---
@@ -85,6 +128,11 @@ input.mouse_button_values = mouse_button_values
 ---     - A string of the key's name. This is the name of the xkbcommon key without the `KEY_` prefix.
 --- - `action`: The function that will be run when the keybind is pressed.
 ---
+---You can also provide a `KeybindInfo` with a group and description for the keybind.
+---These are listed along with it by `Input.keybinds`, so you can show what your keybinds do.
+---Set `on_release` in it to trigger the keybind when its key is released, or `repeating`
+---to keep triggering it at the keyboard's repeat rate while the key is held.
+---
 ---It is important to note that `"a"` is different than `"A"`. Similarly, `key.a` is different than `key.A`.
 ---Usually, it's best to use the non-modified key to prevent confusion and unintended behavior.
 ---
@@ -105,13 +153,19 @@ input.mouse_button_values = mouse_button_values
 --- -- Set `super + Return` to open Alacritty
 ---Input.keybind({ "super" }, Input.key.Return, function()
 ---    Process.spawn("alacritty")
----end)
+---end, { group = "Launchers", description = "Open a terminal" })
+---
+--- -- Keep raising the volume while the key is held
+---Input.keybind({}, "XF86AudioRaiseVolume", function()
+---    Process.spawn({ "wpctl", "set-volume", "@DEFAULT_AUDIO_SINK@", "5%+" })
+---end, { repeating = true })
 ---```
 ---
 ---@param mods Modifier[] The modifiers that need to be held down for the bind to trigger
 ---@param key Key | string The key used to trigger the bind
 ---@param action fun() The function to run when the bind is triggered
-function input.keybind(mods, key, action)
+---@param info KeybindInfo? What the keybind is for
+function input.keybind(mods, key, action, info)
     local raw_code = nil
     local xkb_name = nil
 
@@ -130,13 +184,62 @@ function input.keybind(mods, key, action)
         modifiers = mod_values,
         raw_code = raw_code,
         xkb_name = xkb_name,
+        group = info and info.group,
+        description = info and info.description,
+        on_release = info and info.on_release,
+        ["repeat"] = info and info.repeating,
+        priority = info and info.priority,
     }, action)
 end
 
+---@class Keybind
+---@field mods Modifier[] The modifiers that need to be held down
+---@field key integer The keysym that triggers the keybind
+---@field key_name string The xkbcommon name of the key, without the `KEY_` prefix
+---@field group string? What the keybind is grouped under
+---@field description string? What the keybind does
+
+---Get every keybind set by this config, in the order they were first set.
+---
+---### Example
+---```lua
+---for _, bind in ipairs(Input.keybinds()) do
+---    local mods = table.concat(bind.mods, " + ")
+---    print(("[%s] %s + %s: %s"):format(bind.group, mods, bind.key_name, bind.description))
+---end
+---```
+---
+---@return Keybind[]
+function input.keybinds()
+    local response = client.unary_request(input_service.ListKeybinds, {})
+
+    ---@type Keybind[]
+    local keybinds = {}
+    for _, bind in ipairs(response.keybinds or {}) do
+        local mods = {}
+        for _, mod in ipairs(bind.modifiers or {}) do
+            table.insert(mods, modifier_names[mod])
+        end
+
+        table.insert(keybinds, {
+            mods = mods,
+            key = bind.raw_code,
+            key_name = bind.xkb_name,
+            group = bind.group,
+            description = bind.description,
+        })
+    end
+
+    return keybinds
+end
+
 ---Set a mousebind. If called with an already existing mousebind, it gets replaced.
 ---
 ---You must specify whether the keybind happens on button press or button release.
 ---
+---Buttons without a name can be bound with their code from input-event-codes.h,
+---like `0x118` for `BTN_TASK + 1`.
+---
 ---### Example
 ---```lua
 --- -- Set `super + left mouse button` to move a window on press
@@ -160,17 +263,82 @@ function input.mousebind(mods, button, edge, action)
 
     client.server_streaming_request(input_service.SetMousebind, {
         modifiers = mod_values,
-        button = mouse_button_values[button],
+        button = mouse_button_values[button] or button,
         edge = edge,
     }, action)
 end
 
+---Set a scrollbind. If called with an already existing scrollbind, it gets replaced.
+---
+---The bind triggers once for every notch a scroll wheel turns, and every so often
+---while scrolling on a touchpad. While `mods` are held down, scrolling isn't sent to windows.
+---
+---### Example
+---```lua
+--- -- Set `super + scroll` to switch between tags
+---Input.scrollbind({ "super" }, "up", function()
+---    Tag.switch_left()
+---end)
+---Input.scrollbind({ "super" }, "down", function()
+---    Tag.switch_right()
+---end)
+---```
+---
+---@param mods Modifier[] The modifiers that need to be held down for the bind to trigger
+---@param direction ScrollDirection The direction to scroll in
+---@param action fun() The function to run when the bind is triggered
+function input.scrollbind(mods, direction, action)
+    local mod_values = {}
+    for _, mod in ipairs(mods) do
+        table.insert(mod_values, modifier_values[mod])
+    end
+
+    client.server_streaming_request(input_service.SetScrollbind, {
+        modifiers = mod_values,
+        direction = scroll_direction_values[direction],
+    }, action)
+end
+
+---Set a swipebind. If called with an already existing swipebind, it gets replaced.
+---
+---The bind triggers once the fingers are lifted from the touchpad. While `mods` are held down,
+---swipes with as many fingers as any swipebind using them aren't sent to windows.
+---
+---### Example
+---```lua
+--- -- Swipe left or right with three fingers to switch between tags
+---Input.swipebind({}, 3, "left", function()
+---    Tag.switch_right()
+---end)
+---Input.swipebind({}, 3, "right", function()
+---    Tag.switch_left()
+---end)
+---```
+---
+---@param mods Modifier[] The modifiers that need to be held down for the bind to trigger
+---@param fingers integer How many fingers the swipe is made with
+---@param direction SwipeDirection The direction to swipe in
+---@param action fun() The function to run when the bind is triggered
+function input.swipebind(mods, fingers, direction, action)
+    local mod_values = {}
+    for _, mod in ipairs(mods) do
+        table.insert(mod_values, modifier_values[mod])
+    end
+
+    client.server_streaming_request(input_service.SetSwipebind, {
+        modifiers = mod_values,
+        fingers = fingers,
+        direction = swipe_direction_values[direction],
+    }, action)
+end
+
 ---@class XkbConfig
 ---@field rules string?
 ---@field model string?
 ---@field layout string?
 ---@field variant string?
 ---@field options string?
+---@field compose_key string? The key to use as the compose key, like "ralt" or "menu"
 
 ---Set the xkbconfig for your keyboard.
 ---
@@ -184,6 +352,9 @@ end
 ---    layout = "us,fr,ge",
 ---    options = "ctrl:swapcaps,caps:shift"
 ---})
+---
+--- -- Use right alt as the compose key. This adds `compose:ralt` to the options.
+---Input.set_xkb_config({ compose_key = "ralt" })
 ---```
 ---
 ---@param xkb_config XkbConfig The new xkbconfig
@@ -191,6 +362,80 @@ function input.set_xkb_config(xkb_config)
     client.unary_request(input_service.SetXkbConfig, xkb_config)
 end
 
+---Switch the keyboard to the layout at `index` in the xkb config's layouts.
+---
+---Indices start at 1.
+---
+---### Example
+---```lua
+---Input.set_xkb_config({ layout = "us,fr" })
+---
+--- -- Switch to the French layout
+---Input.switch_xkb_layout(2)
+---```
+---
+---@param index integer
+function input.switch_xkb_layout(index)
+    client.unary_request(input_service.SwitchXkbLayout, { index = index - 1 })
+end
+
+---Switch the keyboard `offset` layouts forward, or backward if negative, wrapping around.
+---
+---### Example
+---```lua
+--- -- Cycle through layouts with `super + space`
+---Input.keybind({ "super" }, Key.space, function()
+---    Input.switch_xkb_layout_relative(1)
+---end)
+---```
+---
+---@param offset integer
+function input.switch_xkb_layout_relative(offset)
+    client.unary_request(input_service.SwitchXkbLayout, { offset = offset })
+end
+
+---Switch the keyboard to the next layout, wrapping around.
+function input.next_xkb_layout()
+    input.switch_xkb_layout_relative(1)
+end
+
+---Switch the keyboard to the previous layout, wrapping around.
+function input.prev_xkb_layout()
+    input.switch_xkb_layout_relative(-1)
+end
+
+---@class XkbLayouts
+---@field names string[] The names of the layouts, like "English (US)"
+---@field active integer The index of the active layout in `names`
+
+---Get the keyboard's layouts and which one is active.
+---
+---### Example
+---```lua
+---local layouts = Input.xkb_layouts()
+---print("Active layout: " .. layouts.names[layouts.active])
+---```
+---
+---@return XkbLayouts
+function input.xkb_layouts()
+    local response = client.unary_request(input_service.GetXkbLayouts, {})
+
+    return {
+        names = response.names or {},
+        active = (response.active_index or 0) + 1,
+    }
+end
+
+---Set whether windows remember their keyboard layout.
+---
+---When enabled, a window remembers the layout that was active while it was focused
+---and the keyboard switches back to it when the window is focused again.
+---
+---@param enabled boolean
+function input.set_per_window_xkb_layout(enabled)
+    client.unary_request(input_service.SetPerWindowXkbLayout, { enabled = enabled })
+end
+
 ---Set the keyboard's repeat rate and delay.
 ---
 ---### Example
@@ -207,6 +452,109 @@ function input.set_repeat_rate(rate, delay)
     })
 end
 
+---Move the pointer to `x` and `y` in the global space.
+---
+---The pointer is kept inside outputs, and the output it moves to gets focused.
+---
+---### Example
+---```lua
+---Input.warp_pointer(960, 540)
+---```
+---
+---@param x number
+---@param y number
+function input.warp_pointer(x, y)
+    client.unary_request(input_service.WarpPointer, { location = { x = x, y = y } })
+end
+
+---Move the pointer to the center of `win`.
+---
+---### Example
+---```lua
+---local focused = Window.get_focused()
+---if focused then
+---    Input.warp_pointer_to_window(focused)
+---end
+---```
+---
+---@param win WindowHandle
+function input.warp_pointer_to_window(win)
+    client.unary_request(input_service.WarpPointer, { window_id = win.id })
+end
+
+---Move the pointer to the center of `output`.
+---
+---### Example
+---```lua
+---local op = Output.get_by_name("HDMI-1")
+---if op then
+---    Input.warp_pointer_to_output(op)
+---end
+---```
+---
+---@param output OutputHandle
+function input.warp_pointer_to_output(output)
+    client.unary_request(input_service.WarpPointer, { output_name = output.name })
+end
+
+---Set whether the pointer moves to the center of an output when it gets focused.
+---
+---This only happens when focus moves to an output without the pointer, like from a keybind.
+---
+---@param enabled boolean
+function input.set_warp_pointer_on_output_focus(enabled)
+    client.unary_request(input_service.SetWarpPointerOnOutputFocus, { enabled = enabled })
+end
+
+---Change the cursor theme and size.
+---
+---`nil` keeps the current theme or size. The theme defaults to `XCURSOR_THEME` and
+---`XCURSOR_SIZE` from the environment Pinnacle was started in, and reloading the config
+---goes back to it. Clients spawned after this get the new theme through those variables,
+---but already running clients may need to be restarted to use it.
+---
+---### Example
+---```lua
+---Input.set_cursor_theme("Adwaita", 32)
+---
+--- -- Only make the cursor bigger
+---Input.set_cursor_theme(nil, 48)
+---```
+---
+---@param theme string?
+---@param size integer?
+function input.set_cursor_theme(theme, size)
+    client.unary_request(input_service.SetCursorTheme, { theme = theme, size = size })
+end
+
+---@enum (key) LidSwitchPolicy
+local lid_switch_policy_values = {
+    ---Do nothing
+    ignore = 1,
+    ---Disable internal panels while the lid is closed and an external monitor is connected
+    clamshell = 2,
+}
+
+---Set what happens when a laptop's lid is closed.
+---
+---With `"clamshell"`, the default, internal panels are disabled while the lid is closed and
+---an external monitor is connected. Their tags and windows are moved to the external monitor
+---and moved back when the lid is opened.
+---
+---### Example
+---```lua
+--- -- Keep using the laptop's screen with the lid closed
+---Input.set_lid_switch_policy("ignore")
+---```
+---
+---@param policy LidSwitchPolicy
+function input.set_lid_switch_policy(policy)
+    client.unary_request(
+        input_service.SetLidSwitchPolicy,
+        { policy = lid_switch_policy_values[policy] }
+    )
+end
+
 local accel_profile_values = {
     flat = 1,
     adaptive = 2,
@@ -261,50 +609,413 @@ local tap_button_map_values = {
 ---@field tap_drag_lock boolean?
 ---@field tap boolean?
 
+local device_type_values = {
+    touchpad = 1,
+    trackpoint = 2,
+    mouse = 3,
+    tablet = 4,
+    touchscreen = 5,
+    keyboard = 6,
+}
+local device_type_names = {}
+for name, value in pairs(device_type_values) do
+    device_type_names[value] = name
+end
+---@alias DeviceType
+---| "touchpad"
+---| "trackpoint" A pointing stick, like a ThinkPad TrackPoint
+---| "mouse" A mouse or other pointer device that isn't one of the others
+---| "tablet" A drawing tablet
+---| "touchscreen"
+---| "keyboard"
+
+---Which input devices libinput settings apply to.
+---
+---A device matches if it matches every field that's set, so an empty filter matches every device.
+---@class DeviceFilter
+---@field name string? A glob matched against the device's name, with `*` and `?` as wildcards
+---@field vendor_id integer? The device's USB vendor id
+---@field product_id integer? The device's USB product id
+---@field type DeviceType? What kind of device it is
+
+---@param filter DeviceFilter?
+---@return pinnacle.input.v0alpha1.DeviceFilter?
+local function device_filter_to_api(filter)
+    if not filter then
+        return nil
+    end
+
+    return {
+        name = filter.name,
+        vendor_id = filter.vendor_id,
+        product_id = filter.product_id,
+        device_type = filter.type and device_type_values[filter.type],
+    }
+end
+
 ---Set a libinput setting.
 ---
 ---This includes settings for pointer devices, like acceleration profiles, natural scroll, and more.
 ---
+---If `filter` is specified, the settings only apply to devices matching it. Settings are applied
+---in the order they were set, including to devices that connect later, so set a setting for
+---specific devices after setting it for every device to override it.
+---
 ---### Example
 ---```lua
 ---Input.set_libinput_settings({
 ---    accel_profile = "flat",
----    natural_scroll = true,
+---    natural_scroll = false,
 ---})
+---
+--- -- Only use natural scrolling on touchpads
+---Input.set_libinput_settings({ natural_scroll = true }, { type = "touchpad" })
 ---```
 ---
 ---@param settings LibinputSettings
-function input.set_libinput_settings(settings)
+---@param filter DeviceFilter?
+function input.set_libinput_settings(settings, filter)
+    local api_filter = device_filter_to_api(filter)
+
     for setting, value in pairs(settings) do
         if setting == "accel_profile" then
-            client.unary_request(
-                input_service.SetLibinputSetting,
-                { [setting] = accel_profile_values[value] }
-            )
+            value = accel_profile_values[value]
         elseif setting == "calibration_matrix" then
-            client.unary_request(
-                input_service.SetLibinputSetting,
-                { [setting] = { matrix = value } }
-            )
+            value = { matrix = value }
         elseif setting == "click_method" then
-            client.unary_request(
-                input_service.SetLibinputSetting,
-                { [setting] = click_method_values[value] }
-            )
+            value = click_method_values[value]
         elseif setting == "scroll_method" then
-            client.unary_request(
-                input_service.SetLibinputSetting,
-                { [setting] = scroll_method_values[value] }
-            )
+            value = scroll_method_values[value]
         elseif setting == "tap_button_map" then
-            client.unary_request(
-                input_service.SetLibinputSetting,
-                { [setting] = tap_button_map_values[value] }
-            )
-        else
-            client.unary_request(input_service.SetLibinputSetting, { [setting] = value })
+            value = tap_button_map_values[value]
         end
+
+        client.unary_request(
+            input_service.SetLibinputSetting,
+            { [setting] = value, filter = api_filter }
+        )
+    end
+end
+
+---@class DeviceInfo
+---@field name string The device's name
+---@field vendor_id integer The device's USB vendor id
+---@field product_id integer The device's USB product id
+---@field type DeviceType? What kind of device it is, or `nil` if it isn't one of the known kinds
+
+---Get the connected libinput devices matching `filter`, or every device if it's not specified.
+---
+---This is useful for checking which devices a filter will apply settings to.
+---
+---### Example
+---```lua
+---for _, device in ipairs(Input.devices({ type = "mouse" })) do
+---    print(device.name)
+---end
+---```
+---
+---@param filter DeviceFilter?
+---
+---@return DeviceInfo[]
+function input.devices(filter)
+    local response =
+        client.unary_request(input_service.GetDevices, { filter = device_filter_to_api(filter) })
+
+    ---@type DeviceInfo[]
+    local devices = {}
+    for _, device in ipairs(response.devices or {}) do
+        table.insert(devices, {
+            name = device.name or "",
+            vendor_id = device.vendor_id or 0,
+            product_id = device.product_id or 0,
+            type = device.device_type and device_type_names[device.device_type],
+        })
     end
+
+    return devices
+end
+
+---Multiply scrolling by `factor`.
+---
+---If `filter` is specified, only scrolling on devices matching it is multiplied.
+---Setting a factor again for the same device overrides the earlier one.
+---
+---For button scrolling, like scrolling by holding a trackpoint's middle button, use the
+---`scroll_method`, `scroll_button` and `scroll_button_lock` libinput settings.
+---
+---### Example
+---```lua
+--- -- Scroll half as fast, but faster on touchpads
+---Input.set_scroll_factor(0.5)
+---Input.set_scroll_factor(1.5, { type = "touchpad" })
+---```
+---
+---@param factor number
+---@param filter DeviceFilter?
+function input.set_scroll_factor(factor, filter)
+    client.unary_request(input_service.SetScrollFactor, {
+        filter = device_filter_to_api(filter),
+        factor = factor,
+    })
+end
+
+---Settings for drawing tablets.
+---
+---Tablets cover the first output until they're mapped to an output or region.
+---
+---The pressure curve is a cubic bezier curve from (0, 0) to (1, 1) that pen pressure is mapped
+---through, given as its control points `{ x1, y1, x2, y2 }` with coordinates between 0 and 1.
+---`{ 0, 0, 1, 1 }` leaves pressure as is.
+---@class TabletSettings
+---@field output OutputHandle? Map the tablet's area to this output
+---@field region { x: integer, y: integer, width: integer, height: integer }? Map the tablet's area to this region in the global space
+---@field pressure_curve number[]? The pressure curve's control points
+---@field button_remaps table<integer, integer>? Pen buttons mapped to the buttons to remap them to
+
+---Set tablet settings.
+---
+---If `filter` is specified, the settings only apply to tablets matching it.
+---Setting a setting again for the same tablet overrides the earlier one.
+---
+---### Example
+---```lua
+---local op = Output.get_by_name("DP-1")
+---if op then
+---    Input.set_tablet_settings({ output = op })
+---end
+---
+--- -- Make the pen feel softer and swap its two buttons
+---Input.set_tablet_settings({
+---    pressure_curve = { 0, 0.2, 0.8, 1 },
+---    button_remaps = { [0x14b] = 0x14c, [0x14c] = 0x14b },
+---}, { name = "Wacom*" })
+---```
+---
+---@param settings TabletSettings
+---@param filter DeviceFilter?
+function input.set_tablet_settings(settings, filter)
+    local api_filter = device_filter_to_api(filter)
+
+    ---@type pinnacle.input.v0alpha1.SetTabletSettingRequest[]
+    local requests = {}
+
+    if settings.output then
+        table.insert(requests, { output_name = settings.output.name })
+    end
+    if settings.region then
+        table.insert(requests, { region = settings.region })
+    end
+    if settings.pressure_curve then
+        local curve = settings.pressure_curve
+        table.insert(requests, {
+            pressure_curve = { x1 = curve[1], y1 = curve[2], x2 = curve[3], y2 = curve[4] },
+        })
+    end
+    for from, to in pairs(settings.button_remaps or {}) do
+        table.insert(requests, { button_remap = { from = from, to = to } })
+    end
+
+    for _, request in ipairs(requests) do
+        request.filter = api_filter
+        client.unary_request(input_service.SetTabletSetting, request)
+    end
+end
+
+---Map touchscreens to `output` so touches land on it.
+---
+---If `filter` is specified, only touchscreens matching it are mapped.
+---Mapping a touchscreen again overrides the earlier mapping.
+---
+---Touchscreens cover the first output until they're mapped. Touches follow the output's
+---transform, so rotated panels work without extra setup. A touchscreen that isn't quite aligned
+---with its panel can be corrected with the `calibration_matrix` libinput setting.
+---
+---### Example
+---```lua
+---local op = Output.get_by_name("DP-2")
+---if op then
+---    Input.map_touch_to_output(op, { name = "ELAN Touchscreen", type = "touchscreen" })
+---end
+---```
+---
+---@param output OutputHandle
+---@param filter DeviceFilter?
+function input.map_touch_to_output(output, filter)
+    client.unary_request(input_service.SetTouchOutput, {
+        filter = device_filter_to_api(filter),
+        output_name = output.name,
+    })
+end
+
+---Turn num lock on or off.
+---
+---Put this in your config to start with num lock on.
+---
+---### Example
+---```lua
+---Input.set_num_lock(true)
+---```
+---
+---@param on boolean
+function input.set_num_lock(on)
+    client.unary_request(input_service.SetLockKeys, { num_lock = on })
+end
+
+---Turn caps lock on or off.
+---
+---### Example
+---```lua
+---Input.set_caps_lock(false)
+---```
+---
+---@param on boolean
+function input.set_caps_lock(on)
+    client.unary_request(input_service.SetLockKeys, { caps_lock = on })
+end
+
+---Enable or disable sticky keys.
+---
+---With sticky keys, tapping a modifier key makes it apply to the next key, so shortcuts
+---can be typed one key at a time. Tapping it twice locks it until it's tapped again.
+---
+---### Example
+---```lua
+---Input.set_sticky_keys(true)
+---```
+---
+---@param enabled boolean
+function input.set_sticky_keys(enabled)
+    client.unary_request(input_service.SetStickyKeys, { enabled = enabled })
+end
+
+---Enable slow keys, or disable it with `nil`.
+---
+---With slow keys, keys have to be held down for `delay_ms` milliseconds before their press
+---goes through, so keys brushed by accident are ignored.
+---
+---### Example
+---```lua
+---Input.set_slow_keys(300)
+---```
+---
+---@param delay_ms integer? How long keys have to be held down
+function input.set_slow_keys(delay_ms)
+    client.unary_request(input_service.SetSlowKeys, { delay_ms = delay_ms })
+end
+
+---Enable or disable mouse keys.
+---
+---With mouse keys, the numpad controls the pointer instead of typing. The keys around 5
+---move the pointer, speeding up the longer they're held. 5 clicks, + double clicks,
+---0 holds the button down and . lets it go. /, * and - pick the left, middle and right button.
+---
+---### Example
+---```lua
+--- -- Toggle mouse keys with `super + m`
+---local mouse_keys = false
+---Input.keybind({ "super" }, "m", function()
+---    mouse_keys = not mouse_keys
+---    Input.set_mouse_keys(mouse_keys)
+---end)
+---```
+---
+---@param enabled boolean
+function input.set_mouse_keys(enabled)
+    client.unary_request(input_service.SetMouseKeys, { enabled = enabled })
+end
+
+---A key to remap or remap to.
+---
+---This is either a `Key` or key name like in `Input.keybind`, standing for the key that types it
+---without modifiers in the active layout, or a table with an evdev keycode.
+---@alias RemapKey Key | string | { keycode: integer }
+
+---@param key RemapKey
+---@return pinnacle.input.v0alpha1.RemapKey
+local function remap_key_to_api(key)
+    if type(key) == "number" then
+        return { keysym = key }
+    elseif type(key) == "string" then
+        return { xkb_name = key }
+    else
+        return { keycode = key.keycode }
+    end
+end
+
+---Remap a key to another.
+---
+---The key is turned into the other before keybinds and clients see it.
+---Remapping a key again overrides the earlier remap.
+---
+---If `filter` is specified, the key is only remapped on keyboards matching it.
+---
+---Tools like `wev` or `evtest` show the keycodes of keys.
+---
+---### Example
+---```lua
+--- -- Make caps lock another left control
+---Input.remap_key(Input.key.Caps_Lock, Input.key.Control_L)
+---
+--- -- Swap escape and caps lock on the laptop's keyboard
+---local laptop = { name = "AT Translated Set 2 keyboard" }
+---Input.remap_key({ keycode = 1 }, { keycode = 58 }, laptop)
+---Input.remap_key({ keycode = 58 }, { keycode = 1 }, laptop)
+---```
+---
+---@param from RemapKey The key to remap
+---@param to RemapKey The key to remap it to
+---@param filter DeviceFilter?
+function input.remap_key(from, to, filter)
+    client.unary_request(input_service.SetKeyRemap, {
+        from = remap_key_to_api(from),
+        to = remap_key_to_api(to),
+        filter = device_filter_to_api(filter),
+    })
+end
+
+---@type table<string, SignalServiceMethod>
+local signal_name_to_SignalName = {
+    xkb_layout = "InputXkbLayout",
+}
+
+---@class InputSignal Signals related to input events.
+---@field xkb_layout fun(index: integer, name: string)? The active keyboard layout changed.
+
+---Connect to an input signal.
+---
+---The compositor sends signals about various events. Use this function to run a callback when
+---some input signal occurs.
+---
+---This function returns a table of signal handles with each handle stored at the same key used
+---to connect to the signal. See `SignalHandles` for more information.
+---
+---# Example
+---```lua
+---Input.connect_signal({
+---    xkb_layout = function(index, name)
+---        print("Switched to layout " .. name)
+---    end
+---})
+---```
+---
+---@param signals InputSignal The signal you want to connect to
+---
+---@return SignalHandles signal_handles Handles to every signal you connected to wrapped in a table, with keys being the same as the connected signal.
+---
+---@see SignalHandles.disconnect_all - To disconnect from these signals
+function input.connect_signal(signals)
+    ---@diagnostic disable-next-line: invisible
+    local handles = require("pinnacle.signal").handles.new({})
+
+    for signal, callback in pairs(signals) do
+        require("pinnacle.signal").add_callback(signal_name_to_SignalName[signal], callback)
+        local handle =
+            ---@diagnostic disable-next-line: invisible
+            require("pinnacle.signal").handle.new(signal_name_to_SignalName[signal], callback)
+        handles[signal] = handle
+    end
+
+    return handles
 end
 
 return input
@@ -4,17 +4,40 @@ local layout_service = require("pinnacle.grpc.defs").pinnacle.layout.v0alpha1.La
 
 local mfloor = math.floor
 
+local set_or_toggle = {
+    SET = 1,
+    [true] = 1,
+    UNSET = 2,
+    [false] = 2,
+    TOGGLE = 3,
+}
+
 ---@class LayoutArgs
 ---@field output OutputHandle
 ---@field windows WindowHandle[]
 ---@field tags TagHandle[]
 ---@field output_width integer
 ---@field output_height integer
+---The name of the layout set on the first active tag, if any. See `TagHandle:set_layout`.
+---@field layout string?
+---The tiled window the user wants resized, if this layout was requested to resize one.
+---@field resize TileResize?
+
+---A request to resize a tiled window.
+---@class TileResize
+---@field window WindowHandle The window to resize
+---@field width_delta integer How many pixels wider the window should get; negative shrinks it
+---@field height_delta integer How many pixels taller the window should get; negative shrinks it
 
 ---A layout generator.
 ---@class LayoutGenerator
 ---Generate an array of geometries from the given `LayoutArgs`.
 ---@field layout fun(self: self, args: LayoutArgs): { x: integer, y: integer, width: integer, height: integer }[]
+---The name of this layout, used to remember it per tag. Layouts without a name aren't remembered.
+---@field name string?
+---Adjust this layout's split ratios so the window in `resize` grows or shrinks
+---by about the requested amount. The new ratios should be kept for later layouts.
+---@field resize_tile fun(self: self, args: LayoutArgs, resize: TileResize)?
 
 ---Builtin layout generators.
 ---
@@ -22,6 +45,85 @@ local mfloor = math.floor
 ---@class Builtin
 local builtins = {}
 
+---Get the index of `win` in the windows being laid out.
+---
+---@param args LayoutArgs
+---@param win WindowHandle
+---
+---@return integer?
+local function find_window(args, win)
+    for i, w in ipairs(args.windows) do
+        if w.id == win.id then
+            return i
+        end
+    end
+end
+
+---Adjust the split factors of a dwindle or spiral layout so the resized window changes size.
+---
+---Each split gives one window its first or second part, as reported by `window_gets_first`,
+---and leaves the other part for the remaining windows. Odd splits divide the width
+---and even splits divide the height.
+---
+---@param generator Builtin.Dwindle | Builtin.Spiral
+---@param args LayoutArgs
+---@param resize TileResize
+---@param window_gets_first fun(split: integer): boolean
+local function resize_split_factors(generator, args, resize, window_gets_first)
+    local count = #args.windows
+    local index = find_window(args, resize.window)
+    if not index or count < 2 then
+        return
+    end
+
+    -- Don't modify the split factors shared by every generator of this kind
+    if not rawget(generator, "split_factors") then
+        local split_factors = {}
+        for split, factor in pairs(generator.split_factors) do
+            split_factors[split] = factor
+        end
+        generator.split_factors = split_factors
+    end
+
+    local function factor(split)
+        return math.min(math.max(generator.split_factors[split] or 0.5, 0.1), 0.9)
+    end
+
+    -- The last window gets what's left of the last split
+    local is_last = index == count
+    local split = is_last and count - 1 or index
+
+    -- Find the size of the area the split divides
+    local width = args.output_width
+    local height = args.output_height
+    for prev_split = 1, split - 1 do
+        local rest = window_gets_first(prev_split) and 1 - factor(prev_split) or factor(prev_split)
+        if prev_split % 2 == 1 then
+            width = width * rest
+        else
+            height = height * rest
+        end
+    end
+
+    local delta, len
+    if split % 2 == 1 then
+        delta, len = resize.width_delta, width
+    else
+        delta, len = resize.height_delta, height
+    end
+
+    if len < 1 then
+        return
+    end
+
+    delta = delta / len
+    if window_gets_first(split) == is_last then
+        delta = -delta
+    end
+
+    generator.split_factors[split] = math.min(math.max(factor(split) + delta, 0.1), 0.9)
+end
+
 ----------------------------------------
 -- Master Stack                       --
 ----------------------------------------
@@ -50,6 +152,7 @@ local builtins = {}
 ---How many windows the master side will have.
 ---@field master_count integer
 local MasterStack = {
+    name = "master_stack",
     gaps = 8,
     master_factor = 0.5,
     master_side = "left",
@@ -228,6 +331,35 @@ function MasterStack:layout(args)
     return geos
 end
 
+---@param args LayoutArgs
+---@param resize TileResize
+function MasterStack:resize_tile(args, resize)
+    local index = find_window(args, resize.window)
+    if not index or #args.windows <= self.master_count then
+        return
+    end
+
+    local delta, len
+    if self.master_side == "left" or self.master_side == "right" then
+        delta, len = resize.width_delta, args.output_width
+    else
+        delta, len = resize.height_delta, args.output_height
+    end
+
+    if len == 0 then
+        return
+    end
+
+    delta = delta / len
+    -- Growing a stack window shrinks the master area
+    if index > self.master_count then
+        delta = -delta
+    end
+
+    local master_factor = math.max(math.min(self.master_factor, 0.9), 0.1)
+    self.master_factor = math.max(math.min(master_factor + delta, 0.9), 0.1)
+end
+
 ---Create a master stack layout generator.
 ---
 ---Pass in `settings` to override the defaults.
@@ -267,6 +399,7 @@ end
 ---the second at [2], and so on.
 ---@field split_factors table<integer, number>
 local Dwindle = {
+    name = "dwindle",
     gaps = 8,
     split_factors = {},
 }
@@ -377,6 +510,14 @@ function Dwindle:layout(args)
     return geos
 end
 
+---@param args LayoutArgs
+---@param resize TileResize
+function Dwindle:resize_tile(args, resize)
+    resize_split_factors(self, args, resize, function()
+        return true
+    end)
+end
+
 ---Create a dwindle layout generator.
 ---
 ---Pass in `settings` to override the defaults.
@@ -417,6 +558,7 @@ end
 ---Which corner the corner window will be in.
 ---@field corner_loc "top_left"|"top_right"|"bottom_left"|"bottom_right"
 local Corner = {
+    name = "corner",
     gaps = 8,
     corner_width_factor = 0.5,
     corner_height_factor = 0.5,
@@ -592,6 +734,29 @@ function Corner:layout(args)
     return geos
 end
 
+---@param args LayoutArgs
+---@param resize TileResize
+function Corner:resize_tile(args, resize)
+    local index = find_window(args, resize.window)
+    if not index or #args.windows < 2 or args.output_width == 0 or args.output_height == 0 then
+        return
+    end
+
+    local width_delta = resize.width_delta / args.output_width
+    local height_delta = resize.height_delta / args.output_height
+
+    -- Growing a stack window shrinks the corner window
+    if index ~= 1 then
+        width_delta = -width_delta
+        height_delta = -height_delta
+    end
+
+    self.corner_width_factor =
+        math.max(math.min(self.corner_width_factor + width_delta, 0.9), 0.1)
+    self.corner_height_factor =
+        math.max(math.min(self.corner_height_factor + height_delta, 0.9), 0.1)
+end
+
 ---Create a corner layout generator.
 ---
 ---Pass in `settings` to override the defaults.
@@ -633,6 +798,7 @@ end
 ---the second at [2], and so on.
 ---@field split_factors table<integer, number>
 local Spiral = {
+    name = "spiral",
     gaps = 8,
     split_factors = {},
 }
@@ -748,6 +914,14 @@ function Spiral:layout(args)
     return geos
 end
 
+---@param args LayoutArgs
+---@param resize TileResize
+function Spiral:resize_tile(args, resize)
+    resize_split_factors(self, args, resize, function(split)
+        return split % 4 == 1 or split % 4 == 2
+    end)
+end
+
 ---Create a spiral layout generator.
 ---
 ---Pass in `settings` to override the defaults.
@@ -784,6 +958,7 @@ end
 ---The direction of the lines of windows.
 ---@field direction "horizontal"|"vertical"
 local Fair = {
+    name = "fair",
     gaps = 8,
     direction = "vertical",
 }
@@ -965,6 +1140,72 @@ function builtins.fair(settings)
     return fair
 end
 
+----------------------------------------
+-- Monocle                            --
+----------------------------------------
+
+---A `LayoutGenerator` that gives every window the whole output.
+---
+---Windows are stacked on top of each other, with the focused one shown.
+---Use `Window.cycle_focus_forward` and `Window.cycle_focus_backward` to go through them,
+---and `Layout.set_monocle_indicator` to show how many there are.
+---@class Builtin.Monocle : LayoutGenerator
+---Gaps around each window, in pixels.
+---
+---This can be an integer or the table { inner: integer, outer: integer }.
+---If it is a table, the gap is `inner + outer`.
+---@field gaps integer | { inner: integer, outer: integer }
+local Monocle = {
+    name = "monocle",
+    gaps = 8,
+}
+
+---@class Builtin.Monocle.Args
+---Gaps around each window, in pixels.
+---
+---This can be an integer or the table { inner: integer, outer: integer }.
+---If it is a table, the gap is `inner + outer`.
+---
+---Defaults to 8.
+---@field gaps? integer | { inner: integer, outer: integer }
+
+---@param args LayoutArgs
+---
+---@return { x: integer, y: integer, width: integer, height: integer }[]
+function Monocle:layout(args)
+    local gaps = self.gaps
+    if type(gaps) == "table" then
+        gaps = gaps.inner + gaps.outer
+    end
+    ---@cast gaps integer
+
+    local geos = {}
+    for i = 1, #args.windows do
+        geos[i] = {
+            x = gaps,
+            y = gaps,
+            width = math.max(args.output_width - gaps * 2, 0),
+            height = math.max(args.output_height - gaps * 2, 0),
+        }
+    end
+
+    return geos
+end
+
+---Create a monocle layout generator.
+---
+---Pass in `settings` to override the defaults.
+---
+---@param settings? Builtin.Monocle.Args
+---
+---@return Builtin.Monocle
+function builtins.monocle(settings)
+    local monocle = settings or {}
+    setmetatable(monocle, { __index = Monocle })
+    ---@cast monocle Builtin.Monocle
+    return monocle
+end
+
 ---@class Layout
 ---@field private stream H2Stream?
 local layout = {
@@ -1001,9 +1242,22 @@ function layout.set_manager(manager)
             tags = tag_handles,
             output_width = response.output_width,
             output_height = response.output_height,
+            layout = response.layout,
         }
 
+        if response.resize then
+            args.resize = {
+                ---@diagnostic disable-next-line: invisible
+                window = require("pinnacle.window").handle.new(response.resize.window_id),
+                width_delta = response.resize.width_delta or 0,
+                height_delta = response.resize.height_delta or 0,
+            }
+        end
+
         local a = manager:get_active(args)
+        if args.resize and a.resize_tile then
+            a:resize_tile(args, args.resize)
+        end
         local success, geos = pcall(a.layout, a, args)
 
         if not success then
@@ -1016,6 +1270,7 @@ function layout.set_manager(manager)
                 request_id = request_id,
                 geometries = geos,
                 output_name = response.output_name,
+                layout_name = a.name,
             },
         })
 
@@ -1023,6 +1278,62 @@ function layout.set_manager(manager)
     end)
 end
 
+---@enum (key) FallbackLayout
+local fallback_layout_name_to_code = {
+    master_stack = 1,
+    dwindle = 2,
+    grid = 3,
+}
+
+---Set the layout the compositor uses when no layout manager is set,
+---such as before the config starts or after it crashes.
+---
+---The default is `"master_stack"`.
+---
+---#### Example
+---```lua
+---Layout.set_fallback_layout("grid")
+---```
+---
+---@param fallback_layout FallbackLayout
+function layout.set_fallback_layout(fallback_layout)
+    client.unary_request(
+        layout_service.SetFallbackLayout,
+        { layout = fallback_layout_name_to_code[fallback_layout] }
+    )
+end
+
+---Show or hide the monocle indicator.
+---
+---While it is shown, outputs laid out with the monocle layout show which window
+---of how many is shown, like "2/5", at the top of the output.
+---
+---#### Example
+---```lua
+---Layout.set_monocle_indicator(true)
+---```
+---
+---@param show boolean
+function layout.set_monocle_indicator(show)
+    client.unary_request(
+        layout_service.SetMonocleIndicator,
+        { set_or_toggle = set_or_toggle[show] }
+    )
+end
+
+---Toggle the monocle indicator.
+---
+---#### Example
+---```lua
+---Layout.toggle_monocle_indicator()
+---```
+function layout.toggle_monocle_indicator()
+    client.unary_request(
+        layout_service.SetMonocleIndicator,
+        { set_or_toggle = set_or_toggle.TOGGLE }
+    )
+end
+
 ---Request a layout on the given output, or the focused output if nil.
 ---
 ---If no `LayoutManager` was set, this will do nothing.
@@ -1072,6 +1383,16 @@ function CyclingLayoutManager:get_active(args)
         }
     end
 
+    -- Restore the layout remembered on the tag
+    if args.layout then
+        for i, generator in ipairs(self.layouts) do
+            if generator.name == args.layout then
+                self.tag_indices[first_tag.id] = i
+                break
+            end
+        end
+    end
+
     if not self.tag_indices[first_tag.id] then
         self.tag_indices[first_tag.id] = 1
     end
@@ -1079,6 +1400,16 @@ function CyclingLayoutManager:get_active(args)
     return self.layouts[self.tag_indices[first_tag.id]]
 end
 
+---Remember the layout the given tag uses on the tag itself.
+---
+---@param tag TagHandle
+function CyclingLayoutManager:persist_layout(tag)
+    local generator = self.layouts[self.tag_indices[tag.id]]
+    if generator and generator.name then
+        tag:set_layout(generator.name)
+    end
+end
+
 ---Cycle the layout for the given tag forward.
 ---
 ---@param tag TagHandle
@@ -1092,6 +1423,8 @@ function CyclingLayoutManager:cycle_layout_forward(tag)
     if self.tag_indices[tag.id] > #self.layouts then
         self.tag_indices[tag.id] = 1
     end
+
+    self:persist_layout(tag)
 end
 
 ---Cycle the layout for the given tag backward.
@@ -1107,6 +1440,8 @@ function CyclingLayoutManager:cycle_layout_backward(tag)
     if self.tag_indices[tag.id] < 1 then
         self.tag_indices[tag.id] = #self.layouts
     end
+
+    self:persist_layout(tag)
 end
 
 ---Create a new cycling layout manager.
@@ -97,6 +97,33 @@ function output.get_focused()
     return nil
 end
 
+---Save the current output configuration as a profile for the connected monitors.
+---
+---Whenever this exact set of monitors is connected again, Pinnacle will restore each output's
+---mode, location, scale, and whether it's enabled. Saving again replaces the profile.
+---
+---Monitors are identified by their make, model, and serial number.
+---
+---Profiles are stored in Pinnacle's state directory and persist across restarts.
+---
+---### Example
+---```lua
+--- -- Remember the current docked setup
+---Output.save_profile()
+---```
+function output.save_profile()
+    client.unary_request(output_service.SaveProfile, {})
+end
+
+---Remove the profile for the connected monitors.
+---
+---If `all` is true, every saved profile is removed instead.
+---
+---@param all boolean?
+function output.clear_profiles(all)
+    client.unary_request(output_service.ClearProfiles, { all = all })
+end
+
 ---Connect a function to be run with all current and future outputs.
 ---
 ---This method does two things:
@@ -664,6 +691,8 @@ end
 --- -- Notice that y = 0 aligns with the top of "DP-1", and the top of "HDMI-1" is at y = -360.
 ---```
 ---
+---This stops the output from following an output set with `set_loc_adj_to`.
+---
 ---@param loc { x: integer?, y: integer? }
 ---
 ---@see OutputHandle.set_loc_adj_to
@@ -689,8 +718,26 @@ end
 ---| "right_align_center" Set to right, align centers
 ---| "right_align_bottom" Set to right, align bottom borders
 
+local alignment_name_to_code = {
+    top_align_left = 1,
+    top_align_center = 2,
+    top_align_right = 3,
+    bottom_align_left = 4,
+    bottom_align_center = 5,
+    bottom_align_right = 6,
+    left_align_top = 7,
+    left_align_center = 8,
+    left_align_bottom = 9,
+    right_align_top = 10,
+    right_align_center = 11,
+    right_align_bottom = 12,
+}
+
 ---Set the location of this output adjacent to another one.
 ---
+---Unlike `set_location`, this doesn't hard-code coordinates: the output stays next to `other`
+---when either output changes mode, scale, or transform, or when `other` moves.
+---
 ---`alignment` is how you want this output to be placed.
 ---For example, "top_align_left" will place this output above `other` and align the left borders.
 ---Similarly, "right_align_center" will place this output to the right of `other` and align their centers.
@@ -720,70 +767,11 @@ end
 ---@param other OutputHandle
 ---@param alignment Alignment
 function OutputHandle:set_loc_adj_to(other, alignment)
-    local self_props = self:props()
-    local other_props = other:props()
-
-    if not self_props.x or not other_props.x then
-        -- TODO: notify
-        return
-    end
-
-    local alignment_parts = {}
-
-    for str in alignment:gmatch("%a+") do
-        table.insert(alignment_parts, str)
-    end
-
-    ---@type "top"|"bottom"|"left"|"right"
-    local dir = alignment_parts[1]
-    ---@type "top"|"bottom"|"center"|"left"|"right"
-    local align = alignment_parts[3]
-
-    ---@type integer
-    local x
-    ---@type integer
-    local y
-
-    local self_width = self_props.logical_width
-    local self_height = self_props.logical_height
-    local other_width = other_props.logical_width
-    local other_height = other_props.logical_height
-
-    if not (self_width and self_height and other_width and other_height) then
-        return
-    end
-
-    if dir == "top" or dir == "bottom" then
-        if dir == "top" then
-            y = other_props.y - self_height
-        else
-            y = other_props.y + other_height
-        end
-
-        if align == "left" then
-            x = other_props.x
-        elseif align == "center" then
-            x = other_props.x + math.floor((other_width - self_width) / 2)
-        elseif align == "bottom" then
-            x = other_props.x + (other_width - self_width)
-        end
-    else
-        if dir == "left" then
-            x = other_props.x - self_width
-        else
-            x = other_props.x + other_width
-        end
-
-        if align == "top" then
-            y = other_props.y
-        elseif align == "center" then
-            y = other_props.y + math.floor((other_height - self_height) / 2)
-        elseif align == "bottom" then
-            y = other_props.y + (other_height - self_height)
-        end
-    end
-
-    self:set_location({ x = x, y = y })
+    client.unary_request(output_service.SetLocation, {
+        output_name = self.name,
+        relative_to = other.name,
+        alignment = alignment_name_to_code[alignment],
+    })
 end
 
 ---Set this output's mode.
@@ -813,8 +801,101 @@ function OutputHandle:set_mode(pixel_width, pixel_height, refresh_rate_millihz)
     })
 end
 
+---Custom mode timings, in the same order as an X11 modeline.
+---
+---@class Modeline
+---@field clock number The pixel clock in kilohertz
+---@field hdisplay integer
+---@field hsync_start integer
+---@field hsync_end integer
+---@field htotal integer
+---@field vdisplay integer
+---@field vsync_start integer
+---@field vsync_end integer
+---@field vtotal integer
+---@field hsync_pos boolean `true` for `+hsync`, `false` for `-hsync`
+---@field vsync_pos boolean `true` for `+vsync`, `false` for `-vsync`
+
+---Set this output's mode to a custom modeline.
+---
+---Unlike `set_mode`, the modeline is not validated against the modes the output advertises,
+---so a bad modeline may leave the monitor without a signal.
+---
+---### Example
+---```lua
+--- -- 173.00  1920 2048 2248 2576  1080 1083 1088 1120 -hsync +vsync
+---Output.get_focused():set_modeline({
+---    clock = 173000,
+---    hdisplay = 1920,
+---    hsync_start = 2048,
+---    hsync_end = 2248,
+---    htotal = 2576,
+---    vdisplay = 1080,
+---    vsync_start = 1083,
+---    vsync_end = 1088,
+---    vtotal = 1120,
+---    hsync_pos = false,
+---    vsync_pos = true,
+---})
+---```
+---
+---@param modeline Modeline
+function OutputHandle:set_modeline(modeline)
+    client.unary_request(output_service.SetModeline, {
+        output_name = self.name,
+        modeline = modeline,
+    })
+end
+
+---A mode to add with `add_custom_mode`.
+---
+---@alias CustomMode
+---| Modeline A mode with explicit timings
+---| string An X11 modeline, like the output of `cvt`. The pixel clock is in megahertz, and a leading `Modeline "name"` is optional.
+---| { width: integer, height: integer, refresh_rate_millihz: integer, reduced_blanking: boolean? } A mode with timings generated by VESA Coordinated Video Timings
+
+---Add a mode that this output's monitor doesn't advertise.
+---
+---This is useful for monitors with broken EDID or that can be overclocked.
+---Once added, the mode can be selected with `set_mode` like any other.
+---
+---### Example
+---```lua
+---local op = Output.get_focused()
+---
+---op:add_custom_mode({ width = 2560, height = 1440, refresh_rate_millihz = 75000, reduced_blanking = true })
+---op:add_custom_mode("173.00 1920 2048 2248 2576 1080 1083 1088 1120 -hsync +vsync")
+---
+---op:set_mode(2560, 1440, 75000)
+---```
+---
+---@param mode CustomMode
+function OutputHandle:add_custom_mode(mode)
+    ---@type pinnacle.output.v0alpha1.AddCustomModeRequest
+    local request = { output_name = self.name }
+
+    if type(mode) == "string" then
+        request.modeline_string = mode
+    elseif mode.clock then
+        ---@cast mode Modeline
+        request.modeline = mode
+    else
+        request.cvt = {
+            pixel_width = mode.width,
+            pixel_height = mode.height,
+            refresh_rate_millihz = mode.refresh_rate_millihz,
+            reduced_blanking = mode.reduced_blanking,
+        }
+    end
+
+    client.unary_request(output_service.AddCustomMode, request)
+end
+
 ---Set this output's scaling factor.
 ---
+---Fractional scales are rounded to the nearest multiple of 1/120, which is the precision
+---clients receive them with. The scale will not go below 0.25.
+---
 ---@param scale number
 function OutputHandle:set_scale(scale)
     client.unary_request(output_service.SetScale, { output_name = self.name, absolute = scale })
@@ -870,6 +951,261 @@ function OutputHandle:set_transform(transform)
     )
 end
 
+---@enum (key) Vrr
+local vrr_name_to_code = {
+    off = 1,
+    on = 2,
+    on_demand = 3,
+}
+
+local vrr_code_to_name = {
+    [1] = "off",
+    [2] = "on",
+    [3] = "on_demand",
+}
+
+---Set this output's variable refresh rate setting.
+---
+---`"on_demand"` only enables VRR while a fullscreen game or video is on this output.
+---This does nothing if the output doesn't support VRR.
+---
+---@param vrr Vrr
+function OutputHandle:set_vrr(vrr)
+    client.unary_request(
+        output_service.SetVrr,
+        { output_name = self.name, vrr = vrr_name_to_code[vrr] }
+    )
+end
+
+---Set when this output starts rendering a frame.
+---
+---Rendering as late as possible before vblank shortens the time between input and it showing
+---up on screen, at the risk of missing the vblank. Pass the number of milliseconds before
+---vblank to start rendering at, `"adaptive"` to base it on how long recent frames took to render,
+---or `nil` to render right after vblank.
+---
+---This only works on the udev backend and does nothing while VRR is active.
+---
+---#### Example
+---```lua
+---Output.get_focused():set_max_render_time(4)
+---Output.get_focused():set_max_render_time("adaptive")
+---```
+---
+---@param max_render_time integer | "adaptive" | nil
+function OutputHandle:set_max_render_time(max_render_time)
+    ---@type pinnacle.output.v0alpha1.SetMaxRenderTimeRequest
+    local request = { output_name = self.name }
+
+    if max_render_time == "adaptive" then
+        request.adaptive = {}
+    elseif max_render_time then
+        request.millis = max_render_time
+    end
+
+    client.unary_request(output_service.SetMaxRenderTime, request)
+end
+
+---Cap how many frames per second this output shows, or remove the cap with `nil`.
+---
+---Frames are shown for a whole number of refresh cycles, so the cap is rounded down to this
+---output's refresh rate divided by a whole number. Clients on this output also only get frame
+---callbacks at the capped rate.
+---
+---This only works on the udev backend.
+---
+---#### Example
+---```lua
+------ Run a 240 Hz monitor at 120 fps to save power
+---Output.get_focused():set_max_fps(120)
+---```
+---
+---@param max_fps integer?
+function OutputHandle:set_max_fps(max_fps)
+    client.unary_request(output_service.SetMaxFps, { output_name = self.name, max_fps = max_fps })
+end
+
+---@enum (key) ColorFilter
+local color_filter_name_to_code = {
+    none = 1,
+    grayscale = 2,
+    invert = 3,
+    protanopia = 4,
+    deuteranopia = 5,
+    tritanopia = 6,
+    protanopia_correction = 7,
+    deuteranopia_correction = 8,
+    tritanopia_correction = 9,
+}
+
+---Set this output's color filter.
+---
+---A color filter transforms every color on this output, except the cursor.
+---`"protanopia"`, `"deuteranopia"`, and `"tritanopia"` simulate color blindness, and the
+---`"_correction"` variants shift colors so they are easier to tell apart with it.
+---Set it to `"none"` to remove the filter.
+---
+---@param color_filter ColorFilter
+function OutputHandle:set_color_filter(color_filter)
+    client.unary_request(output_service.SetColorFilter, {
+        output_name = self.name,
+        color_filter = color_filter_name_to_code[color_filter],
+    })
+end
+
+---A night light that shifts an output's colors to a warmer color temperature.
+---
+---Set `from` and `to` to turn it on between two local times, or `latitude` and `longitude`
+---to turn it on from sunset to sunrise. Without either, it is always on.
+---@class NightLight
+---@field temperature integer The color temperature in Kelvin, from 1000 to 10000. 6500 leaves colors unchanged; lower is warmer.
+---@field from string? When the night light turns on, as `"HH:MM"`
+---@field to string? When the night light turns off, as `"HH:MM"`
+---@field latitude number? The latitude in degrees, positive is north
+---@field longitude number? The longitude in degrees, positive is east
+
+---@param time string
+---@return integer?
+local function minutes_after_midnight(time)
+    local hour, minute = time:match("^(%d%d?):(%d%d)$")
+    if not hour then
+        return nil
+    end
+    return tonumber(hour) * 60 + tonumber(minute)
+end
+
+---Turn this output's night light on or off.
+---
+---The night light shifts this output's colors to a warmer color temperature.
+---It is applied through the output's gamma, so it only works on the udev backend.
+---It fades in and out over half an hour at the start and end of its schedule.
+---Pass `nil` to turn it off.
+---
+---#### Example
+---```lua
+------ Warm colors from 9 PM to 7 AM
+---Output.get_focused():set_night_light({ temperature = 3500, from = "21:00", to = "07:00" })
+---
+------ Warm colors from sunset to sunrise in Berlin
+---Output.get_focused():set_night_light({ temperature = 4000, latitude = 52.5, longitude = 13.4 })
+---```
+---
+---@param night_light NightLight?
+function OutputHandle:set_night_light(night_light)
+    ---@type pinnacle.output.v0alpha1.SetNightLightRequest
+    local request = { output_name = self.name }
+
+    if night_light then
+        request.temperature = night_light.temperature
+
+        if night_light.from or night_light.to then
+            request.fixed = {
+                from_minute = night_light.from and minutes_after_midnight(night_light.from),
+                to_minute = night_light.to and minutes_after_midnight(night_light.to),
+            }
+        elseif night_light.latitude or night_light.longitude then
+            request.location = {
+                latitude = night_light.latitude,
+                longitude = night_light.longitude,
+            }
+        end
+    end
+
+    client.unary_request(output_service.SetNightLight, request)
+end
+
+---@enum (key) WallpaperMode
+local wallpaper_mode_name_to_code = {
+    fill = 1,
+    fit = 2,
+    stretch = 3,
+}
+
+---What is drawn beneath everything else on an output.
+---
+---Set `color` for a solid color, or `path` for a PNG image.
+---@class Wallpaper
+---@field color { r: number, g: number, b: number }? Each component from 0.0 to 1.0
+---@field path string? The path to the image. `~` and environment variables are expanded.
+---@field mode WallpaperMode? How the image is fit onto the output. Defaults to `"fill"`.
+
+---Set this output's wallpaper, or remove it with `nil`.
+---
+---The wallpaper is drawn beneath everything else on this output, including background
+---layer surfaces. `"fill"` scales the image to cover the output and crops what doesn't fit,
+---`"fit"` scales it to fit inside the output with black bars around it, and `"stretch"`
+---stretches it to the output's size.
+---
+---#### Example
+---```lua
+---Output.get_focused():set_wallpaper({ path = "~/Pictures/wallpaper.png", mode = "fill" })
+---Output.get_focused():set_wallpaper({ color = { r = 0.1, g = 0.1, b = 0.15 } })
+---```
+---
+---@param wallpaper Wallpaper?
+function OutputHandle:set_wallpaper(wallpaper)
+    ---@type pinnacle.output.v0alpha1.SetWallpaperRequest
+    local request = { output_name = self.name }
+
+    if wallpaper then
+        if wallpaper.path then
+            request.image = {
+                path = wallpaper.path,
+                mode = wallpaper.mode and wallpaper_mode_name_to_code[wallpaper.mode],
+            }
+        elseif wallpaper.color then
+            request.color = {
+                r = wallpaper.color.r,
+                g = wallpaper.color.g,
+                b = wallpaper.color.b,
+            }
+        end
+    end
+
+    client.unary_request(output_service.SetWallpaper, request)
+end
+
+---Turn this output's monitor on or off.
+---
+---A powered off output stays in the layout; windows can still be moved to it.
+---
+---@param powered boolean
+function OutputHandle:set_powered(powered)
+    client.unary_request(output_service.SetPowered, { output_name = self.name, powered = powered })
+end
+
+---Add this output to or remove it from the layout.
+---
+---Disabling an output turns it off and moves its tags, along with their windows,
+---to the focused output. Enabling it again moves those tags back and restores its location.
+---
+---The last enabled output cannot be disabled.
+---
+---### Example
+---```lua
+--- -- Only use the external monitor
+---Output.get_by_name("eDP-1"):set_enabled(false)
+---```
+---
+---@param enabled boolean
+function OutputHandle:set_enabled(enabled)
+    client.unary_request(output_service.SetEnabled, { output_name = self.name, enabled = enabled })
+end
+
+---Make this output the primary output.
+---
+---New windows open on the primary output instead of the focused one unless a
+---window rule says otherwise, and windows that request activation while not
+---on any output are moved to it. X11 clients also see it as the primary output.
+---
+---### Example
+---```lua
+---Output.get_by_name("DP-1"):set_primary()
+---```
+function OutputHandle:set_primary()
+    client.unary_request(output_service.SetPrimary, { output_name = self.name })
+end
+
 ---@class Mode
 ---@field pixel_width integer
 ---@field pixel_height integer
@@ -892,6 +1228,10 @@ end
 ---@field scale number?
 ---@field transform Transform?
 ---@field serial integer?
+---@field vrr Vrr?
+---@field enabled boolean? Whether this output is part of the layout
+---@field powered boolean? Whether this output's monitor is turned on
+---@field primary boolean? Whether this is the primary output
 
 ---Get all properties of this output.
 ---
@@ -906,6 +1246,7 @@ function OutputHandle:props()
     response.tag_ids = nil
     response.modes = response.modes or {}
     response.transform = transform_code_to_name[response.transform]
+    response.vrr = vrr_code_to_name[response.vrr]
 
     return response
 end
@@ -1060,6 +1401,33 @@ function OutputHandle:serial()
     return self:props().serial
 end
 
+---Get whether this output is part of the layout.
+---
+---Shorthand for `handle:props().enabled`.
+---
+---@return boolean?
+function OutputHandle:enabled()
+    return self:props().enabled
+end
+
+---Get whether this output's monitor is turned on.
+---
+---Shorthand for `handle:props().powered`.
+---
+---@return boolean?
+function OutputHandle:powered()
+    return self:props().powered
+end
+
+---Get whether this is the primary output.
+---
+---Shorthand for `handle:props().primary`.
+---
+---@return boolean?
+function OutputHandle:primary()
+    return self:props().primary
+end
+
 ---@nodoc
 ---Create a new `OutputHandle` from its raw name.
 ---@param output_name string
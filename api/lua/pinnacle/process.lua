@@ -11,10 +11,17 @@ local process_service = require("pinnacle.grpc.defs").pinnacle.process.v0alpha1.
 ---@class Process
 local process = {}
 
+---Where to open the first window of a spawned process.
+---@class SpawnPlacement
+---@field tags TagHandle[]? The tags to open the window with
+---@field output OutputHandle? The output to open the window on, with the output's active tags
+---@field follow boolean? `true` to switch to the window's tag and focus it when it opens
+
 ---@param args string[]
 ---@param callbacks { stdout: fun(line: string)?, stderr: fun(line: string)?, exit: fun(code: integer, msg: string)? }?
 ---@param once boolean
-local function spawn_inner(args, callbacks, once)
+---@param placement SpawnPlacement?
+local function spawn_inner(args, callbacks, once, placement)
     local callback = function() end
 
     if callbacks then
@@ -31,10 +38,21 @@ local function spawn_inner(args, callbacks, once)
         end
     end
 
+    local tag_ids = nil
+    if placement and placement.tags then
+        tag_ids = {}
+        for _, tg in ipairs(placement.tags) do
+            table.insert(tag_ids, tg.id)
+        end
+    end
+
     client.server_streaming_request(process_service.Spawn, {
         args = args,
         once = once,
         has_callback = callbacks ~= nil,
+        tag_ids = tag_ids,
+        output = placement and placement.output and placement.output.name,
+        follow = placement and placement.follow,
     }, callback)
 end
 
@@ -81,6 +99,34 @@ function process.spawn(args, callbacks)
     spawn_inner(args, callbacks, false)
 end
 
+---Spawn a program and open its first window at the given placement.
+---
+---The window is found by process id, including windows opened by child processes,
+---so this works for programs that are slow to start or start through a launcher.
+---Windows placed on inactive tags open in the background unless `follow` is `true`.
+---
+---### Example
+---```lua
+--- -- Open Firefox on tag "2" of "DP-1" without switching to it
+---Process.spawn_placed("firefox", { tags = { Tag.get("2", Output.get_by_name("DP-1")) } })
+---
+--- -- Open Alacritty on "HDMI-1" and focus it
+---Process.spawn_placed("alacritty", { output = Output.get_by_name("HDMI-1"), follow = true })
+---```
+---
+---@param args string | string[]
+---@param placement SpawnPlacement
+---@param callbacks { stdout: fun(line: string)?, stderr: fun(line: string)?, exit: fun(code: integer, msg: string)? }?
+---
+---@see Process.spawn
+function process.spawn_placed(args, placement, callbacks)
+    if type(args) == "string" then
+        args = { args }
+    end
+
+    spawn_inner(args, callbacks, false, placement)
+end
+
 ---Like `Process.spawn` but will only spawn the program if it isn't already running.
 ---
 ---@param args string | string[]
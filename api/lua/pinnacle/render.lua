@@ -40,4 +40,59 @@ function render.set_downscale_filter(filter)
     )
 end
 
+---Enable or disable window animations.
+---
+---Animations are enabled by default. They can also be disabled for specific windows
+---with the `animations` window rule. Windows that say they're showing a game or video
+---are never animated.
+---
+---@param enabled boolean
+function render.set_animations_enabled(enabled)
+    client.unary_request(render_service.SetAnimationsEnabled, { enabled = enabled })
+end
+
+---Set the corner radius of windows in logical pixels.
+---
+---A radius of 0, the default, gives windows square corners. Fullscreen windows always
+---have square corners. The radius can be overridden for specific windows with the
+---`corner_radius` window rule, and while specific tags are active with
+---`TagHandle:set_corner_radius`.
+---
+---@param radius number
+function render.set_corner_radius(radius)
+    client.unary_request(render_service.SetCornerRadius, { radius = radius })
+end
+
+---Set how the background behind blurred windows and layer surfaces is blurred.
+---
+---`passes` is how many times the background is downsampled, from 1 to 8.
+---More passes give a stronger blur. `offset` is how far apart samples are taken in each pass.
+---
+---Windows are blurred with the `blur` window rule. Top and overlay layer surfaces whose
+---namespace is in `layer_namespaces` are also blurred.
+---
+---Only the background and bottom layer surfaces are blurred, not windows behind other windows.
+---
+---@param passes integer
+---@param offset number
+---@param layer_namespaces string[]?
+function render.set_blur(passes, offset, layer_namespaces)
+    client.unary_request(render_service.SetBlur, {
+        passes = passes,
+        offset = offset,
+        layer_namespaces = layer_namespaces or {},
+    })
+end
+
+---Dim windows that aren't focused.
+---
+---`dim` is how much to darken them, from 0.0 (not at all), the default, to 1.0 (black).
+---Specific windows can be kept from dimming with the `dim` window rule. Windows that say
+---they're showing a game or video are never dimmed.
+---
+---@param dim number
+function render.set_dim_inactive(dim)
+    client.unary_request(render_service.SetDimInactive, { dim = dim })
+end
+
 return render
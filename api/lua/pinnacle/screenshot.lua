@@ -0,0 +1,173 @@
+-- This Source Code Form is subject to the terms of the Mozilla Public
+-- License, v. 2.0. If a copy of the MPL was not distributed with this
+-- file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+local client = require("pinnacle.grpc.client")
+local screenshot_service =
+    require("pinnacle.grpc.defs").pinnacle.screenshot.v0alpha1.ScreenshotService
+
+---Screenshots.
+---
+---This module lets you capture outputs, regions, and windows as PNGs,
+---so you can bind printscreen without any other tools.
+---It can also record outputs and regions to video files.
+---
+---@class Screenshot
+local screenshot = {}
+
+---What to take a screenshot of. Set exactly one of the fields.
+---
+---@class ScreenshotTarget
+---@field output OutputHandle? Capture an entire output
+---@field region { x: integer, y: integer, width: integer, height: integer }? Capture a region in the global space. Only the part on the output containing its top-left corner is captured.
+---@field window WindowHandle? Capture a single window, without anything above or below it
+
+---@param target ScreenshotTarget
+---@param include_cursor boolean?
+---@param path string?
+---@return pinnacle.screenshot.v0alpha1.ScreenshotRequest
+local function screenshot_request(target, include_cursor, path)
+    return {
+        output_name = target.output and target.output.name,
+        region = target.region,
+        window_id = target.window and target.window.id,
+        include_cursor = include_cursor,
+        path = path,
+    }
+end
+
+---Take a screenshot and get it as a PNG.
+---
+---If `include_cursor` is true, the cursor is drawn as well. It is never drawn on windows.
+---
+---#### Example
+---```lua
+---local png = Screenshot.capture({ output = Output.get_focused() }, true)
+---```
+---
+---@param target ScreenshotTarget
+---@param include_cursor boolean?
+---
+---@return string? png The PNG, or `nil` if the target doesn't exist or couldn't be captured
+function screenshot.capture(target, include_cursor)
+    local response = client.unary_request(
+        screenshot_service.Screenshot,
+        screenshot_request(target, include_cursor)
+    )
+
+    ---@cast response pinnacle.screenshot.v0alpha1.ScreenshotResponse
+    return response.png
+end
+
+---Take a screenshot and save it as a PNG file.
+---
+---If `include_cursor` is true, the cursor is drawn as well. It is never drawn on windows.
+---`~` and environment variables in `path` are expanded by Pinnacle.
+---
+---#### Example
+---```lua
+---Input.keybind({}, Key.Print, function()
+---    Screenshot.save({ output = Output.get_focused() }, "~/Pictures/screenshot.png")
+---end)
+---```
+---
+---@param target ScreenshotTarget
+---@param path string
+---@param include_cursor boolean?
+---
+---@return boolean saved Whether the screenshot was saved
+function screenshot.save(target, path, include_cursor)
+    local response = client.unary_request(
+        screenshot_service.Screenshot,
+        screenshot_request(target, include_cursor, path)
+    )
+
+    ---@cast response pinnacle.screenshot.v0alpha1.ScreenshotResponse
+    return response.width ~= nil
+end
+
+---Capture a window as a PNG thumbnail for previews like taskbars and window switchers.
+---
+---The window is rendered on its own without its popups or anything above it, even if it
+---is hidden. It is scaled down to fit in `max_width` by `max_height` pixels, keeping its
+---aspect ratio. Thumbnails are never larger than the window itself.
+---
+---#### Example
+---```lua
+---for _, win in ipairs(Window.get_all()) do
+---    local thumbnail = Screenshot.window_thumbnail(win, 256, 256)
+---end
+---```
+---
+---@param window WindowHandle
+---@param max_width integer
+---@param max_height integer
+---
+---@return string? png The PNG, or `nil` if the window doesn't exist or couldn't be captured
+function screenshot.window_thumbnail(window, max_width, max_height)
+    local response = client.unary_request(screenshot_service.WindowThumbnail, {
+        window_id = window.id,
+        max_width = max_width,
+        max_height = max_height,
+    })
+
+    ---@cast response pinnacle.screenshot.v0alpha1.ScreenshotResponse
+    return response.png
+end
+
+---What to record. Set exactly one of the fields.
+---
+---@class RecordingTarget
+---@field output OutputHandle? Record an entire output
+---@field region { x: integer, y: integer, width: integer, height: integer }? Record a region in the global space. Only the part on the output containing its top-left corner is recorded.
+
+---Options for a recording.
+---
+---@class RecordingOptions
+---@field framerate integer? Frames per second. Defaults to 30.
+---@field include_cursor boolean? Whether to draw the cursor. Defaults to false.
+
+---Start recording to a video file.
+---
+---Frames are encoded by `ffmpeg`, which must be installed. The container and codec are
+---chosen from the extension of `path`. `~` and environment variables in `path` are
+---expanded by Pinnacle.
+---
+---While recording, a red square is shown in the top-right corner of the recorded output.
+---It isn't part of the recording. Only one recording can run at a time.
+---
+---#### Example
+---```lua
+---Input.keybind({ "super" }, Key.Print, function()
+---    Screenshot.start_recording({ output = Output.get_focused() }, "~/Videos/recording.mp4")
+---end)
+---```
+---
+---@param target RecordingTarget
+---@param path string
+---@param opts RecordingOptions?
+function screenshot.start_recording(target, path, opts)
+    opts = opts or {}
+
+    client.unary_request(screenshot_service.StartRecording, {
+        output_name = target.output and target.output.name,
+        region = target.region,
+        path = path,
+        framerate = opts.framerate,
+        include_cursor = opts.include_cursor,
+    })
+end
+
+---Stop the current recording, if any.
+---
+---The video file is finished once all captured frames are encoded.
+---
+---#### Example
+---```lua
+---Screenshot.stop_recording()
+---```
+function screenshot.stop_recording()
+    client.unary_request(screenshot_service.StopRecording, {})
+end
+
+return screenshot
@@ -82,6 +82,17 @@ local signals = {
         ---@type fun(response: table)
         on_response = nil,
     },
+    WindowUrgent = {
+        ---@nodoc
+        ---@type H2Stream?
+        sender = nil,
+        ---@nodoc
+        ---@type (fun(window: WindowHandle, urgent: boolean))[]
+        callbacks = {},
+        ---@nodoc
+        ---@type fun(response: table)
+        on_response = nil,
+    },
     TagActive = {
         ---@nodoc
         ---@type H2Stream?
@@ -93,6 +104,50 @@ local signals = {
         ---@type fun(response: table)
         on_response = nil,
     },
+    TagUrgent = {
+        ---@nodoc
+        ---@type H2Stream?
+        sender = nil,
+        ---@nodoc
+        ---@type (fun(tag: TagHandle, urgent: boolean))[]
+        callbacks = {},
+        ---@nodoc
+        ---@type fun(response: table)
+        on_response = nil,
+    },
+    TagOccupied = {
+        ---@nodoc
+        ---@type H2Stream?
+        sender = nil,
+        ---@nodoc
+        ---@type (fun(tag: TagHandle, occupied: boolean))[]
+        callbacks = {},
+        ---@nodoc
+        ---@type fun(response: table)
+        on_response = nil,
+    },
+    TagRenamed = {
+        ---@nodoc
+        ---@type H2Stream?
+        sender = nil,
+        ---@nodoc
+        ---@type (fun(tag: TagHandle, name: string))[]
+        callbacks = {},
+        ---@nodoc
+        ---@type fun(response: table)
+        on_response = nil,
+    },
+    InputXkbLayout = {
+        ---@nodoc
+        ---@type H2Stream?
+        sender = nil,
+        ---@nodoc
+        ---@type (fun(index: integer, name: string))[]
+        callbacks = {},
+        ---@nodoc
+        ---@type fun(response: table)
+        on_response = nil,
+    },
 }
 
 signals.OutputConnect.on_response = function(response)
@@ -145,6 +200,15 @@ signals.WindowPointerLeave.on_response = function(response)
     end
 end
 
+signals.WindowUrgent.on_response = function(response)
+    ---@diagnostic disable-next-line: invisible
+    local window_handle = require("pinnacle.window").handle.new(response.window_id)
+
+    for _, callback in ipairs(signals.WindowUrgent.callbacks) do
+        callback(window_handle, response.urgent)
+    end
+end
+
 signals.TagActive.on_response = function(response)
     ---@diagnostic disable-next-line: invisible
     local tag_handle = require("pinnacle.tag").handle.new(response.tag_id)
@@ -154,6 +218,40 @@ signals.TagActive.on_response = function(response)
     end
 end
 
+signals.TagUrgent.on_response = function(response)
+    ---@diagnostic disable-next-line: invisible
+    local tag_handle = require("pinnacle.tag").handle.new(response.tag_id)
+
+    for _, callback in ipairs(signals.TagUrgent.callbacks) do
+        callback(tag_handle, response.urgent)
+    end
+end
+
+signals.TagOccupied.on_response = function(response)
+    ---@diagnostic disable-next-line: invisible
+    local tag_handle = require("pinnacle.tag").handle.new(response.tag_id)
+
+    for _, callback in ipairs(signals.TagOccupied.callbacks) do
+        callback(tag_handle, response.occupied)
+    end
+end
+
+signals.TagRenamed.on_response = function(response)
+    ---@diagnostic disable-next-line: invisible
+    local tag_handle = require("pinnacle.tag").handle.new(response.tag_id)
+
+    for _, callback in ipairs(signals.TagRenamed.callbacks) do
+        callback(tag_handle, response.name)
+    end
+end
+
+signals.InputXkbLayout.on_response = function(response)
+    -- Layout indices are 1-based in Lua
+    for _, callback in ipairs(signals.InputXkbLayout.callbacks) do
+        callback(response.index + 1, response.name)
+    end
+end
+
 -----------------------------------------------------------------------------
 
 ---@nodoc
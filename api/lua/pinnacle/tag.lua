@@ -153,15 +153,23 @@ end
 
 ---Remove the given tags.
 ---
+---Windows left without tags are moved to `fallback` if it's given. Otherwise, they're moved
+---to the first active tag left on their output, or its first tag if none are active.
+---Outputs left without an active tag activate the tag windows are moved to.
+---
 ---### Example
 ---```lua
 ---local tags = Tag.add(Output.get_by_name("HDMI-1"), "1", "2", "Buckle", "Shoe")
 ---
 ---Tag.remove(tags) -- "HDMI-1" no longer has those tags
+---
+--- -- Remove a workspace, keeping its windows on "1"
+---Tag.remove({ Tag.get("scratch") }, Tag.get("1"))
 ---```
 ---
 ---@param tags TagHandle[]
-function tag.remove(tags)
+---@param fallback TagHandle? The tag windows left without tags are moved to
+function tag.remove(tags, fallback)
     ---@type integer[]
     local ids = {}
 
@@ -169,16 +177,170 @@ function tag.remove(tags)
         table.insert(ids, tg.id)
     end
 
-    client.unary_request(tag_service.Remove, { tag_ids = ids })
+    client.unary_request(tag_service.Remove, {
+        tag_ids = ids,
+        fallback_tag_id = fallback and fallback.id,
+    })
+end
+
+---Switch an output back to the tags that were active on it before.
+---
+---Switching back repeatedly flips between the two most recently active sets of tags.
+---
+---`index` picks an older set from `Tag.history`, starting at 1 for the most recent one.
+---
+---If `output` is not specified, this uses the focused output.
+---
+---### Example
+---```lua
+---Tag.get("1"):switch_to()
+---Tag.get("2"):switch_to()
+---
+---Tag.switch_to_previous() -- Displays "1"
+---Tag.switch_to_previous() -- Displays "2"
+---
+--- -- Go back to the tags that were active two switches ago
+---Tag.switch_to_previous(nil, 2)
+---```
+---
+---@param output OutputHandle?
+---@param index integer? Which set in the history to switch to, defaults to 1
+function tag.switch_to_previous(output, index)
+    output = output or require("pinnacle.output").get_focused()
+
+    if not output then
+        return
+    end
+
+    client.unary_request(tag_service.SwitchToPrevious, {
+        output_name = output.name,
+        index = (index or 1) - 1,
+    })
+end
+
+---Set whether tags are dynamic, like workspaces in GNOME.
+---
+---While they are, every output keeps one empty tag after the last tag that has windows
+---or is active, adding a new one when that tag gets a window. New tags are named one more
+---than the largest numbered tag. Any other empty tags at the end are hidden: they're skipped
+---by `Tag.switch_relative` and aren't shown by workspace clients. Bars using this API can check
+---`TagHandle:hidden`.
+---
+---### Example
+---```lua
+---Tag.set_dynamic(true)
+---Tag.add(Output.get_focused(), "1")
+---```
+---
+---@param enabled boolean
+function tag.set_dynamic(enabled)
+    client.unary_request(tag_service.SetDynamic, { enabled = enabled })
+end
+
+---Switch an output to the tag `offset` places right of its first active one, or left if `offset`
+---is negative, wrapping around the ends.
+---
+---Places are counted in tag order, which can be changed with `TagHandle:set_index`.
+---If no tag is active, positive offsets count from before the first tag
+---and negative ones from after the last.
+---
+---If `output` is not specified, this uses the focused output.
+---
+---### Example
+---```lua
+---Tag.get("1"):switch_to()
+---Tag.switch_relative(1) -- Displays "2"
+---Tag.switch_relative(-2) -- Displays the last tag
+---```
+---
+---@param offset integer
+---@param output OutputHandle?
+function tag.switch_relative(offset, output)
+    output = output or require("pinnacle.output").get_focused()
+
+    if not output then
+        return
+    end
+
+    client.unary_request(tag_service.SwitchToRelative, {
+        output_name = output.name,
+        offset = offset,
+    })
+end
+
+---Switch an output to the tag after its first active one, wrapping around to the first tag.
+---
+---If `output` is not specified, this uses the focused output.
+---
+---@param output OutputHandle?
+function tag.switch_right(output)
+    tag.switch_relative(1, output)
+end
+
+---Switch an output to the tag before its first active one, wrapping around to the last tag.
+---
+---If `output` is not specified, this uses the focused output.
+---
+---@param output OutputHandle?
+function tag.switch_left(output)
+    tag.switch_relative(-1, output)
+end
+
+---Get the sets of tags that were active on an output before, most recent first.
+---
+---The currently active tags aren't included.
+---
+---If `output` is not specified, this uses the focused output.
+---
+---### Example
+---```lua
+---for _, tags in ipairs(Tag.history()) do
+---    for _, tg in ipairs(tags) do
+---        print(tg:name())
+---    end
+---end
+---```
+---
+---@param output OutputHandle?
+---
+---@return TagHandle[][]
+function tag.history(output)
+    output = output or require("pinnacle.output").get_focused()
+
+    if not output then
+        return {}
+    end
+
+    local response = client.unary_request(tag_service.GetHistory, { output_name = output.name })
+
+    ---@type TagHandle[][]
+    local history = {}
+
+    for _, entry in ipairs(response.entries or {}) do
+        ---@type TagHandle[]
+        local handles = {}
+        for _, id in ipairs(entry.tag_ids or {}) do
+            table.insert(handles, tag_handle.new(id))
+        end
+        table.insert(history, handles)
+    end
+
+    return history
 end
 
 ---@type table<string, SignalServiceMethod>
 local signal_name_to_SignalName = {
     active = "TagActive",
+    urgent = "TagUrgent",
+    occupied = "TagOccupied",
+    renamed = "TagRenamed",
 }
 
 ---@class TagSignal Signals related to tag events.
 ---@field active fun(tag: TagHandle, active: boolean)? A tag was set to active or not active.
+---@field urgent fun(tag: TagHandle, urgent: boolean)? A tag started or stopped having windows that want attention.
+---@field occupied fun(tag: TagHandle, occupied: boolean)? A tag got its first window or lost its last one.
+---@field renamed fun(tag: TagHandle, name: string)? A tag was renamed.
 
 ---Connect to a tag signal.
 ---
@@ -221,6 +383,8 @@ end
 
 ---Remove this tag.
 ---
+---Its windows are moved like with `Tag.remove`.
+---
 ---### Example
 ---```lua
 ---local tags = Tag.add(Output.get_by_name("HDMI-1"), "1", "2", "Buckle", "Shoe")
@@ -287,11 +451,139 @@ function TagHandle:toggle_active()
     )
 end
 
+---Gaps around tiled windows, in logical pixels.
+---@class Gaps
+---@field inner integer? The gap between neighboring windows
+---@field outer integer? The gap between windows and the edges of the output and exclusive zones
+
+---Set the gaps around tiled windows while this tag is active.
+---
+---Gaps that aren't specified are left unchanged.
+---If multiple tags on an output are active, the largest of their gaps are used.
+---
+---### Example
+---```lua
+---Tag.get("1"):set_gaps({ inner = 8, outer = 16 })
+---Tag.get("2"):set_gaps({ outer = 0 })
+---```
+---
+---@param gaps Gaps
+function TagHandle:set_gaps(gaps)
+    client.unary_request(
+        tag_service.SetGaps,
+        { tag_id = self.id, inner = gaps.inner, outer = gaps.outer }
+    )
+end
+
+---Set the corner radius of windows while this tag is active, in logical pixels,
+---or go back to the global one with `nil`.
+---
+---This overrides `Render.set_corner_radius` but not the `corner_radius` window rule.
+---If a window has multiple active tags, the first one that sets a radius is used.
+---
+---### Example
+---```lua
+--- -- Square corners and no gaps on "code", round corners and wide gaps on "chat"
+---local code = Tag.get("code")
+---code:set_corner_radius(0)
+---code:set_gaps({ inner = 0, outer = 0 })
+---
+---local chat = Tag.get("chat")
+---chat:set_corner_radius(12)
+---chat:set_gaps({ inner = 16, outer = 32 })
+---```
+---
+---@param radius number?
+function TagHandle:set_corner_radius(radius)
+    client.unary_request(tag_service.SetCornerRadius, { tag_id = self.id, radius = radius })
+end
+
+---Set the name of the layout this tag uses, or forget it with `nil`.
+---
+---The compositor remembers the name and passes it to the layout manager in `LayoutArgs.layout`
+---when this tag is the first active tag on its output, so switching back to this tag restores
+---its layout. `CyclingLayoutManager`s pick the layout generator with this `name`.
+---
+---### Example
+---```lua
+---Tag.get("1"):set_layout("dwindle")
+---Tag.get("2"):set_layout(nil)
+---```
+---
+---@param layout string?
+function TagHandle:set_layout(layout)
+    client.unary_request(tag_service.SetLayout, { tag_id = self.id, layout = layout })
+end
+
+---Rename this tag.
+---
+---Tags are looked up by their new name with `Tag.get` afterwards.
+---
+---### Example
+---```lua
+--- -- Show what's on the tag
+---Tag.get("3"):rename("3: web")
+---```
+---
+---@param name string
+function TagHandle:rename(name)
+    client.unary_request(tag_service.SetName, { tag_id = self.id, name = name })
+end
+
+---Move this tag and its windows to `output`.
+---
+---Both outputs are laid out again. Floating windows keep their position relative to the output.
+---If the old output is left without an active tag, its first tag is activated.
+---
+---### Example
+---```lua
+--- -- Move tag "3" on the focused output to "HDMI-1"
+---Tag.get("3"):move_to_output(Output.get_by_name("HDMI-1"))
+---```
+---
+---@param output OutputHandle
+function TagHandle:move_to_output(output)
+    client.unary_request(tag_service.MoveToOutput, { tag_id = self.id, output_name = output.name })
+end
+
+---Move this tag to `index` among the tags on its output, starting at 1.
+---
+---Indices past the last tag move it to the end. Tag order is the order tags are returned in,
+---the order `Tag.switch_relative` goes through, and the order workspace clients show tags in.
+---
+---### Example
+---```lua
+--- -- Make "web" the first tag
+---Tag.get("web"):set_index(1)
+---```
+---
+---@param index integer
+function TagHandle:set_index(index)
+    client.unary_request(tag_service.Reorder, { tag_id = self.id, index = math.max(index - 1, 0) })
+end
+
+---Move this tag one place left among the tags on its output.
+---
+---Does nothing if it's already the first tag.
+function TagHandle:move_left()
+    client.unary_request(tag_service.Reorder, { tag_id = self.id, offset = -1 })
+end
+
+---Move this tag one place right among the tags on its output.
+---
+---Does nothing if it's already the last tag.
+function TagHandle:move_right()
+    client.unary_request(tag_service.Reorder, { tag_id = self.id, offset = 1 })
+end
+
 ---@class TagProperties
 ---@field active boolean? Whether or not the tag is currently being displayed
 ---@field name string? The name of the tag
 ---@field output OutputHandle? The output the tag is on
 ---@field windows WindowHandle[] The windows that have this tag
+---@field layout string? The name of the layout this tag uses
+---@field urgent boolean? Whether or not any window with this tag wants attention
+---@field hidden boolean? Whether or not this is an empty trailing tag hidden in dynamic mode
 
 ---Get all properties of this tag.
 ---
@@ -307,6 +599,9 @@ function TagHandle:props()
             and require("pinnacle.output").handle.new(response.output_name),
         ---@diagnostic disable-next-line: invisible
         windows = require("pinnacle.window").handle.new_from_table(response.window_ids or {}),
+        layout = response.layout,
+        urgent = response.urgent,
+        hidden = response.hidden,
     }
 end
 
@@ -346,6 +641,35 @@ function TagHandle:windows()
     return self:props().windows
 end
 
+---Get the name of the layout this tag uses.
+---
+---Shorthand for `handle:props().layout`.
+---
+---@return string?
+function TagHandle:layout()
+    return self:props().layout
+end
+
+---Get whether or not any window with this tag wants attention.
+---
+---Shorthand for `handle:props().urgent`.
+---
+---@return boolean?
+function TagHandle:urgent()
+    return self:props().urgent
+end
+
+---Get whether or not this tag is hidden because it's an empty trailing tag in dynamic mode.
+---
+---See `Tag.set_dynamic`.
+---
+---Shorthand for `handle:props().hidden`.
+---
+---@return boolean?
+function TagHandle:hidden()
+    return self:props().hidden
+end
+
 ---@nodoc
 ---Create a new `TagHandle` from an id.
 ---@param tag_id integer
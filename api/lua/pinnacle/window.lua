@@ -95,6 +95,9 @@ end
 ---The button must be pressed at the time this method is called.
 ---If the button is lifted, the move will end.
 ---
+---Tiled windows are lifted off their tile and follow the pointer, and are swapped with
+---the tile they're dropped on.
+---
 ---### Example
 ---```lua
 ---Input.mousebind({ "super" }, "btn_left", function()
@@ -104,7 +107,7 @@ end
 ---@param button MouseButton The button that will initiate the move
 function window.begin_move(button)
     ---@diagnostic disable-next-line: redefined-local, invisible
-    local button = require("pinnacle.input").mouse_button_values[button]
+    local button = require("pinnacle.input").mouse_button_values[button] or button
     client.unary_request(window_service.MoveGrab, { button = button })
 end
 
@@ -113,6 +116,8 @@ end
 ---The button must be pressed at the time this method is called.
 ---If the button is lifted, the resize will end.
 ---
+---Tiled windows are resized by adjusting the layout's split ratios; see `WindowHandle:resize_tile`.
+---
 ---### Example
 ---```lua
 ---Input.mousebind({ "super" }, "btn_right", function()
@@ -122,26 +127,42 @@ end
 ---@param button MouseButton The button that will initiate the resize
 function window.begin_resize(button)
     ---@diagnostic disable-next-line: redefined-local, invisible
-    local button = require("pinnacle.input").mouse_button_values[button]
+    local button = require("pinnacle.input").mouse_button_values[button] or button
     client.unary_request(window_service.ResizeGrab, { button = button })
 end
 
 ---@class WindowRuleCondition
 ---@field any WindowRuleCondition[]?
 ---@field all WindowRuleCondition[]?
+---@field none WindowRuleCondition[]? Conditions that must all be false
 ---@field classes string[]?
 ---@field titles string[]?
 ---@field tags TagHandle[]?
+---@field class_regexes string[]? Regexes matched against the window's class
+---@field title_regexes string[]? Regexes matched against the window's title
+---@field x11_classes string[]? Regexes matched against an Xwayland window's `WM_CLASS`
+---@field x11_roles string[]? Regexes matched against an Xwayland window's `WM_WINDOW_ROLE`
+---@field outputs OutputHandle[]?
+---@field floating boolean? `true` to match floating windows, `false` to match tiled ones
 
 ---@class WindowRule
 ---@field output OutputHandle?
 ---@field tags TagHandle[]?
 ---@field floating boolean?
 ---@field fullscreen_or_maximized FullscreenOrMaximized?
----@field x integer?
----@field y integer?
+---@field x integer? The x-coordinate of the window, or its x-offset from `anchor`
+---@field y integer? The y-coordinate of the window, or its y-offset from `anchor`
 ---@field width integer?
 ---@field height integer?
+---@field anchor FloatingAnchor? The part of its output the window is placed at while floating
+---@field animations boolean? `false` to disable animations for the window
+---@field corner_radius number? The corner radius of the window in logical pixels
+---@field blur boolean? `true` to blur the background behind the window
+---@field dim boolean? `false` to never dim the window when it's unfocused
+---@field layer WindowLayer? The layer the window is stacked in while floating
+---@field pip boolean? `true` to make the window picture-in-picture, `false` to never detect it
+---@field fake_fullscreen boolean? `true` to only tell the window it's fullscreen when it asks to be
+---@field follow boolean? `true` to switch to the window's tag and focus it when it opens
 
 ---@enum (key) FullscreenOrMaximized
 local _fullscreen_or_maximized = {
@@ -156,6 +177,45 @@ local _fullscreen_or_maximized_keys = {
     [3] = "maximized",
 }
 
+---Where a floating window is stacked relative to other windows.
+---
+---Tiled windows are always stacked in the normal layer.
+---@enum (key) WindowLayer
+local _window_layer = {
+    normal = 1,
+    above = 2,
+    below = 3,
+}
+
+---The part of an output a window rule places a floating window at.
+---@enum (key) FloatingAnchor
+local _floating_anchor = {
+    center = 1,
+    top = 2,
+    bottom = 3,
+    left = 4,
+    right = 5,
+    top_left = 6,
+    top_right = 7,
+    bottom_left = 8,
+    bottom_right = 9,
+}
+
+---A direction to move focus or windows in.
+---@enum (key) Direction
+local _direction = {
+    left = 1,
+    right = 2,
+    up = 3,
+    down = 4,
+}
+
+local _window_layer_keys = {
+    [1] = "normal",
+    [2] = "above",
+    [3] = "below",
+}
+
 ---@param rule WindowRule
 local function process_window_rule(rule)
     if rule.output then
@@ -174,6 +234,14 @@ local function process_window_rule(rule)
     if rule.fullscreen_or_maximized then
         rule.fullscreen_or_maximized = _fullscreen_or_maximized[rule.fullscreen_or_maximized]
     end
+
+    if rule.layer then
+        rule.layer = _window_layer[rule.layer]
+    end
+
+    if rule.anchor then
+        rule.anchor = _floating_anchor[rule.anchor]
+    end
 end
 
 ---@param cond WindowRuleCondition
@@ -186,6 +254,15 @@ local function process_window_rule_cond(cond)
         cond.tags = ids
     end
 
+    if cond.outputs then
+        local names = {}
+        for _, op in ipairs(cond.outputs) do
+            table.insert(names, op.name)
+        end
+        ---@diagnostic disable-next-line: assign-type-mismatch
+        cond.outputs = names
+    end
+
     if cond.all then
         for _, con in ipairs(cond.all) do
             process_window_rule_cond(con)
@@ -197,6 +274,24 @@ local function process_window_rule_cond(cond)
             process_window_rule_cond(con)
         end
     end
+
+    if cond.none then
+        for _, con in ipairs(cond.none) do
+            process_window_rule_cond(con)
+        end
+    end
+end
+
+---A handle to an added window rule.
+---@class WindowRuleHandle
+---@field id integer
+local WindowRuleHandle = {}
+
+---Remove this window rule.
+---
+---Windows it was already applied to keep what it set.
+function WindowRuleHandle:remove()
+    client.unary_request(window_service.RemoveWindowRule, { rule_id = self.id })
 end
 
 ---Add a window rule.
@@ -216,6 +311,16 @@ end
 --- - The outermost block of a window rule condition is implicitly an `all` block.
 --- - Within an `all` block, all items in each array must be true for the attribute to be true.
 --- - Within an `any` block, only one item in each array needs to be true for the attribute to be true.
+--- - All children inside a `none` block must be false for the block to be true.
+---
+---Besides exact `classes` and `titles`, windows can be matched with regexes on their class,
+---title, and for Xwayland windows, their `WM_CLASS` and `WM_WINDOW_ROLE`. Regexes use the
+---syntax of Rust's `regex` crate and match anywhere unless anchored with `^` and `$`.
+---
+---Rules are checked when a window opens, and again when its title or class changes.
+---Rules that start applying to the window then are applied as well.
+---
+---This returns a handle that can remove the rule.
 ---
 ---`cond` can be a bit confusing and quite table heavy. Examples are shown below for guidance.
 ---
@@ -285,28 +390,331 @@ end
 ---    },
 ---    rule = { floating = true },
 ---})
+---
+--- -- Regexes and `none` can be used to match windows more loosely.
+--- -- The following will float every JetBrains dialog that isn't the main window.
+---local handle = Window.add_window_rule({
+---    cond = {
+---        class_regexes = { "^jetbrains-" },
+---        none = { { title_regexes = { " – " } } },
+---    },
+---    rule = { floating = true },
+---})
+---
+--- -- Remove the rule later
+---handle:remove()
+---
+--- -- Floating windows with a size open at that size, so they can be placed exactly.
+--- -- The following opens a dropdown terminal along the top of the output.
+---Window.add_window_rule({
+---    cond = { classes = { "dropdown" } },
+---    rule = { floating = true, width = 1200, height = 400, anchor = "top" },
+---})
 ---```
 ---
 ---@param rule { cond: WindowRuleCondition, rule: WindowRule } The condition and rule
+---
+---@return WindowRuleHandle
 function window.add_window_rule(rule)
     process_window_rule(rule.rule)
 
     process_window_rule_cond(rule.cond)
 
-    client.unary_request(window_service.AddWindowRule, {
+    local response = client.unary_request(window_service.AddWindowRule, {
         cond = rule.cond,
         rule = rule.rule,
     })
+
+    ---@type WindowRuleHandle
+    local handle = { id = response.rule_id }
+    setmetatable(handle, { __index = WindowRuleHandle })
+    return handle
+end
+
+---@enum (key) InsertPosition
+local insert_position_name_to_code = {
+    ["end"] = 1,
+    after_focused = 2,
+    master = 3,
+}
+
+---Set where new windows are inserted into the layout.
+---
+---The default is `"end"`. `WindowHandle:mark_insert_position` overrides this for the next window.
+---
+---### Example
+---```lua
+---Window.set_insert_position("after_focused")
+---```
+---
+---@param position InsertPosition
+function window.set_insert_position(position)
+    client.unary_request(
+        window_service.SetInsertPosition,
+        { position = insert_position_name_to_code[position] }
+    )
+end
+
+---@enum (key) PipCorner
+local pip_corner_name_to_code = {
+    top_left = 1,
+    top_right = 2,
+    bottom_left = 3,
+    bottom_right = 4,
+}
+
+---Set where picture-in-picture windows are placed.
+---
+---Windows titled like browser picture-in-picture players, or that a window rule
+---marks with `pip = true`, are floated above other windows, shown on every tag of
+---their output, and pinned to `corner` at the given size. They are put back into
+---the corner when their output's resolution changes.
+---
+---The default is the bottom right corner at 480x270.
+---
+---### Example
+---```lua
+---Window.set_pip_placement("top_right", 640, 360)
+---```
+---
+---@param corner PipCorner
+---@param width integer
+---@param height integer
+function window.set_pip_placement(corner, width, height)
+    client.unary_request(window_service.SetPipPlacement, {
+        corner = pip_corner_name_to_code[corner],
+        width = width,
+        height = height,
+    })
+end
+
+---Set how close in logical pixels floating windows have to get to snap while being moved.
+---
+---Moved floating windows snap to the edges of outputs, the edges of the area not taken
+---up by panels and other layer surfaces, and the edges of other floating windows.
+---Guide lines are shown along the edges a window snapped to.
+---
+---The default is 12. A threshold of 0 disables snapping.
+---
+---### Example
+---```lua
+---Window.set_snap_threshold(20)
+---```
+---
+---@param threshold integer
+function window.set_snap_threshold(threshold)
+    client.unary_request(window_service.SetSnapThreshold, {
+        threshold = threshold,
+    })
+end
+
+---Get the floating geometry remembered for each app, keyed by app id or class.
+---
+---When a floating window closes, its geometry is remembered, and the next window of the
+---same app opens with it unless a window rule sets its size or location.
+---Remembered geometries persist across restarts.
+---
+---### Example
+---```lua
+---for app_id, geo in pairs(Window.remembered_geometries()) do
+---    print(app_id, geo.x, geo.y, geo.width, geo.height)
+---end
+---```
+---
+---@return table<string, { x: integer, y: integer, width: integer, height: integer }>
+function window.remembered_geometries()
+    local response = client.unary_request(window_service.GetRememberedGeometries, {})
+
+    ---@type table<string, { x: integer, y: integer, width: integer, height: integer }>
+    local geometries = {}
+
+    for _, remembered in ipairs(response.geometries or {}) do
+        local geo = remembered.geometry
+        if remembered.app_id and geo then
+            geometries[remembered.app_id] = {
+                x = geo.x or 0,
+                y = geo.y or 0,
+                width = geo.width or 0,
+                height = geo.height or 0,
+            }
+        end
+    end
+
+    return geometries
+end
+
+---Forget the remembered floating geometry of the app with the given app id or class,
+---or of every app if `app_id` is nil.
+---
+---### Example
+---```lua
+---Window.forget_geometry("org.gnome.Calculator")
+---Window.forget_geometry() -- Forget every app's geometry
+---```
+---
+---@param app_id string?
+function window.forget_geometry(app_id)
+    client.unary_request(window_service.ForgetGeometry, {
+        app_id = app_id,
+    })
+end
+
+---Focus and raise the next tiled window on the focused output, in layout order.
+---
+---This is mainly useful with the monocle layout, where only one window is visible at a time.
+---
+---### Example
+---```lua
+---Window.cycle_focus_forward()
+---```
+function window.cycle_focus_forward()
+    client.unary_request(window_service.CycleFocus, { backward = false })
+end
+
+---Focus and raise the previous tiled window on the focused output, in layout order.
+---
+---### Example
+---```lua
+---Window.cycle_focus_backward()
+---```
+function window.cycle_focus_backward()
+    client.unary_request(window_service.CycleFocus, { backward = true })
+end
+
+---Focus and raise the window nearest to the focused one in the given direction.
+---
+---Windows on other outputs are also considered, so this moves focus to a neighboring
+---output when there's nothing left in that direction on the current one.
+---
+---### Example
+---```lua
+---Window.move_focus("left")
+---```
+---
+---@param direction Direction
+function window.move_focus(direction)
+    client.unary_request(window_service.MoveFocus, { direction = _direction[direction] })
+end
+
+---Focus and raise the first window that wants attention.
+---
+---If the window isn't on an active tag, its output switches to the window's first tag.
+---
+---### Example
+---```lua
+---Window.focus_urgent()
+---```
+function window.focus_urgent()
+    client.unary_request(window_service.FocusUrgent, {})
+end
+
+---Open the window switcher, or select the next window if it's already open.
+---
+---The switcher shows every visible window, most recently focused first, and opens with
+---the window focused before the current one selected. It stays open while any modifier
+---is held and focuses the selected window once they're all released, so this is meant
+---to be called from a keybind with a modifier, like alt+tab. Escape closes the switcher
+---without changing focus.
+---
+---### Example
+---```lua
+---Input.keybind({ "alt" }, Input.key.Tab, function()
+---    Window.cycle_switcher_forward()
+---end)
+---```
+function window.cycle_switcher_forward()
+    client.unary_request(window_service.CycleWindowSwitcher, { backward = false })
+end
+
+---Open the window switcher, or select the previous window if it's already open.
+---
+---See `Window.cycle_switcher_forward`.
+---
+---### Example
+---```lua
+---Window.cycle_switcher_backward()
+---```
+function window.cycle_switcher_backward()
+    client.unary_request(window_service.CycleWindowSwitcher, { backward = true })
+end
+
+---Set whether the window switcher shows thumbnails of windows.
+---
+---Thumbnails are shown by default.
+---
+---### Example
+---```lua
+---Window.set_switcher_thumbnails(false)
+---```
+---
+---@param thumbnails boolean
+function window.set_switcher_thumbnails(thumbnails)
+    client.unary_request(window_service.SetWindowSwitcherThumbnails, { thumbnails = thumbnails })
+end
+
+---Set whether clicking a window raises it.
+---
+---Clicking a window always focuses it. Windows are raised on click by default.
+---
+---### Example
+---```lua
+--- -- Only raise windows with `WindowHandle:raise`
+---Window.set_click_to_raise(false)
+---```
+---
+---@param click_to_raise boolean
+function window.set_click_to_raise(click_to_raise)
+    client.unary_request(window_service.SetClickToRaise, { click_to_raise = click_to_raise })
+end
+
+---Set whether windows the pointer moves onto get focused.
+---
+---Windows are focused after the pointer stays on them for `delay_ms` milliseconds, or right away
+---if it's `nil`. Focus doesn't move when the layout moves a window under the pointer, only when
+---the pointer moves onto one. Hovered windows aren't raised. Focus doesn't follow the mouse
+---by default.
+---
+---### Example
+---```lua
+--- -- Don't focus windows the pointer only passes over
+---Window.set_focus_follows_mouse(true, 150)
+---```
+---
+---@param enabled boolean
+---@param delay_ms integer? How long the pointer has to stay on a window
+function window.set_focus_follows_mouse(enabled, delay_ms)
+    client.unary_request(
+        window_service.SetFocusFollowsMouse,
+        { enabled = enabled, delay_ms = delay_ms }
+    )
+end
+
+---Set whether windows of unresponsive clients are darkened.
+---
+---A client is unresponsive when it doesn't answer the compositor's pings for a few seconds,
+---usually because it's frozen. Its windows stay darkened until it answers again.
+---Only Wayland clients are pinged.
+---
+---### Example
+---```lua
+---Window.set_unresponsive_overlay(true)
+---```
+---
+---@param enabled boolean
+function window.set_unresponsive_overlay(enabled)
+    client.unary_request(window_service.SetUnresponsiveOverlay, { enabled = enabled })
 end
 
 local signal_name_to_SignalName = {
     pointer_enter = "WindowPointerEnter",
     pointer_leave = "WindowPointerLeave",
+    urgent = "WindowUrgent",
 }
 
 ---@class WindowSignal Signals related to compositor events.
 ---@field pointer_enter fun(window: WindowHandle)? The pointer entered a window.
 ---@field pointer_leave fun(window: WindowHandle)? The pointer left a window.
+---@field urgent fun(window: WindowHandle, urgent: boolean)? A window started or stopped wanting attention.
 
 ---Connect to a window signal.
 ---
@@ -358,6 +766,27 @@ function WindowHandle:close()
     client.unary_request(window_service.Close, { window_id = self.id })
 end
 
+---Send a close request to this window, killing its process if it's still open
+---after `timeout` seconds.
+---
+---This closes windows that don't respond to `close`. The process is killed with `SIGKILL`,
+---so any unsaved work in it is lost.
+---
+---### Example
+---```lua
+--- -- Close the focused window, killing it if it doesn't close within 3 seconds
+---local focused = Window.get_focused()
+---if focused then focused:close_or_kill(3) end
+---```
+---
+---@param timeout number? How long the window has to close, in seconds. Defaults to 5.
+function WindowHandle:close_or_kill(timeout)
+    client.unary_request(window_service.CloseOrKill, {
+        window_id = self.id,
+        timeout_ms = timeout and math.floor(timeout * 1000),
+    })
+end
+
 ---Set this window's location and/or size.
 ---
 ---The coordinate system has the following axes:
@@ -425,6 +854,9 @@ end
 
 ---Set this window to maximized or not.
 ---
+---Maximized windows fill their output except for the space taken up by panels and other
+---layer surfaces. Floating windows get their previous geometry back when unmaximized.
+---
 ---### Example
 ---```lua
 ---local focused = Window.get_focused()
@@ -493,6 +925,66 @@ function WindowHandle:toggle_floating()
     )
 end
 
+---Set whether fullscreen requests from this window only tell it that it's fullscreen.
+---
+---While set, a window that asks to be fullscreen, like a video player, thinks it is but
+---stays in its tile or floating geometry. This does not affect setting it to fullscreen
+---through this API.
+---
+---### Example
+---```lua
+---local focused = Window.get_focused()
+---if focused then
+---    focused:set_fake_fullscreen(true)
+---end
+---```
+---
+---@param fake_fullscreen boolean
+function WindowHandle:set_fake_fullscreen(fake_fullscreen)
+    client.unary_request(
+        window_service.SetFakeFullscreen,
+        { window_id = self.id, set_or_toggle = set_or_toggle[fake_fullscreen] }
+    )
+end
+
+---Toggle whether fullscreen requests from this window only tell it that it's fullscreen.
+---
+---### Example
+---```lua
+---local focused = Window.get_focused()
+---if focused then
+---    focused:toggle_fake_fullscreen()
+---end
+---```
+function WindowHandle:toggle_fake_fullscreen()
+    client.unary_request(
+        window_service.SetFakeFullscreen,
+        { window_id = self.id, set_or_toggle = set_or_toggle.TOGGLE }
+    )
+end
+
+---Set the layer this window is stacked in while it's floating.
+---
+---Windows in the above layer always stay above normal windows, and windows
+---in the below layer always stay below them. Tiled windows are always stacked
+---in the normal layer.
+---
+---### Example
+---```lua
+---local focused = Window.get_focused()
+---if focused then
+---    focused:set_layer("above")
+---end
+---```
+---
+---@param layer WindowLayer
+function WindowHandle:set_layer(layer)
+    client.unary_request(
+        window_service.SetLayer,
+        { window_id = self.id, layer = _window_layer[layer] }
+    )
+end
+
 ---Focus or unfocus this window.
 ---
 ---### Example
@@ -545,8 +1037,32 @@ function WindowHandle:move_to_tag(tag)
     client.unary_request(window_service.MoveToTag, { window_id = self.id, tag_id = tag.id })
 end
 
+---Move this window in the given direction.
+---
+---Tiled windows swap places with the nearest tiled window in that direction on the same output.
+---Floating windows are pushed a small step in that direction.
+---
+---### Example
+---```lua
+---local focused = Window.get_focused()
+---if focused then
+---    focused:move_in_direction("right")
+---end
+---```
+---
+---@param direction Direction
+function WindowHandle:move_in_direction(direction)
+    client.unary_request(
+        window_service.MoveInDirection,
+        { window_id = self.id, direction = _direction[direction] }
+    )
+end
+
 ---Tag or untag the given tag on this window.
 ---
+---Windows can have several tags and show up while any of them is active.
+---Setting a tag on another output moves this window there, dropping its other tags.
+---
 ---### Example
 ---```lua
 --- -- Assume the focused output has the tag "Tag"
@@ -570,6 +1086,32 @@ function WindowHandle:set_tag(tag, set)
     )
 end
 
+---Replace this window's tags.
+---
+---This window shows up while any of `tags` is active. Tags on a different output
+---than the first one are ignored.
+---
+---### Example
+---```lua
+--- -- Show the focused window on both "1" and "3"
+---local focused = Window.get_focused()
+---if focused then
+---    focused:set_tags({ Tag.get("1"), Tag.get("3") })
+---end
+---```
+---
+---@param tags TagHandle[]
+function WindowHandle:set_tags(tags)
+    ---@type integer[]
+    local tag_ids = {}
+
+    for _, tg in ipairs(tags) do
+        table.insert(tag_ids, tg.id)
+    end
+
+    client.unary_request(window_service.SetTags, { window_id = self.id, tag_ids = tag_ids })
+end
+
 ---Toggle the given tag on this window.
 ---
 ---### Example
@@ -610,6 +1152,115 @@ function WindowHandle:raise()
     client.unary_request(window_service.Raise, { window_id = self.id })
 end
 
+---Lower a window.
+---
+---This will lower a window all the way to the bottom of the z-stack,
+---though windows in the below layer stay below it.
+---
+---### Example
+---```lua
+---local focused = Window.get_focused()
+---if focused then
+---    focused:lower()
+---end
+---```
+function WindowHandle:lower()
+    client.unary_request(window_service.Lower, { window_id = self.id })
+end
+
+---Add this window to `target`'s group, creating a group if `target` isn't in one.
+---
+---Windows in a group share one tile, and only the group's active window is shown.
+---An indicator above the group shows its windows as tabs or stacked rows.
+---This window becomes the group's active window.
+---
+---If `target` is nil, the next tiled window on the same output is used.
+---Only tiled windows can be grouped.
+---
+---### Example
+---```lua
+------ Group the focused window with the next one
+---local focused = Window.get_focused()
+---if focused then
+---    focused:add_to_group()
+---end
+---```
+---
+---@param target WindowHandle?
+function WindowHandle:add_to_group(target)
+    client.unary_request(
+        window_service.AddToGroup,
+        { window_id = self.id, target_window_id = target and target.id }
+    )
+end
+
+---Remove this window from its group, giving it its own tile again.
+function WindowHandle:remove_from_group()
+    client.unary_request(window_service.RemoveFromGroup, { window_id = self.id })
+end
+
+---@enum (key) GroupMode
+local group_mode_name_to_code = {
+    tabbed = 1,
+    stacked = 2,
+}
+
+---Set how the indicator of this window's group shows its windows.
+---
+---@param mode GroupMode
+function WindowHandle:set_group_mode(mode)
+    client.unary_request(
+        window_service.SetGroupMode,
+        { window_id = self.id, mode = group_mode_name_to_code[mode] }
+    )
+end
+
+---Show and focus the next window in this window's group.
+function WindowHandle:cycle_group_forward()
+    client.unary_request(window_service.CycleGroup, { window_id = self.id, backward = false })
+end
+
+---Show and focus the previous window in this window's group.
+function WindowHandle:cycle_group_backward()
+    client.unary_request(window_service.CycleGroup, { window_id = self.id, backward = true })
+end
+
+---Open the next new window right after this one, once.
+---
+---This overrides the insert position set with `Window.set_insert_position` for the next window.
+---
+---### Example
+---```lua
+--- -- The next window opens after the focused window
+---Window.get_focused():mark_insert_position()
+---```
+function WindowHandle:mark_insert_position()
+    client.unary_request(window_service.MarkInsertPosition, { window_id = self.id })
+end
+
+---Resize this window if it's tiled by adjusting the layout's split ratios.
+---
+---The deltas are how many pixels wider and taller the window should get.
+---Negative values shrink it. The active layout generator is asked to adjust its ratios
+---through its `resize_tile` method and keeps them for later layouts.
+---
+---This does nothing if no layout manager is set.
+---
+---### Example
+---```lua
+--- -- Make the focused window 50 pixels wider
+---Window.get_focused():resize_tile(50, 0)
+---```
+---
+---@param width_delta integer
+---@param height_delta integer
+function WindowHandle:resize_tile(width_delta, height_delta)
+    client.unary_request(
+        window_service.ResizeTile,
+        { window_id = self.id, width_delta = width_delta, height_delta = height_delta }
+    )
+end
+
 ---@class WindowProperties
 ---@field geometry { x: integer?, y: integer?, width: integer?, height: integer? }? The location and size of the window
 ---@field class string? The window's class
@@ -618,6 +1269,8 @@ end
 ---@field floating boolean? Whether or not the window is floating
 ---@field fullscreen_or_maximized FullscreenOrMaximized? Whether the window is fullscreen, maximized, or neither
 ---@field tags TagHandle[]? The tags the window has
+---@field layer WindowLayer? The layer the window is stacked in while floating
+---@field urgent boolean? Whether or not the window wants attention
 
 ---Get all the properties of this window.
 ---
@@ -633,6 +1286,8 @@ function WindowHandle:props()
         and require("pinnacle.tag").handle.new_from_table(response.tag_ids)
     response.tag_ids = nil
 
+    response.layer = _window_layer_keys[response.layer]
+
     return response
 end
 
@@ -699,6 +1354,28 @@ function WindowHandle:tags()
     return self:props().tags
 end
 
+---Get the layer this window is stacked in while floating.
+---
+---Shorthand for `handle:props().layer`.
+---
+---@return WindowLayer?
+function WindowHandle:layer()
+    return self:props().layer
+end
+
+---Get whether or not this window wants attention.
+---
+---Windows want attention when their client asks to be activated while they aren't focused,
+---or when an X11 client sets the urgency hint on them. They stop wanting attention
+---once they're focused.
+---
+---Shorthand for `handle:props().urgent`.
+---
+---@return boolean?
+function WindowHandle:urgent()
+    return self:props().urgent
+end
+
 ---@nodoc
 ---Create a new `WindowHandle` from an id.
 ---@param window_id integer
@@ -1,14 +1,17 @@
+use std::time::Duration;
+
 use pinnacle_api::input::libinput::LibinputSetting;
 use pinnacle_api::layout::{
     CornerLayout, CornerLocation, CyclingLayoutManager, DwindleLayout, FairLayout, MasterSide,
-    MasterStackLayout, SpiralLayout,
+    MasterStackLayout, MonocleLayout, SpiralLayout,
 };
 use pinnacle_api::output::OutputSetup;
 use pinnacle_api::signal::WindowSignal;
 use pinnacle_api::util::{Axis, Batch};
+use pinnacle_api::window::Direction;
 use pinnacle_api::xkbcommon::xkb::Keysym;
 use pinnacle_api::{
-    input::{Mod, MouseButton, MouseEdge},
+    input::{Mod, MouseButton, MouseEdge, ScrollDirection, SwipeDirection},
     ApiModules,
 };
 
@@ -70,6 +73,13 @@ async fn main() {
         }
     });
 
+    // `mod_key + alt + shift + c` closes the focused window, killing it if it doesn't close
+    input.keybind([mod_key, Mod::Alt, Mod::Shift], 'c', || {
+        if let Some(window) = window.get_focused() {
+            window.close_or_kill(Duration::from_secs(3));
+        }
+    });
+
     // `mod_key + Return` spawns a terminal
     input.keybind([mod_key], Keysym::Return, move || {
         process.spawn([terminal]);
@@ -99,6 +109,83 @@ async fn main() {
         }
     });
 
+    // `mod_key + g` groups the focused window with the next one
+    input.keybind([mod_key], 'g', || {
+        if let Some(window) = window.get_focused() {
+            window.add_to_group(None);
+        }
+    });
+
+    // `mod_key + shift + g` removes the focused window from its group
+    input.keybind([mod_key, Mod::Shift], 'g', || {
+        if let Some(window) = window.get_focused() {
+            window.remove_from_group();
+        }
+    });
+
+    // `mod_key + tab` and `mod_key + shift + tab` cycle through the focused window's group
+    input.keybind([mod_key], Keysym::Tab, || {
+        if let Some(window) = window.get_focused() {
+            window.cycle_group_forward();
+        }
+    });
+
+    input.keybind([mod_key, Mod::Shift], Keysym::Tab, || {
+        if let Some(window) = window.get_focused() {
+            window.cycle_group_backward();
+        }
+    });
+
+    // `mod_key + i` opens the next window right after the focused one
+    input.keybind([mod_key], 'i', || {
+        if let Some(window) = window.get_focused() {
+            window.mark_insert_position();
+        }
+    });
+
+    // `mod_key + j` and `mod_key + k` focus the next and previous tiled window
+    input.keybind([mod_key], 'j', || {
+        window.cycle_focus_forward();
+    });
+
+    input.keybind([mod_key], 'k', || {
+        window.cycle_focus_backward();
+    });
+
+    // `alt + tab` and `alt + shift + tab` open the window switcher and cycle through windows
+    // in the order they were focused. Releasing alt focuses the selected window.
+    input.keybind([Mod::Alt], Keysym::Tab, || {
+        window.cycle_switcher_forward();
+    });
+
+    input.keybind([Mod::Alt, Mod::Shift], Keysym::Tab, || {
+        window.cycle_switcher_backward();
+    });
+
+    // `mod_key + u` jumps to a window that wants attention
+    input.keybind([mod_key], 'u', || {
+        window.focus_urgent();
+    });
+
+    // `mod_key + arrow keys` focus the nearest window in that direction
+    for (keysym, direction) in [
+        (Keysym::Left, Direction::Left),
+        (Keysym::Right, Direction::Right),
+        (Keysym::Up, Direction::Up),
+        (Keysym::Down, Direction::Down),
+    ] {
+        input.keybind([mod_key], keysym, move || {
+            window.move_focus(direction);
+        });
+
+        // `mod_key + shift + arrow keys` move the focused window in that direction
+        input.keybind([mod_key, Mod::Shift], keysym, move || {
+            if let Some(window) = window.get_focused() {
+                window.move_in_direction(direction);
+            }
+        });
+    }
+
     //------------------------
     // Window rules          |
     //------------------------
@@ -131,7 +218,7 @@ async fn main() {
     // It takes in layout arguments and computes a vector of geometries that will
     // determine the size and position of windows being laid out.
     //
-    // There is one built-in layout manager and five built-in layout generators,
+    // There is one built-in layout manager and six built-in layout generators,
     // as shown below.
     //
     // Additionally, this system is designed to be user-extensible;
@@ -175,6 +262,7 @@ async fn main() {
             axis: Axis::Horizontal,
             ..Default::default()
         }) as _,
+        Box::<MonocleLayout>::default() as _,
     ]));
 
     let mut layout_requester_clone = layout_requester.clone();
@@ -213,9 +301,16 @@ async fn main() {
 
     let tag_names = ["1", "2", "3", "4", "5"];
 
+    // Remember which tags were active and which tags apps' windows were on
+    // across restarts and config reloads
+    pinnacle.set_session_persistence(true);
+
     // Setup all monitors with tags "1" through "5"
     output.setup([OutputSetup::new_with_matcher(|_| true).with_tags(tag_names)]);
 
+    // Bring back the tags that were active last time
+    pinnacle.restore_session();
+
     for tag_name in tag_names {
         // `mod_key + 1-5` switches to tag "1" to "5"
         input.keybind([mod_key], tag_name, move || {
@@ -250,6 +345,37 @@ async fn main() {
         });
     }
 
+    // `mod_key + grave` flips back to the previously active tags
+    input.keybind([mod_key], Keysym::grave, || {
+        if let Some(op) = output.get_focused() {
+            tag.switch_to_previous(&op);
+        }
+    });
+
+    // `mod_key + scroll up/down` switches to the previous or next tag
+    input.scrollbind([mod_key], ScrollDirection::Up, || {
+        if let Some(op) = output.get_focused() {
+            tag.switch_left(&op);
+        }
+    });
+    input.scrollbind([mod_key], ScrollDirection::Down, || {
+        if let Some(op) = output.get_focused() {
+            tag.switch_right(&op);
+        }
+    });
+
+    // Swiping left or right with three fingers switches to the next or previous tag
+    input.swipebind([], 3, SwipeDirection::Left, || {
+        if let Some(op) = output.get_focused() {
+            tag.switch_right(&op);
+        }
+    });
+    input.swipebind([], 3, SwipeDirection::Right, || {
+        if let Some(op) = output.get_focused() {
+            tag.switch_left(&op);
+        }
+    });
+
     input.set_libinput_setting(LibinputSetting::Tap(true));
 
     // Enable sloppy focus
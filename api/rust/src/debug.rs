@@ -0,0 +1,113 @@
+//! Debugging utilities.
+
+use pinnacle_api_defs::pinnacle::{
+    debug::v0alpha1::{
+        debug_service_client::DebugServiceClient, SetDamageVisualizationRequest,
+        SetPerfOverlayRequest,
+    },
+    v0alpha1::SetOrToggle,
+};
+use tonic::transport::Channel;
+
+use crate::block_on_tokio;
+
+/// A struct that allows you to toggle debugging aids.
+#[derive(Debug, Clone)]
+pub struct Debug {
+    client: DebugServiceClient<Channel>,
+}
+
+impl Debug {
+    pub(crate) fn new(channel: Channel) -> Self {
+        Self {
+            client: DebugServiceClient::new(channel),
+        }
+    }
+
+    /// Show or hide the performance overlay.
+    ///
+    /// The overlay is drawn in the top-left corner of every output and shows
+    /// the output's FPS, its 50th and 99th percentile frame times, the percentage of
+    /// the output that was damaged in the last frame, and whether the last frame
+    /// was scanned out directly or composited.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// debug.set_perf_overlay(true);
+    /// ```
+    pub fn set_perf_overlay(&self, enabled: bool) {
+        let mut client = self.client.clone();
+        block_on_tokio(client.set_perf_overlay(SetPerfOverlayRequest {
+            set_or_toggle: Some(match enabled {
+                true => SetOrToggle::Set,
+                false => SetOrToggle::Unset,
+            } as i32),
+        }))
+        .unwrap();
+    }
+
+    /// Toggle the performance overlay.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::input::Mod;
+    ///
+    /// input.keybind([Mod::Super, Mod::Shift], 'p', || {
+    ///     debug.toggle_perf_overlay();
+    /// });
+    /// ```
+    pub fn toggle_perf_overlay(&self) {
+        let mut client = self.client.clone();
+        block_on_tokio(client.set_perf_overlay(SetPerfOverlayRequest {
+            set_or_toggle: Some(SetOrToggle::Toggle as i32),
+        }))
+        .unwrap();
+    }
+
+    /// Enable or disable damage visualization.
+    ///
+    /// While enabled, regions of outputs that were redrawn flash red
+    /// and every render element is outlined in yellow. This is useful for finding
+    /// clients or parts of Pinnacle that redraw more than they need to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// debug.set_damage_visualization(true);
+    /// ```
+    pub fn set_damage_visualization(&self, enabled: bool) {
+        let mut client = self.client.clone();
+        block_on_tokio(
+            client.set_damage_visualization(SetDamageVisualizationRequest {
+                set_or_toggle: Some(match enabled {
+                    true => SetOrToggle::Set,
+                    false => SetOrToggle::Unset,
+                } as i32),
+            }),
+        )
+        .unwrap();
+    }
+
+    /// Toggle damage visualization.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::input::Mod;
+    ///
+    /// input.keybind([Mod::Super, Mod::Shift], 'd', || {
+    ///     debug.toggle_damage_visualization();
+    /// });
+    /// ```
+    pub fn toggle_damage_visualization(&self) {
+        let mut client = self.client.clone();
+        block_on_tokio(
+            client.set_damage_visualization(SetDamageVisualizationRequest {
+                set_or_toggle: Some(SetOrToggle::Toggle as i32),
+            }),
+        )
+        .unwrap();
+    }
+}
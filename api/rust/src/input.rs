@@ -8,6 +8,8 @@
 //! methods for setting key- and mousebinds, changing xkeyboard settings, and more.
 //! View the struct's documentation for more information.
 
+use std::{sync::OnceLock, time::Duration};
+
 use futures::{future::BoxFuture, FutureExt, StreamExt};
 use num_enum::TryFromPrimitive;
 use pinnacle_api_defs::pinnacle::input::{
@@ -15,17 +17,31 @@ use pinnacle_api_defs::pinnacle::input::{
     v0alpha1::{
         input_service_client::InputServiceClient,
         set_libinput_setting_request::{CalibrationMatrix, Setting},
-        SetKeybindRequest, SetLibinputSettingRequest, SetMousebindRequest, SetRepeatRateRequest,
-        SetXkbConfigRequest,
+        set_tablet_setting_request, switch_xkb_layout_request, warp_pointer_request,
+        GetDevicesRequest, GetXkbLayoutsRequest, ListKeybindsRequest, SetCursorThemeRequest,
+        SetKeyRemapRequest, SetKeybindRequest, SetLibinputSettingRequest,
+        SetLidSwitchPolicyRequest, SetLockKeysRequest, SetMouseKeysRequest, SetMousebindRequest,
+        SetPerWindowXkbLayoutRequest, SetRepeatRateRequest, SetScrollFactorRequest,
+        SetScrollbindRequest, SetSlowKeysRequest, SetStickyKeysRequest, SetSwipebindRequest,
+        SetTabletSettingRequest, SetTouchOutputRequest, SetWarpPointerOnOutputFocusRequest,
+        SetXkbConfigRequest, SwitchXkbLayoutRequest, WarpPointerRequest,
     },
 };
 use tokio::sync::mpsc::UnboundedSender;
 use tonic::transport::Channel;
 use xkbcommon::xkb::Keysym;
 
-use crate::block_on_tokio;
+use crate::{
+    block_on_tokio,
+    output::OutputHandle,
+    screenshot::geometry_to_api,
+    signal::{InputSignal, SignalHandle},
+    util::Geometry,
+    window::WindowHandle,
+    ApiModules,
+};
 
-use self::libinput::LibinputSetting;
+use self::libinput::{DeviceFilter, DeviceInfo, DeviceType, LibinputSetting};
 
 pub mod libinput;
 
@@ -33,19 +49,40 @@ pub mod libinput;
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
 pub enum MouseButton {
     /// The left mouse button
-    Left = 0x110,
+    Left,
     /// The right mouse button
-    Right = 0x111,
+    Right,
     /// The middle mouse button
-    Middle = 0x112,
+    Middle,
     /// The side mouse button
-    Side = 0x113,
+    Side,
     /// The extra mouse button
-    Extra = 0x114,
+    Extra,
     /// The forward mouse button
-    Forward = 0x115,
+    Forward,
     /// The backward mouse button
-    Back = 0x116,
+    Back,
+    /// The task mouse button
+    Task,
+    /// Any other button, by one of the `BTN_` prefixed codes in input-event-codes.h
+    Other(u32),
+}
+
+impl MouseButton {
+    /// Get the code of this button from input-event-codes.h.
+    pub fn code(self) -> u32 {
+        match self {
+            MouseButton::Left => 0x110,
+            MouseButton::Right => 0x111,
+            MouseButton::Middle => 0x112,
+            MouseButton::Side => 0x113,
+            MouseButton::Extra => 0x114,
+            MouseButton::Forward => 0x115,
+            MouseButton::Back => 0x116,
+            MouseButton::Task => 0x117,
+            MouseButton::Other(code) => code,
+        }
+    }
 }
 
 /// Keyboard modifiers.
@@ -72,6 +109,48 @@ pub enum MouseEdge {
     Release,
 }
 
+/// A scroll direction.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, TryFromPrimitive)]
+pub enum ScrollDirection {
+    /// Scroll up
+    Up = 1,
+    /// Scroll down
+    Down,
+    /// Scroll left
+    Left,
+    /// Scroll right
+    Right,
+}
+
+/// A touchpad swipe direction.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, TryFromPrimitive)]
+pub enum SwipeDirection {
+    /// Swipe up
+    Up = 1,
+    /// Swipe down
+    Down,
+    /// Swipe left
+    Left,
+    /// Swipe right
+    Right,
+}
+
+/// What to do when a laptop's lid is closed.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default, TryFromPrimitive)]
+pub enum LidSwitchPolicy {
+    /// Do nothing
+    Ignore = 1,
+    /// Disable internal panels while the lid is closed and an external monitor is connected.
+    ///
+    /// Their tags and windows are moved to the external monitor and moved back
+    /// when the lid is opened.
+    #[default]
+    Clamshell,
+}
+
 /// A struct that lets you define xkeyboard config options.
 ///
 /// See `xkeyboard-config(7)` for more information.
@@ -87,6 +166,93 @@ pub struct XkbConfig {
     pub variant: Option<&'static str>,
     /// Extra xkb configuration options
     pub options: Option<&'static str>,
+    /// The key to use as the compose key, like "ralt" or "menu".
+    ///
+    /// This adds `compose:<key>` to `options`.
+    pub compose_key: Option<&'static str>,
+}
+
+/// The keyboard's layouts, as returned by [`Input::xkb_layouts`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct XkbLayouts {
+    /// The names of the layouts, like "English (US)"
+    pub names: Vec<String>,
+    /// The index of the active layout in `names`
+    pub active: u32,
+}
+
+/// A key to remap or remap to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RemapKey {
+    /// An evdev keycode, like 58 for caps lock.
+    ///
+    /// Tools like `wev` or `evtest` show the keycodes of keys.
+    Keycode(u32),
+    /// The key that types this keysym without modifiers in the active layout.
+    Keysym(Keysym),
+}
+
+/// A setting for drawing tablets.
+#[derive(Debug, Clone)]
+pub enum TabletSetting {
+    /// Map the tablet's area to this output.
+    ///
+    /// Tablets cover the first output until they're mapped.
+    MapToOutput(OutputHandle),
+    /// Map the tablet's area to this region in the global space.
+    MapToRegion(Geometry),
+    /// Map pen pressure through a cubic bezier curve from (0, 0) to (1, 1) with these
+    /// control points.
+    ///
+    /// Coordinates are between 0 and 1. Control points (0, 0) and (1, 1) leave pressure as is,
+    /// while ones above that line make the pen feel softer.
+    PressureCurve {
+        /// The first control point
+        control_1: (f64, f64),
+        /// The second control point
+        control_2: (f64, f64),
+    },
+    /// Turn a pen button into another, like `0x14b` (`BTN_STYLUS`) into `0x14c` (`BTN_STYLUS2`).
+    RemapButton {
+        /// The button to remap
+        from: u32,
+        /// The button to remap it to
+        to: u32,
+    },
+}
+
+/// What a keybind is for, listed along with it by [`Input::keybinds`], and how it's triggered.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct KeybindInfo {
+    /// What the keybind is grouped under, like "Window" or "Launchers"
+    pub group: Option<String>,
+    /// What the keybind does
+    pub description: Option<String>,
+    /// Trigger the keybind when its key is released instead of pressed
+    pub on_release: bool,
+    /// Trigger the keybind repeatedly at the keyboard's repeat rate while its key is held,
+    /// like for volume keys
+    pub repeat: bool,
+    /// Keybinds for the same key with a lower priority can't replace this one.
+    ///
+    /// Setting a keybind for a key that's already bound replaces the existing keybind
+    /// if they have the same priority.
+    pub priority: i32,
+}
+
+/// A keybind set by the config.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Keybind {
+    /// The modifiers that need to be held down
+    pub mods: Vec<Mod>,
+    /// The key that triggers the keybind
+    pub key: Keysym,
+    /// The xkbcommon name of the key, without the `XKB_KEY_` prefix
+    pub xkb_name: String,
+    /// What the keybind is grouped under
+    pub group: Option<String>,
+    /// What the keybind does
+    pub description: Option<String>,
 }
 
 /// The `Input` struct.
@@ -97,6 +263,7 @@ pub struct XkbConfig {
 pub struct Input {
     channel: Channel,
     fut_sender: UnboundedSender<BoxFuture<'static, ()>>,
+    api: OnceLock<ApiModules>,
 }
 
 impl Input {
@@ -107,16 +274,23 @@ impl Input {
         Self {
             channel,
             fut_sender,
+            api: OnceLock::new(),
         }
     }
 
+    pub(crate) fn finish_init(&self, api: ApiModules) {
+        self.api.set(api).unwrap();
+    }
+
     fn create_input_client(&self) -> InputServiceClient<Channel> {
         InputServiceClient::new(self.channel.clone())
     }
 
     /// Set a keybind.
     ///
-    /// If called with an already set keybind, it gets replaced.
+    /// If called with an already set keybind, it gets replaced unless the existing one has
+    /// a higher [priority][KeybindInfo::priority]. Whichever keybind loses prints an error
+    /// naming the one it conflicted with.
     ///
     /// You must supply:
     /// - `mods`: A list of [`Mod`]s. These must be held down for the keybind to trigger.
@@ -156,6 +330,54 @@ impl Input {
         &self,
         mods: impl IntoIterator<Item = Mod>,
         key: impl Key + Send + 'static,
+        action: impl FnMut() + Send + 'static,
+    ) {
+        self.keybind_with_info(mods, key, KeybindInfo::default(), action);
+    }
+
+    /// Set a keybind with a group and description, or one that triggers on release or repeats.
+    ///
+    /// This works like [`Input::keybind`], but the keybind is listed by [`Input::keybinds`]
+    /// with `info` so you can show what it does, like in a help overlay.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::input::{KeybindInfo, Mod};
+    ///
+    /// input.keybind_with_info(
+    ///     [Mod::Super],
+    ///     'q',
+    ///     KeybindInfo {
+    ///         group: Some("Window".into()),
+    ///         description: Some("Close the focused window".into()),
+    ///         ..Default::default()
+    ///     },
+    ///     || {
+    ///         if let Some(win) = window.get_focused() {
+    ///             win.close();
+    ///         }
+    ///     },
+    /// );
+    ///
+    /// // Keep raising the volume while the key is held
+    /// input.keybind_with_info(
+    ///     [],
+    ///     "XF86AudioRaiseVolume",
+    ///     KeybindInfo {
+    ///         repeat: true,
+    ///         ..Default::default()
+    ///     },
+    ///     || {
+    ///         process.spawn(["wpctl", "set-volume", "@DEFAULT_AUDIO_SINK@", "5%+"]);
+    ///     },
+    /// );
+    /// ```
+    pub fn keybind_with_info(
+        &self,
+        mods: impl IntoIterator<Item = Mod>,
+        key: impl Key + Send + 'static,
+        info: KeybindInfo,
         mut action: impl FnMut() + Send + 'static,
     ) {
         let mut client = self.create_input_client();
@@ -171,12 +393,21 @@ impl Input {
                             key: Some(input::v0alpha1::set_keybind_request::Key::RawCode(
                                 key.into_keysym().raw(),
                             )),
+                            group: info.group,
+                            description: info.description,
+                            on_release: Some(info.on_release),
+                            repeat: Some(info.repeat),
+                            priority: Some(info.priority),
                         })
                         .await
                         .unwrap()
                         .into_inner();
 
-                    while let Some(Ok(_response)) = stream.next().await {
+                    while let Some(response) = stream.next().await {
+                        if let Err(status) = response {
+                            eprintln!("ERROR: {status}");
+                            break;
+                        }
                         action();
                         tokio::task::yield_now().await;
                     }
@@ -186,6 +417,48 @@ impl Input {
             .unwrap();
     }
 
+    /// Get every keybind set by this config, in the order they were first set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// for bind in input.keybinds() {
+    ///     let group = bind.group.as_deref().unwrap_or("Other");
+    ///     let description = bind.description.as_deref().unwrap_or("");
+    ///     println!("[{group}] {:?} + {}: {description}", bind.mods, bind.xkb_name);
+    /// }
+    /// ```
+    pub fn keybinds(&self) -> Vec<Keybind> {
+        block_on_tokio(self.keybinds_async())
+    }
+
+    /// The async version of [`Input::keybinds`].
+    pub async fn keybinds_async(&self) -> Vec<Keybind> {
+        let mut client = self.create_input_client();
+
+        let response = client
+            .list_keybinds(ListKeybindsRequest {})
+            .await
+            .unwrap()
+            .into_inner();
+
+        response
+            .keybinds
+            .into_iter()
+            .map(|bind| Keybind {
+                mods: bind
+                    .modifiers
+                    .iter()
+                    .filter_map(|modifier| Mod::try_from(*modifier).ok())
+                    .collect(),
+                key: Keysym::new(bind.raw_code.unwrap_or_default()),
+                xkb_name: bind.xkb_name.unwrap_or_default(),
+                group: bind.group,
+                description: bind.description,
+            })
+            .collect()
+    }
+
     /// Set a mousebind.
     ///
     /// If called with an already set mousebind, it gets replaced.
@@ -225,7 +498,7 @@ impl Input {
                     let mut stream = client
                         .set_mousebind(SetMousebindRequest {
                             modifiers,
-                            button: Some(button as u32),
+                            button: Some(button.code()),
                             edge: Some(edge as i32),
                         })
                         .await
@@ -242,6 +515,129 @@ impl Input {
             .unwrap();
     }
 
+    /// Set a scrollbind.
+    ///
+    /// If called with an already set scrollbind, it gets replaced.
+    ///
+    /// The action runs once for every notch a scroll wheel turns, and every so often
+    /// while scrolling on a touchpad. While `mods` are held down, scrolling isn't sent
+    /// to windows.
+    ///
+    /// You must supply:
+    /// - `mods`: A list of [`Mod`]s. These must be held down for the scrollbind to trigger.
+    /// - `direction`: The [`ScrollDirection`] to scroll in.
+    /// - `action`: A closure that will be run when the scrollbind is triggered.
+    ///     - Currently, any captures must be both `Send` and `'static`. If you want to mutate
+    ///       something, consider using channels or [`Box::leak`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::input::{Mod, ScrollDirection};
+    ///
+    /// // Set `Super + scroll` to switch between tags
+    /// input.scrollbind([Mod::Super], ScrollDirection::Up, || {
+    ///     tag.switch_left(&output.get_focused().unwrap());
+    /// });
+    /// input.scrollbind([Mod::Super], ScrollDirection::Down, || {
+    ///     tag.switch_right(&output.get_focused().unwrap());
+    /// });
+    /// ```
+    pub fn scrollbind(
+        &self,
+        mods: impl IntoIterator<Item = Mod>,
+        direction: ScrollDirection,
+        mut action: impl FnMut() + 'static + Send,
+    ) {
+        let mut client = self.create_input_client();
+
+        let modifiers = mods.into_iter().map(|modif| modif as i32).collect();
+
+        self.fut_sender
+            .send(
+                async move {
+                    let mut stream = client
+                        .set_scrollbind(SetScrollbindRequest {
+                            modifiers,
+                            direction: Some(direction as i32),
+                        })
+                        .await
+                        .unwrap()
+                        .into_inner();
+
+                    while let Some(Ok(_response)) = stream.next().await {
+                        action();
+                        tokio::task::yield_now().await;
+                    }
+                }
+                .boxed(),
+            )
+            .unwrap();
+    }
+
+    /// Set a swipebind.
+    ///
+    /// If called with an already set swipebind, it gets replaced.
+    ///
+    /// The action runs once the fingers are lifted from the touchpad. While `mods` are
+    /// held down, swipes with as many fingers as any swipebind using them aren't sent
+    /// to windows.
+    ///
+    /// You must supply:
+    /// - `mods`: A list of [`Mod`]s. These must be held down for the swipebind to trigger.
+    /// - `fingers`: How many fingers the swipe is made with.
+    /// - `direction`: The [`SwipeDirection`] to swipe in.
+    /// - `action`: A closure that will be run when the swipebind is triggered.
+    ///     - Currently, any captures must be both `Send` and `'static`. If you want to mutate
+    ///       something, consider using channels or [`Box::leak`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::input::SwipeDirection;
+    ///
+    /// // Swipe left or right with three fingers to switch between tags
+    /// input.swipebind([], 3, SwipeDirection::Left, || {
+    ///     tag.switch_right(&output.get_focused().unwrap());
+    /// });
+    /// input.swipebind([], 3, SwipeDirection::Right, || {
+    ///     tag.switch_left(&output.get_focused().unwrap());
+    /// });
+    /// ```
+    pub fn swipebind(
+        &self,
+        mods: impl IntoIterator<Item = Mod>,
+        fingers: u32,
+        direction: SwipeDirection,
+        mut action: impl FnMut() + 'static + Send,
+    ) {
+        let mut client = self.create_input_client();
+
+        let modifiers = mods.into_iter().map(|modif| modif as i32).collect();
+
+        self.fut_sender
+            .send(
+                async move {
+                    let mut stream = client
+                        .set_swipebind(SetSwipebindRequest {
+                            modifiers,
+                            fingers: Some(fingers),
+                            direction: Some(direction as i32),
+                        })
+                        .await
+                        .unwrap()
+                        .into_inner();
+
+                    while let Some(Ok(_response)) = stream.next().await {
+                        action();
+                        tokio::task::yield_now().await;
+                    }
+                }
+                .boxed(),
+            )
+            .unwrap();
+    }
+
     /// Set the xkeyboard config.
     ///
     /// This allows you to set several xkeyboard options like `layout` and `rules`.
@@ -268,10 +664,109 @@ impl Input {
             layout: xkb_config.layout.map(String::from),
             model: xkb_config.model.map(String::from),
             options: xkb_config.options.map(String::from),
+            compose_key: xkb_config.compose_key.map(String::from),
+        }))
+        .unwrap();
+    }
+
+    /// Switch the keyboard to the layout at `index` in the xkeyboard config's layouts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::input::XkbConfig;
+    ///
+    /// input.set_xkb_config(XkbConfig {
+    ///     layout: Some("us,fr"),
+    ///     ..Default::default()
+    /// });
+    ///
+    /// // Switch to the French layout
+    /// input.switch_xkb_layout(1);
+    /// ```
+    pub fn switch_xkb_layout(&self, index: u32) {
+        let mut client = self.create_input_client();
+
+        block_on_tokio(client.switch_xkb_layout(SwitchXkbLayoutRequest {
+            target: Some(switch_xkb_layout_request::Target::Index(index)),
+        }))
+        .unwrap();
+    }
+
+    /// Switch the keyboard `offset` layouts forward, or backward if negative, wrapping around.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::input::Mod;
+    ///
+    /// // Cycle through layouts with `Super + space`
+    /// input.keybind([Mod::Super], ' ', || {
+    ///     input.switch_xkb_layout_relative(1);
+    /// });
+    /// ```
+    pub fn switch_xkb_layout_relative(&self, offset: i32) {
+        let mut client = self.create_input_client();
+
+        block_on_tokio(client.switch_xkb_layout(SwitchXkbLayoutRequest {
+            target: Some(switch_xkb_layout_request::Target::Offset(offset)),
         }))
         .unwrap();
     }
 
+    /// Switch the keyboard to the next layout, wrapping around.
+    pub fn next_xkb_layout(&self) {
+        self.switch_xkb_layout_relative(1);
+    }
+
+    /// Switch the keyboard to the previous layout, wrapping around.
+    pub fn prev_xkb_layout(&self) {
+        self.switch_xkb_layout_relative(-1);
+    }
+
+    /// Get the keyboard's layouts and which one is active.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let layouts = input.xkb_layouts();
+    /// println!("Active layout: {}", layouts.names[layouts.active as usize]);
+    /// ```
+    pub fn xkb_layouts(&self) -> XkbLayouts {
+        block_on_tokio(self.xkb_layouts_async())
+    }
+
+    /// The async version of [`Input::xkb_layouts`].
+    pub async fn xkb_layouts_async(&self) -> XkbLayouts {
+        let mut client = self.create_input_client();
+
+        let response = client
+            .get_xkb_layouts(GetXkbLayoutsRequest {})
+            .await
+            .unwrap()
+            .into_inner();
+
+        XkbLayouts {
+            names: response.names,
+            active: response.active_index(),
+        }
+    }
+
+    /// Set whether windows remember their keyboard layout.
+    ///
+    /// When enabled, a window remembers the layout that was active while it was focused
+    /// and the keyboard switches back to it when the window is focused again.
+    pub fn set_per_window_xkb_layout(&self, enabled: bool) {
+        let mut client = self.create_input_client();
+
+        block_on_tokio(
+            client.set_per_window_xkb_layout(SetPerWindowXkbLayoutRequest {
+                enabled: Some(enabled),
+            }),
+        )
+        .unwrap();
+    }
+
     /// Set the keyboard's repeat rate.
     ///
     /// This allows you to set the time between holding down a key and it repeating
@@ -296,6 +791,292 @@ impl Input {
         .unwrap();
     }
 
+    /// Turn num lock on or off.
+    ///
+    /// Put this in your config to start with num lock on.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// input.set_num_lock(true);
+    /// ```
+    pub fn set_num_lock(&self, on: bool) {
+        self.set_lock_keys(Some(on), None);
+    }
+
+    /// Turn caps lock on or off.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// input.set_caps_lock(false);
+    /// ```
+    pub fn set_caps_lock(&self, on: bool) {
+        self.set_lock_keys(None, Some(on));
+    }
+
+    fn set_lock_keys(&self, num_lock: Option<bool>, caps_lock: Option<bool>) {
+        let mut client = self.create_input_client();
+
+        block_on_tokio(client.set_lock_keys(SetLockKeysRequest {
+            num_lock,
+            caps_lock,
+        }))
+        .unwrap();
+    }
+
+    /// Enable or disable sticky keys.
+    ///
+    /// With sticky keys, tapping a modifier key makes it apply to the next key, so shortcuts
+    /// can be typed one key at a time. Tapping it twice locks it until it's tapped again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// input.set_sticky_keys(true);
+    /// ```
+    pub fn set_sticky_keys(&self, enabled: bool) {
+        let mut client = self.create_input_client();
+
+        block_on_tokio(client.set_sticky_keys(SetStickyKeysRequest {
+            enabled: Some(enabled),
+        }))
+        .unwrap();
+    }
+
+    /// Enable slow keys with `delay`, or disable it with `None`.
+    ///
+    /// With slow keys, keys have to be held down for `delay` before their press goes through,
+    /// so keys brushed by accident are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// input.set_slow_keys(Some(Duration::from_millis(300)));
+    /// ```
+    pub fn set_slow_keys(&self, delay: Option<Duration>) {
+        let mut client = self.create_input_client();
+
+        block_on_tokio(client.set_slow_keys(SetSlowKeysRequest {
+            delay_ms: delay.map(|delay| delay.as_millis() as u32),
+        }))
+        .unwrap();
+    }
+
+    /// Enable or disable mouse keys.
+    ///
+    /// With mouse keys, the numpad controls the pointer instead of typing. The keys around 5
+    /// move the pointer, speeding up the longer they're held. 5 clicks, + double clicks,
+    /// 0 holds the button down and . lets it go. /, * and - pick the left, middle and
+    /// right button.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::input::Mod;
+    ///
+    /// // Toggle mouse keys with `Super + m`
+    /// let mut enabled = false;
+    /// input.keybind([Mod::Super], 'm', move || {
+    ///     enabled = !enabled;
+    ///     input.set_mouse_keys(enabled);
+    /// });
+    /// ```
+    pub fn set_mouse_keys(&self, enabled: bool) {
+        let mut client = self.create_input_client();
+
+        block_on_tokio(client.set_mouse_keys(SetMouseKeysRequest {
+            enabled: Some(enabled),
+        }))
+        .unwrap();
+    }
+
+    /// Remap a key to another on every keyboard.
+    ///
+    /// The key is turned into the other before keybinds and clients see it.
+    /// Remapping a key again overrides the earlier remap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::input::RemapKey;
+    /// use pinnacle_api::xkbcommon::xkb::Keysym;
+    ///
+    /// // Make caps lock another left control
+    /// input.remap_key(
+    ///     RemapKey::Keysym(Keysym::Caps_Lock),
+    ///     RemapKey::Keysym(Keysym::Control_L),
+    /// );
+    ///
+    /// // Swap keycodes 1 and 58, escape and caps lock
+    /// input.remap_key(RemapKey::Keycode(1), RemapKey::Keycode(58));
+    /// input.remap_key(RemapKey::Keycode(58), RemapKey::Keycode(1));
+    /// ```
+    pub fn remap_key(&self, from: RemapKey, to: RemapKey) {
+        self.remap_key_inner(from, to, None);
+    }
+
+    /// Remap a key to another on keyboards matching `filter`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::input::{libinput::DeviceFilter, RemapKey};
+    ///
+    /// // Swap escape and caps lock only on the laptop's keyboard
+    /// let laptop = DeviceFilter {
+    ///     name: Some("AT Translated Set 2 keyboard".into()),
+    ///     ..Default::default()
+    /// };
+    /// input.remap_key_for(laptop.clone(), RemapKey::Keycode(1), RemapKey::Keycode(58));
+    /// input.remap_key_for(laptop, RemapKey::Keycode(58), RemapKey::Keycode(1));
+    /// ```
+    pub fn remap_key_for(&self, filter: DeviceFilter, from: RemapKey, to: RemapKey) {
+        self.remap_key_inner(from, to, Some(filter));
+    }
+
+    fn remap_key_inner(&self, from: RemapKey, to: RemapKey, filter: Option<DeviceFilter>) {
+        let mut client = self.create_input_client();
+
+        let to_api = |key: RemapKey| {
+            let key = match key {
+                RemapKey::Keycode(code) => input::v0alpha1::remap_key::Key::Keycode(code),
+                RemapKey::Keysym(keysym) => input::v0alpha1::remap_key::Key::Keysym(keysym.raw()),
+            };
+            input::v0alpha1::RemapKey { key: Some(key) }
+        };
+
+        block_on_tokio(client.set_key_remap(SetKeyRemapRequest {
+            from: Some(to_api(from)),
+            to: Some(to_api(to)),
+            filter: filter.map(device_filter_to_api),
+        }))
+        .unwrap();
+    }
+
+    /// Move the pointer to `x` and `y` in the global space.
+    ///
+    /// The pointer is kept inside outputs, and the output it moves to gets focused.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// input.warp_pointer(960.0, 540.0);
+    /// ```
+    pub fn warp_pointer(&self, x: f64, y: f64) {
+        self.warp_pointer_to(warp_pointer_request::Target::Location(
+            warp_pointer_request::Location {
+                x: Some(x),
+                y: Some(y),
+            },
+        ));
+    }
+
+    /// Move the pointer to the center of `window`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// if let Some(win) = window.get_focused() {
+    ///     input.warp_pointer_to_window(&win);
+    /// }
+    /// ```
+    pub fn warp_pointer_to_window(&self, window: &WindowHandle) {
+        self.warp_pointer_to(warp_pointer_request::Target::WindowId(window.id));
+    }
+
+    /// Move the pointer to the center of `output`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// if let Some(op) = output.get_by_name("HDMI-1") {
+    ///     input.warp_pointer_to_output(&op);
+    /// }
+    /// ```
+    pub fn warp_pointer_to_output(&self, output: &OutputHandle) {
+        self.warp_pointer_to(warp_pointer_request::Target::OutputName(
+            output.name.clone(),
+        ));
+    }
+
+    fn warp_pointer_to(&self, target: warp_pointer_request::Target) {
+        let mut client = self.create_input_client();
+
+        block_on_tokio(client.warp_pointer(WarpPointerRequest {
+            target: Some(target),
+        }))
+        .unwrap();
+    }
+
+    /// Set whether the pointer moves to the center of an output when it gets focused.
+    ///
+    /// This only happens when focus moves to an output without the pointer, like from a keybind.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// input.set_warp_pointer_on_output_focus(true);
+    /// ```
+    pub fn set_warp_pointer_on_output_focus(&self, enabled: bool) {
+        let mut client = self.create_input_client();
+
+        block_on_tokio(client.set_warp_pointer_on_output_focus(
+            SetWarpPointerOnOutputFocusRequest {
+                enabled: Some(enabled),
+            },
+        ))
+        .unwrap();
+    }
+
+    /// Change the cursor theme and size.
+    ///
+    /// `None` keeps the current theme or size. The theme defaults to `XCURSOR_THEME` and
+    /// `XCURSOR_SIZE` from the environment Pinnacle was started in, and reloading the config
+    /// goes back to it. Clients spawned after this get the new theme through those variables,
+    /// but already running clients may need to be restarted to use it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// input.set_cursor_theme(Some("Adwaita"), Some(32));
+    ///
+    /// // Only make the cursor bigger
+    /// input.set_cursor_theme(None, Some(48));
+    /// ```
+    pub fn set_cursor_theme(&self, theme: Option<&str>, size: Option<u32>) {
+        let mut client = self.create_input_client();
+
+        block_on_tokio(client.set_cursor_theme(SetCursorThemeRequest {
+            theme: theme.map(ToString::to_string),
+            size,
+        }))
+        .unwrap();
+    }
+
+    /// Set what happens when a laptop's lid is closed.
+    ///
+    /// The default is [`LidSwitchPolicy::Clamshell`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::input::LidSwitchPolicy;
+    ///
+    /// // Keep using the laptop's screen with the lid closed
+    /// input.set_lid_switch_policy(LidSwitchPolicy::Ignore);
+    /// ```
+    pub fn set_lid_switch_policy(&self, policy: LidSwitchPolicy) {
+        let mut client = self.create_input_client();
+
+        block_on_tokio(client.set_lid_switch_policy(SetLidSwitchPolicyRequest {
+            policy: Some(policy as i32),
+        }))
+        .unwrap();
+    }
+
     /// Set a libinput setting.
     ///
     /// From [freedesktop.org](https://www.freedesktop.org/wiki/Software/libinput/):
@@ -306,8 +1087,8 @@ impl Input {
     ///
     /// See [`LibinputSetting`] for all the settings you can change.
     ///
-    /// Note: currently Pinnacle applies anything set here to *every* device, regardless of what it
-    /// actually is. This will be fixed in the future.
+    /// This applies the setting to every device. Use [`Input::set_libinput_setting_for`]
+    /// to only apply it to some devices.
     ///
     /// # Examples
     ///
@@ -321,6 +1102,35 @@ impl Input {
     /// input.set_libinput_setting(LibinputSetting::NaturalScroll(true));
     /// ```
     pub fn set_libinput_setting(&self, setting: LibinputSetting) {
+        self.set_libinput_setting_inner(setting, None);
+    }
+
+    /// Set a libinput setting for devices matching `filter`.
+    ///
+    /// Settings are applied in the order they were set, including to devices that connect
+    /// later, so set a setting for specific devices after setting it for every device
+    /// to override it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::input::libinput::*;
+    ///
+    /// // Flat acceleration for every mouse, but natural scrolling only on touchpads
+    /// input.set_libinput_setting(LibinputSetting::AccelProfile(AccelProfile::Flat));
+    /// input.set_libinput_setting_for(
+    ///     DeviceFilter {
+    ///         device_type: Some(DeviceType::Touchpad),
+    ///         ..Default::default()
+    ///     },
+    ///     LibinputSetting::NaturalScroll(true),
+    /// );
+    /// ```
+    pub fn set_libinput_setting_for(&self, filter: DeviceFilter, setting: LibinputSetting) {
+        self.set_libinput_setting_inner(setting, Some(filter));
+    }
+
+    fn set_libinput_setting_inner(&self, setting: LibinputSetting, filter: Option<DeviceFilter>) {
         let mut client = self.create_input_client();
 
         let setting = match setting {
@@ -336,7 +1146,7 @@ impl Input {
             LibinputSetting::LeftHanded(enable) => Setting::LeftHanded(enable),
             LibinputSetting::MiddleEmulation(enable) => Setting::MiddleEmulation(enable),
             LibinputSetting::RotationAngle(angle) => Setting::RotationAngle(angle),
-            LibinputSetting::ScrollButton(button) => Setting::RotationAngle(button),
+            LibinputSetting::ScrollButton(button) => Setting::ScrollButton(button),
             LibinputSetting::ScrollButtonLock(enable) => Setting::ScrollButtonLock(enable),
             LibinputSetting::ScrollMethod(method) => Setting::ScrollMethod(method as i32),
             LibinputSetting::NaturalScroll(enable) => Setting::NaturalScroll(enable),
@@ -348,9 +1158,261 @@ impl Input {
 
         block_on_tokio(client.set_libinput_setting(SetLibinputSettingRequest {
             setting: Some(setting),
+            filter: filter.map(device_filter_to_api),
+        }))
+        .unwrap();
+    }
+
+    /// Get the connected libinput devices matching `filter`.
+    ///
+    /// This is useful for checking which devices a filter will apply settings to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::input::libinput::*;
+    ///
+    /// for device in input.devices(DeviceFilter::default()) {
+    ///     println!("{} ({:04x}:{:04x})", device.name, device.vendor_id, device.product_id);
+    /// }
+    /// ```
+    pub fn devices(&self, filter: DeviceFilter) -> Vec<DeviceInfo> {
+        block_on_tokio(self.devices_async(filter))
+    }
+
+    /// The async version of [`Input::devices`].
+    pub async fn devices_async(&self, filter: DeviceFilter) -> Vec<DeviceInfo> {
+        let mut client = self.create_input_client();
+
+        let response = client
+            .get_devices(GetDevicesRequest {
+                filter: Some(device_filter_to_api(filter)),
+            })
+            .await
+            .unwrap()
+            .into_inner();
+
+        response
+            .devices
+            .into_iter()
+            .map(|device| DeviceInfo {
+                name: device.name.unwrap_or_default(),
+                vendor_id: device.vendor_id.unwrap_or_default(),
+                product_id: device.product_id.unwrap_or_default(),
+                device_type: device
+                    .device_type
+                    .and_then(|device_type| DeviceType::try_from(device_type).ok()),
+            })
+            .collect()
+    }
+
+    /// Map every touchscreen to `output` so touches land on it.
+    ///
+    /// Touchscreens cover the first output until they're mapped. Touches follow the output's
+    /// transform, so rotated panels work without extra setup. A touchscreen that isn't quite
+    /// aligned with its panel can be corrected with [`LibinputSetting::CalibrationMatrix`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// if let Some(op) = output.get_by_name("eDP-1") {
+    ///     input.map_touch_to_output(&op);
+    /// }
+    /// ```
+    pub fn map_touch_to_output(&self, output: &OutputHandle) {
+        self.map_touch_to_output_inner(output, None);
+    }
+
+    /// Map touchscreens matching `filter` to `output` so touches land on it.
+    ///
+    /// Mapping a touchscreen again overrides the earlier mapping.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::input::libinput::{DeviceFilter, DeviceType};
+    ///
+    /// // Touches on the external touch monitor land on it instead of the laptop's screen
+    /// let touchscreen = DeviceFilter {
+    ///     name: Some("ELAN Touchscreen".into()),
+    ///     device_type: Some(DeviceType::Touchscreen),
+    ///     ..Default::default()
+    /// };
+    /// if let Some(op) = output.get_by_name("DP-2") {
+    ///     input.map_touch_to_output_for(touchscreen, &op);
+    /// }
+    /// ```
+    pub fn map_touch_to_output_for(&self, filter: DeviceFilter, output: &OutputHandle) {
+        self.map_touch_to_output_inner(output, Some(filter));
+    }
+
+    fn map_touch_to_output_inner(&self, output: &OutputHandle, filter: Option<DeviceFilter>) {
+        let mut client = self.create_input_client();
+
+        block_on_tokio(client.set_touch_output(SetTouchOutputRequest {
+            filter: filter.map(device_filter_to_api),
+            output_name: Some(output.name.clone()),
+        }))
+        .unwrap();
+    }
+
+    /// Multiply scrolling on every device by `factor`.
+    ///
+    /// This applies to both scroll wheels and touchpads. For button scrolling, like scrolling
+    /// by holding a trackpoint's middle button, use [`LibinputSetting::ScrollMethod`] with
+    /// [`LibinputSetting::ScrollButton`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Scroll half as fast
+    /// input.set_scroll_factor(0.5);
+    /// ```
+    pub fn set_scroll_factor(&self, factor: f64) {
+        self.set_scroll_factor_inner(factor, None);
+    }
+
+    /// Multiply scrolling on devices matching `filter` by `factor`.
+    ///
+    /// Setting a factor again for the same device overrides the earlier one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::input::libinput::{DeviceFilter, DeviceType};
+    ///
+    /// // Scroll faster on touchpads only
+    /// input.set_scroll_factor_for(
+    ///     DeviceFilter {
+    ///         device_type: Some(DeviceType::Touchpad),
+    ///         ..Default::default()
+    ///     },
+    ///     1.5,
+    /// );
+    /// ```
+    pub fn set_scroll_factor_for(&self, filter: DeviceFilter, factor: f64) {
+        self.set_scroll_factor_inner(factor, Some(filter));
+    }
+
+    fn set_scroll_factor_inner(&self, factor: f64, filter: Option<DeviceFilter>) {
+        let mut client = self.create_input_client();
+
+        block_on_tokio(client.set_scroll_factor(SetScrollFactorRequest {
+            filter: filter.map(device_filter_to_api),
+            factor: Some(factor),
+        }))
+        .unwrap();
+    }
+
+    /// Set a tablet setting for every tablet.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::input::TabletSetting;
+    ///
+    /// if let Some(op) = output.get_by_name("DP-1") {
+    ///     input.set_tablet_setting(TabletSetting::MapToOutput(op));
+    /// }
+    ///
+    /// // Make the pen feel a bit softer
+    /// input.set_tablet_setting(TabletSetting::PressureCurve {
+    ///     control_1: (0.0, 0.2),
+    ///     control_2: (0.8, 1.0),
+    /// });
+    /// ```
+    pub fn set_tablet_setting(&self, setting: TabletSetting) {
+        self.set_tablet_setting_inner(setting, None);
+    }
+
+    /// Set a tablet setting for tablets matching `filter`.
+    ///
+    /// Setting a setting again for the same tablet overrides the earlier one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::input::{libinput::DeviceFilter, TabletSetting};
+    /// use pinnacle_api::util::Geometry;
+    ///
+    /// // Map the small tablet to the top-left quarter of a 4K output
+    /// let small_tablet = DeviceFilter {
+    ///     name: Some("Wacom Intuos S*".into()),
+    ///     ..Default::default()
+    /// };
+    /// input.set_tablet_setting_for(
+    ///     small_tablet,
+    ///     TabletSetting::MapToRegion(Geometry {
+    ///         x: 0,
+    ///         y: 0,
+    ///         width: 1920,
+    ///         height: 1080,
+    ///     }),
+    /// );
+    /// ```
+    pub fn set_tablet_setting_for(&self, filter: DeviceFilter, setting: TabletSetting) {
+        self.set_tablet_setting_inner(setting, Some(filter));
+    }
+
+    fn set_tablet_setting_inner(&self, setting: TabletSetting, filter: Option<DeviceFilter>) {
+        let mut client = self.create_input_client();
+
+        let setting = match setting {
+            TabletSetting::MapToOutput(output) => {
+                set_tablet_setting_request::Setting::OutputName(output.name)
+            }
+            TabletSetting::MapToRegion(region) => {
+                set_tablet_setting_request::Setting::Region(geometry_to_api(region))
+            }
+            TabletSetting::PressureCurve {
+                control_1,
+                control_2,
+            } => set_tablet_setting_request::Setting::PressureCurve(
+                set_tablet_setting_request::PressureCurve {
+                    x1: Some(control_1.0),
+                    y1: Some(control_1.1),
+                    x2: Some(control_2.0),
+                    y2: Some(control_2.1),
+                },
+            ),
+            TabletSetting::RemapButton { from, to } => {
+                set_tablet_setting_request::Setting::ButtonRemap(
+                    set_tablet_setting_request::ButtonRemap {
+                        from: Some(from),
+                        to: Some(to),
+                    },
+                )
+            }
+        };
+
+        block_on_tokio(client.set_tablet_setting(SetTabletSettingRequest {
+            filter: filter.map(device_filter_to_api),
+            setting: Some(setting),
         }))
         .unwrap();
     }
+
+    /// Connect to an input signal.
+    ///
+    /// The compositor will fire off signals that your config can listen for and act upon.
+    /// You can pass in an [`InputSignal`] along with a callback and it will get run
+    /// with the necessary arguments every time a signal of that type is received.
+    pub fn connect_signal(&self, signal: InputSignal) -> SignalHandle {
+        let mut signal_state = block_on_tokio(self.api.get().unwrap().signal.write());
+
+        match signal {
+            InputSignal::XkbLayout(f) => signal_state.input_xkb_layout.add_callback(f),
+        }
+    }
+}
+
+fn device_filter_to_api(filter: DeviceFilter) -> input::v0alpha1::DeviceFilter {
+    input::v0alpha1::DeviceFilter {
+        name: filter.name,
+        vendor_id: filter.vendor_id,
+        product_id: filter.product_id,
+        device_type: filter.device_type.map(|device_type| device_type as i32),
+    }
 }
 
 /// A trait that designates anything that can be converted into a [`Keysym`].
@@ -4,6 +4,8 @@
 
 //! Types for libinput configuration.
 
+use num_enum::TryFromPrimitive;
+
 /// Pointer acceleration profile
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum AccelProfile {
@@ -87,3 +89,71 @@ pub enum LibinputSetting {
     /// Enable or disable tap-to-click
     Tap(bool),
 }
+
+/// What kind of device an input device is.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, TryFromPrimitive)]
+pub enum DeviceType {
+    /// A touchpad
+    Touchpad = 1,
+    /// A pointing stick, like a ThinkPad TrackPoint
+    Trackpoint,
+    /// A mouse or other pointer device that isn't one of the others
+    Mouse,
+    /// A drawing tablet
+    Tablet,
+    /// A touchscreen
+    Touchscreen,
+    /// A keyboard
+    Keyboard,
+}
+
+/// Which input devices a libinput setting applies to.
+///
+/// A device matches if it matches every field that's set, so the default filter
+/// matches every device.
+///
+/// # Examples
+///
+/// ```
+/// use pinnacle_api::input::libinput::*;
+///
+/// // Every touchpad
+/// let touchpads = DeviceFilter {
+///     device_type: Some(DeviceType::Touchpad),
+///     ..Default::default()
+/// };
+///
+/// // Logitech mice by name
+/// let logitech = DeviceFilter {
+///     name: Some("Logitech*".into()),
+///     device_type: Some(DeviceType::Mouse),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct DeviceFilter {
+    /// A glob matched against the device's name.
+    ///
+    /// `*` matches any number of characters and `?` matches exactly one.
+    pub name: Option<String>,
+    /// The device's USB vendor id
+    pub vendor_id: Option<u32>,
+    /// The device's USB product id
+    pub product_id: Option<u32>,
+    /// What kind of device it is
+    pub device_type: Option<DeviceType>,
+}
+
+/// A connected libinput device.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DeviceInfo {
+    /// The device's name
+    pub name: String,
+    /// The device's USB vendor id
+    pub vendor_id: u32,
+    /// The device's USB product id
+    pub product_id: u32,
+    /// What kind of device it is, or `None` if it isn't one of the known kinds
+    pub device_type: Option<DeviceType>,
+}
@@ -12,10 +12,13 @@ use std::{
 };
 
 use futures::{future::BoxFuture, FutureExt};
-use pinnacle_api_defs::pinnacle::layout::v0alpha1::{
-    layout_request::{Body, ExplicitLayout, Geometries},
-    layout_service_client::LayoutServiceClient,
-    LayoutRequest,
+use pinnacle_api_defs::pinnacle::{
+    layout::v0alpha1::{
+        layout_request::{Body, ExplicitLayout, Geometries},
+        layout_service_client::LayoutServiceClient,
+        LayoutRequest, SetFallbackLayoutRequest, SetMonocleIndicatorRequest,
+    },
+    v0alpha1::SetOrToggle,
 };
 use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
 use tokio_stream::StreamExt;
@@ -97,8 +100,24 @@ impl Layout {
                         .collect(),
                     output_width: response.output_width.unwrap_or_default(),
                     output_height: response.output_height.unwrap_or_default(),
+                    layout: response.layout.clone(),
+                    resize: response.resize.map(|resize| TileResize {
+                        window: api.window.new_handle(resize.window_id()),
+                        width_delta: resize.width_delta(),
+                        height_delta: resize.height_delta(),
+                    }),
+                };
+                let (geos, layout_name) = {
+                    let mut manager = manager.lock().unwrap();
+                    if let Some(resize) = args.resize.as_ref() {
+                        manager.resize_tile(&args, resize);
+                    }
+                    let generator = manager.active_layout(&args);
+                    (
+                        generator.layout(&args),
+                        generator.name().map(ToString::to_string),
+                    )
                 };
-                let geos = manager.lock().unwrap().active_layout(&args).layout(&args);
                 from_client
                     .send(LayoutRequest {
                         body: Some(Body::Geometries(Geometries {
@@ -113,6 +132,7 @@ impl Layout {
                                     height: Some(geo.height as i32),
                                 })
                                 .collect(),
+                            layout_name,
                         })),
                     })
                     .unwrap();
@@ -123,6 +143,74 @@ impl Layout {
         self.fut_sender.send(thing).unwrap();
         requester
     }
+
+    /// Set the layout the compositor uses when no layout manager is connected,
+    /// such as before the config starts or after it crashes.
+    ///
+    /// The default is [`FallbackLayout::MasterStack`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::layout::FallbackLayout;
+    ///
+    /// layout.set_fallback_layout(FallbackLayout::Grid);
+    /// ```
+    pub fn set_fallback_layout(&self, fallback_layout: FallbackLayout) {
+        let mut client = self.layout_client.clone();
+        block_on_tokio(client.set_fallback_layout(SetFallbackLayoutRequest {
+            layout: Some(fallback_layout as i32),
+        }))
+        .unwrap();
+    }
+
+    /// Show or hide the monocle indicator.
+    ///
+    /// While it is shown, outputs laid out with a [`MonocleLayout`] show which window
+    /// of how many is shown, like "2/5", at the top of the output.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// layout.set_monocle_indicator(true);
+    /// ```
+    pub fn set_monocle_indicator(&self, show: bool) {
+        let mut client = self.layout_client.clone();
+        block_on_tokio(client.set_monocle_indicator(SetMonocleIndicatorRequest {
+            set_or_toggle: Some(match show {
+                true => SetOrToggle::Set,
+                false => SetOrToggle::Unset,
+            } as i32),
+        }))
+        .unwrap();
+    }
+
+    /// Toggle the monocle indicator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// layout.toggle_monocle_indicator();
+    /// ```
+    pub fn toggle_monocle_indicator(&self) {
+        let mut client = self.layout_client.clone();
+        block_on_tokio(client.set_monocle_indicator(SetMonocleIndicatorRequest {
+            set_or_toggle: Some(SetOrToggle::Toggle as i32),
+        }))
+        .unwrap();
+    }
+}
+
+/// A layout the compositor uses when no layout manager is connected.
+#[derive(num_enum::TryFromPrimitive, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(i32)]
+pub enum FallbackLayout {
+    /// One master window on the left and the rest stacked on the right.
+    MasterStack = 1,
+    /// Each window takes half of the space left by the previous one.
+    Dwindle,
+    /// Windows are arranged in a grid of equally sized cells.
+    Grid,
 }
 
 /// Arguments that [`LayoutGenerator`]s receive when a layout is requested.
@@ -138,18 +226,58 @@ pub struct LayoutArgs {
     pub output_width: u32,
     /// The height of the layout area, in pixels.
     pub output_height: u32,
+    /// The name of the layout set on the first focused tag, if any.
+    ///
+    /// See [`TagHandle::set_layout`].
+    pub layout: Option<String>,
+    /// The tiled window the user wants resized, if this layout was requested to resize one.
+    pub resize: Option<TileResize>,
+}
+
+/// A request to resize a tiled window.
+///
+/// These are sent when tiled windows are resized with the mouse
+/// or with [`WindowHandle::resize_tile`].
+#[derive(Clone, Debug)]
+pub struct TileResize {
+    /// The window to resize.
+    pub window: WindowHandle,
+    /// How much wider the window should get, in pixels. Negative values shrink it.
+    pub width_delta: i32,
+    /// How much taller the window should get, in pixels. Negative values shrink it.
+    pub height_delta: i32,
 }
 
 /// Types that can manage layouts.
 pub trait LayoutManager {
     /// Get the currently active layout for layouting.
     fn active_layout(&mut self, args: &LayoutArgs) -> &dyn LayoutGenerator;
+
+    /// Resize a tiled window by adjusting the active layout.
+    ///
+    /// This is called before [`LayoutManager::active_layout`] when a layout was requested
+    /// to resize a window. The default implementation does nothing.
+    fn resize_tile(&mut self, _args: &LayoutArgs, _resize: &TileResize) {}
 }
 
 /// Types that can generate layouts by computing a vector of [geometries][Geometry].
 pub trait LayoutGenerator {
     /// Generate a vector of [geometries][Geometry] using the given [`LayoutArgs`].
     fn layout(&self, args: &LayoutArgs) -> Vec<Geometry>;
+
+    /// The name of this layout, used to remember it per tag.
+    ///
+    /// Layouts without a name aren't remembered.
+    fn name(&self) -> Option<&str> {
+        None
+    }
+
+    /// Adjust this layout's split ratios so the window in `resize` grows or shrinks
+    /// by about the requested amount.
+    ///
+    /// The new ratios should be kept for later layouts. The default implementation
+    /// does nothing.
+    fn resize_tile(&mut self, _args: &LayoutArgs, _resize: &TileResize) {}
 }
 
 /// Gaps between windows.
@@ -205,6 +333,12 @@ impl CyclingLayoutManager {
         }
     }
 
+    /// Get the name of the layout the given tag uses.
+    pub fn layout_name(&self, tag: &TagHandle) -> Option<&str> {
+        let index = self.tag_indices.get(&tag.id).copied().unwrap_or_default();
+        self.layouts.get(index)?.name()
+    }
+
     /// Cycle the layout forward on the given tag.
     pub fn cycle_layout_forward(&mut self, tag: &TagHandle) {
         let index = self.tag_indices.entry(tag.id).or_default();
@@ -225,17 +359,44 @@ impl CyclingLayoutManager {
     }
 }
 
+impl CyclingLayoutManager {
+    /// Get the index of the active layout, or `None` if there are no focused tags.
+    fn active_index(&mut self, args: &LayoutArgs) -> Option<usize> {
+        let first_tag = args.tags.first()?;
+
+        // Restore the layout remembered on the tag
+        if let Some(index) = args.layout.as_deref().and_then(|layout| {
+            self.layouts
+                .iter()
+                .position(|generator| generator.name() == Some(layout))
+        }) {
+            self.tag_indices.insert(first_tag.id, index);
+        }
+
+        Some(*self.tag_indices.entry(first_tag.id).or_default())
+    }
+}
+
 impl LayoutManager for CyclingLayoutManager {
     fn active_layout(&mut self, args: &LayoutArgs) -> &dyn LayoutGenerator {
-        let Some(first_tag) = args.tags.first() else {
+        let Some(index) = self.active_index(args) else {
             return &NoopLayout;
         };
 
         self.layouts
-            .get(*self.tag_indices.entry(first_tag.id).or_default())
+            .get(index)
             .expect("no layouts in manager")
             .as_ref()
     }
+
+    fn resize_tile(&mut self, args: &LayoutArgs, resize: &TileResize) {
+        if let Some(generator) = self
+            .active_index(args)
+            .and_then(|index| self.layouts.get_mut(index))
+        {
+            generator.resize_tile(args, resize);
+        }
+    }
 }
 
 /// A struct that can request layouts and provides access to a consumed [`LayoutManager`].
@@ -285,15 +446,32 @@ impl<T> LayoutRequester<T> {
 
 impl LayoutRequester<CyclingLayoutManager> {
     /// Cycle the layout forward for the given tag.
+    ///
+    /// The new layout is remembered on the tag so it's restored when switching back to it.
     pub fn cycle_layout_forward(&self, tag: &TagHandle) {
-        let mut lock = self.manager.lock().unwrap();
-        lock.cycle_layout_forward(tag);
+        let name = {
+            let mut lock = self.manager.lock().unwrap();
+            lock.cycle_layout_forward(tag);
+            lock.layout_name(tag).map(str::to_string)
+        };
+        // The lock is released first because the compositor requests a layout in response
+        if let Some(name) = name {
+            tag.set_layout(Some(&name));
+        }
     }
 
     /// Cycle the layout backward for the given tag.
+    ///
+    /// The new layout is remembered on the tag so it's restored when switching back to it.
     pub fn cycle_layout_backward(&mut self, tag: &TagHandle) {
-        let mut lock = self.manager.lock().unwrap();
-        lock.cycle_layout_backward(tag);
+        let name = {
+            let mut lock = self.manager.lock().unwrap();
+            lock.cycle_layout_backward(tag);
+            lock.layout_name(tag).map(str::to_string)
+        };
+        if let Some(name) = name {
+            tag.set_layout(Some(&name));
+        }
     }
 }
 
@@ -512,6 +690,37 @@ impl LayoutGenerator for MasterStackLayout {
 
         geos
     }
+
+    fn name(&self) -> Option<&str> {
+        Some("master_stack")
+    }
+
+    fn resize_tile(&mut self, args: &LayoutArgs, resize: &TileResize) {
+        let Some(index) = args.windows.iter().position(|win| win == &resize.window) else {
+            return;
+        };
+
+        if args.windows.len() as u32 <= self.master_count {
+            return;
+        }
+
+        let (delta, len) = match self.master_side {
+            MasterSide::Left | MasterSide::Right => (resize.width_delta, args.output_width),
+            MasterSide::Top | MasterSide::Bottom => (resize.height_delta, args.output_height),
+        };
+
+        if len == 0 {
+            return;
+        }
+
+        // Growing a stack window shrinks the master area
+        let mut delta = delta as f32 / len as f32;
+        if index as u32 >= self.master_count {
+            delta = -delta;
+        }
+
+        self.master_factor = (self.master_factor.clamp(0.1, 0.9) + delta).clamp(0.1, 0.9);
+    }
 }
 
 /// A [`LayoutGenerator`] that lays out windows in a shrinking fashion
@@ -624,6 +833,14 @@ impl LayoutGenerator for DwindleLayout {
 
         geos
     }
+
+    fn name(&self) -> Option<&str> {
+        Some("dwindle")
+    }
+
+    fn resize_tile(&mut self, args: &LayoutArgs, resize: &TileResize) {
+        resize_split_factors(&mut self.split_factors, args, resize, |_| true);
+    }
 }
 
 /// A [`LayoutGenerator`] that lays out windows in a spiral.
@@ -746,6 +963,86 @@ impl LayoutGenerator for SpiralLayout {
 
         geos
     }
+
+    fn name(&self) -> Option<&str> {
+        Some("spiral")
+    }
+
+    fn resize_tile(&mut self, args: &LayoutArgs, resize: &TileResize) {
+        resize_split_factors(&mut self.split_factors, args, resize, |split| {
+            matches!(split % 4, 1 | 2)
+        });
+    }
+}
+
+/// Adjust the split factors of a [`DwindleLayout`] or [`SpiralLayout`] so the resized
+/// window changes size.
+///
+/// Each split gives one window its first or second part, as reported by `window_gets_first`,
+/// and leaves the other part for the remaining windows. Odd splits divide the width
+/// and even splits divide the height.
+fn resize_split_factors(
+    split_factors: &mut HashMap<usize, f32>,
+    args: &LayoutArgs,
+    resize: &TileResize,
+    window_gets_first: impl Fn(usize) -> bool,
+) {
+    let count = args.windows.len();
+    let Some(index) = args.windows.iter().position(|win| win == &resize.window) else {
+        return;
+    };
+
+    if count < 2 {
+        return;
+    }
+
+    let factor = |split_factors: &HashMap<usize, f32>, split: usize| {
+        split_factors
+            .get(&split)
+            .copied()
+            .unwrap_or(0.5)
+            .clamp(0.1, 0.9)
+    };
+
+    // The last window gets what's left of the last split
+    let is_last = index == count - 1;
+    let split = if is_last { count - 1 } else { index + 1 };
+
+    // Find the size of the area the split divides
+    let mut width = args.output_width as f32;
+    let mut height = args.output_height as f32;
+    for prev_split in 1..split {
+        let factor = factor(split_factors, prev_split);
+        let rest = if window_gets_first(prev_split) {
+            1.0 - factor
+        } else {
+            factor
+        };
+        if prev_split % 2 == 1 {
+            width *= rest;
+        } else {
+            height *= rest;
+        }
+    }
+
+    let (delta, len) = if split % 2 == 1 {
+        (resize.width_delta, width)
+    } else {
+        (resize.height_delta, height)
+    };
+
+    if len < 1.0 {
+        return;
+    }
+
+    let in_first_part = window_gets_first(split) != is_last;
+    let mut delta = delta as f32 / len;
+    if !in_first_part {
+        delta = -delta;
+    }
+
+    let new_factor = (factor(split_factors, split) + delta).clamp(0.1, 0.9);
+    split_factors.insert(split, new_factor);
 }
 
 /// Which corner the corner window will in.
@@ -960,6 +1257,32 @@ impl LayoutGenerator for CornerLayout {
 
         geos
     }
+
+    fn name(&self) -> Option<&str> {
+        Some("corner")
+    }
+
+    fn resize_tile(&mut self, args: &LayoutArgs, resize: &TileResize) {
+        let Some(index) = args.windows.iter().position(|win| win == &resize.window) else {
+            return;
+        };
+
+        if args.windows.len() < 2 || args.output_width == 0 || args.output_height == 0 {
+            return;
+        }
+
+        let mut width_delta = resize.width_delta as f32 / args.output_width as f32;
+        let mut height_delta = resize.height_delta as f32 / args.output_height as f32;
+
+        // Growing a stack window shrinks the corner window
+        if index != 0 {
+            width_delta = -width_delta;
+            height_delta = -height_delta;
+        }
+
+        self.corner_width_factor = (self.corner_width_factor + width_delta).clamp(0.1, 0.9);
+        self.corner_height_factor = (self.corner_height_factor + height_delta).clamp(0.1, 0.9);
+    }
 }
 
 /// A [`LayoutGenerator`] that attempts to layout windows such that
@@ -1132,4 +1455,52 @@ impl LayoutGenerator for FairLayout {
 
         geos
     }
+
+    fn name(&self) -> Option<&str> {
+        Some("fair")
+    }
+}
+
+/// A [`LayoutGenerator`] that gives every window the whole output.
+///
+/// Windows are stacked on top of each other, with the focused one shown.
+/// Use [`Window::cycle_focus_forward`][crate::window::Window::cycle_focus_forward]
+/// and [`Window::cycle_focus_backward`][crate::window::Window::cycle_focus_backward]
+/// to go through them, and [`Layout::set_monocle_indicator`] to show how many there are.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MonocleLayout {
+    /// Gaps around each window.
+    ///
+    /// Defaults to `Gaps::Absolute(8)`.
+    pub gaps: Gaps,
+}
+
+impl Default for MonocleLayout {
+    fn default() -> Self {
+        Self {
+            gaps: Gaps::Absolute(8),
+        }
+    }
+}
+
+impl LayoutGenerator for MonocleLayout {
+    fn layout(&self, args: &LayoutArgs) -> Vec<Geometry> {
+        let gaps = match self.gaps {
+            Gaps::Absolute(gaps) => gaps,
+            Gaps::Split { inner, outer } => inner + outer,
+        };
+
+        let geo = Geometry {
+            x: gaps as i32,
+            y: gaps as i32,
+            width: args.output_width.saturating_sub(gaps * 2),
+            height: args.output_height.saturating_sub(gaps * 2),
+        };
+
+        args.windows.iter().map(|_| geo).collect()
+    }
+
+    fn name(&self) -> Option<&str> {
+        Some("monocle")
+    }
 }
@@ -77,6 +77,7 @@
 
 use std::sync::Arc;
 
+use debug::Debug;
 use futures::{future::BoxFuture, Future, FutureExt, StreamExt};
 use input::Input;
 use layout::Layout;
@@ -84,6 +85,7 @@ use output::Output;
 use pinnacle::Pinnacle;
 use process::Process;
 use render::Render;
+use screenshot::Screenshot;
 use signal::SignalState;
 use tag::Tag;
 use tokio::sync::{
@@ -95,12 +97,14 @@ use tonic::transport::{Endpoint, Uri};
 use tower::service_fn;
 use window::Window;
 
+pub mod debug;
 pub mod input;
 pub mod layout;
 pub mod output;
 pub mod pinnacle;
 pub mod process;
 pub mod render;
+pub mod screenshot;
 pub mod signal;
 pub mod tag;
 pub mod util;
@@ -130,6 +134,10 @@ pub struct ApiModules {
     pub layout: &'static Layout,
     /// The [`Render`] struct
     pub render: &'static Render,
+    /// The [`Screenshot`] struct
+    pub screenshot: &'static Screenshot,
+    /// The [`Debug`] struct
+    pub debug: &'static Debug,
     signal: Arc<RwLock<SignalState>>,
 }
 
@@ -144,6 +152,8 @@ impl std::fmt::Debug for ApiModules {
             .field("tag", &self.tag)
             .field("layout", &self.layout)
             .field("render", &self.render)
+            .field("screenshot", &self.screenshot)
+            .field("debug", &self.debug)
             .field("signal", &"...")
             .finish()
     }
@@ -179,6 +189,8 @@ pub async fn connect(
     let output = Box::leak(Box::new(Output::new(channel.clone())));
     let tag = Box::leak(Box::new(Tag::new(channel.clone())));
     let render = Box::leak(Box::new(Render::new(channel.clone())));
+    let screenshot = Box::leak(Box::new(Screenshot::new(channel.clone())));
+    let debug = Box::leak(Box::new(Debug::new(channel.clone())));
     let layout = Box::leak(Box::new(Layout::new(channel.clone(), fut_sender.clone())));
 
     let modules = ApiModules {
@@ -190,12 +202,15 @@ pub async fn connect(
         tag,
         layout,
         render,
+        screenshot,
+        debug,
         signal: signal.clone(),
     };
 
     window.finish_init(modules.clone());
     output.finish_init(modules.clone());
     tag.finish_init(modules.clone());
+    input.finish_init(modules.clone());
     layout.finish_init(modules.clone());
     signal.read().await.finish_init(modules.clone());
 
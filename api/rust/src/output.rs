@@ -9,14 +9,20 @@
 //! This module provides [`Output`], which allows you to get [`OutputHandle`]s for different
 //! connected monitors and set them up.
 
-use std::{num::NonZeroU32, sync::OnceLock};
+use std::{num::NonZeroU32, sync::OnceLock, time::Duration};
 
 use futures::FutureExt;
 use pinnacle_api_defs::pinnacle::output::{
     self,
     v0alpha1::{
-        output_service_client::OutputServiceClient, set_scale_request::AbsoluteOrRelative,
-        SetLocationRequest, SetModeRequest, SetScaleRequest, SetTransformRequest,
+        add_custom_mode_request, output_service_client::OutputServiceClient,
+        set_max_render_time_request, set_night_light_request,
+        set_scale_request::AbsoluteOrRelative, set_wallpaper_request, AddCustomModeRequest,
+        ClearProfilesRequest, CvtMode, SaveProfileRequest, SetColorFilterRequest,
+        SetEnabledRequest, SetLocationRequest, SetMaxFpsRequest, SetMaxRenderTimeRequest,
+        SetModeRequest, SetModelineRequest, SetNightLightRequest, SetPoweredRequest,
+        SetPrimaryRequest, SetScaleRequest, SetTransformRequest, SetVrrRequest,
+        SetWallpaperRequest,
     },
 };
 use tonic::transport::Channel;
@@ -130,6 +136,41 @@ impl Output {
         )
     }
 
+    /// Save the current output configuration as a profile for the connected monitors.
+    ///
+    /// Whenever this exact set of monitors is connected again, Pinnacle will restore each
+    /// output's mode, location, scale, and whether it's enabled. Saving again replaces
+    /// the profile.
+    ///
+    /// Monitors are identified by their make, model, and serial number.
+    ///
+    /// Profiles are stored in Pinnacle's state directory and persist across restarts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Remember the current docked setup
+    /// output.save_profile();
+    /// ```
+    pub fn save_profile(&self) {
+        let mut client = self.output_client.clone();
+        block_on_tokio(client.save_profile(SaveProfileRequest {})).unwrap();
+    }
+
+    /// Remove the profile for the connected monitors.
+    ///
+    /// If `all` is true, every saved profile is removed instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// output.clear_profiles(false);
+    /// ```
+    pub fn clear_profiles(&self, all: bool) {
+        let mut client = self.output_client.clone();
+        block_on_tokio(client.clear_profiles(ClearProfilesRequest { all: Some(all) })).unwrap();
+    }
+
     /// Connect a closure to be run on all current and future outputs.
     ///
     /// When called, `connect_for_all` will do two things:
@@ -581,10 +622,11 @@ impl std::hash::Hash for OutputHandle {
 }
 
 /// The alignment to use for [`OutputHandle::set_loc_adj_to`].
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(num_enum::TryFromPrimitive, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(i32)]
 pub enum Alignment {
     /// Set above, align left borders
-    TopAlignLeft,
+    TopAlignLeft = 1,
     /// Set above, align centers
     TopAlignCenter,
     /// Set above, align right borders
@@ -634,6 +676,128 @@ pub enum Transform {
     Flipped270,
 }
 
+/// An output's variable refresh rate setting.
+#[derive(num_enum::TryFromPrimitive, Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(i32)]
+pub enum Vrr {
+    /// VRR is disabled.
+    #[default]
+    Off = 1,
+    /// VRR is always enabled.
+    On,
+    /// VRR is only enabled while a fullscreen game or video is on the output.
+    OnDemand,
+}
+
+/// When an output starts rendering a frame.
+///
+/// Rendering as late as possible before vblank shortens the time between input
+/// and it showing up on screen, at the risk of missing the vblank and showing the
+/// frame a refresh later.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum MaxRenderTime {
+    /// Render right after vblank.
+    #[default]
+    Off,
+    /// Start rendering this long before the next vblank.
+    ///
+    /// This is rounded down to whole milliseconds.
+    Fixed(Duration),
+    /// Start rendering based on how long recent frames took to render.
+    Adaptive,
+}
+
+/// A transformation applied to every color on an output.
+#[derive(num_enum::TryFromPrimitive, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(i32)]
+pub enum ColorFilter {
+    /// Show everything in shades of gray.
+    Grayscale = 2,
+    /// Invert all colors.
+    Invert,
+    /// Simulate protanopia (no red cones).
+    Protanopia,
+    /// Simulate deuteranopia (no green cones).
+    Deuteranopia,
+    /// Simulate tritanopia (no blue cones).
+    Tritanopia,
+    /// Shift colors so they are easier to tell apart with protanopia.
+    ProtanopiaCorrection,
+    /// Shift colors so they are easier to tell apart with deuteranopia.
+    DeuteranopiaCorrection,
+    /// Shift colors so they are easier to tell apart with tritanopia.
+    TritanopiaCorrection,
+}
+
+/// A night light that shifts an output's colors to a warmer color temperature.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct NightLight {
+    /// The color temperature in Kelvin, from 1000 to 10000.
+    ///
+    /// 6500 leaves colors unchanged; lower is warmer.
+    pub temperature: u32,
+    /// When the night light is on.
+    pub schedule: NightLightSchedule,
+}
+
+/// When a [`NightLight`] is on.
+///
+/// The night light fades in and out over half an hour at the start and end.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum NightLightSchedule {
+    /// Always on.
+    #[default]
+    Always,
+    /// On between two local times, given as `(hour, minute)`.
+    Fixed {
+        /// When the night light turns on.
+        from: (u32, u32),
+        /// When the night light turns off.
+        to: (u32, u32),
+    },
+    /// On from sunset to sunrise at a location, in degrees.
+    Sun {
+        /// The latitude, positive is north.
+        latitude: f64,
+        /// The longitude, positive is east.
+        longitude: f64,
+    },
+}
+
+/// How a wallpaper image is fit onto an output.
+#[derive(num_enum::TryFromPrimitive, Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(i32)]
+pub enum WallpaperMode {
+    /// Scale the image to cover the output, cropping what doesn't fit.
+    #[default]
+    Fill = 1,
+    /// Scale the image to fit inside the output, with bars around it.
+    Fit,
+    /// Stretch the image to the output's size.
+    Stretch,
+}
+
+/// What is drawn beneath everything else on an output.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Wallpaper {
+    /// A solid color, with each component from 0.0 to 1.0.
+    Color {
+        /// The red component.
+        r: f32,
+        /// The green component.
+        g: f32,
+        /// The blue component.
+        b: f32,
+    },
+    /// A PNG image.
+    Image {
+        /// The path to the image. `~` and environment variables are expanded.
+        path: String,
+        /// How the image is fit onto the output.
+        mode: WallpaperMode,
+    },
+}
+
 impl OutputHandle {
     /// Set the location of this output in the global space.
     ///
@@ -645,6 +809,9 @@ impl OutputHandle {
     /// Note: If you leave space between two outputs when setting their locations,
     /// the pointer will not be able to move between them.
     ///
+    /// This stops the output from following an output set with
+    /// [`set_loc_adj_to`][OutputHandle::set_loc_adj_to].
+    ///
     /// # Examples
     ///
     /// ```
@@ -673,14 +840,17 @@ impl OutputHandle {
             output_name: Some(self.name.clone()),
             x: x.into(),
             y: y.into(),
+            relative_to: None,
+            alignment: None,
         }))
         .unwrap();
     }
 
     /// Set this output adjacent to another one.
     ///
-    /// This is a helper method over [`OutputHandle::set_location`] to make laying out outputs
-    /// easier.
+    /// Unlike [`OutputHandle::set_location`], this doesn't hard-code coordinates:
+    /// the output stays next to `other` when either output changes mode, scale,
+    /// or transform, or when `other` moves.
     ///
     /// `alignment` is an [`Alignment`] of how you want this output to be placed.
     /// For example, [`TopAlignLeft`][Alignment::TopAlignLeft] will place this output
@@ -715,67 +885,15 @@ impl OutputHandle {
     /// // "HDMI-1" was placed at (1920, 0) during the compositor's initial output layout.
     /// ```
     pub fn set_loc_adj_to(&self, other: &OutputHandle, alignment: Alignment) {
-        let self_props = self.props();
-        let other_props = other.props();
-
-        // poor man's try {}
-        let attempt_set_loc = || -> Option<()> {
-            let other_x = other_props.x?;
-            let other_y = other_props.y?;
-            let other_width = other_props.logical_width? as i32;
-            let other_height = other_props.logical_height? as i32;
-
-            let self_width = self_props.logical_width? as i32;
-            let self_height = self_props.logical_height? as i32;
-
-            use Alignment::*;
-
-            let x: i32;
-            let y: i32;
-
-            if let TopAlignLeft | TopAlignCenter | TopAlignRight | BottomAlignLeft
-            | BottomAlignCenter | BottomAlignRight = alignment
-            {
-                if let TopAlignLeft | TopAlignCenter | TopAlignRight = alignment {
-                    y = other_y - self_height;
-                } else {
-                    // bottom
-                    y = other_y + other_height;
-                }
-
-                match alignment {
-                    TopAlignLeft | BottomAlignLeft => x = other_x,
-                    TopAlignCenter | BottomAlignCenter => {
-                        x = other_x + (other_width - self_width) / 2;
-                    }
-                    TopAlignRight | BottomAlignRight => x = other_x + (other_width - self_width),
-                    _ => unreachable!(),
-                }
-            } else {
-                if let LeftAlignTop | LeftAlignCenter | LeftAlignBottom = alignment {
-                    x = other_x - self_width;
-                } else {
-                    x = other_x + other_width;
-                }
-
-                match alignment {
-                    LeftAlignTop | RightAlignTop => y = other_y,
-                    LeftAlignCenter | RightAlignCenter => {
-                        y = other_y + (other_height - self_height) / 2;
-                    }
-                    LeftAlignBottom | RightAlignBottom => {
-                        y = other_y + (other_height - self_height);
-                    }
-                    _ => unreachable!(),
-                }
-            }
-
-            self.set_location(Some(x), Some(y));
-
-            Some(())
-        };
-
-        attempt_set_loc();
+        let mut client = self.output_client.clone();
+        block_on_tokio(client.set_location(SetLocationRequest {
+            output_name: Some(self.name.clone()),
+            x: None,
+            y: None,
+            relative_to: Some(other.name.clone()),
+            alignment: Some(alignment as i32),
+        }))
+        .unwrap();
     }
 
     /// Set this output's mode.
@@ -810,8 +928,97 @@ impl OutputHandle {
         .unwrap();
     }
 
+    /// Set this output's mode to a custom modeline.
+    ///
+    /// Unlike [`OutputHandle::set_mode`], the modeline is not validated against the modes
+    /// the output advertises, so a bad modeline may leave the monitor without a signal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::output::Modeline;
+    ///
+    /// output.get_focused()?.set_modeline(Modeline {
+    ///     clock: 173000.0,
+    ///     hdisplay: 1920,
+    ///     hsync_start: 2048,
+    ///     hsync_end: 2248,
+    ///     htotal: 2576,
+    ///     vdisplay: 1080,
+    ///     vsync_start: 1083,
+    ///     vsync_end: 1088,
+    ///     vtotal: 1120,
+    ///     hsync_pos: false,
+    ///     vsync_pos: true,
+    /// });
+    /// ```
+    pub fn set_modeline(&self, modeline: Modeline) {
+        let mut client = self.output_client.clone();
+        block_on_tokio(client.set_modeline(SetModelineRequest {
+            output_name: Some(self.name.clone()),
+            modeline: Some(modeline.into()),
+        }))
+        .unwrap();
+    }
+
+    /// Add a mode that this output's monitor doesn't advertise.
+    ///
+    /// This is useful for monitors with broken EDID or that can be overclocked.
+    /// Once added, the mode can be selected with [`OutputHandle::set_mode`] like any other.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::output::CustomMode;
+    ///
+    /// let op = output.get_focused()?;
+    ///
+    /// op.add_custom_mode(CustomMode::Cvt {
+    ///     width: 2560,
+    ///     height: 1440,
+    ///     refresh_rate_millihz: 75000,
+    ///     reduced_blanking: true,
+    /// });
+    /// op.add_custom_mode(CustomMode::ModelineString(
+    ///     "173.00 1920 2048 2248 2576 1080 1083 1088 1120 -hsync +vsync".to_string(),
+    /// ));
+    ///
+    /// op.set_mode(2560, 1440, 75000);
+    /// ```
+    pub fn add_custom_mode(&self, mode: CustomMode) {
+        let mode = match mode {
+            CustomMode::Modeline(modeline) => {
+                add_custom_mode_request::Mode::Modeline(modeline.into())
+            }
+            CustomMode::ModelineString(modeline) => {
+                add_custom_mode_request::Mode::ModelineString(modeline)
+            }
+            CustomMode::Cvt {
+                width,
+                height,
+                refresh_rate_millihz,
+                reduced_blanking,
+            } => add_custom_mode_request::Mode::Cvt(CvtMode {
+                pixel_width: Some(width),
+                pixel_height: Some(height),
+                refresh_rate_millihz: Some(refresh_rate_millihz),
+                reduced_blanking: Some(reduced_blanking),
+            }),
+        };
+
+        let mut client = self.output_client.clone();
+        block_on_tokio(client.add_custom_mode(AddCustomModeRequest {
+            output_name: Some(self.name.clone()),
+            mode: Some(mode),
+        }))
+        .unwrap();
+    }
+
     /// Set this output's scaling factor.
     ///
+    /// Fractional scales are rounded to the nearest multiple of 1/120, which is the precision
+    /// clients receive them with. The scale will not go below 0.25.
+    ///
     /// # Examples
     ///
     /// ```
@@ -874,6 +1081,286 @@ impl OutputHandle {
         .unwrap();
     }
 
+    /// Set this output's variable refresh rate setting.
+    ///
+    /// This does nothing if the output doesn't support VRR.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::output::Vrr;
+    ///
+    /// // Only use VRR when playing a fullscreen game
+    /// output.get_focused()?.set_vrr(Vrr::OnDemand);
+    /// ```
+    pub fn set_vrr(&self, vrr: Vrr) {
+        let mut client = self.output_client.clone();
+        block_on_tokio(client.set_vrr(SetVrrRequest {
+            output_name: Some(self.name.clone()),
+            vrr: Some(vrr as i32),
+        }))
+        .unwrap();
+    }
+
+    /// Set when this output starts rendering a frame.
+    ///
+    /// Rendering later reduces input latency. If frames start missing vblank,
+    /// give them more time or use [`MaxRenderTime::Adaptive`].
+    ///
+    /// This only works on the udev backend and does nothing while VRR is active.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use pinnacle_api::output::MaxRenderTime;
+    ///
+    /// // Start rendering 4 milliseconds before vblank
+    /// output.get_focused()?.set_max_render_time(MaxRenderTime::Fixed(Duration::from_millis(4)));
+    /// ```
+    pub fn set_max_render_time(&self, max_render_time: MaxRenderTime) {
+        let max_render_time = match max_render_time {
+            MaxRenderTime::Off => None,
+            MaxRenderTime::Fixed(time) => Some(set_max_render_time_request::MaxRenderTime::Millis(
+                time.as_millis().try_into().unwrap_or(u32::MAX),
+            )),
+            MaxRenderTime::Adaptive => {
+                Some(set_max_render_time_request::MaxRenderTime::Adaptive(()))
+            }
+        };
+
+        let mut client = self.output_client.clone();
+        block_on_tokio(client.set_max_render_time(SetMaxRenderTimeRequest {
+            output_name: Some(self.name.clone()),
+            max_render_time,
+        }))
+        .unwrap();
+    }
+
+    /// Cap how many frames per second this output shows, or remove the cap with `None`.
+    ///
+    /// Frames are shown for a whole number of refresh cycles, so the cap is rounded down
+    /// to this output's refresh rate divided by a whole number. Clients on this output
+    /// also only get frame callbacks at the capped rate.
+    ///
+    /// This only works on the udev backend.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Run a 240 Hz monitor at 120 fps to save power
+    /// output.get_focused()?.set_max_fps(Some(120));
+    /// ```
+    pub fn set_max_fps(&self, max_fps: Option<u32>) {
+        let mut client = self.output_client.clone();
+        block_on_tokio(client.set_max_fps(SetMaxFpsRequest {
+            output_name: Some(self.name.clone()),
+            max_fps,
+        }))
+        .unwrap();
+    }
+
+    /// Set or remove this output's color filter.
+    ///
+    /// A color filter transforms every color on this output, except the cursor.
+    /// Pass `None` to remove the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::output::ColorFilter;
+    ///
+    /// output.get_focused()?.set_color_filter(Some(ColorFilter::Grayscale));
+    /// output.get_focused()?.set_color_filter(None);
+    /// ```
+    pub fn set_color_filter(&self, color_filter: Option<ColorFilter>) {
+        let color_filter = match color_filter {
+            Some(filter) => filter as i32,
+            None => output::v0alpha1::ColorFilter::None as i32,
+        };
+
+        let mut client = self.output_client.clone();
+        block_on_tokio(client.set_color_filter(SetColorFilterRequest {
+            output_name: Some(self.name.clone()),
+            color_filter: Some(color_filter),
+        }))
+        .unwrap();
+    }
+
+    /// Turn this output's night light on or off.
+    ///
+    /// The night light shifts this output's colors to a warmer color temperature.
+    /// It is applied through the output's gamma, so it only works on the udev backend.
+    /// Pass `None` to turn it off.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::output::{NightLight, NightLightSchedule};
+    ///
+    /// // Warm colors from 9 PM to 7 AM
+    /// output.get_focused()?.set_night_light(Some(NightLight {
+    ///     temperature: 3500,
+    ///     schedule: NightLightSchedule::Fixed {
+    ///         from: (21, 0),
+    ///         to: (7, 0),
+    ///     },
+    /// }));
+    ///
+    /// // Warm colors from sunset to sunrise in Berlin
+    /// output.get_focused()?.set_night_light(Some(NightLight {
+    ///     temperature: 4000,
+    ///     schedule: NightLightSchedule::Sun {
+    ///         latitude: 52.5,
+    ///         longitude: 13.4,
+    ///     },
+    /// }));
+    /// ```
+    pub fn set_night_light(&self, night_light: Option<NightLight>) {
+        let (temperature, schedule) = match night_light {
+            Some(NightLight {
+                temperature,
+                schedule,
+            }) => {
+                let schedule = match schedule {
+                    NightLightSchedule::Always => None,
+                    NightLightSchedule::Fixed { from, to } => {
+                        Some(set_night_light_request::Schedule::Fixed(
+                            set_night_light_request::FixedSchedule {
+                                from_minute: Some(from.0 * 60 + from.1),
+                                to_minute: Some(to.0 * 60 + to.1),
+                            },
+                        ))
+                    }
+                    NightLightSchedule::Sun {
+                        latitude,
+                        longitude,
+                    } => Some(set_night_light_request::Schedule::Location(
+                        set_night_light_request::Location {
+                            latitude: Some(latitude),
+                            longitude: Some(longitude),
+                        },
+                    )),
+                };
+                (Some(temperature), schedule)
+            }
+            None => (None, None),
+        };
+
+        let mut client = self.output_client.clone();
+        block_on_tokio(client.set_night_light(SetNightLightRequest {
+            output_name: Some(self.name.clone()),
+            temperature,
+            schedule,
+        }))
+        .unwrap();
+    }
+
+    /// Set what is drawn beneath everything else on this output, or remove it with `None`.
+    ///
+    /// Returns whether the wallpaper was set, which fails if the image couldn't be loaded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::output::{Wallpaper, WallpaperMode};
+    ///
+    /// output.get_focused()?.set_wallpaper(Some(Wallpaper::Image {
+    ///     path: "~/Pictures/wallpaper.png".into(),
+    ///     mode: WallpaperMode::Fill,
+    /// }));
+    ///
+    /// output.get_focused()?.set_wallpaper(Some(Wallpaper::Color {
+    ///     r: 0.1,
+    ///     g: 0.1,
+    ///     b: 0.15,
+    /// }));
+    /// ```
+    pub fn set_wallpaper(&self, wallpaper: Option<Wallpaper>) -> bool {
+        let wallpaper = wallpaper.map(|wallpaper| match wallpaper {
+            Wallpaper::Color { r, g, b } => {
+                set_wallpaper_request::Wallpaper::Color(set_wallpaper_request::Color {
+                    r: Some(r),
+                    g: Some(g),
+                    b: Some(b),
+                })
+            }
+            Wallpaper::Image { path, mode } => {
+                set_wallpaper_request::Wallpaper::Image(set_wallpaper_request::Image {
+                    path: Some(path),
+                    mode: Some(mode as i32),
+                })
+            }
+        });
+
+        let mut client = self.output_client.clone();
+        block_on_tokio(client.set_wallpaper(SetWallpaperRequest {
+            output_name: Some(self.name.clone()),
+            wallpaper,
+        }))
+        .is_ok()
+    }
+
+    /// Turn this output's monitor on or off.
+    ///
+    /// A powered off output stays in the layout; windows can still be moved to it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// output.get_focused()?.set_powered(false);
+    /// ```
+    pub fn set_powered(&self, powered: bool) {
+        let mut client = self.output_client.clone();
+        block_on_tokio(client.set_powered(SetPoweredRequest {
+            output_name: Some(self.name.clone()),
+            powered: Some(powered),
+        }))
+        .unwrap();
+    }
+
+    /// Add this output to or remove it from the layout.
+    ///
+    /// Disabling an output turns it off and moves its tags, along with their windows,
+    /// to the focused output. Enabling it again moves those tags back and restores
+    /// its location.
+    ///
+    /// The last enabled output cannot be disabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Only use the external monitor
+    /// output.get_by_name("eDP-1")?.set_enabled(false);
+    /// ```
+    pub fn set_enabled(&self, enabled: bool) {
+        let mut client = self.output_client.clone();
+        block_on_tokio(client.set_enabled(SetEnabledRequest {
+            output_name: Some(self.name.clone()),
+            enabled: Some(enabled),
+        }))
+        .unwrap();
+    }
+
+    /// Make this output the primary output.
+    ///
+    /// New windows open on the primary output instead of the focused one unless a
+    /// window rule says otherwise, and windows that request activation while not
+    /// on any output are moved to it. X11 clients also see it as the primary output.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// output.get_by_name("DP-1")?.set_primary();
+    /// ```
+    pub fn set_primary(&self) {
+        let mut client = self.output_client.clone();
+        block_on_tokio(client.set_primary(SetPrimaryRequest {
+            output_name: Some(self.name.clone()),
+        }))
+        .unwrap();
+    }
+
     /// Get all properties of this output.
     ///
     /// # Examples
@@ -943,6 +1430,10 @@ impl OutputHandle {
             scale: response.scale,
             transform: response.transform.and_then(|tf| tf.try_into().ok()),
             serial: response.serial,
+            vrr: response.vrr.and_then(|vrr| vrr.try_into().ok()),
+            enabled: response.enabled,
+            powered: response.powered,
+            primary: response.primary,
         }
     }
 
@@ -1142,6 +1633,54 @@ impl OutputHandle {
         self.props_async().await.serial
     }
 
+    /// Get this output's VRR setting.
+    ///
+    /// Shorthand for `self.props().vrr`
+    pub fn vrr(&self) -> Option<Vrr> {
+        self.props().vrr
+    }
+
+    /// The async version of [`OutputHandle::vrr`].
+    pub async fn vrr_async(&self) -> Option<Vrr> {
+        self.props_async().await.vrr
+    }
+
+    /// Get whether this output is part of the layout.
+    ///
+    /// Shorthand for `self.props().enabled`
+    pub fn enabled(&self) -> Option<bool> {
+        self.props().enabled
+    }
+
+    /// The async version of [`OutputHandle::enabled`].
+    pub async fn enabled_async(&self) -> Option<bool> {
+        self.props_async().await.enabled
+    }
+
+    /// Get whether this output's monitor is turned on.
+    ///
+    /// Shorthand for `self.props().powered`
+    pub fn powered(&self) -> Option<bool> {
+        self.props().powered
+    }
+
+    /// The async version of [`OutputHandle::powered`].
+    pub async fn powered_async(&self) -> Option<bool> {
+        self.props_async().await.powered
+    }
+
+    /// Get whether this is the primary output.
+    ///
+    /// Shorthand for `self.props().primary`
+    pub fn primary(&self) -> Option<bool> {
+        self.props().primary
+    }
+
+    /// The async version of [`OutputHandle::primary`].
+    pub async fn primary_async(&self) -> Option<bool> {
+        self.props_async().await.primary
+    }
+
     /// Get this output's unique name (the name of its connector).
     pub fn name(&self) -> &str {
         &self.name
@@ -1161,6 +1700,95 @@ pub struct Mode {
     pub refresh_rate_millihertz: u32,
 }
 
+/// Custom mode timings, in the same order as an X11 modeline.
+///
+/// For example, the modeline
+/// `173.00  1920 2048 2248 2576  1080 1083 1088 1120 -hsync +vsync`
+/// corresponds to
+///
+/// ```
+/// Modeline {
+///     clock: 173000.0,
+///     hdisplay: 1920,
+///     hsync_start: 2048,
+///     hsync_end: 2248,
+///     htotal: 2576,
+///     vdisplay: 1080,
+///     vsync_start: 1083,
+///     vsync_end: 1088,
+///     vtotal: 1120,
+///     hsync_pos: false,
+///     vsync_pos: true,
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct Modeline {
+    /// The pixel clock, in kilohertz.
+    pub clock: f32,
+    /// The width of the output, in pixels.
+    pub hdisplay: u32,
+    /// The start of the horizontal sync pulse.
+    pub hsync_start: u32,
+    /// The end of the horizontal sync pulse.
+    pub hsync_end: u32,
+    /// The total width of a line, including blanking.
+    pub htotal: u32,
+    /// The height of the output, in pixels.
+    pub vdisplay: u32,
+    /// The start of the vertical sync pulse.
+    pub vsync_start: u32,
+    /// The end of the vertical sync pulse.
+    pub vsync_end: u32,
+    /// The total height of a frame, including blanking.
+    pub vtotal: u32,
+    /// `true` for `+hsync`, `false` for `-hsync`.
+    pub hsync_pos: bool,
+    /// `true` for `+vsync`, `false` for `-vsync`.
+    pub vsync_pos: bool,
+}
+
+impl From<Modeline> for output::v0alpha1::Modeline {
+    fn from(modeline: Modeline) -> Self {
+        Self {
+            clock: Some(modeline.clock),
+            hdisplay: Some(modeline.hdisplay),
+            hsync_start: Some(modeline.hsync_start),
+            hsync_end: Some(modeline.hsync_end),
+            htotal: Some(modeline.htotal),
+            vdisplay: Some(modeline.vdisplay),
+            vsync_start: Some(modeline.vsync_start),
+            vsync_end: Some(modeline.vsync_end),
+            vtotal: Some(modeline.vtotal),
+            hsync_pos: Some(modeline.hsync_pos),
+            vsync_pos: Some(modeline.vsync_pos),
+        }
+    }
+}
+
+/// A mode to add with [`OutputHandle::add_custom_mode`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum CustomMode {
+    /// A mode with explicit timings.
+    Modeline(Modeline),
+    /// An X11 modeline, like the output of `cvt`.
+    ///
+    /// Unlike [`Modeline::clock`], the pixel clock here is in megahertz.
+    /// A leading `Modeline "name"` is optional.
+    ModelineString(String),
+    /// A mode with timings generated by VESA Coordinated Video Timings.
+    Cvt {
+        /// The width of the mode, in pixels.
+        width: u32,
+        /// The height of the mode, in pixels.
+        height: u32,
+        /// The refresh rate, in millihertz.
+        refresh_rate_millihz: u32,
+        /// Whether to use reduced blanking (CVT-RB), which lowers the required
+        /// bandwidth on digital displays.
+        reduced_blanking: bool,
+    },
+}
+
 /// The properties of an output.
 #[non_exhaustive]
 #[derive(Debug, Clone, PartialEq, Default)]
@@ -1204,4 +1832,12 @@ pub struct OutputProperties {
     pub transform: Option<Transform>,
     /// This output's EDID serial number.
     pub serial: Option<u32>,
+    /// This output's VRR setting.
+    pub vrr: Option<Vrr>,
+    /// Whether this output is part of the layout.
+    pub enabled: Option<bool>,
+    /// Whether this output's monitor is turned on.
+    pub powered: Option<bool>,
+    /// Whether this is the primary output.
+    pub primary: Option<bool>,
 }
@@ -10,7 +10,8 @@ use std::time::Duration;
 
 use pinnacle_api_defs::pinnacle::v0alpha1::{
     pinnacle_service_client::PinnacleServiceClient, PingRequest, QuitRequest, ReloadConfigRequest,
-    ShutdownWatchRequest, ShutdownWatchResponse,
+    RestoreSessionRequest, SetSessionPersistenceRequest, ShutdownWatchRequest,
+    ShutdownWatchResponse,
 };
 use rand::RngCore;
 use tonic::{transport::Channel, Request, Streaming};
@@ -51,6 +52,48 @@ impl Pinnacle {
         let _ = block_on_tokio(client.reload_config(ReloadConfigRequest {}));
     }
 
+    /// Set whether tags and the tags windows are on are saved across restarts
+    /// and config reloads.
+    ///
+    /// Tags are saved by name for each output, and windows by app id. While enabled,
+    /// new windows are put on the tags their app was last on unless a window rule places them.
+    /// Use [`Pinnacle::restore_session`] to bring back the saved tags.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// pinnacle.set_session_persistence(true);
+    /// ```
+    pub fn set_session_persistence(&self, enabled: bool) {
+        let mut client = self.client.clone();
+        block_on_tokio(
+            client.set_session_persistence(SetSessionPersistenceRequest {
+                enabled: Some(enabled),
+            }),
+        )
+        .unwrap();
+    }
+
+    /// Restore the saved session.
+    ///
+    /// Every output activates the tags that were active on it, and open windows move back
+    /// to the tags their app was last on. Call this after adding tags so there's something
+    /// to restore.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// pinnacle.set_session_persistence(true);
+    ///
+    /// output.setup([OutputSetup::new_with_matcher(|_| true).with_tags(["1", "2", "3"])]);
+    ///
+    /// pinnacle.restore_session();
+    /// ```
+    pub fn restore_session(&self) {
+        let mut client = self.client.clone();
+        block_on_tokio(client.restore_session(RestoreSessionRequest {})).unwrap();
+    }
+
     pub(crate) async fn shutdown_watch(&self) -> Streaming<ShutdownWatchResponse> {
         let mut client = self.client.clone();
         client
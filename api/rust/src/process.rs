@@ -14,7 +14,7 @@ use pinnacle_api_defs::pinnacle::process::v0alpha1::{
 use tokio::sync::mpsc::UnboundedSender;
 use tonic::transport::Channel;
 
-use crate::block_on_tokio;
+use crate::{block_on_tokio, output::OutputHandle, tag::TagHandle};
 
 /// A struct containing methods to spawn processes with optional callbacks and set environment
 /// variables.
@@ -36,6 +36,47 @@ pub struct SpawnCallbacks {
     pub exit: Option<Box<dyn FnMut(Option<i32>, String) + Send>>,
 }
 
+/// Where to open the first window of a spawned process.
+///
+/// The window is found by process id, including windows opened by child processes,
+/// so this works for apps that are slow to start or start through a launcher.
+///
+/// `SpawnPlacement`s are built using the builder pattern.
+#[derive(Debug, Clone, Default)]
+pub struct SpawnPlacement {
+    tag_ids: Vec<u32>,
+    output: Option<String>,
+    follow: Option<bool>,
+}
+
+impl SpawnPlacement {
+    /// Create a new, empty `SpawnPlacement`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Open the window with the given tags.
+    ///
+    /// The window opens in the background if none of them are active,
+    /// unless it's [followed][SpawnPlacement::follow].
+    pub fn tags<'a>(mut self, tags: impl IntoIterator<Item = &'a TagHandle>) -> Self {
+        self.tag_ids = tags.into_iter().map(|tag| tag.id).collect();
+        self
+    }
+
+    /// Open the window on the given output with the output's active tags.
+    pub fn output(mut self, output: &OutputHandle) -> Self {
+        self.output = Some(output.name.clone());
+        self
+    }
+
+    /// Switch to the window when it opens, activating its first tag and focusing it.
+    pub fn follow(mut self, follow: bool) -> Self {
+        self.follow = Some(follow);
+        self
+    }
+}
+
 impl Process {
     pub(crate) fn new(
         channel: Channel,
@@ -64,7 +105,28 @@ impl Process {
     /// process.spawn(["bash", "-c", "swaybg -i ~/path_to_wallpaper"]);
     /// ```
     pub fn spawn(&self, args: impl IntoIterator<Item = impl Into<String>>) {
-        self.spawn_inner(args, false, None);
+        self.spawn_inner(args, false, None, None);
+    }
+
+    /// Spawn a process and open its first window at the given placement.
+    ///
+    /// See [`SpawnPlacement`] for where the window can be placed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::process::SpawnPlacement;
+    ///
+    /// // Open Firefox on tag "2" of "DP-1" without switching to it
+    /// let tag = tag.get("2", &output.get_by_name("DP-1")?)?;
+    /// process.spawn_placed(["firefox"], SpawnPlacement::new().tags([&tag]));
+    /// ```
+    pub fn spawn_placed(
+        &self,
+        args: impl IntoIterator<Item = impl Into<String>>,
+        placement: SpawnPlacement,
+    ) {
+        self.spawn_inner(args, false, None, Some(placement));
     }
 
     /// Spawn a process with callbacks for its stdout, stderr, and exit information.
@@ -91,7 +153,7 @@ impl Process {
         args: impl IntoIterator<Item = impl Into<String>>,
         callbacks: SpawnCallbacks,
     ) {
-        self.spawn_inner(args, false, Some(callbacks));
+        self.spawn_inner(args, false, Some(callbacks), None);
     }
 
     /// Spawn a process only if it isn't already running.
@@ -100,7 +162,7 @@ impl Process {
     ///
     /// See [`Process::spawn`] for details.
     pub fn spawn_once(&self, args: impl IntoIterator<Item = impl Into<String>>) {
-        self.spawn_inner(args, true, None);
+        self.spawn_inner(args, true, None, None);
     }
 
     /// Spawn a process only if it isn't already running with optional callbacks for its stdout,
@@ -114,7 +176,7 @@ impl Process {
         args: impl IntoIterator<Item = impl Into<String>>,
         callbacks: SpawnCallbacks,
     ) {
-        self.spawn_inner(args, true, Some(callbacks));
+        self.spawn_inner(args, true, Some(callbacks), None);
     }
 
     fn spawn_inner(
@@ -122,15 +184,20 @@ impl Process {
         args: impl IntoIterator<Item = impl Into<String>>,
         once: bool,
         callbacks: Option<SpawnCallbacks>,
+        placement: Option<SpawnPlacement>,
     ) {
         let mut client = self.create_process_client();
 
         let args = args.into_iter().map(Into::into).collect::<Vec<_>>();
+        let placement = placement.unwrap_or_default();
 
         let request = SpawnRequest {
             args,
             once: Some(once),
             has_callback: Some(callbacks.is_some()),
+            tag_ids: placement.tag_ids,
+            output: placement.output,
+            follow: placement.follow,
         };
 
         self.fut_sender
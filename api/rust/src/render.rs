@@ -1,7 +1,9 @@
 //! Rendering management.
 
 use pinnacle_api_defs::pinnacle::render::v0alpha1::{
-    render_service_client::RenderServiceClient, SetDownscaleFilterRequest, SetUpscaleFilterRequest,
+    render_service_client::RenderServiceClient, SetAnimationsEnabledRequest, SetBlurRequest,
+    SetCornerRadiusRequest, SetDimInactiveRequest, SetDownscaleFilterRequest,
+    SetUpscaleFilterRequest,
 };
 use tonic::transport::Channel;
 
@@ -65,4 +67,98 @@ impl Render {
         }))
         .unwrap();
     }
+
+    /// Enable or disable window animations.
+    ///
+    /// Animations are enabled by default. They can also be disabled for specific windows
+    /// with [`WindowRule::animations`][crate::window::rules::WindowRule::animations].
+    /// Windows that say they're showing a game or video are never animated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// render.set_animations_enabled(false);
+    /// ```
+    pub fn set_animations_enabled(&self, enabled: bool) {
+        let mut client = self.client.clone();
+        block_on_tokio(client.set_animations_enabled(SetAnimationsEnabledRequest {
+            enabled: Some(enabled),
+        }))
+        .unwrap();
+    }
+
+    /// Set the corner radius of windows in logical pixels.
+    ///
+    /// A radius of 0, the default, gives windows square corners. Fullscreen windows
+    /// always have square corners. The radius can be overridden for specific windows
+    /// with [`WindowRule::corner_radius`][crate::window::rules::WindowRule::corner_radius],
+    /// and while specific tags are active with
+    /// [`TagHandle::set_corner_radius`][crate::tag::TagHandle::set_corner_radius].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// render.set_corner_radius(8.0);
+    /// ```
+    pub fn set_corner_radius(&self, radius: f32) {
+        let mut client = self.client.clone();
+        block_on_tokio(client.set_corner_radius(SetCornerRadiusRequest {
+            radius: Some(radius),
+        }))
+        .unwrap();
+    }
+
+    /// Set how the background behind blurred windows and layer surfaces is blurred.
+    ///
+    /// `passes` is how many times the background is downsampled, from 1 to 8.
+    /// More passes give a stronger blur. `offset` is how far apart samples are taken
+    /// in each pass.
+    ///
+    /// Windows are blurred with [`WindowRule::blur`][crate::window::rules::WindowRule::blur].
+    /// Top and overlay layer surfaces whose namespace is in `layer_namespaces` are also blurred.
+    ///
+    /// Only the background and bottom layer surfaces are blurred, not windows
+    /// behind other windows.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// render.set_blur(3, 4.0, ["waybar"]);
+    /// ```
+    pub fn set_blur(
+        &self,
+        passes: u32,
+        offset: f32,
+        layer_namespaces: impl IntoIterator<Item = impl ToString>,
+    ) {
+        let mut client = self.client.clone();
+        block_on_tokio(
+            client.set_blur(SetBlurRequest {
+                passes: Some(passes),
+                offset: Some(offset),
+                layer_namespaces: layer_namespaces
+                    .into_iter()
+                    .map(|namespace| namespace.to_string())
+                    .collect(),
+            }),
+        )
+        .unwrap();
+    }
+
+    /// Dim windows that aren't focused.
+    ///
+    /// `dim` is how much to darken them, from 0.0 (not at all), the default, to 1.0 (black).
+    /// Specific windows can be kept from dimming with
+    /// [`WindowRule::dim`][crate::window::rules::WindowRule::dim]. Windows that say they're
+    /// showing a game or video are never dimmed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// render.set_dim_inactive(0.2);
+    /// ```
+    pub fn set_dim_inactive(&self, dim: f32) {
+        let mut client = self.client.clone();
+        block_on_tokio(client.set_dim_inactive(SetDimInactiveRequest { dim: Some(dim) })).unwrap();
+    }
 }
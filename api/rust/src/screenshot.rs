@@ -0,0 +1,223 @@
+//! Screenshots.
+//!
+//! This module lets you capture outputs, regions, and windows as PNGs,
+//! so you can bind printscreen without any other tools.
+//! It can also record outputs and regions to video files.
+
+use std::path::Path;
+
+use pinnacle_api_defs::pinnacle::{
+    screenshot::v0alpha1::{
+        screenshot_request, screenshot_service_client::ScreenshotServiceClient,
+        start_recording_request, ScreenshotRequest, StartRecordingRequest, StopRecordingRequest,
+        WindowThumbnailRequest,
+    },
+    v0alpha1,
+};
+use tonic::transport::Channel;
+
+use crate::{block_on_tokio, output::OutputHandle, util::Geometry, window::WindowHandle};
+
+/// A struct that allows you to take screenshots.
+#[derive(Debug, Clone)]
+pub struct Screenshot {
+    client: ScreenshotServiceClient<Channel>,
+}
+
+/// What to take a screenshot of.
+#[derive(Debug, Clone)]
+pub enum Target {
+    /// An entire output.
+    Output(OutputHandle),
+    /// A region in the global space.
+    ///
+    /// Only the part of the region on the output containing its top-left corner is captured.
+    Region(Geometry),
+    /// A single window, without anything above or below it.
+    Window(WindowHandle),
+}
+
+/// What to record.
+#[derive(Debug, Clone)]
+pub enum RecordingTarget {
+    /// An entire output.
+    Output(OutputHandle),
+    /// A region in the global space.
+    ///
+    /// Only the part of the region on the output containing its top-left corner is recorded.
+    Region(Geometry),
+}
+
+impl Screenshot {
+    pub(crate) fn new(channel: Channel) -> Self {
+        Self {
+            client: ScreenshotServiceClient::new(channel),
+        }
+    }
+
+    /// Take a screenshot and get it as a PNG.
+    ///
+    /// If `include_cursor` is true, the cursor is drawn as well. It is never drawn on windows.
+    ///
+    /// Returns `None` if the target doesn't exist or couldn't be captured.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::screenshot::Target;
+    ///
+    /// if let Some(focused) = output.get_focused() {
+    ///     let png = screenshot.capture(Target::Output(focused), true);
+    /// }
+    /// ```
+    pub fn capture(&self, target: Target, include_cursor: bool) -> Option<Vec<u8>> {
+        let mut client = self.client.clone();
+        block_on_tokio(client.screenshot(request(target, include_cursor, None)))
+            .ok()?
+            .into_inner()
+            .png
+    }
+
+    /// Take a screenshot and save it as a PNG file.
+    ///
+    /// If `include_cursor` is true, the cursor is drawn as well. It is never drawn on windows.
+    /// `~` and environment variables in `path` are expanded by Pinnacle.
+    ///
+    /// Returns whether the screenshot was saved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::screenshot::Target;
+    ///
+    /// if let Some(focused) = window.get_focused() {
+    ///     screenshot.save(Target::Window(focused), false, "~/Pictures/window.png");
+    /// }
+    /// ```
+    pub fn save(&self, target: Target, include_cursor: bool, path: impl AsRef<Path>) -> bool {
+        let path = path.as_ref().to_string_lossy().to_string();
+
+        let mut client = self.client.clone();
+        block_on_tokio(client.screenshot(request(target, include_cursor, Some(path)))).is_ok()
+    }
+
+    /// Capture a window as a PNG thumbnail for previews like taskbars and window switchers.
+    ///
+    /// The window is rendered on its own without its popups or anything above it, even if it
+    /// is hidden. It is scaled down to fit in `max_width` by `max_height` pixels, keeping its
+    /// aspect ratio. Thumbnails are never larger than the window itself.
+    ///
+    /// Returns `None` if the window doesn't exist or couldn't be captured.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// for win in window.get_all() {
+    ///     let thumbnail = screenshot.window_thumbnail(&win, 256, 256);
+    /// }
+    /// ```
+    pub fn window_thumbnail(
+        &self,
+        window: &WindowHandle,
+        max_width: u32,
+        max_height: u32,
+    ) -> Option<Vec<u8>> {
+        let mut client = self.client.clone();
+        block_on_tokio(client.window_thumbnail(WindowThumbnailRequest {
+            window_id: Some(window.id),
+            max_width: Some(max_width),
+            max_height: Some(max_height),
+        }))
+        .ok()?
+        .into_inner()
+        .png
+    }
+
+    /// Start recording to a video file.
+    ///
+    /// Frames are encoded by `ffmpeg`, which must be installed. The container and codec are
+    /// chosen from the extension of `path`. `~` and environment variables in `path` are
+    /// expanded by Pinnacle. `framerate` defaults to 30 frames per second.
+    ///
+    /// While recording, a red square is shown in the top-right corner of the recorded output.
+    /// It isn't part of the recording.
+    ///
+    /// Only one recording can run at a time. Returns whether the recording started.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::screenshot::RecordingTarget;
+    ///
+    /// if let Some(focused) = output.get_focused() {
+    ///     screenshot.start_recording(
+    ///         RecordingTarget::Output(focused),
+    ///         "~/Videos/recording.mp4",
+    ///         None,
+    ///         true,
+    ///     );
+    /// }
+    /// ```
+    pub fn start_recording(
+        &self,
+        target: RecordingTarget,
+        path: impl AsRef<Path>,
+        framerate: Option<u32>,
+        include_cursor: bool,
+    ) -> bool {
+        let target = match target {
+            RecordingTarget::Output(output) => {
+                start_recording_request::Target::OutputName(output.name)
+            }
+            RecordingTarget::Region(geometry) => {
+                start_recording_request::Target::Region(geometry_to_api(geometry))
+            }
+        };
+
+        let mut client = self.client.clone();
+        block_on_tokio(client.start_recording(StartRecordingRequest {
+            target: Some(target),
+            path: Some(path.as_ref().to_string_lossy().to_string()),
+            framerate,
+            include_cursor: Some(include_cursor),
+        }))
+        .is_ok()
+    }
+
+    /// Stop the current recording, if any.
+    ///
+    /// The video file is finished once all captured frames are encoded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// screenshot.stop_recording();
+    /// ```
+    pub fn stop_recording(&self) {
+        let mut client = self.client.clone();
+        block_on_tokio(client.stop_recording(StopRecordingRequest {})).unwrap();
+    }
+}
+
+pub(crate) fn geometry_to_api(geometry: Geometry) -> v0alpha1::Geometry {
+    v0alpha1::Geometry {
+        x: Some(geometry.x),
+        y: Some(geometry.y),
+        width: Some(geometry.width as i32),
+        height: Some(geometry.height as i32),
+    }
+}
+
+fn request(target: Target, include_cursor: bool, path: Option<String>) -> ScreenshotRequest {
+    let target = match target {
+        Target::Output(output) => screenshot_request::Target::OutputName(output.name),
+        Target::Region(geometry) => screenshot_request::Target::Region(geometry_to_api(geometry)),
+        Target::Window(window) => screenshot_request::Target::WindowId(window.id),
+    };
+
+    ScreenshotRequest {
+        target: Some(target),
+        include_cursor: Some(include_cursor),
+        path,
+    }
+}
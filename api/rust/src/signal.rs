@@ -230,6 +230,23 @@ signals! {
                 }
             },
         }
+        /// A window started or stopped wanting attention.
+        ///
+        /// Callbacks receive the window and whether it wants attention.
+        WindowUrgent = {
+            enum_name = Urgent,
+            callback_type = Box<dyn FnMut(&WindowHandle, bool) + Send + 'static>,
+            client_request = window_urgent,
+            on_response = |response, callbacks, api| {
+                if let Some(window_id) = response.window_id {
+                    let handle = api.window.new_handle(window_id);
+
+                    for callback in callbacks {
+                        callback(&handle, response.urgent());
+                    }
+                }
+            },
+        }
     }
     /// Signals relating to tag events.
     TagSignal => {
@@ -248,6 +265,75 @@ signals! {
                 }
             },
         }
+        /// A window with a tag started wanting attention, or no window with it does anymore.
+        ///
+        /// Callbacks receive the tag and whether any of its windows want attention.
+        TagUrgent = {
+            enum_name = Urgent,
+            callback_type = Box<dyn FnMut(&TagHandle, bool) + Send + 'static>,
+            client_request = tag_urgent,
+            on_response = |response, callbacks, api| {
+                if let Some(tag_id) = response.tag_id {
+                    let handle = api.tag.new_handle(tag_id);
+
+                    for callback in callbacks {
+                        callback(&handle, response.urgent());
+                    }
+                }
+            },
+        }
+        /// A tag got its first window or lost its last one.
+        ///
+        /// Callbacks receive the tag and whether any window has it.
+        TagOccupied = {
+            enum_name = Occupied,
+            callback_type = Box<dyn FnMut(&TagHandle, bool) + Send + 'static>,
+            client_request = tag_occupied,
+            on_response = |response, callbacks, api| {
+                if let Some(tag_id) = response.tag_id {
+                    let handle = api.tag.new_handle(tag_id);
+
+                    for callback in callbacks {
+                        callback(&handle, response.occupied());
+                    }
+                }
+            },
+        }
+        /// A tag was renamed.
+        ///
+        /// Callbacks receive the tag and its new name.
+        TagRenamed = {
+            enum_name = Renamed,
+            callback_type = Box<dyn FnMut(&TagHandle, &str) + Send + 'static>,
+            client_request = tag_renamed,
+            on_response = |response, callbacks, api| {
+                if let Some(tag_id) = response.tag_id {
+                    let handle = api.tag.new_handle(tag_id);
+
+                    for callback in callbacks {
+                        callback(&handle, response.name());
+                    }
+                }
+            },
+        }
+    }
+    /// Signals relating to input events.
+    InputSignal => {
+        /// The active keyboard layout changed.
+        ///
+        /// Callbacks receive the index of the new layout and its name.
+        InputXkbLayout = {
+            enum_name = XkbLayout,
+            callback_type = Box<dyn FnMut(u32, &str) + Send + 'static>,
+            client_request = input_xkb_layout,
+            on_response = |response, callbacks, _api| {
+                if let Some(index) = response.index {
+                    for callback in callbacks {
+                        callback(index, response.name());
+                    }
+                }
+            },
+        }
     }
 }
 
@@ -262,8 +348,14 @@ pub(crate) struct SignalState {
 
     pub(crate) window_pointer_enter: SignalData<WindowPointerEnter>,
     pub(crate) window_pointer_leave: SignalData<WindowPointerLeave>,
+    pub(crate) window_urgent: SignalData<WindowUrgent>,
 
     pub(crate) tag_active: SignalData<TagActive>,
+    pub(crate) tag_urgent: SignalData<TagUrgent>,
+    pub(crate) tag_occupied: SignalData<TagOccupied>,
+    pub(crate) tag_renamed: SignalData<TagRenamed>,
+
+    pub(crate) input_xkb_layout: SignalData<InputXkbLayout>,
 }
 
 impl std::fmt::Debug for SignalState {
@@ -285,7 +377,12 @@ impl SignalState {
             output_move: SignalData::new(client.clone(), fut_sender.clone()),
             window_pointer_enter: SignalData::new(client.clone(), fut_sender.clone()),
             window_pointer_leave: SignalData::new(client.clone(), fut_sender.clone()),
+            window_urgent: SignalData::new(client.clone(), fut_sender.clone()),
             tag_active: SignalData::new(client.clone(), fut_sender.clone()),
+            tag_urgent: SignalData::new(client.clone(), fut_sender.clone()),
+            tag_occupied: SignalData::new(client.clone(), fut_sender.clone()),
+            tag_renamed: SignalData::new(client.clone(), fut_sender.clone()),
+            input_xkb_layout: SignalData::new(client.clone(), fut_sender.clone()),
         }
     }
 
@@ -296,7 +393,12 @@ impl SignalState {
         self.output_move.api.set(api.clone()).unwrap();
         self.window_pointer_enter.api.set(api.clone()).unwrap();
         self.window_pointer_leave.api.set(api.clone()).unwrap();
+        self.window_urgent.api.set(api.clone()).unwrap();
         self.tag_active.api.set(api.clone()).unwrap();
+        self.tag_urgent.api.set(api.clone()).unwrap();
+        self.tag_occupied.api.set(api.clone()).unwrap();
+        self.tag_renamed.api.set(api.clone()).unwrap();
+        self.input_xkb_layout.api.set(api.clone()).unwrap();
     }
 
     pub(crate) fn shutdown(&mut self) {
@@ -306,7 +408,12 @@ impl SignalState {
         self.output_move.reset();
         self.window_pointer_enter.reset();
         self.window_pointer_leave.reset();
+        self.window_urgent.reset();
         self.tag_active.reset();
+        self.tag_urgent.reset();
+        self.tag_occupied.reset();
+        self.tag_renamed.reset();
+        self.input_xkb_layout.reset();
     }
 }
 
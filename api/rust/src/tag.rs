@@ -36,8 +36,10 @@ use pinnacle_api_defs::pinnacle::{
     tag::{
         self,
         v0alpha1::{
-            tag_service_client::TagServiceClient, AddRequest, RemoveRequest, SetActiveRequest,
-            SwitchToRequest,
+            reorder_request, tag_service_client::TagServiceClient, AddRequest, GetHistoryRequest,
+            MoveToOutputRequest, RemoveRequest, ReorderRequest, SetActiveRequest,
+            SetCornerRadiusRequest, SetDynamicRequest, SetGapsRequest, SetLayoutRequest,
+            SetNameRequest, SwitchToPreviousRequest, SwitchToRelativeRequest, SwitchToRequest,
         },
     },
     v0alpha1::SetOrToggle,
@@ -220,6 +222,10 @@ impl Tag {
 
     /// Remove the given tags from their outputs.
     ///
+    /// Windows left without tags are moved to the first active tag left on their output,
+    /// or its first tag if none are active. Outputs left without an active tag activate
+    /// that tag.
+    ///
     /// # Examples
     ///
     /// ```
@@ -232,7 +238,186 @@ impl Tag {
 
         let mut client = self.tag_client.clone();
 
-        block_on_tokio(client.remove(RemoveRequest { tag_ids })).unwrap();
+        block_on_tokio(client.remove(RemoveRequest {
+            tag_ids,
+            fallback_tag_id: None,
+        }))
+        .unwrap();
+    }
+
+    /// Remove the given tags from their outputs, moving windows left without tags
+    /// to `fallback`.
+    ///
+    /// Outputs left without an active tag activate `fallback` if it's on them,
+    /// or their first tag otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Remove an empty-ish workspace, keeping its windows on "1"
+    /// let op = output.get_focused()?;
+    /// tag.remove_with_fallback([tag.get("scratch")?], &tag.get_on_output("1", &op)?);
+    /// ```
+    pub fn remove_with_fallback(
+        &self,
+        tags: impl IntoIterator<Item = TagHandle>,
+        fallback: &TagHandle,
+    ) {
+        let tag_ids = tags.into_iter().map(|handle| handle.id).collect::<Vec<_>>();
+
+        let mut client = self.tag_client.clone();
+
+        block_on_tokio(client.remove(RemoveRequest {
+            tag_ids,
+            fallback_tag_id: Some(fallback.id),
+        }))
+        .unwrap();
+    }
+
+    /// Switch `output` back to the tags that were active on it before.
+    ///
+    /// Switching back repeatedly flips between the two most recently active sets of tags.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// tag.get("1")?.switch_to();
+    /// tag.get("2")?.switch_to();
+    ///
+    /// tag.switch_to_previous(&output.get_focused()?); // Displays "1"
+    /// tag.switch_to_previous(&output.get_focused()?); // Displays "2"
+    /// ```
+    pub fn switch_to_previous(&self, output: &OutputHandle) {
+        self.switch_to_history(output, 0);
+    }
+
+    /// Switch `output` to the `index`th most recent set of tags in its history.
+    ///
+    /// The history is what [`Tag::history`] returns, so an `index` of 0 does the same
+    /// as [`Tag::switch_to_previous`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Go back to the tags that were active two switches ago
+    /// tag.switch_to_history(&output.get_focused()?, 1);
+    /// ```
+    pub fn switch_to_history(&self, output: &OutputHandle, index: u32) {
+        let mut client = self.tag_client.clone();
+        block_on_tokio(client.switch_to_previous(SwitchToPreviousRequest {
+            output_name: Some(output.name.clone()),
+            index: Some(index),
+        }))
+        .unwrap();
+    }
+
+    /// Set whether tags are dynamic, like workspaces in GNOME.
+    ///
+    /// While they are, every output keeps one empty tag after the last tag that has windows
+    /// or is active, adding a new one when that tag gets a window. New tags are named one
+    /// more than the largest numbered tag. Any other empty tags at the end are hidden:
+    /// they're skipped by [`Tag::switch_relative`] and aren't shown by workspace clients.
+    /// Bars using this API can check [`TagHandle::hidden`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// tag.set_dynamic(true);
+    /// tag.add(&output.get_focused()?, ["1"]);
+    /// ```
+    pub fn set_dynamic(&self, enabled: bool) {
+        let mut client = self.tag_client.clone();
+        block_on_tokio(client.set_dynamic(SetDynamicRequest {
+            enabled: Some(enabled),
+        }))
+        .unwrap();
+    }
+
+    /// Switch `output` to the tag after its first active one, wrapping around to the first tag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// tag.get("1")?.switch_to();
+    /// tag.switch_right(&output.get_focused()?); // Displays "2"
+    /// ```
+    pub fn switch_right(&self, output: &OutputHandle) {
+        self.switch_relative(output, 1);
+    }
+
+    /// Switch `output` to the tag before its first active one, wrapping around to the last tag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// tag.get("2")?.switch_to();
+    /// tag.switch_left(&output.get_focused()?); // Displays "1"
+    /// ```
+    pub fn switch_left(&self, output: &OutputHandle) {
+        self.switch_relative(output, -1);
+    }
+
+    /// Switch `output` to the tag `offset` places right of its first active one,
+    /// or left if `offset` is negative, wrapping around the ends.
+    ///
+    /// Places are counted in tag order, which can be changed with [`TagHandle::set_index`].
+    /// If no tag is active, positive offsets count from before the first tag
+    /// and negative ones from after the last.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// tag.get("1")?.switch_to();
+    /// tag.switch_relative(&output.get_focused()?, 2); // Displays "3"
+    /// ```
+    pub fn switch_relative(&self, output: &OutputHandle, offset: i32) {
+        let mut client = self.tag_client.clone();
+        block_on_tokio(client.switch_to_relative(SwitchToRelativeRequest {
+            output_name: Some(output.name.clone()),
+            offset: Some(offset),
+        }))
+        .unwrap();
+    }
+
+    /// Get the sets of tags that were active on `output` before, most recent first.
+    ///
+    /// The currently active tags aren't included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// for tags in tag.history(&output.get_focused()?) {
+    ///     let names = tags.iter().filter_map(|tag| tag.name()).collect::<Vec<_>>();
+    ///     println!("{}", names.join(", "));
+    /// }
+    /// ```
+    pub fn history(&self, output: &OutputHandle) -> Vec<Vec<TagHandle>> {
+        block_on_tokio(self.history_async(output))
+    }
+
+    /// The async version of [`Tag::history`].
+    pub async fn history_async(&self, output: &OutputHandle) -> Vec<Vec<TagHandle>> {
+        let mut client = self.tag_client.clone();
+
+        let response = client
+            .get_history(GetHistoryRequest {
+                output_name: Some(output.name.clone()),
+            })
+            .await
+            .unwrap()
+            .into_inner();
+
+        response
+            .entries
+            .into_iter()
+            .map(|entry| {
+                entry
+                    .tag_ids
+                    .into_iter()
+                    .map(|id| self.new_handle(id))
+                    .collect()
+            })
+            .collect()
     }
 
     /// Connect to a tag signal.
@@ -245,6 +430,9 @@ impl Tag {
 
         match signal {
             TagSignal::Active(f) => signal_state.tag_active.add_callback(f),
+            TagSignal::Urgent(f) => signal_state.tag_urgent.add_callback(f),
+            TagSignal::Occupied(f) => signal_state.tag_occupied.add_callback(f),
+            TagSignal::Renamed(f) => signal_state.tag_renamed.add_callback(f),
         }
     }
 }
@@ -354,8 +542,177 @@ impl TagHandle {
         .unwrap();
     }
 
+    /// Set the gaps around tiled windows while this tag is active, in logical pixels.
+    ///
+    /// `inner` is the gap between neighboring windows and `outer` is the gap between windows
+    /// and the edges of the output and exclusive zones. `None` leaves a gap unchanged.
+    ///
+    /// If multiple tags on an output are active, the largest of their gaps are used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// tag.get("1")?.set_gaps(Some(8), Some(16));
+    /// tag.get("2")?.set_gaps(None, Some(0));
+    /// ```
+    pub fn set_gaps(&self, inner: Option<u32>, outer: Option<u32>) {
+        let mut client = self.tag_client.clone();
+        block_on_tokio(client.set_gaps(SetGapsRequest {
+            tag_id: Some(self.id),
+            inner,
+            outer,
+        }))
+        .unwrap();
+    }
+
+    /// Set the corner radius of windows while this tag is active, in logical pixels,
+    /// or go back to the global one with `None`.
+    ///
+    /// This overrides [`Render::set_corner_radius`][crate::render::Render::set_corner_radius]
+    /// but not window rules. If a window has multiple active tags, the first one
+    /// that sets a radius is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Square corners and no gaps on "code", round corners and wide gaps on "chat"
+    /// let code = tag.get("code")?;
+    /// code.set_corner_radius(Some(0.0));
+    /// code.set_gaps(Some(0), Some(0));
+    ///
+    /// let chat = tag.get("chat")?;
+    /// chat.set_corner_radius(Some(12.0));
+    /// chat.set_gaps(Some(16), Some(32));
+    /// ```
+    pub fn set_corner_radius(&self, radius: Option<f32>) {
+        let mut client = self.tag_client.clone();
+        block_on_tokio(client.set_corner_radius(SetCornerRadiusRequest {
+            tag_id: Some(self.id),
+            radius,
+        }))
+        .unwrap();
+    }
+
+    /// Set the name of the layout this tag uses, or forget it with `None`.
+    ///
+    /// The compositor remembers the name and passes it to the layout manager in
+    /// [`LayoutArgs::layout`][crate::layout::LayoutArgs::layout] when this tag is the first
+    /// focused tag on its output, so switching back to this tag restores its layout.
+    /// [`CyclingLayoutManager`][crate::layout::CyclingLayoutManager] picks the layout
+    /// with this [name][crate::layout::LayoutGenerator::name].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// tag.get("1")?.set_layout(Some("dwindle"));
+    /// tag.get("2")?.set_layout(None);
+    /// ```
+    pub fn set_layout(&self, layout: Option<&str>) {
+        let mut client = self.tag_client.clone();
+        block_on_tokio(client.set_layout(SetLayoutRequest {
+            tag_id: Some(self.id),
+            layout: layout.map(str::to_string),
+        }))
+        .unwrap();
+    }
+
+    /// Rename this tag.
+    ///
+    /// Tags are looked up by their new name with [`Tag::get`] afterwards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Show what's on the tag
+    /// tag.get("3")?.rename("3: web");
+    /// ```
+    pub fn rename(&self, name: impl Into<String>) {
+        let mut client = self.tag_client.clone();
+        block_on_tokio(client.set_name(SetNameRequest {
+            tag_id: Some(self.id),
+            name: Some(name.into()),
+        }))
+        .unwrap();
+    }
+
+    /// Move this tag and its windows to `output`.
+    ///
+    /// Both outputs are laid out again. Floating windows keep their position relative to
+    /// the output. If the old output is left without an active tag, its first tag is activated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Move the focused output's first active tag to "HDMI-1"
+    /// let op = output.get_focused()?;
+    /// if let Some(tg) = op.tags().into_iter().find(|tg| tg.active() == Some(true)) {
+    ///     tg.move_to_output(&output.get_by_name("HDMI-1")?);
+    /// }
+    /// ```
+    pub fn move_to_output(&self, output: &OutputHandle) {
+        let mut client = self.tag_client.clone();
+        block_on_tokio(client.move_to_output(MoveToOutputRequest {
+            tag_id: Some(self.id),
+            output_name: Some(output.name.clone()),
+        }))
+        .unwrap();
+    }
+
+    /// Move this tag to `index` among the tags on its output.
+    ///
+    /// Indices past the last tag move it to the end. Tag order is the order tags are
+    /// returned in, the order [`Tag::switch_relative`] goes through, and the order
+    /// workspace clients show tags in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Make "web" the first tag
+    /// tag.get("web")?.set_index(0);
+    /// ```
+    pub fn set_index(&self, index: u32) {
+        self.reorder(reorder_request::AbsoluteOrRelative::Index(index));
+    }
+
+    /// Move this tag one place left among the tags on its output.
+    ///
+    /// Does nothing if it's already the first tag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// tag.get("3")?.move_left(); // Tags are now "1", "3", "2"
+    /// ```
+    pub fn move_left(&self) {
+        self.reorder(reorder_request::AbsoluteOrRelative::Offset(-1));
+    }
+
+    /// Move this tag one place right among the tags on its output.
+    ///
+    /// Does nothing if it's already the last tag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// tag.get("1")?.move_right(); // Tags are now "2", "1", "3"
+    /// ```
+    pub fn move_right(&self) {
+        self.reorder(reorder_request::AbsoluteOrRelative::Offset(1));
+    }
+
+    fn reorder(&self, absolute_or_relative: reorder_request::AbsoluteOrRelative) {
+        let mut client = self.tag_client.clone();
+        block_on_tokio(client.reorder(ReorderRequest {
+            tag_id: Some(self.id),
+            absolute_or_relative: Some(absolute_or_relative),
+        }))
+        .unwrap();
+    }
+
     /// Remove this tag from its output.
     ///
+    /// Its windows are moved like with [`Tag::remove`].
+    ///
     /// # Examples
     ///
     /// ```
@@ -371,6 +728,7 @@ impl TagHandle {
         let mut tag_client = self.tag_client.clone();
         block_on_tokio(tag_client.remove(RemoveRequest {
             tag_ids: vec![self.id],
+            fallback_tag_id: None,
         }))
         .unwrap();
     }
@@ -416,6 +774,9 @@ impl TagHandle {
                 .into_iter()
                 .map(|id| window.new_handle(id))
                 .collect(),
+            layout: response.layout,
+            urgent: response.urgent,
+            hidden: response.hidden,
         }
     }
 
@@ -466,6 +827,44 @@ impl TagHandle {
     pub async fn windows_async(&self) -> Vec<WindowHandle> {
         self.props_async().await.windows
     }
+
+    /// Get the name of the layout this tag uses.
+    ///
+    /// Shorthand for `self.props().layout`.
+    pub fn layout(&self) -> Option<String> {
+        self.props().layout
+    }
+
+    /// The async version of [`TagHandle::layout`].
+    pub async fn layout_async(&self) -> Option<String> {
+        self.props_async().await.layout
+    }
+
+    /// Get whether any window with this tag wants attention.
+    ///
+    /// Shorthand for `self.props().urgent`.
+    pub fn urgent(&self) -> Option<bool> {
+        self.props().urgent
+    }
+
+    /// The async version of [`TagHandle::urgent`].
+    pub async fn urgent_async(&self) -> Option<bool> {
+        self.props_async().await.urgent
+    }
+
+    /// Get whether this tag is hidden because it's an empty trailing tag in dynamic mode.
+    ///
+    /// See [`Tag::set_dynamic`].
+    ///
+    /// Shorthand for `self.props().hidden`.
+    pub fn hidden(&self) -> Option<bool> {
+        self.props().hidden
+    }
+
+    /// The async version of [`TagHandle::hidden`].
+    pub async fn hidden_async(&self) -> Option<bool> {
+        self.props_async().await.hidden
+    }
 }
 
 /// Properties of a tag.
@@ -479,4 +878,10 @@ pub struct TagProperties {
     pub output: Option<OutputHandle>,
     /// The windows that have this tag
     pub windows: Vec<WindowHandle>,
+    /// The name of the layout this tag uses
+    pub layout: Option<String>,
+    /// Whether any window with this tag wants attention
+    pub urgent: Option<bool>,
+    /// Whether this is an empty trailing tag hidden in dynamic mode
+    pub hidden: Option<bool>,
 }
@@ -12,7 +12,7 @@
 //!
 //! This module also allows you to set window rules; see the [rules] module for more information.
 
-use std::sync::OnceLock;
+use std::{sync::OnceLock, time::Duration};
 
 use futures::FutureExt;
 use num_enum::TryFromPrimitive;
@@ -21,10 +21,17 @@ use pinnacle_api_defs::pinnacle::{
     window::{
         self,
         v0alpha1::{
-            window_service_client::WindowServiceClient, AddWindowRuleRequest, CloseRequest,
-            GetRequest, MoveGrabRequest, MoveToTagRequest, RaiseRequest, ResizeGrabRequest,
-            SetFloatingRequest, SetFocusedRequest, SetFullscreenRequest, SetMaximizedRequest,
-            SetTagRequest,
+            window_service_client::WindowServiceClient, AddToGroupRequest, AddWindowRuleRequest,
+            CloseOrKillRequest, CloseRequest, CycleFocusRequest, CycleGroupRequest,
+            CycleWindowSwitcherRequest, FocusUrgentRequest, ForgetGeometryRequest,
+            GetRememberedGeometriesRequest, GetRequest, LowerRequest, MarkInsertPositionRequest,
+            MoveFocusRequest, MoveGrabRequest, MoveInDirectionRequest, MoveToTagRequest,
+            RaiseRequest, RemoveFromGroupRequest, ResizeGrabRequest, ResizeTileRequest,
+            SetClickToRaiseRequest, SetFakeFullscreenRequest, SetFloatingRequest,
+            SetFocusFollowsMouseRequest, SetFocusedRequest, SetFullscreenRequest,
+            SetGroupModeRequest, SetInsertPositionRequest, SetLayerRequest, SetMaximizedRequest,
+            SetPipPlacementRequest, SetSnapThresholdRequest, SetTagRequest, SetTagsRequest,
+            SetUnresponsiveOverlayRequest, SetWindowSwitcherThumbnailsRequest,
         },
     },
 };
@@ -39,7 +46,7 @@ use crate::{
     ApiModules,
 };
 
-use self::rules::{WindowRule, WindowRuleCondition};
+use self::rules::{WindowRule, WindowRuleCondition, WindowRuleHandle};
 
 pub mod rules;
 
@@ -77,6 +84,9 @@ impl Window {
     /// This will begin moving the window under the pointer using the specified [`MouseButton`].
     /// The button must be held down at the time this method is called for the move to start.
     ///
+    /// Tiled windows are lifted off their tile and follow the pointer, and are swapped with
+    /// the tile they're dropped on.
+    ///
     /// This is intended to be used with [`Input::mousebind`][crate::input::Input::mousebind].
     ///
     /// # Examples
@@ -92,7 +102,7 @@ impl Window {
     pub fn begin_move(&self, button: MouseButton) {
         let mut client = self.window_client.clone();
         if let Err(status) = block_on_tokio(client.move_grab(MoveGrabRequest {
-            button: Some(button as u32),
+            button: Some(button.code()),
         })) {
             eprintln!("ERROR: {status}");
         }
@@ -103,6 +113,9 @@ impl Window {
     /// This will begin resizing the window under the pointer using the specified [`MouseButton`].
     /// The button must be held down at the time this method is called for the resize to start.
     ///
+    /// Tiled windows are resized by adjusting the layout's split ratios;
+    /// see [`WindowHandle::resize_tile`].
+    ///
     /// This is intended to be used with [`Input::mousebind`][crate::input::Input::mousebind].
     ///
     /// # Examples
@@ -118,7 +131,7 @@ impl Window {
     pub fn begin_resize(&self, button: MouseButton) {
         let mut client = self.window_client.clone();
         block_on_tokio(client.resize_grab(ResizeGrabRequest {
-            button: Some(button as u32),
+            button: Some(button.code()),
         }))
         .unwrap();
     }
@@ -173,13 +186,366 @@ impl Window {
     /// For it to apply, a [`WindowRuleCondition`] must evaluate to true for the window in question.
     ///
     /// See the [`rules`] module for more information.
-    pub fn add_window_rule(&self, cond: WindowRuleCondition, rule: WindowRule) {
+    ///
+    /// This returns a [`WindowRuleHandle`] that can remove the rule.
+    pub fn add_window_rule(&self, cond: WindowRuleCondition, rule: WindowRule) -> WindowRuleHandle {
         let mut client = self.window_client.clone();
 
-        block_on_tokio(client.add_window_rule(AddWindowRuleRequest {
+        let id = block_on_tokio(client.add_window_rule(AddWindowRuleRequest {
             cond: Some(cond.0),
             rule: Some(rule.0),
         }))
+        .unwrap()
+        .into_inner()
+        .rule_id
+        .unwrap_or_default();
+
+        WindowRuleHandle {
+            id,
+            window_client: self.window_client.clone(),
+        }
+    }
+
+    /// Set where new windows are inserted into the layout.
+    ///
+    /// The default is [`InsertPosition::End`].
+    /// [`WindowHandle::mark_insert_position`] overrides this for the next window.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::window::InsertPosition;
+    ///
+    /// window.set_insert_position(InsertPosition::AfterFocused);
+    /// ```
+    pub fn set_insert_position(&self, position: InsertPosition) {
+        let mut client = self.window_client.clone();
+
+        block_on_tokio(client.set_insert_position(SetInsertPositionRequest {
+            position: Some(position as i32),
+        }))
+        .unwrap();
+    }
+
+    /// Set where picture-in-picture windows are placed.
+    ///
+    /// Windows titled like browser picture-in-picture players, or that a window rule
+    /// [marks as one][crate::window::rules::WindowRule::pip], are floated above other windows,
+    /// shown on every tag of their output, and pinned to `corner` at the given size.
+    /// They are put back into the corner when their output's resolution changes.
+    ///
+    /// The default is the bottom right corner at 480x270.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::window::PipCorner;
+    ///
+    /// window.set_pip_placement(PipCorner::TopRight, 640, 360);
+    /// ```
+    pub fn set_pip_placement(&self, corner: PipCorner, width: u32, height: u32) {
+        let mut client = self.window_client.clone();
+
+        block_on_tokio(client.set_pip_placement(SetPipPlacementRequest {
+            corner: Some(corner as i32),
+            width: Some(width),
+            height: Some(height),
+        }))
+        .unwrap();
+    }
+
+    /// Set how close in logical pixels floating windows have to get to snap while being moved.
+    ///
+    /// Moved floating windows snap to the edges of outputs, the edges of the area not taken
+    /// up by panels and other layer surfaces, and the edges of other floating windows.
+    /// Guide lines are shown along the edges a window snapped to.
+    ///
+    /// The default is 12. A threshold of 0 disables snapping.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// window.set_snap_threshold(20);
+    /// ```
+    pub fn set_snap_threshold(&self, threshold: u32) {
+        let mut client = self.window_client.clone();
+
+        block_on_tokio(client.set_snap_threshold(SetSnapThresholdRequest {
+            threshold: Some(threshold),
+        }))
+        .unwrap();
+    }
+
+    /// Get the floating geometry remembered for each app, keyed by app id or class.
+    ///
+    /// When a floating window closes, its geometry is remembered, and the next window of the
+    /// same app opens with it unless a window rule sets its size or location.
+    /// Remembered geometries persist across restarts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// for (app_id, geo) in window.remembered_geometries() {
+    ///     println!("{app_id}: {}x{} at {}, {}", geo.width, geo.height, geo.x, geo.y);
+    /// }
+    /// ```
+    pub fn remembered_geometries(&self) -> Vec<(String, Geometry)> {
+        block_on_tokio(self.remembered_geometries_async())
+    }
+
+    /// The async version of [`Window::remembered_geometries`].
+    pub async fn remembered_geometries_async(&self) -> Vec<(String, Geometry)> {
+        let mut client = self.window_client.clone();
+        client
+            .get_remembered_geometries(GetRememberedGeometriesRequest {})
+            .await
+            .unwrap()
+            .into_inner()
+            .geometries
+            .into_iter()
+            .filter_map(|remembered| {
+                let geo = remembered.geometry?;
+                let geo = Geometry {
+                    x: geo.x(),
+                    y: geo.y(),
+                    width: geo.width() as u32,
+                    height: geo.height() as u32,
+                };
+                Some((remembered.app_id?, geo))
+            })
+            .collect()
+    }
+
+    /// Forget the remembered floating geometry of the app with the given app id or class.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// window.forget_geometry("org.gnome.Calculator");
+    /// ```
+    pub fn forget_geometry(&self, app_id: impl Into<String>) {
+        let mut client = self.window_client.clone();
+
+        block_on_tokio(client.forget_geometry(ForgetGeometryRequest {
+            app_id: Some(app_id.into()),
+        }))
+        .unwrap();
+    }
+
+    /// Forget the remembered floating geometry of every app.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// window.forget_all_geometries();
+    /// ```
+    pub fn forget_all_geometries(&self) {
+        let mut client = self.window_client.clone();
+
+        block_on_tokio(client.forget_geometry(ForgetGeometryRequest { app_id: None })).unwrap();
+    }
+
+    /// Focus and raise the next tiled window on the focused output, in layout order.
+    ///
+    /// This is mainly useful with a [`MonocleLayout`][crate::layout::MonocleLayout],
+    /// where only one window is visible at a time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// window.cycle_focus_forward();
+    /// ```
+    pub fn cycle_focus_forward(&self) {
+        let mut client = self.window_client.clone();
+
+        block_on_tokio(client.cycle_focus(CycleFocusRequest {
+            backward: Some(false),
+        }))
+        .unwrap();
+    }
+
+    /// Focus and raise the previous tiled window on the focused output, in layout order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// window.cycle_focus_backward();
+    /// ```
+    pub fn cycle_focus_backward(&self) {
+        let mut client = self.window_client.clone();
+
+        block_on_tokio(client.cycle_focus(CycleFocusRequest {
+            backward: Some(true),
+        }))
+        .unwrap();
+    }
+
+    /// Focus and raise the window nearest to the focused one in the given direction.
+    ///
+    /// Windows on other outputs are also considered, so this moves focus to a neighboring
+    /// output when there's nothing left in that direction on the current one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::window::Direction;
+    ///
+    /// window.move_focus(Direction::Left);
+    /// ```
+    pub fn move_focus(&self, direction: Direction) {
+        let mut client = self.window_client.clone();
+
+        block_on_tokio(client.move_focus(MoveFocusRequest {
+            direction: Some(direction as i32),
+        }))
+        .unwrap();
+    }
+
+    /// Focus and raise the first window that wants attention.
+    ///
+    /// If the window isn't on an active tag, its output switches to the window's first tag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// window.focus_urgent();
+    /// ```
+    pub fn focus_urgent(&self) {
+        let mut client = self.window_client.clone();
+
+        block_on_tokio(client.focus_urgent(FocusUrgentRequest {})).unwrap();
+    }
+
+    /// Open the window switcher, or select the next window if it's already open.
+    ///
+    /// The switcher shows every visible window, most recently focused first, and opens with
+    /// the window focused before the current one selected. It stays open while any modifier
+    /// is held and focuses the selected window once they're all released, so this is meant
+    /// to be called from a keybind with a modifier, like alt+tab. Escape closes the switcher
+    /// without changing focus.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::input::Mod;
+    /// use pinnacle_api::xkbcommon::xkb::Keysym;
+    ///
+    /// input.keybind([Mod::Alt], Keysym::Tab, || {
+    ///     window.cycle_switcher_forward();
+    /// });
+    /// ```
+    pub fn cycle_switcher_forward(&self) {
+        let mut client = self.window_client.clone();
+
+        block_on_tokio(client.cycle_window_switcher(CycleWindowSwitcherRequest {
+            backward: Some(false),
+        }))
+        .unwrap();
+    }
+
+    /// Open the window switcher, or select the previous window if it's already open.
+    ///
+    /// See [`Window::cycle_switcher_forward`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// window.cycle_switcher_backward();
+    /// ```
+    pub fn cycle_switcher_backward(&self) {
+        let mut client = self.window_client.clone();
+
+        block_on_tokio(client.cycle_window_switcher(CycleWindowSwitcherRequest {
+            backward: Some(true),
+        }))
+        .unwrap();
+    }
+
+    /// Set whether the window switcher shows thumbnails of windows.
+    ///
+    /// Thumbnails are shown by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// window.set_switcher_thumbnails(false);
+    /// ```
+    pub fn set_switcher_thumbnails(&self, thumbnails: bool) {
+        let mut client = self.window_client.clone();
+
+        block_on_tokio(
+            client.set_window_switcher_thumbnails(SetWindowSwitcherThumbnailsRequest {
+                thumbnails: Some(thumbnails),
+            }),
+        )
+        .unwrap();
+    }
+
+    /// Set whether clicking a window raises it.
+    ///
+    /// Clicking a window always focuses it. Windows are raised on click by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Only raise windows with `WindowHandle::raise`
+    /// window.set_click_to_raise(false);
+    /// ```
+    pub fn set_click_to_raise(&self, click_to_raise: bool) {
+        let mut client = self.window_client.clone();
+
+        block_on_tokio(client.set_click_to_raise(SetClickToRaiseRequest {
+            click_to_raise: Some(click_to_raise),
+        }))
+        .unwrap();
+    }
+
+    /// Focus windows the pointer moves onto after it stays on them for `delay`,
+    /// or stop focusing them with `None`.
+    ///
+    /// Focus doesn't move when the layout moves a window under the pointer, only when the pointer
+    /// moves onto one. Hovered windows aren't raised. Focus doesn't follow the mouse by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// // Focus windows right away
+    /// window.set_focus_follows_mouse(Some(Duration::ZERO));
+    ///
+    /// // Don't focus windows the pointer only passes over
+    /// window.set_focus_follows_mouse(Some(Duration::from_millis(150)));
+    /// ```
+    pub fn set_focus_follows_mouse(&self, delay: Option<Duration>) {
+        let mut client = self.window_client.clone();
+
+        block_on_tokio(client.set_focus_follows_mouse(SetFocusFollowsMouseRequest {
+            enabled: Some(delay.is_some()),
+            delay_ms: delay.map(|delay| delay.as_millis() as u32),
+        }))
+        .unwrap();
+    }
+
+    /// Set whether windows of unresponsive clients are darkened.
+    ///
+    /// A client is unresponsive when it doesn't answer the compositor's pings for a few seconds,
+    /// usually because it's frozen. Its windows stay darkened until it answers again.
+    /// Only Wayland clients are pinged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// window.set_unresponsive_overlay(true);
+    /// ```
+    pub fn set_unresponsive_overlay(&self, enabled: bool) {
+        let mut client = self.window_client.clone();
+
+        block_on_tokio(
+            client.set_unresponsive_overlay(SetUnresponsiveOverlayRequest {
+                enabled: Some(enabled),
+            }),
+        )
         .unwrap();
     }
 
@@ -194,6 +560,7 @@ impl Window {
         match signal {
             WindowSignal::PointerEnter(f) => signal_state.window_pointer_enter.add_callback(f),
             WindowSignal::PointerLeave(f) => signal_state.window_pointer_leave.add_callback(f),
+            WindowSignal::Urgent(f) => signal_state.window_urgent.add_callback(f),
         }
     }
 }
@@ -203,7 +570,7 @@ impl Window {
 /// This allows you to manipulate the window and get its properties.
 #[derive(Debug, Clone)]
 pub struct WindowHandle {
-    id: u32,
+    pub(crate) id: u32,
     window_client: WindowServiceClient<Channel>,
     api: ApiModules,
 }
@@ -234,6 +601,94 @@ pub enum FullscreenOrMaximized {
     Maximized,
 }
 
+/// How a window group's indicator shows its windows.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, TryFromPrimitive)]
+pub enum GroupMode {
+    /// Windows are shown as tabs next to each other
+    Tabbed = 1,
+    /// Windows are shown as rows stacked on top of each other
+    Stacked,
+}
+
+/// Where new windows are inserted into the layout.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, TryFromPrimitive)]
+pub enum InsertPosition {
+    /// After every other window
+    End = 1,
+    /// Right after the focused window
+    AfterFocused,
+    /// Before every other window, making it the master window
+    Master,
+}
+
+/// The part of an output a window rule places a floating window at.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, TryFromPrimitive)]
+pub enum FloatingAnchor {
+    /// The center of the output
+    Center = 1,
+    /// The middle of the top edge
+    Top,
+    /// The middle of the bottom edge
+    Bottom,
+    /// The middle of the left edge
+    Left,
+    /// The middle of the right edge
+    Right,
+    /// The top left corner
+    TopLeft,
+    /// The top right corner
+    TopRight,
+    /// The bottom left corner
+    BottomLeft,
+    /// The bottom right corner
+    BottomRight,
+}
+
+/// Where a floating window is stacked relative to other windows.
+///
+/// Tiled windows are always stacked in the normal layer.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, TryFromPrimitive)]
+pub enum WindowLayer {
+    /// Stacked with other windows
+    Normal = 1,
+    /// Always above windows in the normal layer
+    Above,
+    /// Always below windows in the normal layer
+    Below,
+}
+
+/// The corner of an output picture-in-picture windows are pinned to.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, TryFromPrimitive)]
+pub enum PipCorner {
+    /// The top left corner
+    TopLeft = 1,
+    /// The top right corner
+    TopRight,
+    /// The bottom left corner
+    BottomLeft,
+    /// The bottom right corner
+    BottomRight,
+}
+
+/// A direction to move focus or windows in.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, TryFromPrimitive)]
+pub enum Direction {
+    /// Left
+    Left = 1,
+    /// Right
+    Right,
+    /// Up
+    Up,
+    /// Down
+    Down,
+}
+
 /// Properties of a window.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
 pub struct WindowProperties {
@@ -254,6 +709,10 @@ pub struct WindowProperties {
     pub fullscreen_or_maximized: Option<FullscreenOrMaximized>,
     /// All the tags on the window
     pub tags: Vec<TagHandle>,
+    /// The layer the window is stacked in while floating
+    pub layer: Option<WindowLayer>,
+    /// Whether the window wants attention
+    pub urgent: Option<bool>,
 }
 
 impl WindowHandle {
@@ -275,6 +734,29 @@ impl WindowHandle {
         .unwrap();
     }
 
+    /// Send a close request to this window, killing its process if it's still open
+    /// after `timeout`.
+    ///
+    /// This closes windows that don't respond to [`WindowHandle::close`].
+    /// The process is killed with `SIGKILL`, so any unsaved work in it is lost.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// // Close the focused window, killing it if it doesn't close within 3 seconds
+    /// window.get_focused()?.close_or_kill(Duration::from_secs(3));
+    /// ```
+    pub fn close_or_kill(&self, timeout: Duration) {
+        let mut window_client = self.window_client.clone();
+        block_on_tokio(window_client.close_or_kill(CloseOrKillRequest {
+            window_id: Some(self.id),
+            timeout_ms: Some(u32::try_from(timeout.as_millis()).unwrap_or(u32::MAX)),
+        }))
+        .unwrap();
+    }
+
     /// Set this window to fullscreen or not.
     ///
     /// If it is maximized, setting it to fullscreen will remove the maximized state.
@@ -316,8 +798,53 @@ impl WindowHandle {
         .unwrap();
     }
 
+    /// Set whether fullscreen requests from this window only tell it that it's fullscreen.
+    ///
+    /// While set, a window that asks to be fullscreen, like a video player, thinks it is but
+    /// stays in its tile or floating geometry. This does not affect setting it to fullscreen
+    /// through this API.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Fullscreen videos in the focused window inside the window
+    /// window.get_focused()?.set_fake_fullscreen(true);
+    /// ```
+    pub fn set_fake_fullscreen(&self, set: bool) {
+        let mut client = self.window_client.clone();
+        block_on_tokio(client.set_fake_fullscreen(SetFakeFullscreenRequest {
+            window_id: Some(self.id),
+            set_or_toggle: Some(match set {
+                true => SetOrToggle::Set,
+                false => SetOrToggle::Unset,
+            } as i32),
+        }))
+        .unwrap();
+    }
+
+    /// Toggle whether fullscreen requests from this window only tell it that it's fullscreen.
+    ///
+    /// See [`WindowHandle::set_fake_fullscreen`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// window.get_focused()?.toggle_fake_fullscreen();
+    /// ```
+    pub fn toggle_fake_fullscreen(&self) {
+        let mut client = self.window_client.clone();
+        block_on_tokio(client.set_fake_fullscreen(SetFakeFullscreenRequest {
+            window_id: Some(self.id),
+            set_or_toggle: Some(SetOrToggle::Toggle as i32),
+        }))
+        .unwrap();
+    }
+
     /// Set this window to maximized or not.
     ///
+    /// Maximized windows fill their output except for the space taken up by panels and other
+    /// layer surfaces. Floating windows get their previous geometry back when unmaximized.
+    ///
     /// If it is fullscreen, setting it to maximized will remove the fullscreen state.
     ///
     /// # Examples
@@ -404,6 +931,29 @@ impl WindowHandle {
         .unwrap();
     }
 
+    /// Set the layer this window is stacked in while it's floating.
+    ///
+    /// Windows in the above layer always stay above normal windows, and windows
+    /// in the below layer always stay below them. Tiled windows are always stacked
+    /// in the normal layer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::window::WindowLayer;
+    ///
+    /// // Keep the focused window above other windows
+    /// window.get_focused()?.set_layer(WindowLayer::Above);
+    /// ```
+    pub fn set_layer(&self, layer: WindowLayer) {
+        let mut client = self.window_client.clone();
+        block_on_tokio(client.set_layer(SetLayerRequest {
+            window_id: Some(self.id),
+            layer: Some(layer as i32),
+        }))
+        .unwrap();
+    }
+
     /// Focus or unfocus this window.
     ///
     /// # Examples
@@ -464,8 +1014,33 @@ impl WindowHandle {
         .unwrap();
     }
 
+    /// Move this window in the given direction.
+    ///
+    /// Tiled windows swap places with the nearest tiled window in that direction on the same
+    /// output. Floating windows are pushed a small step in that direction.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::window::Direction;
+    ///
+    /// window.get_focused()?.move_in_direction(Direction::Right);
+    /// ```
+    pub fn move_in_direction(&self, direction: Direction) {
+        let mut client = self.window_client.clone();
+
+        block_on_tokio(client.move_in_direction(MoveInDirectionRequest {
+            window_id: Some(self.id),
+            direction: Some(direction as i32),
+        }))
+        .unwrap();
+    }
+
     /// Set or unset a tag on this window.
     ///
+    /// Windows can have several tags and show up while any of them is active.
+    /// Setting a tag on another output moves this window there, dropping its other tags.
+    ///
     /// # Examples
     ///
     /// ```
@@ -489,6 +1064,27 @@ impl WindowHandle {
         .unwrap();
     }
 
+    /// Replace this window's tags.
+    ///
+    /// This window shows up while any of `tags` is active. Tags on a different output
+    /// than the first one are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Show the focused window on both "1" and "3"
+    /// window.get_focused()?.set_tags([tag.get("1")?, tag.get("3")?]);
+    /// ```
+    pub fn set_tags(&self, tags: impl IntoIterator<Item = TagHandle>) {
+        let mut client = self.window_client.clone();
+
+        block_on_tokio(client.set_tags(SetTagsRequest {
+            window_id: Some(self.id),
+            tag_ids: tags.into_iter().map(|tag| tag.id).collect(),
+        }))
+        .unwrap();
+    }
+
     /// Toggle a tag on this window.
     ///
     /// # Examples
@@ -531,6 +1127,165 @@ impl WindowHandle {
         .unwrap();
     }
 
+    /// Lower this window.
+    ///
+    /// This will lower this window all the way to the bottom of the z-stack,
+    /// though windows in the below [layer][WindowLayer] stay below it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// window.get_focused()?.lower();
+    /// ```
+    pub fn lower(&self) {
+        let mut client = self.window_client.clone();
+
+        block_on_tokio(client.lower(LowerRequest {
+            window_id: Some(self.id),
+        }))
+        .unwrap();
+    }
+
+    /// Add this window to `target`'s group, creating a group if `target` isn't in one.
+    ///
+    /// Windows in a group share one tile, and only the group's active window is shown.
+    /// An indicator above the group shows its windows as tabs or stacked rows.
+    /// This window becomes the group's active window.
+    ///
+    /// If `target` is `None`, the next tiled window on the same output is used.
+    /// Only tiled windows can be grouped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Group the focused window with the next one
+    /// window.get_focused()?.add_to_group(None);
+    /// ```
+    pub fn add_to_group(&self, target: Option<&WindowHandle>) {
+        let mut client = self.window_client.clone();
+
+        block_on_tokio(client.add_to_group(AddToGroupRequest {
+            window_id: Some(self.id),
+            target_window_id: target.map(|target| target.id),
+        }))
+        .unwrap();
+    }
+
+    /// Remove this window from its group, giving it its own tile again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// window.get_focused()?.remove_from_group();
+    /// ```
+    pub fn remove_from_group(&self) {
+        let mut client = self.window_client.clone();
+
+        block_on_tokio(client.remove_from_group(RemoveFromGroupRequest {
+            window_id: Some(self.id),
+        }))
+        .unwrap();
+    }
+
+    /// Set how the indicator of this window's group shows its windows.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::window::GroupMode;
+    ///
+    /// window.get_focused()?.set_group_mode(GroupMode::Stacked);
+    /// ```
+    pub fn set_group_mode(&self, mode: GroupMode) {
+        let mut client = self.window_client.clone();
+
+        block_on_tokio(client.set_group_mode(SetGroupModeRequest {
+            window_id: Some(self.id),
+            mode: Some(mode as i32),
+        }))
+        .unwrap();
+    }
+
+    /// Show and focus the next window in this window's group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// window.get_focused()?.cycle_group_forward();
+    /// ```
+    pub fn cycle_group_forward(&self) {
+        let mut client = self.window_client.clone();
+
+        block_on_tokio(client.cycle_group(CycleGroupRequest {
+            window_id: Some(self.id),
+            backward: Some(false),
+        }))
+        .unwrap();
+    }
+
+    /// Show and focus the previous window in this window's group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// window.get_focused()?.cycle_group_backward();
+    /// ```
+    pub fn cycle_group_backward(&self) {
+        let mut client = self.window_client.clone();
+
+        block_on_tokio(client.cycle_group(CycleGroupRequest {
+            window_id: Some(self.id),
+            backward: Some(true),
+        }))
+        .unwrap();
+    }
+
+    /// Resize this window if it's tiled by adjusting the layout's split ratios.
+    ///
+    /// The deltas are how much wider and taller the window should get, in logical pixels.
+    /// Negative values shrink it. The layout manager is asked to adjust the active layout
+    /// and keeps the new ratios; see [`LayoutGenerator::resize_tile`].
+    ///
+    /// This does nothing if no layout manager is set.
+    ///
+    /// [`LayoutGenerator::resize_tile`]: crate::layout::LayoutGenerator::resize_tile
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Make the focused window 50 pixels wider
+    /// window.get_focused()?.resize_tile(50, 0);
+    /// ```
+    pub fn resize_tile(&self, width_delta: i32, height_delta: i32) {
+        let mut client = self.window_client.clone();
+
+        block_on_tokio(client.resize_tile(ResizeTileRequest {
+            window_id: Some(self.id),
+            width_delta: Some(width_delta),
+            height_delta: Some(height_delta),
+        }))
+        .unwrap();
+    }
+
+    /// Open the next new window right after this one, once.
+    ///
+    /// This overrides the [insert position][Window::set_insert_position] for the next window.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // The next window opens after the focused window
+    /// window.get_focused()?.mark_insert_position();
+    /// ```
+    pub fn mark_insert_position(&self) {
+        let mut client = self.window_client.clone();
+
+        block_on_tokio(client.mark_insert_position(MarkInsertPositionRequest {
+            window_id: Some(self.id),
+        }))
+        .unwrap();
+    }
+
     /// Get all properties of this window.
     ///
     /// # Examples
@@ -546,6 +1301,8 @@ impl WindowHandle {
     ///     floating,
     ///     fullscreen_or_maximized,
     ///     tags,
+    ///     layer,
+    ///     urgent,
     /// } = window.get_focused()?.props();
     /// ```
     pub fn props(&self) -> WindowProperties {
@@ -575,6 +1332,8 @@ impl WindowHandle {
             .try_into()
             .ok();
 
+        let layer = response.layer.unwrap_or_default().try_into().ok();
+
         let geometry = response.geometry.map(|geo| Geometry {
             x: geo.x(),
             y: geo.y(),
@@ -594,6 +1353,8 @@ impl WindowHandle {
                 .into_iter()
                 .map(|id| self.api.tag.new_handle(id))
                 .collect(),
+            layer,
+            urgent: response.urgent,
         }
     }
 
@@ -680,4 +1441,32 @@ impl WindowHandle {
     pub async fn tags_async(&self) -> Vec<TagHandle> {
         self.props_async().await.tags
     }
+
+    /// Get the layer this window is stacked in while floating.
+    ///
+    /// Shorthand for `self.props().layer`.
+    pub fn layer(&self) -> Option<WindowLayer> {
+        self.props().layer
+    }
+
+    /// The async version of [`layer`][Self::layer].
+    pub async fn layer_async(&self) -> Option<WindowLayer> {
+        self.props_async().await.layer
+    }
+
+    /// Get whether or not this window wants attention.
+    ///
+    /// Windows want attention when their client asks to be activated while they aren't focused,
+    /// or when an X11 client sets the urgency hint on them. They stop wanting attention
+    /// once they're focused.
+    ///
+    /// Shorthand for `self.props().urgent`.
+    pub fn urgent(&self) -> Option<bool> {
+        self.props().urgent
+    }
+
+    /// The async version of [`urgent`][Self::urgent].
+    pub async fn urgent_async(&self) -> Option<bool> {
+        self.props_async().await.urgent
+    }
 }
@@ -5,6 +5,8 @@
 //! Types for window rules.
 //!
 //! A window rule is a way to set the properties of a window on open.
+//! Rules are also checked again when a window's title or class changes, and any that
+//! start applying to it then are applied as well.
 //!
 //! They are comprised of two parts: the [condition][WindowRuleCondition] and the actual [rule][WindowRule].
 //!
@@ -210,11 +212,15 @@
 //! Note: this only applies to floating windows; tiled windows' geometry will be overridden by
 //! layouting.
 
-use pinnacle_api_defs::pinnacle::window;
+use pinnacle_api_defs::pinnacle::window::{
+    self,
+    v0alpha1::{window_service_client::WindowServiceClient, RemoveWindowRuleRequest},
+};
+use tonic::transport::Channel;
 
-use crate::{output::OutputHandle, tag::TagHandle};
+use crate::{block_on_tokio, output::OutputHandle, tag::TagHandle};
 
-use super::FullscreenOrMaximized;
+use super::{FloatingAnchor, FullscreenOrMaximized, WindowLayer};
 
 /// A condition for a [`WindowRule`] to apply to a window.
 ///
@@ -362,6 +368,172 @@ impl WindowRuleCondition {
         self.0.tags = tags.into_iter().map(|tag| tag.id).collect();
         self
     }
+
+    /// This condition requires that none of the provided conditions are true.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::window::rules::WindowRuleCondition;
+    ///
+    /// // `cond` will be true if the window is neither "Alacritty" nor "firefox"
+    /// let cond = WindowRuleCondition::new()
+    ///     .none([WindowRuleCondition::new().any([
+    ///         WindowRuleCondition::new().classes(["Alacritty", "firefox"]),
+    ///     ])]);
+    /// ```
+    pub fn none(mut self, conds: impl IntoIterator<Item = WindowRuleCondition>) -> Self {
+        self.0.none = conds.into_iter().map(|cond| cond.0).collect();
+        self
+    }
+
+    /// This condition requires that the window's class matches the given regexes.
+    ///
+    /// Regexes use the syntax of the [`regex`](https://docs.rs/regex) crate and match anywhere
+    /// in the class unless anchored with `^` and `$`. Invalid regexes make adding the window
+    /// rule fail.
+    ///
+    /// Like [`WindowRuleCondition::classes`], all regexes must match in a top level condition
+    /// or inside of [`WindowRuleCondition::all`], and one must match inside of
+    /// [`WindowRuleCondition::any`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::window::rules::WindowRuleCondition;
+    ///
+    /// // `cond` will be true for all JetBrains IDEs
+    /// let cond = WindowRuleCondition::new().class_regexes(["^jetbrains-"]);
+    /// ```
+    pub fn class_regexes(mut self, regexes: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.0.class_regexes = regexes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// This condition requires that the window's title matches the given regexes.
+    ///
+    /// See [`WindowRuleCondition::class_regexes`] for how regexes are matched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::window::rules::WindowRuleCondition;
+    ///
+    /// // `cond` will be true for windows with "Picture-in-Picture" anywhere in their title
+    /// let cond = WindowRuleCondition::new().title_regexes(["Picture-in-Picture"]);
+    /// ```
+    pub fn title_regexes(mut self, regexes: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.0.title_regexes = regexes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// This condition requires that the window is an Xwayland window whose `WM_CLASS` class
+    /// or instance matches the given regexes.
+    ///
+    /// See [`WindowRuleCondition::class_regexes`] for how regexes are matched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::window::rules::WindowRuleCondition;
+    ///
+    /// let cond = WindowRuleCondition::new().x11_classes(["^steam$"]);
+    /// ```
+    pub fn x11_classes(mut self, regexes: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.0.x11_classes = regexes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// This condition requires that the window is an Xwayland window whose `WM_WINDOW_ROLE`
+    /// matches the given regexes.
+    ///
+    /// See [`WindowRuleCondition::class_regexes`] for how regexes are matched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::window::rules::WindowRuleCondition;
+    ///
+    /// // GIMP's tool windows
+    /// let cond = WindowRuleCondition::new().x11_roles(["^gimp-toolbox"]);
+    /// ```
+    pub fn x11_roles(mut self, regexes: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.0.x11_roles = regexes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// This condition requires that the window is opened on the given outputs.
+    ///
+    /// Like [`WindowRuleCondition::tags`], the window must be on all outputs in a top level
+    /// condition or inside of [`WindowRuleCondition::all`], and on one of them inside of
+    /// [`WindowRuleCondition::any`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::window::rules::WindowRuleCondition;
+    ///
+    /// let cond = WindowRuleCondition::new().outputs([&output.get_by_name("HDMI-1")?]);
+    /// ```
+    pub fn outputs<'a>(mut self, outputs: impl IntoIterator<Item = &'a OutputHandle>) -> Self {
+        self.0.outputs = outputs
+            .into_iter()
+            .map(|output| output.name.clone())
+            .collect();
+        self
+    }
+
+    /// This condition requires that the window is floating if `floating` is `true`,
+    /// or tiled if it's `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::window::rules::WindowRuleCondition;
+    ///
+    /// // `cond` will be true for floating Alacritty windows
+    /// let cond = WindowRuleCondition::new().classes(["Alacritty"]).floating(true);
+    /// ```
+    pub fn floating(mut self, floating: bool) -> Self {
+        self.0.floating = Some(floating);
+        self
+    }
+}
+
+/// A handle to an added window rule.
+///
+/// Returned by [`Window::add_window_rule`][crate::window::Window::add_window_rule].
+#[derive(Debug, Clone)]
+pub struct WindowRuleHandle {
+    pub(super) id: u32,
+    pub(super) window_client: WindowServiceClient<Channel>,
+}
+
+impl WindowRuleHandle {
+    /// Remove this window rule.
+    ///
+    /// Windows it was already applied to keep what it set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::window::rules::{WindowRule, WindowRuleCondition};
+    ///
+    /// let rule = window.add_window_rule(
+    ///     WindowRuleCondition::new().classes(["firefox"]),
+    ///     WindowRule::new().floating(true),
+    /// );
+    ///
+    /// rule.remove();
+    /// ```
+    pub fn remove(&self) {
+        let mut client = self.window_client.clone();
+
+        block_on_tokio(client.remove_window_rule(RemoveWindowRuleRequest {
+            rule_id: Some(self.id),
+        }))
+        .unwrap();
+    }
 }
 
 /// A window rule.
@@ -522,4 +694,179 @@ impl WindowRule {
         self.0.height = Some(height as i32);
         self
     }
+
+    /// This rule will place floating windows at part of their output, like its center
+    /// or one of its corners.
+    ///
+    /// Windows are kept out of areas reserved by layer-shell clients like bars.
+    /// [`WindowRule::x`] and [`WindowRule::y`] offset the window from the anchored location.
+    ///
+    /// Floating windows with a [`WindowRule::width`] and [`WindowRule::height`] are asked to
+    /// open with that size, so they appear where they're placed without resizing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::window::{rules::WindowRule, FloatingAnchor};
+    ///
+    /// // A dropdown terminal along the top of the output
+    /// let rule = WindowRule::new()
+    ///     .floating(true)
+    ///     .width(1200)
+    ///     .height(400)
+    ///     .anchor(FloatingAnchor::Top);
+    /// ```
+    pub fn anchor(mut self, anchor: FloatingAnchor) -> Self {
+        self.0.anchor = Some(anchor as i32);
+        self
+    }
+
+    /// This rule will enable or disable animations for windows.
+    ///
+    /// This only has an effect if animations are enabled globally.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::window::rules::WindowRule;
+    ///
+    /// // Don't animate the window
+    /// let rule = WindowRule::new().animations(false);
+    /// ```
+    pub fn animations(mut self, animations: bool) -> Self {
+        self.0.animations = Some(animations);
+        self
+    }
+
+    /// This rule will force windows to have a specific corner radius in logical pixels.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::window::rules::WindowRule;
+    ///
+    /// // Give the window square corners
+    /// let rule = WindowRule::new().corner_radius(0.0);
+    /// ```
+    pub fn corner_radius(mut self, radius: f32) -> Self {
+        self.0.corner_radius = Some(radius);
+        self
+    }
+
+    /// This rule will blur the background behind windows.
+    ///
+    /// This is only visible on windows with transparent parts.
+    /// Set how strong the blur is with [`Render::set_blur`][crate::render::Render::set_blur].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::window::rules::WindowRule;
+    ///
+    /// let rule = WindowRule::new().blur(true);
+    /// ```
+    pub fn blur(mut self, blur: bool) -> Self {
+        self.0.blur = Some(blur);
+        self
+    }
+
+    /// This rule will set whether windows are dimmed when they aren't focused.
+    ///
+    /// Set how much windows are dimmed with
+    /// [`Render::set_dim_inactive`][crate::render::Render::set_dim_inactive].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::window::rules::WindowRule;
+    ///
+    /// // Never dim the window
+    /// let rule = WindowRule::new().dim(false);
+    /// ```
+    pub fn dim(mut self, dim: bool) -> Self {
+        self.0.dim = Some(dim);
+        self
+    }
+
+    /// This rule will set the layer windows are stacked in while floating.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::window::{rules::WindowRule, WindowLayer};
+    ///
+    /// // Keep windows above other windows
+    /// let rule = WindowRule::new().floating(true).layer(WindowLayer::Above);
+    /// ```
+    pub fn layer(mut self, layer: WindowLayer) -> Self {
+        self.0.layer = Some(layer as i32);
+        self
+    }
+
+    /// This rule will make windows picture-in-picture windows, or never treat them as one.
+    ///
+    /// Windows titled like browser picture-in-picture players are detected automatically.
+    /// See [`Window::set_pip_placement`][crate::window::Window::set_pip_placement]
+    /// for what happens to them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::window::rules::{WindowRule, WindowRuleCondition};
+    ///
+    /// // Pin mpv's windows to a corner
+    /// window.add_window_rule(
+    ///     WindowRuleCondition::new().classes(["mpv"]),
+    ///     WindowRule::new().pip(true),
+    /// );
+    /// ```
+    pub fn pip(mut self, pip: bool) -> Self {
+        self.0.pip = Some(pip);
+        self
+    }
+
+    /// This rule will make fullscreen requests from windows only tell them they're
+    /// fullscreen, keeping them in their tile or floating geometry.
+    ///
+    /// See [`WindowHandle::set_fake_fullscreen`][crate::window::WindowHandle::set_fake_fullscreen].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::window::rules::{WindowRule, WindowRuleCondition};
+    ///
+    /// // Fullscreen videos in Firefox inside the window
+    /// window.add_window_rule(
+    ///     WindowRuleCondition::new().classes(["firefox"]),
+    ///     WindowRule::new().fake_fullscreen(true),
+    /// );
+    /// ```
+    pub fn fake_fullscreen(mut self, fake_fullscreen: bool) -> Self {
+        self.0.fake_fullscreen = Some(fake_fullscreen);
+        self
+    }
+
+    /// This rule will switch to windows when they open, activating their first tag
+    /// and focusing them.
+    ///
+    /// Without this, windows opened with [`WindowRule::tags`] on inactive tags open in
+    /// the background.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::window::rules::{WindowRule, WindowRuleCondition};
+    ///
+    /// // Open Firefox on tag "2" of "DP-1" and switch to it
+    /// window.add_window_rule(
+    ///     WindowRuleCondition::new().classes(["firefox"]),
+    ///     WindowRule::new()
+    ///         .tags([&tag.get("2", &output.get_by_name("DP-1")?)?])
+    ///         .follow(true),
+    /// );
+    /// ```
+    pub fn follow(mut self, follow: bool) -> Self {
+        self.0.follow = Some(follow);
+        self
+    }
 }
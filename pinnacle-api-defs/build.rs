@@ -16,6 +16,8 @@ fn main() {
         formatcp!("../api/protocol/pinnacle/signal/{VERSION}/signal.proto"),
         formatcp!("../api/protocol/pinnacle/layout/{VERSION}/layout.proto"),
         formatcp!("../api/protocol/pinnacle/render/{VERSION}/render.proto"),
+        formatcp!("../api/protocol/pinnacle/screenshot/{VERSION}/screenshot.proto"),
+        formatcp!("../api/protocol/pinnacle/debug/{VERSION}/debug.proto"),
     ];
 
     let descriptor_path = PathBuf::from(std::env::var("OUT_DIR").unwrap()).join("pinnacle.bin");
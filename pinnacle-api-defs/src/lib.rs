@@ -68,7 +68,12 @@ pub mod pinnacle {
                 OutputMoveRequest,
                 WindowPointerEnterRequest,
                 WindowPointerLeaveRequest,
-                TagActiveRequest
+                WindowUrgentRequest,
+                TagActiveRequest,
+                TagUrgentRequest,
+                TagOccupiedRequest,
+                TagRenamedRequest,
+                InputXkbLayoutRequest
             );
         }
     }
@@ -84,6 +89,18 @@ pub mod pinnacle {
             tonic::include_proto!("pinnacle.render.v0alpha1");
         }
     }
+
+    pub mod screenshot {
+        pub mod v0alpha1 {
+            tonic::include_proto!("pinnacle.screenshot.v0alpha1");
+        }
+    }
+
+    pub mod debug {
+        pub mod v0alpha1 {
+            tonic::include_proto!("pinnacle.debug.v0alpha1");
+        }
+    }
 }
 
 pub const FILE_DESCRIPTOR_SET: &[u8] = tonic::include_file_descriptor_set!("pinnacle");
@@ -0,0 +1,427 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Window animations.
+//!
+//! Animations are sampled by the renderer every frame. Windows fade and scale in when they
+//! open, fade in and out when tags are switched, slide between geometries when the layout
+//! changes, and fade and scale out when they are closed through the API.
+//!
+//! Windows that close on their own are unmapped by the time the compositor knows about it,
+//! so they disappear without an animation.
+
+use std::time::{Duration, Instant};
+
+use smithay::{
+    desktop::{space::SpaceElement, WindowSurface},
+    reexports::calloop::timer::{TimeoutAction, Timer},
+    utils::{Logical, Point, Rectangle, Size},
+};
+use tracing::error;
+
+use crate::{
+    state::{Pinnacle, WithState},
+    window::WindowElement,
+};
+
+/// How much a window is scaled down when it opens or closes.
+const OPEN_CLOSE_SCALE: f64 = 0.9;
+
+/// How long a closed window stays hidden waiting for its client to unmap it.
+///
+/// If the client doesn't close, for example because it's asking to save changes,
+/// the window is shown again after this.
+const CLOSE_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// A timing curve, mapping linear progress to eased progress.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Curve {
+    Linear,
+    #[default]
+    EaseOutCubic,
+    EaseInOutCubic,
+}
+
+impl Curve {
+    /// Apply this curve to linear progress `t` in `0.0..=1.0`.
+    pub fn apply(self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Curve::Linear => t,
+            Curve::EaseOutCubic => 1.0 - (1.0 - t).powi(3),
+            Curve::EaseInOutCubic => {
+                if t < 0.5 {
+                    4.0 * t.powi(3)
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+        }
+    }
+}
+
+/// A single running animation.
+#[derive(Debug, Clone, Copy)]
+pub struct Animation {
+    start: Instant,
+    duration: Duration,
+    curve: Curve,
+}
+
+impl Animation {
+    /// Start a new animation now.
+    pub fn new(duration: Duration, curve: Curve) -> Self {
+        Self {
+            start: Instant::now(),
+            duration,
+            curve,
+        }
+    }
+
+    /// The eased progress of this animation, from 0.0 to 1.0.
+    pub fn progress(&self) -> f64 {
+        if self.duration.is_zero() {
+            return 1.0;
+        }
+        let t = self.start.elapsed().as_secs_f64() / self.duration.as_secs_f64();
+        self.curve.apply(t)
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.start.elapsed() >= self.duration
+    }
+}
+
+/// Global animation settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnimationConfig {
+    pub enabled: bool,
+    pub duration: Duration,
+    pub curve: Curve,
+}
+
+impl Default for AnimationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            duration: Duration::from_millis(200),
+            curve: Curve::default(),
+        }
+    }
+}
+
+impl AnimationConfig {
+    fn animation(&self) -> Animation {
+        Animation::new(self.duration, self.curve)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VisibilityChange {
+    /// The window was just mapped.
+    Open,
+    /// The window was closed through the API.
+    Close,
+    /// One of the window's tags became active.
+    Show,
+    /// All of the window's tags became inactive.
+    Hide,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct GeometryAnimation {
+    from: Rectangle<i32, Logical>,
+    animation: Animation,
+    /// Whether the window has moved away from `from` yet.
+    ///
+    /// Windows are only remapped once they commit their new size, so the animation
+    /// starts then instead of when the layout was applied.
+    started: bool,
+}
+
+/// A window's running animations.
+#[derive(Debug, Default)]
+pub struct WindowAnimations {
+    /// Whether a window rule disabled animations for this window.
+    pub disabled: bool,
+    visibility: Option<(VisibilityChange, Animation)>,
+    geometry: Option<GeometryAnimation>,
+    /// Whether the window was shown last time animations were updated, or `None` if
+    /// it hasn't been seen yet.
+    was_visible: Option<bool>,
+}
+
+impl WindowAnimations {
+    fn is_running(&self) -> bool {
+        self.visibility.is_some_and(|(_, anim)| !anim.is_done())
+            || self
+                .geometry
+                .is_some_and(|geo| geo.started && !geo.animation.is_done())
+    }
+}
+
+/// How a window should be drawn this frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnimatedGeometry {
+    /// Where and how large the window is drawn.
+    pub geometry: Rectangle<f64, Logical>,
+    pub alpha: f32,
+}
+
+impl WindowElement {
+    /// Whether this window has any animations running.
+    ///
+    /// RefCell Safety: This uses `with_state` on this window.
+    pub fn is_animating(&self) -> bool {
+        self.with_state(|state| state.animations.is_running())
+    }
+
+    /// Whether this window is still fading out after being hidden.
+    ///
+    /// RefCell Safety: This uses `with_state` on this window.
+    pub fn is_animating_out(&self) -> bool {
+        self.with_state(|state| {
+            state
+                .animations
+                .visibility
+                .is_some_and(|(change, anim)| change == VisibilityChange::Hide && !anim.is_done())
+        })
+    }
+
+    /// Sample this window's animations given where it actually is.
+    ///
+    /// Returns `None` if the window is drawn as is.
+    ///
+    /// RefCell Safety: This uses `with_state_mut` on this window.
+    pub fn animated_geometry(&self, target: Rectangle<i32, Logical>) -> Option<AnimatedGeometry> {
+        self.with_state_mut(|state| {
+            let anims = &mut state.animations;
+
+            let mut geometry = target.to_f64();
+            let mut alpha = 1.0;
+            let mut animated = false;
+
+            if let Some(geo_anim) = anims.geometry.as_mut() {
+                if !geo_anim.started && geo_anim.from != target {
+                    geo_anim.started = true;
+                    geo_anim.animation =
+                        Animation::new(geo_anim.animation.duration, geo_anim.animation.curve);
+                }
+
+                if geo_anim.animation.is_done() {
+                    anims.geometry = None;
+                } else if geo_anim.started {
+                    let progress = geo_anim.animation.progress();
+                    let from = geo_anim.from.to_f64();
+                    geometry = Rectangle::from_loc_and_size(
+                        lerp_point(from.loc, geometry.loc, progress),
+                        lerp_size(from.size, geometry.size, progress),
+                    );
+                    animated = true;
+                }
+            }
+
+            if let Some((change, anim)) = anims.visibility {
+                // Closed windows stay hidden until they unmap or the close times out
+                if anim.is_done() && change != VisibilityChange::Close {
+                    anims.visibility = None;
+                } else {
+                    let progress = anim.progress();
+                    let (opacity, scale) = match change {
+                        VisibilityChange::Open => (progress, lerp(OPEN_CLOSE_SCALE, 1.0, progress)),
+                        VisibilityChange::Close => {
+                            (1.0 - progress, lerp(1.0, OPEN_CLOSE_SCALE, progress))
+                        }
+                        VisibilityChange::Show => (progress, 1.0),
+                        VisibilityChange::Hide => (1.0 - progress, 1.0),
+                    };
+
+                    // Scale around the center of the window
+                    let size = Size::from((geometry.size.w * scale, geometry.size.h * scale));
+                    let loc = Point::from((
+                        geometry.loc.x + (geometry.size.w - size.w) / 2.0,
+                        geometry.loc.y + (geometry.size.h - size.h) / 2.0,
+                    ));
+                    geometry = Rectangle::from_loc_and_size(loc, size);
+                    alpha = opacity as f32;
+                    animated = true;
+                }
+            }
+
+            animated.then_some(AnimatedGeometry { geometry, alpha })
+        })
+    }
+}
+
+impl Pinnacle {
+    fn window_animations_enabled(&self, window: &WindowElement) -> bool {
+        self.config.animations.enabled
+            && !window.is_x11_override_redirect()
+            && !window.with_state(|state| state.animations.disabled)
+            // Animating a game or video would only get in the way of what it's showing
+            && !window.is_game_or_video()
+    }
+
+    /// Start open and tag switch animations for windows that were shown or hidden
+    /// since the last time this was called.
+    ///
+    /// This should be called before generating render elements.
+    pub fn update_window_animations(&self) {
+        for window in self.windows.iter() {
+            let visible = window.is_visible();
+            let enabled = self.window_animations_enabled(window);
+            let anim = self.config.animations.animation();
+
+            window.with_state_mut(|state| {
+                let anims = &mut state.animations;
+                let change = match anims.was_visible.replace(visible) {
+                    None if visible => Some(VisibilityChange::Open),
+                    Some(false) if visible => Some(VisibilityChange::Show),
+                    Some(true) if !visible => Some(VisibilityChange::Hide),
+                    _ => None,
+                };
+
+                if let Some(change) = change.filter(|_| enabled) {
+                    anims.visibility = Some((change, anim));
+                }
+            });
+        }
+    }
+
+    /// Remember where the given windows currently are so that they animate from there
+    /// to wherever the layout puts them.
+    pub fn start_geometry_animations(&self, windows: &[WindowElement]) {
+        for window in windows {
+            if !self.window_animations_enabled(window)
+                || !window.with_state(|state| state.animations.was_visible == Some(true))
+            {
+                continue;
+            }
+
+            let Some(loc) = self.space.element_location(window) else {
+                continue;
+            };
+            let current = Rectangle::from_loc_and_size(loc, window.geometry().size);
+            let animation = self.config.animations.animation();
+
+            window.with_state_mut(|state| {
+                // If the window is already moving, start from where it's being drawn
+                let from = state
+                    .animations
+                    .geometry
+                    .filter(|geo| geo.started && !geo.animation.is_done())
+                    .map(|geo| {
+                        let progress = geo.animation.progress();
+                        let from = geo.from.to_f64();
+                        Rectangle::from_loc_and_size(
+                            lerp_point(from.loc, current.loc.to_f64(), progress),
+                            lerp_size(from.size, current.size.to_f64(), progress),
+                        )
+                        .to_i32_round()
+                    })
+                    .unwrap_or(current);
+
+                state.animations.geometry = Some(GeometryAnimation {
+                    from,
+                    animation,
+                    started: false,
+                });
+            });
+        }
+    }
+
+    /// Close a window, fading it out first if animations are enabled.
+    pub fn close_window(&self, window: &WindowElement) {
+        if window.is_x11_override_redirect() {
+            tracing::warn!("tried to close OR window");
+            return;
+        }
+
+        if !self.window_animations_enabled(window) || !window.is_visible() {
+            send_close(window);
+            return;
+        }
+
+        let anim = self.config.animations.animation();
+        window.with_state_mut(|state| {
+            state.animations.visibility = Some((VisibilityChange::Close, anim));
+        });
+
+        let window = window.clone();
+        let mut closed = false;
+        let res = self.loop_handle.insert_source(
+            Timer::from_duration(anim.duration),
+            move |_, _, state| {
+                if !state.pinnacle.windows.contains(&window) {
+                    return TimeoutAction::Drop;
+                }
+
+                if !closed {
+                    closed = true;
+                    send_close(&window);
+                    return TimeoutAction::ToDuration(CLOSE_TIMEOUT);
+                }
+
+                // The client didn't close the window
+                window.with_state_mut(|state| state.animations.visibility = None);
+                if let Some(output) = window.output(&state.pinnacle) {
+                    state.schedule_render(&output);
+                }
+                TimeoutAction::Drop
+            },
+        );
+
+        if let Err(err) = res {
+            error!("Failed to insert close animation timer: {err}");
+        }
+    }
+}
+
+fn send_close(window: &WindowElement) {
+    match window.underlying_surface() {
+        WindowSurface::Wayland(toplevel) => toplevel.send_close(),
+        WindowSurface::X11(surface) => {
+            if let Err(err) = surface.close() {
+                error!("failed to close x11 window: {err}");
+            }
+        }
+    }
+}
+
+fn lerp(from: f64, to: f64, t: f64) -> f64 {
+    from + (to - from) * t
+}
+
+fn lerp_point(from: Point<f64, Logical>, to: Point<f64, Logical>, t: f64) -> Point<f64, Logical> {
+    (lerp(from.x, to.x, t), lerp(from.y, to.y, t)).into()
+}
+
+fn lerp_size(from: Size<f64, Logical>, to: Size<f64, Logical>, t: f64) -> Size<f64, Logical> {
+    (lerp(from.w, to.w, t), lerp(from.h, to.h, t)).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn curves_start_and_end_at_bounds() {
+        for curve in [Curve::Linear, Curve::EaseOutCubic, Curve::EaseInOutCubic] {
+            assert_eq!(curve.apply(0.0), 0.0);
+            assert_eq!(curve.apply(1.0), 1.0);
+            assert_eq!(curve.apply(2.0), 1.0);
+        }
+    }
+
+    #[test]
+    fn ease_out_is_ahead_of_linear() {
+        assert!(Curve::EaseOutCubic.apply(0.5) > Curve::Linear.apply(0.5));
+        assert_eq!(Curve::EaseInOutCubic.apply(0.5), 0.5);
+    }
+
+    #[test]
+    fn zero_duration_animation_is_done() {
+        let anim = Animation::new(Duration::ZERO, Curve::Linear);
+        assert!(anim.is_done());
+        assert_eq!(anim.progress(), 1.0);
+    }
+}
@@ -1,38 +1,70 @@
+pub mod debug;
 pub mod layout;
+pub mod screenshot;
 pub mod signal;
 pub mod window;
 
-use std::{ffi::OsString, pin::Pin, process::Stdio};
+use std::{
+    ffi::OsString, num::NonZeroU32, path::PathBuf, pin::Pin, process::Stdio, time::Duration,
+};
+
+use chrono::NaiveTime;
+use image::RgbaImage;
 
 use pinnacle_api_defs::pinnacle::{
-    input::v0alpha1::{
-        input_service_server,
-        set_libinput_setting_request::{AccelProfile, ClickMethod, ScrollMethod, TapButtonMap},
-        set_mousebind_request::MouseEdge,
-        SetKeybindRequest, SetKeybindResponse, SetLibinputSettingRequest, SetMousebindRequest,
-        SetMousebindResponse, SetRepeatRateRequest, SetXkbConfigRequest,
+    input::{
+        self,
+        v0alpha1::{
+            input_service_server, list_keybinds_response,
+            set_libinput_setting_request::{AccelProfile, ClickMethod, ScrollMethod, TapButtonMap},
+            set_mousebind_request::MouseEdge,
+            set_scrollbind_request::ScrollDirection,
+            set_swipebind_request::SwipeDirection,
+            set_tablet_setting_request, switch_xkb_layout_request, warp_pointer_request,
+            GetDevicesRequest, GetDevicesResponse, GetXkbLayoutsRequest, GetXkbLayoutsResponse,
+            ListKeybindsRequest, ListKeybindsResponse, SetCursorThemeRequest, SetKeyRemapRequest,
+            SetKeybindRequest, SetKeybindResponse, SetLibinputSettingRequest,
+            SetLidSwitchPolicyRequest, SetLockKeysRequest, SetMouseKeysRequest,
+            SetMousebindRequest, SetMousebindResponse, SetPerWindowXkbLayoutRequest,
+            SetRepeatRateRequest, SetScrollFactorRequest, SetScrollbindRequest,
+            SetScrollbindResponse, SetSlowKeysRequest, SetStickyKeysRequest, SetSwipebindRequest,
+            SetSwipebindResponse, SetTabletSettingRequest, SetTouchOutputRequest,
+            SetWarpPointerOnOutputFocusRequest, SetXkbConfigRequest, SwitchXkbLayoutRequest,
+            WarpPointerRequest,
+        },
     },
     output::{
         self,
         v0alpha1::{
-            output_service_server, set_scale_request::AbsoluteOrRelative, SetLocationRequest,
-            SetModeRequest, SetScaleRequest, SetTransformRequest,
+            add_custom_mode_request, output_service_server, set_max_render_time_request,
+            set_night_light_request, set_scale_request::AbsoluteOrRelative, set_wallpaper_request,
+            AddCustomModeRequest, ClearProfilesRequest, SaveProfileRequest, SetColorFilterRequest,
+            SetEnabledRequest, SetLocationRequest, SetMaxFpsRequest, SetMaxRenderTimeRequest,
+            SetModeRequest, SetModelineRequest, SetNightLightRequest, SetPoweredRequest,
+            SetPrimaryRequest, SetScaleRequest, SetTransformRequest, SetVrrRequest,
+            SetWallpaperRequest,
         },
     },
     process::v0alpha1::{process_service_server, SetEnvRequest, SpawnRequest, SpawnResponse},
     render::v0alpha1::{
-        render_service_server, Filter, SetDownscaleFilterRequest, SetUpscaleFilterRequest,
+        render_service_server, Filter, SetAnimationsEnabledRequest, SetBlurRequest,
+        SetCornerRadiusRequest, SetDimInactiveRequest, SetDownscaleFilterRequest,
+        SetUpscaleFilterRequest,
     },
+    signal::v0alpha1::TagRenamedResponse,
     tag::{
         self,
         v0alpha1::{
-            tag_service_server, AddRequest, AddResponse, RemoveRequest, SetActiveRequest,
-            SwitchToRequest,
+            tag_service_server, AddRequest, AddResponse, GetHistoryRequest, GetHistoryResponse,
+            MoveToOutputRequest, RemoveRequest, ReorderRequest, SetActiveRequest,
+            SetDynamicRequest, SetGapsRequest, SetLayoutRequest, SetNameRequest,
+            SwitchToPreviousRequest, SwitchToRelativeRequest, SwitchToRequest,
         },
     },
     v0alpha1::{
         pinnacle_service_server, PingRequest, PingResponse, QuitRequest, ReloadConfigRequest,
-        SetOrToggle, ShutdownWatchRequest, ShutdownWatchResponse,
+        RestoreSessionRequest, SetOrToggle, SetSessionPersistenceRequest, ShutdownWatchRequest,
+        ShutdownWatchResponse,
     },
 };
 use smithay::{
@@ -40,6 +72,7 @@ use smithay::{
     input::keyboard::XkbConfig,
     output::Scale,
     reexports::{calloop, input as libinput},
+    utils::Point,
 };
 use sysinfo::ProcessRefreshKind;
 use tokio::{
@@ -54,10 +87,24 @@ use tracing::{debug, error, info, trace, warn};
 use crate::{
     backend::BackendData,
     config::ConnectorSavedState,
-    input::ModifierMask,
-    output::OutputName,
+    input::{
+        keybind::{KeyRepeat, Keybind},
+        libinput::{DeviceFilter, DeviceType, LibinputSetting},
+        remap::{KeyRemap, RemapKey},
+        tablet::{PressureCurve, TabletArea, TabletConfig, TabletSetting},
+        touch::TouchOutputMapping,
+        warp, xkb, KeybindInfo, LidSwitchPolicy, ModifierMask, ScrollFactor,
+    },
+    night_light::{NightLight, NightLightSchedule, TEMPERATURE_RANGE},
+    output::{add_custom_mode, find_mode, Alignment, MaxRenderTime, Modeline, OutputName, Vrr},
+    render::{
+        blur::{self, BlurSettings},
+        color_filter::ColorFilter,
+        wallpaper::{Wallpaper, WallpaperImage, WallpaperMode},
+    },
     state::{State, WithState},
     tag::{Tag, TagId},
+    window::{placement::SpawnPlacement, rules::WindowRule, window_state::WindowId},
 };
 
 type ResponseStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
@@ -174,6 +221,19 @@ where
     Ok(Response::new(Box::pin(receiver_stream)))
 }
 
+/// A wallpaper from a request, before it is turned into a [`Wallpaper`].
+enum RequestedWallpaper {
+    Color(f32, f32, f32),
+    Image(RgbaImage, WallpaperMode),
+}
+
+/// Expand `~` and environment variables in a path from a request.
+fn expand_path(path: &str) -> Result<PathBuf, Status> {
+    shellexpand::path::full(path)
+        .map(|path| path.into_owned())
+        .map_err(|err| Status::invalid_argument(format!("invalid path: {err}")))
+}
+
 pub struct PinnacleService {
     sender: StateFnSender,
 }
@@ -213,6 +273,33 @@ impl pinnacle_service_server::PinnacleService for PinnacleService {
         .await
     }
 
+    async fn set_session_persistence(
+        &self,
+        request: Request<SetSessionPersistenceRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let enabled = request
+            .enabled
+            .ok_or_else(|| Status::invalid_argument("no enabled specified"))?;
+
+        run_unary_no_response(&self.sender, move |state| {
+            state.pinnacle.config.session.persist = enabled;
+            state.pinnacle.session_changed();
+        })
+        .await
+    }
+
+    async fn restore_session(
+        &self,
+        _request: Request<RestoreSessionRequest>,
+    ) -> Result<Response<()>, Status> {
+        run_unary_no_response(&self.sender, |state| {
+            state.restore_session();
+        })
+        .await
+    }
+
     async fn ping(&self, request: Request<PingRequest>) -> Result<Response<PingResponse>, Status> {
         let payload = request.into_inner().payload;
         Ok(Response::new(PingResponse { payload }))
@@ -242,6 +329,8 @@ impl InputService {
 impl input_service_server::InputService for InputService {
     type SetKeybindStream = ResponseStream<SetKeybindResponse>;
     type SetMousebindStream = ResponseStream<SetMousebindResponse>;
+    type SetScrollbindStream = ResponseStream<SetScrollbindResponse>;
+    type SetSwipebindStream = ResponseStream<SetSwipebindResponse>;
 
     async fn set_keybind(
         &self,
@@ -278,27 +367,70 @@ impl input_service_server::InputService for InputService {
                 xkbcommon::xkb::Keysym::new(num)
             }
             Key::XkbName(s) => {
-                if s.chars().count() == 1 {
-                    let Some(ch) = s.chars().next() else { unreachable!() };
-                    let keysym = xkbcommon::xkb::Keysym::from_char(ch);
-                    debug!("Set keybind: {:?}, {:?}", modifiers, keysym);
-                    keysym
-                } else {
-                    let keysym =
-                        xkbcommon::xkb::keysym_from_name(&s, xkbcommon::xkb::KEYSYM_NO_FLAGS);
-                    debug!("Set keybind: {:?}, {:?}", modifiers, keysym);
-                    keysym
-                }
+                let keysym = keysym_from_xkb_name(&s);
+                debug!("Set keybind: {:?}, {:?}", modifiers, keysym);
+                keysym
             }
         };
 
+        let info = KeybindInfo {
+            group: request.group,
+            description: request.description,
+        };
+        let on_release = request.on_release.unwrap_or_default();
+        let repeat = request.repeat.unwrap_or_default();
+        let priority = request.priority.unwrap_or_default();
+
         run_server_streaming(&self.sender, move |state, sender| {
-            state
+            state.pinnacle.input_state.set_keybind(
+                modifiers,
+                keysym,
+                Keybind {
+                    sender,
+                    on_release,
+                    repeat,
+                    priority,
+                },
+                info,
+            );
+        })
+    }
+
+    async fn list_keybinds(
+        &self,
+        _request: Request<ListKeybindsRequest>,
+    ) -> Result<Response<ListKeybindsResponse>, Status> {
+        run_unary(&self.sender, |state| {
+            let keybinds = state
                 .pinnacle
                 .input_state
-                .keybinds
-                .insert((modifiers, keysym), sender);
+                .keybind_info
+                .iter()
+                .map(|((mods, keysym), info)| {
+                    let modifiers = [
+                        (ModifierMask::SHIFT, input::v0alpha1::Modifier::Shift),
+                        (ModifierMask::CTRL, input::v0alpha1::Modifier::Ctrl),
+                        (ModifierMask::ALT, input::v0alpha1::Modifier::Alt),
+                        (ModifierMask::SUPER, input::v0alpha1::Modifier::Super),
+                    ]
+                    .into_iter()
+                    .filter(|(mask, _)| mods.contains(*mask))
+                    .map(|(_, modifier)| modifier as i32)
+                    .collect();
+
+                    list_keybinds_response::Keybind {
+                        modifiers,
+                        raw_code: Some(keysym.raw()),
+                        xkb_name: Some(xkbcommon::xkb::keysym_get_name(*keysym)),
+                        group: info.group.clone(),
+                        description: info.description.clone(),
+                    }
+                })
+                .collect();
+
+            ListKeybindsResponse { keybinds }
         })
+        .await
     }
 
     async fn set_mousebind(
@@ -345,6 +477,91 @@ impl input_service_server::InputService for InputService {
         })
     }
 
+    async fn set_scrollbind(
+        &self,
+        request: Request<SetScrollbindRequest>,
+    ) -> Result<Response<Self::SetScrollbindStream>, Status> {
+        let request = request.into_inner();
+
+        debug!(request = ?request);
+
+        let modifiers = request
+            .modifiers()
+            .fold(ModifierMask::empty(), |acc, modifier| match modifier {
+                pinnacle_api_defs::pinnacle::input::v0alpha1::Modifier::Unspecified => acc,
+                pinnacle_api_defs::pinnacle::input::v0alpha1::Modifier::Shift => {
+                    acc | ModifierMask::SHIFT
+                }
+                pinnacle_api_defs::pinnacle::input::v0alpha1::Modifier::Ctrl => {
+                    acc | ModifierMask::CTRL
+                }
+                pinnacle_api_defs::pinnacle::input::v0alpha1::Modifier::Alt => {
+                    acc | ModifierMask::ALT
+                }
+                pinnacle_api_defs::pinnacle::input::v0alpha1::Modifier::Super => {
+                    acc | ModifierMask::SUPER
+                }
+            });
+
+        let direction = request.direction();
+
+        if let ScrollDirection::Unspecified = direction {
+            return Err(Status::invalid_argument("no scroll direction specified"));
+        }
+
+        run_server_streaming(&self.sender, move |state, sender| {
+            state
+                .pinnacle
+                .input_state
+                .scrollbinds
+                .insert((modifiers, direction), sender);
+        })
+    }
+
+    async fn set_swipebind(
+        &self,
+        request: Request<SetSwipebindRequest>,
+    ) -> Result<Response<Self::SetSwipebindStream>, Status> {
+        let request = request.into_inner();
+
+        debug!(request = ?request);
+
+        let modifiers = request
+            .modifiers()
+            .fold(ModifierMask::empty(), |acc, modifier| match modifier {
+                pinnacle_api_defs::pinnacle::input::v0alpha1::Modifier::Unspecified => acc,
+                pinnacle_api_defs::pinnacle::input::v0alpha1::Modifier::Shift => {
+                    acc | ModifierMask::SHIFT
+                }
+                pinnacle_api_defs::pinnacle::input::v0alpha1::Modifier::Ctrl => {
+                    acc | ModifierMask::CTRL
+                }
+                pinnacle_api_defs::pinnacle::input::v0alpha1::Modifier::Alt => {
+                    acc | ModifierMask::ALT
+                }
+                pinnacle_api_defs::pinnacle::input::v0alpha1::Modifier::Super => {
+                    acc | ModifierMask::SUPER
+                }
+            });
+        let fingers = request
+            .fingers
+            .ok_or_else(|| Status::invalid_argument("no finger count specified"))?;
+
+        let direction = request.direction();
+
+        if let SwipeDirection::Unspecified = direction {
+            return Err(Status::invalid_argument("no swipe direction specified"));
+        }
+
+        run_server_streaming(&self.sender, move |state, sender| {
+            state
+                .pinnacle
+                .input_state
+                .swipebinds
+                .insert((modifiers, fingers, direction), sender);
+        })
+    }
+
     async fn set_xkb_config(
         &self,
         request: Request<SetXkbConfigRequest>,
@@ -357,7 +574,10 @@ impl input_service_server::InputService for InputService {
                 variant: request.variant(),
                 model: request.model(),
                 layout: request.layout(),
-                options: request.options.clone(),
+                options: xkb::options_with_compose_key(
+                    request.options.clone(),
+                    request.compose_key.clone(),
+                ),
             };
             if let Some(kb) = state.pinnacle.seat.get_keyboard() {
                 if let Err(err) = kb.set_xkb_config(state, new_config) {
@@ -368,6 +588,54 @@ impl input_service_server::InputService for InputService {
         .await
     }
 
+    async fn switch_xkb_layout(
+        &self,
+        request: Request<SwitchXkbLayoutRequest>,
+    ) -> Result<Response<()>, Status> {
+        let target = request
+            .into_inner()
+            .target
+            .ok_or_else(|| Status::invalid_argument("no layout specified"))?;
+
+        run_unary_no_response(&self.sender, move |state| match target {
+            switch_xkb_layout_request::Target::Index(index) => state.set_xkb_layout(index),
+            switch_xkb_layout_request::Target::Offset(offset) => {
+                state.switch_relative_xkb_layout(offset)
+            }
+        })
+        .await
+    }
+
+    async fn get_xkb_layouts(
+        &self,
+        _request: Request<GetXkbLayoutsRequest>,
+    ) -> Result<Response<GetXkbLayoutsResponse>, Status> {
+        run_unary(&self.sender, move |state| {
+            let layouts = state.xkb_layouts();
+
+            GetXkbLayoutsResponse {
+                names: layouts.names,
+                active_index: Some(layouts.active),
+            }
+        })
+        .await
+    }
+
+    async fn set_per_window_xkb_layout(
+        &self,
+        request: Request<SetPerWindowXkbLayoutRequest>,
+    ) -> Result<Response<()>, Status> {
+        let enabled = request
+            .into_inner()
+            .enabled
+            .ok_or_else(|| Status::invalid_argument("no enabled specified"))?;
+
+        run_unary_no_response(&self.sender, move |state| {
+            state.pinnacle.input_state.per_window_xkb_layout = enabled;
+        })
+        .await
+    }
+
     async fn set_repeat_rate(
         &self,
         request: Request<SetRepeatRateRequest>,
@@ -385,6 +653,181 @@ impl input_service_server::InputService for InputService {
             if let Some(kb) = state.pinnacle.seat.get_keyboard() {
                 kb.change_repeat_info(rate, delay);
             }
+            state.pinnacle.input_state.key_repeat = KeyRepeat { rate, delay };
+        })
+        .await
+    }
+
+    async fn set_lock_keys(
+        &self,
+        request: Request<SetLockKeysRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        run_unary_no_response(&self.sender, move |state| {
+            state.set_lock_keys(request.num_lock, request.caps_lock);
+        })
+        .await
+    }
+
+    async fn set_sticky_keys(
+        &self,
+        request: Request<SetStickyKeysRequest>,
+    ) -> Result<Response<()>, Status> {
+        let enabled = request
+            .into_inner()
+            .enabled
+            .ok_or_else(|| Status::invalid_argument("no enabled specified"))?;
+
+        run_unary_no_response(&self.sender, move |state| {
+            state.pinnacle.input_state.sticky_keys.enabled = enabled;
+        })
+        .await
+    }
+
+    async fn set_slow_keys(
+        &self,
+        request: Request<SetSlowKeysRequest>,
+    ) -> Result<Response<()>, Status> {
+        let delay = request
+            .into_inner()
+            .delay_ms
+            .filter(|delay| *delay > 0)
+            .map(|delay| Duration::from_millis(delay as u64));
+
+        run_unary_no_response(&self.sender, move |state| {
+            state.pinnacle.input_state.slow_keys.delay = delay;
+        })
+        .await
+    }
+
+    async fn set_mouse_keys(
+        &self,
+        request: Request<SetMouseKeysRequest>,
+    ) -> Result<Response<()>, Status> {
+        let enabled = request
+            .into_inner()
+            .enabled
+            .ok_or_else(|| Status::invalid_argument("no enabled specified"))?;
+
+        run_unary_no_response(&self.sender, move |state| {
+            state.pinnacle.input_state.mouse_keys.enabled = enabled;
+        })
+        .await
+    }
+
+    async fn set_key_remap(
+        &self,
+        request: Request<SetKeyRemapRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let from = request
+            .from
+            .and_then(remap_key_from_api)
+            .ok_or_else(|| Status::invalid_argument("no key to remap specified"))?;
+        let to = request
+            .to
+            .and_then(remap_key_from_api)
+            .ok_or_else(|| Status::invalid_argument("no key to remap to specified"))?;
+        let filter = request
+            .filter
+            .map(device_filter_from_api)
+            .unwrap_or_default();
+
+        run_unary_no_response(&self.sender, move |state| {
+            state
+                .pinnacle
+                .input_state
+                .key_remaps
+                .push(KeyRemap { from, to, filter });
+        })
+        .await
+    }
+
+    async fn warp_pointer(
+        &self,
+        request: Request<WarpPointerRequest>,
+    ) -> Result<Response<()>, Status> {
+        let target = request
+            .into_inner()
+            .target
+            .ok_or_else(|| Status::invalid_argument("no target specified"))?;
+
+        run_unary_no_response(&self.sender, move |state| {
+            let location = match target {
+                warp_pointer_request::Target::Location(location) => {
+                    Some(Point::from((location.x(), location.y())))
+                }
+                warp_pointer_request::Target::WindowId(window_id) => WindowId(window_id)
+                    .window(&state.pinnacle)
+                    .and_then(|win| state.pinnacle.space.element_geometry(&win))
+                    .map(warp::center),
+                warp_pointer_request::Target::OutputName(output_name) => OutputName(output_name)
+                    .output(&state.pinnacle)
+                    .and_then(|op| state.pinnacle.space.output_geometry(&op))
+                    .map(warp::center),
+            };
+
+            if let Some(location) = location {
+                state.warp_pointer(location);
+            }
+        })
+        .await
+    }
+
+    async fn set_warp_pointer_on_output_focus(
+        &self,
+        request: Request<SetWarpPointerOnOutputFocusRequest>,
+    ) -> Result<Response<()>, Status> {
+        let enabled = request
+            .into_inner()
+            .enabled
+            .ok_or_else(|| Status::invalid_argument("no enabled specified"))?;
+
+        run_unary_no_response(&self.sender, move |state| {
+            state.pinnacle.input_state.warp_pointer_on_output_focus = enabled;
+        })
+        .await
+    }
+
+    async fn set_cursor_theme(
+        &self,
+        request: Request<SetCursorThemeRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        if request.size == Some(0) {
+            return Err(Status::invalid_argument("size must be greater than 0"));
+        }
+
+        run_unary_no_response(&self.sender, move |state| {
+            state.pinnacle.set_cursor_theme(request.theme, request.size);
+
+            for output in state.pinnacle.space.outputs().cloned().collect::<Vec<_>>() {
+                state.schedule_render(&output);
+            }
+        })
+        .await
+    }
+
+    async fn set_lid_switch_policy(
+        &self,
+        request: Request<SetLidSwitchPolicyRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let policy = match request.policy() {
+            input::v0alpha1::LidSwitchPolicy::Unspecified => {
+                return Err(Status::invalid_argument("policy was unspecified"));
+            }
+            input::v0alpha1::LidSwitchPolicy::Ignore => LidSwitchPolicy::Ignore,
+            input::v0alpha1::LidSwitchPolicy::Clamshell => LidSwitchPolicy::Clamshell,
+        };
+
+        run_unary_no_response(&self.sender, move |state| {
+            state.pinnacle.input_state.lid_switch_policy = policy;
+            state.update_lid_outputs();
         })
         .await
     }
@@ -398,6 +841,10 @@ impl input_service_server::InputService for InputService {
         let setting = request
             .setting
             .ok_or_else(|| Status::invalid_argument("no setting specified"))?;
+        let filter = request
+            .filter
+            .map(device_filter_from_api)
+            .unwrap_or_default();
 
         let discriminant = std::mem::discriminant(&setting);
 
@@ -523,29 +970,220 @@ impl input_service_server::InputService for InputService {
         };
 
         run_unary_no_response(&self.sender, move |state| {
-            for device in state.pinnacle.input_state.libinput_devices.iter_mut() {
-                apply_setting(device);
-            }
+            state.pinnacle.set_libinput_setting(LibinputSetting {
+                kind: discriminant,
+                filter,
+                apply: apply_setting,
+            });
+        })
+        .await
+    }
 
-            state
+    async fn get_devices(
+        &self,
+        request: Request<GetDevicesRequest>,
+    ) -> Result<Response<GetDevicesResponse>, Status> {
+        let filter = request
+            .into_inner()
+            .filter
+            .map(device_filter_from_api)
+            .unwrap_or_default();
+
+        run_unary(&self.sender, move |state| {
+            let devices = state
                 .pinnacle
                 .input_state
-                .libinput_settings
-                .insert(discriminant, apply_setting);
+                .libinput_devices
+                .iter()
+                .filter(|device| filter.matches(device))
+                .map(|device| input::v0alpha1::Device {
+                    name: Some(device.name().to_string()),
+                    vendor_id: Some(device.id_vendor()),
+                    product_id: Some(device.id_product()),
+                    device_type: Some(device_type_to_api(DeviceType::of(device)) as i32),
+                })
+                .collect();
+
+            GetDevicesResponse { devices }
         })
         .await
     }
-}
 
-pub struct ProcessService {
-    sender: StateFnSender,
-}
+    async fn set_scroll_factor(
+        &self,
+        request: Request<SetScrollFactorRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
 
-impl ProcessService {
-    pub fn new(sender: StateFnSender) -> Self {
-        Self { sender }
+        let factor = request
+            .factor
+            .ok_or_else(|| Status::invalid_argument("no factor specified"))?;
+        if !factor.is_finite() {
+            return Err(Status::invalid_argument("factor must be finite"));
+        }
+        let filter = request
+            .filter
+            .map(device_filter_from_api)
+            .unwrap_or_default();
+
+        run_unary_no_response(&self.sender, move |state| {
+            state
+                .pinnacle
+                .input_state
+                .scroll_factors
+                .push(ScrollFactor { filter, factor });
+        })
+        .await
     }
-}
+
+    async fn set_touch_output(
+        &self,
+        request: Request<SetTouchOutputRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let output = request
+            .output_name
+            .map(OutputName)
+            .ok_or_else(|| Status::invalid_argument("no output specified"))?;
+        let filter = request
+            .filter
+            .map(device_filter_from_api)
+            .unwrap_or_default();
+
+        run_unary_no_response(&self.sender, move |state| {
+            state
+                .pinnacle
+                .input_state
+                .touch_output_mappings
+                .push(TouchOutputMapping { filter, output });
+        })
+        .await
+    }
+
+    async fn set_tablet_setting(
+        &self,
+        request: Request<SetTabletSettingRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let filter = request
+            .filter
+            .map(device_filter_from_api)
+            .unwrap_or_default();
+
+        let config = match request
+            .setting
+            .ok_or_else(|| Status::invalid_argument("no setting specified"))?
+        {
+            set_tablet_setting_request::Setting::OutputName(output_name) => {
+                TabletConfig::Area(TabletArea::Output(OutputName(output_name)))
+            }
+            set_tablet_setting_request::Setting::Region(region) => {
+                TabletConfig::Area(TabletArea::Region(screenshot::region(region)?))
+            }
+            set_tablet_setting_request::Setting::PressureCurve(curve) => {
+                let (Some(x1), Some(y1), Some(x2), Some(y2)) =
+                    (curve.x1, curve.y1, curve.x2, curve.y2)
+                else {
+                    return Err(Status::invalid_argument(
+                        "pressure curve was missing fields",
+                    ));
+                };
+                if [x1, y1, x2, y2]
+                    .iter()
+                    .any(|coord| !(0.0..=1.0).contains(coord))
+                {
+                    return Err(Status::invalid_argument(
+                        "pressure curve control points must be between 0 and 1",
+                    ));
+                }
+                TabletConfig::PressureCurve(PressureCurve {
+                    control_1: (x1, y1),
+                    control_2: (x2, y2),
+                })
+            }
+            set_tablet_setting_request::Setting::ButtonRemap(remap) => {
+                let (Some(from), Some(to)) = (remap.from, remap.to) else {
+                    return Err(Status::invalid_argument("button remap was missing fields"));
+                };
+                TabletConfig::ButtonRemap { from, to }
+            }
+        };
+
+        run_unary_no_response(&self.sender, move |state| {
+            state
+                .pinnacle
+                .input_state
+                .tablet_settings
+                .push(TabletSetting { filter, config });
+        })
+        .await
+    }
+}
+
+/// Get the keysym named `name`, which is either a single character or an xkbcommon name
+/// without the `XKB_KEY_` prefix.
+fn keysym_from_xkb_name(name: &str) -> xkbcommon::xkb::Keysym {
+    let mut chars = name.chars();
+    match (chars.next(), chars.next()) {
+        (Some(ch), None) => xkbcommon::xkb::Keysym::from_char(ch),
+        _ => xkbcommon::xkb::keysym_from_name(name, xkbcommon::xkb::KEYSYM_NO_FLAGS),
+    }
+}
+
+fn remap_key_from_api(key: input::v0alpha1::RemapKey) -> Option<RemapKey> {
+    use input::v0alpha1::remap_key::Key;
+
+    let key = match key.key? {
+        Key::Keycode(code) => RemapKey::Keycode(code),
+        Key::Keysym(raw) => RemapKey::Keysym(xkbcommon::xkb::Keysym::new(raw)),
+        Key::XkbName(name) => RemapKey::Keysym(keysym_from_xkb_name(&name)),
+    };
+
+    Some(key)
+}
+
+fn device_filter_from_api(filter: input::v0alpha1::DeviceFilter) -> DeviceFilter {
+    let device_type = filter.device_type();
+
+    DeviceFilter {
+        name: filter.name,
+        vendor_id: filter.vendor_id,
+        product_id: filter.product_id,
+        device_type: match device_type {
+            input::v0alpha1::DeviceType::Unspecified => None,
+            input::v0alpha1::DeviceType::Touchpad => Some(DeviceType::Touchpad),
+            input::v0alpha1::DeviceType::Trackpoint => Some(DeviceType::Trackpoint),
+            input::v0alpha1::DeviceType::Mouse => Some(DeviceType::Mouse),
+            input::v0alpha1::DeviceType::Tablet => Some(DeviceType::Tablet),
+            input::v0alpha1::DeviceType::Touchscreen => Some(DeviceType::Touchscreen),
+            input::v0alpha1::DeviceType::Keyboard => Some(DeviceType::Keyboard),
+        },
+    }
+}
+
+fn device_type_to_api(device_type: DeviceType) -> input::v0alpha1::DeviceType {
+    match device_type {
+        DeviceType::Touchpad => input::v0alpha1::DeviceType::Touchpad,
+        DeviceType::Trackpoint => input::v0alpha1::DeviceType::Trackpoint,
+        DeviceType::Mouse => input::v0alpha1::DeviceType::Mouse,
+        DeviceType::Tablet => input::v0alpha1::DeviceType::Tablet,
+        DeviceType::Touchscreen => input::v0alpha1::DeviceType::Touchscreen,
+        DeviceType::Keyboard => input::v0alpha1::DeviceType::Keyboard,
+        DeviceType::Other => input::v0alpha1::DeviceType::Unspecified,
+    }
+}
+
+pub struct ProcessService {
+    sender: StateFnSender,
+}
+
+impl ProcessService {
+    pub fn new(sender: StateFnSender) -> Self {
+        Self { sender }
+    }
+}
 
 #[tonic::async_trait]
 impl process_service_server::ProcessService for ProcessService {
@@ -560,6 +1198,17 @@ impl process_service_server::ProcessService for ProcessService {
 
         let once = request.once();
         let has_callback = request.has_callback();
+
+        let placement =
+            (!request.tag_ids.is_empty() || request.output.is_some() || request.follow.is_some())
+                .then(|| WindowRule {
+                    output: request.output.map(OutputName),
+                    tags: (!request.tag_ids.is_empty())
+                        .then(|| request.tag_ids.into_iter().map(TagId).collect()),
+                    follow: request.follow,
+                    ..Default::default()
+                });
+
         let mut command = request.args.into_iter();
         let arg0 = command
             .next()
@@ -607,6 +1256,13 @@ impl process_service_server::ProcessService for ProcessService {
                 return;
             };
 
+            if let Some((pid, rule)) = child.id().zip(placement) {
+                state
+                    .pinnacle
+                    .spawn_placements
+                    .insert(pid, SpawnPlacement::new(rule));
+            }
+
             if !has_callback {
                 return;
             }
@@ -790,7 +1446,10 @@ impl tag_service_server::TagService for TagService {
         .await
     }
 
-    async fn add(&self, request: Request<AddRequest>) -> Result<Response<AddResponse>, Status> {
+    async fn switch_to_previous(
+        &self,
+        request: Request<SwitchToPreviousRequest>,
+    ) -> Result<Response<()>, Status> {
         let request = request.into_inner();
 
         let output_name = OutputName(
@@ -798,108 +1457,77 @@ impl tag_service_server::TagService for TagService {
                 .output_name
                 .ok_or_else(|| Status::invalid_argument("no output specified"))?,
         );
+        let index = request.index.unwrap_or_default() as usize;
 
-        run_unary(&self.sender, move |state| {
-            let new_tags = request
-                .tag_names
-                .into_iter()
-                .map(Tag::new)
-                .collect::<Vec<_>>();
-
-            let tag_ids = new_tags
-                .iter()
-                .map(|tag| tag.id())
-                .map(|id| id.0)
-                .collect::<Vec<_>>();
-
-            state
-                .pinnacle
-                .config
-                .connector_saved_states
-                .entry(output_name.clone())
-                .or_default()
-                .tags
-                .extend(new_tags.clone());
-
-            if let Some(output) = output_name.output(&state.pinnacle) {
-                output.with_state_mut(|state| {
-                    state.tags.extend(new_tags.clone());
-                    debug!("tags added, are now {:?}", state.tags);
-                });
-            }
-
-            for tag in new_tags {
-                for window in state.pinnacle.windows.iter() {
-                    window.with_state_mut(|state| {
-                        for win_tag in state.tags.iter_mut() {
-                            if win_tag.id() == tag.id() {
-                                *win_tag = tag.clone();
-                            }
-                        }
-                    });
-                }
-            }
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(output) = output_name.output(&state.pinnacle) else {
+                return;
+            };
 
-            AddResponse { tag_ids }
+            state.switch_to_previous_tags(&output, index);
         })
         .await
     }
 
-    // TODO: test
-    async fn remove(&self, request: Request<RemoveRequest>) -> Result<Response<()>, Status> {
+    async fn switch_to_relative(
+        &self,
+        request: Request<SwitchToRelativeRequest>,
+    ) -> Result<Response<()>, Status> {
         let request = request.into_inner();
 
-        let tag_ids = request.tag_ids.into_iter().map(TagId);
+        let output_name = OutputName(
+            request
+                .output_name
+                .ok_or_else(|| Status::invalid_argument("no output specified"))?,
+        );
+        let offset = request
+            .offset
+            .ok_or_else(|| Status::invalid_argument("no offset specified"))?;
 
         run_unary_no_response(&self.sender, move |state| {
-            let tags_to_remove = tag_ids
-                .flat_map(|id| id.tag(&state.pinnacle))
-                .collect::<Vec<_>>();
-
-            for output in state.pinnacle.space.outputs().cloned().collect::<Vec<_>>() {
-                // TODO: seriously, convert state.tags into a hashset
-                output.with_state_mut(|state| {
-                    for tag_to_remove in tags_to_remove.iter() {
-                        state.tags.retain(|tag| tag != tag_to_remove);
-                    }
-                });
-
-                state.pinnacle.request_layout(&output);
-                state.schedule_render(&output);
-            }
+            let Some(output) = output_name.output(&state.pinnacle) else {
+                return;
+            };
 
-            for conn_saved_state in state.pinnacle.config.connector_saved_states.values_mut() {
-                for tag_to_remove in tags_to_remove.iter() {
-                    conn_saved_state.tags.retain(|tag| tag != tag_to_remove);
-                }
-            }
+            state.switch_to_relative_tag(&output, offset);
         })
         .await
     }
 
-    async fn get(
+    async fn get_history(
         &self,
-        _request: Request<tag::v0alpha1::GetRequest>,
-    ) -> Result<Response<tag::v0alpha1::GetResponse>, Status> {
+        request: Request<GetHistoryRequest>,
+    ) -> Result<Response<GetHistoryResponse>, Status> {
+        let request = request.into_inner();
+
+        let output_name = OutputName(
+            request
+                .output_name
+                .ok_or_else(|| Status::invalid_argument("no output specified"))?,
+        );
+
         run_unary(&self.sender, move |state| {
-            let tag_ids = state
-                .pinnacle
-                .space
-                .outputs()
-                .flat_map(|op| op.with_state(|state| state.tags.clone()))
-                .map(|tag| tag.id())
-                .map(|id| id.0)
-                .collect::<Vec<_>>();
+            let entries = output_name
+                .output(&state.pinnacle)
+                .map(|output| {
+                    output.with_state(|op_state| {
+                        op_state
+                            .tag_history
+                            .previous()
+                            .map(|tags| tag::v0alpha1::get_history_response::TagSet {
+                                tag_ids: tags.iter().map(|tag| tag.id().0).collect(),
+                            })
+                            .collect()
+                    })
+                })
+                .unwrap_or_default();
 
-            tag::v0alpha1::GetResponse { tag_ids }
+            GetHistoryResponse { entries }
         })
         .await
     }
 
-    async fn get_properties(
-        &self,
-        request: Request<tag::v0alpha1::GetPropertiesRequest>,
-    ) -> Result<Response<tag::v0alpha1::GetPropertiesResponse>, Status> {
+    async fn set_gaps(&self, request: Request<SetGapsRequest>) -> Result<Response<()>, Status> {
         let request = request.into_inner();
 
         let tag_id = TagId(
@@ -908,89 +1536,494 @@ impl tag_service_server::TagService for TagService {
                 .ok_or_else(|| Status::invalid_argument("no tag specified"))?,
         );
 
-        run_unary(&self.sender, move |state| {
-            let tag = tag_id.tag(&state.pinnacle);
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(tag) = tag_id.tag(&state.pinnacle) else {
+                return;
+            };
 
-            let output_name = tag
-                .as_ref()
-                .and_then(|tag| tag.output(&state.pinnacle))
-                .map(|output| output.name());
-            let active = tag.as_ref().map(|tag| tag.active());
-            let name = tag.as_ref().map(|tag| tag.name());
-            let window_ids = tag
-                .as_ref()
-                .map(|tag| {
-                    state
-                        .pinnacle
-                        .windows
-                        .iter()
-                        .filter_map(|win| {
-                            win.with_state(|win_state| {
-                                win_state.tags.contains(tag).then_some(win_state.id.0)
-                            })
-                        })
-                        .collect()
-                })
-                .unwrap_or_default();
+            let mut gaps = tag.gaps();
+            if let Some(inner) = request.inner {
+                gaps.inner = inner;
+            }
+            if let Some(outer) = request.outer {
+                gaps.outer = outer;
+            }
+            tag.set_gaps(gaps);
 
-            tag::v0alpha1::GetPropertiesResponse {
-                active,
-                name,
-                output_name,
-                window_ids,
+            if !tag.active() {
+                return;
             }
-        })
-        .await
-    }
-}
 
-pub struct OutputService {
-    sender: StateFnSender,
-}
+            let Some(output) = tag.output(&state.pinnacle) else {
+                return;
+            };
 
-impl OutputService {
-    pub fn new(sender: StateFnSender) -> Self {
-        Self { sender }
+            state.pinnacle.request_layout(&output);
+            state.schedule_render(&output);
+        })
+        .await
     }
-}
 
-#[tonic::async_trait]
-impl output_service_server::OutputService for OutputService {
-    async fn set_location(
+    async fn set_corner_radius(
         &self,
-        request: Request<SetLocationRequest>,
+        request: Request<tag::v0alpha1::SetCornerRadiusRequest>,
     ) -> Result<Response<()>, Status> {
         let request = request.into_inner();
 
-        let output_name = OutputName(
+        let tag_id = TagId(
             request
-                .output_name
-                .ok_or_else(|| Status::invalid_argument("no output specified"))?,
+                .tag_id
+                .ok_or_else(|| Status::invalid_argument("no tag specified"))?,
         );
+        let radius = request.radius;
 
-        let x = request.x;
-        let y = request.y;
+        if radius.is_some_and(|radius| !radius.is_finite() || radius < 0.0) {
+            return Err(Status::invalid_argument("radius must be non-negative"));
+        }
 
         run_unary_no_response(&self.sender, move |state| {
-            if let Some(saved_state) = state
-                .pinnacle
-                .config
-                .connector_saved_states
-                .get_mut(&output_name)
-            {
-                if let Some(x) = x {
-                    saved_state.loc.x = x;
-                }
-                if let Some(y) = y {
-                    saved_state.loc.y = y;
-                }
-            } else {
-                state.pinnacle.config.connector_saved_states.insert(
-                    output_name.clone(),
-                    ConnectorSavedState {
-                        loc: (x.unwrap_or_default(), y.unwrap_or_default()).into(),
-                        ..Default::default()
-                    },
+            let Some(tag) = tag_id.tag(&state.pinnacle) else {
+                return;
+            };
+
+            tag.set_corner_radius(radius.map(f64::from));
+
+            if !tag.active() {
+                return;
+            }
+
+            let Some(output) = tag.output(&state.pinnacle) else {
+                return;
+            };
+
+            state.backend.reset_buffers(&output);
+            state.schedule_render(&output);
+        })
+        .await
+    }
+
+    async fn set_layout(&self, request: Request<SetLayoutRequest>) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let tag_id = TagId(
+            request
+                .tag_id
+                .ok_or_else(|| Status::invalid_argument("no tag specified"))?,
+        );
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(tag) = tag_id.tag(&state.pinnacle) else {
+                return;
+            };
+
+            if tag.layout() == request.layout {
+                return;
+            }
+
+            tag.set_layout(request.layout);
+
+            if !tag.active() {
+                return;
+            }
+
+            let Some(output) = tag.output(&state.pinnacle) else {
+                return;
+            };
+
+            state.pinnacle.request_layout(&output);
+            state.schedule_render(&output);
+        })
+        .await
+    }
+
+    async fn set_name(&self, request: Request<SetNameRequest>) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let tag_id = TagId(
+            request
+                .tag_id
+                .ok_or_else(|| Status::invalid_argument("no tag specified"))?,
+        );
+        let name = request
+            .name
+            .ok_or_else(|| Status::invalid_argument("no name specified"))?;
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(tag) = tag_id.tag(&state.pinnacle) else {
+                return;
+            };
+
+            if tag.name() == name {
+                return;
+            }
+
+            tag.set_name(name.clone());
+            state.pinnacle.session_changed();
+
+            state.pinnacle.signal_state.tag_renamed.signal(|buf| {
+                buf.push_back(TagRenamedResponse {
+                    tag_id: Some(tag_id.0),
+                    name: Some(name),
+                })
+            });
+        })
+        .await
+    }
+
+    async fn move_to_output(
+        &self,
+        request: Request<MoveToOutputRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let tag_id = TagId(
+            request
+                .tag_id
+                .ok_or_else(|| Status::invalid_argument("no tag specified"))?,
+        );
+        let output_name = OutputName(
+            request
+                .output_name
+                .ok_or_else(|| Status::invalid_argument("no output specified"))?,
+        );
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(tag) = tag_id.tag(&state.pinnacle) else {
+                return;
+            };
+            let Some(output) = output_name.output(&state.pinnacle) else {
+                return;
+            };
+
+            state.move_tag_to_output(&tag, &output);
+        })
+        .await
+    }
+
+    async fn reorder(&self, request: Request<ReorderRequest>) -> Result<Response<()>, Status> {
+        let ReorderRequest {
+            tag_id: Some(tag_id),
+            absolute_or_relative: Some(absolute_or_relative),
+        } = request.into_inner()
+        else {
+            return Err(Status::invalid_argument(
+                "tag_id or absolute_or_relative were null",
+            ));
+        };
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(tag) = TagId(tag_id).tag(&state.pinnacle) else {
+                return;
+            };
+            let Some(output) = tag.output(&state.pinnacle) else {
+                return;
+            };
+            let Some(current) =
+                output.with_state(|op_state| op_state.tags.iter().position(|tg| tg == &tag))
+            else {
+                return;
+            };
+
+            let index = match absolute_or_relative {
+                tag::v0alpha1::reorder_request::AbsoluteOrRelative::Index(index) => index as usize,
+                tag::v0alpha1::reorder_request::AbsoluteOrRelative::Offset(offset) => {
+                    current.saturating_add_signed(offset as isize)
+                }
+            };
+
+            state.pinnacle.set_tag_index(&tag, index);
+            state.pinnacle.session_changed();
+        })
+        .await
+    }
+
+    async fn set_dynamic(
+        &self,
+        request: Request<SetDynamicRequest>,
+    ) -> Result<Response<()>, Status> {
+        let enabled = request
+            .into_inner()
+            .enabled
+            .ok_or_else(|| Status::invalid_argument("no enabled specified"))?;
+
+        run_unary_no_response(&self.sender, move |state| {
+            state.pinnacle.config.dynamic_tags.enabled = enabled;
+            state.pinnacle.update_dynamic_tags();
+        })
+        .await
+    }
+
+    async fn add(&self, request: Request<AddRequest>) -> Result<Response<AddResponse>, Status> {
+        let request = request.into_inner();
+
+        let output_name = OutputName(
+            request
+                .output_name
+                .ok_or_else(|| Status::invalid_argument("no output specified"))?,
+        );
+
+        run_unary(&self.sender, move |state| {
+            let new_tags = request
+                .tag_names
+                .into_iter()
+                .map(Tag::new)
+                .collect::<Vec<_>>();
+
+            let tag_ids = new_tags
+                .iter()
+                .map(|tag| tag.id())
+                .map(|id| id.0)
+                .collect::<Vec<_>>();
+
+            state
+                .pinnacle
+                .config
+                .connector_saved_states
+                .entry(output_name.clone())
+                .or_default()
+                .tags
+                .extend(new_tags.clone());
+
+            if let Some(output) = output_name.output(&state.pinnacle) {
+                output.with_state_mut(|state| {
+                    state.tags.extend(new_tags.clone());
+                    debug!("tags added, are now {:?}", state.tags);
+                });
+            }
+
+            state.pinnacle.session_changed();
+
+            for tag in new_tags {
+                for window in state.pinnacle.windows.iter() {
+                    window.with_state_mut(|state| {
+                        for win_tag in state.tags.iter_mut() {
+                            if win_tag.id() == tag.id() {
+                                *win_tag = tag.clone();
+                            }
+                        }
+                    });
+                }
+            }
+
+            AddResponse { tag_ids }
+        })
+        .await
+    }
+
+    // TODO: test
+    async fn remove(&self, request: Request<RemoveRequest>) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let tag_ids = request.tag_ids.into_iter().map(TagId);
+        let fallback_tag_id = request.fallback_tag_id.map(TagId);
+
+        run_unary_no_response(&self.sender, move |state| {
+            let tags_to_remove = tag_ids
+                .flat_map(|id| id.tag(&state.pinnacle))
+                .collect::<Vec<_>>();
+            let fallback = fallback_tag_id.and_then(|id| id.tag(&state.pinnacle));
+
+            state.remove_tags(tags_to_remove, fallback);
+        })
+        .await
+    }
+
+    async fn get(
+        &self,
+        _request: Request<tag::v0alpha1::GetRequest>,
+    ) -> Result<Response<tag::v0alpha1::GetResponse>, Status> {
+        run_unary(&self.sender, move |state| {
+            let tag_ids = state
+                .pinnacle
+                .space
+                .outputs()
+                .flat_map(|op| op.with_state(|state| state.tags.clone()))
+                .map(|tag| tag.id())
+                .map(|id| id.0)
+                .collect::<Vec<_>>();
+
+            tag::v0alpha1::GetResponse { tag_ids }
+        })
+        .await
+    }
+
+    async fn get_properties(
+        &self,
+        request: Request<tag::v0alpha1::GetPropertiesRequest>,
+    ) -> Result<Response<tag::v0alpha1::GetPropertiesResponse>, Status> {
+        let request = request.into_inner();
+
+        let tag_id = TagId(
+            request
+                .tag_id
+                .ok_or_else(|| Status::invalid_argument("no tag specified"))?,
+        );
+
+        run_unary(&self.sender, move |state| {
+            let tag = tag_id.tag(&state.pinnacle);
+
+            let output_name = tag
+                .as_ref()
+                .and_then(|tag| tag.output(&state.pinnacle))
+                .map(|output| output.name());
+            let active = tag.as_ref().map(|tag| tag.active());
+            let name = tag.as_ref().map(|tag| tag.name());
+            let layout = tag.as_ref().and_then(|tag| tag.layout());
+            let urgent = tag.as_ref().map(|tag| state.pinnacle.tag_is_urgent(tag));
+            let hidden = tag.as_ref().map(|tag| tag.hidden());
+            let window_ids = tag
+                .as_ref()
+                .map(|tag| {
+                    state
+                        .pinnacle
+                        .windows
+                        .iter()
+                        .filter_map(|win| {
+                            win.with_state(|win_state| {
+                                win_state.tags.contains(tag).then_some(win_state.id.0)
+                            })
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            tag::v0alpha1::GetPropertiesResponse {
+                active,
+                name,
+                output_name,
+                window_ids,
+                layout,
+                urgent,
+                hidden,
+            }
+        })
+        .await
+    }
+}
+
+/// Convert an API modeline, rejecting null, out-of-range, or 0 Hz timings.
+fn modeline_from_api(modeline: output::v0alpha1::Modeline) -> Result<Modeline, Status> {
+    // poor man's try v2
+    let Some(modeline) = Some(modeline).and_then(|modeline| {
+        Some(Modeline {
+            clock: modeline.clock?,
+            hdisplay: modeline.hdisplay?.try_into().ok()?,
+            hsync_start: modeline.hsync_start?.try_into().ok()?,
+            hsync_end: modeline.hsync_end?.try_into().ok()?,
+            htotal: modeline.htotal?.try_into().ok()?,
+            vdisplay: modeline.vdisplay?.try_into().ok()?,
+            vsync_start: modeline.vsync_start?.try_into().ok()?,
+            vsync_end: modeline.vsync_end?.try_into().ok()?,
+            vtotal: modeline.vtotal?.try_into().ok()?,
+            hsync_pos: modeline.hsync_pos?,
+            vsync_pos: modeline.vsync_pos?,
+        })
+    }) else {
+        return Err(Status::invalid_argument(
+            "modeline had null or out-of-range fields",
+        ));
+    };
+
+    if modeline.refresh_millihz() == 0 {
+        return Err(Status::invalid_argument("modeline has a refresh rate of 0"));
+    }
+
+    Ok(modeline)
+}
+
+pub struct OutputService {
+    sender: StateFnSender,
+}
+
+impl OutputService {
+    pub fn new(sender: StateFnSender) -> Self {
+        Self { sender }
+    }
+}
+
+#[tonic::async_trait]
+impl output_service_server::OutputService for OutputService {
+    async fn set_location(
+        &self,
+        request: Request<SetLocationRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let output_name = OutputName(
+            request
+                .output_name
+                .ok_or_else(|| Status::invalid_argument("no output specified"))?,
+        );
+
+        let alignment = request.alignment();
+        let relative = match request.relative_to {
+            Some(relative_to) => {
+                let alignment = match alignment {
+                    output::v0alpha1::Alignment::Unspecified => {
+                        return Err(Status::invalid_argument("alignment was unspecified"));
+                    }
+                    output::v0alpha1::Alignment::TopAlignLeft => Alignment::TopAlignLeft,
+                    output::v0alpha1::Alignment::TopAlignCenter => Alignment::TopAlignCenter,
+                    output::v0alpha1::Alignment::TopAlignRight => Alignment::TopAlignRight,
+                    output::v0alpha1::Alignment::BottomAlignLeft => Alignment::BottomAlignLeft,
+                    output::v0alpha1::Alignment::BottomAlignCenter => Alignment::BottomAlignCenter,
+                    output::v0alpha1::Alignment::BottomAlignRight => Alignment::BottomAlignRight,
+                    output::v0alpha1::Alignment::LeftAlignTop => Alignment::LeftAlignTop,
+                    output::v0alpha1::Alignment::LeftAlignCenter => Alignment::LeftAlignCenter,
+                    output::v0alpha1::Alignment::LeftAlignBottom => Alignment::LeftAlignBottom,
+                    output::v0alpha1::Alignment::RightAlignTop => Alignment::RightAlignTop,
+                    output::v0alpha1::Alignment::RightAlignCenter => Alignment::RightAlignCenter,
+                    output::v0alpha1::Alignment::RightAlignBottom => Alignment::RightAlignBottom,
+                };
+                if relative_to == output_name.0 {
+                    return Err(Status::invalid_argument(
+                        "cannot place an output relative to itself",
+                    ));
+                }
+                Some((OutputName(relative_to), alignment))
+            }
+            None => None,
+        };
+
+        let x = request.x;
+        let y = request.y;
+
+        run_unary_no_response(&self.sender, move |state| {
+            if let Some(relative) = relative {
+                state
+                    .pinnacle
+                    .config
+                    .relative_output_locs
+                    .insert(output_name, relative);
+                state.pinnacle.update_relative_output_locs();
+                return;
+            }
+
+            state
+                .pinnacle
+                .config
+                .relative_output_locs
+                .remove(&output_name);
+
+            if let Some(saved_state) = state
+                .pinnacle
+                .config
+                .connector_saved_states
+                .get_mut(&output_name)
+            {
+                if let Some(x) = x {
+                    saved_state.loc.x = x;
+                }
+                if let Some(y) = y {
+                    saved_state.loc.y = y;
+                }
+            } else {
+                state.pinnacle.config.connector_saved_states.insert(
+                    output_name.clone(),
+                    ConnectorSavedState {
+                        loc: (x.unwrap_or_default(), y.unwrap_or_default()).into(),
+                        ..Default::default()
+                    },
                 );
             }
 
@@ -1013,110 +2046,569 @@ impl output_service_server::OutputService for OutputService {
         .await
     }
 
-    async fn set_mode(&self, request: Request<SetModeRequest>) -> Result<Response<()>, Status> {
+    async fn set_mode(&self, request: Request<SetModeRequest>) -> Result<Response<()>, Status> {
+        let SetModeRequest {
+            output_name: Some(output_name),
+            pixel_width: Some(pixel_width),
+            pixel_height: Some(pixel_height),
+            refresh_rate_millihz,
+        } = request.into_inner()
+        else {
+            return Err(Status::invalid_argument(
+                "output_name, pixel_width, or pixel_height were null",
+            ));
+        };
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(output) = OutputName(output_name).output(&state.pinnacle) else {
+                return;
+            };
+
+            let size = (pixel_width as i32, pixel_height as i32).into();
+
+            // If the output doesn't advertise a matching mode (as is the case on non-udev
+            // backends), pass the requested mode through as-is. Udev will reject it if
+            // the connector doesn't support it.
+            let mode =
+                find_mode(&output.modes(), size, refresh_rate_millihz).unwrap_or_else(|| {
+                    smithay::output::Mode {
+                        size,
+                        refresh: refresh_rate_millihz
+                            .map(|refresh| refresh as i32)
+                            .or_else(|| output.current_mode().map(|mode| mode.refresh))
+                            .unwrap_or(60_000),
+                    }
+                });
+
+            state.resize_output(&output, mode);
+        })
+        .await
+    }
+
+    async fn set_modeline(
+        &self,
+        request: Request<SetModelineRequest>,
+    ) -> Result<Response<()>, Status> {
+        let SetModelineRequest {
+            output_name: Some(output_name),
+            modeline: Some(modeline),
+        } = request.into_inner()
+        else {
+            return Err(Status::invalid_argument(
+                "output_name or modeline were null",
+            ));
+        };
+
+        let modeline = modeline_from_api(modeline)?;
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(output) = OutputName(output_name).output(&state.pinnacle) else {
+                return;
+            };
+
+            state.set_output_modeline(&output, modeline);
+        })
+        .await
+    }
+
+    async fn add_custom_mode(
+        &self,
+        request: Request<AddCustomModeRequest>,
+    ) -> Result<Response<()>, Status> {
+        let AddCustomModeRequest {
+            output_name: Some(output_name),
+            mode: Some(mode),
+        } = request.into_inner()
+        else {
+            return Err(Status::invalid_argument("output_name or mode were null"));
+        };
+
+        let modeline = match mode {
+            add_custom_mode_request::Mode::Modeline(modeline) => modeline_from_api(modeline)?,
+            add_custom_mode_request::Mode::ModelineString(modeline) => {
+                let modeline = modeline.parse::<Modeline>().map_err(|err| {
+                    Status::invalid_argument(format!("invalid modeline: {err:#}"))
+                })?;
+                if modeline.refresh_millihz() == 0 {
+                    return Err(Status::invalid_argument("modeline has a refresh rate of 0"));
+                }
+                modeline
+            }
+            add_custom_mode_request::Mode::Cvt(cvt) => {
+                let (Some(width), Some(height), Some(refresh_rate_millihz)) =
+                    (cvt.pixel_width, cvt.pixel_height, cvt.refresh_rate_millihz)
+                else {
+                    return Err(Status::invalid_argument(
+                        "pixel_width, pixel_height, or refresh_rate_millihz were null",
+                    ));
+                };
+                let (Ok(width), Ok(height)) = (u16::try_from(width), u16::try_from(height)) else {
+                    return Err(Status::invalid_argument("mode size is too large"));
+                };
+                if width == 0 || height == 0 || refresh_rate_millihz == 0 {
+                    return Err(Status::invalid_argument(
+                        "mode has a zero dimension or refresh",
+                    ));
+                }
+                Modeline::cvt(
+                    width,
+                    height,
+                    refresh_rate_millihz as f64 / 1000.0,
+                    cvt.reduced_blanking.unwrap_or_default(),
+                )
+            }
+        };
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(output) = OutputName(output_name).output(&state.pinnacle) else {
+                return;
+            };
+
+            add_custom_mode(&output, modeline);
+        })
+        .await
+    }
+
+    async fn set_scale(&self, request: Request<SetScaleRequest>) -> Result<Response<()>, Status> {
+        let SetScaleRequest {
+            output_name: Some(output_name),
+            absolute_or_relative: Some(absolute_or_relative),
+        } = request.into_inner()
+        else {
+            return Err(Status::invalid_argument(
+                "output_name or absolute_or_relative were null",
+            ));
+        };
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(output) = OutputName(output_name).output(&state.pinnacle) else {
+                return;
+            };
+
+            let mut current_scale = output.current_scale().fractional_scale();
+
+            match absolute_or_relative {
+                AbsoluteOrRelative::Absolute(abs) => current_scale = abs as f64,
+                AbsoluteOrRelative::Relative(rel) => current_scale += rel as f64,
+            }
+
+            // wp-fractional-scale-v1 communicates scales in 120ths, so round to the nearest
+            // one to keep clients and the compositor in agreement.
+            current_scale = f64::max((current_scale * 120.0).round() / 120.0, 0.25);
+
+            if output.current_scale().fractional_scale() == current_scale {
+                return;
+            }
+
+            state.pinnacle.change_output_state(
+                &output,
+                None,
+                None,
+                Some(Scale::Fractional(current_scale)),
+                None,
+            );
+            state.pinnacle.request_layout(&output);
+            state.schedule_render(&output);
+        })
+        .await
+    }
+
+    async fn set_transform(
+        &self,
+        request: Request<SetTransformRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let smithay_transform = match request.transform() {
+            output::v0alpha1::Transform::Unspecified => {
+                return Err(Status::invalid_argument("transform was unspecified"));
+            }
+            output::v0alpha1::Transform::Normal => smithay::utils::Transform::Normal,
+            output::v0alpha1::Transform::Transform90 => smithay::utils::Transform::_90,
+            output::v0alpha1::Transform::Transform180 => smithay::utils::Transform::_180,
+            output::v0alpha1::Transform::Transform270 => smithay::utils::Transform::_270,
+            output::v0alpha1::Transform::Flipped => smithay::utils::Transform::Flipped,
+            output::v0alpha1::Transform::Flipped90 => smithay::utils::Transform::Flipped90,
+            output::v0alpha1::Transform::Flipped180 => smithay::utils::Transform::Flipped180,
+            output::v0alpha1::Transform::Flipped270 => smithay::utils::Transform::Flipped270,
+        };
+
+        let Some(output_name) = request.output_name else {
+            return Err(Status::invalid_argument("output_name was null"));
+        };
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(output) = OutputName(output_name).output(&state.pinnacle) else {
+                return;
+            };
+
+            if output.current_transform() == smithay_transform {
+                return;
+            }
+
+            state
+                .pinnacle
+                .change_output_state(&output, None, Some(smithay_transform), None, None);
+            state.pinnacle.request_layout(&output);
+            state.schedule_render(&output);
+        })
+        .await
+    }
+
+    async fn set_vrr(&self, request: Request<SetVrrRequest>) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let vrr = match request.vrr() {
+            output::v0alpha1::Vrr::Unspecified => {
+                return Err(Status::invalid_argument("vrr was unspecified"));
+            }
+            output::v0alpha1::Vrr::Off => Vrr::Off,
+            output::v0alpha1::Vrr::On => Vrr::On,
+            output::v0alpha1::Vrr::OnDemand => Vrr::OnDemand,
+        };
+
+        let Some(output_name) = request.output_name else {
+            return Err(Status::invalid_argument("output_name was null"));
+        };
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(output) = OutputName(output_name).output(&state.pinnacle) else {
+                return;
+            };
+
+            output.with_state_mut(|state| state.vrr = vrr);
+            state.schedule_render(&output);
+        })
+        .await
+    }
+
+    async fn set_max_render_time(
+        &self,
+        request: Request<SetMaxRenderTimeRequest>,
+    ) -> Result<Response<()>, Status> {
         let request = request.into_inner();
 
-        run_unary_no_response(&self.sender, |state| {
-            let Some(output) = request
-                .output_name
-                .clone()
-                .map(OutputName)
-                .and_then(|name| name.output(&state.pinnacle))
-            else {
-                return;
-            };
+        let Some(output_name) = request.output_name else {
+            return Err(Status::invalid_argument("output_name was null"));
+        };
 
-            // poor man's try v2
-            let Some(mode) = Some(request).and_then(|request| {
-                Some(smithay::output::Mode {
-                    size: (request.pixel_width? as i32, request.pixel_height? as i32).into(),
-                    refresh: request.refresh_rate_millihz? as i32,
-                })
-            }) else {
+        let max_render_time = match request.max_render_time {
+            None | Some(set_max_render_time_request::MaxRenderTime::Millis(0)) => {
+                MaxRenderTime::Off
+            }
+            Some(set_max_render_time_request::MaxRenderTime::Millis(millis)) => {
+                MaxRenderTime::Fixed(Duration::from_millis(millis.into()))
+            }
+            Some(set_max_render_time_request::MaxRenderTime::Adaptive(())) => {
+                MaxRenderTime::Adaptive
+            }
+        };
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(output) = OutputName(output_name).output(&state.pinnacle) else {
                 return;
             };
 
-            state.resize_output(&output, mode);
+            output.with_state_mut(|state| state.max_render_time = max_render_time);
         })
         .await
     }
 
-    async fn set_scale(&self, request: Request<SetScaleRequest>) -> Result<Response<()>, Status> {
-        let SetScaleRequest {
-            output_name: Some(output_name),
-            absolute_or_relative: Some(absolute_or_relative),
-        } = request.into_inner()
-        else {
-            return Err(Status::invalid_argument(
-                "output_name or absolute_or_relative were null",
-            ));
+    async fn set_max_fps(
+        &self,
+        request: Request<SetMaxFpsRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let Some(output_name) = request.output_name else {
+            return Err(Status::invalid_argument("output_name was null"));
         };
 
+        let max_fps = request.max_fps.and_then(NonZeroU32::new);
+
         run_unary_no_response(&self.sender, move |state| {
             let Some(output) = OutputName(output_name).output(&state.pinnacle) else {
                 return;
             };
 
-            let mut current_scale = output.current_scale().fractional_scale();
+            output.with_state_mut(|state| state.max_fps = max_fps);
+            state.schedule_render(&output);
+        })
+        .await
+    }
 
-            match absolute_or_relative {
-                AbsoluteOrRelative::Absolute(abs) => current_scale = abs as f64,
-                AbsoluteOrRelative::Relative(rel) => current_scale += rel as f64,
+    async fn set_color_filter(
+        &self,
+        request: Request<SetColorFilterRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let color_filter = match request.color_filter() {
+            output::v0alpha1::ColorFilter::Unspecified => {
+                return Err(Status::invalid_argument("color_filter was unspecified"));
+            }
+            output::v0alpha1::ColorFilter::None => None,
+            output::v0alpha1::ColorFilter::Grayscale => Some(ColorFilter::Grayscale),
+            output::v0alpha1::ColorFilter::Invert => Some(ColorFilter::Invert),
+            output::v0alpha1::ColorFilter::Protanopia => Some(ColorFilter::Protanopia),
+            output::v0alpha1::ColorFilter::Deuteranopia => Some(ColorFilter::Deuteranopia),
+            output::v0alpha1::ColorFilter::Tritanopia => Some(ColorFilter::Tritanopia),
+            output::v0alpha1::ColorFilter::ProtanopiaCorrection => {
+                Some(ColorFilter::ProtanopiaCorrection)
             }
+            output::v0alpha1::ColorFilter::DeuteranopiaCorrection => {
+                Some(ColorFilter::DeuteranopiaCorrection)
+            }
+            output::v0alpha1::ColorFilter::TritanopiaCorrection => {
+                Some(ColorFilter::TritanopiaCorrection)
+            }
+        };
+
+        let Some(output_name) = request.output_name else {
+            return Err(Status::invalid_argument("output_name was null"));
+        };
 
-            current_scale = f64::max(current_scale, 0.25);
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(output) = OutputName(output_name).output(&state.pinnacle) else {
+                return;
+            };
 
-            state.pinnacle.change_output_state(
-                &output,
-                None,
-                None,
-                Some(Scale::Fractional(current_scale)),
-                None,
-            );
-            state.pinnacle.request_layout(&output);
+            output.with_state_mut(|state| state.color_filter = color_filter);
             state.schedule_render(&output);
         })
         .await
     }
 
-    async fn set_transform(
+    async fn set_night_light(
         &self,
-        request: Request<SetTransformRequest>,
+        request: Request<SetNightLightRequest>,
     ) -> Result<Response<()>, Status> {
         let request = request.into_inner();
 
-        let smithay_transform = match request.transform() {
-            output::v0alpha1::Transform::Unspecified => {
-                return Err(Status::invalid_argument("transform was unspecified"));
+        let Some(output_name) = request.output_name else {
+            return Err(Status::invalid_argument("output_name was null"));
+        };
+
+        let night_light = match request.temperature {
+            None => None,
+            Some(temperature) => {
+                if !TEMPERATURE_RANGE.contains(&temperature) {
+                    return Err(Status::invalid_argument(format!(
+                        "temperature must be between {} and {}",
+                        TEMPERATURE_RANGE.start(),
+                        TEMPERATURE_RANGE.end()
+                    )));
+                }
+
+                let schedule = match request.schedule {
+                    None => NightLightSchedule::Always,
+                    Some(set_night_light_request::Schedule::Fixed(fixed)) => {
+                        let time = |minute: Option<u32>| {
+                            minute
+                                .filter(|minute| *minute < 24 * 60)
+                                .and_then(|minute| {
+                                    NaiveTime::from_hms_opt(minute / 60, minute % 60, 0)
+                                })
+                        };
+                        let (Some(from), Some(to)) =
+                            (time(fixed.from_minute), time(fixed.to_minute))
+                        else {
+                            return Err(Status::invalid_argument(
+                                "from_minute and to_minute must be between 0 and 1439",
+                            ));
+                        };
+                        NightLightSchedule::Fixed { from, to }
+                    }
+                    Some(set_night_light_request::Schedule::Location(location)) => {
+                        let (Some(latitude), Some(longitude)) =
+                            (location.latitude, location.longitude)
+                        else {
+                            return Err(Status::invalid_argument("latitude or longitude was null"));
+                        };
+                        if !(-90.0..=90.0).contains(&latitude)
+                            || !(-180.0..=180.0).contains(&longitude)
+                        {
+                            return Err(Status::invalid_argument(
+                                "latitude or longitude was out of range",
+                            ));
+                        }
+                        NightLightSchedule::Sun {
+                            latitude,
+                            longitude,
+                        }
+                    }
+                };
+
+                Some(NightLight {
+                    temperature,
+                    schedule,
+                })
             }
-            output::v0alpha1::Transform::Normal => smithay::utils::Transform::Normal,
-            output::v0alpha1::Transform::Transform90 => smithay::utils::Transform::_90,
-            output::v0alpha1::Transform::Transform180 => smithay::utils::Transform::_180,
-            output::v0alpha1::Transform::Transform270 => smithay::utils::Transform::_270,
-            output::v0alpha1::Transform::Flipped => smithay::utils::Transform::Flipped,
-            output::v0alpha1::Transform::Flipped90 => smithay::utils::Transform::Flipped90,
-            output::v0alpha1::Transform::Flipped180 => smithay::utils::Transform::Flipped180,
-            output::v0alpha1::Transform::Flipped270 => smithay::utils::Transform::Flipped270,
         };
 
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(output) = OutputName(output_name).output(&state.pinnacle) else {
+                return;
+            };
+
+            state.set_night_light(&output, night_light);
+        })
+        .await
+    }
+
+    async fn set_wallpaper(
+        &self,
+        request: Request<SetWallpaperRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
         let Some(output_name) = request.output_name else {
             return Err(Status::invalid_argument("output_name was null"));
         };
 
+        let wallpaper = match request.wallpaper {
+            None => None,
+            Some(set_wallpaper_request::Wallpaper::Color(color)) => {
+                Some(RequestedWallpaper::Color(
+                    color.r.unwrap_or_default(),
+                    color.g.unwrap_or_default(),
+                    color.b.unwrap_or_default(),
+                ))
+            }
+            Some(set_wallpaper_request::Wallpaper::Image(image)) => {
+                let mode = match image.mode() {
+                    output::v0alpha1::WallpaperMode::Unspecified
+                    | output::v0alpha1::WallpaperMode::Fill => WallpaperMode::Fill,
+                    output::v0alpha1::WallpaperMode::Fit => WallpaperMode::Fit,
+                    output::v0alpha1::WallpaperMode::Stretch => WallpaperMode::Stretch,
+                };
+
+                let Some(path) = image.path else {
+                    return Err(Status::invalid_argument("path was null"));
+                };
+                let path = expand_path(&path)?;
+
+                // Decoding can take a while, so it doesn't happen on the compositor's thread
+                let image = tokio::task::spawn_blocking(move || WallpaperImage::decode(&path))
+                    .await
+                    .map_err(|err| Status::internal(format!("failed to load wallpaper: {err}")))?
+                    .map_err(|err| {
+                        Status::invalid_argument(format!("failed to load wallpaper: {err:#}"))
+                    })?;
+
+                Some(RequestedWallpaper::Image(image, mode))
+            }
+        };
+
         run_unary_no_response(&self.sender, move |state| {
             let Some(output) = OutputName(output_name).output(&state.pinnacle) else {
                 return;
             };
 
-            state
-                .pinnacle
-                .change_output_state(&output, None, Some(smithay_transform), None, None);
-            state.pinnacle.request_layout(&output);
+            // Render buffers can't be sent between threads, so it's created here
+            let wallpaper = wallpaper.map(|wallpaper| match wallpaper {
+                RequestedWallpaper::Color(r, g, b) => Wallpaper::color(r, g, b),
+                RequestedWallpaper::Image(image, mode) => {
+                    Wallpaper::image(WallpaperImage::new(&image), mode)
+                }
+            });
+
+            output.with_state_mut(|state| state.wallpaper = wallpaper);
             state.schedule_render(&output);
         })
         .await
     }
 
+    async fn set_powered(
+        &self,
+        request: Request<SetPoweredRequest>,
+    ) -> Result<Response<()>, Status> {
+        let SetPoweredRequest {
+            output_name: Some(output_name),
+            powered: Some(powered),
+        } = request.into_inner()
+        else {
+            return Err(Status::invalid_argument("output_name or powered were null"));
+        };
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(output) = state
+                .pinnacle
+                .output_including_disabled(&OutputName(output_name))
+            else {
+                return;
+            };
+
+            state.set_output_powered(&output, powered);
+        })
+        .await
+    }
+
+    async fn set_enabled(
+        &self,
+        request: Request<SetEnabledRequest>,
+    ) -> Result<Response<()>, Status> {
+        let SetEnabledRequest {
+            output_name: Some(output_name),
+            enabled: Some(enabled),
+        } = request.into_inner()
+        else {
+            return Err(Status::invalid_argument("output_name or enabled were null"));
+        };
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(output) = state
+                .pinnacle
+                .output_including_disabled(&OutputName(output_name))
+            else {
+                return;
+            };
+
+            if enabled {
+                state.enable_output(&output);
+            } else {
+                state.disable_output(&output);
+            }
+        })
+        .await
+    }
+
+    async fn set_primary(
+        &self,
+        request: Request<SetPrimaryRequest>,
+    ) -> Result<Response<()>, Status> {
+        let Some(output_name) = request.into_inner().output_name else {
+            return Err(Status::invalid_argument("output_name was null"));
+        };
+
+        run_unary_no_response(&self.sender, move |state| {
+            state.pinnacle.set_primary_output(OutputName(output_name));
+        })
+        .await
+    }
+
+    async fn save_profile(
+        &self,
+        _request: Request<SaveProfileRequest>,
+    ) -> Result<Response<()>, Status> {
+        run_unary_no_response(&self.sender, |state| {
+            state.pinnacle.save_output_profile();
+        })
+        .await
+    }
+
+    async fn clear_profiles(
+        &self,
+        request: Request<ClearProfilesRequest>,
+    ) -> Result<Response<()>, Status> {
+        let all = request.into_inner().all.unwrap_or_default();
+
+        run_unary_no_response(&self.sender, move |state| {
+            state.pinnacle.clear_output_profiles(all);
+        })
+        .await
+    }
+
     async fn get(
         &self,
         _request: Request<output::v0alpha1::GetRequest>,
@@ -1126,6 +2618,7 @@ impl output_service_server::OutputService for OutputService {
                 .pinnacle
                 .space
                 .outputs()
+                .chain(state.pinnacle.disabled_outputs.iter())
                 .map(|output| output.name())
                 .collect::<Vec<_>>();
 
@@ -1155,7 +2648,7 @@ impl output_service_server::OutputService for OutputService {
         };
 
         run_unary(&self.sender, move |state| {
-            let output = output_name.output(&state.pinnacle);
+            let output = state.pinnacle.output_including_disabled(&output_name);
 
             let logical_size = output
                 .as_ref()
@@ -1240,6 +2733,26 @@ impl output_service_server::OutputService for OutputService {
                 output.with_state(|state| state.serial.map(|serial| serial.get()))
             });
 
+            let vrr = output.as_ref().map(|output| {
+                (match output.with_state(|state| state.vrr) {
+                    Vrr::Off => output::v0alpha1::Vrr::Off,
+                    Vrr::On => output::v0alpha1::Vrr::On,
+                    Vrr::OnDemand => output::v0alpha1::Vrr::OnDemand,
+                }) as i32
+            });
+
+            let enabled = output
+                .as_ref()
+                .map(|output| output.with_state(|state| state.enabled));
+
+            let powered = output
+                .as_ref()
+                .map(|output| output.with_state(|state| state.powered));
+
+            let primary = output.as_ref().map(|output| {
+                state.pinnacle.primary_output_name.as_ref() == Some(&OutputName(output.name()))
+            });
+
             output::v0alpha1::GetPropertiesResponse {
                 make,
                 model,
@@ -1257,6 +2770,10 @@ impl output_service_server::OutputService for OutputService {
                 scale,
                 transform,
                 serial,
+                vrr,
+                enabled,
+                powered,
+                primary,
             }
         })
         .await
@@ -1324,4 +2841,92 @@ impl render_service_server::RenderService for RenderService {
         })
         .await
     }
+
+    async fn set_animations_enabled(
+        &self,
+        request: Request<SetAnimationsEnabledRequest>,
+    ) -> Result<Response<()>, Status> {
+        let Some(enabled) = request.into_inner().enabled else {
+            return Err(Status::invalid_argument("no enabled specified"));
+        };
+
+        run_unary_no_response(&self.sender, move |state| {
+            state.pinnacle.config.animations.enabled = enabled;
+        })
+        .await
+    }
+
+    async fn set_corner_radius(
+        &self,
+        request: Request<SetCornerRadiusRequest>,
+    ) -> Result<Response<()>, Status> {
+        let Some(radius) = request.into_inner().radius else {
+            return Err(Status::invalid_argument("no radius specified"));
+        };
+
+        if !radius.is_finite() || radius < 0.0 {
+            return Err(Status::invalid_argument("radius must be non-negative"));
+        }
+
+        run_unary_no_response(&self.sender, move |state| {
+            state.pinnacle.config.render_settings.corner_radius = f64::from(radius);
+            for output in state.pinnacle.space.outputs().cloned().collect::<Vec<_>>() {
+                state.backend.reset_buffers(&output);
+                state.schedule_render(&output);
+            }
+        })
+        .await
+    }
+
+    async fn set_blur(&self, request: Request<SetBlurRequest>) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let Some(passes) = request.passes else {
+            return Err(Status::invalid_argument("no passes specified"));
+        };
+        let Some(offset) = request.offset else {
+            return Err(Status::invalid_argument("no offset specified"));
+        };
+
+        if !offset.is_finite() || offset < 0.0 {
+            return Err(Status::invalid_argument("offset must be non-negative"));
+        }
+
+        let layer_namespaces = request.layer_namespaces;
+
+        run_unary_no_response(&self.sender, move |state| {
+            state.pinnacle.config.render_settings.blur = BlurSettings {
+                passes: passes.clamp(1, blur::MAX_PASSES),
+                offset,
+                layer_namespaces,
+            };
+            for output in state.pinnacle.space.outputs().cloned().collect::<Vec<_>>() {
+                state.backend.reset_buffers(&output);
+                state.schedule_render(&output);
+            }
+        })
+        .await
+    }
+
+    async fn set_dim_inactive(
+        &self,
+        request: Request<SetDimInactiveRequest>,
+    ) -> Result<Response<()>, Status> {
+        let Some(dim) = request.into_inner().dim else {
+            return Err(Status::invalid_argument("no dim specified"));
+        };
+
+        if !(0.0..=1.0).contains(&dim) {
+            return Err(Status::invalid_argument("dim must be between 0.0 and 1.0"));
+        }
+
+        run_unary_no_response(&self.sender, move |state| {
+            state.pinnacle.config.render_settings.dim_inactive = dim;
+            for output in state.pinnacle.space.outputs().cloned().collect::<Vec<_>>() {
+                state.backend.reset_buffers(&output);
+                state.schedule_render(&output);
+            }
+        })
+        .await
+    }
 }
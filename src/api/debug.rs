@@ -0,0 +1,72 @@
+use pinnacle_api_defs::pinnacle::{
+    debug::v0alpha1::{debug_service_server, SetDamageVisualizationRequest, SetPerfOverlayRequest},
+    v0alpha1::SetOrToggle,
+};
+use tonic::{Request, Response, Status};
+
+use super::{run_unary_no_response, StateFnSender};
+
+pub struct DebugService {
+    sender: StateFnSender,
+}
+
+impl DebugService {
+    pub fn new(sender: StateFnSender) -> Self {
+        Self { sender }
+    }
+}
+
+#[tonic::async_trait]
+impl debug_service_server::DebugService for DebugService {
+    async fn set_perf_overlay(
+        &self,
+        request: Request<SetPerfOverlayRequest>,
+    ) -> Result<Response<()>, Status> {
+        let set_or_toggle = request.into_inner().set_or_toggle();
+
+        if set_or_toggle == SetOrToggle::Unspecified {
+            return Err(Status::invalid_argument("unspecified set or toggle"));
+        }
+
+        run_unary_no_response(&self.sender, move |state| {
+            let perf_overlay = &mut state.pinnacle.debug_settings.perf_overlay;
+            *perf_overlay = match set_or_toggle {
+                SetOrToggle::Set => true,
+                SetOrToggle::Unset => false,
+                SetOrToggle::Toggle => !*perf_overlay,
+                SetOrToggle::Unspecified => unreachable!(),
+            };
+
+            for output in state.pinnacle.space.outputs().cloned().collect::<Vec<_>>() {
+                state.schedule_render(&output);
+            }
+        })
+        .await
+    }
+
+    async fn set_damage_visualization(
+        &self,
+        request: Request<SetDamageVisualizationRequest>,
+    ) -> Result<Response<()>, Status> {
+        let set_or_toggle = request.into_inner().set_or_toggle();
+
+        if set_or_toggle == SetOrToggle::Unspecified {
+            return Err(Status::invalid_argument("unspecified set or toggle"));
+        }
+
+        run_unary_no_response(&self.sender, move |state| {
+            let damage_visualization = &mut state.pinnacle.debug_settings.damage_visualization;
+            *damage_visualization = match set_or_toggle {
+                SetOrToggle::Set => true,
+                SetOrToggle::Unset => false,
+                SetOrToggle::Toggle => !*damage_visualization,
+                SetOrToggle::Unspecified => unreachable!(),
+            };
+
+            for output in state.pinnacle.space.outputs().cloned().collect::<Vec<_>>() {
+                state.schedule_render(&output);
+            }
+        })
+        .await
+    }
+}
@@ -1,12 +1,19 @@
-use pinnacle_api_defs::pinnacle::layout::v0alpha1::{
-    layout_request::{self, ExplicitLayout},
-    layout_service_server, LayoutRequest, LayoutResponse,
+use pinnacle_api_defs::pinnacle::{
+    layout::{
+        self,
+        v0alpha1::{
+            layout_request::{self, ExplicitLayout},
+            layout_service_server, LayoutRequest, LayoutResponse, SetFallbackLayoutRequest,
+            SetMonocleIndicatorRequest,
+        },
+    },
+    v0alpha1::SetOrToggle,
 };
 use tonic::{Request, Response, Status, Streaming};
 
-use crate::output::OutputName;
+use crate::{layout::fallback::FallbackLayout, output::OutputName, state::State};
 
-use super::{run_bidirectional_streaming, ResponseStream, StateFnSender};
+use super::{run_bidirectional_streaming, run_unary_no_response, ResponseStream, StateFnSender};
 
 pub struct LayoutService {
     sender: StateFnSender,
@@ -27,6 +34,7 @@ impl layout_service_server::LayoutService for LayoutService {
         request: Request<Streaming<LayoutRequest>>,
     ) -> Result<Response<Self::LayoutStream>, Status> {
         let in_stream = request.into_inner();
+        let fn_sender = self.sender.clone();
 
         run_bidirectional_streaming(
             self.sender.clone(),
@@ -63,9 +71,68 @@ impl layout_service_server::LayoutService for LayoutService {
                     }
                 }
             },
-            |state, sender, _join_handle| {
-                state.pinnacle.layout_state.layout_request_sender = Some(sender);
+            move |state, sender, join_handle| {
+                state.pinnacle.layout_state.layout_request_sender = Some(sender.clone());
+                state.relayout_all();
+
+                // Fall back to compositor-side layouts once this client goes away
+                tokio::spawn(async move {
+                    let _ = join_handle.await;
+                    let _ = fn_sender.send(Box::new(move |state: &mut State| {
+                        state.layout_client_disconnected(&sender);
+                    }));
+                });
             },
         )
     }
+
+    async fn set_fallback_layout(
+        &self,
+        request: Request<SetFallbackLayoutRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let fallback_layout = match request.layout() {
+            layout::v0alpha1::FallbackLayout::Unspecified => {
+                return Err(Status::invalid_argument("unspecified layout"));
+            }
+            layout::v0alpha1::FallbackLayout::MasterStack => FallbackLayout::MasterStack,
+            layout::v0alpha1::FallbackLayout::Dwindle => FallbackLayout::Dwindle,
+            layout::v0alpha1::FallbackLayout::Grid => FallbackLayout::Grid,
+        };
+
+        run_unary_no_response(&self.sender, move |state| {
+            state.pinnacle.layout_state.fallback_layout = fallback_layout;
+            if state.pinnacle.layout_state.layout_request_sender.is_none() {
+                state.relayout_all();
+            }
+        })
+        .await
+    }
+
+    async fn set_monocle_indicator(
+        &self,
+        request: Request<SetMonocleIndicatorRequest>,
+    ) -> Result<Response<()>, Status> {
+        let set_or_toggle = request.into_inner().set_or_toggle();
+
+        if set_or_toggle == SetOrToggle::Unspecified {
+            return Err(Status::invalid_argument("unspecified set or toggle"));
+        }
+
+        run_unary_no_response(&self.sender, move |state| {
+            let monocle_indicator = &mut state.pinnacle.layout_state.monocle_indicator;
+            *monocle_indicator = match set_or_toggle {
+                SetOrToggle::Set => true,
+                SetOrToggle::Unset => false,
+                SetOrToggle::Toggle => !*monocle_indicator,
+                SetOrToggle::Unspecified => unreachable!(),
+            };
+
+            for output in state.pinnacle.space.outputs().cloned().collect::<Vec<_>>() {
+                state.schedule_render(&output);
+            }
+        })
+        .await
+    }
 }
@@ -0,0 +1,225 @@
+use std::path::PathBuf;
+
+use pinnacle_api_defs::pinnacle::{
+    screenshot::v0alpha1::{
+        screenshot_request::Target, screenshot_service_server, start_recording_request,
+        ScreenshotRequest, ScreenshotResponse, StartRecordingRequest, StopRecordingRequest,
+        WindowThumbnailRequest,
+    },
+    v0alpha1::Geometry,
+};
+use smithay::utils::{Logical, Rectangle, Size};
+use tonic::{Request, Response, Status};
+
+use crate::{
+    output::OutputName,
+    recording::DEFAULT_FRAMERATE,
+    screenshot::{Screenshot, ScreenshotTarget},
+    state::State,
+    window::window_state::WindowId,
+};
+
+use super::{expand_path, run_unary, run_unary_no_response, StateFnSender};
+
+pub struct ScreenshotService {
+    sender: StateFnSender,
+}
+
+impl ScreenshotService {
+    pub fn new(sender: StateFnSender) -> Self {
+        Self { sender }
+    }
+}
+
+/// Which target a request asked for, before it is looked up in the state.
+enum RequestedTarget {
+    Output(OutputName),
+    Region(Rectangle<i32, Logical>),
+    Window(WindowId),
+}
+
+impl RequestedTarget {
+    fn resolve(self, state: &State) -> Result<ScreenshotTarget, Status> {
+        Ok(match self {
+            RequestedTarget::Output(output_name) => ScreenshotTarget::Output(
+                output_name
+                    .output(&state.pinnacle)
+                    .ok_or_else(|| Status::not_found("output does not exist"))?,
+            ),
+            RequestedTarget::Region(region) => ScreenshotTarget::Region(region),
+            RequestedTarget::Window(window_id) => ScreenshotTarget::Window(
+                window_id
+                    .window(&state.pinnacle)
+                    .ok_or_else(|| Status::not_found("window does not exist"))?,
+            ),
+        })
+    }
+}
+
+#[tonic::async_trait]
+impl screenshot_service_server::ScreenshotService for ScreenshotService {
+    async fn screenshot(
+        &self,
+        request: Request<ScreenshotRequest>,
+    ) -> Result<Response<ScreenshotResponse>, Status> {
+        let request = request.into_inner();
+
+        let target = match request.target {
+            Some(Target::OutputName(name)) => RequestedTarget::Output(OutputName(name)),
+            Some(Target::Region(geometry)) => RequestedTarget::Region(region(geometry)?),
+            Some(Target::WindowId(id)) => RequestedTarget::Window(WindowId(id)),
+            None => return Err(Status::invalid_argument("no target specified")),
+        };
+
+        let include_cursor = request.include_cursor.unwrap_or_default();
+
+        let path = request.path.map(|path| expand_path(&path)).transpose()?;
+
+        let screenshot = run_unary(&self.sender, move |state| {
+            let target = target.resolve(state)?;
+
+            state
+                .screenshot(&target, include_cursor)
+                .map_err(|err| Status::internal(format!("failed to take screenshot: {err}")))
+        })
+        .await?
+        .into_inner()?;
+
+        // Encoding can take a while, so it doesn't happen on the compositor's thread
+        tokio::task::spawn_blocking(move || encode(screenshot, path))
+            .await
+            .map_err(|err| Status::internal(format!("failed to encode screenshot: {err}")))?
+            .map(Response::new)
+    }
+
+    async fn window_thumbnail(
+        &self,
+        request: Request<WindowThumbnailRequest>,
+    ) -> Result<Response<ScreenshotResponse>, Status> {
+        let request = request.into_inner();
+
+        let window_id = WindowId(
+            request
+                .window_id
+                .ok_or_else(|| Status::invalid_argument("no window specified"))?,
+        );
+
+        let (Some(max_width), Some(max_height)) = (request.max_width, request.max_height) else {
+            return Err(Status::invalid_argument("max_width or max_height was null"));
+        };
+        let max_size = Size::from((
+            i32::try_from(max_width).unwrap_or(i32::MAX),
+            i32::try_from(max_height).unwrap_or(i32::MAX),
+        ));
+        if max_size.w == 0 || max_size.h == 0 {
+            return Err(Status::invalid_argument("max size was empty"));
+        }
+
+        let screenshot = run_unary(&self.sender, move |state| {
+            let window = window_id
+                .window(&state.pinnacle)
+                .ok_or_else(|| Status::not_found("window does not exist"))?;
+
+            state
+                .screenshot(
+                    &ScreenshotTarget::WindowThumbnail { window, max_size },
+                    false,
+                )
+                .map_err(|err| Status::internal(format!("failed to capture window: {err}")))
+        })
+        .await?
+        .into_inner()?;
+
+        tokio::task::spawn_blocking(move || encode(screenshot, None))
+            .await
+            .map_err(|err| Status::internal(format!("failed to encode thumbnail: {err}")))?
+            .map(Response::new)
+    }
+
+    async fn start_recording(
+        &self,
+        request: Request<StartRecordingRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let target = match request.target {
+            Some(start_recording_request::Target::OutputName(name)) => {
+                RequestedTarget::Output(OutputName(name))
+            }
+            Some(start_recording_request::Target::Region(geometry)) => {
+                RequestedTarget::Region(region(geometry)?)
+            }
+            None => return Err(Status::invalid_argument("no target specified")),
+        };
+
+        let path = expand_path(
+            &request
+                .path
+                .ok_or_else(|| Status::invalid_argument("no path specified"))?,
+        )?;
+
+        let framerate = request.framerate.unwrap_or(DEFAULT_FRAMERATE);
+        if framerate == 0 {
+            return Err(Status::invalid_argument("framerate must be positive"));
+        }
+
+        let include_cursor = request.include_cursor.unwrap_or_default();
+
+        run_unary(&self.sender, move |state| {
+            let target = target.resolve(state)?;
+
+            state
+                .start_recording(target, path, framerate, include_cursor)
+                .map_err(|err| Status::internal(format!("failed to start recording: {err}")))
+        })
+        .await?
+        .into_inner()
+        .map(Response::new)
+    }
+
+    async fn stop_recording(
+        &self,
+        _request: Request<StopRecordingRequest>,
+    ) -> Result<Response<()>, Status> {
+        run_unary_no_response(&self.sender, |state| {
+            state.stop_recording();
+        })
+        .await
+    }
+}
+
+pub(super) fn region(geometry: Geometry) -> Result<Rectangle<i32, Logical>, Status> {
+    let (Some(x), Some(y), Some(width), Some(height)) =
+        (geometry.x, geometry.y, geometry.width, geometry.height)
+    else {
+        return Err(Status::invalid_argument("region was missing fields"));
+    };
+    if width <= 0 || height <= 0 {
+        return Err(Status::invalid_argument("region was empty"));
+    }
+    Ok(Rectangle::from_loc_and_size((x, y), (width, height)))
+}
+
+fn encode(screenshot: Screenshot, path: Option<PathBuf>) -> Result<ScreenshotResponse, Status> {
+    let (width, height) = screenshot.size();
+
+    let png = match path {
+        Some(path) => {
+            screenshot
+                .save_png(&path)
+                .map_err(|err| Status::internal(format!("failed to save screenshot: {err}")))?;
+            None
+        }
+        None => Some(
+            screenshot
+                .encode_png()
+                .map_err(|err| Status::internal(format!("failed to encode screenshot: {err}")))?,
+        ),
+    };
+
+    Ok(ScreenshotResponse {
+        png,
+        width: Some(width),
+        height: Some(height),
+    })
+}
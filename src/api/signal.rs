@@ -1,11 +1,13 @@
 use std::collections::VecDeque;
 
 use pinnacle_api_defs::pinnacle::signal::v0alpha1::{
-    signal_service_server, OutputConnectRequest, OutputConnectResponse, OutputDisconnectRequest,
-    OutputDisconnectResponse, OutputMoveRequest, OutputMoveResponse, OutputResizeRequest,
-    OutputResizeResponse, SignalRequest, StreamControl, TagActiveRequest, TagActiveResponse,
+    signal_service_server, InputXkbLayoutRequest, InputXkbLayoutResponse, OutputConnectRequest,
+    OutputConnectResponse, OutputDisconnectRequest, OutputDisconnectResponse, OutputMoveRequest,
+    OutputMoveResponse, OutputResizeRequest, OutputResizeResponse, SignalRequest, StreamControl,
+    TagActiveRequest, TagActiveResponse, TagOccupiedRequest, TagOccupiedResponse,
+    TagRenamedRequest, TagRenamedResponse, TagUrgentRequest, TagUrgentResponse,
     WindowPointerEnterRequest, WindowPointerEnterResponse, WindowPointerLeaveRequest,
-    WindowPointerLeaveResponse,
+    WindowPointerLeaveResponse, WindowUrgentRequest, WindowUrgentResponse,
 };
 use tokio::{sync::mpsc::UnboundedSender, task::JoinHandle};
 use tonic::{Request, Response, Status, Streaming};
@@ -28,9 +30,16 @@ pub struct SignalState {
         SignalData<WindowPointerEnterResponse, VecDeque<WindowPointerEnterResponse>>,
     pub window_pointer_leave:
         SignalData<WindowPointerLeaveResponse, VecDeque<WindowPointerLeaveResponse>>,
+    pub window_urgent: SignalData<WindowUrgentResponse, VecDeque<WindowUrgentResponse>>,
 
     // Tag
     pub tag_active: SignalData<TagActiveResponse, VecDeque<TagActiveResponse>>,
+    pub tag_urgent: SignalData<TagUrgentResponse, VecDeque<TagUrgentResponse>>,
+    pub tag_occupied: SignalData<TagOccupiedResponse, VecDeque<TagOccupiedResponse>>,
+    pub tag_renamed: SignalData<TagRenamedResponse, VecDeque<TagRenamedResponse>>,
+
+    // Input
+    pub input_xkb_layout: SignalData<InputXkbLayoutResponse, VecDeque<InputXkbLayoutResponse>>,
 }
 
 impl SignalState {
@@ -41,6 +50,11 @@ impl SignalState {
         self.output_move.disconnect();
         self.window_pointer_enter.disconnect();
         self.window_pointer_leave.disconnect();
+        self.window_urgent.disconnect();
+        self.tag_urgent.disconnect();
+        self.tag_occupied.disconnect();
+        self.tag_renamed.disconnect();
+        self.input_xkb_layout.disconnect();
     }
 }
 
@@ -191,8 +205,14 @@ impl signal_service_server::SignalService for SignalService {
 
     type WindowPointerEnterStream = ResponseStream<WindowPointerEnterResponse>;
     type WindowPointerLeaveStream = ResponseStream<WindowPointerLeaveResponse>;
+    type WindowUrgentStream = ResponseStream<WindowUrgentResponse>;
 
     type TagActiveStream = ResponseStream<TagActiveResponse>;
+    type TagUrgentStream = ResponseStream<TagUrgentResponse>;
+    type TagOccupiedStream = ResponseStream<TagOccupiedResponse>;
+    type TagRenamedStream = ResponseStream<TagRenamedResponse>;
+
+    type InputXkbLayoutStream = ResponseStream<InputXkbLayoutResponse>;
 
     async fn output_connect(
         &self,
@@ -260,6 +280,17 @@ impl signal_service_server::SignalService for SignalService {
         })
     }
 
+    async fn window_urgent(
+        &self,
+        request: Request<Streaming<WindowUrgentRequest>>,
+    ) -> Result<Response<Self::WindowUrgentStream>, Status> {
+        let in_stream = request.into_inner();
+
+        start_signal_stream(self.sender.clone(), in_stream, |state| {
+            &mut state.pinnacle.signal_state.window_urgent
+        })
+    }
+
     async fn tag_active(
         &self,
         request: Request<Streaming<TagActiveRequest>>,
@@ -270,4 +301,48 @@ impl signal_service_server::SignalService for SignalService {
             &mut state.pinnacle.signal_state.tag_active
         })
     }
+
+    async fn tag_urgent(
+        &self,
+        request: Request<Streaming<TagUrgentRequest>>,
+    ) -> Result<Response<Self::TagUrgentStream>, Status> {
+        let in_stream = request.into_inner();
+
+        start_signal_stream(self.sender.clone(), in_stream, |state| {
+            &mut state.pinnacle.signal_state.tag_urgent
+        })
+    }
+
+    async fn tag_occupied(
+        &self,
+        request: Request<Streaming<TagOccupiedRequest>>,
+    ) -> Result<Response<Self::TagOccupiedStream>, Status> {
+        let in_stream = request.into_inner();
+
+        start_signal_stream(self.sender.clone(), in_stream, |state| {
+            &mut state.pinnacle.signal_state.tag_occupied
+        })
+    }
+
+    async fn tag_renamed(
+        &self,
+        request: Request<Streaming<TagRenamedRequest>>,
+    ) -> Result<Response<Self::TagRenamedStream>, Status> {
+        let in_stream = request.into_inner();
+
+        start_signal_stream(self.sender.clone(), in_stream, |state| {
+            &mut state.pinnacle.signal_state.tag_renamed
+        })
+    }
+
+    async fn input_xkb_layout(
+        &self,
+        request: Request<Streaming<InputXkbLayoutRequest>>,
+    ) -> Result<Response<Self::InputXkbLayoutStream>, Status> {
+        let in_stream = request.into_inner();
+
+        start_signal_stream(self.sender.clone(), in_stream, |state| {
+            &mut state.pinnacle.signal_state.input_xkb_layout
+        })
+    }
 }
@@ -5,25 +5,45 @@ use pinnacle_api_defs::pinnacle::{
     window::{
         self,
         v0alpha1::{
-            window_service_server, AddWindowRuleRequest, CloseRequest, FullscreenOrMaximized,
-            MoveGrabRequest, MoveToTagRequest, RaiseRequest, ResizeGrabRequest, SetFloatingRequest,
-            SetFocusedRequest, SetFullscreenRequest, SetGeometryRequest, SetMaximizedRequest,
-            SetTagRequest, WindowRule, WindowRuleCondition,
+            window_service_server, AddToGroupRequest, AddWindowRuleRequest, AddWindowRuleResponse,
+            CloseOrKillRequest, CloseRequest, CycleFocusRequest, CycleGroupRequest,
+            CycleWindowSwitcherRequest, FocusUrgentRequest, ForgetGeometryRequest,
+            FullscreenOrMaximized, LowerRequest, MarkInsertPositionRequest, MoveFocusRequest,
+            MoveGrabRequest, MoveInDirectionRequest, MoveToTagRequest, RaiseRequest,
+            RemoveFromGroupRequest, RemoveWindowRuleRequest, ResizeGrabRequest, ResizeTileRequest,
+            SetClickToRaiseRequest, SetFakeFullscreenRequest, SetFloatingRequest,
+            SetFocusFollowsMouseRequest, SetFocusedRequest, SetFullscreenRequest,
+            SetGeometryRequest, SetGroupModeRequest, SetInsertPositionRequest, SetLayerRequest,
+            SetMaximizedRequest, SetPipPlacementRequest, SetSnapThresholdRequest, SetTagRequest,
+            SetTagsRequest, SetUnresponsiveOverlayRequest, SetWindowSwitcherThumbnailsRequest,
+            WindowRule, WindowRuleCondition,
         },
     },
 };
+use regex::Regex;
 use smithay::{
-    desktop::{space::SpaceElement, WindowSurface},
+    desktop::space::SpaceElement,
     reexports::wayland_protocols::xdg::shell::server,
     utils::{Point, Rectangle, SERIAL_COUNTER},
     wayland::seat::WaylandFocus,
 };
 use tonic::{Request, Response, Status};
-use tracing::{error, warn};
+use tracing::warn;
 
 use crate::{
-    focus::keyboard::KeyboardFocusTarget, output::OutputName, state::WithState, tag::TagId,
-    window::window_state::WindowId,
+    focus::{directional::Direction, keyboard::KeyboardFocusTarget},
+    layout::insert::InsertPosition,
+    output::OutputName,
+    state::WithState,
+    tag::TagId,
+    window::{
+        group::GroupMode,
+        pip::PipCorner,
+        rules::{FloatingAnchor, WindowMatcher, WindowRuleId},
+        unresponsive::DEFAULT_KILL_TIMEOUT,
+        window_state::{WindowId, WindowLayer},
+        WindowElement,
+    },
 };
 
 use super::{run_unary, run_unary_no_response, StateFnSender};
@@ -54,17 +74,37 @@ impl window_service_server::WindowService for WindowService {
                 return;
             };
 
-            match window.underlying_surface() {
-                WindowSurface::Wayland(toplevel) => toplevel.send_close(),
-                WindowSurface::X11(surface) => {
-                    if !surface.is_override_redirect() {
-                        if let Err(err) = surface.close() {
-                            error!("failed to close x11 window: {err}");
-                        }
-                    } else {
-                        warn!("tried to close OR window");
-                    }
-                }
+            state.pinnacle.close_window(&window);
+            if let Some(output) = window.output(&state.pinnacle) {
+                state.schedule_render(&output);
+            }
+        })
+        .await
+    }
+
+    async fn close_or_kill(
+        &self,
+        request: Request<CloseOrKillRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let window_id = WindowId(
+            request
+                .window_id
+                .ok_or_else(|| Status::invalid_argument("no window specified"))?,
+        );
+        let timeout = request.timeout_ms.map_or(DEFAULT_KILL_TIMEOUT, |ms| {
+            std::time::Duration::from_millis(u64::from(ms))
+        });
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(window) = window_id.window(&state.pinnacle) else {
+                return;
+            };
+
+            state.close_or_kill_window(&window, timeout);
+            if let Some(output) = window.output(&state.pinnacle) {
+                state.schedule_render(&output);
             }
         })
         .await
@@ -115,7 +155,8 @@ impl window_service_server::WindowService for WindowService {
                 state.floating_or_tiled = match state.floating_or_tiled {
                     FloatingOrTiled::Floating(_) => FloatingOrTiled::Floating(rect),
                     FloatingOrTiled::Tiled(_) => FloatingOrTiled::Tiled(Some(rect)),
-                }
+                };
+                state.edge_tile = None;
             });
 
             for output in state.pinnacle.space.outputs_for_element(&window) {
@@ -308,6 +349,11 @@ impl window_service_server::WindowService for WindowService {
                 win.set_activate(false);
             }
 
+            // Focusing a window hidden in a group shows it
+            if set_or_toggle != SetOrToggle::Unset && window.is_hidden_in_group() {
+                state.pinnacle.show_window_in_group(&window);
+            }
+
             match set_or_toggle {
                 SetOrToggle::Set => {
                     window.set_activate(true);
@@ -381,17 +427,12 @@ impl window_service_server::WindowService for WindowService {
         );
 
         run_unary_no_response(&self.sender, move |state| {
-            let pinnacle = &mut state.pinnacle;
-            let Some(window) = window_id.window(pinnacle) else {
+            let Some(window) = window_id.window(&state.pinnacle) else {
                 return;
             };
-            let Some(tag) = tag_id.tag(pinnacle) else { return };
-            window.with_state_mut(|state| {
-                state.tags = vec![tag.clone()];
-            });
-            let Some(output) = tag.output(pinnacle) else { return };
-            pinnacle.request_layout(&output);
-            state.schedule_render(&output);
+            let Some(tag) = tag_id.tag(&state.pinnacle) else { return };
+
+            state.set_window_tags(&window, vec![tag]);
         })
         .await
     }
@@ -418,34 +459,63 @@ impl window_service_server::WindowService for WindowService {
         }
 
         run_unary_no_response(&self.sender, move |state| {
-            let pinnacle = &mut state.pinnacle;
-            let Some(window) = window_id.window(pinnacle) else {
+            let Some(window) = window_id.window(&state.pinnacle) else {
                 return;
             };
-            let Some(tag) = tag_id.tag(pinnacle) else { return };
+            let Some(tag) = tag_id.tag(&state.pinnacle) else { return };
 
-            // TODO: turn state.tags into a hashset
-            match set_or_toggle {
-                SetOrToggle::Set => window.with_state_mut(|state| {
-                    state.tags.retain(|tg| tg != &tag);
-                    state.tags.push(tag.clone());
-                }),
-                SetOrToggle::Unset => window.with_state_mut(|state| {
-                    state.tags.retain(|tg| tg != &tag);
-                }),
-                SetOrToggle::Toggle => window.with_state_mut(|state| {
-                    if !state.tags.contains(&tag) {
-                        state.tags.push(tag.clone());
-                    } else {
-                        state.tags.retain(|tg| tg != &tag);
-                    }
-                }),
+            let mut tags = window.with_state(|state| state.tags.clone());
+            let set = match set_or_toggle {
+                SetOrToggle::Set => true,
+                SetOrToggle::Unset => false,
+                SetOrToggle::Toggle => !tags.contains(&tag),
                 SetOrToggle::Unspecified => unreachable!(),
+            };
+
+            if set {
+                // Tagging a window with a tag on another output moves it there
+                let output = tag.output(&state.pinnacle);
+                tags.retain(|tg| tg != &tag && tg.output(&state.pinnacle) == output);
+                tags.push(tag);
+            } else {
+                tags.retain(|tg| tg != &tag);
             }
 
-            let Some(output) = tag.output(pinnacle) else { return };
-            pinnacle.request_layout(&output);
-            state.schedule_render(&output);
+            state.set_window_tags(&window, tags);
+        })
+        .await
+    }
+
+    async fn set_tags(&self, request: Request<SetTagsRequest>) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let window_id = WindowId(
+            request
+                .window_id
+                .ok_or_else(|| Status::invalid_argument("no window specified"))?,
+        );
+
+        if request.tag_ids.is_empty() {
+            return Err(Status::invalid_argument("no tags specified"));
+        }
+
+        let tag_ids = request.tag_ids.into_iter().map(TagId).collect::<Vec<_>>();
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(window) = window_id.window(&state.pinnacle) else {
+                return;
+            };
+
+            let tags = tag_ids
+                .iter()
+                .filter_map(|tag_id| tag_id.tag(&state.pinnacle))
+                .collect::<Vec<_>>();
+
+            if tags.is_empty() {
+                return;
+            }
+
+            state.set_window_tags(&window, tags);
         })
         .await
     }
@@ -466,7 +536,593 @@ impl window_service_server::WindowService for WindowService {
                 return;
             };
 
-            pinnacle.raise_window(window, false);
+            pinnacle.raise_window(window.clone(), false);
+            for output in state.pinnacle.space.outputs_for_element(&window) {
+                state.schedule_render(&output);
+            }
+        })
+        .await
+    }
+
+    async fn lower(&self, request: Request<LowerRequest>) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let window_id = WindowId(
+            request
+                .window_id
+                .ok_or_else(|| Status::invalid_argument("no window specified"))?,
+        );
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(window) = window_id.window(&state.pinnacle) else {
+                warn!("`lower` was called on a nonexistent window");
+                return;
+            };
+
+            state.pinnacle.lower_window(&window);
+            for output in state.pinnacle.space.outputs_for_element(&window) {
+                state.schedule_render(&output);
+            }
+        })
+        .await
+    }
+
+    async fn set_click_to_raise(
+        &self,
+        request: Request<SetClickToRaiseRequest>,
+    ) -> Result<Response<()>, Status> {
+        let click_to_raise = request
+            .into_inner()
+            .click_to_raise
+            .ok_or_else(|| Status::invalid_argument("no click_to_raise specified"))?;
+
+        run_unary_no_response(&self.sender, move |state| {
+            state.pinnacle.config.focus.click_to_raise = click_to_raise;
+        })
+        .await
+    }
+
+    async fn set_focus_follows_mouse(
+        &self,
+        request: Request<SetFocusFollowsMouseRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let enabled = request
+            .enabled
+            .ok_or_else(|| Status::invalid_argument("no enabled specified"))?;
+        let delay =
+            std::time::Duration::from_millis(u64::from(request.delay_ms.unwrap_or_default()));
+
+        run_unary_no_response(&self.sender, move |state| {
+            state.pinnacle.config.focus.focus_follows_mouse = enabled.then_some(delay);
+        })
+        .await
+    }
+
+    async fn add_to_group(
+        &self,
+        request: Request<AddToGroupRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let window_id = WindowId(
+            request
+                .window_id
+                .ok_or_else(|| Status::invalid_argument("no window specified"))?,
+        );
+        let target_id = request.target_window_id.map(WindowId);
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(window) = window_id.window(&state.pinnacle) else {
+                return;
+            };
+            let Some(output) = window.output(&state.pinnacle) else {
+                return;
+            };
+
+            let is_tiled = |win: &WindowElement| {
+                win.with_state(|state| {
+                    state.floating_or_tiled.is_tiled() && state.fullscreen_or_maximized.is_neither()
+                })
+            };
+
+            let target = match target_id {
+                Some(target_id) => target_id.window(&state.pinnacle),
+                None => {
+                    // The next tiled window on the output that isn't already grouped with this one
+                    let group = window.group();
+                    let candidates = state
+                        .pinnacle
+                        .windows
+                        .iter()
+                        .filter(|win| win.is_visible() && is_tiled(win))
+                        .filter(|win| win.output(&state.pinnacle).as_ref() == Some(&output))
+                        .collect::<Vec<_>>();
+                    let index = candidates.iter().position(|win| *win == &window);
+                    candidates
+                        .iter()
+                        .cycle()
+                        .skip(index.map_or(0, |index| index + 1))
+                        .take(candidates.len())
+                        .find(|win| **win != &window && (group.is_none() || win.group() != group))
+                        .map(|win| (*win).clone())
+                }
+            };
+
+            let Some(target) = target else {
+                return;
+            };
+
+            if !is_tiled(&window) || !is_tiled(&target) {
+                warn!("Only tiled windows can be grouped");
+                return;
+            }
+
+            state.pinnacle.add_window_to_group(&window, &target);
+
+            state.pinnacle.request_layout(&output);
+            state.update_focus(&output);
+            state.schedule_render(&output);
+        })
+        .await
+    }
+
+    async fn remove_from_group(
+        &self,
+        request: Request<RemoveFromGroupRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let window_id = WindowId(
+            request
+                .window_id
+                .ok_or_else(|| Status::invalid_argument("no window specified"))?,
+        );
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(window) = window_id.window(&state.pinnacle) else {
+                return;
+            };
+
+            state.pinnacle.remove_window_from_group(&window);
+
+            let Some(output) = window.output(&state.pinnacle) else {
+                return;
+            };
+
+            state.pinnacle.request_layout(&output);
+            state.update_focus(&output);
+            state.schedule_render(&output);
+        })
+        .await
+    }
+
+    async fn set_group_mode(
+        &self,
+        request: Request<SetGroupModeRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let window_id = WindowId(
+            request
+                .window_id
+                .ok_or_else(|| Status::invalid_argument("no window specified"))?,
+        );
+
+        let mode = match request.mode() {
+            window::v0alpha1::GroupMode::Unspecified => {
+                return Err(Status::invalid_argument("unspecified group mode"));
+            }
+            window::v0alpha1::GroupMode::Tabbed => GroupMode::Tabbed,
+            window::v0alpha1::GroupMode::Stacked => GroupMode::Stacked,
+        };
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(window) = window_id.window(&state.pinnacle) else {
+                return;
+            };
+            let Some(group) = window.group() else {
+                return;
+            };
+
+            group.set_mode(mode);
+
+            // The indicator's height depends on the mode
+            if let Some(output) = window.output(&state.pinnacle) {
+                state.pinnacle.request_layout(&output);
+                state.schedule_render(&output);
+            }
+        })
+        .await
+    }
+
+    async fn cycle_group(
+        &self,
+        request: Request<CycleGroupRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let window_id = WindowId(
+            request
+                .window_id
+                .ok_or_else(|| Status::invalid_argument("no window specified"))?,
+        );
+        let offset = if request.backward() { -1 } else { 1 };
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(window) = window_id.window(&state.pinnacle) else {
+                return;
+            };
+            let Some(next) = state.pinnacle.cycle_window_group(&window, offset) else {
+                return;
+            };
+            let Some(output) = next.output(&state.pinnacle) else {
+                return;
+            };
+
+            output.with_state_mut(|state| state.focus_stack.set_focus(next.clone()));
+            state.pinnacle.raise_window(next, true);
+            state.update_focus(&output);
+            state.schedule_render(&output);
+        })
+        .await
+    }
+
+    async fn set_insert_position(
+        &self,
+        request: Request<SetInsertPositionRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let position = match request.position() {
+            window::v0alpha1::InsertPosition::Unspecified => {
+                return Err(Status::invalid_argument("unspecified insert position"));
+            }
+            window::v0alpha1::InsertPosition::End => InsertPosition::End,
+            window::v0alpha1::InsertPosition::AfterFocused => InsertPosition::AfterFocused,
+            window::v0alpha1::InsertPosition::Master => InsertPosition::Master,
+        };
+
+        run_unary_no_response(&self.sender, move |state| {
+            state.pinnacle.layout_state.insert_position = position;
+        })
+        .await
+    }
+
+    async fn mark_insert_position(
+        &self,
+        request: Request<MarkInsertPositionRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let window_id = WindowId(
+            request
+                .window_id
+                .ok_or_else(|| Status::invalid_argument("no window specified"))?,
+        );
+
+        run_unary_no_response(&self.sender, move |state| {
+            if window_id.window(&state.pinnacle).is_some() {
+                state.pinnacle.layout_state.insert_marker = Some(window_id);
+            }
+        })
+        .await
+    }
+
+    async fn resize_tile(
+        &self,
+        request: Request<ResizeTileRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let window_id = WindowId(
+            request
+                .window_id
+                .ok_or_else(|| Status::invalid_argument("no window specified"))?,
+        );
+        let width_delta = request.width_delta();
+        let height_delta = request.height_delta();
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(window) = window_id.window(&state.pinnacle) else {
+                return;
+            };
+            state
+                .pinnacle
+                .resize_tile(&window, width_delta, height_delta);
+        })
+        .await
+    }
+
+    async fn cycle_focus(
+        &self,
+        request: Request<CycleFocusRequest>,
+    ) -> Result<Response<()>, Status> {
+        let offset = if request.into_inner().backward() { -1 } else { 1 };
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(output) = state.pinnacle.focused_output().cloned() else {
+                return;
+            };
+            if state.pinnacle.cycle_tiled_focus(&output, offset).is_none() {
+                return;
+            }
+
+            state.update_focus(&output);
+            state.schedule_render(&output);
+        })
+        .await
+    }
+
+    async fn move_focus(&self, request: Request<MoveFocusRequest>) -> Result<Response<()>, Status> {
+        let direction = match request.into_inner().direction() {
+            window::v0alpha1::Direction::Unspecified => {
+                return Err(Status::invalid_argument("unspecified direction"));
+            }
+            window::v0alpha1::Direction::Left => Direction::Left,
+            window::v0alpha1::Direction::Right => Direction::Right,
+            window::v0alpha1::Direction::Up => Direction::Up,
+            window::v0alpha1::Direction::Down => Direction::Down,
+        };
+
+        run_unary_no_response(&self.sender, move |state| {
+            state.focus_in_direction(direction);
+        })
+        .await
+    }
+
+    async fn move_in_direction(
+        &self,
+        request: Request<MoveInDirectionRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let window_id = WindowId(
+            request
+                .window_id
+                .ok_or_else(|| Status::invalid_argument("no window specified"))?,
+        );
+
+        let direction = match request.direction() {
+            window::v0alpha1::Direction::Unspecified => {
+                return Err(Status::invalid_argument("unspecified direction"));
+            }
+            window::v0alpha1::Direction::Left => Direction::Left,
+            window::v0alpha1::Direction::Right => Direction::Right,
+            window::v0alpha1::Direction::Up => Direction::Up,
+            window::v0alpha1::Direction::Down => Direction::Down,
+        };
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(window) = window_id.window(&state.pinnacle) else {
+                return;
+            };
+
+            state.pinnacle.move_window_in_direction(&window, direction);
+        })
+        .await
+    }
+
+    async fn cycle_window_switcher(
+        &self,
+        request: Request<CycleWindowSwitcherRequest>,
+    ) -> Result<Response<()>, Status> {
+        let backward = request.into_inner().backward();
+
+        run_unary_no_response(&self.sender, move |state| {
+            state.cycle_window_switcher(backward);
+        })
+        .await
+    }
+
+    async fn focus_urgent(
+        &self,
+        _request: Request<FocusUrgentRequest>,
+    ) -> Result<Response<()>, Status> {
+        run_unary_no_response(&self.sender, move |state| {
+            state.focus_urgent_window();
+        })
+        .await
+    }
+
+    async fn set_window_switcher_thumbnails(
+        &self,
+        request: Request<SetWindowSwitcherThumbnailsRequest>,
+    ) -> Result<Response<()>, Status> {
+        let thumbnails = request
+            .into_inner()
+            .thumbnails
+            .ok_or_else(|| Status::invalid_argument("no thumbnails specified"))?;
+
+        run_unary_no_response(&self.sender, move |state| {
+            state.pinnacle.config.switcher.thumbnails = thumbnails;
+        })
+        .await
+    }
+
+    async fn set_unresponsive_overlay(
+        &self,
+        request: Request<SetUnresponsiveOverlayRequest>,
+    ) -> Result<Response<()>, Status> {
+        let enabled = request
+            .into_inner()
+            .enabled
+            .ok_or_else(|| Status::invalid_argument("no enabled specified"))?;
+
+        run_unary_no_response(&self.sender, move |state| {
+            state.pinnacle.config.render_settings.darken_unresponsive = enabled;
+            for output in state.pinnacle.space.outputs().cloned().collect::<Vec<_>>() {
+                state.schedule_render(&output);
+            }
+        })
+        .await
+    }
+
+    async fn set_layer(&self, request: Request<SetLayerRequest>) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let window_id = WindowId(
+            request
+                .window_id
+                .ok_or_else(|| Status::invalid_argument("no window specified"))?,
+        );
+
+        let layer = match request.layer() {
+            window::v0alpha1::WindowLayer::Unspecified => {
+                return Err(Status::invalid_argument("unspecified layer"));
+            }
+            window::v0alpha1::WindowLayer::Normal => WindowLayer::Normal,
+            window::v0alpha1::WindowLayer::Above => WindowLayer::Above,
+            window::v0alpha1::WindowLayer::Below => WindowLayer::Below,
+        };
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(window) = window_id.window(&state.pinnacle) else {
+                return;
+            };
+
+            window.with_state_mut(|state| state.layer = layer);
+            state.pinnacle.fixup_z_layering();
+            state.pinnacle.fixup_xwayland_window_layering();
+
+            if let Some(output) = window.output(&state.pinnacle) {
+                state.schedule_render(&output);
+            }
+        })
+        .await
+    }
+
+    async fn set_fake_fullscreen(
+        &self,
+        request: Request<SetFakeFullscreenRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let window_id = WindowId(
+            request
+                .window_id
+                .ok_or_else(|| Status::invalid_argument("no window specified"))?,
+        );
+
+        let set_or_toggle = request.set_or_toggle();
+
+        if set_or_toggle == SetOrToggle::Unspecified {
+            return Err(Status::invalid_argument("unspecified set or toggle"));
+        }
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(window) = window_id.window(&state.pinnacle) else {
+                return;
+            };
+
+            let fake_fullscreen = match set_or_toggle {
+                SetOrToggle::Set => true,
+                SetOrToggle::Unset => false,
+                SetOrToggle::Toggle => !window.with_state(|state| state.fake_fullscreen),
+                SetOrToggle::Unspecified => unreachable!(),
+            };
+
+            window.set_fake_fullscreen(fake_fullscreen);
+
+            if let Some(output) = window.output(&state.pinnacle) {
+                state.pinnacle.request_layout(&output);
+                state.schedule_render(&output);
+            }
+        })
+        .await
+    }
+
+    async fn set_pip_placement(
+        &self,
+        request: Request<SetPipPlacementRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let corner = match request.corner() {
+            window::v0alpha1::PipCorner::Unspecified => None,
+            window::v0alpha1::PipCorner::TopLeft => Some(PipCorner::TopLeft),
+            window::v0alpha1::PipCorner::TopRight => Some(PipCorner::TopRight),
+            window::v0alpha1::PipCorner::BottomLeft => Some(PipCorner::BottomLeft),
+            window::v0alpha1::PipCorner::BottomRight => Some(PipCorner::BottomRight),
+        };
+        let width = request.width.map(|width| width as i32);
+        let height = request.height.map(|height| height as i32);
+
+        if width == Some(0) || height == Some(0) {
+            return Err(Status::invalid_argument("size must be positive"));
+        }
+
+        run_unary_no_response(&self.sender, move |state| {
+            let placement = &mut state.pinnacle.config.pip_placement;
+            if let Some(corner) = corner {
+                placement.corner = corner;
+            }
+            if let Some(width) = width {
+                placement.size.w = width;
+            }
+            if let Some(height) = height {
+                placement.size.h = height;
+            }
+
+            for output in state.pinnacle.space.outputs().cloned().collect::<Vec<_>>() {
+                state.pinnacle.place_pip_windows(&output);
+            }
+        })
+        .await
+    }
+
+    async fn set_snap_threshold(
+        &self,
+        request: Request<SetSnapThresholdRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let threshold = request
+            .threshold
+            .ok_or_else(|| Status::invalid_argument("no threshold specified"))?;
+        let threshold = i32::try_from(threshold)
+            .map_err(|_| Status::invalid_argument("threshold is too large"))?;
+
+        run_unary_no_response(&self.sender, move |state| {
+            state.pinnacle.config.snap.threshold = threshold;
+        })
+        .await
+    }
+
+    async fn get_remembered_geometries(
+        &self,
+        _request: Request<window::v0alpha1::GetRememberedGeometriesRequest>,
+    ) -> Result<Response<window::v0alpha1::GetRememberedGeometriesResponse>, Status> {
+        run_unary(&self.sender, move |state| {
+            let geometries = state
+                .pinnacle
+                .remembered_geometries
+                .iter()
+                .map(|(app_id, geo)| window::v0alpha1::RememberedGeometry {
+                    app_id: Some(app_id.to_string()),
+                    geometry: Some(Geometry {
+                        x: Some(geo.loc.0),
+                        y: Some(geo.loc.1),
+                        width: Some(geo.size.0),
+                        height: Some(geo.size.1),
+                    }),
+                })
+                .collect();
+
+            window::v0alpha1::GetRememberedGeometriesResponse { geometries }
+        })
+        .await
+    }
+
+    async fn forget_geometry(
+        &self,
+        request: Request<ForgetGeometryRequest>,
+    ) -> Result<Response<()>, Status> {
+        let app_id = request.into_inner().app_id;
+
+        run_unary_no_response(&self.sender, move |state| {
+            state.pinnacle.forget_floating_geometry(app_id.as_deref());
         })
         .await
     }
@@ -682,6 +1338,19 @@ impl window_service_server::WindowService for WindowService {
                 })
                 .unwrap_or_default();
 
+            let layer = window
+                .as_ref()
+                .map(|win| win.with_state(|state| state.layer))
+                .map(|layer| match layer {
+                    WindowLayer::Normal => window::v0alpha1::WindowLayer::Normal,
+                    WindowLayer::Above => window::v0alpha1::WindowLayer::Above,
+                    WindowLayer::Below => window::v0alpha1::WindowLayer::Below,
+                } as i32);
+
+            let urgent = window
+                .as_ref()
+                .map(|win| win.with_state(|state| state.urgent));
+
             window::v0alpha1::GetPropertiesResponse {
                 geometry,
                 class,
@@ -690,6 +1359,8 @@ impl window_service_server::WindowService for WindowService {
                 floating,
                 fullscreen_or_maximized,
                 tag_ids,
+                layer,
+                urgent,
             }
         })
         .await
@@ -698,71 +1369,128 @@ impl window_service_server::WindowService for WindowService {
     async fn add_window_rule(
         &self,
         request: Request<AddWindowRuleRequest>,
-    ) -> Result<Response<()>, Status> {
+    ) -> Result<Response<AddWindowRuleResponse>, Status> {
         let request = request.into_inner();
 
         let cond = request
             .cond
-            .ok_or_else(|| Status::invalid_argument("no condition specified"))?
-            .into();
+            .ok_or_else(|| Status::invalid_argument("no condition specified"))?;
+        let matcher = window_matcher(cond, false)
+            .map_err(|err| Status::invalid_argument(format!("invalid regex: {err}")))?;
 
         let rule = request
             .rule
             .ok_or_else(|| Status::invalid_argument("no rule specified"))?
             .into();
 
-        run_unary_no_response(&self.sender, move |state| {
-            state.pinnacle.config.window_rules.push((cond, rule));
+        run_unary(&self.sender, move |state| {
+            let id = WindowRuleId::next();
+            state.pinnacle.config.window_rules.push((id, matcher, rule));
+
+            AddWindowRuleResponse {
+                rule_id: Some(id.0),
+            }
         })
         .await
     }
-}
 
-impl From<WindowRuleCondition> for crate::window::rules::WindowRuleCondition {
-    fn from(cond: WindowRuleCondition) -> Self {
-        let cond_any = match cond.any.is_empty() {
-            true => None,
-            false => Some(
-                cond.any
-                    .into_iter()
-                    .map(crate::window::rules::WindowRuleCondition::from)
-                    .collect::<Vec<_>>(),
-            ),
-        };
+    async fn remove_window_rule(
+        &self,
+        request: Request<RemoveWindowRuleRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
 
-        let cond_all = match cond.all.is_empty() {
-            true => None,
-            false => Some(
-                cond.all
-                    .into_iter()
-                    .map(crate::window::rules::WindowRuleCondition::from)
-                    .collect::<Vec<_>>(),
-            ),
-        };
+        let rule_id = WindowRuleId(
+            request
+                .rule_id
+                .ok_or_else(|| Status::invalid_argument("no rule id specified"))?,
+        );
 
-        let class = match cond.classes.is_empty() {
-            true => None,
-            false => Some(cond.classes),
-        };
+        run_unary_no_response(&self.sender, move |state| {
+            state
+                .pinnacle
+                .config
+                .window_rules
+                .retain(|(id, _, _)| *id != rule_id);
+        })
+        .await
+    }
+}
 
-        let title = match cond.titles.is_empty() {
-            true => None,
-            false => Some(cond.titles),
-        };
+/// Convert a condition from the API into a [`WindowMatcher`].
+///
+/// Every item in `cond` has to match when `any` is `false`, and at least one has to when it's
+/// `true`, which is how conditions inside an `any` block behave.
+fn window_matcher(cond: WindowRuleCondition, any: bool) -> Result<WindowMatcher, regex::Error> {
+    let mut matchers = Vec::new();
+
+    if !cond.any.is_empty() {
+        let any = cond
+            .any
+            .into_iter()
+            .map(|cond| window_matcher(cond, true))
+            .collect::<Result<Vec<_>, _>>()?;
+        matchers.push(WindowMatcher::Any(any));
+    }
 
-        let tag = match cond.tags.is_empty() {
-            true => None,
-            false => Some(cond.tags.into_iter().map(TagId).collect::<Vec<_>>()),
-        };
+    if !cond.all.is_empty() {
+        let all = cond
+            .all
+            .into_iter()
+            .map(|cond| window_matcher(cond, false))
+            .collect::<Result<Vec<_>, _>>()?;
+        matchers.push(WindowMatcher::All(all));
+    }
 
-        crate::window::rules::WindowRuleCondition {
-            cond_any,
-            cond_all,
-            class,
-            title,
-            tag,
-        }
+    if !cond.none.is_empty() {
+        let none = cond
+            .none
+            .into_iter()
+            .map(|cond| window_matcher(cond, false))
+            .collect::<Result<Vec<_>, _>>()?;
+        matchers.push(WindowMatcher::Not(Box::new(WindowMatcher::Any(none))));
     }
+
+    matchers.extend(
+        cond.classes
+            .iter()
+            .map(|class| WindowMatcher::AppId(WindowMatcher::exact(class))),
+    );
+    matchers.extend(
+        cond.titles
+            .iter()
+            .map(|title| WindowMatcher::Title(WindowMatcher::exact(title))),
+    );
+    matchers.extend(
+        cond.tags
+            .into_iter()
+            .map(|id| WindowMatcher::Tag(TagId(id))),
+    );
+
+    for regex in cond.class_regexes {
+        matchers.push(WindowMatcher::AppId(Regex::new(&regex)?));
+    }
+    for regex in cond.title_regexes {
+        matchers.push(WindowMatcher::Title(Regex::new(&regex)?));
+    }
+    for regex in cond.x11_classes {
+        matchers.push(WindowMatcher::X11Class(Regex::new(&regex)?));
+    }
+    for regex in cond.x11_roles {
+        matchers.push(WindowMatcher::X11Role(Regex::new(&regex)?));
+    }
+
+    matchers.extend(
+        cond.outputs
+            .into_iter()
+            .map(|name| WindowMatcher::Output(OutputName(name))),
+    );
+    matchers.extend(cond.floating.map(WindowMatcher::Floating));
+
+    Ok(match any {
+        true => WindowMatcher::Any(matchers),
+        false => WindowMatcher::All(matchers),
+    })
 }
 
 impl From<WindowRule> for crate::window::rules::WindowRule {
@@ -797,6 +1525,34 @@ impl From<WindowRule> for crate::window::rules::WindowRule {
             })
         });
         let location = rule.x.and_then(|x| rule.y.map(|y| (x, y)));
+        let anchor = match rule.anchor() {
+            window::v0alpha1::FloatingAnchor::Unspecified => None,
+            window::v0alpha1::FloatingAnchor::Center => Some(FloatingAnchor::Center),
+            window::v0alpha1::FloatingAnchor::Top => Some(FloatingAnchor::Top),
+            window::v0alpha1::FloatingAnchor::Bottom => Some(FloatingAnchor::Bottom),
+            window::v0alpha1::FloatingAnchor::Left => Some(FloatingAnchor::Left),
+            window::v0alpha1::FloatingAnchor::Right => Some(FloatingAnchor::Right),
+            window::v0alpha1::FloatingAnchor::TopLeft => Some(FloatingAnchor::TopLeft),
+            window::v0alpha1::FloatingAnchor::TopRight => Some(FloatingAnchor::TopRight),
+            window::v0alpha1::FloatingAnchor::BottomLeft => Some(FloatingAnchor::BottomLeft),
+            window::v0alpha1::FloatingAnchor::BottomRight => Some(FloatingAnchor::BottomRight),
+        };
+        let animations = rule.animations;
+        let corner_radius = rule
+            .corner_radius
+            .filter(|radius| radius.is_finite() && *radius >= 0.0)
+            .map(f64::from);
+        let blur = rule.blur;
+        let dim = rule.dim;
+        let layer = match rule.layer() {
+            window::v0alpha1::WindowLayer::Unspecified => None,
+            window::v0alpha1::WindowLayer::Normal => Some(WindowLayer::Normal),
+            window::v0alpha1::WindowLayer::Above => Some(WindowLayer::Above),
+            window::v0alpha1::WindowLayer::Below => Some(WindowLayer::Below),
+        };
+        let pip = rule.pip;
+        let fake_fullscreen = rule.fake_fullscreen;
+        let follow = rule.follow;
 
         crate::window::rules::WindowRule {
             output,
@@ -805,6 +1561,15 @@ impl From<WindowRule> for crate::window::rules::WindowRule {
             fullscreen_or_maximized,
             size,
             location,
+            anchor,
+            animations,
+            corner_radius,
+            blur,
+            dim,
+            layer,
+            pip,
+            fake_fullscreen,
+            follow,
         }
     }
 }
@@ -33,16 +33,20 @@ use smithay::{
 use tracing::error;
 
 use crate::{
+    render::{color_filter::take_filtered_render_element_states, fullscreen_scanout_window},
     state::{State, SurfaceDmabufFeedback},
     window::WindowElement,
 };
 
 #[cfg(feature = "testing")]
 use self::dummy::Dummy;
-use self::{udev::Udev, winit::Winit};
+use self::{headless::Headless, nested::Nested, udev::Udev, winit::Winit};
 
 #[cfg(feature = "testing")]
 pub mod dummy;
+pub mod headless;
+pub mod nested;
+pub mod offscreen;
 pub mod udev;
 pub mod winit;
 #[cfg(feature = "wlcs")]
@@ -53,6 +57,10 @@ pub enum Backend {
     Winit(Winit),
     /// The compositor is running in a tty
     Udev(Udev),
+    /// The compositor is running without a display
+    Headless(Headless),
+    /// The compositor is running in a window inside another Wayland compositor
+    Nested(Nested),
     #[cfg(feature = "testing")]
     Dummy(Dummy),
 }
@@ -66,6 +74,16 @@ impl Backend {
                 }
             }
             Backend::Udev(udev) => udev.upscale_filter = filter,
+            Backend::Headless(headless) => {
+                if let Err(err) = headless.renderer.upscale_filter(filter) {
+                    error!("Failed to set headless upscale filter: {err}");
+                }
+            }
+            Backend::Nested(nested) => {
+                if let Err(err) = nested.renderer.upscale_filter(filter) {
+                    error!("Failed to set nested upscale filter: {err}");
+                }
+            }
             #[cfg(feature = "testing")]
             Backend::Dummy(_) => (),
         }
@@ -79,6 +97,16 @@ impl Backend {
                 }
             }
             Backend::Udev(udev) => udev.downscale_filter = filter,
+            Backend::Headless(headless) => {
+                if let Err(err) = headless.renderer.downscale_filter(filter) {
+                    error!("Failed to set headless downscale filter: {err}");
+                }
+            }
+            Backend::Nested(nested) => {
+                if let Err(err) = nested.renderer.downscale_filter(filter) {
+                    error!("Failed to set nested downscale filter: {err}");
+                }
+            }
             #[cfg(feature = "testing")]
             Backend::Dummy(_) => (),
         }
@@ -88,6 +116,8 @@ impl Backend {
         match self {
             Backend::Winit(winit) => winit.seat_name(),
             Backend::Udev(udev) => udev.seat_name(),
+            Backend::Headless(headless) => headless.seat_name(),
+            Backend::Nested(nested) => nested.seat_name(),
             #[cfg(feature = "testing")]
             Backend::Dummy(dummy) => dummy.seat_name(),
         }
@@ -97,6 +127,8 @@ impl Backend {
         match self {
             Backend::Winit(winit) => winit.early_import(surface),
             Backend::Udev(udev) => udev.early_import(surface),
+            Backend::Headless(headless) => headless.early_import(surface),
+            Backend::Nested(nested) => nested.early_import(surface),
             #[cfg(feature = "testing")]
             Backend::Dummy(dummy) => dummy.early_import(surface),
         }
@@ -122,6 +154,8 @@ impl Backend {
 pub trait BackendData: 'static {
     fn seat_name(&self) -> String;
     fn reset_buffers(&mut self, output: &Output);
+    /// Turn the given output's monitor on or off.
+    fn set_output_powered(&mut self, output: &Output, powered: bool);
 
     // INFO: only for udev in anvil, maybe shouldn't be a trait fn?
     fn early_import(&mut self, surface: &WlSurface);
@@ -132,6 +166,8 @@ impl BackendData for Backend {
         match self {
             Backend::Winit(winit) => winit.seat_name(),
             Backend::Udev(udev) => udev.seat_name(),
+            Backend::Headless(headless) => headless.seat_name(),
+            Backend::Nested(nested) => nested.seat_name(),
             #[cfg(feature = "testing")]
             Backend::Dummy(dummy) => dummy.seat_name(),
         }
@@ -141,15 +177,30 @@ impl BackendData for Backend {
         match self {
             Backend::Winit(winit) => winit.reset_buffers(output),
             Backend::Udev(udev) => udev.reset_buffers(output),
+            Backend::Headless(headless) => headless.reset_buffers(output),
+            Backend::Nested(nested) => nested.reset_buffers(output),
             #[cfg(feature = "testing")]
             Backend::Dummy(dummy) => dummy.reset_buffers(output),
         }
     }
 
+    fn set_output_powered(&mut self, output: &Output, powered: bool) {
+        match self {
+            Backend::Winit(winit) => winit.set_output_powered(output, powered),
+            Backend::Udev(udev) => udev.set_output_powered(output, powered),
+            Backend::Headless(headless) => headless.set_output_powered(output, powered),
+            Backend::Nested(nested) => nested.set_output_powered(output, powered),
+            #[cfg(feature = "testing")]
+            Backend::Dummy(dummy) => dummy.set_output_powered(output, powered),
+        }
+    }
+
     fn early_import(&mut self, surface: &WlSurface) {
         match self {
             Backend::Winit(winit) => winit.early_import(surface),
             Backend::Udev(udev) => udev.early_import(surface),
+            Backend::Headless(headless) => headless.early_import(surface),
+            Backend::Nested(nested) => nested.early_import(surface),
             #[cfg(feature = "testing")]
             Backend::Dummy(dummy) => dummy.early_import(surface),
         }
@@ -169,6 +220,12 @@ pub fn post_repaint(
     // let throttle = Some(Duration::from_secs(1));
     let throttle = Some(Duration::ZERO);
 
+    // With a color filter, surfaces were rendered into the filter's texture instead
+    let filtered_states = take_filtered_render_element_states(output);
+    let render_element_states = filtered_states.as_ref().unwrap_or(render_element_states);
+
+    let scanout_window = fullscreen_scanout_window(output, space);
+
     space.elements().for_each(|window| {
         window.with_surfaces(|surface, states_inner| {
             let primary_scanout_output = update_surface_primary_scanout_output(
@@ -189,10 +246,16 @@ pub fn post_repaint(
         if space.outputs_for_element(window).contains(output) {
             window.send_frame(output, time, throttle, surface_primary_scanout_output);
             if let Some(dmabuf_feedback) = dmabuf_feedback {
+                // Nudge fullscreen windows towards scanout-capable buffers even while they're
+                // still being composited
+                let is_scanout_window = scanout_window.as_ref() == Some(window);
                 window.send_dmabuf_feedback(
                     output,
                     surface_primary_scanout_output,
                     |surface, _| {
+                        if is_scanout_window {
+                            return dmabuf_feedback.scanout_feedback;
+                        }
                         select_dmabuf_feedback(
                             surface,
                             render_element_states,
@@ -257,6 +320,8 @@ impl DmabufHandler for State {
                     .expect("udev had no dmabuf state")
                     .0
             }
+            Backend::Headless(headless) => &mut headless.dmabuf_state.0,
+            Backend::Nested(nested) => &mut nested.dmabuf_state.0,
             #[cfg(feature = "testing")]
             Backend::Dummy(_) => unreachable!(),
         }
@@ -281,6 +346,8 @@ impl DmabufHandler for State {
                 .and_then(|mut renderer| renderer.import_dmabuf(&dmabuf, None))
                 .map(|_| ())
                 .map_err(|_| ()),
+            Backend::Headless(headless) => headless.renderer.import_dmabuf(&dmabuf).map_err(|_| ()),
+            Backend::Nested(nested) => nested.renderer.import_dmabuf(&dmabuf).map_err(|_| ()),
             #[cfg(feature = "testing")]
             Backend::Dummy(dummy) => dummy
                 .renderer
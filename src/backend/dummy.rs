@@ -44,6 +44,8 @@ impl BackendData for Dummy {
 
     fn reset_buffers(&mut self, _output: &Output) {}
 
+    fn set_output_powered(&mut self, _output: &Output, _powered: bool) {}
+
     fn early_import(&mut self, _surface: &WlSurface) {}
 }
 
@@ -0,0 +1,177 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! A backend with no real display.
+//!
+//! Outputs are virtual and are rendered into offscreen buffers, so things like screencopy
+//! still work. This is useful for CI, remote sessions, and automated tests.
+
+use std::{ffi::OsString, path::PathBuf, time::Duration};
+
+use smithay::{
+    output::{Output, PhysicalProperties, Subpixel},
+    reexports::{
+        calloop::{
+            timer::{TimeoutAction, Timer},
+            EventLoop,
+        },
+        wayland_server::{protocol::wl_surface::WlSurface, Display},
+    },
+    utils::{Physical, Size},
+    wayland::dmabuf::{DmabufFeedback, DmabufGlobal, DmabufState},
+};
+use tracing::error;
+
+use crate::state::State;
+
+use super::{
+    offscreen::{create_offscreen_renderer, OffscreenOutput, OffscreenRenderer},
+    Backend, BackendData,
+};
+
+/// The refresh rate of headless outputs, in millihertz.
+const REFRESH_RATE: i32 = 60_000;
+
+pub struct Headless {
+    pub renderer: OffscreenRenderer,
+    pub dmabuf_state: (DmabufState, DmabufGlobal, Option<DmabufFeedback>),
+    outputs: Vec<OffscreenOutput>,
+}
+
+impl BackendData for Headless {
+    fn seat_name(&self) -> String {
+        "headless".to_string()
+    }
+
+    fn reset_buffers(&mut self, output: &Output) {
+        if let Some(offscreen) = self.outputs.iter_mut().find(|op| op.output() == output) {
+            offscreen.reset_buffers();
+        }
+    }
+
+    fn set_output_powered(&mut self, output: &Output, powered: bool) {
+        // Rendering is skipped while powered off, see `OffscreenOutput::render`
+        if powered {
+            self.reset_buffers(output);
+        }
+    }
+
+    fn early_import(&mut self, _surface: &WlSurface) {}
+}
+
+impl Backend {
+    fn headless_mut(&mut self) -> &mut Headless {
+        let Backend::Headless(headless) = self else {
+            unreachable!()
+        };
+        headless
+    }
+}
+
+/// Start Pinnacle without a display, creating a virtual output for every given size.
+pub fn setup_headless(
+    no_config: bool,
+    config_dir: Option<PathBuf>,
+    output_sizes: Vec<Size<i32, Physical>>,
+) -> anyhow::Result<(State, EventLoop<'static, State>)> {
+    let event_loop: EventLoop<State> = EventLoop::try_new()?;
+
+    let display: Display<State> = Display::new()?;
+    let display_handle = display.handle();
+
+    let loop_handle = event_loop.handle();
+
+    let (renderer, dmabuf_state) = create_offscreen_renderer(&display_handle)?;
+
+    let backend = Backend::Headless(Headless {
+        renderer,
+        dmabuf_state,
+        outputs: Vec::new(),
+    });
+
+    let mut state = State::init(
+        backend,
+        display,
+        event_loop.get_signal(),
+        loop_handle,
+        no_config,
+        config_dir,
+    )?;
+
+    let headless = state.backend.headless_mut();
+
+    state
+        .pinnacle
+        .shm_state
+        .update_formats(headless.renderer.shm_formats());
+
+    let mut x = 0;
+    for (i, size) in output_sizes.into_iter().enumerate() {
+        let output = Output::new(
+            format!("HEADLESS-{}", i + 1),
+            PhysicalProperties {
+                size: (0, 0).into(),
+                subpixel: Subpixel::Unknown,
+                make: "Pinnacle".to_string(),
+                model: "Headless Output".to_string(),
+            },
+        );
+
+        let mode = smithay::output::Mode {
+            size,
+            refresh: REFRESH_RATE,
+        };
+
+        output.create_global::<State>(&display_handle);
+        output.set_preferred(mode);
+        state
+            .pinnacle
+            .change_output_state(&output, Some(mode), None, None, Some((x, 0).into()));
+        x += size.w;
+
+        if i == 0 {
+            state.pinnacle.output_focus_stack.set_focus(output.clone());
+        }
+
+        state
+            .backend
+            .headless_mut()
+            .outputs
+            .push(OffscreenOutput::new(output));
+    }
+
+    if let Err(err) = state.pinnacle.xwayland.start(
+        state.pinnacle.loop_handle.clone(),
+        None,
+        std::iter::empty::<(OsString, OsString)>(),
+        true,
+        |_| {},
+    ) {
+        error!("Failed to start XWayland: {err}");
+    }
+
+    let insert_ret = state.pinnacle.loop_handle.insert_source(
+        Timer::immediate(),
+        move |_instant, _metadata, state| {
+            state.render_headless_outputs();
+            TimeoutAction::ToDuration(Duration::from_secs_f64(1000.0 / REFRESH_RATE as f64))
+        },
+    );
+    if let Err(err) = insert_ret {
+        anyhow::bail!("Failed to insert headless render timer into event loop: {err}");
+    }
+
+    Ok((state, event_loop))
+}
+
+impl State {
+    fn render_headless_outputs(&mut self) {
+        let headless = self.backend.headless_mut();
+
+        self.pinnacle.update_window_animations();
+        let windows = self.pinnacle.space.elements().cloned().collect::<Vec<_>>();
+
+        for offscreen in headless.outputs.iter_mut() {
+            offscreen.render(&mut headless.renderer, &mut self.pinnacle, &windows);
+        }
+    }
+}
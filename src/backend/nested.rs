@@ -0,0 +1,860 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! A backend that runs Pinnacle as a window inside another Wayland compositor.
+//!
+//! Unlike the winit backend, this talks to the host compositor directly.
+//! Frames are rendered offscreen, only their damaged regions are copied into shm buffers,
+//! and nothing is committed to the host while the output is idle.
+
+mod input;
+
+use std::{collections::HashSet, ffi::OsString, path::PathBuf, time::Duration};
+
+use anyhow::Context;
+use smithay::{
+    backend::input::{AxisSource, ButtonState, InputEvent, KeyState},
+    input::pointer::RelativeMotionEvent,
+    output::{Output, PhysicalProperties, Subpixel},
+    reexports::{
+        calloop::{
+            self,
+            generic::Generic,
+            timer::{TimeoutAction, Timer},
+            EventLoop, Interest, PostAction,
+        },
+        wayland_server::{protocol::wl_surface::WlSurface, Display},
+    },
+    utils::{Physical, Rectangle, Size},
+    wayland::dmabuf::{DmabufFeedback, DmabufGlobal, DmabufState},
+};
+use smithay_client_toolkit::{
+    compositor::{CompositorHandler, CompositorState},
+    delegate_compositor, delegate_output, delegate_pointer, delegate_registry,
+    delegate_relative_pointer, delegate_seat, delegate_shm, delegate_xdg_shell,
+    delegate_xdg_window,
+    output::{OutputHandler, OutputState},
+    reexports::{
+        client::{
+            backend::WaylandError,
+            globals::registry_queue_init,
+            protocol::{wl_keyboard, wl_output, wl_pointer, wl_seat, wl_shm, wl_surface},
+            Connection, Dispatch, EventQueue, QueueHandle, WEnum,
+        },
+        protocols::wp::relative_pointer::zv1::client::zwp_relative_pointer_v1::ZwpRelativePointerV1,
+    },
+    registry::{ProvidesRegistryState, RegistryState},
+    registry_handlers,
+    seat::{
+        pointer::{PointerEvent, PointerEventKind, PointerHandler},
+        relative_pointer::{self, RelativePointerHandler, RelativePointerState},
+        Capability, SeatHandler, SeatState,
+    },
+    shell::{
+        xdg::{
+            window::{Window, WindowConfigure, WindowDecorations, WindowHandler},
+            XdgShell,
+        },
+        WaylandSurface,
+    },
+    shm::{
+        slot::{Buffer, SlotPool},
+        Shm, ShmHandler,
+    },
+};
+use tracing::{error, warn};
+
+use crate::state::State;
+
+use self::input::{
+    NestedInput, NestedKeyboardKeyEvent, NestedPointerAxisEvent, NestedPointerButtonEvent,
+    NestedPointerMotionAbsoluteEvent,
+};
+
+use super::{
+    offscreen::{create_offscreen_renderer, OffscreenOutput, OffscreenRenderer},
+    Backend, BackendData,
+};
+
+/// The refresh rate of the nested output, in millihertz.
+const REFRESH_RATE: i32 = 60_000;
+
+/// The size of the window before the host configures it.
+const DEFAULT_SIZE: (i32, i32) = (1280, 720);
+
+/// The maximum number of shm buffers to allocate.
+///
+/// If the host holds onto all of them, frames are skipped until one is released.
+const MAX_SHM_BUFFERS: usize = 3;
+
+pub struct Nested {
+    pub renderer: OffscreenRenderer,
+    pub dmabuf_state: (DmabufState, DmabufGlobal, Option<DmabufFeedback>),
+    offscreen: OffscreenOutput,
+    event_queue: EventQueue<NestedHost>,
+    host: NestedHost,
+}
+
+/// Client-side state for the connection to the host compositor.
+struct NestedHost {
+    registry_state: RegistryState,
+    seat_state: SeatState,
+    output_state: OutputState,
+    shm: Shm,
+    relative_pointer_state: RelativePointerState,
+    _compositor_state: CompositorState,
+    _xdg_shell: XdgShell,
+
+    window: Window,
+    pool: SlotPool,
+    buffers: Vec<ShmBuffer>,
+
+    keyboard: Option<wl_keyboard::WlKeyboard>,
+    pointer: Option<wl_pointer::WlPointer>,
+    relative_pointer: Option<ZwpRelativePointerV1>,
+
+    /// Keys pressed while the window had keyboard focus.
+    pressed_keys: HashSet<u32>,
+    /// The size of the window.
+    size: Size<i32, Physical>,
+    configured: bool,
+    /// Whether a commit is waiting on a frame callback.
+    frame_pending: bool,
+    /// Damage from frames that haven't been committed yet.
+    uncommitted_damage: Vec<Rectangle<i32, Physical>>,
+
+    /// Events for the compositor to process after dispatching.
+    events: Vec<HostEvent>,
+}
+
+/// An shm buffer and the regions of it that are out of date.
+struct ShmBuffer {
+    buffer: Buffer,
+    size: Size<i32, Physical>,
+    damage: Vec<Rectangle<i32, Physical>>,
+}
+
+enum HostEvent {
+    Resized(Size<i32, Physical>),
+    CloseRequested,
+    Input(InputEvent<NestedInput>),
+    RelativeMotion(relative_pointer::RelativeMotionEvent),
+}
+
+impl BackendData for Nested {
+    fn seat_name(&self) -> String {
+        "nested".to_string()
+    }
+
+    fn reset_buffers(&mut self, output: &Output) {
+        if self.offscreen.output() == output {
+            self.offscreen.reset_buffers();
+        }
+    }
+
+    fn set_output_powered(&mut self, output: &Output, powered: bool) {
+        // Rendering is skipped while powered off, see `OffscreenOutput::render`
+        if powered {
+            self.reset_buffers(output);
+        }
+    }
+
+    fn early_import(&mut self, _surface: &WlSurface) {}
+}
+
+impl Backend {
+    fn nested_mut(&mut self) -> &mut Nested {
+        let Backend::Nested(nested) = self else { unreachable!() };
+        nested
+    }
+}
+
+/// Start Pinnacle as a window inside the Wayland compositor at `WAYLAND_DISPLAY`.
+pub fn setup_nested(
+    no_config: bool,
+    config_dir: Option<PathBuf>,
+) -> anyhow::Result<(State, EventLoop<'static, State>)> {
+    let event_loop: EventLoop<State> = EventLoop::try_new()?;
+
+    let display: Display<State> = Display::new()?;
+    let display_handle = display.handle();
+
+    let loop_handle = event_loop.handle();
+
+    let connection =
+        Connection::connect_to_env().context("failed to connect to the host compositor")?;
+    let (globals, mut event_queue) = registry_queue_init::<NestedHost>(&connection)?;
+    let qh = event_queue.handle();
+
+    let compositor_state =
+        CompositorState::bind(&globals, &qh).context("host has no wl_compositor")?;
+    let xdg_shell = XdgShell::bind(&globals, &qh).context("host has no xdg_wm_base")?;
+    let shm = Shm::bind(&globals, &qh).context("host has no wl_shm")?;
+
+    let surface = compositor_state.create_surface(&qh);
+    let window = xdg_shell.create_window(surface, WindowDecorations::RequestServer, &qh);
+    window.set_title("Pinnacle");
+    window.set_app_id("pinnacle");
+    window.commit();
+
+    let pool = SlotPool::new((DEFAULT_SIZE.0 * DEFAULT_SIZE.1 * 4) as usize, &shm)?;
+
+    let mut host = NestedHost {
+        registry_state: RegistryState::new(&globals),
+        seat_state: SeatState::new(&globals, &qh),
+        output_state: OutputState::new(&globals, &qh),
+        shm,
+        relative_pointer_state: RelativePointerState::bind(&globals, &qh),
+        _compositor_state: compositor_state,
+        _xdg_shell: xdg_shell,
+        window,
+        pool,
+        buffers: Vec::new(),
+        keyboard: None,
+        pointer: None,
+        relative_pointer: None,
+        pressed_keys: HashSet::new(),
+        size: DEFAULT_SIZE.into(),
+        configured: false,
+        frame_pending: false,
+        uncommitted_damage: Vec::new(),
+        events: Vec::new(),
+    };
+
+    // Wait for the initial configure so the output starts out at the right size
+    while !host.configured {
+        event_queue.blocking_dispatch(&mut host)?;
+    }
+    host.events.clear();
+
+    let mode = smithay::output::Mode {
+        size: host.size,
+        refresh: REFRESH_RATE,
+    };
+
+    let output = Output::new(
+        "NESTED-1".to_string(),
+        PhysicalProperties {
+            size: (0, 0).into(),
+            subpixel: Subpixel::Unknown,
+            make: "Pinnacle".to_string(),
+            model: "Nested Window".to_string(),
+        },
+    );
+
+    let (renderer, dmabuf_state) = create_offscreen_renderer(&display_handle)?;
+
+    let backend = Backend::Nested(Nested {
+        renderer,
+        dmabuf_state,
+        offscreen: OffscreenOutput::new(output.clone()),
+        event_queue,
+        host,
+    });
+
+    let mut state = State::init(
+        backend,
+        display,
+        event_loop.get_signal(),
+        loop_handle,
+        no_config,
+        config_dir,
+    )?;
+
+    output.create_global::<State>(&display_handle);
+    output.set_preferred(mode);
+    state
+        .pinnacle
+        .change_output_state(&output, Some(mode), None, None, Some((0, 0).into()));
+    state.pinnacle.output_focus_stack.set_focus(output);
+
+    let nested = state.backend.nested_mut();
+
+    state
+        .pinnacle
+        .shm_state
+        .update_formats(nested.renderer.shm_formats());
+
+    if let Err(err) = state.pinnacle.xwayland.start(
+        state.pinnacle.loop_handle.clone(),
+        None,
+        std::iter::empty::<(OsString, OsString)>(),
+        true,
+        |_| {},
+    ) {
+        error!("Failed to start XWayland: {err}");
+    }
+
+    let host_fd = connection.backend().poll_fd().try_clone_to_owned()?;
+
+    let insert_ret = state.pinnacle.loop_handle.insert_source(
+        Generic::new(host_fd, Interest::READ, calloop::Mode::Level),
+        |_, _, state| {
+            state.dispatch_nested_host();
+            Ok(PostAction::Continue)
+        },
+    );
+    if let Err(err) = insert_ret {
+        anyhow::bail!("Failed to insert host connection into event loop: {err}");
+    }
+
+    let insert_ret = state.pinnacle.loop_handle.insert_source(
+        Timer::immediate(),
+        move |_instant, _metadata, state| {
+            state.render_nested_output();
+            TimeoutAction::ToDuration(Duration::from_secs_f64(1000.0 / REFRESH_RATE as f64))
+        },
+    );
+    if let Err(err) = insert_ret {
+        anyhow::bail!("Failed to insert nested render timer into event loop: {err}");
+    }
+
+    Ok((state, event_loop))
+}
+
+impl State {
+    /// Read and dispatch events from the host compositor.
+    fn dispatch_nested_host(&mut self) {
+        let nested = self.backend.nested_mut();
+
+        if let Some(guard) = nested.event_queue.prepare_read() {
+            if let Err(err) = guard.read() {
+                // WouldBlock just means there was nothing to read
+                if !matches!(&err, WaylandError::Io(io) if io.kind() == std::io::ErrorKind::WouldBlock)
+                {
+                    error!("Failed to read from the host compositor: {err}");
+                    self.pinnacle.shutdown();
+                    return;
+                }
+            }
+        }
+
+        if let Err(err) = nested.event_queue.dispatch_pending(&mut nested.host) {
+            error!("Failed to dispatch host compositor events: {err}");
+            self.pinnacle.shutdown();
+            return;
+        }
+
+        let events = std::mem::take(&mut nested.host.events);
+        let output = nested.offscreen.output().clone();
+
+        for event in events {
+            match event {
+                HostEvent::Resized(size) => {
+                    let mode = smithay::output::Mode {
+                        size,
+                        refresh: REFRESH_RATE,
+                    };
+                    self.pinnacle
+                        .change_output_state(&output, Some(mode), None, None, None);
+                    self.pinnacle.request_layout(&output);
+                }
+                HostEvent::CloseRequested => self.pinnacle.shutdown(),
+                HostEvent::Input(event) => self.process_input_event(event),
+                HostEvent::RelativeMotion(event) => self.nested_relative_motion(event),
+            }
+        }
+
+        if let Err(err) = self.backend.nested_mut().event_queue.flush() {
+            warn!("Failed to flush host compositor connection: {err}");
+        }
+    }
+
+    /// Pass relative pointer motion from the host through to clients.
+    ///
+    /// Absolute motion already moves the pointer, so this only sends relative motion events.
+    fn nested_relative_motion(&mut self, event: relative_pointer::RelativeMotionEvent) {
+        let Some(pointer) = self.pinnacle.seat.get_pointer() else {
+            return;
+        };
+
+        let focus = self.pointer_focus_target_under(pointer.current_location());
+
+        pointer.relative_motion(
+            self,
+            focus,
+            &RelativeMotionEvent {
+                delta: event.delta.into(),
+                delta_unaccel: event.delta_unaccel.into(),
+                utime: event.utime,
+            },
+        );
+        pointer.frame(self);
+    }
+
+    fn render_nested_output(&mut self) {
+        let nested = self.backend.nested_mut();
+
+        if nested.host.frame_pending {
+            return;
+        }
+
+        self.pinnacle.update_window_animations();
+        let windows = self.pinnacle.space.elements().cloned().collect::<Vec<_>>();
+
+        let Some(damage) =
+            nested
+                .offscreen
+                .render(&mut nested.renderer, &mut self.pinnacle, &windows)
+        else {
+            return;
+        };
+
+        if let Err(err) = nested.present(&damage) {
+            warn!("Failed to present to the host compositor: {err}");
+        }
+
+        if let Err(err) = nested.event_queue.flush() {
+            warn!("Failed to flush host compositor connection: {err}");
+        }
+    }
+}
+
+impl Nested {
+    /// Copy the damaged parts of the offscreen buffer into a free shm buffer and commit it.
+    fn present(&mut self, damage: &[Rectangle<i32, Physical>]) -> anyhow::Result<()> {
+        let host = &mut self.host;
+
+        host.uncommitted_damage.extend_from_slice(damage);
+
+        // Don't bother the host if nothing changed
+        if host.uncommitted_damage.is_empty() {
+            return Ok(());
+        }
+
+        let Some(mode) = self.offscreen.output().current_mode() else {
+            return Ok(());
+        };
+        let size = mode.size;
+
+        host.buffers.retain(|buffer| buffer.size == size);
+        for buffer in host.buffers.iter_mut() {
+            buffer.damage.extend_from_slice(damage);
+        }
+
+        let free_buffer = host
+            .buffers
+            .iter()
+            .position(|buffer| host.pool.canvas(&buffer.buffer).is_some());
+
+        let idx = match free_buffer {
+            Some(idx) => idx,
+            None if host.buffers.len() < MAX_SHM_BUFFERS => {
+                let (buffer, _) = host.pool.create_buffer(
+                    size.w,
+                    size.h,
+                    size.w * 4,
+                    wl_shm::Format::Argb8888,
+                )?;
+                host.buffers.push(ShmBuffer {
+                    buffer,
+                    size,
+                    damage: vec![Rectangle::from_loc_and_size((0, 0), size)],
+                });
+                host.buffers.len() - 1
+            }
+            // The host is holding onto every buffer; try again next frame
+            None => return Ok(()),
+        };
+
+        let shm_buffer = &mut host.buffers[idx];
+        let canvas = host
+            .pool
+            .canvas(&shm_buffer.buffer)
+            .context("shm buffer is in use")?;
+
+        let buffer_rect = Rectangle::from_loc_and_size((0, 0), size);
+        let stride = size.w as usize * 4;
+
+        for rect in shm_buffer.damage.drain(..) {
+            let Some(rect) = rect.intersection(buffer_rect) else {
+                continue;
+            };
+
+            let bytes = self.offscreen.read_region(
+                &mut self.renderer,
+                Rectangle::from_loc_and_size((rect.loc.x, rect.loc.y), (rect.size.w, rect.size.h)),
+            )?;
+
+            let row_len = rect.size.w as usize * 4;
+            for row in 0..rect.size.h as usize {
+                let src = row * row_len;
+                let dst = (rect.loc.y as usize + row) * stride + rect.loc.x as usize * 4;
+                canvas[dst..dst + row_len].copy_from_slice(&bytes[src..src + row_len]);
+            }
+        }
+
+        let surface = host.window.wl_surface();
+        shm_buffer.buffer.attach_to(surface)?;
+        for rect in host.uncommitted_damage.drain(..) {
+            surface.damage_buffer(rect.loc.x, rect.loc.y, rect.size.w, rect.size.h);
+        }
+        surface.frame(&self.event_queue.handle(), surface.clone());
+        surface.commit();
+
+        host.frame_pending = true;
+
+        Ok(())
+    }
+}
+
+impl CompositorHandler for NestedHost {
+    fn scale_factor_changed(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _surface: &wl_surface::WlSurface,
+        _new_factor: i32,
+    ) {
+        // TODO: HiDPI hosts
+    }
+
+    fn transform_changed(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _surface: &wl_surface::WlSurface,
+        _new_transform: wl_output::Transform,
+    ) {
+    }
+
+    fn frame(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _surface: &wl_surface::WlSurface,
+        _time: u32,
+    ) {
+        self.frame_pending = false;
+    }
+
+    fn surface_enter(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _surface: &wl_surface::WlSurface,
+        _output: &wl_output::WlOutput,
+    ) {
+    }
+
+    fn surface_leave(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _surface: &wl_surface::WlSurface,
+        _output: &wl_output::WlOutput,
+    ) {
+    }
+}
+
+impl OutputHandler for NestedHost {
+    fn output_state(&mut self) -> &mut OutputState {
+        &mut self.output_state
+    }
+
+    fn new_output(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _output: wl_output::WlOutput,
+    ) {
+    }
+
+    fn update_output(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _output: wl_output::WlOutput,
+    ) {
+    }
+
+    fn output_destroyed(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _output: wl_output::WlOutput,
+    ) {
+    }
+}
+
+impl WindowHandler for NestedHost {
+    fn request_close(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, _window: &Window) {
+        self.events.push(HostEvent::CloseRequested);
+    }
+
+    fn configure(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _window: &Window,
+        configure: WindowConfigure,
+        _serial: u32,
+    ) {
+        let size = match configure.new_size {
+            (Some(w), Some(h)) => Size::from((w.get() as i32, h.get() as i32)),
+            _ => self.size,
+        };
+
+        self.configured = true;
+
+        if size != self.size {
+            self.size = size;
+            self.events.push(HostEvent::Resized(size));
+        }
+
+        // Nothing gets committed while idle, so make sure the new configure is acked
+        self.frame_pending = false;
+    }
+}
+
+impl SeatHandler for NestedHost {
+    fn seat_state(&mut self) -> &mut SeatState {
+        &mut self.seat_state
+    }
+
+    fn new_seat(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, _seat: wl_seat::WlSeat) {}
+
+    fn new_capability(
+        &mut self,
+        _conn: &Connection,
+        qh: &QueueHandle<Self>,
+        seat: wl_seat::WlSeat,
+        capability: Capability,
+    ) {
+        match capability {
+            Capability::Keyboard if self.keyboard.is_none() => {
+                self.keyboard = Some(seat.get_keyboard(qh, ()));
+            }
+            Capability::Pointer if self.pointer.is_none() => {
+                let pointer = match self.seat_state.get_pointer(qh, &seat) {
+                    Ok(pointer) => pointer,
+                    Err(err) => {
+                        warn!("Failed to get host pointer: {err}");
+                        return;
+                    }
+                };
+                self.relative_pointer = self
+                    .relative_pointer_state
+                    .get_relative_pointer(&pointer, qh)
+                    .ok();
+                self.pointer = Some(pointer);
+            }
+            _ => (),
+        }
+    }
+
+    fn remove_capability(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _seat: wl_seat::WlSeat,
+        capability: Capability,
+    ) {
+        match capability {
+            Capability::Keyboard => {
+                self.release_pressed_keys(0);
+                if let Some(keyboard) = self.keyboard.take() {
+                    keyboard.release();
+                }
+            }
+            Capability::Pointer => {
+                if let Some(relative_pointer) = self.relative_pointer.take() {
+                    relative_pointer.destroy();
+                }
+                if let Some(pointer) = self.pointer.take() {
+                    pointer.release();
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn remove_seat(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, _seat: wl_seat::WlSeat) {
+    }
+}
+
+impl NestedHost {
+    /// Release every key Pinnacle thinks is pressed.
+    ///
+    /// The host stops sending key events once the window loses keyboard focus,
+    /// so without this keys pressed at that moment would get stuck.
+    fn release_pressed_keys(&mut self, time: u32) {
+        for key in self.pressed_keys.drain() {
+            self.events.push(HostEvent::Input(InputEvent::Keyboard {
+                event: NestedKeyboardKeyEvent {
+                    time,
+                    key,
+                    state: KeyState::Released,
+                    count: 0,
+                },
+            }));
+        }
+    }
+}
+
+impl Dispatch<wl_keyboard::WlKeyboard, ()> for NestedHost {
+    fn event(
+        state: &mut Self,
+        _proxy: &wl_keyboard::WlKeyboard,
+        event: wl_keyboard::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        match event {
+            // Pinnacle uses its own keymap, and the host's modifiers are derived from key events
+            wl_keyboard::Event::Keymap { .. }
+            | wl_keyboard::Event::Modifiers { .. }
+            | wl_keyboard::Event::RepeatInfo { .. } => (),
+            // Keys that were already pressed on enter are intentionally not forwarded
+            // so that the keybind used to focus the window doesn't trigger anything.
+            wl_keyboard::Event::Enter { .. } => (),
+            wl_keyboard::Event::Leave { .. } => state.release_pressed_keys(0),
+            wl_keyboard::Event::Key {
+                time,
+                key,
+                state: WEnum::Value(key_state),
+                ..
+            } => {
+                let key_state = match key_state {
+                    wl_keyboard::KeyState::Pressed => {
+                        state.pressed_keys.insert(key);
+                        KeyState::Pressed
+                    }
+                    wl_keyboard::KeyState::Released => {
+                        // Don't forward releases for keys pressed before we had focus
+                        if !state.pressed_keys.remove(&key) {
+                            return;
+                        }
+                        KeyState::Released
+                    }
+                    _ => return,
+                };
+
+                state.events.push(HostEvent::Input(InputEvent::Keyboard {
+                    event: NestedKeyboardKeyEvent {
+                        time,
+                        key,
+                        state: key_state,
+                        count: state.pressed_keys.len() as u32,
+                    },
+                }));
+            }
+            _ => (),
+        }
+    }
+}
+
+impl PointerHandler for NestedHost {
+    fn pointer_frame(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _pointer: &wl_pointer::WlPointer,
+        events: &[PointerEvent],
+    ) {
+        let window_size = self.size.to_logical(1);
+
+        for event in events {
+            if &event.surface != self.window.wl_surface() {
+                continue;
+            }
+
+            let (x, y) = event.position;
+
+            let input_event = match event.kind {
+                PointerEventKind::Enter { .. } | PointerEventKind::Motion { .. } => {
+                    let time = match event.kind {
+                        PointerEventKind::Motion { time } => time,
+                        _ => 0,
+                    };
+                    InputEvent::PointerMotionAbsolute {
+                        event: NestedPointerMotionAbsoluteEvent {
+                            time,
+                            x,
+                            y,
+                            window_size,
+                        },
+                    }
+                }
+                PointerEventKind::Leave { .. } => continue,
+                PointerEventKind::Press { time, button, .. } => InputEvent::PointerButton {
+                    event: NestedPointerButtonEvent {
+                        time,
+                        button,
+                        state: ButtonState::Pressed,
+                    },
+                },
+                PointerEventKind::Release { time, button, .. } => InputEvent::PointerButton {
+                    event: NestedPointerButtonEvent {
+                        time,
+                        button,
+                        state: ButtonState::Released,
+                    },
+                },
+                PointerEventKind::Axis {
+                    time,
+                    horizontal,
+                    vertical,
+                    source,
+                } => {
+                    let source = match source {
+                        Some(wl_pointer::AxisSource::Finger) => AxisSource::Finger,
+                        Some(wl_pointer::AxisSource::Continuous) => AxisSource::Continuous,
+                        Some(wl_pointer::AxisSource::WheelTilt) => AxisSource::WheelTilt,
+                        _ => AxisSource::Wheel,
+                    };
+                    let v120 = |discrete: i32| (discrete != 0).then_some(discrete as f64 * 120.0);
+                    InputEvent::PointerAxis {
+                        event: NestedPointerAxisEvent {
+                            time,
+                            source,
+                            horizontal: horizontal.absolute,
+                            vertical: vertical.absolute,
+                            horizontal_v120: v120(horizontal.discrete),
+                            vertical_v120: v120(vertical.discrete),
+                        },
+                    }
+                }
+            };
+
+            self.events.push(HostEvent::Input(input_event));
+        }
+    }
+}
+
+impl RelativePointerHandler for NestedHost {
+    fn relative_pointer_motion(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _relative_pointer: &ZwpRelativePointerV1,
+        _pointer: &wl_pointer::WlPointer,
+        event: relative_pointer::RelativeMotionEvent,
+    ) {
+        self.events.push(HostEvent::RelativeMotion(event));
+    }
+}
+
+impl ShmHandler for NestedHost {
+    fn shm_state(&mut self) -> &mut Shm {
+        &mut self.shm
+    }
+}
+
+impl ProvidesRegistryState for NestedHost {
+    fn registry(&mut self) -> &mut RegistryState {
+        &mut self.registry_state
+    }
+
+    registry_handlers![OutputState, SeatState];
+}
+
+delegate_compositor!(NestedHost);
+delegate_output!(NestedHost);
+delegate_seat!(NestedHost);
+delegate_pointer!(NestedHost);
+delegate_relative_pointer!(NestedHost);
+delegate_shm!(NestedHost);
+delegate_xdg_shell!(NestedHost);
+delegate_xdg_window!(NestedHost);
+delegate_registry!(NestedHost);
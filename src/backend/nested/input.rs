@@ -0,0 +1,229 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Input events forwarded from the host compositor.
+
+use std::path::PathBuf;
+
+use smithay::{
+    backend::input::{
+        AbsolutePositionEvent, Axis, AxisSource, ButtonState, Device, DeviceCapability, Event,
+        InputBackend, KeyState, KeyboardKeyEvent, Keycode, PointerAxisEvent, PointerButtonEvent,
+        PointerMotionAbsoluteEvent, UnusedEvent,
+    },
+    utils::{Logical, Size},
+};
+
+/// An [`InputBackend`] for input coming from the host compositor.
+#[derive(Debug)]
+pub struct NestedInput;
+
+/// The host's seat, exposed as a single input device.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NestedInputDevice;
+
+impl Device for NestedInputDevice {
+    fn id(&self) -> String {
+        "nested".to_string()
+    }
+
+    fn name(&self) -> String {
+        "nested".to_string()
+    }
+
+    fn has_capability(&self, capability: DeviceCapability) -> bool {
+        matches!(
+            capability,
+            DeviceCapability::Keyboard | DeviceCapability::Pointer
+        )
+    }
+
+    fn usb_id(&self) -> Option<(u32, u32)> {
+        None
+    }
+
+    fn syspath(&self) -> Option<PathBuf> {
+        None
+    }
+}
+
+/// A key press or release from the host.
+#[derive(Debug, Clone)]
+pub struct NestedKeyboardKeyEvent {
+    /// Time in milliseconds
+    pub time: u32,
+    /// The evdev keycode
+    pub key: u32,
+    pub state: KeyState,
+    /// The number of keys pressed on the host's keyboard
+    pub count: u32,
+}
+
+impl Event<NestedInput> for NestedKeyboardKeyEvent {
+    fn time(&self) -> u64 {
+        self.time as u64 * 1000
+    }
+
+    fn device(&self) -> NestedInputDevice {
+        NestedInputDevice
+    }
+}
+
+impl KeyboardKeyEvent<NestedInput> for NestedKeyboardKeyEvent {
+    fn key_code(&self) -> Keycode {
+        // xkb keycodes are offset from evdev keycodes by 8
+        (self.key + 8).into()
+    }
+
+    fn state(&self) -> KeyState {
+        self.state
+    }
+
+    fn count(&self) -> u32 {
+        self.count
+    }
+}
+
+/// Pointer motion inside the host window.
+#[derive(Debug, Clone)]
+pub struct NestedPointerMotionAbsoluteEvent {
+    pub time: u32,
+    /// The location of the pointer relative to the window.
+    pub x: f64,
+    pub y: f64,
+    /// The size of the window.
+    pub window_size: Size<i32, Logical>,
+}
+
+impl Event<NestedInput> for NestedPointerMotionAbsoluteEvent {
+    fn time(&self) -> u64 {
+        self.time as u64 * 1000
+    }
+
+    fn device(&self) -> NestedInputDevice {
+        NestedInputDevice
+    }
+}
+
+impl AbsolutePositionEvent<NestedInput> for NestedPointerMotionAbsoluteEvent {
+    fn x(&self) -> f64 {
+        self.x
+    }
+
+    fn y(&self) -> f64 {
+        self.y
+    }
+
+    fn x_transformed(&self, width: i32) -> f64 {
+        f64::max(self.x * width as f64 / self.window_size.w as f64, 0.0)
+    }
+
+    fn y_transformed(&self, height: i32) -> f64 {
+        f64::max(self.y * height as f64 / self.window_size.h as f64, 0.0)
+    }
+}
+
+impl PointerMotionAbsoluteEvent<NestedInput> for NestedPointerMotionAbsoluteEvent {}
+
+/// A pointer button press or release from the host.
+#[derive(Debug, Clone)]
+pub struct NestedPointerButtonEvent {
+    pub time: u32,
+    pub button: u32,
+    pub state: ButtonState,
+}
+
+impl Event<NestedInput> for NestedPointerButtonEvent {
+    fn time(&self) -> u64 {
+        self.time as u64 * 1000
+    }
+
+    fn device(&self) -> NestedInputDevice {
+        NestedInputDevice
+    }
+}
+
+impl PointerButtonEvent<NestedInput> for NestedPointerButtonEvent {
+    fn button_code(&self) -> u32 {
+        self.button
+    }
+
+    fn state(&self) -> ButtonState {
+        self.state
+    }
+}
+
+/// A scroll from the host.
+#[derive(Debug, Clone)]
+pub struct NestedPointerAxisEvent {
+    pub time: u32,
+    pub source: AxisSource,
+    pub horizontal: f64,
+    pub vertical: f64,
+    pub horizontal_v120: Option<f64>,
+    pub vertical_v120: Option<f64>,
+}
+
+impl Event<NestedInput> for NestedPointerAxisEvent {
+    fn time(&self) -> u64 {
+        self.time as u64 * 1000
+    }
+
+    fn device(&self) -> NestedInputDevice {
+        NestedInputDevice
+    }
+}
+
+impl PointerAxisEvent<NestedInput> for NestedPointerAxisEvent {
+    fn amount(&self, axis: Axis) -> Option<f64> {
+        let amount = match axis {
+            Axis::Horizontal => self.horizontal,
+            Axis::Vertical => self.vertical,
+        };
+        (amount != 0.0).then_some(amount)
+    }
+
+    fn amount_v120(&self, axis: Axis) -> Option<f64> {
+        match axis {
+            Axis::Horizontal => self.horizontal_v120,
+            Axis::Vertical => self.vertical_v120,
+        }
+    }
+
+    fn source(&self) -> AxisSource {
+        self.source
+    }
+}
+
+impl InputBackend for NestedInput {
+    type Device = NestedInputDevice;
+    type KeyboardKeyEvent = NestedKeyboardKeyEvent;
+    type PointerAxisEvent = NestedPointerAxisEvent;
+    type PointerButtonEvent = NestedPointerButtonEvent;
+    // Relative motion is passed through separately, see `State::nested_relative_motion`
+    type PointerMotionEvent = UnusedEvent;
+    type PointerMotionAbsoluteEvent = NestedPointerMotionAbsoluteEvent;
+
+    type GestureSwipeBeginEvent = UnusedEvent;
+    type GestureSwipeUpdateEvent = UnusedEvent;
+    type GestureSwipeEndEvent = UnusedEvent;
+    type GesturePinchBeginEvent = UnusedEvent;
+    type GesturePinchUpdateEvent = UnusedEvent;
+    type GesturePinchEndEvent = UnusedEvent;
+    type GestureHoldBeginEvent = UnusedEvent;
+    type GestureHoldEndEvent = UnusedEvent;
+
+    type TouchDownEvent = UnusedEvent;
+    type TouchUpEvent = UnusedEvent;
+    type TouchMotionEvent = UnusedEvent;
+    type TouchCancelEvent = UnusedEvent;
+    type TouchFrameEvent = UnusedEvent;
+
+    type TabletToolAxisEvent = UnusedEvent;
+    type TabletToolProximityEvent = UnusedEvent;
+    type TabletToolTipEvent = UnusedEvent;
+    type TabletToolButtonEvent = UnusedEvent;
+
+    type SwitchToggleEvent = UnusedEvent;
+
+    type SpecialEvent = UnusedEvent;
+}
@@ -0,0 +1,546 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Rendering into buffers that aren't tied to a display.
+//!
+//! This is shared by the headless and nested backends. GLES is used when possible,
+//! otherwise rendering falls back to pixman. The udev and winit backends always
+//! render with GLES.
+
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, ensure, Context};
+use smithay::{
+    backend::{
+        allocator::{dmabuf::Dmabuf, Format, Fourcc},
+        egl::{EGLContext, EGLDevice, EGLDisplay},
+        renderer::{
+            buffer_type,
+            damage::{self, OutputDamageTracker, RenderOutputResult},
+            gles::{GlesRenderbuffer, GlesRenderer},
+            pixman::PixmanRenderer,
+            Bind, BufferType, ExportMem, ImportDma, ImportEgl, ImportMemWl, Offscreen, Renderer,
+            Texture, TextureFilter,
+        },
+    },
+    output::Output,
+    reexports::{
+        calloop::LoopHandle,
+        pixman::Image,
+        wayland_protocols::wp::presentation_time::server::wp_presentation_feedback,
+        wayland_server::{protocol::wl_shm, DisplayHandle},
+    },
+    utils::{Buffer, Physical, Rectangle, Size, Transform},
+    wayland::dmabuf::{DmabufFeedback, DmabufFeedbackBuilder, DmabufGlobal, DmabufState},
+};
+use tracing::{error, info, warn};
+
+use crate::{
+    recording::recording_indicator_element,
+    render::{
+        damage_debug::damage_debug_elements,
+        edge_tile_preview::edge_tile_preview_element,
+        monocle_indicator::monocle_indicator_elements,
+        perf_overlay::{damage_fraction, perf_overlay_elements},
+        pointer::PointerElement,
+        pointer_render_elements,
+        snap_guides::snap_guide_elements,
+        take_presentation_feedback,
+        window_switcher::window_switcher_elements,
+        OutputRenderElement, PinnacleRenderer,
+    },
+    screenshot::{render_screenshot, Screenshot, ScreenshotTarget},
+    state::{Pinnacle, State, WithState},
+    window::WindowElement,
+};
+
+use super::winit::handle_pending_screencopy;
+
+/// A renderer for offscreen outputs.
+pub enum OffscreenRenderer {
+    Gles(GlesRenderer),
+    /// Software rendering, used when GLES fails to initialize.
+    Pixman(PixmanRenderer),
+}
+
+impl OffscreenRenderer {
+    pub fn upscale_filter(&mut self, filter: TextureFilter) -> anyhow::Result<()> {
+        match self {
+            OffscreenRenderer::Gles(renderer) => renderer.upscale_filter(filter)?,
+            OffscreenRenderer::Pixman(renderer) => renderer.upscale_filter(filter)?,
+        }
+        Ok(())
+    }
+
+    pub fn downscale_filter(&mut self, filter: TextureFilter) -> anyhow::Result<()> {
+        match self {
+            OffscreenRenderer::Gles(renderer) => renderer.downscale_filter(filter)?,
+            OffscreenRenderer::Pixman(renderer) => renderer.downscale_filter(filter)?,
+        }
+        Ok(())
+    }
+
+    pub fn shm_formats(&self) -> Vec<wl_shm::Format> {
+        match self {
+            OffscreenRenderer::Gles(renderer) => renderer.shm_formats().collect(),
+            OffscreenRenderer::Pixman(renderer) => renderer.shm_formats().collect(),
+        }
+    }
+
+    pub fn import_dmabuf(&mut self, dmabuf: &Dmabuf) -> anyhow::Result<()> {
+        match self {
+            OffscreenRenderer::Gles(renderer) => {
+                renderer.import_dmabuf(dmabuf, None)?;
+            }
+            OffscreenRenderer::Pixman(renderer) => {
+                renderer.import_dmabuf(dmabuf, None)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Take a screenshot with this renderer.
+    pub fn screenshot(
+        &mut self,
+        pinnacle: &mut Pinnacle,
+        target: &ScreenshotTarget,
+        include_cursor: bool,
+    ) -> anyhow::Result<Screenshot> {
+        match self {
+            OffscreenRenderer::Gles(renderer) => {
+                render_screenshot::<_, GlesRenderbuffer>(renderer, pinnacle, target, include_cursor)
+            }
+            OffscreenRenderer::Pixman(renderer) => render_screenshot::<_, Image<'static, 'static>>(
+                renderer,
+                pinnacle,
+                target,
+                include_cursor,
+            ),
+        }
+    }
+
+    fn dmabuf_formats(&self) -> Vec<Format> {
+        match self {
+            OffscreenRenderer::Gles(renderer) => renderer.dmabuf_formats().collect(),
+            OffscreenRenderer::Pixman(renderer) => renderer.dmabuf_formats().collect(),
+        }
+    }
+}
+
+/// The buffer an [`OffscreenOutput`] renders into.
+enum OffscreenBuffer {
+    Gles(GlesRenderbuffer),
+    Pixman(Image<'static, 'static>),
+}
+
+/// An output and the offscreen buffer it renders to.
+pub(super) struct OffscreenOutput {
+    output: Output,
+    damage_tracker: OutputDamageTracker,
+    /// The buffer this output renders to and its size.
+    ///
+    /// This is recreated whenever the output's mode changes.
+    buffer: Option<(OffscreenBuffer, Size<i32, Physical>)>,
+    full_redraw: bool,
+}
+
+/// Create an [`OffscreenRenderer`] along with a dmabuf global for it.
+///
+/// This falls back to pixman if GLES can't be initialized.
+pub(super) fn create_offscreen_renderer(
+    display_handle: &DisplayHandle,
+) -> anyhow::Result<(
+    OffscreenRenderer,
+    (DmabufState, DmabufGlobal, Option<DmabufFeedback>),
+)> {
+    let (mut renderer, render_node) = match create_gles_renderer() {
+        Ok((renderer, render_node)) => (OffscreenRenderer::Gles(renderer), render_node),
+        Err(err) => {
+            warn!("Failed to initialize GLES, falling back to pixman: {err}");
+            let renderer = PixmanRenderer::new().context("failed to create pixman renderer")?;
+            (OffscreenRenderer::Pixman(renderer), None)
+        }
+    };
+
+    let dmabuf_formats = renderer.dmabuf_formats();
+
+    let dmabuf_state = match render_node {
+        Some(node) => {
+            let default_feedback = DmabufFeedbackBuilder::new(node.dev_id(), dmabuf_formats)
+                .build()
+                .expect("DmabufFeedbackBuilder error");
+            let mut dmabuf_state = DmabufState::new();
+            let dmabuf_global = dmabuf_state
+                .create_global_with_default_feedback::<State>(display_handle, &default_feedback);
+            (dmabuf_state, dmabuf_global, Some(default_feedback))
+        }
+        None => {
+            warn!("Offscreen renderer has no render node, dmabuf will use v3");
+            let mut dmabuf_state = DmabufState::new();
+            let dmabuf_global = dmabuf_state.create_global::<State>(display_handle, dmabuf_formats);
+            (dmabuf_state, dmabuf_global, None)
+        }
+    };
+
+    if let OffscreenRenderer::Gles(renderer) = &mut renderer {
+        if renderer.bind_wl_display(display_handle).is_ok() {
+            info!("EGL hardware-acceleration enabled");
+        }
+    }
+
+    Ok((renderer, dmabuf_state))
+}
+
+fn create_gles_renderer() -> anyhow::Result<(GlesRenderer, Option<smithay::backend::drm::DrmNode>)>
+{
+    // Prefer a device with a render node, but fall back to something like llvmpipe
+    // so this works on machines without a gpu.
+    let mut devices = EGLDevice::enumerate()?.collect::<Vec<_>>();
+    let device = devices
+        .iter()
+        .position(|device| device.try_get_render_node().ok().flatten().is_some())
+        .map(|idx| devices.swap_remove(idx))
+        .or_else(|| devices.into_iter().next())
+        .context("no EGL devices found")?;
+
+    let render_node = device.try_get_render_node().ok().flatten();
+
+    // SAFETY: the device lives as long as the display
+    let egl_display = unsafe { EGLDisplay::new(device) }?;
+    let egl_context = EGLContext::new(&egl_display)?;
+    // SAFETY: the context is not shared with anything else
+    let renderer = unsafe { GlesRenderer::new(egl_context) }?;
+
+    Ok((renderer, render_node))
+}
+
+impl OffscreenOutput {
+    pub(super) fn new(output: Output) -> Self {
+        Self {
+            damage_tracker: OutputDamageTracker::from_output(&output),
+            output,
+            buffer: None,
+            full_redraw: true,
+        }
+    }
+
+    pub(super) fn output(&self) -> &Output {
+        &self.output
+    }
+
+    /// Redraw everything on the next render.
+    pub(super) fn reset_buffers(&mut self) {
+        self.full_redraw = true;
+    }
+
+    /// Render this output into its buffer, recreating it if the output's mode changed.
+    ///
+    /// Returns the damage of the rendered frame, or `None` if nothing was rendered.
+    pub(super) fn render(
+        &mut self,
+        renderer: &mut OffscreenRenderer,
+        pinnacle: &mut Pinnacle,
+        windows: &[WindowElement],
+    ) -> Option<Vec<Rectangle<i32, Physical>>> {
+        if !self.output.with_state(|state| state.is_displaying()) {
+            return None;
+        }
+
+        let mode = self.output.current_mode()?;
+
+        if !self
+            .buffer
+            .as_ref()
+            .is_some_and(|(_, size)| *size == mode.size)
+        {
+            let size = mode.size.to_logical(1).to_buffer(1, Transform::Normal);
+            let buffer = match renderer {
+                OffscreenRenderer::Gles(renderer) => renderer
+                    .create_buffer(Fourcc::Abgr8888, size)
+                    .map(OffscreenBuffer::Gles)
+                    .map_err(|err| err.to_string()),
+                OffscreenRenderer::Pixman(renderer) => renderer
+                    .create_buffer(Fourcc::Argb8888, size)
+                    .map(OffscreenBuffer::Pixman)
+                    .map_err(|err| err.to_string()),
+            };
+            match buffer {
+                Ok(buffer) => self.buffer = Some((buffer, mode.size)),
+                Err(err) => {
+                    error!("Failed to create offscreen buffer: {err}");
+                    return None;
+                }
+            }
+            self.full_redraw = true;
+        }
+
+        match renderer {
+            OffscreenRenderer::Gles(renderer) => {
+                let Some((OffscreenBuffer::Gles(buffer), _)) = &self.buffer else {
+                    unreachable!("buffer was created by this renderer above");
+                };
+                let buffer = buffer.clone();
+                self.render_with(
+                    renderer,
+                    buffer,
+                    pinnacle,
+                    windows,
+                    handle_pending_screencopy,
+                )
+            }
+            OffscreenRenderer::Pixman(renderer) => {
+                let Some((OffscreenBuffer::Pixman(buffer), _)) = &self.buffer else {
+                    unreachable!("buffer was created by this renderer above");
+                };
+                let buffer = buffer.clone();
+                self.render_with(
+                    renderer,
+                    buffer,
+                    pinnacle,
+                    windows,
+                    handle_pending_pixman_screencopy,
+                )
+            }
+        }
+    }
+
+    fn render_with<R, B>(
+        &mut self,
+        renderer: &mut R,
+        buffer: B,
+        pinnacle: &mut Pinnacle,
+        windows: &[WindowElement],
+        handle_screencopy: fn(&mut R, &Output, &RenderOutputResult, &LoopHandle<'static, State>),
+    ) -> Option<Vec<Rectangle<i32, Physical>>>
+    where
+        R: PinnacleRenderer + Bind<B>,
+        <R as Renderer>::TextureId: Texture + Clone + 'static,
+    {
+        let output = &self.output;
+
+        let mode = output.current_mode()?;
+
+        // There is no cursor plane to draw named cursors, so only cursor surfaces are rendered.
+        let mut pointer_element = PointerElement::<R::TextureId>::new();
+        pointer_element.set_status(pinnacle.cursor_status.clone());
+
+        let mut output_render_elements = Vec::new();
+
+        output_render_elements.extend(perf_overlay_elements(renderer, pinnacle, output));
+        output_render_elements.extend(window_switcher_elements(renderer, pinnacle, output));
+        output_render_elements.extend(monocle_indicator_elements(renderer, pinnacle, output));
+        output_render_elements.extend(snap_guide_elements(pinnacle, output));
+        output_render_elements.extend(edge_tile_preview_element(pinnacle, output));
+        output_render_elements
+            .extend(recording_indicator_element(pinnacle, output).map(OutputRenderElement::from));
+
+        let pending_screencopy_without_cursor = output.with_state(|state| {
+            state
+                .screencopy
+                .as_ref()
+                .is_some_and(|sc| !sc.overlay_cursor())
+        });
+
+        if !pending_screencopy_without_cursor {
+            let pointer_location = pinnacle
+                .seat
+                .get_pointer()
+                .map(|ptr| ptr.current_location())
+                .unwrap_or((0.0, 0.0).into());
+
+            output_render_elements.extend(pointer_render_elements(
+                output,
+                renderer,
+                &pinnacle.space,
+                pointer_location,
+                &mut pinnacle.cursor_status,
+                pinnacle.dnd_icon.as_ref(),
+                &pointer_element,
+            ));
+        }
+
+        output_render_elements.extend(crate::render::output_render_elements(
+            output,
+            renderer,
+            &pinnacle.space,
+            windows,
+            &pinnacle.config.render_settings,
+        ));
+
+        let damage_debug_elements =
+            damage_debug_elements(pinnacle, output, &output_render_elements);
+        output_render_elements.splice(0..0, damage_debug_elements);
+
+        // Blurring renders into other textures, so the buffer is bound
+        // only after all render elements are created.
+        if let Err(err) = renderer.bind(buffer) {
+            error!("Failed to bind offscreen buffer: {err}");
+            return None;
+        }
+
+        // The buffer is reused every frame, so it always has the previous frame's contents.
+        let age = if std::mem::take(&mut self.full_redraw) { 0 } else { 1 };
+
+        let render_start = Instant::now();
+
+        let render_output_result = match self.damage_tracker.render_output(
+            renderer,
+            age,
+            &output_render_elements,
+            [0.6, 0.6, 0.6, 1.0],
+        ) {
+            Ok(result) => result,
+            Err(damage::Error::Rendering(err)) => {
+                warn!("Failed to render offscreen output: {err}");
+                return None;
+            }
+            Err(damage::Error::OutputNoMode(_)) => unreachable!("checked for a mode above"),
+        };
+
+        if let Some(damage) = render_output_result.damage {
+            let damage = damage_fraction(damage, mode.size);
+            let render_time = render_start.elapsed();
+            output.with_state_mut(|state| {
+                state
+                    .frame_stats
+                    .record(Instant::now(), render_time, false, damage)
+            });
+        }
+
+        handle_screencopy(
+            renderer,
+            output,
+            &render_output_result,
+            &pinnacle.loop_handle,
+        );
+
+        let time = pinnacle.clock.now();
+
+        super::post_repaint(
+            output,
+            &render_output_result.states,
+            &pinnacle.space,
+            None,
+            time.into(),
+            &pinnacle.cursor_status,
+        );
+
+        let damage = render_output_result
+            .damage
+            .as_ref()
+            .map(|damage| damage.iter().copied().collect::<Vec<_>>())?;
+
+        let mut output_presentation_feedback =
+            take_presentation_feedback(output, &pinnacle.space, &render_output_result.states);
+        output_presentation_feedback.presented(
+            time,
+            Duration::from_secs_f64(1000.0 / mode.refresh as f64),
+            0,
+            wp_presentation_feedback::Kind::empty(),
+        );
+
+        Some(damage)
+    }
+
+    /// Read a region of the last rendered frame as [`Fourcc::Argb8888`] pixels.
+    pub(super) fn read_region(
+        &self,
+        renderer: &mut OffscreenRenderer,
+        region: Rectangle<i32, Buffer>,
+    ) -> anyhow::Result<Vec<u8>> {
+        match (renderer, self.buffer.as_ref().map(|(buffer, _)| buffer)) {
+            (OffscreenRenderer::Gles(renderer), Some(OffscreenBuffer::Gles(buffer))) => {
+                renderer.bind(buffer.clone())?;
+                read_framebuffer(renderer, region)
+            }
+            (OffscreenRenderer::Pixman(renderer), Some(OffscreenBuffer::Pixman(buffer))) => {
+                renderer.bind(buffer.clone())?;
+                read_framebuffer(renderer, region)
+            }
+            _ => bail!("offscreen output has no buffer"),
+        }
+    }
+}
+
+/// Read a region of the currently bound framebuffer as [`Fourcc::Argb8888`] pixels.
+fn read_framebuffer<R>(renderer: &mut R, region: Rectangle<i32, Buffer>) -> anyhow::Result<Vec<u8>>
+where
+    R: ExportMem,
+    <R as Renderer>::Error: Send + Sync + 'static,
+{
+    let mapping = renderer.copy_framebuffer(region, Fourcc::Argb8888)?;
+    Ok(renderer.map_texture(&mapping)?.to_vec())
+}
+
+/// Complete a pending screencopy on a pixman-rendered output.
+///
+/// Pixman can't blit into dmabufs, so only shm buffers are supported.
+fn handle_pending_pixman_screencopy(
+    renderer: &mut PixmanRenderer,
+    output: &Output,
+    render_output_result: &RenderOutputResult,
+    _loop_handle: &LoopHandle<'static, State>,
+) {
+    let Some(mut screencopy) = output.with_state_mut(|state| state.screencopy.take()) else {
+        return;
+    };
+
+    assert!(screencopy.output() == output);
+
+    if screencopy.with_damage() {
+        match render_output_result.damage.as_ref() {
+            Some(damage) if !damage.is_empty() => screencopy.damage(damage),
+            _ => {
+                output.with_state_mut(|state| state.screencopy.replace(screencopy));
+                return;
+            }
+        }
+    }
+
+    if !matches!(buffer_type(screencopy.buffer()), Some(BufferType::Shm)) {
+        // Dropping the screencopy fails it
+        warn!("Screencopy with the pixman renderer requires a shm buffer");
+        return;
+    }
+
+    let region = screencopy.physical_region();
+    let buffer_region = region.to_logical(1).to_buffer(
+        1,
+        Transform::Normal,
+        &output
+            .current_mode()
+            .map(|mode| mode.size.to_logical(1))
+            .unwrap_or_default(),
+    );
+
+    // The output's buffer is still bound from rendering
+    let res = smithay::wayland::shm::with_buffer_contents_mut(
+        &screencopy.buffer().clone(),
+        |shm_ptr, shm_len, buffer_data| {
+            ensure!(
+                buffer_data.format == wl_shm::Format::Argb8888
+                    && buffer_data.stride == region.size.w * 4
+                    && buffer_data.height == region.size.h
+                    && shm_len as i32 == buffer_data.stride * buffer_data.height,
+                "invalid buffer format or size"
+            );
+
+            let bytes = read_framebuffer(renderer, buffer_region)?;
+
+            ensure!(bytes.len() == shm_len, "mapped buffer has wrong length");
+
+            // SAFETY: `bytes` is valid for reads of `shm_len` as checked above,
+            // and `shm_ptr` is assumed to be valid for writes of `shm_len`.
+            unsafe {
+                std::ptr::copy_nonoverlapping(bytes.as_ptr(), shm_ptr, shm_len);
+            }
+
+            Ok(())
+        },
+    );
+
+    match res {
+        Ok(Ok(())) => screencopy.submit(false),
+        Ok(Err(err)) => error!("Failed to submit screencopy: {err}"),
+        Err(err) => error!("Failed to access screencopy buffer: {err}"),
+    }
+}
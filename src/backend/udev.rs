@@ -7,7 +7,8 @@ use std::{
     collections::{HashMap, HashSet},
     ffi::OsString,
     path::{Path, PathBuf},
-    time::Duration,
+    sync::Arc,
+    time::{Duration, Instant},
 };
 
 use anyhow::{anyhow, ensure, Context};
@@ -29,13 +30,14 @@ use smithay::{
             DrmNode, NodeType,
         },
         egl::{self, EGLDevice, EGLDisplay},
+        input::InputEvent,
         libinput::{LibinputInputBackend, LibinputSessionInterface},
         renderer::{
             self, damage,
             element::{
                 self, surface::WaylandSurfaceRenderElement, texture::TextureBuffer, Element,
             },
-            gles::{GlesRenderbuffer, GlesRenderer},
+            gles::{GlesFrame, GlesRenderbuffer, GlesRenderer},
             multigpu::{gbm::GbmGlesBackend, GpuManager, MultiRenderer, MultiTexture},
             sync::SyncPoint,
             utils::{CommitCounter, DamageSet},
@@ -51,19 +53,27 @@ use smithay::{
         vulkan::{self, version::Version, PhysicalDevice},
         SwapBuffersError,
     },
+    delegate_drm_lease,
     desktop::{
         layer_map_for_output,
-        utils::{send_frames_surface_tree, OutputPresentationFeedback},
+        utils::{
+            send_frames_surface_tree, surface_primary_scanout_output, OutputPresentationFeedback,
+        },
     },
-    input::pointer::CursorImageStatus,
+    input::pointer::{CursorIcon, CursorImageStatus},
     output::{Output, PhysicalProperties, Subpixel},
     reexports::{
         ash::vk::ExtPhysicalDeviceDrmFn,
         calloop::{
-            self, generic::Generic, Dispatcher, EventLoop, Idle, Interest, LoopHandle, PostAction,
-            RegistrationToken,
+            self,
+            generic::Generic,
+            timer::{TimeoutAction, Timer},
+            Dispatcher, EventLoop, Idle, Interest, LoopHandle, PostAction, RegistrationToken,
+        },
+        drm::{
+            control::{connector, crtc, ModeTypeFlags},
+            Device as _, DriverCapability,
         },
-        drm::control::{connector, crtc, ModeTypeFlags},
         gbm::BufferObject,
         input::Libinput,
         rustix::fs::OFlags,
@@ -77,9 +87,13 @@ use smithay::{
             Display, DisplayHandle,
         },
     },
-    utils::{DeviceFd, IsAlive, Point, Rectangle, Transform},
+    utils::{Buffer, DeviceFd, IsAlive, Point, Rectangle, Size, Transform},
     wayland::{
         dmabuf::{self, DmabufFeedback, DmabufFeedbackBuilder, DmabufGlobal, DmabufState},
+        drm_lease::{
+            DrmLease, DrmLeaseBuilder, DrmLeaseHandler, DrmLeaseRequest, DrmLeaseState,
+            LeaseRejected,
+        },
         shm::shm_format_to_fourcc,
     },
 };
@@ -89,15 +103,27 @@ use tracing::{debug, error, info, trace, warn};
 use crate::{
     backend::Backend,
     config::ConnectorSavedState,
-    output::OutputName,
+    output::{add_custom_mode, profile::MonitorId, refreshes_per_frame, Modeline, OutputName, Vrr},
+    protocol::drm_syncobj::{self, DrmReleasePoint, DrmSyncobjState},
+    recording::recording_indicator_element,
     render::{
-        pointer::PointerElement, pointer_render_elements, take_presentation_feedback,
-        OutputRenderElement,
+        damage_debug::damage_debug_elements,
+        edge_tile_preview::edge_tile_preview_element,
+        fullscreen_scanout_window,
+        monocle_indicator::monocle_indicator_elements,
+        perf_overlay::{damage_fraction, perf_overlay_elements},
+        pointer::PointerElement,
+        pointer_render_elements, release_points,
+        snap_guides::snap_guide_elements,
+        take_presentation_feedback,
+        window_switcher::window_switcher_elements,
+        OutputRenderElement, PinnacleRenderer,
     },
+    screenshot::{render_screenshot, Screenshot, ScreenshotTarget},
     state::{Pinnacle, State, SurfaceDmabufFeedback, WithState},
 };
 
-use self::drm_util::EdidInfo;
+use self::drm_util::{create_drm_mode, is_non_desktop, is_vrr_capable, set_vrr_enabled, EdidInfo};
 
 use super::BackendData;
 
@@ -117,6 +143,21 @@ type UdevRenderer<'a> = MultiRenderer<
     GbmGlesBackend<GlesRenderer, DrmDeviceFd>,
 >;
 
+impl PinnacleRenderer for UdevRenderer<'_> {
+    fn gles_renderer(&mut self) -> Option<&mut GlesRenderer> {
+        Some(self.as_mut())
+    }
+
+    fn gles_frame<'a, 'frame>(
+        frame: &'a mut Self::Frame<'frame>,
+    ) -> Option<&'a mut GlesFrame<'frame>>
+    where
+        Self: 'frame,
+    {
+        Some(frame.as_mut())
+    }
+}
+
 type UdevRenderFrameResult<'a> = RenderFrameResult<
     'a,
     BufferObject<()>,
@@ -145,10 +186,19 @@ pub struct Udev {
     backends: HashMap<DrmNode, UdevBackendData>,
     pointer_images: Vec<(xcursor::parser::Image, TextureBuffer<MultiTexture>)>,
     pointer_element: PointerElement<MultiTexture>,
-    pointer_image: crate::cursor::Cursor,
+    /// The cursor theme and size `pointer_images` were imported from
+    pointer_images_theme: (String, u32),
 
     pub(super) upscale_filter: TextureFilter,
     pub(super) downscale_filter: TextureFilter,
+
+    /// Render nodes whose devices are about to be recreated after a GPU reset.
+    pending_gpu_recoveries: HashSet<DrmNode>,
+    /// Lease states of removed devices.
+    ///
+    /// Clients may still make requests on lease objects of a removed device, so these are
+    /// kept until the device is added again.
+    retired_lease_states: HashMap<DrmNode, DrmLeaseState>,
 }
 
 impl Backend {
@@ -166,26 +216,48 @@ impl Backend {
 impl Udev {
     /// Schedule a new render that will cause the compositor to redraw everything.
     pub fn schedule_render(&mut self, loop_handle: &LoopHandle<State>, output: &Output) {
+        // Everything gets rerendered on resume
+        if !self.session.is_active() {
+            return;
+        }
+
+        if !output.with_state(|state| state.is_displaying()) {
+            return;
+        }
+
         let Some(surface) = render_surface_for_output(output, &mut self.backends) else {
             return;
         };
 
-        match &surface.render_state {
+        match &mut surface.render_state {
             RenderState::Idle => {
-                let output = output.clone();
-                let token = loop_handle.insert_idle(move |state| {
-                    state
-                        .backend
-                        .udev_mut()
-                        .render_surface(&mut state.pinnacle, &output);
-                });
-
-                surface.render_state = RenderState::Scheduled(token);
+                surface.render_state = match render_delay(output, surface) {
+                    Some(delay) => {
+                        let op = output.clone();
+                        let token = loop_handle.insert_source(
+                            Timer::from_duration(delay),
+                            move |_, _, state| {
+                                state
+                                    .backend
+                                    .udev_mut()
+                                    .on_render_deadline(&state.pinnacle.loop_handle, &op);
+                                TimeoutAction::Drop
+                            },
+                        );
+
+                        match token {
+                            Ok(token) => RenderState::Delayed { token },
+                            Err(_) => queue_render(loop_handle, output),
+                        }
+                    }
+                    None => queue_render(loop_handle, output),
+                };
             }
-            RenderState::Scheduled(_) => (),
+            RenderState::Scheduled(_) | RenderState::Delayed { .. } => (),
             RenderState::WaitingForVblank { dirty: _ } => {
                 surface.render_state = RenderState::WaitingForVblank { dirty: true }
             }
+            RenderState::WaitingForEstimatedVblank { dirty, .. } => *dirty = true,
         }
     }
 }
@@ -248,22 +320,44 @@ impl State {
         }
     }
 
+    /// Advertise the given output's `wl_output` global to clients or stop advertising it.
+    ///
+    /// Does nothing on other backends.
+    pub fn set_output_global_enabled(&mut self, output: &Output, enabled: bool) {
+        if let Backend::Udev(udev) = &mut self.backend {
+            let Some(surface) = render_surface_for_output(output, &mut udev.backends) else {
+                return;
+            };
+
+            match (enabled, surface.global.take()) {
+                (true, None) => {
+                    surface.global = Some(output.create_global::<State>(&udev.display_handle));
+                }
+                (false, Some(global)) => {
+                    udev.display_handle.remove_global::<State>(global);
+                }
+                (_, global) => surface.global = global,
+            }
+        }
+    }
+
     /// Resize the output with the given mode.
     ///
+    /// On udev, the mode must be one of the modes the output's connector advertises.
+    ///
     /// TODO: This is in udev.rs but is also used in winit.rs.
     /// |     I've got no clue how to make things public without making a mess.
     pub fn resize_output(&mut self, output: &Output, mode: smithay::output::Mode) {
         if let Backend::Udev(udev) = &mut self.backend {
-            let drm_mode = udev.backends.iter().find_map(|(_, backend)| {
+            let Some(data) = output.user_data().get::<UdevOutputData>() else {
+                return;
+            };
+
+            let drm_mode = udev.backends.get(&data.device_id).and_then(|backend| {
                 backend
                     .drm_scanner
                     .crtcs()
-                    .find(|(_, handle)| {
-                        output
-                            .user_data()
-                            .get::<UdevOutputData>()
-                            .is_some_and(|data| &data.crtc == handle)
-                    })
+                    .find(|(_, handle)| &data.crtc == handle)
                     .and_then(|(info, _)| {
                         info.modes()
                             .iter()
@@ -272,16 +366,35 @@ impl State {
                     .copied()
             });
 
-            if let Some(drm_mode) = drm_mode {
-                if let Some(render_surface) = render_surface_for_output(output, &mut udev.backends)
-                {
-                    match render_surface.compositor.use_mode(drm_mode) {
-                        Ok(()) => {
-                            self.pinnacle
-                                .change_output_state(output, Some(mode), None, None, None);
-                        }
-                        Err(err) => error!("Failed to resize output: {err}"),
+            // Fall back to modes added by the config
+            let drm_mode = drm_mode.or_else(|| {
+                output.with_state(|state| {
+                    state
+                        .custom_modes
+                        .iter()
+                        .find(|modeline| modeline.mode() == mode)
+                        .map(create_drm_mode)
+                })
+            });
+
+            let Some(drm_mode) = drm_mode else {
+                warn!(
+                    "Output {} does not support mode {}x{}@{}mHz",
+                    output.name(),
+                    mode.size.w,
+                    mode.size.h,
+                    mode.refresh
+                );
+                return;
+            };
+
+            if let Some(render_surface) = render_surface_for_output(output, &mut udev.backends) {
+                match render_surface.compositor.use_mode(drm_mode) {
+                    Ok(()) => {
+                        self.pinnacle
+                            .change_output_state(output, Some(mode), None, None, None);
                     }
+                    Err(err) => error!("Failed to resize output: {err}"),
                 }
             }
         } else {
@@ -292,6 +405,29 @@ impl State {
         self.pinnacle.request_layout(output);
         self.schedule_render(output);
     }
+
+    /// Set the output's mode to a custom modeline.
+    ///
+    /// Modelines bypass validation against the connector's modes, so a bad one
+    /// may fail to commit or leave the monitor without a signal.
+    pub fn set_output_modeline(&mut self, output: &Output, modeline: Modeline) {
+        add_custom_mode(output, modeline);
+        self.resize_output(output, modeline.mode());
+    }
+
+    /// Take a screenshot with the primary gpu.
+    pub fn screenshot(
+        &mut self,
+        pinnacle: &mut Pinnacle,
+        target: &ScreenshotTarget,
+        include_cursor: bool,
+    ) -> anyhow::Result<Screenshot> {
+        let mut renderer = self.gpu_manager.single_renderer(&self.primary_gpu)?;
+        let _ = renderer.upscale_filter(self.upscale_filter);
+        let _ = renderer.downscale_filter(self.downscale_filter);
+
+        render_screenshot::<_, GlesRenderbuffer>(&mut renderer, pinnacle, target, include_cursor)
+    }
 }
 
 impl BackendData for Udev {
@@ -309,6 +445,34 @@ impl BackendData for Udev {
         }
     }
 
+    fn set_output_powered(&mut self, output: &Output, powered: bool) {
+        let Some(surface) = render_surface_for_output(output, &mut self.backends) else {
+            return;
+        };
+
+        if powered {
+            // Force a full modeset on the next render to turn the crtc back on
+            if let Err(err) = surface.compositor.reset_state() {
+                warn!("Failed to reset drm surface state: {err}");
+            }
+            surface.compositor.reset_buffers();
+            surface.vrr_enabled = false;
+        } else {
+            if let RenderState::Scheduled(token) =
+                std::mem::replace(&mut surface.render_state, RenderState::Idle)
+            {
+                token.cancel();
+            }
+            // Disables the crtc
+            if let Err(err) = surface.compositor.clear() {
+                warn!("Failed to power off {}: {err}", output.name());
+            }
+            // No more vblanks arrive to release these
+            surface.queued_release_points.clear();
+            surface.presented_release_points.clear();
+        }
+    }
+
     fn early_import(&mut self, surface: &WlSurface) {
         if let Err(err) = self.gpu_manager.early_import(self.primary_gpu, surface) {
             warn!("early buffer import failed: {}", err);
@@ -316,6 +480,96 @@ impl BackendData for Udev {
     }
 }
 
+impl DrmLeaseHandler for State {
+    fn drm_lease_state(&mut self, node: DrmNode) -> &mut DrmLeaseState {
+        let udev = self.backend.udev_mut();
+
+        // Lease globals only exist for devices with a lease state, and removed devices keep
+        // theirs in `retired_lease_states`, so requests on a lease object always find one.
+        // Leases themselves are only granted by `lease_request`, which rejects requests
+        // for devices that are gone.
+        let lease_state = match udev.backends.get_mut(&node) {
+            Some(UdevBackendData {
+                lease_state: Some(lease_state),
+                ..
+            }) => lease_state,
+            _ => udev
+                .retired_lease_states
+                .get_mut(&node)
+                .expect("lease objects only exist for devices with a lease state"),
+        };
+
+        lease_state
+    }
+
+    fn lease_request(
+        &mut self,
+        node: DrmNode,
+        request: DrmLeaseRequest,
+    ) -> Result<DrmLeaseBuilder, LeaseRejected> {
+        let Some(backend) = self
+            .backend
+            .udev()
+            .backends
+            .get(&node)
+            .filter(|backend| backend.lease_state.is_some())
+        else {
+            warn!("Lease requested for {node}, which was removed or can't lease, denying request");
+            return Err(LeaseRejected::default());
+        };
+
+        let mut builder = DrmLeaseBuilder::new(&backend.drm);
+        for connector in request.connectors {
+            let Some((_, crtc)) = backend
+                .non_desktop_connectors
+                .iter()
+                .find(|(handle, _)| *handle == connector)
+            else {
+                warn!(
+                    ?connector,
+                    "Lease requested for desktop connector, denying request"
+                );
+                return Err(LeaseRejected::default());
+            };
+
+            builder.add_connector(connector);
+            builder.add_crtc(*crtc);
+
+            let planes = backend
+                .drm
+                .planes(crtc)
+                .map_err(LeaseRejected::with_cause)?;
+
+            let primary_plane_claim = backend
+                .drm
+                .claim_plane(planes.primary.handle, *crtc)
+                .ok_or_else(LeaseRejected::default)?;
+            builder.add_plane(planes.primary.handle, primary_plane_claim);
+
+            if let Some(cursor) = planes.cursor {
+                if let Some(claim) = backend.drm.claim_plane(cursor.handle, *crtc) {
+                    builder.add_plane(cursor.handle, claim);
+                }
+            }
+        }
+
+        Ok(builder)
+    }
+
+    fn new_active_lease(&mut self, node: DrmNode, lease: DrmLease) {
+        if let Some(backend) = self.backend.udev_mut().backends.get_mut(&node) {
+            backend.active_leases.push(lease);
+        }
+    }
+
+    fn lease_destroyed(&mut self, node: DrmNode, lease_id: u32) {
+        if let Some(backend) = self.backend.udev_mut().backends.get_mut(&node) {
+            backend.active_leases.retain(|lease| lease.id() != lease_id);
+        }
+    }
+}
+delegate_drm_lease!(State);
+
 pub fn setup_udev(
     no_config: bool,
     config_dir: Option<PathBuf>,
@@ -376,7 +630,12 @@ pub fn setup_udev(
             // GPU disconnected
             UdevEvent::Removed { device_id } => {
                 if let Ok(node) = DrmNode::from_dev_id(device_id) {
-                    udev.device_removed(pinnacle, node)
+                    udev.device_removed(pinnacle, node);
+
+                    let render_node = node.node_with_type(NodeType::Render).and_then(Result::ok);
+                    if node == udev.primary_gpu || render_node == Some(udev.primary_gpu) {
+                        udev.replace_primary_gpu(pinnacle);
+                    }
                 }
             }
         }
@@ -395,12 +654,15 @@ pub fn setup_udev(
         gpu_manager,
         allocator: None,
         backends: HashMap::new(),
-        pointer_image: crate::cursor::Cursor::load(),
         pointer_images: Vec::new(),
         pointer_element: PointerElement::default(),
+        pointer_images_theme: Default::default(),
 
         upscale_filter: TextureFilter::Linear,
         downscale_filter: TextureFilter::Linear,
+
+        pending_gpu_recoveries: HashSet::new(),
+        retired_lease_states: HashMap::new(),
     };
 
     let display_handle = display.handle();
@@ -450,7 +712,12 @@ pub fn setup_udev(
         .handle()
         .insert_source(libinput_backend, move |event, _, state| {
             state.pinnacle.apply_libinput_settings(&event);
+            // New keyboards start with their lock key leds off
+            let device_added = matches!(event, InputEvent::DeviceAdded { .. });
             state.process_input_event(event);
+            if device_added {
+                state.update_keyboard_leds();
+            }
         });
 
     if let Err(err) = insert_ret {
@@ -462,12 +729,28 @@ pub fn setup_udev(
         .insert_source(notifier, move |event, _, state| {
             match event {
                 session::Event::PauseSession => {
+                    // Releases for keys held right now will go to the other session
+                    state.release_all_keys();
+
                     let udev = state.backend.udev_mut();
                     libinput_context.suspend();
                     info!("pausing session");
 
                     for backend in udev.backends.values_mut() {
                         backend.drm.pause();
+                        if let Some(lease_state) = backend.lease_state.as_mut() {
+                            lease_state.suspend();
+                        }
+
+                        // No vblanks arrive while paused, so stop waiting on them
+                        // to not leave outputs frozen on resume.
+                        for surface in backend.surfaces.values_mut() {
+                            if let RenderState::Scheduled(token) =
+                                std::mem::replace(&mut surface.render_state, RenderState::Idle)
+                            {
+                                token.cancel();
+                            }
+                        }
                     }
                 }
                 session::Event::ActivateSession => {
@@ -516,6 +799,21 @@ pub fn setup_udev(
                             if let Err(err) = backend.drm.activate(true) {
                                 error!("Error activating DRM device: {err}");
                             }
+
+                            if let Some(lease_state) = backend.lease_state.as_mut() {
+                                lease_state.resume::<State>();
+                            }
+
+                            for surface in backend.surfaces.values_mut() {
+                                // The other session may have changed anything about the crtcs,
+                                // so force a full modeset and redraw.
+                                if let Err(err) = surface.compositor.reset_state() {
+                                    warn!("Failed to reset drm surface state: {err}");
+                                }
+                                surface.compositor.reset_buffers();
+                                // Reapplied on the next render
+                                surface.vrr_enabled = false;
+                            }
                         }
 
                         udev.device_changed(pinnacle, node);
@@ -536,6 +834,7 @@ pub fn setup_udev(
                                         &backend.drm,
                                         crtc,
                                         surface.previous_gamma.clone(),
+                                        surface.night_light,
                                     ) {
                                         warn!("Failed to reset gamma: {err}");
                                         surface.previous_gamma = None;
@@ -547,6 +846,7 @@ pub fn setup_udev(
                                         &backend.drm,
                                         crtc,
                                         None::<[&[u16]; 3]>,
+                                        surface.night_light,
                                     ) {
                                         warn!("Failed to reset gamma: {err}");
                                     }
@@ -558,6 +858,7 @@ pub fn setup_udev(
                                         &backend.drm,
                                         crtc,
                                         Some([&gamma[0], &gamma[1], &gamma[2]]),
+                                        surface.night_light,
                                     ) {
                                         Ok(()) => {
                                             surface.previous_gamma = Some(gamma);
@@ -584,6 +885,9 @@ pub fn setup_udev(
                         }
                     }
 
+                    // The other session may have toggled lock keys
+                    state.update_keyboard_leds();
+
                     for output in state.pinnacle.space.outputs().cloned().collect::<Vec<_>>() {
                         state.schedule_render(&output);
                     }
@@ -592,9 +896,12 @@ pub fn setup_udev(
         })
         .expect("failed to insert libinput notifier into event loop");
 
+    // Unlike the headless and nested backends, this has no pixman fallback,
+    // as both the gpu manager and drm compositors are built on GBM and GLES.
     state.pinnacle.shm_state.update_formats(
         udev.gpu_manager
-            .single_renderer(&primary_gpu)?
+            .single_renderer(&primary_gpu)
+            .context("failed to initialize GLES on the primary gpu")?
             .shm_formats(),
     );
 
@@ -634,7 +941,8 @@ pub fn setup_udev(
         info!("No vulkan allocator found, using GBM.");
         let gbm = udev
             .backends
-            .get(&primary_gpu)
+            .values()
+            .find(|backend| backend.render_node == primary_gpu)
             // If the primary_gpu failed to initialize, we likely have a kmsro device
             .or_else(|| udev.backends.values().next())
             // Don't fail, if there is no allocator. There is a chance, that this a single gpu system and we don't need one.
@@ -669,6 +977,29 @@ pub fn setup_udev(
         .create_global_with_default_feedback::<State>(&display_handle, &default_feedback);
     udev.dmabuf_state = Some((dmabuf_state, global));
 
+    // Explicit sync needs syncobj timeline support from the primary gpu
+    if let Some(device) = udev
+        .backends
+        .values()
+        .find(|backend| backend.render_node == primary_gpu)
+        .map(|backend| backend.drm.device_fd().clone())
+    {
+        if device
+            .get_driver_capability(DriverCapability::SyncObjTimeline)
+            .is_ok_and(|cap| cap != 0)
+            && drm_syncobj::supports_eventfd(&device)
+        {
+            info!("Enabling explicit sync");
+            state.pinnacle.drm_syncobj_state = Some(DrmSyncobjState::new::<State, _>(
+                &display_handle,
+                device,
+                |_| true,
+            ));
+        } else {
+            info!("Primary gpu doesn't support syncobj timeline eventfds, disabling explicit sync");
+        }
+    }
+
     let gpu_manager = &mut udev.gpu_manager;
     udev.backends.values_mut().for_each(|backend_data| {
         // Update the per drm surface dmabuf feedback
@@ -705,6 +1036,14 @@ struct UdevBackendData {
     drm_scanner: DrmScanner,
     render_node: DrmNode,
     registration_token: RegistrationToken,
+    /// The DRM lease global for this device, if it could be created.
+    lease_state: Option<DrmLeaseState>,
+    /// Connectors that are marked as non-desktop, like VR headsets, along with their crtcs.
+    ///
+    /// These aren't turned into outputs and are instead offered to clients for leasing.
+    non_desktop_connectors: Vec<(connector::Handle, crtc::Handle)>,
+    /// Leases that have been granted to clients.
+    active_leases: Vec<DrmLease>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -802,12 +1141,31 @@ enum RenderState {
         #[allow(dead_code)] // TODO:
         Idle<'static>,
     ),
+    /// A render will be queued once it is late enough to finish right before
+    /// the next vblank.
+    Delayed {
+        /// The timer that queues the render.
+        #[allow(dead_code)]
+        token: RegistrationToken,
+    },
     /// A frame was rendered and scheduled and we are waiting for vblank.
     WaitingForVblank {
         /// A render was scheduled while waiting for vblank.
         /// In this case, another render will be scheduled once vblank happens.
         dirty: bool,
     },
+    /// A render had no damage so nothing was queued. We are waiting for when
+    /// vblank would have happened before rendering again.
+    ///
+    /// This keeps clients that commit without damage from having the output
+    /// rerender as fast as possible.
+    WaitingForEstimatedVblank {
+        /// The timer that fires at the estimated vblank.
+        #[allow(dead_code)]
+        token: RegistrationToken,
+        /// A render was scheduled while waiting.
+        dirty: bool,
+    },
 }
 
 /// Render surface for an output.
@@ -828,9 +1186,32 @@ struct RenderSurface {
     dmabuf_feedback: Option<DrmSurfaceDmabufFeedback>,
     render_state: RenderState,
     screencopy_commit_state: ScreencopyCommitState,
+    /// The swapchain commit the last frame's damage was recorded at for the frame stats.
+    frame_stats_commit: Option<CommitCounter>,
+    /// When the last vblank happened, used to delay renders according to the
+    /// output's [`MaxRenderTime`][crate::output::MaxRenderTime].
+    last_vblank: Option<Instant>,
 
     previous_gamma: Option<[Box<[u16]>; 3]>,
     pending_gamma_change: PendingGammaChange,
+    /// The whitepoint of the output's night light, applied on top of the gamma.
+    night_light: Option<[f64; 3]>,
+
+    /// Release points of the buffers the queued frame was rendered from.
+    queued_release_points: Vec<Arc<DrmReleasePoint>>,
+    /// Release points of the buffers the frame on screen was rendered from.
+    ///
+    /// Scanned out buffers are read from until the next frame replaces them,
+    /// so these are only signaled once that frame is presented.
+    presented_release_points: Vec<Arc<DrmReleasePoint>>,
+
+    /// Whether the connector supports variable refresh rate.
+    vrr_capable: bool,
+    /// Whether `VRR_ENABLED` is currently set on the crtc.
+    vrr_enabled: bool,
+    /// Whether the cursor is composited onto the primary plane
+    /// because the cursor plane rejected a frame.
+    composite_cursor: bool,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -929,17 +1310,34 @@ impl Udev {
             .expect("failed to insert drm notifier into event loop");
 
         // SAFETY: no clue lol just copied this from anvil
-        let render_node = EGLDevice::device_for_display(&unsafe {
-            EGLDisplay::new(gbm.clone()).expect("failed to create EGLDisplay")
-        })
-        .ok()
-        .and_then(|x| x.try_get_render_node().ok().flatten())
-        .unwrap_or(node);
+        let render_node = unsafe { EGLDisplay::new(gbm.clone()) }
+            .ok()
+            .and_then(|display| EGLDevice::device_for_display(&display).ok())
+            .and_then(|x| x.try_get_render_node().ok().flatten())
+            .unwrap_or(node);
+
+        let render_node = match self.gpu_manager.as_mut().add_node(render_node, gbm.clone()) {
+            Ok(()) => render_node,
+            Err(err) if render_node == self.primary_gpu => {
+                return Err(DeviceAddError::AddNode(err));
+            }
+            // Display-only devices like DisplayLink docks can't render,
+            // so the primary gpu renders for them and the result is copied over.
+            Err(err) => {
+                warn!(
+                    "Failed to add {render_node} to the gpu manager, \
+                    rendering with the primary gpu instead: {err}"
+                );
+                self.primary_gpu
+            }
+        };
 
-        self.gpu_manager
-            .as_mut()
-            .add_node(render_node, gbm.clone())
-            .map_err(DeviceAddError::AddNode)?;
+        // The device is back, so clients of the old lease state can let go of it
+        self.retired_lease_states.remove(&node);
+
+        let lease_state = DrmLeaseState::new::<State>(&self.display_handle, &node)
+            .map_err(|err| warn!("Failed to initialize drm lease global for {node}: {err}"))
+            .ok();
 
         self.backends.insert(
             node,
@@ -950,6 +1348,9 @@ impl Udev {
                 drm_scanner: DrmScanner::new(),
                 render_node,
                 surfaces: HashMap::new(),
+                lease_state,
+                non_desktop_connectors: Vec::new(),
+                active_leases: Vec::new(),
             },
         );
 
@@ -972,10 +1373,13 @@ impl Udev {
             return;
         };
 
-        let mut renderer = self
-            .gpu_manager
-            .single_renderer(&device.render_node)
-            .expect("failed to get primary gpu MultiRenderer");
+        let mut renderer = match self.gpu_manager.single_renderer(&device.render_node) {
+            Ok(renderer) => renderer,
+            Err(err) => {
+                warn!("Failed to get renderer for {}: {err}", device.render_node);
+                return;
+            }
+        };
         let render_formats = renderer
             .as_mut()
             .egl_context()
@@ -998,17 +1402,6 @@ impl Udev {
         let drm_mode = connector.modes()[mode_id];
         let wl_mode = smithay::output::Mode::from(drm_mode);
 
-        let surface = match device
-            .drm
-            .create_surface(crtc, drm_mode, &[connector.handle()])
-        {
-            Ok(surface) => surface,
-            Err(err) => {
-                warn!("Failed to create drm surface: {}", err);
-                return;
-            }
-        };
-
         let output_name = format!(
             "{}-{}",
             connector.interface().as_str(),
@@ -1022,6 +1415,32 @@ impl Udev {
                 ("Unknown".into(), "Unknown".into(), None)
             });
 
+        if is_non_desktop(&device.drm, connector.handle()) {
+            info!("Connector {output_name} is non-desktop, setting up for leasing");
+            device
+                .non_desktop_connectors
+                .push((connector.handle(), crtc));
+            if let Some(lease_state) = device.lease_state.as_mut() {
+                lease_state.add_connector::<State>(
+                    connector.handle(),
+                    output_name,
+                    format!("{make} {model}"),
+                );
+            }
+            return;
+        }
+
+        let surface = match device
+            .drm
+            .create_surface(crtc, drm_mode, &[connector.handle()])
+        {
+            Ok(surface) => surface,
+            Err(err) => {
+                warn!("Failed to create drm surface: {}", err);
+                return;
+            }
+        };
+
         let (phys_w, phys_h) = connector.size().unwrap_or((0, 0));
 
         if pinnacle.space.outputs().any(|op| {
@@ -1045,6 +1464,9 @@ impl Udev {
 
         output.with_state_mut(|state| state.serial = serial);
 
+        for mode in connector.modes() {
+            output.add_mode(smithay::output::Mode::from(*mode));
+        }
         output.set_preferred(wl_mode);
 
         pinnacle.output_focus_stack.set_focus(output.clone());
@@ -1075,12 +1497,10 @@ impl Udev {
         };
 
         let compositor = {
-            let mut planes = surface.planes().clone();
-
-            // INFO: We are disabling overlay planes because it seems that any elements on
-            // |     overlay planes don't get up/downscaled according to the set filter;
-            // |     it always defaults to linear.
-            planes.overlay.clear();
+            // INFO: Elements on overlay planes are scaled by the display hardware, which
+            // |     ignores the set up/downscale filter, so only video surfaces are marked as
+            // |     candidates for them. See `AsRenderElements for WindowElement`.
+            let planes = surface.planes().clone();
 
             match DrmCompositor::new(
                 &output,
@@ -1117,25 +1537,63 @@ impl Udev {
             dmabuf_feedback,
             render_state: RenderState::Idle,
             screencopy_commit_state: ScreencopyCommitState::default(),
+            frame_stats_commit: None,
+            last_vblank: None,
             previous_gamma: None,
             pending_gamma_change: PendingGammaChange::Idle,
+            night_light: None,
+            queued_release_points: Vec::new(),
+            presented_release_points: Vec::new(),
+            vrr_capable: is_vrr_capable(&device.drm, connector.handle()),
+            vrr_enabled: false,
+            composite_cursor: false,
         };
 
         device.surfaces.insert(crtc, surface);
 
         pinnacle.change_output_state(&output, Some(wl_mode), None, None, Some(position));
 
-        // If there is saved connector state, the connector was previously plugged in.
+        // If there is saved connector state, the monitor was previously plugged in.
         // In this case, restore its tags and location.
-        // TODO: instead of checking the connector, check the monitor's edid info instead
-        if let Some(saved_state) = pinnacle
-            .config
-            .connector_saved_states
-            .get(&OutputName(output.name()))
-        {
-            let ConnectorSavedState { loc, tags, scale } = saved_state;
-            output.with_state_mut(|state| state.tags = tags.clone());
-            pinnacle.change_output_state(&output, None, None, *scale, Some(*loc));
+        let monitor = MonitorId::from_output(&output);
+        let saved_states = &mut pinnacle.config.connector_saved_states;
+        let saved_state = saved_states
+            .iter()
+            .find(|(_, saved)| saved.monitor.as_ref().is_some_and(|m| m.matches(&monitor)))
+            .map(|(name, _)| name.clone())
+            // The monitor's state is restored once; it's saved again on the next disconnect
+            .and_then(|name| saved_states.remove(&name))
+            .or_else(|| {
+                saved_states
+                    .get(&OutputName(output.name()))
+                    .filter(|saved| saved.monitor.is_none())
+                    .cloned()
+            });
+
+        if let Some(saved_state) = saved_state {
+            let ConnectorSavedState {
+                loc,
+                tags,
+                scale,
+                transform,
+                tags_migrated,
+                ..
+            } = saved_state;
+
+            // Take back tags and their windows that were moved away on disconnect
+            let tags = if tags_migrated {
+                pinnacle.reclaim_tags(&tags)
+            } else {
+                tags
+            };
+            output.with_state_mut(|state| state.tags = tags);
+            pinnacle.change_output_state(&output, None, transform, scale, Some(loc));
+
+            if tags_migrated {
+                for op in pinnacle.space.outputs().cloned().collect::<Vec<_>>() {
+                    pinnacle.request_layout(&op);
+                }
+            }
         } else {
             pinnacle.signal_state.output_connect.signal(|buffer| {
                 buffer.push_back(OutputConnectResponse {
@@ -1143,6 +1601,8 @@ impl Udev {
                 })
             });
         }
+
+        pinnacle.queue_outputs_changed();
     }
 
     /// A display was unplugged.
@@ -1150,7 +1610,7 @@ impl Udev {
         &mut self,
         pinnacle: &mut Pinnacle,
         node: DrmNode,
-        _connector: connector::Info,
+        connector: connector::Info,
         crtc: crtc::Handle,
     ) {
         tracing::debug!(?crtc, "connector_disconnected");
@@ -1161,11 +1621,24 @@ impl Udev {
             return;
         };
 
+        if let Some(pos) = device
+            .non_desktop_connectors
+            .iter()
+            .position(|(handle, _)| *handle == connector.handle())
+        {
+            device.non_desktop_connectors.remove(pos);
+            if let Some(lease_state) = device.lease_state.as_mut() {
+                lease_state.withdraw_connector(connector.handle());
+            }
+            return;
+        }
+
         device.surfaces.remove(&crtc);
 
         let output = pinnacle
             .space
             .outputs()
+            .chain(pinnacle.disabled_outputs.iter())
             .find(|o| {
                 o.user_data()
                     .get::<UdevOutputData>()
@@ -1175,13 +1648,51 @@ impl Udev {
             .cloned();
 
         if let Some(output) = output {
+            // A disabled output has no layout state to save and comes back
+            // enabled when replugged, but it should still get its tags back
+            if !output.with_state(|state| state.enabled) {
+                pinnacle.config.connector_saved_states.insert(
+                    OutputName(output.name()),
+                    ConnectorSavedState {
+                        monitor: Some(MonitorId::from_output(&output)),
+                        loc: output.current_location(),
+                        tags: output.with_state(|state| state.migrated_tags.clone()),
+                        scale: Some(output.current_scale()),
+                        transform: Some(output.current_transform()),
+                        tags_migrated: true,
+                    },
+                );
+
+                pinnacle.disabled_outputs.retain(|op| op != &output);
+                pinnacle.gamma_control_manager_state.output_removed(&output);
+                pinnacle.signal_state.output_disconnect.signal(|buffer| {
+                    buffer.push_back(OutputDisconnectResponse {
+                        output_name: Some(output.name()),
+                    })
+                });
+                pinnacle.queue_outputs_changed();
+                return;
+            }
+
+            // Move this output's tags and windows somewhere visible until it comes back
+            let (tags, tags_migrated) = match pinnacle.migrate_tags(&output) {
+                Some((target, tags)) => {
+                    pinnacle.request_layout(&target);
+                    (tags, true)
+                }
+                None => (output.with_state(|state| state.tags.clone()), false),
+            };
+
             // Save this output's state. It will be restored if the monitor gets replugged.
             pinnacle.config.connector_saved_states.insert(
                 OutputName(output.name()),
                 ConnectorSavedState {
+                    monitor: Some(MonitorId::from_output(&output)),
                     loc: output.current_location(),
-                    tags: output.with_state(|state| state.tags.clone()),
+                    tags,
                     scale: Some(output.current_scale()),
+                    transform: Some(output.current_transform()),
+                    tags_migrated,
                 },
             );
 
@@ -1198,6 +1709,8 @@ impl Udev {
                     output_name: Some(output.name()),
                 })
             });
+
+            pinnacle.queue_outputs_changed();
         }
     }
 
@@ -1246,10 +1759,23 @@ impl Udev {
         tracing::debug!("Surfaces dropped");
 
         // drop the backends on this side
-        if let Some(backend_data) = self.backends.remove(&node) {
-            self.gpu_manager
-                .as_mut()
-                .remove_node(&backend_data.render_node);
+        if let Some(mut backend_data) = self.backends.remove(&node) {
+            if let Some(mut lease_state) = backend_data.lease_state.take() {
+                lease_state.disable_global::<State>();
+                lease_state.suspend();
+                self.retired_lease_states.insert(node, lease_state);
+            }
+
+            // Devices that can't render share the primary gpu's render node
+            if !self
+                .backends
+                .values()
+                .any(|backend| backend.render_node == backend_data.render_node)
+            {
+                self.gpu_manager
+                    .as_mut()
+                    .remove_node(&backend_data.render_node);
+            }
 
             pinnacle.loop_handle.remove(backend_data.registration_token);
 
@@ -1257,6 +1783,129 @@ impl Udev {
         }
     }
 
+    /// Recreate every device rendered by `render_node` once the event loop is idle.
+    ///
+    /// This is used when a GPU reset caused its rendering context to be lost.
+    fn schedule_gpu_recovery(
+        &mut self,
+        loop_handle: &LoopHandle<'static, State>,
+        render_node: DrmNode,
+    ) {
+        // Every output on the GPU will report the lost context
+        if !self.pending_gpu_recoveries.insert(render_node) {
+            return;
+        }
+
+        loop_handle.insert_idle(move |state| {
+            let udev = state.backend.udev_mut();
+            udev.pending_gpu_recoveries.remove(&render_node);
+
+            warn!("Recreating devices rendered by {render_node} after a GPU reset");
+
+            let nodes = udev
+                .backends
+                .iter()
+                .filter(|(_, backend)| backend.render_node == render_node)
+                .map(|(node, _)| *node)
+                .collect();
+            udev.readd_devices(&mut state.pinnacle, nodes);
+        });
+    }
+
+    /// Switch to another GPU after the primary one was removed.
+    fn replace_primary_gpu(&mut self, pinnacle: &mut Pinnacle) {
+        let Some(new_primary_gpu) = self
+            .backends
+            .keys()
+            .find_map(|node| node.node_with_type(NodeType::Render)?.ok())
+        else {
+            error!("The primary gpu was removed and there is no other gpu to render with");
+            return;
+        };
+
+        info!("The primary gpu was removed, using {new_primary_gpu} as the primary gpu");
+
+        self.primary_gpu = new_primary_gpu;
+
+        // Display-only devices were rendered by the old primary gpu
+        let nodes = self.backends.keys().copied().collect();
+        self.readd_devices(pinnacle, nodes);
+
+        let mut renderer = match self.gpu_manager.single_renderer(&new_primary_gpu) {
+            Ok(renderer) => renderer,
+            Err(err) => {
+                error!("Failed to create renderer for the new primary gpu: {err}");
+                return;
+            }
+        };
+
+        pinnacle.shm_state.update_formats(renderer.shm_formats());
+
+        // Advertise the new gpu to clients so they allocate buffers on it
+        let dmabuf_formats = renderer.dmabuf_formats().collect::<Vec<_>>();
+        let default_feedback = DmabufFeedbackBuilder::new(new_primary_gpu.dev_id(), dmabuf_formats)
+            .build()
+            .expect("failed to create dmabuf feedback");
+
+        if let Some((dmabuf_state, global)) = self.dmabuf_state.as_mut() {
+            let new_global = dmabuf_state.create_global_with_default_feedback::<State>(
+                &self.display_handle,
+                &default_feedback,
+            );
+            let old_global = std::mem::replace(global, new_global);
+            dmabuf_state.destroy_global::<State>(&self.display_handle, old_global);
+        }
+    }
+
+    /// Remove the given devices and add them back, recreating their renderers and outputs.
+    fn readd_devices(&mut self, pinnacle: &mut Pinnacle, mut nodes: Vec<DrmNode>) {
+        let device_paths = self
+            .udev_dispatcher
+            .as_source_ref()
+            .device_list()
+            .flat_map(|(id, path)| Some((DrmNode::from_dev_id(id).ok()?, path.to_path_buf())))
+            .collect::<HashMap<_, _>>();
+
+        for node in nodes.iter() {
+            self.device_removed(pinnacle, *node);
+        }
+
+        // Textures imported with the old renderers are no longer valid
+        self.pointer_images.clear();
+
+        let primary_gpu = self.primary_gpu;
+        let is_primary_gpu = |node: &DrmNode| {
+            *node == primary_gpu
+                || node.node_with_type(NodeType::Render).and_then(Result::ok) == Some(primary_gpu)
+        };
+
+        // Display-only devices fall back to the primary gpu, so it needs to be added first
+        nodes.sort_by_key(|node| !is_primary_gpu(node));
+
+        let readds_primary_gpu = nodes.first().is_some_and(is_primary_gpu);
+
+        for node in nodes {
+            // The device may have been unplugged in the meantime
+            let Some(path) = device_paths.get(&node) else {
+                continue;
+            };
+            if let Err(err) = self.device_added(pinnacle, node, path) {
+                error!("Failed to recreate device {node}: {err}");
+            }
+        }
+
+        if readds_primary_gpu {
+            match self.gpu_manager.single_renderer(&primary_gpu) {
+                Ok(mut renderer) => {
+                    if let Err(err) = renderer.bind_wl_display(&self.display_handle) {
+                        warn!("Failed to rebind EGL display: {err}");
+                    }
+                }
+                Err(err) => error!("Failed to create renderer for the primary gpu: {err}"),
+            }
+        }
+    }
+
     /// Mark [`OutputPresentationFeedback`]s as presented and schedule a new render on idle.
     fn on_vblank(
         &mut self,
@@ -1284,6 +1933,11 @@ impl Udev {
             return;
         };
 
+        surface.last_vblank = Some(Instant::now());
+
+        // Buffers only used by the previous frame are released once nothing else holds them
+        surface.presented_release_points = std::mem::take(&mut surface.queued_release_points);
+
         match surface
             .compositor
             .frame_submitted()
@@ -1325,13 +1979,18 @@ impl Udev {
             Err(err) => {
                 warn!("Error during rendering: {:?}", err);
                 if let SwapBuffersError::ContextLost(err) = err {
-                    panic!("Rendering loop lost: {}", err)
+                    error!("Rendering loop lost: {err}");
+                    let render_node = surface.render_node;
+                    self.schedule_gpu_recovery(&pinnacle.loop_handle, render_node);
+                    return;
                 }
             }
         };
 
+        // The render state is reset when the session is paused,
+        // so a vblank from before that may still show up here.
         let RenderState::WaitingForVblank { dirty } = surface.render_state else {
-            unreachable!();
+            return;
         };
 
         surface.render_state = RenderState::Idle;
@@ -1339,28 +1998,118 @@ impl Udev {
         if dirty {
             self.schedule_render(&pinnacle.loop_handle, &output);
         } else {
+            // Pace surfaces by the output they are mainly shown on, so each output's
+            // clients follow its own refresh rate. Surfaces that aren't shown anywhere
+            // still get a frame every second.
             for window in pinnacle.windows.iter() {
                 window.send_frame(
                     &output,
                     pinnacle.clock.now(),
-                    Some(Duration::ZERO),
-                    |_, _| Some(output.clone()),
+                    Some(Duration::from_secs(1)),
+                    surface_primary_scanout_output,
                 );
             }
         }
     }
 
+    /// Enable or disable VRR on the given output's crtc according to its [`Vrr`] setting.
+    fn update_vrr(&mut self, pinnacle: &Pinnacle, output: &Output) {
+        let Some(UdevOutputData { device_id, crtc }) = output.user_data().get() else {
+            return;
+        };
+        let Some(device) = self.backends.get_mut(device_id) else {
+            return;
+        };
+        let Some(surface) = device.surfaces.get_mut(crtc) else {
+            return;
+        };
+
+        if !surface.vrr_capable {
+            return;
+        }
+
+        let enable = match output.with_state(|state| state.vrr) {
+            Vrr::Off => false,
+            Vrr::On => true,
+            Vrr::OnDemand => fullscreen_scanout_window(output, &pinnacle.space)
+                .is_some_and(|win| win.is_game_or_video()),
+        };
+
+        if enable == surface.vrr_enabled {
+            return;
+        }
+
+        match set_vrr_enabled(&device.drm, *crtc, enable) {
+            Ok(()) => surface.vrr_enabled = enable,
+            Err(err) => warn!("Failed to set VRR on {}: {err}", output.name()),
+        }
+    }
+
     /// Render to the [`RenderSurface`] associated with the given `output`.
     #[tracing::instrument(level = "debug", skip(self, pinnacle), fields(output = output.name()))]
+    /// Stop waiting after a frame with no damage and render again if needed.
+    fn on_estimated_vblank(&mut self, pinnacle: &mut Pinnacle, output: &Output) {
+        let Some(surface) = render_surface_for_output(output, &mut self.backends) else {
+            return;
+        };
+
+        // The wait may have been cancelled by the session pausing or the output
+        // being turned off
+        let RenderState::WaitingForEstimatedVblank { dirty, .. } = surface.render_state else {
+            return;
+        };
+
+        surface.render_state = RenderState::Idle;
+
+        if dirty {
+            self.schedule_render(&pinnacle.loop_handle, output);
+        }
+    }
+
+    /// Queue a render that was delayed to finish right before vblank.
+    fn on_render_deadline(&mut self, loop_handle: &LoopHandle<'static, State>, output: &Output) {
+        let Some(surface) = render_surface_for_output(output, &mut self.backends) else {
+            return;
+        };
+
+        if !matches!(surface.render_state, RenderState::Delayed { .. }) {
+            return;
+        }
+
+        surface.render_state = RenderState::Idle;
+
+        // The deadline has passed, so this queues the render unless the session
+        // was paused or the output turned off in the meantime
+        self.schedule_render(loop_handle, output);
+    }
+
     fn render_surface(&mut self, pinnacle: &mut Pinnacle, output: &Output) {
+        self.update_vrr(pinnacle, output);
+
         let Some(surface) = render_surface_for_output(output, &mut self.backends) else {
             return;
         };
 
         assert!(matches!(surface.render_state, RenderState::Scheduled(_)));
 
+        let cursor_icon = match &pinnacle.cursor_status {
+            CursorImageStatus::Named(icon) => *icon,
+            _ => CursorIcon::Default,
+        };
+
         // TODO get scale from the rendersurface when supporting HiDPI
-        let frame = self.pointer_image.get_image(
+        let cursor_theme = (
+            pinnacle.cursor.theme_name().to_string(),
+            pinnacle.cursor.size(),
+        );
+        if self.pointer_images_theme != cursor_theme {
+            // The theme changed, so the old textures won't be used again
+            self.pointer_images.clear();
+            self.pointer_images_theme = cursor_theme;
+        }
+
+        let frame = pinnacle.cursor.get_image(
+            cursor_icon,
             1,
             // output.current_scale().integer_scale() as u32,
             pinnacle.clock.now().into(),
@@ -1368,14 +2117,20 @@ impl Udev {
 
         let render_node = surface.render_node;
         let primary_gpu = self.primary_gpu;
-        let mut renderer = if primary_gpu == render_node {
+        let mut renderer = match if primary_gpu == render_node {
             self.gpu_manager.single_renderer(&render_node)
         } else {
             let format = surface.compositor.format();
             self.gpu_manager
                 .renderer(&primary_gpu, &render_node, format)
-        }
-        .expect("failed to create MultiRenderer");
+        } {
+            Ok(renderer) => renderer,
+            Err(err) => {
+                warn!("Failed to create renderer for {}: {err}", output.name());
+                surface.render_state = RenderState::Idle;
+                return;
+            }
+        };
 
         let _ = renderer.upscale_filter(self.upscale_filter);
         let _ = renderer.downscale_filter(self.downscale_filter);
@@ -1408,6 +2163,7 @@ impl Udev {
                 texture
             });
 
+        pinnacle.update_window_animations();
         let windows = pinnacle.space.elements().cloned().collect::<Vec<_>>();
 
         let pointer_location = pinnacle
@@ -1443,45 +2199,39 @@ impl Udev {
 
         let mut output_render_elements = Vec::new();
 
+        output_render_elements.extend(perf_overlay_elements(&mut renderer, pinnacle, output));
+        output_render_elements.extend(window_switcher_elements(&mut renderer, pinnacle, output));
+        output_render_elements.extend(monocle_indicator_elements(&mut renderer, pinnacle, output));
+        output_render_elements.extend(snap_guide_elements(pinnacle, output));
+        output_render_elements.extend(edge_tile_preview_element(pinnacle, output));
+        output_render_elements
+            .extend(recording_indicator_element(pinnacle, output).map(OutputRenderElement::from));
+
         // If there isn't a pending screencopy that doesn't want to overlay the cursor,
         // render it.
-        match pending_screencopy_with_cursor {
-            Some(include_cursor) => {
-                if include_cursor {
-                    // HACK: Doing `RenderFrameResult::blit_frame_result` with something on the
-                    // |     cursor plane causes the cursor to overwrite the pixels underneath it,
-                    // |     leading to a transparent hole under the cursor.
-                    // |     To circumvent that, we set the cursor to render on the primary plane instead.
-                    // |     Unfortunately that means I can't composite the cursor separately from
-                    // |     the screencopy, meaning if you have an active screencopy recording
-                    // |     without cursor overlay then the cursor will dim/flicker out/disappear.
-                    self.pointer_element
-                        .set_element_kind(element::Kind::Unspecified);
-                    let pointer_render_elements = pointer_render_elements(
-                        output,
-                        &mut renderer,
-                        &pinnacle.space,
-                        pointer_location,
-                        &mut pinnacle.cursor_status,
-                        pinnacle.dnd_icon.as_ref(),
-                        &self.pointer_element,
-                    );
-                    self.pointer_element.set_element_kind(element::Kind::Cursor);
-                    output_render_elements.extend(pointer_render_elements);
-                }
-            }
-            None => {
-                let pointer_render_elements = pointer_render_elements(
-                    output,
-                    &mut renderer,
-                    &pinnacle.space,
-                    pointer_location,
-                    &mut pinnacle.cursor_status,
-                    pinnacle.dnd_icon.as_ref(),
-                    &self.pointer_element,
-                );
-                output_render_elements.extend(pointer_render_elements);
+        if pending_screencopy_with_cursor != Some(false) {
+            // HACK: Doing `RenderFrameResult::blit_frame_result` with something on the
+            // |     cursor plane causes the cursor to overwrite the pixels underneath it,
+            // |     leading to a transparent hole under the cursor.
+            // |     To circumvent that, we set the cursor to render on the primary plane instead.
+            // |     Unfortunately that means I can't composite the cursor separately from
+            // |     the screencopy, meaning if you have an active screencopy recording
+            // |     without cursor overlay then the cursor will dim/flicker out/disappear.
+            if pending_screencopy_with_cursor == Some(true) || surface.composite_cursor {
+                self.pointer_element
+                    .set_element_kind(element::Kind::Unspecified);
             }
+            let pointer_render_elements = pointer_render_elements(
+                output,
+                &mut renderer,
+                &pinnacle.space,
+                pointer_location,
+                &mut pinnacle.cursor_status,
+                pinnacle.dnd_icon.as_ref(),
+                &self.pointer_element,
+            );
+            self.pointer_element.set_element_kind(element::Kind::Cursor);
+            output_render_elements.extend(pointer_render_elements);
         }
 
         output_render_elements.extend(crate::render::output_render_elements(
@@ -1489,9 +2239,33 @@ impl Udev {
             &mut renderer,
             &pinnacle.space,
             &windows,
+            &pinnacle.config.render_settings,
         ));
 
+        let damage_debug_elements =
+            damage_debug_elements(pinnacle, output, &output_render_elements);
+        output_render_elements.splice(0..0, damage_debug_elements);
+
+        // Keep rendering until all animations and damage flashes have finished
+        let animating = windows.iter().any(|win| win.is_animating())
+            || output.with_state(|state| {
+                state
+                    .damage_debug
+                    .as_ref()
+                    .is_some_and(|debug| debug.is_flashing())
+            });
+
+        // `RenderFrameResult::blit_frame_result` leaves holes where elements were put on
+        // overlay planes, so keep everything composited while a screencopy is pending.
+        surface
+            .compositor
+            .use_direct_scanout(pending_screencopy_with_cursor.is_none());
+
+        let mut cursor_on_plane = false;
+
         let result = (|| -> Result<bool, SwapBuffersError> {
+            let render_start = Instant::now();
+
             let render_frame_result = render_frame(
                 &mut surface.compositor,
                 &mut renderer,
@@ -1499,11 +2273,8 @@ impl Udev {
                 [0.6, 0.6, 0.6, 1.0],
             )?;
 
-            if let PrimaryPlaneElement::Swapchain(element) = &render_frame_result.primary_element {
-                if let Err(err) = element.sync.wait() {
-                    warn!("Failed to wait for sync point: {err}");
-                }
-            }
+            // The frame isn't waited on here. The kernel waits for the GPU to finish it
+            // before flipping, so a slow frame doesn't hold up rendering other outputs.
 
             handle_pending_screencopy(
                 &mut renderer,
@@ -1529,8 +2300,32 @@ impl Udev {
             );
 
             let rendered = !render_frame_result.is_empty;
+            cursor_on_plane = render_frame_result.cursor_element.is_some();
 
             if rendered {
+                let (scanout, damage, sync) = match &render_frame_result.primary_element {
+                    PrimaryPlaneElement::Swapchain(element) => {
+                        let damage = element.damage.damage_since(surface.frame_stats_commit);
+                        surface.frame_stats_commit = Some(element.damage.current_commit());
+                        let size = output
+                            .current_mode()
+                            .map(|mode| Size::<i32, Buffer>::from((mode.size.w, mode.size.h)))
+                            .unwrap_or_default();
+                        let damage = damage.map_or(1.0, |damage| damage_fraction(&*damage, size));
+                        (false, damage, Some(element.sync.clone()))
+                    }
+                    // A scanned out buffer replaces the whole frame
+                    PrimaryPlaneElement::Element(_) => (true, 1.0, None),
+                };
+                record_frame_stats(
+                    &pinnacle.loop_handle,
+                    output,
+                    render_start,
+                    sync,
+                    scanout,
+                    damage,
+                );
+
                 let output_presentation_feedback = take_presentation_feedback(
                     output,
                     &pinnacle.space,
@@ -1541,18 +2336,155 @@ impl Udev {
                     .compositor
                     .queue_frame(Some(output_presentation_feedback))
                     .map_err(SwapBuffersError::from)?;
+
+                surface.queued_release_points =
+                    release_points(output, &pinnacle.space, &render_frame_result.states);
             }
 
             Ok(rendered)
         })();
 
         match result {
-            Ok(true) => surface.render_state = RenderState::WaitingForVblank { dirty: false },
-            Ok(false) | Err(_) => surface.render_state = RenderState::Idle,
+            Ok(true) => surface.render_state = RenderState::WaitingForVblank { dirty: animating },
+            Ok(false) => {
+                let max_fps = output.with_state(|state| state.max_fps);
+                let frame_duration = output
+                    .current_mode()
+                    .filter(|mode| mode.refresh > 0)
+                    .map(|mode| {
+                        Duration::from_secs_f64(1000.0 / mode.refresh as f64)
+                            * refreshes_per_frame(mode.refresh, max_fps)
+                    })
+                    .unwrap_or(Duration::from_micros(16_667));
+
+                let op = output.clone();
+                let token = pinnacle.loop_handle.insert_source(
+                    Timer::from_duration(frame_duration),
+                    move |_, _, state| {
+                        state
+                            .backend
+                            .udev_mut()
+                            .on_estimated_vblank(&mut state.pinnacle, &op);
+                        TimeoutAction::Drop
+                    },
+                );
+
+                surface.render_state = match token {
+                    Ok(token) => RenderState::WaitingForEstimatedVblank {
+                        token,
+                        dirty: animating,
+                    },
+                    Err(_) => RenderState::Idle,
+                };
+            }
+            Err(SwapBuffersError::ContextLost(err)) => {
+                surface.render_state = RenderState::Idle;
+                error!("Lost the rendering context of {}: {err}", output.name());
+                self.schedule_gpu_recovery(&pinnacle.loop_handle, render_node);
+            }
+            Err(err) => {
+                surface.render_state = RenderState::Idle;
+
+                // Some drivers only reject the cursor plane's buffer on commit,
+                // so draw the cursor on the primary plane from now on and try again.
+                if cursor_on_plane && !surface.composite_cursor {
+                    warn!(
+                        "Failed to queue frame on {} with a hardware cursor, \
+                        falling back to a composited cursor: {err}",
+                        output.name()
+                    );
+                    surface.composite_cursor = true;
+                    self.schedule_render(&pinnacle.loop_handle, output);
+                }
+            }
         }
     }
 }
 
+/// Record a rendered frame in `output`'s frame stats once the GPU has finished it.
+///
+/// The frame's sync point is waited on in the event loop instead of blocking on it.
+fn record_frame_stats(
+    loop_handle: &LoopHandle<'static, State>,
+    output: &Output,
+    render_start: Instant,
+    sync: Option<SyncPoint>,
+    scanout: bool,
+    damage: f64,
+) {
+    let record = move |output: &Output| {
+        let now = Instant::now();
+        output.with_state_mut(|state| {
+            state
+                .frame_stats
+                .record(now, now.duration_since(render_start), scanout, damage)
+        });
+    };
+
+    let Some(sync_fd) = sync
+        .filter(|sync| !sync.is_reached())
+        .and_then(|sync| sync.export())
+    else {
+        record(output);
+        return;
+    };
+
+    let output = output.clone();
+    let source = Generic::new(sync_fd, Interest::READ, calloop::Mode::OneShot);
+    let res = loop_handle.insert_source(source, move |_, _, _| {
+        record(&output);
+        Ok(PostAction::Remove)
+    });
+    if res.is_err() {
+        warn!("Failed to wait for frame to finish rendering");
+    }
+}
+
+/// Queue a render of `output` for when the event loop is idle.
+fn queue_render(loop_handle: &LoopHandle<State>, output: &Output) -> RenderState {
+    let output = output.clone();
+    let token = loop_handle.insert_idle(move |state| {
+        state
+            .backend
+            .udev_mut()
+            .render_surface(&mut state.pinnacle, &output);
+    });
+
+    RenderState::Scheduled(token)
+}
+
+/// Get how long to wait before rendering `output` so the frame finishes right before
+/// the vblank it should be shown at, according to its
+/// [`MaxRenderTime`][crate::output::MaxRenderTime] and frame rate cap.
+///
+/// Returns `None` if it should render right away.
+fn render_delay(output: &Output, surface: &RenderSurface) -> Option<Duration> {
+    let last_vblank = surface.last_vblank?;
+    let (max_render_time, max_fps) = output.with_state(|state| {
+        (
+            state.max_render_time.render_time(&state.frame_stats),
+            state.max_fps,
+        )
+    });
+
+    let render_start = if surface.vrr_enabled {
+        // With VRR the next vblank waits for the frame, so only the cap applies
+        last_vblank + Duration::from_secs_f64(1.0 / f64::from(max_fps?.get()))
+    } else {
+        let mode = output.current_mode().filter(|mode| mode.refresh > 0)?;
+        let refresh_interval = Duration::from_secs_f64(1000.0 / mode.refresh as f64);
+        let next_vblank =
+            last_vblank + refresh_interval * refreshes_per_frame(mode.refresh, max_fps);
+        // Without a max render time, render right after the vblank before the one
+        // the frame should be shown at
+        next_vblank.checked_sub(max_render_time.unwrap_or(refresh_interval))?
+    };
+
+    let delay = render_start.checked_duration_since(Instant::now())?;
+
+    (!delay.is_zero()).then_some(delay)
+}
+
 fn render_surface_for_output<'a>(
     output: &Output,
     backends: &'a mut HashMap<DrmNode, UdevBackendData>,
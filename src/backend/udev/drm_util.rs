@@ -1,6 +1,11 @@
 use std::num::NonZeroU32;
 
-use smithay::reexports::drm::control::{connector, property, Device, ResourceHandle};
+use drm_ffi::drm_mode_modeinfo;
+use smithay::reexports::drm::control::{
+    self, connector, crtc, property, Device, ModeFlags, ModeTypeFlags, ResourceHandle,
+};
+
+use crate::output::Modeline;
 
 // A bunch of this stuff is from cosmic-comp
 
@@ -109,6 +114,100 @@ fn parse_edid(buffer: &[u8]) -> anyhow::Result<EdidInfo> {
     })
 }
 
+/// Returns whether the given connector has its `non-desktop` property set.
+///
+/// Non-desktop connectors, like VR headsets, shouldn't be used as outputs. Instead, they should be
+/// leased out to clients through the DRM lease protocol.
+pub fn is_non_desktop(device: &impl Device, connector: connector::Handle) -> bool {
+    let Ok(prop) = get_prop(device, connector, "non-desktop") else {
+        return false;
+    };
+    let Ok(info) = device.get_property(prop) else {
+        return false;
+    };
+    let Ok(props) = device.get_properties(connector) else {
+        return false;
+    };
+
+    let (ids, vals) = props.as_props_and_values();
+    ids.iter()
+        .zip(vals.iter())
+        .find(|(&id, _)| id == prop)
+        .and_then(|(_, &val)| info.value_type().convert_value(val).as_boolean())
+        .unwrap_or(false)
+}
+
+/// Returns whether the given connector supports variable refresh rate.
+pub fn is_vrr_capable(device: &impl Device, connector: connector::Handle) -> bool {
+    let Ok(prop) = get_prop(device, connector, "vrr_capable") else {
+        return false;
+    };
+    let Ok(info) = device.get_property(prop) else {
+        return false;
+    };
+    let Ok(props) = device.get_properties(connector) else {
+        return false;
+    };
+
+    let (ids, vals) = props.as_props_and_values();
+    ids.iter()
+        .zip(vals.iter())
+        .find(|(&id, _)| id == prop)
+        .and_then(|(_, &val)| info.value_type().convert_value(val).as_boolean())
+        .unwrap_or(false)
+}
+
+/// Set the `VRR_ENABLED` property on the given crtc.
+pub fn set_vrr_enabled(
+    device: &impl Device,
+    crtc: crtc::Handle,
+    enabled: bool,
+) -> anyhow::Result<()> {
+    let prop = get_prop(device, crtc, "VRR_ENABLED")?;
+    device.set_property(crtc, prop, property::Value::Boolean(enabled).into())?;
+    Ok(())
+}
+
+/// Create a drm mode from a custom modeline.
+pub fn create_drm_mode(modeline: &Modeline) -> control::Mode {
+    let mut flags = ModeFlags::empty();
+    flags |= if modeline.hsync_pos {
+        ModeFlags::PHSYNC
+    } else {
+        ModeFlags::NHSYNC
+    };
+    flags |= if modeline.vsync_pos {
+        ModeFlags::PVSYNC
+    } else {
+        ModeFlags::NVSYNC
+    };
+
+    let mut name = [0; 32];
+    let name_str = format!("{}x{}", modeline.hdisplay, modeline.vdisplay);
+    for (dst, src) in name.iter_mut().zip(name_str.bytes().take(31)) {
+        *dst = src as _;
+    }
+
+    drm_mode_modeinfo {
+        clock: modeline.clock.round() as u32,
+        hdisplay: modeline.hdisplay,
+        hsync_start: modeline.hsync_start,
+        hsync_end: modeline.hsync_end,
+        htotal: modeline.htotal,
+        hskew: 0,
+        vdisplay: modeline.vdisplay,
+        vsync_start: modeline.vsync_start,
+        vsync_end: modeline.vsync_end,
+        vtotal: modeline.vtotal,
+        vscan: 0,
+        vrefresh: (modeline.refresh_millihz() + 500) / 1000,
+        flags: flags.bits(),
+        type_: ModeTypeFlags::USERDEF.bits(),
+        name,
+    }
+    .into()
+}
+
 fn get_prop(
     device: &impl Device,
     handle: impl ResourceHandle,
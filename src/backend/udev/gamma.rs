@@ -25,6 +25,10 @@ impl Udev {
             return Ok(());
         }
 
+        let night_light = render_surface_for_output(output, &mut self.backends)
+            .context("no render surface for output")?
+            .night_light;
+
         let UdevOutputData { device_id, crtc } = output
             .user_data()
             .get()
@@ -36,7 +40,7 @@ impl Udev {
             .context("no udev backend data for output")?
             .drm;
 
-        let ret = Udev::set_gamma_internal(drm_device, crtc, gamma);
+        let ret = Udev::set_gamma_internal(drm_device, crtc, gamma, night_light);
 
         render_surface_for_output(output, &mut self.backends)
             .context("no render surface for output")?
@@ -48,10 +52,49 @@ impl Udev {
         ret
     }
 
+    /// Shift the output's gamma with a night light whitepoint from
+    /// [`whitepoint`][crate::night_light::whitepoint], or remove it with `None`.
+    ///
+    /// Gamma set by clients is kept and shifted as well.
+    pub fn set_night_light(
+        &mut self,
+        output: &Output,
+        whitepoint: Option<[f64; 3]>,
+    ) -> anyhow::Result<()> {
+        let surface = render_surface_for_output(output, &mut self.backends)
+            .context("no render surface for output")?;
+
+        if surface.night_light == whitepoint {
+            return Ok(());
+        }
+        surface.night_light = whitepoint;
+
+        // The pending gamma change applies the night light when the session is resumed
+        if !self.session.is_active() {
+            return Ok(());
+        }
+
+        let previous_gamma = surface.previous_gamma.clone();
+
+        let UdevOutputData { device_id, crtc } = output
+            .user_data()
+            .get()
+            .context("no udev output data for output")?;
+
+        let drm_device = &self
+            .backends
+            .get(device_id)
+            .context("no udev backend data for output")?
+            .drm;
+
+        Udev::set_gamma_internal(drm_device, crtc, previous_gamma, whitepoint)
+    }
+
     pub(super) fn set_gamma_internal(
         drm_device: &DrmDevice,
         crtc: &crtc::Handle,
         gamma: Option<[impl AsRef<[u16]>; 3]>,
+        night_light: Option<[f64; 3]>,
     ) -> anyhow::Result<()> {
         let gamma = gamma
             .as_ref()
@@ -95,7 +138,21 @@ impl Udev {
             }
         };
 
-        drm_device.set_gamma(*crtc, red, green, blue)?;
+        if let Some(whitepoint) = night_light {
+            let [red, green, blue] = [
+                (red, whitepoint[0]),
+                (green, whitepoint[1]),
+                (blue, whitepoint[2]),
+            ]
+            .map(|(ramp, factor)| {
+                ramp.iter()
+                    .map(|value| (f64::from(*value) * factor).round() as u16)
+                    .collect::<Vec<_>>()
+            });
+            drm_device.set_gamma(*crtc, &red, &green, &blue)?;
+        } else {
+            drm_device.set_gamma(*crtc, red, green, blue)?;
+        }
 
         Ok(())
     }
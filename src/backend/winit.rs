@@ -1,6 +1,10 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use std::{ffi::OsString, path::PathBuf, time::Duration};
+use std::{
+    ffi::OsString,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
 
 use anyhow::{anyhow, ensure};
 use smithay::{
@@ -40,7 +44,19 @@ use smithay::{
 use tracing::{error, trace, warn};
 
 use crate::{
-    render::{pointer::PointerElement, pointer_render_elements, take_presentation_feedback},
+    recording::recording_indicator_element,
+    render::{
+        damage_debug::damage_debug_elements,
+        edge_tile_preview::edge_tile_preview_element,
+        monocle_indicator::monocle_indicator_elements,
+        perf_overlay::{damage_fraction, perf_overlay_elements},
+        pointer::PointerElement,
+        pointer_render_elements,
+        snap_guides::snap_guide_elements,
+        take_presentation_feedback,
+        window_switcher::window_switcher_elements,
+        OutputRenderElement,
+    },
     state::{State, WithState},
 };
 
@@ -64,6 +80,9 @@ impl BackendData for Winit {
         self.full_redraw = 4;
     }
 
+    // There's no monitor to turn off
+    fn set_output_powered(&mut self, _output: &Output, _powered: bool) {}
+
     fn early_import(&mut self, _surface: &WlSurface) {}
 }
 
@@ -93,6 +112,7 @@ pub fn setup_winit(
     let (mut winit_backend, mut winit_evt_loop) =
         match winit::init_from_builder::<GlesRenderer>(window_builder) {
             Ok(ret) => ret,
+            // Winit's graphics backend only supports GLES, so there's no pixman fallback
             Err(err) => anyhow::bail!("Failed to init winit backend: {err}"),
         };
 
@@ -280,12 +300,35 @@ impl State {
 
         pointer_element.set_status(self.pinnacle.cursor_status.clone());
 
+        self.pinnacle.update_window_animations();
+
         // The z-index of these is determined by `state.fixup_z_layering()`, which is called at the end
         // of every event loop cycle
         let windows = self.pinnacle.space.elements().cloned().collect::<Vec<_>>();
 
         let mut output_render_elements = Vec::new();
 
+        output_render_elements.extend(perf_overlay_elements(
+            winit.backend.renderer(),
+            &self.pinnacle,
+            output,
+        ));
+        output_render_elements.extend(window_switcher_elements(
+            winit.backend.renderer(),
+            &self.pinnacle,
+            output,
+        ));
+        output_render_elements.extend(monocle_indicator_elements(
+            winit.backend.renderer(),
+            &self.pinnacle,
+            output,
+        ));
+        output_render_elements.extend(snap_guide_elements(&self.pinnacle, output));
+        output_render_elements.extend(edge_tile_preview_element(&self.pinnacle, output));
+        output_render_elements.extend(
+            recording_indicator_element(&self.pinnacle, output).map(OutputRenderElement::from),
+        );
+
         let pending_screencopy_without_cursor = output.with_state(|state| {
             state
                 .screencopy
@@ -323,8 +366,15 @@ impl State {
             winit.backend.renderer(),
             &self.pinnacle.space,
             &windows,
+            &self.pinnacle.config.render_settings,
         ));
 
+        let damage_debug_elements =
+            damage_debug_elements(&self.pinnacle, output, &output_render_elements);
+        output_render_elements.splice(0..0, damage_debug_elements);
+
+        let render_start = Instant::now();
+
         let render_res = winit.backend.bind().and_then(|_| {
             let age = if *full_redraw > 0 {
                 0
@@ -345,13 +395,33 @@ impl State {
 
         match render_res {
             Ok(render_output_result) => {
-                Winit::handle_pending_screencopy(
-                    &mut winit.backend,
+                if let Some(damage) = render_output_result.damage {
+                    let size = output
+                        .current_mode()
+                        .map(|mode| mode.size)
+                        .unwrap_or_default();
+                    let damage = damage_fraction(damage, size);
+                    let render_time = render_start.elapsed();
+                    output.with_state_mut(|state| {
+                        state
+                            .frame_stats
+                            .record(Instant::now(), render_time, false, damage)
+                    });
+                }
+
+                handle_pending_screencopy(
+                    winit.backend.renderer(),
                     output,
                     &render_output_result,
                     &self.pinnacle.loop_handle,
                 );
 
+                // We must rebind to the underlying EGL surface for buffer swapping
+                // as screencopy may have bound a `GlesRenderbuffer`.
+                if let Err(err) = winit.backend.bind() {
+                    error!("Failed to rebind EGL surface after screencopy: {err}");
+                }
+
                 let has_rendered = render_output_result.damage.is_some();
                 if let Some(damage) = render_output_result.damage {
                     if let Err(err) = winit.backend.submit(Some(damage)) {
@@ -360,6 +430,9 @@ impl State {
                 }
 
                 winit.backend.window().set_cursor_visible(cursor_visible);
+                if let CursorImageStatus::Named(icon) = &self.pinnacle.cursor_status {
+                    winit.backend.window().set_cursor_icon(*icon);
+                }
 
                 let time = self.pinnacle.clock.now();
 
@@ -396,165 +469,155 @@ impl State {
     }
 }
 
-impl Winit {
-    fn handle_pending_screencopy(
-        backend: &mut WinitGraphicsBackend<GlesRenderer>,
-        output: &Output,
-        render_output_result: &RenderOutputResult,
-        loop_handle: &LoopHandle<'static, State>,
-    ) {
-        let Some(mut screencopy) = output.with_state_mut(|state| state.screencopy.take()) else {
-            return;
-        };
+/// Copy the currently bound framebuffer into the output's pending screencopy buffer, if any.
+///
+/// This may bind a different framebuffer to `renderer`, so callers that need their
+/// render target bound afterwards must rebind it.
+pub(super) fn handle_pending_screencopy(
+    renderer: &mut GlesRenderer,
+    output: &Output,
+    render_output_result: &RenderOutputResult,
+    loop_handle: &LoopHandle<'static, State>,
+) {
+    let Some(mut screencopy) = output.with_state_mut(|state| state.screencopy.take()) else {
+        return;
+    };
 
-        assert!(screencopy.output() == output);
+    assert!(screencopy.output() == output);
 
-        if screencopy.with_damage() {
-            match render_output_result.damage.as_ref() {
-                Some(damage) if !damage.is_empty() => screencopy.damage(damage),
-                _ => {
-                    output.with_state_mut(|state| state.screencopy.replace(screencopy));
-                    return;
-                }
+    if screencopy.with_damage() {
+        match render_output_result.damage.as_ref() {
+            Some(damage) if !damage.is_empty() => screencopy.damage(damage),
+            _ => {
+                output.with_state_mut(|state| state.screencopy.replace(screencopy));
+                return;
             }
         }
+    }
 
-        let sync_point = if let Ok(dmabuf) = dmabuf::get_dmabuf(screencopy.buffer()) {
-            trace!("Dmabuf screencopy");
-
-            backend
-                .renderer()
-                .blit_to(
-                    dmabuf,
-                    screencopy.physical_region(),
-                    Rectangle::from_loc_and_size(
-                        Point::from((0, 0)),
-                        screencopy.physical_region().size,
-                    ),
-                    TextureFilter::Nearest,
-                )
-                .map(|_| render_output_result.sync.clone())
-                .map_err(|err| anyhow!("{err}"))
-        } else if !matches!(
-            renderer::buffer_type(screencopy.buffer()),
-            Some(BufferType::Shm)
-        ) {
-            Err(anyhow!("not a shm buffer"))
-        } else {
-            trace!("Shm screencopy");
-
-            let sync_point = {
-                let renderer = backend.renderer();
-                let screencopy = &screencopy;
-                if !matches!(buffer_type(screencopy.buffer()), Some(BufferType::Shm)) {
-                    warn!("screencopy does not have a shm buffer");
-                    return;
-                }
+    let sync_point = if let Ok(dmabuf) = dmabuf::get_dmabuf(screencopy.buffer()) {
+        trace!("Dmabuf screencopy");
+
+        renderer
+            .blit_to(
+                dmabuf,
+                screencopy.physical_region(),
+                Rectangle::from_loc_and_size(
+                    Point::from((0, 0)),
+                    screencopy.physical_region().size,
+                ),
+                TextureFilter::Nearest,
+            )
+            .map(|_| render_output_result.sync.clone())
+            .map_err(|err| anyhow!("{err}"))
+    } else if !matches!(
+        renderer::buffer_type(screencopy.buffer()),
+        Some(BufferType::Shm)
+    ) {
+        Err(anyhow!("not a shm buffer"))
+    } else {
+        trace!("Shm screencopy");
+
+        {
+            let screencopy = &screencopy;
+            if !matches!(buffer_type(screencopy.buffer()), Some(BufferType::Shm)) {
+                warn!("screencopy does not have a shm buffer");
+                return;
+            }
 
-                let res = smithay::wayland::shm::with_buffer_contents_mut(
-                    &screencopy.buffer().clone(),
-                    |shm_ptr, shm_len, buffer_data| {
-                        // yoinked from Niri (thanks yall)
-                        ensure!(
-                            // The buffer prefers pixels in little endian ...
-                            buffer_data.format == wl_shm::Format::Argb8888
-                                && buffer_data.stride == screencopy.physical_region().size.w * 4
-                                && buffer_data.height == screencopy.physical_region().size.h
-                                && shm_len as i32 == buffer_data.stride * buffer_data.height,
-                            "invalid buffer format or size"
-                        );
-
-                        let buffer_rect = screencopy.physical_region().to_logical(1).to_buffer(
-                            1,
-                            Transform::Normal,
-                            &screencopy.physical_region().size.to_logical(1),
-                        );
-
-                        // On winit, we cannot just copy the EGL framebuffer because I get an
-                        // `UnsupportedPixelFormat` error. Therefore we'll blit
-                        // to this buffer and then copy it.
-                        let offscreen: GlesRenderbuffer = renderer.create_buffer(
-                            smithay::backend::allocator::Fourcc::Argb8888,
-                            buffer_rect.size,
-                        )?;
-
-                        renderer.blit_to(
-                            offscreen.clone(),
-                            screencopy.physical_region(),
-                            Rectangle::from_loc_and_size(
-                                Point::from((0, 0)),
-                                screencopy.physical_region().size,
-                            ),
-                            TextureFilter::Nearest,
-                        )?;
-
-                        renderer.bind(offscreen)?;
-
-                        let mapping = renderer.copy_framebuffer(
-                            Rectangle::from_loc_and_size(Point::from((0, 0)), buffer_rect.size),
-                            smithay::backend::allocator::Fourcc::Argb8888,
-                        )?;
-
-                        let bytes = renderer.map_texture(&mapping)?;
-
-                        ensure!(bytes.len() == shm_len, "mapped buffer has wrong length");
-
-                        // SAFETY:
-                        //      - `bytes.as_ptr()` is valid for reads of size `shm_len` because that was
-                        //        checked above and is properly aligned because it
-                        //        originated from safe Rust
-                        //      - We are assuming `shm_ptr` is valid for writes of `shm_len` and is
-                        //        properly aligned
-                        //      - Overlapping-ness: TODO:
-                        unsafe {
-                            std::ptr::copy_nonoverlapping(bytes.as_ptr(), shm_ptr, shm_len);
-                        }
-
-                        Ok(())
-                    },
-                );
+            let res = smithay::wayland::shm::with_buffer_contents_mut(
+                &screencopy.buffer().clone(),
+                |shm_ptr, shm_len, buffer_data| {
+                    // yoinked from Niri (thanks yall)
+                    ensure!(
+                        // The buffer prefers pixels in little endian ...
+                        buffer_data.format == wl_shm::Format::Argb8888
+                            && buffer_data.stride == screencopy.physical_region().size.w * 4
+                            && buffer_data.height == screencopy.physical_region().size.h
+                            && shm_len as i32 == buffer_data.stride * buffer_data.height,
+                        "invalid buffer format or size"
+                    );
 
-                let Ok(res) = res else {
-                    unreachable!(
-                        "buffer is guaranteed to be shm from above and managed by smithay"
+                    let buffer_rect = screencopy.physical_region().to_logical(1).to_buffer(
+                        1,
+                        Transform::Normal,
+                        &screencopy.physical_region().size.to_logical(1),
                     );
-                };
 
-                res
-            }
-            .map(|_| render_output_result.sync.clone());
+                    // On winit, we cannot just copy the EGL framebuffer because I get an
+                    // `UnsupportedPixelFormat` error. Therefore we'll blit
+                    // to this buffer and then copy it.
+                    let offscreen: GlesRenderbuffer = renderer.create_buffer(
+                        smithay::backend::allocator::Fourcc::Argb8888,
+                        buffer_rect.size,
+                    )?;
+
+                    renderer.blit_to(
+                        offscreen.clone(),
+                        screencopy.physical_region(),
+                        Rectangle::from_loc_and_size(
+                            Point::from((0, 0)),
+                            screencopy.physical_region().size,
+                        ),
+                        TextureFilter::Nearest,
+                    )?;
+
+                    renderer.bind(offscreen)?;
+
+                    let mapping = renderer.copy_framebuffer(
+                        Rectangle::from_loc_and_size(Point::from((0, 0)), buffer_rect.size),
+                        smithay::backend::allocator::Fourcc::Argb8888,
+                    )?;
+
+                    let bytes = renderer.map_texture(&mapping)?;
+
+                    ensure!(bytes.len() == shm_len, "mapped buffer has wrong length");
+
+                    // SAFETY:
+                    //      - `bytes.as_ptr()` is valid for reads of size `shm_len` because that was
+                    //        checked above and is properly aligned because it
+                    //        originated from safe Rust
+                    //      - We are assuming `shm_ptr` is valid for writes of `shm_len` and is
+                    //        properly aligned
+                    //      - Overlapping-ness: TODO:
+                    unsafe {
+                        std::ptr::copy_nonoverlapping(bytes.as_ptr(), shm_ptr, shm_len);
+                    }
 
-            // We must rebind to the underlying EGL surface for buffer swapping
-            // as it is bound to a `GlesRenderbuffer` above.
-            if let Err(err) = backend.bind() {
-                error!("Failed to rebind EGL surface after screencopy: {err}");
-            }
+                    Ok(())
+                },
+            );
 
-            sync_point
-        };
+            let Ok(res) = res else {
+                unreachable!("buffer is guaranteed to be shm from above and managed by smithay");
+            };
+
+            res
+        }
+        .map(|_| render_output_result.sync.clone())
+    };
 
-        match sync_point {
-            Ok(sync_point) if !sync_point.is_reached() => {
-                let Some(sync_fd) = sync_point.export() else {
-                    screencopy.submit(false);
-                    return;
+    match sync_point {
+        Ok(sync_point) if !sync_point.is_reached() => {
+            let Some(sync_fd) = sync_point.export() else {
+                screencopy.submit(false);
+                return;
+            };
+            let mut screencopy = Some(screencopy);
+            let source = Generic::new(sync_fd, Interest::READ, calloop::Mode::OneShot);
+            let res = loop_handle.insert_source(source, move |_, _, _| {
+                let Some(screencopy) = screencopy.take() else {
+                    unreachable!("This source is removed after one run");
                 };
-                let mut screencopy = Some(screencopy);
-                let source = Generic::new(sync_fd, Interest::READ, calloop::Mode::OneShot);
-                let res = loop_handle.insert_source(source, move |_, _, _| {
-                    let Some(screencopy) = screencopy.take() else {
-                        unreachable!("This source is removed after one run");
-                    };
-                    screencopy.submit(false);
-                    trace!("Submitted screencopy");
-                    Ok(PostAction::Remove)
-                });
-                if res.is_err() {
-                    error!("Failed to schedule screencopy submission");
-                }
+                screencopy.submit(false);
+                trace!("Submitted screencopy");
+                Ok(PostAction::Remove)
+            });
+            if res.is_err() {
+                error!("Failed to schedule screencopy submission");
             }
-            Ok(_) => screencopy.submit(false),
-            Err(err) => error!("Failed to submit screencopy: {err}"),
         }
+        Ok(_) => screencopy.submit(false),
+        Err(err) => error!("Failed to submit screencopy: {err}"),
     }
 }
@@ -104,7 +104,7 @@ impl Pinnacle {
 
         debug!("Clearing input state");
 
-        self.input_state.clear();
+        self.input_state.clear(&self.loop_handle);
 
         self.config.clear(&self.loop_handle);
 
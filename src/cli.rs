@@ -10,6 +10,10 @@ pub enum Backend {
     Winit,
     /// Run Pinnacle from a tty
     Udev,
+    /// Run Pinnacle without a display
+    Headless,
+    /// Run Pinnacle in a window inside another Wayland compositor
+    Nested,
 }
 
 /// The main CLI struct.
@@ -31,6 +35,13 @@ pub struct Cli {
     #[arg(long, requires = "backend")]
     pub force: bool,
 
+    /// Create a virtual output of this size when running the headless backend
+    ///
+    /// Pass this multiple times to create multiple outputs.
+    /// If not passed, one 1920x1080 output will be created.
+    #[arg(long, value_name("WIDTHxHEIGHT"), value_parser(parse_output_size))]
+    pub headless_output: Vec<(i32, i32)>,
+
     /// Allow running Pinnacle as root (this is NOT recommended)
     #[arg(long)]
     pub allow_root: bool,
@@ -82,6 +93,19 @@ impl Cli {
     }
 }
 
+/// Parse an output size in the form `WIDTHxHEIGHT`.
+fn parse_output_size(size: &str) -> Result<(i32, i32), String> {
+    let (w, h) = size
+        .split_once('x')
+        .ok_or_else(|| format!("`{size}` is not in the form WIDTHxHEIGHT"))?;
+    let w = w.parse::<i32>().map_err(|err| err.to_string())?;
+    let h = h.parse::<i32>().map_err(|err| err.to_string())?;
+    if w <= 0 || h <= 0 {
+        return Err("output sizes must be positive".to_string());
+    }
+    Ok((w, h))
+}
+
 /// Cli subcommands.
 #[derive(clap::Subcommand, Debug)]
 enum CliSubcommand {
@@ -464,6 +488,25 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn cli_headless_outputs_parse_correctly() {
+        let cli = Cli::parse_from([
+            "pinnacle",
+            "--backend",
+            "headless",
+            "--headless-output",
+            "1920x1080",
+            "--headless-output",
+            "800x600",
+        ]);
+
+        assert!(matches!(cli.backend, Some(Backend::Headless)));
+        assert_eq!(cli.headless_output, vec![(1920, 1080), (800, 600)]);
+
+        assert!(Cli::try_parse_from(["pinnacle", "--headless-output", "1920"]).is_err());
+        assert!(Cli::try_parse_from(["pinnacle", "--headless-output", "0x1080"]).is_err());
+    }
+
     #[test]
     fn non_interactive_config_gen_lua_works() -> anyhow::Result<()> {
         let temp_dir = tempfile::tempdir()?;
@@ -1,13 +1,22 @@
 use crate::{
+    animation::AnimationConfig,
     api::{
-        layout::LayoutService, signal::SignalService, window::WindowService, InputService,
-        OutputService, PinnacleService, ProcessService, RenderService, TagService,
+        debug::DebugService, layout::LayoutService, screenshot::ScreenshotService,
+        signal::SignalService, window::WindowService, InputService, OutputService, PinnacleService,
+        ProcessService, RenderService, TagService,
     },
+    focus::{switcher::SwitcherSettings, FocusSettings},
+    grab::snap::SnapSettings,
     input::ModifierMask,
-    output::OutputName,
+    output::{profile::MonitorId, Alignment, OutputName},
+    render::RenderSettings,
+    session::SessionSettings,
     state::Pinnacle,
-    tag::Tag,
-    window::rules::{WindowRule, WindowRuleCondition},
+    tag::{DynamicTagSettings, Tag},
+    window::{
+        pip::PipPlacement,
+        rules::{WindowMatcher, WindowRule, WindowRuleId},
+    },
 };
 use std::{
     collections::HashMap,
@@ -17,11 +26,13 @@ use std::{
 
 use anyhow::Context;
 use pinnacle_api_defs::pinnacle::{
+    debug::v0alpha1::debug_service_server::DebugServiceServer,
     input::v0alpha1::input_service_server::InputServiceServer,
     layout::v0alpha1::layout_service_server::LayoutServiceServer,
     output::v0alpha1::output_service_server::OutputServiceServer,
     process::v0alpha1::process_service_server::ProcessServiceServer,
     render::v0alpha1::render_service_server::RenderServiceServer,
+    screenshot::v0alpha1::screenshot_service_server::ScreenshotServiceServer,
     signal::v0alpha1::signal_service_server::SignalServiceServer,
     tag::v0alpha1::tag_service_server::TagServiceServer,
     v0alpha1::{pinnacle_service_server::PinnacleServiceServer, ShutdownWatchResponse},
@@ -182,10 +193,28 @@ pub enum Key {
 /// The current state of configuration.
 #[derive(Default, Debug)]
 pub struct Config {
-    /// Window rules and conditions on when those rules should apply
-    pub window_rules: Vec<(WindowRuleCondition, WindowRule)>,
+    /// Window rules, their ids, and the matchers deciding which windows they apply to
+    pub window_rules: Vec<(WindowRuleId, WindowMatcher, WindowRule)>,
     /// Saved states when outputs are disconnected
     pub connector_saved_states: HashMap<OutputName, ConnectorSavedState>,
+    /// Outputs placed next to other outputs, kept there when either changes
+    pub relative_output_locs: HashMap<OutputName, (OutputName, Alignment)>,
+    /// Global animation settings
+    pub animations: AnimationConfig,
+    /// Render settings
+    pub render_settings: RenderSettings,
+    /// Where picture-in-picture windows are placed
+    pub pip_placement: PipPlacement,
+    /// Floating window snapping settings
+    pub snap: SnapSettings,
+    /// Window switcher settings
+    pub switcher: SwitcherSettings,
+    /// Focus settings
+    pub focus: FocusSettings,
+    /// Session persistence settings
+    pub session: SessionSettings,
+    /// Dynamic tag settings
+    pub dynamic_tags: DynamicTagSettings,
 
     pub config_join_handle: Option<JoinHandle<()>>,
     pub(crate) config_reload_on_crash_token: Option<RegistrationToken>,
@@ -215,6 +244,15 @@ impl Config {
     pub(crate) fn clear(&mut self, loop_handle: &LoopHandle<State>) {
         self.window_rules.clear();
         self.connector_saved_states.clear();
+        self.relative_output_locs.clear();
+        self.animations = AnimationConfig::default();
+        self.render_settings = RenderSettings::default();
+        self.pip_placement = PipPlacement::default();
+        self.snap = SnapSettings::default();
+        self.switcher = SwitcherSettings::default();
+        self.focus = FocusSettings::default();
+        self.session = SessionSettings::default();
+        self.dynamic_tags = DynamicTagSettings::default();
         if let Some(join_handle) = self.config_join_handle.take() {
             join_handle.abort();
         }
@@ -229,16 +267,23 @@ impl Config {
     }
 }
 
-/// State saved when an output is disconnected. When the output is reconnected to the same
-/// connector, the saved state will apply to restore its state.
+/// State saved when an output is disconnected. When the same monitor is reconnected,
+/// the saved state will apply to restore its state.
 #[derive(Debug, Default, Clone)]
 pub struct ConnectorSavedState {
+    /// The monitor that was disconnected, or `None` if this state was set before
+    /// anything was connected
+    pub monitor: Option<MonitorId>,
     /// The old location
     pub loc: Point<i32, Logical>,
     /// The output's previous tags
     pub tags: Vec<Tag>,
     /// The output's previous scale
     pub scale: Option<smithay::output::Scale>,
+    /// The output's previous transform
+    pub transform: Option<smithay::utils::Transform>,
+    /// Whether `tags` were moved to another output and should be taken back
+    pub tags_migrated: bool,
 }
 
 /// Parse a metaconfig file in `config_dir`, if any.
@@ -285,7 +330,9 @@ impl Pinnacle {
 
         debug!("Clearing input state");
 
-        self.input_state.clear();
+        self.input_state.clear(&self.loop_handle);
+
+        self.reset_cursor_theme();
 
         self.config.clear(&self.loop_handle);
 
@@ -563,6 +610,8 @@ impl Pinnacle {
         let signal_service = SignalService::new(grpc_sender.clone());
         let layout_service = LayoutService::new(grpc_sender.clone());
         let render_service = RenderService::new(grpc_sender.clone());
+        let screenshot_service = ScreenshotService::new(grpc_sender.clone());
+        let debug_service = DebugService::new(grpc_sender.clone());
 
         let refl_service = tonic_reflection::server::Builder::configure()
             .register_encoded_file_descriptor_set(pinnacle_api_defs::FILE_DESCRIPTOR_SET)
@@ -583,7 +632,9 @@ impl Pinnacle {
             .add_service(WindowServiceServer::new(window_service))
             .add_service(SignalServiceServer::new(signal_service))
             .add_service(LayoutServiceServer::new(layout_service))
-            .add_service(RenderServiceServer::new(render_service));
+            .add_service(RenderServiceServer::new(render_service))
+            .add_service(ScreenshotServiceServer::new(screenshot_service))
+            .add_service(DebugServiceServer::new(debug_service));
 
         match self.xdisplay.as_ref() {
             Some(_) => {
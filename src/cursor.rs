@@ -1,28 +1,57 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use std::{io::Read, time::Duration};
+use std::{collections::HashMap, io::Read, time::Duration};
 
+use smithay::{
+    input::pointer::CursorIcon,
+    utils::{Point, Size},
+};
+use tracing::warn;
 use xcursor::{parser::Image, CursorTheme};
 
+use crate::state::Pinnacle;
+
 static FALLBACK_CURSOR_DATA: &[u8] = include_bytes!("../resources/cursor.rgba");
 
+/// The cursor theme used when `XCURSOR_THEME` isn't set.
+pub const DEFAULT_CURSOR_THEME: &str = "default";
+/// The cursor size used when `XCURSOR_SIZE` isn't set.
+pub const DEFAULT_CURSOR_SIZE: u32 = 24;
+
 pub struct Cursor {
-    icons: Vec<Image>,
+    theme: CursorTheme,
+    theme_name: String,
+    /// Loaded images for every requested cursor icon.
+    ///
+    /// Icons that couldn't be found in the theme map to the default icon's images.
+    icons: HashMap<CursorIcon, Vec<Image>>,
     size: u32,
 }
 
+/// Get the cursor theme and size from `XCURSOR_THEME` and `XCURSOR_SIZE`.
+pub fn theme_from_env() -> (String, u32) {
+    let name = std::env::var("XCURSOR_THEME")
+        .ok()
+        .unwrap_or_else(|| DEFAULT_CURSOR_THEME.into());
+    let size = std::env::var("XCURSOR_SIZE")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_CURSOR_SIZE);
+
+    (name, size)
+}
+
 impl Cursor {
+    /// Load the cursor theme from `XCURSOR_THEME` and `XCURSOR_SIZE`.
     pub fn load() -> Self {
-        let name = std::env::var("XCURSOR_THEME")
-            .ok()
-            .unwrap_or_else(|| "default".into());
-        let size = std::env::var("XCURSOR_SIZE")
-            .ok()
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(24);
+        let (name, size) = theme_from_env();
+        Self::load_theme(name, size)
+    }
 
+    /// Load the cursor theme `name` with cursors of `size`.
+    pub fn load_theme(name: String, size: u32) -> Self {
         let theme = CursorTheme::load(&name);
-        let icons = load_icon(&theme)
+        let default_icon = load_icon(&theme, CursorIcon::Default)
             .map_err(|err| tracing::warn!("Unable to load xcursor: {}, using fallback cursor", err))
             .unwrap_or_else(|_| {
                 vec![Image {
@@ -37,12 +66,85 @@ impl Cursor {
                 }]
             });
 
-        Cursor { icons, size }
+        Cursor {
+            theme,
+            theme_name: name,
+            icons: HashMap::from([(CursorIcon::Default, default_icon)]),
+            size,
+        }
     }
 
-    pub fn get_image(&self, scale: u32, time: Duration) -> Image {
+    pub fn theme_name(&self) -> &str {
+        &self.theme_name
+    }
+
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Get the current frame of the given cursor icon.
+    ///
+    /// Icons are loaded from the cursor theme the first time they are requested.
+    pub fn get_image(&mut self, icon: CursorIcon, scale: u32, time: Duration) -> Image {
         let size = self.size * scale;
-        frame(time.as_millis() as u32, size, &self.icons)
+        frame(time.as_millis() as u32, size, self.icon_images(icon))
+    }
+
+    fn icon_images(&mut self, icon: CursorIcon) -> &[Image] {
+        if !self.icons.contains_key(&icon) {
+            let images = load_icon(&self.theme, icon).unwrap_or_else(|err| {
+                tracing::debug!("Unable to load xcursor icon {}: {err}", icon.name());
+                self.icons[&CursorIcon::Default].clone()
+            });
+            self.icons.insert(icon, images);
+        }
+
+        &self.icons[&icon]
+    }
+}
+
+impl Pinnacle {
+    /// Switch to another cursor theme or size without restarting.
+    ///
+    /// `None` keeps the current value. The new theme is exported through `XCURSOR_THEME`
+    /// and `XCURSOR_SIZE` so clients spawned afterwards pick it up, and is given to Xwayland.
+    pub fn set_cursor_theme(&mut self, theme: Option<String>, size: Option<u32>) {
+        let theme = theme.unwrap_or_else(|| self.cursor.theme_name().to_string());
+        let size = size.unwrap_or(self.cursor.size());
+
+        if theme == self.cursor.theme_name() && size == self.cursor.size() {
+            return;
+        }
+
+        std::env::set_var("XCURSOR_THEME", &theme);
+        std::env::set_var("XCURSOR_SIZE", size.to_string());
+
+        self.cursor = Cursor::load_theme(theme, size);
+        self.update_xwayland_cursor();
+    }
+
+    /// Go back to the cursor theme Pinnacle was started with.
+    pub fn reset_cursor_theme(&mut self) {
+        let (theme, size) = self.startup_cursor_theme.clone();
+        self.set_cursor_theme(Some(theme), Some(size));
+    }
+
+    /// Set Xwayland's default cursor to the current theme's.
+    pub fn update_xwayland_cursor(&mut self) {
+        let Some(xwm) = self.xwm.as_mut() else {
+            return;
+        };
+
+        let image = self
+            .cursor
+            .get_image(CursorIcon::Default, 1, Duration::ZERO);
+        if let Err(err) = xwm.set_cursor(
+            &image.pixels_rgba,
+            Size::from((image.width as u16, image.height as u16)),
+            Point::from((image.xhot as u16, image.yhot as u16)),
+        ) {
+            warn!("Failed to set xwayland default cursor: {err}");
+        }
     }
 }
 
@@ -74,16 +176,20 @@ fn frame(mut millis: u32, size: u32, images: &[Image]) -> Image {
 
 #[derive(thiserror::Error, Debug)]
 enum Error {
-    #[error("Theme has no default cursor")]
-    NoDefaultCursor,
+    #[error("Theme has no cursor for this icon")]
+    NoCursor,
     #[error("Error opening xcursor file: {0}")]
     File(#[from] std::io::Error),
     #[error("Failed to parse XCursor file")]
     Parse,
 }
 
-fn load_icon(theme: &CursorTheme) -> Result<Vec<Image>, Error> {
-    let icon_path = theme.load_icon("default").ok_or(Error::NoDefaultCursor)?;
+fn load_icon(theme: &CursorTheme, icon: CursorIcon) -> Result<Vec<Image>, Error> {
+    // Themes may only have the legacy X11 names for some icons, so try those too
+    let icon_path = std::iter::once(icon.name())
+        .chain(icon.alt_names().iter().copied())
+        .find_map(|name| theme.load_icon(name))
+        .ok_or(Error::NoCursor)?;
     let mut cursor_file = std::fs::File::open(icon_path)?;
     let mut cursor_data = Vec::new();
     cursor_file.read_to_end(&mut cursor_data)?;
@@ -1,5 +1,7 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use std::time::Duration;
+
 use smithay::{output::Output, utils::SERIAL_COUNTER};
 use tracing::warn;
 
@@ -8,8 +10,29 @@ use crate::{
     window::WindowElement,
 };
 
+pub mod directional;
 pub mod keyboard;
 pub mod pointer;
+pub mod switcher;
+
+/// Focus settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FocusSettings {
+    /// Whether clicking a window raises it.
+    pub click_to_raise: bool,
+    /// How long the pointer has to stay on a window it moved onto before the window is
+    /// focused, or `None` if focus doesn't follow the mouse.
+    pub focus_follows_mouse: Option<Duration>,
+}
+
+impl Default for FocusSettings {
+    fn default() -> Self {
+        Self {
+            click_to_raise: true,
+            focus_follows_mouse: None,
+        }
+    }
+}
 
 impl State {
     /// Update the keyboard focus.
@@ -24,6 +47,12 @@ impl State {
             }
         }
 
+        // Unfocused windows are drawn darker, which the damage tracker can't see
+        if self.pinnacle.config.render_settings.dim_inactive > 0.0 {
+            self.backend.reset_buffers(output);
+            self.schedule_render(output);
+        }
+
         self.pinnacle
             .seat
             .get_keyboard()
@@ -44,24 +73,19 @@ impl Pinnacle {
         // TODO: see if the below is necessary
         // output.with_state(|state| state.focus_stack.stack.retain(|win| win.alive()));
 
-        output
-            .with_state(|state| {
-                state.focus_stack.focused.then(|| {
-                    state
-                        .focus_stack
-                        .stack
-                        .iter()
-                        .rev()
-                        .filter(|win| win.is_on_active_tag())
-                        .find(|win| !win.is_x11_override_redirect())
-                        .cloned()
-                })
-            })
-            .flatten()
+        output.with_state(|state| state.focus_stack.current_focus().cloned())
     }
 
+    /// Restack windows in the space to match the z-index stack.
+    ///
+    /// Windows in lower [layers][crate::window::window_state::WindowLayer] are kept
+    /// below windows in higher ones.
     pub fn fixup_z_layering(&mut self) {
-        for win in self.z_index_stack.iter() {
+        let mut windows = self.z_index_stack.iter().collect::<Vec<_>>();
+        // Stable, so windows keep their order within a layer
+        windows.sort_by_key(|win| win.with_state(|state| state.stacking_layer()));
+
+        for win in windows {
             self.space.raise_element(win, false);
         }
     }
@@ -80,6 +104,24 @@ impl Pinnacle {
         self.z_index_stack.retain(|win| win != &window);
         self.z_index_stack.push(window);
 
+        // Keep windows in higher layers above the raised window
+        self.fixup_z_layering();
+        self.fixup_xwayland_window_layering();
+    }
+
+    /// Lower a window to the bottom of the z-index stack.
+    ///
+    /// Windows in lower layers stay below it. This does nothing if the window is unmapped.
+    pub fn lower_window(&mut self, window: &WindowElement) {
+        if self.space.elements().all(|win| win != window) {
+            warn!("Tried to lower an unmapped window");
+            return;
+        }
+
+        self.z_index_stack.retain(|win| win != window);
+        self.z_index_stack.insert(0, window.clone());
+
+        self.fixup_z_layering();
         self.fixup_xwayland_window_layering();
     }
 
@@ -103,6 +145,11 @@ impl OutputFocusStack {
         self.stack.retain(|op| op != &output);
         self.stack.push(output);
     }
+
+    /// Remove the output from the stack, focusing the output below it.
+    pub fn remove(&mut self, output: &Output) {
+        self.stack.retain(|op| op != output);
+    }
 }
 
 /// A stack of windows, with the top one being the one in focus.
@@ -123,6 +170,21 @@ impl WindowKeyboardFocusStack {
         self.focused = true;
     }
 
+    /// Get the focused window.
+    ///
+    /// This is the topmost window in the stack that is on an active tag.
+    pub fn current_focus(&self) -> Option<&WindowElement> {
+        self.focused
+            .then(|| {
+                self.stack
+                    .iter()
+                    .rev()
+                    .filter(|win| win.is_visible())
+                    .find(|win| !win.is_x11_override_redirect())
+            })
+            .flatten()
+    }
+
     /// Unset the focus by marking this stack as unfocused.
     ///
     /// This will cause [`Self::current_focus`] to return `None`.
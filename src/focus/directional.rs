@@ -0,0 +1,263 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Directional focus and window movement.
+//!
+//! The window to focus is picked by geometry instead of by its place in the window list,
+//! so keyboard navigation works the same with every layout. Windows on every output are
+//! considered, so focus crosses over to a neighboring output when nothing on the current
+//! one is in the way.
+//!
+//! Moving a tiled window in a direction swaps it with the nearest tiled window on its output,
+//! while floating windows are pushed by a fixed step.
+
+use smithay::utils::{Logical, Point, Rectangle};
+
+use crate::{
+    state::{Pinnacle, State, WithState},
+    window::{window_state::FloatingOrTiled, WindowElement},
+};
+
+/// How far floating windows are pushed when moved in a direction, in logical pixels.
+const FLOATING_MOVE_STEP: i32 = 32;
+
+/// A direction to move focus or windows in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// Find the rectangle in `candidates` nearest to `from` in `direction`, returning its index.
+///
+/// Only candidates whose center lies in `direction` of `from`'s center count.
+/// Those that line up with `from` are preferred, then the closest ones,
+/// then the ones best centered on it.
+pub fn nearest_in_direction(
+    from: Rectangle<i32, Logical>,
+    candidates: impl IntoIterator<Item = Rectangle<i32, Logical>>,
+    direction: Direction,
+) -> Option<usize> {
+    // Doubled so centers stay integers
+    let center = |rect: Rectangle<i32, Logical>| -> (i32, i32) {
+        (rect.loc.x * 2 + rect.size.w, rect.loc.y * 2 + rect.size.h)
+    };
+    let (from_x, from_y) = center(from);
+
+    candidates
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, rect)| {
+            let (x, y) = center(rect);
+
+            let (in_direction, gap) = match direction {
+                Direction::Left => (x < from_x, from.loc.x - (rect.loc.x + rect.size.w)),
+                Direction::Right => (x > from_x, rect.loc.x - (from.loc.x + from.size.w)),
+                Direction::Up => (y < from_y, from.loc.y - (rect.loc.y + rect.size.h)),
+                Direction::Down => (y > from_y, rect.loc.y - (from.loc.y + from.size.h)),
+            };
+
+            if !in_direction {
+                return None;
+            }
+
+            let (lines_up, off_center) = match direction {
+                Direction::Left | Direction::Right => (
+                    rect.loc.y < from.loc.y + from.size.h && from.loc.y < rect.loc.y + rect.size.h,
+                    (y - from_y).abs(),
+                ),
+                Direction::Up | Direction::Down => (
+                    rect.loc.x < from.loc.x + from.size.w && from.loc.x < rect.loc.x + rect.size.w,
+                    (x - from_x).abs(),
+                ),
+            };
+
+            Some((i, (!lines_up, gap.max(0), off_center)))
+        })
+        .min_by_key(|(_, key)| *key)
+        .map(|(i, _)| i)
+}
+
+impl Pinnacle {
+    /// Get the visible window nearest to the focused window in `direction`.
+    ///
+    /// Without a focused window, the search starts from the center of the focused output.
+    pub fn window_in_direction(&self, direction: Direction) -> Option<WindowElement> {
+        let output = self.focused_output()?;
+        let focused = self.focused_window(output);
+
+        let geometry = |win: &WindowElement| {
+            self.space
+                .element_location(win)
+                .map(|loc| Rectangle::from_loc_and_size(loc, win.geometry().size))
+        };
+
+        let from = match focused.as_ref().and_then(geometry) {
+            Some(geo) => geo,
+            None => {
+                let output_geo = self.space.output_geometry(output)?;
+                let center = Point::from((
+                    output_geo.loc.x + output_geo.size.w / 2,
+                    output_geo.loc.y + output_geo.size.h / 2,
+                ));
+                Rectangle::from_loc_and_size(center, (0, 0))
+            }
+        };
+
+        let candidates = self
+            .space
+            .elements()
+            .filter(|win| Some(*win) != focused.as_ref())
+            .filter(|win| win.is_on_active_tag() && !win.is_x11_override_redirect())
+            .filter_map(|win| Some((win, geometry(win)?)))
+            .collect::<Vec<_>>();
+
+        let index = nearest_in_direction(from, candidates.iter().map(|(_, geo)| *geo), direction)?;
+
+        Some(candidates[index].0.clone())
+    }
+
+    /// Move `window` in `direction`.
+    ///
+    /// Tiled windows swap places with the nearest tiled window on the same output,
+    /// and floating windows are pushed by [`FLOATING_MOVE_STEP`].
+    /// Fullscreen and maximized windows aren't moved.
+    pub fn move_window_in_direction(&mut self, window: &WindowElement, direction: Direction) {
+        if !window.with_state(|state| state.fullscreen_or_maximized.is_neither()) {
+            return;
+        }
+
+        let Some(loc) = self.space.element_location(window) else {
+            return;
+        };
+        let geo = Rectangle::from_loc_and_size(loc, window.geometry().size);
+
+        if window.with_state(|state| state.floating_or_tiled.is_floating()) {
+            let (dx, dy) = match direction {
+                Direction::Left => (-FLOATING_MOVE_STEP, 0),
+                Direction::Right => (FLOATING_MOVE_STEP, 0),
+                Direction::Up => (0, -FLOATING_MOVE_STEP),
+                Direction::Down => (0, FLOATING_MOVE_STEP),
+            };
+            let rect = Rectangle::from_loc_and_size((loc.x + dx, loc.y + dy), geo.size);
+
+            window.with_state_mut(|state| {
+                state.floating_or_tiled = FloatingOrTiled::Floating(rect);
+                state.edge_tile = None;
+            });
+
+            for output in self.space.outputs_for_element(window) {
+                self.request_layout(&output);
+            }
+            return;
+        }
+
+        let output = window.output(self);
+
+        let candidates = self
+            .space
+            .elements()
+            .filter(|win| *win != window)
+            .filter(|win| win.is_on_active_tag() && win.output(self) == output)
+            .filter(|win| {
+                win.with_state(|state| {
+                    state.floating_or_tiled.is_tiled() && state.fullscreen_or_maximized.is_neither()
+                })
+            })
+            .filter_map(|win| {
+                let loc = self.space.element_location(win)?;
+                Some((win, Rectangle::from_loc_and_size(loc, win.geometry().size)))
+            })
+            .collect::<Vec<_>>();
+
+        let Some(index) =
+            nearest_in_direction(geo, candidates.iter().map(|(_, geo)| *geo), direction)
+        else {
+            return;
+        };
+
+        let neighbor = candidates[index].0.clone();
+        self.swap_window_positions(window, &neighbor);
+    }
+}
+
+impl State {
+    /// Focus and raise the window nearest to the focused one in `direction`,
+    /// focusing its output if it's on another one.
+    pub fn focus_in_direction(&mut self, direction: Direction) {
+        let Some(window) = self.pinnacle.window_in_direction(direction) else {
+            return;
+        };
+        let Some(output) = window.output(&self.pinnacle) else {
+            return;
+        };
+
+        self.pinnacle.output_focus_stack.set_focus(output.clone());
+        output.with_state_mut(|state| state.focus_stack.set_focus(window.clone()));
+        self.pinnacle.raise_window(window, true);
+
+        self.update_focus(&output);
+        self.schedule_render(&output);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: i32, y: i32, w: i32, h: i32) -> Rectangle<i32, Logical> {
+        Rectangle::from_loc_and_size((x, y), (w, h))
+    }
+
+    #[test]
+    fn picks_the_closest_window() {
+        let from = rect(500, 0, 500, 500);
+        let candidates = [rect(0, 0, 200, 500), rect(250, 0, 200, 500)];
+
+        assert_eq!(
+            nearest_in_direction(from, candidates, Direction::Left),
+            Some(1)
+        );
+        assert_eq!(
+            nearest_in_direction(from, candidates, Direction::Right),
+            None
+        );
+    }
+
+    #[test]
+    fn prefers_windows_that_line_up() {
+        // Master window on the left, two stacked windows on the right
+        let master = rect(0, 0, 500, 1000);
+        let top = rect(500, 0, 500, 500);
+        let bottom = rect(500, 500, 500, 500);
+
+        assert_eq!(
+            nearest_in_direction(bottom, [master, top], Direction::Left),
+            Some(0)
+        );
+        assert_eq!(
+            nearest_in_direction(bottom, [master, top], Direction::Up),
+            Some(1)
+        );
+
+        let far_but_aligned = rect(2000, 500, 500, 500);
+        let near_but_offset = rect(1100, 1200, 500, 500);
+        assert_eq!(
+            nearest_in_direction(bottom, [near_but_offset, far_but_aligned], Direction::Right),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn crosses_outputs() {
+        // A window on an output to the right
+        let from = rect(960, 0, 960, 1080);
+        let other_output_window = rect(1920, 0, 1920, 1080);
+
+        assert_eq!(
+            nearest_in_direction(from, [other_output_window], Direction::Right),
+            Some(0)
+        );
+    }
+}
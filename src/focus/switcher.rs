@@ -0,0 +1,175 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! The window switcher.
+//!
+//! Pinnacle remembers the order windows were focused in. Cycling the switcher, usually from
+//! a keybind like alt+tab, opens an overlay on the focused output showing every visible
+//! window, most recently focused first, with the selected one highlighted. Cycling again
+//! while it's open selects the next window, and the selected window is focused once every
+//! modifier is released. Pressing escape closes the switcher without changing focus.
+
+use smithay::{backend::renderer::element::Id, output::Output, utils::IsAlive};
+
+use crate::{
+    state::{Pinnacle, State, WithState},
+    window::WindowElement,
+};
+
+/// Window switcher settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwitcherSettings {
+    /// Whether the overlay shows thumbnails of windows.
+    pub thumbnails: bool,
+}
+
+impl Default for SwitcherSettings {
+    fn default() -> Self {
+        Self { thumbnails: true }
+    }
+}
+
+/// An open window switcher.
+#[derive(Debug)]
+pub struct WindowSwitcher {
+    /// The output the overlay is shown on.
+    pub output: Output,
+    /// The windows to switch between, most recently focused first.
+    pub windows: Vec<WindowElement>,
+    /// The index of the selected window in `windows`.
+    pub selected: usize,
+    pub background: Id,
+    pub highlight: Id,
+    /// The ids of the cell behind each window.
+    pub cells: Vec<Id>,
+}
+
+impl WindowSwitcher {
+    fn new(output: Output, windows: Vec<WindowElement>) -> Self {
+        Self {
+            output,
+            cells: windows.iter().map(|_| Id::new()).collect(),
+            windows,
+            selected: 0,
+            background: Id::new(),
+            highlight: Id::new(),
+        }
+    }
+
+    /// Select the next window, or the previous one if `backward`, wrapping around.
+    fn cycle(&mut self, backward: bool) {
+        let len = self.windows.len();
+        if len == 0 {
+            return;
+        }
+
+        self.selected = match backward {
+            true => (self.selected + len - 1) % len,
+            false => (self.selected + 1) % len,
+        };
+    }
+
+    /// Get the selected window.
+    pub fn selected_window(&self) -> Option<&WindowElement> {
+        self.windows.get(self.selected)
+    }
+}
+
+impl Pinnacle {
+    /// Move `window` to the front of the focus history.
+    pub fn record_focus(&mut self, window: &WindowElement) {
+        self.focus_history
+            .retain(|win| win != window && win.alive());
+        self.focus_history.push(window.clone());
+    }
+
+    /// Get every visible window, most recently focused first.
+    ///
+    /// Windows that were never focused come last, in window list order.
+    pub fn windows_by_recent_focus(&self) -> Vec<WindowElement> {
+        let switchable = |win: &&WindowElement| {
+            win.is_on_active_tag() && !win.is_x11_override_redirect() && self.windows.contains(win)
+        };
+
+        let mut windows = self
+            .focus_history
+            .iter()
+            .rev()
+            .filter(switchable)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let never_focused = self
+            .windows
+            .iter()
+            .filter(switchable)
+            .filter(|win| !windows.contains(win))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        windows.extend(never_focused);
+        windows
+    }
+}
+
+impl State {
+    /// Open the window switcher on the focused output, or select the next window if it's open.
+    ///
+    /// Opening the switcher selects the window focused before the current one,
+    /// or the least recently focused one if `backward`.
+    pub fn cycle_window_switcher(&mut self, backward: bool) {
+        if let Some(switcher) = self.pinnacle.window_switcher.as_mut() {
+            switcher.cycle(backward);
+            let output = switcher.output.clone();
+            self.schedule_render(&output);
+            return;
+        }
+
+        let Some(output) = self.pinnacle.focused_output().cloned() else {
+            return;
+        };
+
+        let windows = self.pinnacle.windows_by_recent_focus();
+        if windows.is_empty() {
+            return;
+        }
+
+        let mut switcher = WindowSwitcher::new(output.clone(), windows);
+        switcher.cycle(backward);
+        self.pinnacle.window_switcher = Some(switcher);
+
+        self.schedule_render(&output);
+    }
+
+    /// Close the window switcher and focus the selected window.
+    pub fn finish_window_switcher(&mut self) {
+        let Some(switcher) = self.pinnacle.window_switcher.take() else {
+            return;
+        };
+
+        self.schedule_render(&switcher.output);
+
+        let Some(window) = switcher.selected_window().cloned() else {
+            return;
+        };
+        if !window.alive() {
+            return;
+        }
+        let Some(output) = window.output(&self.pinnacle) else {
+            return;
+        };
+
+        self.pinnacle.output_focus_stack.set_focus(output.clone());
+        output.with_state_mut(|state| state.focus_stack.set_focus(window.clone()));
+        self.pinnacle.raise_window(window, true);
+
+        self.update_focus(&output);
+        self.schedule_render(&output);
+    }
+
+    /// Close the window switcher without changing focus.
+    pub fn cancel_window_switcher(&mut self) {
+        if let Some(switcher) = self.pinnacle.window_switcher.take() {
+            self.schedule_render(&switcher.output);
+        }
+    }
+}
@@ -2,6 +2,9 @@
 
 pub mod move_grab;
 pub mod resize_grab;
+pub mod snap;
+pub mod tile_resize_grab;
+pub mod tile_swap_grab;
 
 use smithay::{
     input::pointer::{GrabStartData, PointerHandle},
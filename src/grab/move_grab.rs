@@ -13,14 +13,16 @@ use smithay::{
         },
         Seat, SeatHandler,
     },
+    output::Output,
     reexports::wayland_server::protocol::wl_surface::WlSurface,
-    utils::{IsAlive, Logical, Point, Rectangle, Serial},
+    utils::{IsAlive, Logical, Point, Rectangle, Serial, Size},
 };
 use tracing::{debug, warn};
 
 use crate::{
+    grab::{snap::snap, tile_swap_grab::TileSwapGrab},
     state::{State, WithState},
-    window::{window_state::FloatingOrTiled, WindowElement},
+    window::{edge_tile::EdgeTile, window_state::FloatingOrTiled, WindowElement},
 };
 
 /// Data for moving a window.
@@ -29,6 +31,8 @@ pub struct MoveSurfaceGrab {
     /// The window being moved
     pub window: WindowElement,
     pub initial_window_loc: Point<i32, Logical>,
+    /// The part of an output the window will be tiled to when dropped, if any.
+    pub edge_tile: Option<(EdgeTile, Output)>,
 }
 
 impl PointerGrab<State> for MoveSurfaceGrab {
@@ -112,9 +116,9 @@ impl PointerGrab<State> for MoveSurfaceGrab {
                     return;
                 }
 
-                if window_under.with_state(|state| {
-                    state.floating_or_tiled.is_floating() || state.target_loc.is_some()
-                }) {
+                if window_under.with_state(|state| state.floating_or_tiled.is_floating())
+                    || state.pinnacle.layout_state.is_in_transaction(&window_under)
+                {
                     return;
                 }
 
@@ -126,6 +130,35 @@ impl PointerGrab<State> for MoveSurfaceGrab {
         } else {
             let delta = event.location - self.start_data.location;
             let new_loc = (self.initial_window_loc.to_f64() + delta).to_i32_round();
+
+            let targets = state.pinnacle.snap_targets(&self.window);
+            let snapped = snap(
+                Rectangle::from_loc_and_size(new_loc, self.window.geometry().size),
+                &targets,
+                state.pinnacle.config.snap.threshold,
+            );
+            let new_loc = snapped.loc;
+            state.set_snap_guides([snapped.vertical, snapped.horizontal]);
+
+            let can_edge_tile = self
+                .window
+                .with_state(|state| state.fullscreen_or_maximized.is_neither());
+            let output_under = state.pinnacle.space.output_under(event.location).next();
+
+            self.edge_tile = output_under
+                .filter(|_| can_edge_tile)
+                .cloned()
+                .and_then(|output| {
+                    let output_geo = state.pinnacle.space.output_geometry(&output)?;
+                    let tile = EdgeTile::under_pointer(event.location, output_geo)?;
+                    Some((tile, output))
+                });
+            let preview = self
+                .edge_tile
+                .as_ref()
+                .and_then(|(tile, output)| state.pinnacle.edge_tile_geometry(*tile, output));
+            state.set_edge_tile_preview(preview);
+
             state
                 .pinnacle
                 .space
@@ -198,7 +231,16 @@ impl PointerGrab<State> for MoveSurfaceGrab {
         &self.start_data
     }
 
-    fn unset(&mut self, _data: &mut State) {}
+    fn unset(&mut self, data: &mut State) {
+        data.set_snap_guides([None, None]);
+        data.set_edge_tile_preview(None);
+
+        if let Some((tile, output)) = self.edge_tile.take() {
+            if self.window.alive() {
+                data.pinnacle.edge_tile_window(&self.window, tile, &output);
+            }
+        }
+    }
 
     fn gesture_swipe_begin(
         &mut self,
@@ -273,7 +315,55 @@ impl PointerGrab<State> for MoveSurfaceGrab {
     }
 }
 
+/// Get where a window at `loc` with `size` has to go when it's resized to `new_size`
+/// so the pointer stays at the same relative position across it.
+pub fn loc_keeping_pointer(
+    loc: Point<i32, Logical>,
+    size: Size<i32, Logical>,
+    new_size: Size<i32, Logical>,
+    pointer: Point<f64, Logical>,
+) -> Point<i32, Logical> {
+    let offset = pointer - loc.to_f64();
+    let new_offset = Point::<f64, Logical>::from((
+        offset.x * new_size.w as f64 / size.w.max(1) as f64,
+        offset.y * new_size.h as f64 / size.h.max(1) as f64,
+    ));
+    (pointer - new_offset).to_i32_round()
+}
+
 impl State {
+    /// Unmaximize a maximized floating window that starts being moved,
+    /// putting it back under the pointer at its floating size.
+    fn unmaximize_for_move(&mut self, window: &WindowElement, pointer: Point<f64, Logical>) {
+        let FloatingOrTiled::Floating(rect) = window.with_state(|state| state.floating_or_tiled)
+        else {
+            return;
+        };
+
+        if !window.with_state(|state| state.fullscreen_or_maximized.is_maximized()) {
+            return;
+        }
+
+        let Some(loc) = self.pinnacle.space.element_location(window) else {
+            return;
+        };
+
+        let new_loc = loc_keeping_pointer(loc, window.geometry().size, rect.size, pointer);
+        window.with_state_mut(|state| {
+            state.floating_or_tiled =
+                FloatingOrTiled::Floating(Rectangle::from_loc_and_size(new_loc, rect.size));
+        });
+
+        window.toggle_maximized();
+        self.pinnacle
+            .space
+            .map_element(window.clone(), new_loc, false);
+
+        if let Some(output) = window.output(&self.pinnacle) {
+            self.pinnacle.request_layout(&output);
+        }
+    }
+
     /// The application initiated a move grab e.g. when you drag a titlebar.
     pub fn move_request_client(&mut self, surface: &WlSurface, seat: &Seat<State>, serial: Serial) {
         let pointer = seat.get_pointer().expect("seat had no pointer");
@@ -283,6 +373,10 @@ impl State {
                 return;
             };
 
+            self.pinnacle
+                .untile_from_edge(&window, pointer.current_location());
+            self.unmaximize_for_move(&window, pointer.current_location());
+
             let initial_window_loc = self
                 .pinnacle
                 .space
@@ -293,6 +387,7 @@ impl State {
                 start_data,
                 window,
                 initial_window_loc,
+                edge_tile: None,
             };
 
             pointer.set_grab(self, grab, serial, Focus::Clear);
@@ -315,6 +410,24 @@ impl State {
             return;
         };
 
+        // Tiled windows are lifted off their tile and swapped with the one they're dropped on
+        if window.with_state(|state| {
+            state.floating_or_tiled.is_tiled() && state.fullscreen_or_maximized.is_neither()
+        }) {
+            let start_data = smithay::input::pointer::GrabStartData {
+                focus: None,
+                button: button_used,
+                location: pointer.current_location(),
+            };
+            let grab = TileSwapGrab::start(&mut self.pinnacle, start_data, window, button_used);
+            pointer.set_grab(self, grab, serial, Focus::Clear);
+            return;
+        }
+
+        self.pinnacle
+            .untile_from_edge(&window, pointer.current_location());
+        self.unmaximize_for_move(&window, pointer.current_location());
+
         let initial_window_loc = self
             .pinnacle
             .space
@@ -333,6 +446,7 @@ impl State {
             start_data,
             window,
             initial_window_loc,
+            edge_tile: None,
         };
 
         pointer.set_grab(self, grab, serial, Focus::Clear);
@@ -21,6 +21,7 @@ use smithay::{
 };
 
 use crate::{
+    grab::tile_resize_grab::TileResizeGrab,
     state::{Pinnacle, State, WithState},
     window::{window_state::FloatingOrTiled, WindowElement},
 };
@@ -76,6 +77,9 @@ impl ResizeSurfaceGrab {
             };
         });
 
+        // A resized window no longer covers its part of the output
+        window.with_state_mut(|state| state.edge_tile = None);
+
         Some(Self {
             start_data,
             window,
@@ -516,6 +520,16 @@ impl State {
         };
 
         if window.with_state(|state| state.floating_or_tiled.is_tiled()) {
+            // Tiled windows are resized by adjusting the layout instead
+            if window.with_state(|state| state.fullscreen_or_maximized.is_neither()) {
+                let start_data = smithay::input::pointer::GrabStartData {
+                    focus: None,
+                    button: button_used,
+                    location: pointer.current_location(),
+                };
+                let grab = TileResizeGrab::start(start_data, window, edges, button_used);
+                pointer.set_grab(self, grab, serial, Focus::Clear);
+            }
             return;
         }
 
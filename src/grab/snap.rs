@@ -0,0 +1,267 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Edge snapping for floating windows.
+//!
+//! While a floating window is being moved, its edges snap to the edges of outputs,
+//! the area left over by layer-shell exclusive zones, and the edges of other visible
+//! floating windows once they come within the snap threshold. Guide lines are drawn
+//! along the edges the window snapped to.
+
+use smithay::{
+    backend::renderer::element::Id,
+    desktop::{layer_map_for_output, space::SpaceElement},
+    utils::{Logical, Point, Rectangle},
+};
+
+use crate::{
+    state::{Pinnacle, State, WithState},
+    window::WindowElement,
+};
+
+/// How thick snap guide lines are.
+pub const GUIDE_WIDTH: i32 = 2;
+
+/// Snapping settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnapSettings {
+    /// How close in logical pixels an edge has to get before it snaps. 0 disables snapping.
+    pub threshold: i32,
+}
+
+impl Default for SnapSettings {
+    fn default() -> Self {
+        Self { threshold: 12 }
+    }
+}
+
+/// The guide lines of the current snap, if any.
+#[derive(Debug)]
+pub struct SnapGuides {
+    /// The vertical and horizontal guide lines in global coordinates.
+    pub lines: [Option<Rectangle<i32, Logical>>; 2],
+    pub ids: [Id; 2],
+}
+
+impl Default for SnapGuides {
+    fn default() -> Self {
+        Self {
+            lines: [None, None],
+            ids: [Id::new(), Id::new()],
+        }
+    }
+}
+
+/// The result of snapping a window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Snap {
+    /// The location of the window after snapping.
+    pub loc: Point<i32, Logical>,
+    /// The guide line along the vertical edge the window snapped to, if any.
+    pub vertical: Option<Rectangle<i32, Logical>>,
+    /// The guide line along the horizontal edge the window snapped to, if any.
+    pub horizontal: Option<Rectangle<i32, Logical>>,
+}
+
+/// Snap the edges of `geo` to the closest edges of `targets` within `threshold`.
+///
+/// Each axis snaps on its own. Only targets that line up with the window on the other axis
+/// are considered, so windows don't snap to edges they are nowhere near.
+pub fn snap(
+    geo: Rectangle<i32, Logical>,
+    targets: &[Rectangle<i32, Logical>],
+    threshold: i32,
+) -> Snap {
+    let mut snap = Snap {
+        loc: geo.loc,
+        vertical: None,
+        horizontal: None,
+    };
+
+    if threshold <= 0 {
+        return snap;
+    }
+
+    let x = closest_edge(
+        (geo.loc.x, geo.size.w),
+        targets
+            .iter()
+            .filter(|target| {
+                overlaps(
+                    (geo.loc.y, geo.size.h),
+                    (target.loc.y, target.size.h),
+                    threshold,
+                )
+            })
+            .map(|target| ((target.loc.x, target.size.w), (target.loc.y, target.size.h))),
+        threshold,
+    );
+
+    let y = closest_edge(
+        (geo.loc.y, geo.size.h),
+        targets
+            .iter()
+            .filter(|target| {
+                overlaps(
+                    (geo.loc.x, geo.size.w),
+                    (target.loc.x, target.size.w),
+                    threshold,
+                )
+            })
+            .map(|target| ((target.loc.y, target.size.h), (target.loc.x, target.size.w))),
+        threshold,
+    );
+
+    if let Some((delta, edge, (start, len))) = x {
+        snap.loc.x += delta;
+        let top = start.min(geo.loc.y);
+        let bottom = (start + len).max(geo.loc.y + geo.size.h);
+        snap.vertical = Some(Rectangle::from_loc_and_size(
+            (edge - GUIDE_WIDTH / 2, top),
+            (GUIDE_WIDTH, bottom - top),
+        ));
+    }
+
+    if let Some((delta, edge, (start, len))) = y {
+        snap.loc.y += delta;
+        let left = start.min(geo.loc.x);
+        let right = (start + len).max(geo.loc.x + geo.size.w);
+        snap.horizontal = Some(Rectangle::from_loc_and_size(
+            (left, edge - GUIDE_WIDTH / 2),
+            (right - left, GUIDE_WIDTH),
+        ));
+    }
+
+    snap
+}
+
+/// Whether two spans overlap once `a` is grown by `slack` on both sides.
+fn overlaps((a, a_len): (i32, i32), (b, b_len): (i32, i32), slack: i32) -> bool {
+    a - slack < b + b_len && b < a + a_len + slack
+}
+
+/// Find the target edge closest to either edge of `span` along one axis.
+///
+/// Targets are given as their span along this axis and their span along the other one.
+/// Returns how far to move the span, the edge it snapped to, and the other-axis span of
+/// the target.
+fn closest_edge(
+    (start, len): (i32, i32),
+    targets: impl Iterator<Item = ((i32, i32), (i32, i32))>,
+    threshold: i32,
+) -> Option<(i32, i32, (i32, i32))> {
+    targets
+        .flat_map(|((target_start, target_len), other)| {
+            [target_start, target_start + target_len]
+                .into_iter()
+                .flat_map(move |edge| {
+                    [edge - start, edge - (start + len)].map(|delta| (delta, edge, other))
+                })
+        })
+        .filter(|(delta, _, _)| delta.abs() <= threshold)
+        .min_by_key(|(delta, _, _)| delta.abs())
+}
+
+impl Pinnacle {
+    /// Get the rectangles a moving floating `window` can snap to.
+    ///
+    /// These are the geometries of every output, their non-exclusive zones,
+    /// and the geometries of other visible floating windows.
+    pub fn snap_targets(&self, window: &WindowElement) -> Vec<Rectangle<i32, Logical>> {
+        let mut targets = Vec::new();
+
+        for output in self.space.outputs() {
+            let Some(output_geo) = self.space.output_geometry(output) else {
+                continue;
+            };
+            targets.push(output_geo);
+
+            let mut zone = layer_map_for_output(output).non_exclusive_zone();
+            zone.loc += output_geo.loc;
+            if zone != output_geo {
+                targets.push(zone);
+            }
+        }
+
+        targets.extend(
+            self.space
+                .elements()
+                .filter(|win| *win != window)
+                .filter(|win| win.is_on_active_tag())
+                .filter(|win| win.with_state(|state| state.floating_or_tiled.is_floating()))
+                .filter_map(|win| {
+                    let loc = self.space.element_location(win)?;
+                    Some(Rectangle::from_loc_and_size(loc, win.geometry().size))
+                }),
+        );
+
+        targets
+    }
+}
+
+impl State {
+    /// Show the given vertical and horizontal snap guide lines, rendering every output
+    /// if they changed.
+    pub fn set_snap_guides(&mut self, lines: [Option<Rectangle<i32, Logical>>; 2]) {
+        if self.pinnacle.snap_guides.lines == lines {
+            return;
+        }
+
+        self.pinnacle.snap_guides.lines = lines;
+
+        for output in self.pinnacle.space.outputs().cloned().collect::<Vec<_>>() {
+            self.schedule_render(&output);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: i32, y: i32, w: i32, h: i32) -> Rectangle<i32, Logical> {
+        Rectangle::from_loc_and_size((x, y), (w, h))
+    }
+
+    #[test]
+    fn snaps_to_output_edges() {
+        let output = rect(0, 0, 1920, 1080);
+
+        let snapped = snap(rect(8, 500, 400, 300), &[output], 12);
+        assert_eq!(snapped.loc, Point::from((0, 500)));
+        assert_eq!(snapped.vertical, Some(rect(-1, 0, GUIDE_WIDTH, 1080)));
+        assert_eq!(snapped.horizontal, None);
+
+        let snapped = snap(rect(1515, 775, 400, 300), &[output], 12);
+        assert_eq!(snapped.loc, Point::from((1520, 780)));
+    }
+
+    #[test]
+    fn snaps_next_to_other_windows() {
+        let other = rect(100, 100, 300, 300);
+
+        let snapped = snap(rect(405, 150, 200, 200), &[other], 12);
+        assert_eq!(snapped.loc, Point::from((400, 150)));
+    }
+
+    #[test]
+    fn ignores_windows_that_dont_line_up() {
+        let other = rect(100, 100, 300, 300);
+
+        let snapped = snap(rect(405, 800, 200, 200), &[other], 12);
+        assert_eq!(snapped.loc, Point::from((405, 800)));
+    }
+
+    #[test]
+    fn snaps_to_the_closest_edge() {
+        let targets = [rect(0, 0, 1920, 1080), rect(0, 30, 1920, 1050)];
+
+        let snapped = snap(rect(200, 26, 400, 300), &targets, 12);
+        assert_eq!(snapped.loc, Point::from((200, 30)));
+    }
+
+    #[test]
+    fn zero_threshold_disables_snapping() {
+        let snapped = snap(rect(2, 2, 400, 300), &[rect(0, 0, 1920, 1080)], 0);
+        assert_eq!(snapped.loc, Point::from((2, 2)));
+    }
+}
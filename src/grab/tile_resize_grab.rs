@@ -0,0 +1,211 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use smithay::{
+    input::{
+        pointer::{
+            AxisFrame, ButtonEvent, GestureHoldBeginEvent, GestureHoldEndEvent,
+            GesturePinchBeginEvent, GesturePinchEndEvent, GesturePinchUpdateEvent,
+            GestureSwipeBeginEvent, GestureSwipeEndEvent, GestureSwipeUpdateEvent, GrabStartData,
+            PointerGrab, PointerInnerHandle,
+        },
+        SeatHandler,
+    },
+    reexports::wayland_protocols::xdg::shell::server::xdg_toplevel,
+    utils::{IsAlive, Logical, Point},
+};
+
+use crate::{state::State, window::WindowElement};
+
+use super::resize_grab::ResizeEdge;
+
+/// A grab that resizes a tiled window by having the layout client adjust its split ratios.
+pub struct TileResizeGrab {
+    start_data: GrabStartData<State>,
+    window: WindowElement,
+
+    edges: ResizeEdge,
+
+    /// How far the pointer has moved since the start, as already sent to the layout client.
+    sent_delta: Point<i32, Logical>,
+
+    button_used: u32,
+}
+
+impl TileResizeGrab {
+    pub fn start(
+        start_data: GrabStartData<State>,
+        window: WindowElement,
+        edges: ResizeEdge,
+        button_used: u32,
+    ) -> Self {
+        Self {
+            start_data,
+            window,
+            edges,
+            sent_delta: Point::default(),
+            button_used,
+        }
+    }
+}
+
+impl PointerGrab<State> for TileResizeGrab {
+    fn frame(&mut self, data: &mut State, handle: &mut PointerInnerHandle<'_, State>) {
+        handle.frame(data);
+    }
+
+    fn motion(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        _focus: Option<(<State as SeatHandler>::PointerFocus, Point<i32, Logical>)>,
+        event: &smithay::input::pointer::MotionEvent,
+    ) {
+        handle.motion(data, None, event);
+
+        if !self.window.alive() {
+            handle.unset_grab(self, data, event.serial, event.time, true);
+            return;
+        }
+
+        let total_delta = (event.location - self.start_data.location).to_i32_round::<i32>();
+        let delta = total_delta - self.sent_delta;
+
+        // Dragging an edge away from the window grows it
+        let width_delta = match self.edges.0 {
+            xdg_toplevel::ResizeEdge::Left
+            | xdg_toplevel::ResizeEdge::TopLeft
+            | xdg_toplevel::ResizeEdge::BottomLeft => -delta.x,
+            xdg_toplevel::ResizeEdge::Right
+            | xdg_toplevel::ResizeEdge::TopRight
+            | xdg_toplevel::ResizeEdge::BottomRight => delta.x,
+            _ => 0,
+        };
+        let height_delta = match self.edges.0 {
+            xdg_toplevel::ResizeEdge::Top
+            | xdg_toplevel::ResizeEdge::TopLeft
+            | xdg_toplevel::ResizeEdge::TopRight => -delta.y,
+            xdg_toplevel::ResizeEdge::Bottom
+            | xdg_toplevel::ResizeEdge::BottomLeft
+            | xdg_toplevel::ResizeEdge::BottomRight => delta.y,
+            _ => 0,
+        };
+
+        if width_delta == 0 && height_delta == 0 {
+            return;
+        }
+
+        self.sent_delta = total_delta;
+        data.pinnacle
+            .resize_tile(&self.window, width_delta, height_delta);
+    }
+
+    fn relative_motion(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        focus: Option<(<State as SeatHandler>::PointerFocus, Point<i32, Logical>)>,
+        event: &smithay::input::pointer::RelativeMotionEvent,
+    ) {
+        handle.relative_motion(data, focus, event);
+    }
+
+    fn button(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &ButtonEvent,
+    ) {
+        handle.button(data, event);
+
+        if !handle.current_pressed().contains(&self.button_used) {
+            handle.unset_grab(self, data, event.serial, event.time, true);
+        }
+    }
+
+    fn axis(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        details: AxisFrame,
+    ) {
+        handle.axis(data, details);
+    }
+
+    fn start_data(&self) -> &GrabStartData<State> {
+        &self.start_data
+    }
+
+    fn unset(&mut self, _data: &mut State) {}
+
+    fn gesture_swipe_begin(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &GestureSwipeBeginEvent,
+    ) {
+        handle.gesture_swipe_begin(data, event);
+    }
+
+    fn gesture_swipe_update(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &GestureSwipeUpdateEvent,
+    ) {
+        handle.gesture_swipe_update(data, event);
+    }
+
+    fn gesture_swipe_end(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &GestureSwipeEndEvent,
+    ) {
+        handle.gesture_swipe_end(data, event);
+    }
+
+    fn gesture_pinch_begin(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &GesturePinchBeginEvent,
+    ) {
+        handle.gesture_pinch_begin(data, event);
+    }
+
+    fn gesture_pinch_update(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &GesturePinchUpdateEvent,
+    ) {
+        handle.gesture_pinch_update(data, event);
+    }
+
+    fn gesture_pinch_end(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &GesturePinchEndEvent,
+    ) {
+        handle.gesture_pinch_end(data, event);
+    }
+
+    fn gesture_hold_begin(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &GestureHoldBeginEvent,
+    ) {
+        handle.gesture_hold_begin(data, event);
+    }
+
+    fn gesture_hold_end(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &GestureHoldEndEvent,
+    ) {
+        handle.gesture_hold_end(data, event);
+    }
+}
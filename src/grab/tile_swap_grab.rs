@@ -0,0 +1,268 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use smithay::{
+    desktop::space::SpaceElement,
+    input::{
+        pointer::{
+            AxisFrame, ButtonEvent, GestureHoldBeginEvent, GestureHoldEndEvent,
+            GesturePinchBeginEvent, GesturePinchEndEvent, GesturePinchUpdateEvent,
+            GestureSwipeBeginEvent, GestureSwipeEndEvent, GestureSwipeUpdateEvent, GrabStartData,
+            MotionEvent, PointerGrab, PointerInnerHandle, RelativeMotionEvent,
+        },
+        SeatHandler,
+    },
+    utils::{IsAlive, Logical, Point, Rectangle},
+};
+use tracing::debug;
+
+use crate::{
+    state::{Pinnacle, State, WithState},
+    window::WindowElement,
+};
+
+/// How opaque a tiled window being dragged is drawn.
+pub const SWAP_GHOST_ALPHA: f32 = 0.7;
+
+/// A grab that lifts a tiled window off its tile and swaps it with the tile it's dropped on.
+///
+/// While dragging, the window follows the pointer as a translucent ghost and layouts
+/// leave it where it is. Its tile stays reserved until the grab ends.
+pub struct TileSwapGrab {
+    start_data: GrabStartData<State>,
+    window: WindowElement,
+
+    /// Where the window is relative to the pointer.
+    offset: Point<f64, Logical>,
+    /// Where the pointer last was.
+    location: Point<f64, Logical>,
+
+    button_used: u32,
+}
+
+impl TileSwapGrab {
+    pub fn start(
+        pinnacle: &mut Pinnacle,
+        start_data: GrabStartData<State>,
+        window: WindowElement,
+        button_used: u32,
+    ) -> Self {
+        let window_loc = pinnacle.space.element_location(&window).unwrap_or_default();
+
+        window.with_state_mut(|state| state.swap_ghost = true);
+        pinnacle.raise_window(window.clone(), false);
+
+        Self {
+            offset: window_loc.to_f64() - start_data.location,
+            location: start_data.location,
+            start_data,
+            window,
+            button_used,
+        }
+    }
+
+    /// Get the tiled window under `location` this window can be swapped with.
+    fn window_under(
+        &self,
+        pinnacle: &Pinnacle,
+        location: Point<f64, Logical>,
+    ) -> Option<WindowElement> {
+        let output = self.window.output(pinnacle);
+
+        // INFO: the geometry is used instead of space.element_under(location) because that
+        // |     uses the bounding box, which is different from the actual geometry
+        pinnacle
+            .space
+            .elements()
+            .rev()
+            .filter(|win| *win != &self.window)
+            .filter(|win| {
+                win.with_state(|state| {
+                    state.floating_or_tiled.is_tiled() && state.fullscreen_or_maximized.is_neither()
+                })
+            })
+            .find(|win| {
+                pinnacle.space.element_location(win).is_some_and(|loc| {
+                    Rectangle::from_loc_and_size(loc, win.geometry().size)
+                        .contains(location.to_i32_round())
+                })
+            })
+            .filter(|win| win.output(pinnacle) == output)
+            .cloned()
+    }
+}
+
+impl PointerGrab<State> for TileSwapGrab {
+    fn frame(&mut self, data: &mut State, handle: &mut PointerInnerHandle<'_, State>) {
+        handle.frame(data);
+    }
+
+    fn motion(
+        &mut self,
+        state: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        _focus: Option<(<State as SeatHandler>::PointerFocus, Point<i32, Logical>)>,
+        event: &MotionEvent,
+    ) {
+        handle.motion(state, None, event);
+
+        if !self.window.alive() {
+            handle.unset_grab(self, state, event.serial, event.time, true);
+            return;
+        }
+
+        self.location = event.location;
+        let new_loc = (event.location + self.offset).to_i32_round();
+
+        let outputs_before = state.pinnacle.space.outputs_for_element(&self.window);
+        state
+            .pinnacle
+            .space
+            .map_element(self.window.clone(), new_loc, true);
+
+        if let Some(surface) = self.window.x11_surface() {
+            if !surface.is_override_redirect() {
+                let new_geo = Rectangle::from_loc_and_size(new_loc, surface.geometry().size);
+                let _ = surface.configure(new_geo);
+            }
+        }
+
+        let mut outputs = state.pinnacle.space.outputs_for_element(&self.window);
+        outputs.extend(outputs_before);
+        outputs.dedup();
+        for output in outputs {
+            state.schedule_render(&output);
+        }
+    }
+
+    fn relative_motion(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        focus: Option<(<State as SeatHandler>::PointerFocus, Point<i32, Logical>)>,
+        event: &RelativeMotionEvent,
+    ) {
+        handle.relative_motion(data, focus, event);
+    }
+
+    fn button(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &ButtonEvent,
+    ) {
+        handle.button(data, event);
+
+        if !handle.current_pressed().contains(&self.button_used) {
+            handle.unset_grab(self, data, event.serial, event.time, true);
+        }
+    }
+
+    fn axis(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        details: AxisFrame,
+    ) {
+        handle.axis(data, details);
+    }
+
+    fn start_data(&self) -> &GrabStartData<State> {
+        &self.start_data
+    }
+
+    fn unset(&mut self, data: &mut State) {
+        self.window.with_state_mut(|state| state.swap_ghost = false);
+
+        if !self.window.alive() {
+            return;
+        }
+
+        let Some(output) = self.window.output(&data.pinnacle) else {
+            return;
+        };
+
+        match self.window_under(&data.pinnacle, self.location) {
+            Some(window_under) => {
+                debug!("Swapping dropped window with the tile under it");
+                data.pinnacle
+                    .swap_window_positions(&self.window, &window_under);
+            }
+            // Put the window back into its tile
+            None => data.pinnacle.request_layout(&output),
+        }
+
+        data.schedule_render(&output);
+    }
+
+    fn gesture_swipe_begin(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &GestureSwipeBeginEvent,
+    ) {
+        handle.gesture_swipe_begin(data, event);
+    }
+
+    fn gesture_swipe_update(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &GestureSwipeUpdateEvent,
+    ) {
+        handle.gesture_swipe_update(data, event);
+    }
+
+    fn gesture_swipe_end(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &GestureSwipeEndEvent,
+    ) {
+        handle.gesture_swipe_end(data, event);
+    }
+
+    fn gesture_pinch_begin(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &GesturePinchBeginEvent,
+    ) {
+        handle.gesture_pinch_begin(data, event);
+    }
+
+    fn gesture_pinch_update(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &GesturePinchUpdateEvent,
+    ) {
+        handle.gesture_pinch_update(data, event);
+    }
+
+    fn gesture_pinch_end(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &GesturePinchEndEvent,
+    ) {
+        handle.gesture_pinch_end(data, event);
+    }
+
+    fn gesture_hold_begin(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &GestureHoldBeginEvent,
+    ) {
+        handle.gesture_hold_begin(data, event);
+    }
+
+    fn gesture_hold_end(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &GestureHoldEndEvent,
+    ) {
+        handle.gesture_hold_end(data, event);
+    }
+}
@@ -1,15 +1,20 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 mod xdg_shell;
-mod xwayland;
+pub mod xwayland;
 
-use std::{mem, os::fd::OwnedFd, time::Duration};
+use std::{mem, os::fd::OwnedFd, sync::Arc, time::Duration};
 
 use smithay::{
-    backend::renderer::utils::{self, with_renderer_surface_state},
-    delegate_compositor, delegate_data_control, delegate_data_device, delegate_fractional_scale,
-    delegate_layer_shell, delegate_output, delegate_presentation, delegate_primary_selection,
-    delegate_relative_pointer, delegate_seat, delegate_shm, delegate_viewporter,
+    backend::{
+        input::TabletToolDescriptor,
+        renderer::utils::{self, with_renderer_surface_state},
+    },
+    delegate_compositor, delegate_content_type, delegate_cursor_shape, delegate_data_control,
+    delegate_data_device, delegate_fractional_scale, delegate_layer_shell, delegate_output,
+    delegate_pointer_gestures, delegate_presentation, delegate_primary_selection,
+    delegate_relative_pointer, delegate_seat, delegate_security_context, delegate_shm,
+    delegate_tablet_manager, delegate_viewporter, delegate_xdg_activation,
     desktop::{
         self, find_popup_root_surface, get_popup_toplevel_coords, layer_map_for_output,
         utils::surface_primary_scanout_output, PopupKind, WindowSurfaceType,
@@ -17,7 +22,10 @@ use smithay::{
     input::{pointer::CursorImageStatus, Seat, SeatHandler, SeatState},
     output::Output,
     reexports::{
-        calloop::Interest,
+        calloop::{
+            timer::{TimeoutAction, Timer},
+            Interest,
+        },
         wayland_protocols::xdg::shell::server::xdg_positioner::ConstraintAdjustment,
         wayland_server::{
             protocol::{
@@ -27,7 +35,7 @@ use smithay::{
             Client, Resource,
         },
     },
-    utils::{Logical, Rectangle, SERIAL_COUNTER},
+    utils::{Logical, Physical, Rectangle, Size, SERIAL_COUNTER},
     wayland::{
         buffer::BufferHandler,
         compositor::{
@@ -38,6 +46,9 @@ use smithay::{
         fractional_scale::{self, FractionalScaleHandler},
         output::OutputHandler,
         seat::WaylandFocus,
+        security_context::{
+            SecurityContext, SecurityContextHandler, SecurityContextListenerSource,
+        },
         selection::{
             data_device::{
                 set_data_device_focus, ClientDndGrabHandler, DataDeviceHandler, DataDeviceState,
@@ -53,7 +64,11 @@ use smithay::{
             wlr_layer::{self, Layer, LayerSurfaceData, WlrLayerShellHandler, WlrLayerShellState},
             xdg::{PopupSurface, XdgPopupSurfaceData, XdgToplevelSurfaceData},
         },
-        shm::{ShmHandler, ShmState},
+        shm::{self, ShmHandler, ShmState},
+        tablet_manager::TabletSeatHandler,
+        xdg_activation::{
+            XdgActivationHandler, XdgActivationState, XdgActivationToken, XdgActivationTokenData,
+        },
     },
     xwayland::{X11Wm, XWaylandClientData},
 };
@@ -61,13 +76,26 @@ use tracing::{error, trace, warn};
 
 use crate::{
     backend::Backend,
-    delegate_gamma_control, delegate_screencopy,
+    delegate_alpha_modifier, delegate_drm_syncobj, delegate_ext_foreign_toplevel_list,
+    delegate_ext_workspace, delegate_gamma_control, delegate_image_copy_capture,
+    delegate_screencopy, delegate_xdg_toplevel_drag,
     focus::{keyboard::KeyboardFocusTarget, pointer::PointerFocusTarget},
     protocol::{
+        drm_syncobj::{self, DrmSyncobjHandler, DrmSyncobjState},
+        ext_foreign_toplevel_list::{
+            ExtForeignToplevelListHandler, ExtForeignToplevelListState, ForeignToplevel,
+        },
+        ext_workspace::{ExtWorkspaceHandler, ExtWorkspaceManagerState, Workspace, WorkspaceGroup},
         gamma_control::{GammaControlHandler, GammaControlManagerState},
+        image_copy_capture::{
+            ImageCaptureSource, ImageCopyCaptureHandler, ImageCopyFrame, SHM_FORMATS,
+        },
         screencopy::{Screencopy, ScreencopyHandler},
+        xdg_toplevel_drag::{XdgToplevelDragHandler, XdgToplevelDragManagerState},
     },
+    screenshot::ScreenshotTarget,
     state::{ClientState, Pinnacle, State, WithState},
+    tag::TagId,
 };
 
 impl BufferHandler for State {
@@ -92,7 +120,30 @@ impl CompositorHandler for State {
                         _ => None,
                     })
             });
-            if let Some(dmabuf) = maybe_dmabuf {
+            // Explicitly synced buffers wait on their acquire point instead of implicit sync
+            if let Some(acquire_point) = drm_syncobj::pending_acquire_point(surface) {
+                match acquire_point.generate_blocker() {
+                    Ok((blocker, source)) => {
+                        let client = surface
+                            .client()
+                            .expect("Surface has no client/is no longer alive");
+                        let res =
+                            state
+                                .pinnacle
+                                .loop_handle
+                                .insert_source(source, move |_, _, state| {
+                                    state.client_compositor_state(&client).blocker_cleared(
+                                        state,
+                                        &state.pinnacle.display_handle.clone(),
+                                    );
+                                });
+                        if res.is_ok() {
+                            compositor::add_blocker(surface, blocker);
+                        }
+                    }
+                    Err(err) => warn!("Failed to wait on acquire point: {err}"),
+                }
+            } else if let Some(dmabuf) = maybe_dmabuf {
                 if let Ok((blocker, source)) = dmabuf.generate_blocker(Interest::READ) {
                     let client = surface
                         .client()
@@ -118,6 +169,8 @@ impl CompositorHandler for State {
     fn commit(&mut self, surface: &WlSurface) {
         trace!("commit on surface {surface:?}");
 
+        drm_syncobj::update_release_point(surface);
+
         utils::on_commit_buffer_handler::<State>(surface);
 
         X11Wm::commit_hook::<State>(surface);
@@ -132,8 +185,8 @@ impl CompositorHandler for State {
         if !compositor::is_sync_subsurface(surface) {
             if let Some(window) = self.pinnacle.window_for_surface(&root) {
                 window.on_commit();
-                if let Some(loc) = window.with_state_mut(|state| state.target_loc.take()) {
-                    self.pinnacle.space.map_element(window.clone(), loc, false);
+                for output in self.pinnacle.update_layout_transactions() {
+                    self.schedule_render(&output);
                 }
             }
         };
@@ -155,11 +208,14 @@ impl CompositorHandler for State {
 
             if is_mapped {
                 self.pinnacle.new_windows.retain(|win| win != &new_window);
-                self.pinnacle.windows.push(new_window.clone());
 
-                if let Some(output) = self.pinnacle.focused_output() {
+                let output = self.pinnacle.new_window_output();
+                self.pinnacle
+                    .insert_new_window(new_window.clone(), output.as_ref());
+
+                if let Some(output) = output {
                     tracing::debug!("Placing toplevel");
-                    new_window.place_on_output(output);
+                    new_window.place_on_output(&output);
                     output.with_state_mut(|state| state.focus_stack.set_focus(new_window.clone()));
                 }
 
@@ -173,11 +229,15 @@ impl CompositorHandler for State {
                 self.pinnacle.raise_window(new_window.clone(), true);
 
                 self.pinnacle.apply_window_rules(&new_window);
+                self.follow_new_window(&new_window);
 
-                if let Some(focused_output) = self.pinnacle.focused_output().cloned() {
-                    self.pinnacle.request_layout(&focused_output);
+                if let Some(output) = new_window
+                    .output(&self.pinnacle)
+                    .or_else(|| self.pinnacle.focused_output().cloned())
+                {
+                    self.pinnacle.request_layout(&output);
                     new_window.send_frame(
-                        &focused_output,
+                        &output,
                         self.pinnacle.clock.now(),
                         Some(Duration::ZERO),
                         surface_primary_scanout_output,
@@ -185,6 +245,11 @@ impl CompositorHandler for State {
                 }
 
                 self.pinnacle.loop_handle.insert_idle(move |state| {
+                    // Windows placed on an inactive tag open in the background
+                    if !new_window.is_on_active_tag() {
+                        return;
+                    }
+
                     state
                         .pinnacle
                         .seat
@@ -289,6 +354,7 @@ impl Pinnacle {
 
                 if !initial_configure_sent {
                     tracing::debug!("Initial configure");
+                    self.configure_from_window_rules(&window);
                     toplevel.send_configure();
                 }
             }
@@ -345,15 +411,17 @@ impl Pinnacle {
 impl ClientDndGrabHandler for State {
     fn started(
         &mut self,
-        _source: Option<WlDataSource>,
+        source: Option<WlDataSource>,
         icon: Option<WlSurface>,
         _seat: Seat<Self>,
     ) {
         self.pinnacle.dnd_icon = icon;
+        self.pinnacle.dnd_source = source;
     }
 
     fn dropped(&mut self, _seat: Seat<Self>) {
         self.pinnacle.dnd_icon = None;
+        self.pinnacle.dnd_source = None;
     }
 }
 
@@ -428,6 +496,11 @@ impl SeatHandler for State {
     }
 
     fn focus_changed(&mut self, seat: &Seat<Self>, focused: Option<&Self::KeyboardFocus>) {
+        if let Some(KeyboardFocusTarget::Window(window)) = focused {
+            self.pinnacle.record_focus(window);
+            self.pinnacle.set_window_urgent(window, false);
+        }
+
         let focus_client = focused.and_then(|foc_target| {
             self.pinnacle
                 .display_handle
@@ -440,6 +513,16 @@ impl SeatHandler for State {
 }
 delegate_seat!(State);
 
+impl TabletSeatHandler for State {
+    fn tablet_tool_image(&mut self, _tool: &TabletToolDescriptor, image: CursorImageStatus) {
+        // TODO: tablet tools should have their own cursors
+        self.pinnacle.cursor_status = image;
+    }
+}
+delegate_tablet_manager!(State);
+
+delegate_cursor_shape!(State);
+
 impl ShmHandler for State {
     fn shm_state(&self) -> &ShmState {
         &self.pinnacle.shm_state
@@ -512,8 +595,72 @@ delegate_fractional_scale!(State);
 
 delegate_relative_pointer!(State);
 
+delegate_pointer_gestures!(State);
+
 delegate_presentation!(State);
 
+delegate_content_type!(State);
+
+impl SecurityContextHandler for State {
+    fn context_created(
+        &mut self,
+        source: SecurityContextListenerSource,
+        security_context: SecurityContext,
+    ) {
+        let res =
+            self.pinnacle
+                .loop_handle
+                .insert_source(source, move |client_stream, _, state| {
+                    let client_state = ClientState {
+                        security_context: Some(security_context.clone()),
+                        ..ClientState::default()
+                    };
+                    if let Err(err) = state
+                        .pinnacle
+                        .display_handle
+                        .insert_client(client_stream, Arc::new(client_state))
+                    {
+                        warn!("Failed to insert sandboxed client: {err}");
+                    }
+                });
+
+        if let Err(err) = res {
+            error!("Failed to insert security context listener into event loop: {err}");
+        }
+    }
+}
+delegate_security_context!(State);
+
+impl XdgActivationHandler for State {
+    fn activation_state(&mut self) -> &mut XdgActivationState {
+        &mut self.pinnacle.xdg_activation_state
+    }
+
+    fn request_activation(
+        &mut self,
+        token: XdgActivationToken,
+        _token_data: XdgActivationTokenData,
+        surface: WlSurface,
+    ) {
+        // Activation requests would let clients steal focus,
+        // so unfocused windows only get marked as urgent
+        if let Some(window) = self.pinnacle.window_for_surface(&surface) {
+            // Windows that aren't on any output couldn't be focused, so they're brought
+            // to the primary output
+            if window.output(&self.pinnacle).is_none() {
+                if let Some(output) = self.pinnacle.new_window_output() {
+                    window.place_on_output(&output);
+                    self.pinnacle.request_layout(&output);
+                }
+            }
+            self.pinnacle.mark_urgent_unless_focused(&window);
+        }
+
+        self.pinnacle.xdg_activation_state.remove_token(&token);
+    }
+}
+delegate_xdg_activation!(State);
+
 impl WlrLayerShellHandler for State {
     fn shell_state(&mut self) -> &mut WlrLayerShellState {
         &mut self.pinnacle.layer_shell_state
@@ -638,7 +785,237 @@ impl GammaControlHandler for State {
 }
 delegate_gamma_control!(State);
 
+impl ExtWorkspaceHandler for State {
+    fn ext_workspace_manager_state(&mut self) -> &mut ExtWorkspaceManagerState {
+        &mut self.pinnacle.ext_workspace_manager_state
+    }
+
+    fn activate_workspace(&mut self, id: TagId) {
+        let Some(tag) = id.tag(&self.pinnacle) else {
+            return;
+        };
+        let Some(output) = tag.output(&self.pinnacle) else {
+            return;
+        };
+
+        // Activating a workspace switches to its tag, like clicking on a tag in a bar would
+        for op_tag in output.with_state(|state| state.tags.clone()) {
+            op_tag.set_active(false, self);
+        }
+        tag.set_active(true, self);
+
+        self.pinnacle.fixup_xwayland_window_layering();
+
+        self.pinnacle.request_layout(&output);
+        self.update_focus(&output);
+        self.schedule_render(&output);
+    }
+
+    fn deactivate_workspace(&mut self, id: TagId) {
+        let Some(tag) = id.tag(&self.pinnacle) else {
+            return;
+        };
+        let Some(output) = tag.output(&self.pinnacle) else {
+            return;
+        };
+
+        tag.set_active(false, self);
+
+        self.pinnacle.fixup_xwayland_window_layering();
+
+        self.pinnacle.request_layout(&output);
+        self.update_focus(&output);
+        self.schedule_render(&output);
+    }
+}
+delegate_ext_workspace!(State);
+
+impl XdgToplevelDragHandler for State {
+    fn xdg_toplevel_drag_manager_state(&mut self) -> &mut XdgToplevelDragManagerState {
+        &mut self.pinnacle.xdg_toplevel_drag_manager_state
+    }
+}
+delegate_xdg_toplevel_drag!(State);
+
+delegate_alpha_modifier!(State);
+
+impl ExtForeignToplevelListHandler for State {
+    fn ext_foreign_toplevel_list_state(&mut self) -> &mut ExtForeignToplevelListState {
+        &mut self.pinnacle.ext_foreign_toplevel_list_state
+    }
+}
+delegate_ext_foreign_toplevel_list!(State);
+
+impl DrmSyncobjHandler for State {
+    fn drm_syncobj_state(&mut self) -> &mut DrmSyncobjState {
+        self.pinnacle
+            .drm_syncobj_state
+            .as_mut()
+            .expect("drm syncobj global was created without its state")
+    }
+}
+delegate_drm_syncobj!(State);
+
+impl ImageCopyCaptureHandler for State {
+    fn capture_source_size(&mut self, source: &ImageCaptureSource) -> Option<Size<i32, Physical>> {
+        let target = self.pinnacle.image_capture_target(source)?;
+        target.size(&self.pinnacle).ok()
+    }
+
+    fn capture_frame(&mut self, frame: ImageCopyFrame) {
+        let Some(target) = self.pinnacle.image_capture_target(frame.source()) else {
+            frame.stop();
+            return;
+        };
+
+        let output = match &target {
+            ScreenshotTarget::Output(output) => Some(output.clone()),
+            ScreenshotTarget::Window(window) => window.output(&self.pinnacle),
+            _ => None,
+        };
+
+        // Frames are copied once per refresh so clients capturing in a loop
+        // don't make the compositor render as fast as it can
+        let interval = output
+            .and_then(|op| op.current_mode())
+            .filter(|mode| mode.refresh > 0)
+            .map(|mode| Duration::from_secs_f64(1000.0 / f64::from(mode.refresh)))
+            .unwrap_or(Duration::from_millis(16));
+
+        let mut frame = Some(frame);
+        let res = self.pinnacle.loop_handle.insert_source(
+            Timer::from_duration(interval),
+            move |_, _, state| {
+                if let Some(frame) = frame.take() {
+                    state.copy_image_frame(frame);
+                }
+                TimeoutAction::Drop
+            },
+        );
+
+        if let Err(err) = res {
+            error!("Failed to insert image copy capture timer: {err}");
+        }
+    }
+}
+delegate_image_copy_capture!(State);
+
+impl State {
+    /// Render the source of an image copy capture frame into its shm buffer.
+    fn copy_image_frame(&mut self, frame: ImageCopyFrame) {
+        let Some(target) = self.pinnacle.image_capture_target(frame.source()) else {
+            frame.stop();
+            return;
+        };
+
+        let screenshot = match self.screenshot(&target, frame.paint_cursors()) {
+            Ok(screenshot) => screenshot,
+            Err(err) => {
+                // Dropping the frame fails it
+                warn!("Failed to capture image copy frame: {err}");
+                return;
+            }
+        };
+
+        let (width, height) = screenshot.size();
+        let size = Size::from((width as i32, height as i32));
+        if size != frame.size() {
+            frame.resize(size);
+            return;
+        }
+
+        let pixels = screenshot.into_raw();
+
+        let res = shm::with_buffer_contents_mut(frame.buffer(), |shm_ptr, shm_len, data| {
+            if !SHM_FORMATS.contains(&data.format)
+                || data.stride != size.w * 4
+                || data.height != size.h
+                || shm_len != pixels.len()
+            {
+                return false;
+            }
+
+            // SAFETY: `shm_ptr` is assumed to be valid for writes of `shm_len`,
+            // which is the length of `pixels` as checked above.
+            let shm = unsafe { std::slice::from_raw_parts_mut(shm_ptr, shm_len) };
+
+            // Screenshots are RGBA, while the shm formats are BGRA in memory
+            for (dst, src) in shm.chunks_exact_mut(4).zip(pixels.chunks_exact(4)) {
+                dst.copy_from_slice(&[src[2], src[1], src[0], src[3]]);
+            }
+
+            true
+        });
+
+        match res {
+            Ok(true) => frame.submit(),
+            Ok(false) | Err(_) => frame.reject_buffer(),
+        }
+    }
+}
+
 impl Pinnacle {
+    /// Advertise the current tags on every output through `ext-workspace-v1`.
+    ///
+    /// Tags hidden in dynamic mode aren't advertised.
+    ///
+    /// RefCell Safety: This uses RefCells on every mapped output.
+    pub fn update_ext_workspaces(&mut self) {
+        let groups = self
+            .space
+            .outputs()
+            .map(|output| WorkspaceGroup {
+                output: output.clone(),
+                workspaces: output.with_state(|state| {
+                    state
+                        .tags
+                        .iter()
+                        .filter(|tag| !tag.hidden())
+                        .map(|tag| Workspace {
+                            id: tag.id(),
+                            name: tag.name(),
+                            active: tag.active(),
+                        })
+                        .collect()
+                }),
+            })
+            .collect::<Vec<_>>();
+
+        self.ext_workspace_manager_state.update::<State>(groups);
+    }
+
+    /// Get what an image copy capture source captures, if it still exists.
+    fn image_capture_target(&self, source: &ImageCaptureSource) -> Option<ScreenshotTarget> {
+        match source {
+            ImageCaptureSource::Output(output) => self
+                .space
+                .outputs()
+                .any(|op| op == output)
+                .then(|| ScreenshotTarget::Output(output.clone())),
+            ImageCaptureSource::Toplevel(id) => id.window(self).map(ScreenshotTarget::Window),
+            ImageCaptureSource::Invalid => None,
+        }
+    }
+
+    /// Advertise the current windows through `ext-foreign-toplevel-list-v1`.
+    ///
+    /// RefCell Safety: This uses RefCells on every window.
+    pub fn update_ext_foreign_toplevels(&mut self) {
+        let toplevels = self
+            .windows
+            .iter()
+            .filter(|win| !win.is_x11_override_redirect())
+            .map(|win| ForeignToplevel {
+                id: win.with_state(|state| state.id),
+                title: win.title().unwrap_or_default(),
+                app_id: win.class().unwrap_or_default(),
+            })
+            .collect::<Vec<_>>();
+
+        self.ext_foreign_toplevel_list_state
+            .update::<State>(toplevels);
+    }
+
     fn position_popup(&self, popup: &PopupSurface) {
         trace!("State::position_popup");
         let Ok(root) = find_popup_root_surface(&PopupKind::Xdg(popup.clone())) else {
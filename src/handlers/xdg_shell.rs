@@ -17,7 +17,8 @@ use smithay::{
     wayland::{
         seat::WaylandFocus,
         shell::xdg::{
-            PopupSurface, PositionerState, ToplevelSurface, XdgShellHandler, XdgShellState,
+            PopupSurface, PositionerState, ShellClient, ToplevelSurface, XdgShellHandler,
+            XdgShellState,
         },
     },
 };
@@ -34,6 +35,10 @@ impl XdgShellHandler for State {
         &mut self.pinnacle.xdg_shell_state
     }
 
+    fn client_pong(&mut self, client: ShellClient) {
+        self.handle_pong(&client);
+    }
+
     fn new_toplevel(&mut self, surface: ToplevelSurface) {
         surface.with_pending_state(|state| {
             state.states.set(xdg_toplevel::State::TiledTop);
@@ -46,8 +51,27 @@ impl XdgShellHandler for State {
         self.pinnacle.new_windows.push(window);
     }
 
+    fn title_changed(&mut self, surface: ToplevelSurface) {
+        if let Some(window) = self.pinnacle.window_for_surface(surface.wl_surface()) {
+            self.pinnacle.reapply_window_rules(&window);
+        }
+    }
+
+    fn app_id_changed(&mut self, surface: ToplevelSurface) {
+        if let Some(window) = self.pinnacle.window_for_surface(surface.wl_surface()) {
+            self.pinnacle.reapply_window_rules(&window);
+        }
+    }
+
     fn toplevel_destroyed(&mut self, surface: ToplevelSurface) {
         tracing::debug!("toplevel destroyed");
+
+        if let Some(window) = self.pinnacle.window_for_surface(surface.wl_surface()) {
+            self.pinnacle.remember_floating_geometry(&window);
+            self.pinnacle.remove_window_from_group(&window);
+            self.pinnacle.set_window_urgent(&window, false);
+        }
+
         self.pinnacle.windows.retain(|window| {
             window
                 .wl_surface()
@@ -219,6 +243,21 @@ impl XdgShellHandler for State {
         }
 
         let wl_surface = surface.wl_surface();
+
+        // Fake fullscreened windows are only told they're fullscreen and keep their geometry
+        if let Some(window) = self
+            .pinnacle
+            .window_for_surface(wl_surface)
+            .filter(|win| win.with_state(|state| state.fake_fullscreen))
+        {
+            window.with_state_mut(|state| state.client_fullscreen = true);
+            surface.with_pending_state(|state| {
+                state.states.set(xdg_toplevel::State::Fullscreen);
+            });
+            surface.send_configure();
+            return;
+        }
+
         let output = wl_output
             .as_ref()
             .and_then(Output::from_resource)
@@ -284,13 +323,26 @@ impl XdgShellHandler for State {
             state.fullscreen_output.take();
         });
 
-        surface.send_pending_configure();
-
         let Some(window) = self.pinnacle.window_for_surface(surface.wl_surface()) else {
+            surface.send_pending_configure();
             tracing::error!("wl_surface had no window");
             return;
         };
 
+        let was_fake_fullscreened = window.with_state_mut(|state| {
+            let was_fake_fullscreened = state.fake_fullscreen && state.client_fullscreen;
+            state.client_fullscreen = false;
+            was_fake_fullscreened
+        });
+
+        if was_fake_fullscreened {
+            // The window keeps whatever size it has
+            let size = surface.current_state().size;
+            surface.with_pending_state(|state| state.size = size);
+        }
+
+        surface.send_pending_configure();
+
         if window.with_state(|state| state.fullscreen_or_maximized.is_fullscreen()) {
             window.toggle_fullscreen();
             if let Some(output) = window.output(&self.pinnacle) {
@@ -300,6 +352,14 @@ impl XdgShellHandler for State {
     }
 
     fn maximize_request(&mut self, surface: ToplevelSurface) {
+        if !surface
+            .current_state()
+            .capabilities
+            .contains(xdg_toplevel::WmCapabilities::Maximize)
+        {
+            return;
+        }
+
         let Some(window) = self.pinnacle.window_for_surface(surface.wl_surface()) else {
             return;
         };
@@ -308,7 +368,10 @@ impl XdgShellHandler for State {
             window.toggle_maximized();
         }
 
+        // The layout sends the window its maximized size, but every request
+        // still needs a configure in response
         let Some(output) = window.output(&self.pinnacle) else {
+            surface.send_configure();
             return;
         };
         self.pinnacle.request_layout(&output);
@@ -319,11 +382,13 @@ impl XdgShellHandler for State {
             return;
         };
 
+        // Floating windows get back the geometry they had before being maximized
         if window.with_state(|state| state.fullscreen_or_maximized.is_maximized()) {
             window.toggle_maximized();
         }
 
         let Some(output) = window.output(&self.pinnacle) else {
+            surface.send_configure();
             return;
         };
         self.pinnacle.request_layout(&output);
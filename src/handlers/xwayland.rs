@@ -1,5 +1,7 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+pub mod x11_worker;
+
 use smithay::{
     desktop::Window,
     utils::{Logical, Point, Rectangle, SERIAL_COUNTER},
@@ -18,7 +20,7 @@ use smithay::{
         },
     },
     xwayland::{
-        xwm::{Reorder, WmWindowType, XwmId},
+        xwm::{Reorder, WmWindowProperty, WmWindowType, XwmId},
         X11Surface, X11Wm, XwmHandler,
     },
 };
@@ -57,16 +59,16 @@ impl XwmHandler for State {
             .element_bbox(&window)
             .expect("called element_bbox on an unmapped window");
 
-        let output_size = self
-            .pinnacle
-            .focused_output()
+        let output = self.pinnacle.new_window_output();
+
+        let output_size = output
+            .as_ref()
             .and_then(|op| self.pinnacle.space.output_geometry(op))
             .map(|geo| geo.size)
             .unwrap_or((2, 2).into());
 
-        let output_loc = self
-            .pinnacle
-            .focused_output()
+        let output_loc = output
+            .as_ref()
             .map(|op| op.current_location())
             .unwrap_or((0, 0).into());
 
@@ -94,7 +96,7 @@ impl XwmHandler for State {
             .expect("failed to configure x11 window");
         // TODO: ssd
 
-        if let Some(output) = self.pinnacle.focused_output() {
+        if let Some(output) = output.as_ref() {
             window.place_on_output(output);
         }
 
@@ -104,11 +106,18 @@ impl XwmHandler for State {
             });
         }
 
+        // Rules matching the role are applied once it has been read
+        if let Some(x11_worker) = self.pinnacle.x11_worker.as_ref() {
+            x11_worker.read_window_role(surface.window_id());
+        }
+
         // TODO: will an unmap -> map duplicate the window
-        self.pinnacle.windows.push(window.clone());
+        self.pinnacle
+            .insert_new_window(window.clone(), output.as_ref());
         self.pinnacle.raise_window(window.clone(), true);
 
         self.pinnacle.apply_window_rules(&window);
+        self.follow_new_window(&window);
 
         if let Some(output) = window.output(&self.pinnacle) {
             output.with_state_mut(|state| state.focus_stack.set_focus(window.clone()));
@@ -116,6 +125,11 @@ impl XwmHandler for State {
         }
 
         self.pinnacle.loop_handle.insert_idle(move |state| {
+            // Windows placed on an inactive tag open in the background
+            if !window.is_on_active_tag() {
+                return;
+            }
+
             state
                 .pinnacle
                 .seat
@@ -248,6 +262,10 @@ impl XwmHandler for State {
         if let Some(win) = win {
             debug!("removing x11 window from windows");
 
+            self.pinnacle.remember_floating_geometry(&win);
+            self.pinnacle.remove_window_from_group(&win);
+            self.pinnacle.set_window_urgent(&win, false);
+
             // INFO: comparing the windows doesn't work so wlsurface it is
             // self.windows.retain(|elem| &win != elem);
             self.pinnacle
@@ -352,6 +370,32 @@ impl XwmHandler for State {
         self.pinnacle.space.map_element(win, geometry.loc, true);
     }
 
+    fn property_notify(&mut self, _xwm: XwmId, window: X11Surface, property: WmWindowProperty) {
+        let Some(win) = self
+            .pinnacle
+            .windows
+            .iter()
+            .find(|elem| elem.x11_surface() == Some(&window))
+            .cloned()
+        else {
+            return;
+        };
+
+        match property {
+            WmWindowProperty::Hints => {
+                if window.hints().is_some_and(|hints| hints.urgent) {
+                    self.pinnacle.mark_urgent_unless_focused(&win);
+                } else {
+                    self.pinnacle.set_window_urgent(&win, false);
+                }
+            }
+            WmWindowProperty::Title | WmWindowProperty::Class => {
+                self.pinnacle.reapply_window_rules(&win);
+            }
+            _ => (),
+        }
+    }
+
     fn maximize_request(&mut self, _xwm: XwmId, window: X11Surface) {
         window
             .set_maximized(true)
@@ -366,6 +410,9 @@ impl XwmHandler for State {
 
         if !window.with_state(|state| state.fullscreen_or_maximized.is_maximized()) {
             window.toggle_maximized();
+            if let Some(output) = window.output(&self.pinnacle) {
+                self.pinnacle.request_layout(&output);
+            }
         }
     }
 
@@ -383,6 +430,9 @@ impl XwmHandler for State {
 
         if window.with_state(|state| state.fullscreen_or_maximized.is_maximized()) {
             window.toggle_maximized();
+            if let Some(output) = window.output(&self.pinnacle) {
+                self.pinnacle.request_layout(&output);
+            }
         }
     }
 
@@ -398,6 +448,12 @@ impl XwmHandler for State {
             return;
         };
 
+        // Fake fullscreened windows are only told they're fullscreen and keep their geometry
+        if window.with_state(|state| state.fake_fullscreen) {
+            window.with_state_mut(|state| state.client_fullscreen = true);
+            return;
+        }
+
         if !window.with_state(|state| state.fullscreen_or_maximized.is_fullscreen()) {
             window.toggle_fullscreen();
             if let Some(output) = window.output(&self.pinnacle) {
@@ -418,6 +474,8 @@ impl XwmHandler for State {
             return;
         };
 
+        window.with_state_mut(|state| state.client_fullscreen = false);
+
         if window.with_state(|state| state.fullscreen_or_maximized.is_fullscreen()) {
             window.toggle_fullscreen();
             if let Some(output) = window.output(&self.pinnacle) {
@@ -0,0 +1,190 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! A connection to Xwayland for requests Smithay's X11 window manager doesn't make.
+//!
+//! Xwayland may be waiting on the compositor while it handles a request, so waiting for
+//! a reply in the event loop can deadlock. Requests are made on a separate thread instead,
+//! and their results are handled in the event loop once they arrive.
+
+use std::sync::mpsc;
+
+use anyhow::Context;
+use smithay::reexports::calloop::{
+    channel::{self, Event},
+    LoopHandle, RegistrationToken,
+};
+use tracing::warn;
+use x11rb::{
+    connection::Connection,
+    protocol::{
+        randr::ConnectionExt as _,
+        xproto::{self, AtomEnum, ConnectionExt as _},
+    },
+    rust_connection::RustConnection,
+};
+
+use crate::state::{State, WithState};
+
+/// A request to Xwayland.
+#[derive(Debug)]
+enum Request {
+    /// Make the RandR output with the given name primary.
+    SetPrimaryOutput(String),
+    /// Read the `WM_WINDOW_ROLE` property of a window.
+    ReadWindowRole(xproto::Window),
+}
+
+/// The result of a [`Request`] that has one.
+#[derive(Debug)]
+enum Reply {
+    WindowRole {
+        window: xproto::Window,
+        role: Option<String>,
+    },
+}
+
+/// A connection to Xwayland on a separate thread.
+///
+/// The thread exits once this is dropped.
+pub struct X11Worker {
+    requests: mpsc::Sender<Request>,
+    loop_handle: LoopHandle<'static, State>,
+    reply_token: RegistrationToken,
+}
+
+impl X11Worker {
+    /// Connect to the Xwayland instance on `display`.
+    pub fn spawn(display: u32, loop_handle: &LoopHandle<'static, State>) -> anyhow::Result<Self> {
+        let (requests, receiver) = mpsc::channel();
+        let (replies, reply_channel) = channel::channel();
+
+        let reply_token = loop_handle
+            .insert_source(reply_channel, |event, _, state| {
+                if let Event::Msg(reply) = event {
+                    state.handle_x11_reply(reply);
+                }
+            })
+            .map_err(|err| anyhow::anyhow!("failed to insert X11 reply channel: {err}"))?;
+
+        let spawned = std::thread::Builder::new()
+            .name("x11-worker".to_string())
+            .spawn(move || {
+                let (conn, screen) = match x11rb::connect(Some(&format!(":{display}"))) {
+                    Ok(ret) => ret,
+                    Err(err) => {
+                        warn!("Failed to connect to Xwayland: {err}");
+                        return;
+                    }
+                };
+
+                while let Ok(request) = receiver.recv() {
+                    match handle_request(&conn, screen, &request) {
+                        Ok(Some(reply)) => {
+                            // The event loop is gone if this fails
+                            if replies.send(reply).is_err() {
+                                return;
+                            }
+                        }
+                        Ok(None) => (),
+                        Err(err) => warn!("X11 request {request:?} failed: {err}"),
+                    }
+                }
+            });
+
+        if let Err(err) = spawned {
+            loop_handle.remove(reply_token);
+            return Err(err.into());
+        }
+
+        Ok(Self {
+            requests,
+            loop_handle: loop_handle.clone(),
+            reply_token,
+        })
+    }
+
+    /// Make the RandR output with the given name primary.
+    pub fn set_primary_output(&self, name: String) {
+        // This only fails if the thread exited, which it already warned about
+        let _ = self.requests.send(Request::SetPrimaryOutput(name));
+    }
+
+    /// Read the `WM_WINDOW_ROLE` property of `window`.
+    ///
+    /// Once it has been read, it's set on the window and window rules are reapplied.
+    pub fn read_window_role(&self, window: xproto::Window) {
+        let _ = self.requests.send(Request::ReadWindowRole(window));
+    }
+}
+
+impl Drop for X11Worker {
+    fn drop(&mut self) {
+        self.loop_handle.remove(self.reply_token);
+    }
+}
+
+impl State {
+    fn handle_x11_reply(&mut self, reply: Reply) {
+        match reply {
+            Reply::WindowRole { window, role } => {
+                // The window may have been unmapped in the meantime
+                let Some(window) = self.pinnacle.windows.iter().find(|win| {
+                    win.x11_surface()
+                        .is_some_and(|surface| surface.window_id() == window)
+                }) else {
+                    return;
+                };
+                let window = window.clone();
+
+                window.with_state_mut(|state| state.x11_role = role);
+                self.pinnacle.reapply_window_rules(&window);
+            }
+        }
+    }
+}
+
+fn handle_request(
+    conn: &RustConnection,
+    screen: usize,
+    request: &Request,
+) -> anyhow::Result<Option<Reply>> {
+    let root = conn.setup().roots[screen].root;
+
+    match request {
+        Request::SetPrimaryOutput(name) => {
+            let resources = conn.randr_get_screen_resources_current(root)?.reply()?;
+            let output = resources
+                .outputs
+                .into_iter()
+                .find(|&output| {
+                    conn.randr_get_output_info(output, resources.config_timestamp)
+                        .ok()
+                        .and_then(|cookie| cookie.reply().ok())
+                        .is_some_and(|info| info.name == name.as_bytes())
+                })
+                .context("no RandR output with that name")?;
+
+            conn.randr_set_output_primary(root, output)?.check()?;
+
+            Ok(None)
+        }
+        Request::ReadWindowRole(window) => {
+            let atom = conn.intern_atom(true, b"WM_WINDOW_ROLE")?.reply()?.atom;
+            let role = if atom == x11rb::NONE {
+                None
+            } else {
+                let reply = conn
+                    .get_property(false, *window, atom, AtomEnum::STRING, 0, 256)?
+                    .reply()?;
+                String::from_utf8(reply.value)
+                    .ok()
+                    .filter(|role| !role.is_empty())
+            };
+
+            Ok(Some(Reply::WindowRole {
+                window: *window,
+                role,
+            }))
+        }
+    }
+}
@@ -1,30 +1,46 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+pub mod accessibility;
+pub mod focus_follows_mouse;
+pub mod gesture;
+pub mod keybind;
 pub mod libinput;
+pub mod mouse_keys;
+pub mod remap;
+pub mod tablet;
+pub mod touch;
+pub mod warp;
+pub mod xkb;
 
-use std::{collections::HashMap, mem::Discriminant, time::Duration};
+use std::{collections::HashMap, time::Duration};
 
 use crate::{
     focus::{keyboard::KeyboardFocusTarget, pointer::PointerFocusTarget},
     state::WithState,
-    window::WindowElement,
+    window::{window_state::FloatingOrTiled, WindowElement},
 };
 use pinnacle_api_defs::pinnacle::input::v0alpha1::{
-    set_libinput_setting_request::Setting, set_mousebind_request, SetKeybindResponse,
-    SetMousebindResponse,
+    set_mousebind_request, set_scrollbind_request::ScrollDirection,
+    set_swipebind_request::SwipeDirection, SetMousebindResponse, SetScrollbindResponse,
+    SetSwipebindResponse,
 };
 use smithay::{
     backend::input::{
-        AbsolutePositionEvent, Axis, AxisSource, ButtonState, Event, InputBackend, InputEvent,
-        KeyState, KeyboardKeyEvent, PointerAxisEvent, PointerButtonEvent, PointerMotionEvent,
+        AbsolutePositionEvent, Axis, AxisSource, ButtonState, Device, Event, InputBackend,
+        InputEvent, KeyState, KeyboardKeyEvent, Keycode, PointerAxisEvent, PointerButtonEvent,
+        PointerMotionEvent, Switch, SwitchState, SwitchToggleEvent,
     },
     desktop::{layer_map_for_output, space::SpaceElement, WindowSurfaceType},
     input::{
         keyboard::{keysyms, FilterResult, ModifiersState},
         pointer::{AxisFrame, ButtonEvent, MotionEvent, RelativeMotionEvent},
     },
-    reexports::input::{self, Led},
-    utils::{IsAlive, Logical, Point, SERIAL_COUNTER},
+    output::Output,
+    reexports::{
+        calloop::{LoopHandle, RegistrationToken},
+        input::{self, Led},
+    },
+    utils::{IsAlive, Logical, Point, Rectangle, Serial, Size, SERIAL_COUNTER},
     wayland::{
         compositor,
         shell::wlr_layer::{self, KeyboardInteractivity, LayerSurfaceCachedState},
@@ -36,8 +52,19 @@ use xkbcommon::xkb::Keysym;
 
 use crate::state::State;
 
+use self::{
+    accessibility::{SlowKeys, StickyKeys},
+    gesture::BoundSwipe,
+    keybind::{HeldKeybind, KeyRepeat, Keybind},
+    libinput::{DeviceFilter, LibinputSetting},
+    mouse_keys::MouseKeys,
+    remap::KeyRemap,
+    tablet::TabletSetting,
+    touch::TouchOutputMapping,
+};
+
 bitflags::bitflags! {
-    #[derive(Debug, Hash, Copy, Clone, PartialEq, Eq)]
+    #[derive(Debug, Default, Hash, Copy, Clone, PartialEq, Eq)]
     pub struct ModifierMask: u8 {
         const SHIFT = 1;
         const CTRL  = 1 << 1;
@@ -84,36 +111,187 @@ impl From<&ModifiersState> for ModifierMask {
     }
 }
 
+/// What a keybind is for, so configs can list their keybinds.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct KeybindInfo {
+    pub group: Option<String>,
+    pub description: Option<String>,
+}
+
+/// How far a touchpad has to scroll to trigger a scroll bind once.
+const SCROLLBIND_FINGER_DISTANCE: f64 = 15.0;
+/// How far a scroll wheel turns in one notch, in v120 units.
+const SCROLLBIND_WHEEL_DISTANCE: f64 = 120.0;
+
+/// How much scrolling on devices matching `filter` is multiplied by.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScrollFactor {
+    pub filter: DeviceFilter,
+    pub factor: f64,
+}
+
+/// Scrolling that hasn't added up to a scroll bind trigger yet.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct ScrollAccumulator {
+    horizontal: f64,
+    vertical: f64,
+}
+
+/// Add `amount` of scrolling on one axis to `accumulated` and take out every whole `distance`
+/// scrolled, returning how many there were. Negative amounts scroll up or left.
+///
+/// Scrolling stopping or changing direction throws away what was accumulated.
+fn take_scroll_steps(accumulated: &mut f64, amount: f64, distance: f64) -> i32 {
+    if amount == 0.0 || amount.signum() != accumulated.signum() {
+        *accumulated = 0.0;
+    }
+
+    *accumulated += amount;
+    let steps = (*accumulated / distance).trunc();
+    *accumulated -= steps * distance;
+    steps as i32
+}
+
+/// What to do when a laptop's lid is closed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LidSwitchPolicy {
+    /// Do nothing.
+    Ignore,
+    /// Disable internal panels while the lid is closed and an external monitor is enabled.
+    #[default]
+    Clamshell,
+}
+
 #[derive(Default)]
 pub struct InputState {
     pub reload_keybind: Option<(ModifierMask, Keysym)>,
     pub kill_keybind: Option<(ModifierMask, Keysym)>,
-    /// All libinput devices that have been connected
+    /// All connected libinput devices
     pub libinput_devices: Vec<input::Device>,
 
-    pub keybinds:
-        HashMap<(ModifierMask, Keysym), UnboundedSender<Result<SetKeybindResponse, tonic::Status>>>,
+    pub keybinds: HashMap<(ModifierMask, Keysym), Keybind>,
+    /// The group and description of every keybind, in the order they were first set
+    pub keybind_info: Vec<((ModifierMask, Keysym), KeybindInfo)>,
+    /// Keybinds that trigger on release or repeat whose keys are held down
+    held_keybinds: Vec<HeldKeybind>,
+    /// The keyboard's repeat info, kept to repeat keybinds at the same rate
+    pub key_repeat: KeyRepeat,
+    /// Key remaps in the order they were set
+    pub key_remaps: Vec<KeyRemap>,
+    /// Held down keys that were remapped, and what they were remapped to
+    remapped_keys: Vec<(Keycode, Keycode)>,
+    pub sticky_keys: StickyKeys,
+    pub slow_keys: SlowKeys,
+    pub mouse_keys: MouseKeys,
     pub mousebinds: HashMap<
         (ModifierMask, u32, set_mousebind_request::MouseEdge),
         UnboundedSender<Result<SetMousebindResponse, tonic::Status>>,
     >,
-    #[allow(clippy::type_complexity)]
-    pub libinput_settings: HashMap<Discriminant<Setting>, Box<dyn Fn(&mut input::Device) + Send>>,
+    /// Buttons whose press triggered or was held back for a mousebind, so their release
+    /// is held back from clients too
+    bound_buttons: Vec<u32>,
+    pub scrollbinds: HashMap<
+        (ModifierMask, ScrollDirection),
+        UnboundedSender<Result<SetScrollbindResponse, tonic::Status>>,
+    >,
+    scroll_accumulator: ScrollAccumulator,
+    /// Scroll factors in the order they were set
+    pub scroll_factors: Vec<ScrollFactor>,
+    pub swipebinds: HashMap<
+        (ModifierMask, u32, SwipeDirection),
+        UnboundedSender<Result<SetSwipebindResponse, tonic::Status>>,
+    >,
+    /// The ongoing swipe if it's held back for a swipebind
+    bound_swipe: Option<BoundSwipe>,
+    /// Libinput settings in the order they were set
+    pub libinput_settings: Vec<LibinputSetting>,
+    /// Outputs touchscreens are mapped to, in the order they were set
+    pub touch_output_mappings: Vec<TouchOutputMapping>,
+    /// Tablet settings in the order they were set
+    pub tablet_settings: Vec<TabletSetting>,
+    /// Held down tablet pen buttons that were remapped, and what they were remapped to
+    remapped_tablet_buttons: Vec<(u32, u32)>,
 
     /// A keyboard focus target stack that is used when there are exclusive keyboard layer
     /// surfaces. When used, the first item is the previous focus before there were any
     /// exclusive layer surfaces.
     exclusive_layer_focus_stack: Vec<KeyboardFocusTarget>,
+
+    pub lid_switch_policy: LidSwitchPolicy,
+    /// Whether the laptop lid is currently closed
+    pub lid_closed: bool,
+
+    /// Whether windows remember their keyboard layout
+    pub per_window_xkb_layout: bool,
+    /// The active keyboard layout when it was last checked
+    pub xkb_layout: Option<u32>,
+    /// The window that had keyboard focus when the layout was last checked
+    pub xkb_layout_window: Option<WindowElement>,
+
+    /// Whether the pointer moves to the center of an output when it's focused
+    pub warp_pointer_on_output_focus: bool,
+    /// The focused output when pointer warping was last checked
+    pub warp_output: Option<Output>,
+
+    /// The timer that focuses the window the pointer moved onto once the hover delay is up
+    focus_follows_mouse_timer: Option<RegistrationToken>,
 }
 
 impl InputState {
-    pub fn clear(&mut self) {
+    pub fn clear(&mut self, loop_handle: &LoopHandle<State>) {
         self.reload_keybind = None;
         self.kill_keybind = None;
-        self.libinput_devices.clear();
+        // Devices stay connected across config reloads, so forgetting them would stop
+        // the next config's settings from applying until they're replugged
+        for device in self.libinput_devices.iter_mut() {
+            libinput::reset_libinput_settings(device);
+        }
         self.keybinds.clear();
+        self.keybind_info.clear();
+        self.key_remaps.clear();
+        for held in self.held_keybinds.drain(..) {
+            if let Some(timer) = held.repeat_timer {
+                loop_handle.remove(timer);
+            }
+        }
         self.mousebinds.clear();
+        self.scrollbinds.clear();
+        self.scroll_factors.clear();
+        self.swipebinds.clear();
         self.libinput_settings.clear();
+        self.touch_output_mappings.clear();
+        self.tablet_settings.clear();
+        self.lid_switch_policy = LidSwitchPolicy::default();
+        self.per_window_xkb_layout = false;
+        // Modifiers that are still sticky are released on the next key
+        self.sticky_keys.enabled = false;
+        self.slow_keys.delay = None;
+        self.mouse_keys.enabled = false;
+        self.warp_pointer_on_output_focus = false;
+        if let Some(timer) = self.focus_follows_mouse_timer.take() {
+            loop_handle.remove(timer);
+        }
+    }
+
+    /// Get the connected libinput device with the id smithay gives it.
+    pub fn libinput_device(&self, id: &str) -> Option<&input::Device> {
+        self.libinput_devices
+            .iter()
+            .find(|device| device.sysname() == id)
+    }
+
+    /// Set the info of the keybind triggered by `keysym` with `mods` held down.
+    ///
+    /// Setting the info of a keybind again keeps its place in the list.
+    pub fn set_keybind_info(&mut self, mods: ModifierMask, keysym: Keysym, info: KeybindInfo) {
+        match self
+            .keybind_info
+            .iter_mut()
+            .find(|(bind, _)| *bind == (mods, keysym))
+        {
+            Some((_, old_info)) => *old_info = info,
+            None => self.keybind_info.push(((mods, keysym), info)),
+        }
     }
 }
 
@@ -124,8 +302,31 @@ impl std::fmt::Debug for InputState {
             .field("kill_keybind", &self.kill_keybind)
             .field("libinput_devices", &self.libinput_devices)
             .field("keybinds", &self.keybinds)
+            .field("keybind_info", &self.keybind_info)
+            .field("held_keybinds", &self.held_keybinds)
+            .field("key_repeat", &self.key_repeat)
+            .field("key_remaps", &self.key_remaps)
+            .field("remapped_keys", &self.remapped_keys)
+            .field("sticky_keys", &self.sticky_keys)
+            .field("slow_keys", &self.slow_keys)
+            .field("mouse_keys", &self.mouse_keys)
             .field("mousebinds", &self.mousebinds)
+            .field("bound_buttons", &self.bound_buttons)
+            .field("scrollbinds", &self.scrollbinds)
+            .field("scroll_accumulator", &self.scroll_accumulator)
+            .field("swipebinds", &self.swipebinds)
+            .field("bound_swipe", &self.bound_swipe)
             .field("libinput_settings", &"...")
+            .field("lid_switch_policy", &self.lid_switch_policy)
+            .field("lid_closed", &self.lid_closed)
+            .field("per_window_xkb_layout", &self.per_window_xkb_layout)
+            .field("xkb_layout", &self.xkb_layout)
+            .field("xkb_layout_window", &self.xkb_layout_window)
+            .field(
+                "warp_pointer_on_output_focus",
+                &self.warp_pointer_on_output_focus,
+            )
+            .field("warp_output", &self.warp_output)
             .finish()
     }
 }
@@ -138,24 +339,43 @@ impl InputState {
 
 #[derive(Debug)]
 enum KeyAction {
-    CallCallback(UnboundedSender<Result<SetKeybindResponse, tonic::Status>>),
+    CallCallback(Keybind),
+    ReleaseKeybind,
     Quit,
     SwitchVt(i32),
     ReloadConfig,
+    CancelWindowSwitcher,
 }
 
 impl State {
     pub fn process_input_event<B: InputBackend>(&mut self, event: InputEvent<B>) {
         match event {
             // TODO: rest of input events
-
-            // InputEvent::DeviceAdded { device } => todo!(),
-            // InputEvent::DeviceRemoved { device } => todo!(),
+            InputEvent::DeviceAdded { device } => self.tablet_device_added(&device),
+            InputEvent::DeviceRemoved { device } => self.tablet_device_removed(&device),
             InputEvent::Keyboard { event } => self.keyboard::<B>(event),
             InputEvent::PointerMotion { event } => self.pointer_motion::<B>(event),
             InputEvent::PointerMotionAbsolute { event } => self.pointer_motion_absolute::<B>(event),
             InputEvent::PointerButton { event } => self.pointer_button::<B>(event),
             InputEvent::PointerAxis { event } => self.pointer_axis::<B>(event),
+            InputEvent::GestureSwipeBegin { event } => self.gesture_swipe_begin::<B>(event),
+            InputEvent::GestureSwipeUpdate { event } => self.gesture_swipe_update::<B>(event),
+            InputEvent::GestureSwipeEnd { event } => self.gesture_swipe_end::<B>(event),
+            InputEvent::GesturePinchBegin { event } => self.gesture_pinch_begin::<B>(event),
+            InputEvent::GesturePinchUpdate { event } => self.gesture_pinch_update::<B>(event),
+            InputEvent::GesturePinchEnd { event } => self.gesture_pinch_end::<B>(event),
+            InputEvent::GestureHoldBegin { event } => self.gesture_hold_begin::<B>(event),
+            InputEvent::GestureHoldEnd { event } => self.gesture_hold_end::<B>(event),
+            InputEvent::TouchDown { event } => self.touch_down::<B>(event),
+            InputEvent::TouchMotion { event } => self.touch_motion::<B>(event),
+            InputEvent::TouchUp { event } => self.touch_up::<B>(event),
+            InputEvent::TouchCancel { event } => self.touch_cancel::<B>(event),
+            InputEvent::TouchFrame { event } => self.touch_frame::<B>(event),
+            InputEvent::TabletToolAxis { event } => self.tablet_tool_axis::<B>(event),
+            InputEvent::TabletToolProximity { event } => self.tablet_tool_proximity::<B>(event),
+            InputEvent::TabletToolTip { event } => self.tablet_tool_tip::<B>(event),
+            InputEvent::TabletToolButton { event } => self.tablet_tool_button::<B>(event),
+            InputEvent::SwitchToggle { event } => self.switch_toggle::<B>(event),
 
             _ => (),
         }
@@ -185,6 +405,10 @@ impl State {
             .output_geometry(output)
             .expect("called output_geometry on unmapped output");
 
+        // The window attached to a toplevel drag follows the pointer,
+        // so it shouldn't be the drop target.
+        let dragged_window = self.pinnacle.toplevel_drag_window().map(|(win, _)| win);
+
         let mut fullscreen_and_up_split_at = 0;
 
         for (i, win) in self
@@ -192,7 +416,7 @@ impl State {
             .space
             .elements()
             .rev()
-            .filter(|win| win.is_on_active_tag())
+            .filter(|win| win.is_visible())
             .enumerate()
         {
             if win.with_state(|state| state.fullscreen_or_maximized.is_fullscreen()) {
@@ -254,8 +478,9 @@ impl State {
                         .space
                         .elements()
                         .rev()
-                        .filter(|win| win.is_on_active_tag())
+                        .filter(|win| win.is_visible())
                         .take(fullscreen_and_up_split_at)
+                        .filter(|win| Some(*win) != dragged_window.as_ref())
                         .collect::<Vec<_>>(),
                 )
             })
@@ -267,8 +492,9 @@ impl State {
                         .space
                         .elements()
                         .rev()
-                        .filter(|win| win.is_on_active_tag())
+                        .filter(|win| win.is_visible())
                         .skip(fullscreen_and_up_split_at)
+                        .filter(|win| Some(*win) != dragged_window.as_ref())
                         .collect::<Vec<_>>(),
                 )
             })
@@ -303,19 +529,11 @@ impl State {
         pointer.frame(self);
     }
 
-    fn keyboard<I: InputBackend>(&mut self, event: I::KeyboardKeyEvent) {
-        let serial = SERIAL_COUNTER.next_serial();
-        let time = event.time_msec();
-        let press_state = event.state();
-
-        let reload_keybind = self.pinnacle.input_state.reload_keybind;
-        let kill_keybind = self.pinnacle.input_state.kill_keybind;
-
-        let keyboard = self
-            .pinnacle
-            .seat
-            .get_keyboard()
-            .expect("Seat has no keyboard");
+    /// Set the lock key leds of all keyboards to match the current modifier state.
+    pub fn update_keyboard_leds(&mut self) {
+        let Some(keyboard) = self.pinnacle.seat.get_keyboard() else {
+            return;
+        };
 
         let modifiers = keyboard.modifier_state();
 
@@ -327,10 +545,61 @@ impl State {
             leds |= Led::CAPSLOCK;
         }
 
-        // FIXME: Leds only update once another key is pressed.
         for device in self.pinnacle.input_state.libinput_devices.iter_mut() {
             device.led_update(leds);
         }
+    }
+
+    /// Release every key that is currently pressed.
+    ///
+    /// Key releases aren't received while the session is paused, so without this
+    /// keys held while switching VTs (like the ones used to switch) would get stuck.
+    pub fn release_all_keys(&mut self) {
+        let Some(keyboard) = self.pinnacle.seat.get_keyboard() else {
+            return;
+        };
+
+        let time = Duration::from(self.pinnacle.clock.now()).as_millis() as u32;
+
+        for key in keyboard.pressed_keys() {
+            keyboard.input::<(), _>(
+                self,
+                key,
+                KeyState::Released,
+                SERIAL_COUNTER.next_serial(),
+                time,
+                |_, _, _| FilterResult::Forward,
+            );
+        }
+    }
+
+    fn keyboard<I: InputBackend>(&mut self, event: I::KeyboardKeyEvent) {
+        let press_state = event.state();
+        let keycode = self.remap_keycode(&event.device(), event.key_code(), press_state);
+
+        if !self.accept_slow_key(keycode, press_state) {
+            return;
+        }
+
+        self.key(keycode, press_state, event.time_msec());
+    }
+
+    /// Handle a press or release of `keycode` after remapping and slow keys.
+    fn key(&mut self, keycode: Keycode, press_state: KeyState, time: u32) {
+        if self.mouse_key(keycode, press_state, time) {
+            return;
+        }
+
+        let serial = SERIAL_COUNTER.next_serial();
+
+        let reload_keybind = self.pinnacle.input_state.reload_keybind;
+        let kill_keybind = self.pinnacle.input_state.kill_keybind;
+
+        let keyboard = self
+            .pinnacle
+            .seat
+            .get_keyboard()
+            .expect("Seat has no keyboard");
 
         for layer in self.pinnacle.layer_shell_state.layer_surfaces().rev() {
             let data = compositor::with_states(layer.wl_surface(), |states| {
@@ -397,20 +666,36 @@ impl State {
             }
         }
 
+        // The modifier the key is for, to make it sticky
+        let mut key_modifier = None;
+
         let action = keyboard.input(
             self,
-            event.key_code(),
+            keycode,
             press_state,
             serial,
             time,
             |state, modifiers, keysym| {
+                key_modifier = accessibility::modifier_of(keysym.modified_sym());
+
+                if press_state == KeyState::Released && state.release_keybind(keycode) {
+                    return FilterResult::Intercept(KeyAction::ReleaseKeybind);
+                }
+
+                if press_state == KeyState::Pressed
+                    && state.pinnacle.window_switcher.is_some()
+                    && keysym.modified_sym().raw() == keysyms::KEY_Escape
+                {
+                    return FilterResult::Intercept(KeyAction::CancelWindowSwitcher);
+                }
+
                 if press_state == KeyState::Pressed {
                     let mod_mask = ModifierMask::from(modifiers);
 
                     let raw_sym = keysym.raw_syms().iter().next();
                     let mod_sym = keysym.modified_sym();
 
-                    if let (Some(sender), _) | (None, Some(sender)) = (
+                    if let (Some(keybind), _) | (None, Some(keybind)) = (
                         state
                             .pinnacle
                             .input_state
@@ -424,7 +709,7 @@ impl State {
                                 .get(&(mod_mask, *raw_sym))
                         }),
                     ) {
-                        return FilterResult::Intercept(KeyAction::CallCallback(sender.clone()));
+                        return FilterResult::Intercept(KeyAction::CallCallback(keybind.clone()));
                     }
 
                     if kill_keybind == Some((mod_mask, mod_sym)) {
@@ -445,9 +730,10 @@ impl State {
         );
 
         match action {
-            Some(KeyAction::CallCallback(sender)) => {
-                let _ = sender.send(Ok(SetKeybindResponse {}));
+            Some(KeyAction::CallCallback(keybind)) => {
+                self.press_keybind(keycode, keybind);
             }
+            Some(KeyAction::ReleaseKeybind) | None => (),
             Some(KeyAction::SwitchVt(vt)) => {
                 self.switch_vt(vt);
             }
@@ -460,11 +746,31 @@ impl State {
                     .start_config(Some(self.pinnacle.config.dir(&self.pinnacle.xdg_base_dirs)))
                     .expect("failed to restart config");
             }
-            None => (),
+            Some(KeyAction::CancelWindowSwitcher) => {
+                self.cancel_window_switcher();
+            }
+        }
+
+        self.update_sticky_keys(keycode, press_state, key_modifier);
+
+        // Update after the key so lock keys light up as they're pressed
+        self.update_keyboard_leds();
+
+        // The switcher is held open by the modifiers of the keybind that opened it
+        if press_state == KeyState::Released && self.pinnacle.window_switcher.is_some() {
+            let modifiers = keyboard.modifier_state();
+            if !(modifiers.alt || modifiers.ctrl || modifiers.logo || modifiers.shift) {
+                self.finish_window_switcher();
+            }
         }
     }
 
     fn pointer_button<I: InputBackend>(&mut self, event: I::PointerButtonEvent) {
+        self.button(event.button_code(), event.state(), event.time_msec());
+    }
+
+    /// Handle a press or release of `button`.
+    fn button(&mut self, button: u32, button_state: ButtonState, time: u32) {
         let pointer = self
             .pinnacle
             .seat
@@ -478,100 +784,211 @@ impl State {
 
         let serial = SERIAL_COUNTER.next_serial();
 
-        let button = event.button_code();
-
-        let button_state = event.state();
-
         let pointer_loc = pointer.current_location();
 
         let mod_mask = ModifierMask::from(keyboard.modifier_state());
 
-        let mouse_edge = match button_state {
-            ButtonState::Released => set_mousebind_request::MouseEdge::Release,
-            ButtonState::Pressed => set_mousebind_request::MouseEdge::Press,
-        };
+        let input_state = &mut self.pinnacle.input_state;
+        let press_bind = input_state.mousebinds.get(&(
+            mod_mask,
+            button,
+            set_mousebind_request::MouseEdge::Press,
+        ));
+        let release_bind = input_state.mousebinds.get(&(
+            mod_mask,
+            button,
+            set_mousebind_request::MouseEdge::Release,
+        ));
+
+        match button_state {
+            ButtonState::Pressed => {
+                // Clients shouldn't see half of a click that triggers a bind on release
+                if press_bind.is_some() || release_bind.is_some() {
+                    if let Some(stream) = press_bind {
+                        let _ = stream.send(Ok(SetMousebindResponse {}));
+                    }
+                    input_state.bound_buttons.push(button);
+                    return;
+                }
+            }
+            ButtonState::Released => {
+                if let Some(stream) = release_bind {
+                    let _ = stream.send(Ok(SetMousebindResponse {}));
+                }
 
-        if let Some(stream) = self
-            .pinnacle
-            .input_state
-            .mousebinds
-            .get(&(mod_mask, button, mouse_edge))
-        {
-            let _ = stream.send(Ok(SetMousebindResponse {}));
-            return;
+                let len = input_state.bound_buttons.len();
+                input_state.bound_buttons.retain(|btn| *btn != button);
+                if input_state.bound_buttons.len() != len {
+                    return;
+                }
+            }
         }
 
         // If the button was clicked, focus on the window below if exists, else
         // unfocus on windows.
         if button_state == ButtonState::Pressed {
-            if let Some((focus, _)) = self.pointer_focus_target_under(pointer_loc) {
-                // NOTE: *Do not* set keyboard focus to an override redirect window. This leads
-                // |     to wonky things like right-click menus not correctly getting pointer
-                // |     clicks or showing up at all.
+            self.focus_under(pointer_loc, serial);
+        }
+
+        pointer.button(
+            self,
+            &ButtonEvent {
+                button,
+                state: button_state,
+                serial,
+                time,
+            },
+        );
+        pointer.frame(self);
+    }
+
+    /// Focus and raise the window under `location` like it was clicked, or unfocus windows
+    /// if there isn't one.
+    pub fn focus_under(&mut self, location: Point<f64, Logical>, serial: Serial) {
+        let Some(keyboard) = self.pinnacle.seat.get_keyboard() else {
+            return;
+        };
 
-                // TODO: use update_keyboard_focus from anvil
+        if let Some((focus, _)) = self.pointer_focus_target_under(location) {
+            // NOTE: *Do not* set keyboard focus to an override redirect window. This leads
+            // |     to wonky things like right-click menus not correctly getting pointer
+            // |     clicks or showing up at all.
 
-                if let Some(window) = focus.window_for(self) {
+            // TODO: use update_keyboard_focus from anvil
+
+            if let Some(window) = focus.window_for(self) {
+                if self.pinnacle.config.focus.click_to_raise {
                     self.pinnacle.raise_window(window.clone(), true);
-                    if let Some(output) = window.output(&self.pinnacle) {
-                        output.with_state_mut(|state| state.focus_stack.set_focus(window.clone()));
+                } else {
+                    for win in self.pinnacle.space.elements() {
+                        win.set_activate(false);
                     }
+                    window.set_activate(true);
                 }
-
-                if !matches!(
-                    focus.window_for(self),
-                    Some(window) if window.is_x11_override_redirect()
-                ) && focus.popup_for(self).is_none()
-                {
-                    keyboard.set_focus(self, focus.to_keyboard_focus_target(self), serial);
+                if let Some(output) = window.output(&self.pinnacle) {
+                    output.with_state_mut(|state| state.focus_stack.set_focus(window.clone()));
                 }
+            }
 
-                for window in self.pinnacle.space.elements() {
-                    if let Some(toplevel) = window.toplevel() {
-                        toplevel.send_configure();
-                    }
+            if !matches!(
+                focus.window_for(self),
+                Some(window) if window.is_x11_override_redirect()
+            ) && focus.popup_for(self).is_none()
+            {
+                keyboard.set_focus(self, focus.to_keyboard_focus_target(self), serial);
+            }
+
+            for window in self.pinnacle.space.elements() {
+                if let Some(toplevel) = window.toplevel() {
+                    toplevel.send_configure();
                 }
-            } else {
-                if let Some(focused_op) = self.pinnacle.focused_output() {
-                    focused_op.with_state_mut(|state| {
-                        state.focus_stack.unset_focus();
-                        for window in state.focus_stack.stack.iter() {
-                            window.set_activate(false);
-                            if let Some(toplevel) = window.toplevel() {
-                                toplevel.send_configure();
-                            }
+            }
+        } else {
+            if let Some(focused_op) = self.pinnacle.focused_output() {
+                focused_op.with_state_mut(|state| {
+                    state.focus_stack.unset_focus();
+                    for window in state.focus_stack.stack.iter() {
+                        window.set_activate(false);
+                        if let Some(toplevel) = window.toplevel() {
+                            toplevel.send_configure();
                         }
-                    });
-                }
-                keyboard.set_focus(self, None, serial);
+                    }
+                });
             }
-        };
+            keyboard.set_focus(self, None, serial);
+        }
+    }
 
-        pointer.button(
-            self,
-            &ButtonEvent {
-                button,
-                state: button_state,
-                serial,
-                time: event.time_msec(),
-            },
-        );
-        pointer.frame(self);
+    /// Get how much scrolling on `device` is multiplied by.
+    ///
+    /// Later scroll factors override earlier ones.
+    fn scroll_factor<D: Device>(&self, device: &D) -> f64 {
+        let input_state = &self.pinnacle.input_state;
+        let libinput_device = input_state.libinput_device(&device.id());
+
+        input_state
+            .scroll_factors
+            .iter()
+            .rev()
+            .find(|factor| factor.filter.matches_device(libinput_device))
+            .map_or(1.0, |factor| factor.factor)
     }
 
     fn pointer_axis<I: InputBackend>(&mut self, event: I::PointerAxisEvent) {
         let source = event.source();
+        let factor = self.scroll_factor(&event.device());
 
         let horizontal_amount = event
             .amount(Axis::Horizontal)
-            .unwrap_or_else(|| event.amount_v120(Axis::Horizontal).unwrap_or(0.0) * 3.0 / 120.);
+            .unwrap_or_else(|| event.amount_v120(Axis::Horizontal).unwrap_or(0.0) * 3.0 / 120.)
+            * factor;
 
         let vertical_amount = event
             .amount(Axis::Vertical)
-            .unwrap_or_else(|| event.amount_v120(Axis::Vertical).unwrap_or(0.0) * 3.0 / 120.);
+            .unwrap_or_else(|| event.amount_v120(Axis::Vertical).unwrap_or(0.0) * 3.0 / 120.)
+            * factor;
+
+        let horizontal_amount_discrete = event
+            .amount_v120(Axis::Horizontal)
+            .map(|amount| amount * factor);
+        let vertical_amount_discrete = event
+            .amount_v120(Axis::Vertical)
+            .map(|amount| amount * factor);
+
+        let keyboard = self
+            .pinnacle
+            .seat
+            .get_keyboard()
+            .expect("Seat has no keyboard");
+        let mod_mask = ModifierMask::from(keyboard.modifier_state());
+
+        let input_state = &mut self.pinnacle.input_state;
+        if input_state
+            .scrollbinds
+            .keys()
+            .any(|(mods, _)| *mods == mod_mask)
+        {
+            let (horizontal, vertical, distance) =
+                match (horizontal_amount_discrete, vertical_amount_discrete) {
+                    (None, None) => (
+                        horizontal_amount,
+                        vertical_amount,
+                        SCROLLBIND_FINGER_DISTANCE,
+                    ),
+                    (horizontal, vertical) => (
+                        horizontal.unwrap_or(0.0),
+                        vertical.unwrap_or(0.0),
+                        SCROLLBIND_WHEEL_DISTANCE,
+                    ),
+                };
+
+            let accumulator = &mut input_state.scroll_accumulator;
+            let steps = [
+                (
+                    take_scroll_steps(&mut accumulator.horizontal, horizontal, distance),
+                    ScrollDirection::Left,
+                    ScrollDirection::Right,
+                ),
+                (
+                    take_scroll_steps(&mut accumulator.vertical, vertical, distance),
+                    ScrollDirection::Up,
+                    ScrollDirection::Down,
+                ),
+            ];
+
+            for (steps, negative, positive) in steps {
+                let direction = if steps < 0 { negative } else { positive };
+                if let Some(stream) = input_state.scrollbinds.get(&(mod_mask, direction)) {
+                    for _ in 0..steps.unsigned_abs() {
+                        let _ = stream.send(Ok(SetScrollbindResponse {}));
+                    }
+                }
+            }
+
+            return;
+        }
 
-        let horizontal_amount_discrete = event.amount_v120(Axis::Horizontal);
-        let vertical_amount_discrete = event.amount_v120(Axis::Vertical);
+        input_state.scroll_accumulator = ScrollAccumulator::default();
 
         let mut frame = AxisFrame::new(event.time_msec()).source(source);
 
@@ -603,6 +1020,52 @@ impl State {
         pointer.frame(self);
     }
 
+    fn switch_toggle<I: InputBackend>(&mut self, event: I::SwitchToggleEvent) {
+        if event.switch() != Some(Switch::Lid) {
+            return;
+        }
+
+        let closed = event.state() == SwitchState::On;
+        if self.pinnacle.input_state.lid_closed == closed {
+            return;
+        }
+
+        info!("Lid {}", if closed { "closed" } else { "opened" });
+        self.pinnacle.input_state.lid_closed = closed;
+        self.update_lid_outputs();
+    }
+
+    /// Move the window attached to an ongoing `xdg-toplevel-drag` along with the pointer.
+    ///
+    /// Tiled windows are made floating so they can leave the layout.
+    fn move_toplevel_drag_window(&mut self, pointer_loc: Point<f64, Logical>) {
+        let Some((window, offset)) = self.pinnacle.toplevel_drag_window() else {
+            return;
+        };
+
+        if window.with_state(|state| state.floating_or_tiled.is_tiled()) {
+            window.toggle_floating();
+            if let Some(output) = window.output(&self.pinnacle) {
+                self.pinnacle.request_layout(&output);
+            }
+        }
+
+        let new_loc = pointer_loc.to_i32_round() - offset + window.geometry().loc;
+        self.pinnacle
+            .space
+            .map_element(window.clone(), new_loc, true);
+
+        let size = window.geometry().size;
+        window.with_state_mut(|state| {
+            state.floating_or_tiled =
+                FloatingOrTiled::Floating(Rectangle::from_loc_and_size(new_loc, size));
+        });
+
+        for output in self.pinnacle.space.outputs_for_element(&window) {
+            self.schedule_render(&output);
+        }
+    }
+
     /// Clamp pointer coordinates inside outputs.
     ///
     /// This returns the nearest point inside an output.
@@ -640,6 +1103,25 @@ impl State {
     ///
     /// This *should* only be generated on the winit backend.
     /// Unless there's a case where it's generated on udev that I'm unaware of.
+    /// Get the location in the global space of an absolute position on a device covering
+    /// `output`, like a touchscreen.
+    ///
+    /// `position_transformed` scales the position to a size. Devices report positions in the
+    /// panel's orientation, which is rotated back by the output's transform.
+    pub(super) fn absolute_location_on(
+        &self,
+        output: &Output,
+        position_transformed: impl FnOnce(Size<i32, Logical>) -> Point<f64, Logical>,
+    ) -> Option<Point<f64, Logical>> {
+        let output_geo = self.pinnacle.space.output_geometry(output)?;
+
+        let transform = output.current_transform();
+        let size = transform.invert().transform_size(output_geo.size);
+        let location = transform.transform_point_in(position_transformed(size), &size.to_f64());
+
+        Some(location + output_geo.loc.to_f64())
+    }
+
     fn pointer_motion_absolute<I: InputBackend>(&mut self, event: I::PointerMotionAbsoluteEvent) {
         let Some(pointer) = self.pinnacle.seat.get_pointer() else {
             tracing::error!("Pointer motion absolute received with no pointer on seat");
@@ -654,6 +1136,7 @@ impl State {
             unreachable!("output should have a geometry as it was mapped");
         };
 
+        let old_pointer_loc = pointer.current_location();
         let pointer_loc = event.position_transformed(output_geo.size) + output_geo.loc.to_f64();
         let serial = SERIAL_COUNTER.next_serial();
 
@@ -667,6 +1150,8 @@ impl State {
             self.pinnacle.output_focus_stack.set_focus(output);
         }
 
+        self.move_toplevel_drag_window(pointer_loc);
+
         let pointer_focus = self.pointer_focus_target_under(pointer_loc);
 
         pointer.motion(
@@ -680,6 +1165,8 @@ impl State {
         );
 
         pointer.frame(self);
+
+        self.focus_follows_mouse(old_pointer_loc, pointer_loc);
     }
 
     fn pointer_motion<I: InputBackend>(&mut self, event: I::PointerMotionEvent) {
@@ -688,8 +1175,8 @@ impl State {
             return;
         };
 
-        let mut pointer_loc = pointer.current_location();
-        pointer_loc += event.delta();
+        let old_pointer_loc = pointer.current_location();
+        let mut pointer_loc = old_pointer_loc + event.delta();
 
         // clamp to screen limits
         // this event is never generated by winit
@@ -705,6 +1192,8 @@ impl State {
             self.pinnacle.output_focus_stack.set_focus(output);
         }
 
+        self.move_toplevel_drag_window(pointer_loc);
+
         let surface_under = self.pointer_focus_target_under(pointer_loc);
 
         pointer.motion(
@@ -729,8 +1218,38 @@ impl State {
 
         pointer.frame(self);
 
+        self.focus_follows_mouse(old_pointer_loc, pointer_loc);
+
         if let Some(output) = self.pinnacle.focused_output().cloned() {
             self.schedule_render(&output);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scroll_steps_accumulate() {
+        let mut accumulated = 0.0;
+        assert_eq!(take_scroll_steps(&mut accumulated, 10.0, 15.0), 0);
+        assert_eq!(take_scroll_steps(&mut accumulated, 10.0, 15.0), 1);
+        assert_eq!(accumulated, 5.0);
+        assert_eq!(take_scroll_steps(&mut accumulated, 40.0, 15.0), 3);
+        assert_eq!(accumulated, 0.0);
+    }
+
+    #[test]
+    fn scroll_steps_reset_on_stop_and_direction_change() {
+        let mut accumulated = 0.0;
+        take_scroll_steps(&mut accumulated, 10.0, 15.0);
+        assert_eq!(take_scroll_steps(&mut accumulated, -10.0, 15.0), 0);
+        assert_eq!(accumulated, -10.0);
+        assert_eq!(take_scroll_steps(&mut accumulated, -10.0, 15.0), -1);
+
+        take_scroll_steps(&mut accumulated, 0.0, 15.0);
+        assert_eq!(accumulated, 0.0);
+        assert_eq!(take_scroll_steps(&mut accumulated, -120.0, 120.0), -1);
+    }
+}
@@ -0,0 +1,249 @@
+//! Sticky keys and slow keys.
+//!
+//! With sticky keys, tapping a modifier key latches it so it applies to the next key, and
+//! tapping it again locks it until it's tapped a third time. This lets shortcuts be typed
+//! one key at a time.
+//!
+//! With slow keys, a key has to be held down for a while before its press goes through,
+//! so keys brushed by accident are ignored. Keys released before then are dropped entirely.
+
+use std::time::Duration;
+
+use smithay::{
+    backend::input::{KeyState, Keycode},
+    reexports::calloop::{
+        timer::{TimeoutAction, Timer},
+        RegistrationToken,
+    },
+};
+use tracing::error;
+use xkbcommon::xkb::Keysym;
+
+use crate::state::State;
+
+use super::ModifierMask;
+
+/// Get the modifier `keysym` is for, if it's a modifier key.
+pub fn modifier_of(keysym: Keysym) -> Option<ModifierMask> {
+    match keysym {
+        Keysym::Shift_L | Keysym::Shift_R => Some(ModifierMask::SHIFT),
+        Keysym::Control_L | Keysym::Control_R => Some(ModifierMask::CTRL),
+        Keysym::Alt_L | Keysym::Alt_R | Keysym::Meta_L | Keysym::Meta_R => Some(ModifierMask::ALT),
+        Keysym::Super_L | Keysym::Super_R => Some(ModifierMask::SUPER),
+        _ => None,
+    }
+}
+
+/// Sticky keys settings and state.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct StickyKeys {
+    pub enabled: bool,
+    /// Modifiers that apply to the next key
+    latched: ModifierMask,
+    /// Modifiers that apply until they're tapped again
+    locked: ModifierMask,
+    /// The modifier whose key was pressed with no other key pressed since
+    tapping: Option<ModifierMask>,
+    /// Modifier keys that are held down
+    held: Vec<(Keycode, ModifierMask)>,
+}
+
+impl StickyKeys {
+    /// Latch `modifier` after its key was tapped, lock it if it was latched,
+    /// or unlock it if it was locked.
+    fn tap(&mut self, modifier: ModifierMask) {
+        if self.locked.contains(modifier) {
+            self.locked.remove(modifier);
+        } else if self.latched.contains(modifier) {
+            self.latched.remove(modifier);
+            self.locked.insert(modifier);
+        } else {
+            self.latched.insert(modifier);
+        }
+    }
+
+    /// Get the modifiers that are latched or locked.
+    fn active(&self) -> ModifierMask {
+        self.latched | self.locked
+    }
+}
+
+/// Slow keys settings and state.
+#[derive(Debug, Default)]
+pub struct SlowKeys {
+    /// How long keys have to be held down, or `None` if slow keys is disabled
+    pub delay: Option<Duration>,
+    /// Pressed keys that haven't been held down long enough yet
+    pending: Vec<(Keycode, RegistrationToken)>,
+}
+
+impl State {
+    /// Update sticky keys after `keycode` was pressed or released.
+    ///
+    /// `modifier` is the modifier the key is for, if it's a modifier key.
+    pub(super) fn update_sticky_keys(
+        &mut self,
+        keycode: Keycode,
+        press_state: KeyState,
+        modifier: Option<ModifierMask>,
+    ) {
+        let sticky_keys = &mut self.pinnacle.input_state.sticky_keys;
+        let was_active = sticky_keys.active();
+
+        if !sticky_keys.enabled {
+            if !was_active.is_empty() {
+                *sticky_keys = StickyKeys::default();
+                self.apply_sticky_modifiers(was_active);
+            }
+            return;
+        }
+
+        match press_state {
+            KeyState::Pressed => match modifier {
+                Some(modifier) => {
+                    sticky_keys.held.push((keycode, modifier));
+                    sticky_keys.tapping = Some(modifier);
+                }
+                None => {
+                    sticky_keys.tapping = None;
+                    sticky_keys.latched = ModifierMask::empty();
+                }
+            },
+            KeyState::Released => {
+                let Some(pos) = sticky_keys.held.iter().position(|(key, _)| *key == keycode) else {
+                    return;
+                };
+                let (_, modifier) = sticky_keys.held.remove(pos);
+                if sticky_keys.tapping.take() == Some(modifier) {
+                    sticky_keys.tap(modifier);
+                }
+            }
+        }
+
+        // Releasing a modifier key also releases its modifier when it's sticky,
+        // so this is applied again even if nothing changed
+        if !was_active.is_empty() || !sticky_keys.active().is_empty() {
+            self.apply_sticky_modifiers(was_active);
+        }
+    }
+
+    /// Set the keyboard's modifiers to include latched and locked ones, releasing the ones in
+    /// `was_active` that aren't sticky anymore unless their keys are held down.
+    fn apply_sticky_modifiers(&mut self, was_active: ModifierMask) {
+        let Some(keyboard) = self.pinnacle.seat.get_keyboard() else {
+            return;
+        };
+
+        let sticky_keys = &self.pinnacle.input_state.sticky_keys;
+        let active = sticky_keys.active();
+        let held = sticky_keys
+            .held
+            .iter()
+            .fold(ModifierMask::empty(), |acc, (_, modifier)| acc | *modifier);
+
+        let mut modifiers = keyboard.modifier_state();
+        for (mask, state) in [
+            (ModifierMask::SHIFT, &mut modifiers.shift),
+            (ModifierMask::CTRL, &mut modifiers.ctrl),
+            (ModifierMask::ALT, &mut modifiers.alt),
+            (ModifierMask::SUPER, &mut modifiers.logo),
+        ] {
+            if active.contains(mask) {
+                *state = true;
+            } else if was_active.contains(mask) && !held.contains(mask) {
+                *state = false;
+            }
+        }
+        keyboard.set_modifier_state(modifiers);
+    }
+
+    /// Hold back a press of `keycode` until it's been held down long enough if slow keys
+    /// is enabled, and drop its release if it wasn't.
+    ///
+    /// Returns whether the press or release should go through now.
+    pub(super) fn accept_slow_key(&mut self, keycode: Keycode, press_state: KeyState) -> bool {
+        let slow_keys = &mut self.pinnacle.input_state.slow_keys;
+
+        if press_state == KeyState::Released {
+            let Some(pos) = slow_keys
+                .pending
+                .iter()
+                .position(|(key, _)| *key == keycode)
+            else {
+                return true;
+            };
+            let (_, timer) = slow_keys.pending.remove(pos);
+            self.pinnacle.loop_handle.remove(timer);
+            return false;
+        }
+
+        let Some(delay) = slow_keys.delay else {
+            return true;
+        };
+
+        let res = self.pinnacle.loop_handle.insert_source(
+            Timer::from_duration(delay),
+            move |_, _, state| {
+                state
+                    .pinnacle
+                    .input_state
+                    .slow_keys
+                    .pending
+                    .retain(|(key, _)| *key != keycode);
+
+                let time = Duration::from(state.pinnacle.clock.now()).as_millis() as u32;
+                state.key(keycode, KeyState::Pressed, time);
+                TimeoutAction::Drop
+            },
+        );
+
+        match res {
+            Ok(token) => {
+                self.pinnacle
+                    .input_state
+                    .slow_keys
+                    .pending
+                    .push((keycode, token));
+                false
+            }
+            Err(err) => {
+                error!("Failed to insert slow keys timer: {err}");
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tapping_latches_then_locks_then_unlocks() {
+        let mut sticky_keys = StickyKeys::default();
+
+        sticky_keys.tap(ModifierMask::CTRL);
+        assert_eq!(sticky_keys.latched, ModifierMask::CTRL);
+        assert_eq!(sticky_keys.locked, ModifierMask::empty());
+
+        sticky_keys.tap(ModifierMask::CTRL);
+        assert_eq!(sticky_keys.latched, ModifierMask::empty());
+        assert_eq!(sticky_keys.locked, ModifierMask::CTRL);
+
+        sticky_keys.tap(ModifierMask::SHIFT);
+        assert_eq!(
+            sticky_keys.active(),
+            ModifierMask::CTRL | ModifierMask::SHIFT
+        );
+
+        sticky_keys.tap(ModifierMask::CTRL);
+        assert_eq!(sticky_keys.active(), ModifierMask::SHIFT);
+    }
+
+    #[test]
+    fn modifier_keys() {
+        assert_eq!(modifier_of(Keysym::Control_R), Some(ModifierMask::CTRL));
+        assert_eq!(modifier_of(Keysym::Super_L), Some(ModifierMask::SUPER));
+        assert_eq!(modifier_of(Keysym::a), None);
+    }
+}
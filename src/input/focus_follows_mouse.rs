@@ -0,0 +1,134 @@
+//! Focus follows mouse.
+//!
+//! When enabled, a window the pointer moves onto gets focused once the pointer has stayed on it
+//! for the hover delay. Only the pointer crossing into a window counts, so a window that the
+//! layout moves under a resting pointer doesn't take focus from the focused one. Hovering
+//! doesn't raise windows, and clicking still focuses and raises them as usual.
+
+use smithay::{
+    reexports::calloop::timer::{TimeoutAction, Timer},
+    utils::{Logical, Point, SERIAL_COUNTER},
+};
+use tracing::error;
+
+use crate::{
+    focus::keyboard::KeyboardFocusTarget,
+    state::{State, WithState},
+    window::WindowElement,
+};
+
+impl State {
+    /// Get the window under `location` that the pointer can focus.
+    fn hover_focus_target_under(&self, location: Point<f64, Logical>) -> Option<WindowElement> {
+        self.pointer_focus_target_under(location)
+            .and_then(|(target, _)| target.window_for(self))
+            .filter(|win| !win.is_x11_override_redirect())
+    }
+
+    /// Focus the window the pointer moved onto when it moved from `old_location`
+    /// to `new_location`, if focus follows the mouse.
+    pub(super) fn focus_follows_mouse(
+        &mut self,
+        old_location: Point<f64, Logical>,
+        new_location: Point<f64, Logical>,
+    ) {
+        let Some(delay) = self.pinnacle.config.focus.focus_follows_mouse else {
+            return;
+        };
+
+        // A button is held down or a window is being moved or resized
+        if self
+            .pinnacle
+            .seat
+            .get_pointer()
+            .is_some_and(|pointer| pointer.is_grabbed())
+        {
+            return;
+        }
+
+        // Both locations are checked against the current layout, so staying on a window
+        // the layout moved under the pointer doesn't count as moving onto it
+        let window = self.hover_focus_target_under(new_location);
+        if window == self.hover_focus_target_under(old_location) {
+            return;
+        }
+
+        self.cancel_focus_follows_mouse();
+
+        let Some(window) = window else {
+            return;
+        };
+
+        let is_focused = window
+            .output(&self.pinnacle)
+            .and_then(|output| self.pinnacle.focused_window(&output))
+            .is_some_and(|focused| focused == window);
+        if is_focused {
+            return;
+        }
+
+        if delay.is_zero() {
+            self.hover_focus(window);
+            return;
+        }
+
+        let res = self.pinnacle.loop_handle.insert_source(
+            Timer::from_duration(delay),
+            move |_, _, state| {
+                state.pinnacle.input_state.focus_follows_mouse_timer = None;
+
+                // The layout may have moved another window under the pointer in the meantime
+                let still_hovered = state.pinnacle.seat.get_pointer().is_some_and(|pointer| {
+                    state
+                        .hover_focus_target_under(pointer.current_location())
+                        .as_ref()
+                        == Some(&window)
+                });
+                if still_hovered {
+                    state.hover_focus(window.clone());
+                }
+
+                TimeoutAction::Drop
+            },
+        );
+
+        match res {
+            Ok(token) => self.pinnacle.input_state.focus_follows_mouse_timer = Some(token),
+            Err(err) => error!("Failed to insert focus follows mouse timer: {err}"),
+        }
+    }
+
+    /// Stop a window the pointer moved onto from getting focused.
+    fn cancel_focus_follows_mouse(&mut self) {
+        if let Some(token) = self.pinnacle.input_state.focus_follows_mouse_timer.take() {
+            self.pinnacle.loop_handle.remove(token);
+        }
+    }
+
+    /// Focus `window` without raising it.
+    fn hover_focus(&mut self, window: WindowElement) {
+        let Some(output) = window.output(&self.pinnacle) else {
+            return;
+        };
+
+        for win in self.pinnacle.space.elements() {
+            win.set_activate(false);
+        }
+        window.set_activate(true);
+        output.with_state_mut(|state| state.focus_stack.set_focus(window.clone()));
+
+        if let Some(keyboard) = self.pinnacle.seat.get_keyboard() {
+            keyboard.set_focus(
+                self,
+                Some(KeyboardFocusTarget::Window(window)),
+                SERIAL_COUNTER.next_serial(),
+            );
+        }
+
+        for window in self.pinnacle.space.elements() {
+            if let Some(toplevel) = window.toplevel() {
+                toplevel.send_configure();
+            }
+        }
+    }
+}
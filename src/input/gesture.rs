@@ -0,0 +1,257 @@
+//! Touchpad gestures.
+//!
+//! Gestures are sent to clients through the pointer-gestures protocol. A swipe with as many
+//! fingers as a swipebind, made while the bind's modifiers are held, is kept from clients
+//! instead. Its movement is added up until the fingers are lifted, and the bind for the
+//! direction it mostly moved in is triggered.
+
+use pinnacle_api_defs::pinnacle::input::v0alpha1::{
+    set_swipebind_request::SwipeDirection, SetSwipebindResponse,
+};
+use smithay::{
+    backend::input::{
+        Event, GestureBeginEvent, GestureEndEvent, GesturePinchUpdateEvent as _,
+        GestureSwipeUpdateEvent as _, InputBackend,
+    },
+    input::pointer::{
+        GestureHoldBeginEvent, GestureHoldEndEvent, GesturePinchBeginEvent, GesturePinchEndEvent,
+        GesturePinchUpdateEvent, GestureSwipeBeginEvent, GestureSwipeEndEvent,
+        GestureSwipeUpdateEvent,
+    },
+    utils::{Logical, Point, SERIAL_COUNTER},
+};
+
+use crate::state::State;
+
+use super::ModifierMask;
+
+/// How far a swipe has to move to trigger a swipebind.
+const SWIPEBIND_DISTANCE: f64 = 40.0;
+
+/// A swipe that's being held back from clients for a swipebind.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundSwipe {
+    pub mods: ModifierMask,
+    pub fingers: u32,
+    /// How far the swipe has moved.
+    pub delta: Point<f64, Logical>,
+}
+
+/// Get the direction a swipe that moved `delta` mostly went in,
+/// or `None` if it didn't move far enough.
+fn swipe_direction(delta: Point<f64, Logical>) -> Option<SwipeDirection> {
+    if delta.x.abs().max(delta.y.abs()) < SWIPEBIND_DISTANCE {
+        return None;
+    }
+
+    let direction = if delta.x.abs() > delta.y.abs() {
+        match delta.x < 0.0 {
+            true => SwipeDirection::Left,
+            false => SwipeDirection::Right,
+        }
+    } else {
+        match delta.y < 0.0 {
+            true => SwipeDirection::Up,
+            false => SwipeDirection::Down,
+        }
+    };
+
+    Some(direction)
+}
+
+impl State {
+    pub(super) fn gesture_swipe_begin<I: InputBackend>(
+        &mut self,
+        event: I::GestureSwipeBeginEvent,
+    ) {
+        let Some(keyboard) = self.pinnacle.seat.get_keyboard() else {
+            return;
+        };
+        let mods = ModifierMask::from(keyboard.modifier_state());
+        let fingers = event.fingers();
+
+        let input_state = &mut self.pinnacle.input_state;
+        if input_state
+            .swipebinds
+            .keys()
+            .any(|(bind_mods, bind_fingers, _)| *bind_mods == mods && *bind_fingers == fingers)
+        {
+            input_state.bound_swipe = Some(BoundSwipe {
+                mods,
+                fingers,
+                delta: Point::default(),
+            });
+            return;
+        }
+
+        let Some(pointer) = self.pinnacle.seat.get_pointer() else {
+            return;
+        };
+        pointer.gesture_swipe_begin(
+            self,
+            &GestureSwipeBeginEvent {
+                serial: SERIAL_COUNTER.next_serial(),
+                time: event.time_msec(),
+                fingers,
+            },
+        );
+    }
+
+    pub(super) fn gesture_swipe_update<I: InputBackend>(
+        &mut self,
+        event: I::GestureSwipeUpdateEvent,
+    ) {
+        if let Some(swipe) = self.pinnacle.input_state.bound_swipe.as_mut() {
+            swipe.delta += event.delta();
+            return;
+        }
+
+        let Some(pointer) = self.pinnacle.seat.get_pointer() else {
+            return;
+        };
+        pointer.gesture_swipe_update(
+            self,
+            &GestureSwipeUpdateEvent {
+                time: event.time_msec(),
+                delta: event.delta(),
+            },
+        );
+    }
+
+    pub(super) fn gesture_swipe_end<I: InputBackend>(&mut self, event: I::GestureSwipeEndEvent) {
+        if let Some(swipe) = self.pinnacle.input_state.bound_swipe.take() {
+            if event.cancelled() {
+                return;
+            }
+
+            let Some(direction) = swipe_direction(swipe.delta) else {
+                return;
+            };
+
+            if let Some(stream) =
+                self.pinnacle
+                    .input_state
+                    .swipebinds
+                    .get(&(swipe.mods, swipe.fingers, direction))
+            {
+                let _ = stream.send(Ok(SetSwipebindResponse {}));
+            }
+            return;
+        }
+
+        let Some(pointer) = self.pinnacle.seat.get_pointer() else {
+            return;
+        };
+        pointer.gesture_swipe_end(
+            self,
+            &GestureSwipeEndEvent {
+                serial: SERIAL_COUNTER.next_serial(),
+                time: event.time_msec(),
+                cancelled: event.cancelled(),
+            },
+        );
+    }
+
+    pub(super) fn gesture_pinch_begin<I: InputBackend>(
+        &mut self,
+        event: I::GesturePinchBeginEvent,
+    ) {
+        let Some(pointer) = self.pinnacle.seat.get_pointer() else {
+            return;
+        };
+        pointer.gesture_pinch_begin(
+            self,
+            &GesturePinchBeginEvent {
+                serial: SERIAL_COUNTER.next_serial(),
+                time: event.time_msec(),
+                fingers: event.fingers(),
+            },
+        );
+    }
+
+    pub(super) fn gesture_pinch_update<I: InputBackend>(
+        &mut self,
+        event: I::GesturePinchUpdateEvent,
+    ) {
+        let Some(pointer) = self.pinnacle.seat.get_pointer() else {
+            return;
+        };
+        pointer.gesture_pinch_update(
+            self,
+            &GesturePinchUpdateEvent {
+                time: event.time_msec(),
+                delta: event.delta(),
+                scale: event.scale(),
+                rotation: event.rotation(),
+            },
+        );
+    }
+
+    pub(super) fn gesture_pinch_end<I: InputBackend>(&mut self, event: I::GesturePinchEndEvent) {
+        let Some(pointer) = self.pinnacle.seat.get_pointer() else {
+            return;
+        };
+        pointer.gesture_pinch_end(
+            self,
+            &GesturePinchEndEvent {
+                serial: SERIAL_COUNTER.next_serial(),
+                time: event.time_msec(),
+                cancelled: event.cancelled(),
+            },
+        );
+    }
+
+    pub(super) fn gesture_hold_begin<I: InputBackend>(&mut self, event: I::GestureHoldBeginEvent) {
+        let Some(pointer) = self.pinnacle.seat.get_pointer() else {
+            return;
+        };
+        pointer.gesture_hold_begin(
+            self,
+            &GestureHoldBeginEvent {
+                serial: SERIAL_COUNTER.next_serial(),
+                time: event.time_msec(),
+                fingers: event.fingers(),
+            },
+        );
+    }
+
+    pub(super) fn gesture_hold_end<I: InputBackend>(&mut self, event: I::GestureHoldEndEvent) {
+        let Some(pointer) = self.pinnacle.seat.get_pointer() else {
+            return;
+        };
+        pointer.gesture_hold_end(
+            self,
+            &GestureHoldEndEvent {
+                serial: SERIAL_COUNTER.next_serial(),
+                time: event.time_msec(),
+                cancelled: event.cancelled(),
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swipe_direction_follows_the_larger_axis() {
+        assert_eq!(swipe_direction((10.0, 5.0).into()), None);
+        assert_eq!(
+            swipe_direction((-60.0, 20.0).into()),
+            Some(SwipeDirection::Left)
+        );
+        assert_eq!(
+            swipe_direction((60.0, -20.0).into()),
+            Some(SwipeDirection::Right)
+        );
+        assert_eq!(
+            swipe_direction((5.0, -45.0).into()),
+            Some(SwipeDirection::Up)
+        );
+        assert_eq!(
+            swipe_direction((-30.0, 80.0).into()),
+            Some(SwipeDirection::Down)
+        );
+    }
+}
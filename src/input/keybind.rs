@@ -0,0 +1,278 @@
+//! Keybinds that trigger on release or repeat, and keybind conflicts.
+//!
+//! Keybinds normally trigger once when their key is pressed. One set to trigger on release
+//! holds its key back from clients and triggers when the key is let go instead. One set to
+//! repeat triggers again after the keyboard's repeat delay, then at its repeat rate until
+//! the key is released, like holding a volume key.
+//!
+//! Setting a keybind for keys that are already bound replaces the existing keybind unless it
+//! has a higher priority, in which case the new one isn't set. The keybind that loses is told
+//! which one it conflicted with through an error on its stream. The reload and kill keybinds
+//! from the metaconfig always win.
+
+use std::time::Duration;
+
+use pinnacle_api_defs::pinnacle::input::v0alpha1::SetKeybindResponse;
+use smithay::{
+    backend::input::Keycode,
+    reexports::calloop::{
+        timer::{TimeoutAction, Timer},
+        RegistrationToken,
+    },
+};
+use tokio::sync::mpsc::UnboundedSender;
+use tonic::Status;
+use tracing::{error, warn};
+use xkbcommon::xkb::Keysym;
+
+use crate::state::State;
+
+use super::{InputState, KeybindInfo, ModifierMask};
+
+/// A keybind's callback stream and when it's triggered.
+#[derive(Debug, Clone)]
+pub struct Keybind {
+    pub sender: UnboundedSender<Result<SetKeybindResponse, tonic::Status>>,
+    /// Whether the keybind triggers when its key is released instead of pressed
+    pub on_release: bool,
+    /// Whether the keybind triggers repeatedly while its key is held down
+    pub repeat: bool,
+    /// Keybinds for the same keys with a lower priority can't replace this one
+    pub priority: i32,
+}
+
+impl Keybind {
+    fn trigger(&self) {
+        let _ = self.sender.send(Ok(SetKeybindResponse {}));
+    }
+}
+
+/// A keybind whose key is still held down.
+#[derive(Debug)]
+pub struct HeldKeybind {
+    keycode: Keycode,
+    keybind: Keybind,
+    pub(super) repeat_timer: Option<RegistrationToken>,
+}
+
+/// The keyboard's repeat rate and delay, used to repeat keybinds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyRepeat {
+    /// Repeats per second
+    pub rate: i32,
+    /// Milliseconds before repeating starts
+    pub delay: i32,
+}
+
+impl Default for KeyRepeat {
+    fn default() -> Self {
+        Self {
+            rate: 25,
+            delay: 500,
+        }
+    }
+}
+
+impl KeyRepeat {
+    /// Get the time before the first repeat and between later ones,
+    /// or `None` if repeating is disabled.
+    fn timings(&self) -> Option<(Duration, Duration)> {
+        if self.rate <= 0 {
+            return None;
+        }
+
+        let delay = Duration::from_millis(self.delay.max(0) as u64);
+        let interval = Duration::from_secs(1) / self.rate as u32;
+        Some((delay, interval))
+    }
+}
+
+/// Get a readable name for the keys `mods` and `keysym`, like `SUPER+SHIFT+c`.
+fn keybind_name(mods: ModifierMask, keysym: Keysym) -> String {
+    mods.iter_names()
+        .map(|(name, _)| name.to_string())
+        .chain([xkbcommon::xkb::keysym_get_name(keysym)])
+        .collect::<Vec<_>>()
+        .join("+")
+}
+
+/// Describe the keybind with `info` in conflict messages.
+fn keybind_owner(info: Option<&KeybindInfo>) -> String {
+    let info = info.map(|info| (info.description.as_deref(), info.group.as_deref()));
+    match info {
+        Some((Some(description), Some(group))) => format!("\"{description}\" in {group}"),
+        Some((Some(description), None)) => format!("\"{description}\""),
+        Some((None, Some(group))) => format!("a keybind in {group}"),
+        _ => "another keybind".to_string(),
+    }
+}
+
+impl InputState {
+    /// Set the keybind triggered by `keysym` with `mods` held down.
+    ///
+    /// If the keys are already bound, the keybind with the lower priority is told it
+    /// conflicted and dropped, and the existing one is replaced if they're equal.
+    /// Keybinds for the metaconfig's reload and kill keybinds are never set.
+    pub fn set_keybind(
+        &mut self,
+        mods: ModifierMask,
+        keysym: Keysym,
+        keybind: Keybind,
+        info: KeybindInfo,
+    ) {
+        let name = keybind_name(mods, keysym);
+
+        let metaconfig_bind = if self.reload_keybind == Some((mods, keysym)) {
+            Some("reload_keybind")
+        } else if self.kill_keybind == Some((mods, keysym)) {
+            Some("kill_keybind")
+        } else {
+            None
+        };
+        if let Some(metaconfig_bind) = metaconfig_bind {
+            warn!("Not binding {name}, it's the metaconfig's {metaconfig_bind}");
+            let _ = keybind.sender.send(Err(Status::already_exists(format!(
+                "{name} is already bound by the metaconfig's {metaconfig_bind}"
+            ))));
+            return;
+        }
+
+        if let Some(existing) = self.keybinds.get(&(mods, keysym)) {
+            let existing_owner = keybind_owner(
+                self.keybind_info
+                    .iter()
+                    .find(|(bind, _)| *bind == (mods, keysym))
+                    .map(|(_, info)| info),
+            );
+
+            if existing.priority > keybind.priority {
+                warn!("Not binding {name}, it's bound by {existing_owner} with a higher priority");
+                let _ = keybind.sender.send(Err(Status::already_exists(format!(
+                    "{name} is already bound by {existing_owner} with a higher priority"
+                ))));
+                return;
+            }
+
+            let new_owner = keybind_owner(Some(&info));
+            warn!("{name} was bound by {existing_owner} and is now bound by {new_owner}");
+            let _ = existing.sender.send(Err(Status::already_exists(format!(
+                "{name} was rebound by {new_owner}"
+            ))));
+        }
+
+        self.keybinds.insert((mods, keysym), keybind);
+        self.set_keybind_info(mods, keysym, info);
+    }
+}
+
+impl State {
+    /// Handle the press of `keycode` triggering `keybind`.
+    pub(super) fn press_keybind(&mut self, keycode: Keycode, keybind: Keybind) {
+        if !keybind.on_release {
+            keybind.trigger();
+        }
+
+        if !keybind.on_release && !keybind.repeat {
+            return;
+        }
+
+        let repeat_timings = self
+            .pinnacle
+            .input_state
+            .key_repeat
+            .timings()
+            .filter(|_| keybind.repeat && !keybind.on_release);
+
+        let repeat_timer = repeat_timings.and_then(|(delay, interval)| {
+            let repeated = keybind.clone();
+            let res = self.pinnacle.loop_handle.insert_source(
+                Timer::from_duration(delay),
+                move |_, _, _| {
+                    repeated.trigger();
+                    TimeoutAction::ToDuration(interval)
+                },
+            );
+
+            match res {
+                Ok(token) => Some(token),
+                Err(err) => {
+                    error!("Failed to insert keybind repeat timer: {err}");
+                    None
+                }
+            }
+        });
+
+        self.pinnacle.input_state.held_keybinds.push(HeldKeybind {
+            keycode,
+            keybind,
+            repeat_timer,
+        });
+    }
+
+    /// Handle the release of `keycode`, triggering its keybind if it triggers on release.
+    ///
+    /// Returns whether the key's press was for a held keybind, in which case the release
+    /// shouldn't reach clients.
+    pub(super) fn release_keybind(&mut self, keycode: Keycode) -> bool {
+        let held_keybinds = &mut self.pinnacle.input_state.held_keybinds;
+        let Some(pos) = held_keybinds
+            .iter()
+            .position(|held| held.keycode == keycode)
+        else {
+            return false;
+        };
+        let held = held_keybinds.remove(pos);
+
+        if let Some(timer) = held.repeat_timer {
+            self.pinnacle.loop_handle.remove(timer);
+        }
+
+        if held.keybind.on_release {
+            held.keybind.trigger();
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_repeat_timings() {
+        let repeat = KeyRepeat {
+            rate: 25,
+            delay: 600,
+        };
+        assert_eq!(
+            repeat.timings(),
+            Some((Duration::from_millis(600), Duration::from_millis(40)))
+        );
+
+        let disabled = KeyRepeat {
+            rate: 0,
+            delay: 600,
+        };
+        assert_eq!(disabled.timings(), None);
+    }
+
+    #[test]
+    fn conflict_messages() {
+        assert_eq!(
+            keybind_name(ModifierMask::SUPER | ModifierMask::SHIFT, Keysym::c),
+            "SHIFT+SUPER+c"
+        );
+        assert_eq!(
+            keybind_name(ModifierMask::empty(), Keysym::Return),
+            "Return"
+        );
+
+        let info = KeybindInfo {
+            group: Some("Window".to_string()),
+            description: Some("Close".to_string()),
+        };
+        assert_eq!(keybind_owner(Some(&info)), "\"Close\" in Window");
+        assert_eq!(keybind_owner(None), "another keybind");
+    }
+}
@@ -1,8 +1,160 @@
-use smithay::backend::{input::InputEvent, libinput::LibinputInputBackend};
+//! Libinput device settings.
+//!
+//! Settings can be limited to devices matching a [`DeviceFilter`]. They're applied to
+//! connected devices as they're set and replayed in the same order on devices that connect
+//! later, so a setting for some devices set after one for every device overrides it.
+
+use std::mem::Discriminant;
+
+use pinnacle_api_defs::pinnacle::input::v0alpha1::set_libinput_setting_request::Setting;
+use smithay::{
+    backend::{input::InputEvent, libinput::LibinputInputBackend},
+    reexports::input::{self, DeviceCapability},
+};
 
 use crate::state::Pinnacle;
 
+/// What kind of device an input device is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceType {
+    Touchpad,
+    Trackpoint,
+    Mouse,
+    Tablet,
+    Touchscreen,
+    Keyboard,
+    Other,
+}
+
+impl DeviceType {
+    /// Guess what kind of device `device` is from its capabilities.
+    pub fn of(device: &input::Device) -> Self {
+        if device.config_tap_finger_count() > 0 {
+            Self::Touchpad
+        } else if is_trackpoint(device) {
+            Self::Trackpoint
+        } else if device.has_capability(DeviceCapability::TabletTool) {
+            Self::Tablet
+        } else if device.has_capability(DeviceCapability::Touch) {
+            Self::Touchscreen
+        } else if device.has_capability(DeviceCapability::Pointer) {
+            Self::Mouse
+        } else if device.has_capability(DeviceCapability::Keyboard) {
+            Self::Keyboard
+        } else {
+            Self::Other
+        }
+    }
+}
+
+fn is_trackpoint(device: &input::Device) -> bool {
+    // SAFETY: The udev device is only used to read a property and isn't kept around
+    unsafe { device.udev_device() }.is_some_and(|udev_device| {
+        udev_device
+            .property_value("ID_INPUT_POINTINGSTICK")
+            .is_some()
+    })
+}
+
+/// Which devices a libinput setting applies to.
+///
+/// A device matches if it matches every field that's set.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeviceFilter {
+    /// A glob matched against the device's name.
+    pub name: Option<String>,
+    pub vendor_id: Option<u32>,
+    pub product_id: Option<u32>,
+    pub device_type: Option<DeviceType>,
+}
+
+impl DeviceFilter {
+    pub fn matches(&self, device: &input::Device) -> bool {
+        self.name
+            .as_ref()
+            .map_or(true, |name| glob_matches(name, device.name()))
+            && self.vendor_id.map_or(true, |id| id == device.id_vendor())
+            && self.product_id.map_or(true, |id| id == device.id_product())
+            && self
+                .device_type
+                .map_or(true, |device_type| device_type == DeviceType::of(device))
+    }
+
+    /// Returns whether a device matches, where `None` is a device from a backend other
+    /// than libinput.
+    ///
+    /// Those devices can't be filtered, so only a filter for every device matches them.
+    pub fn matches_device(&self, device: Option<&input::Device>) -> bool {
+        match device {
+            Some(device) => self.matches(device),
+            None => *self == DeviceFilter::default(),
+        }
+    }
+}
+
+/// Returns whether `text` matches `pattern`, where `*` matches any number of characters
+/// and `?` matches exactly one.
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let text = text.chars().collect::<Vec<_>>();
+
+    let (mut p, mut t) = (0, 0);
+    // Where the last `*` was in the pattern and how much of the text it has taken
+    let mut backtrack = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&ch) if ch == '?' || ch == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, taken)) => {
+                    p = star + 1;
+                    t = taken + 1;
+                    backtrack = Some((star, taken + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|ch| *ch == '*')
+}
+
+/// A libinput setting and the devices it applies to.
+pub struct LibinputSetting {
+    /// Which setting this is, so setting it again for the same devices replaces it.
+    pub kind: Discriminant<Setting>,
+    pub filter: DeviceFilter,
+    pub apply: Box<dyn Fn(&mut input::Device) + Send>,
+}
+
 impl Pinnacle {
+    /// Apply a libinput setting to connected devices it matches and remember it for
+    /// devices that connect later.
+    ///
+    /// This replaces the same setting previously set with the same filter.
+    pub fn set_libinput_setting(&mut self, setting: LibinputSetting) {
+        for device in self
+            .input_state
+            .libinput_devices
+            .iter_mut()
+            .filter(|device| setting.filter.matches(device))
+        {
+            (setting.apply)(device);
+        }
+
+        self.input_state
+            .libinput_settings
+            .retain(|set| set.kind != setting.kind || set.filter != setting.filter);
+        self.input_state.libinput_settings.push(setting);
+    }
+
     /// Apply current libinput settings to new devices.
     pub fn apply_libinput_settings(&mut self, event: &InputEvent<LibinputInputBackend>) {
         let mut device = match event {
@@ -20,10 +172,69 @@ impl Pinnacle {
             return;
         }
 
-        for setting in self.input_state.libinput_settings.values() {
-            setting(&mut device);
+        for setting in self
+            .input_state
+            .libinput_settings
+            .iter()
+            .filter(|setting| setting.filter.matches(&device))
+        {
+            (setting.apply)(&mut device);
         }
 
         self.input_state.libinput_devices.push(device);
     }
 }
+
+/// Reset every libinput setting the config can change on `device` to its default.
+///
+/// This is used when the config is reloaded so settings it no longer sets don't stick around
+/// on devices that stay connected.
+pub fn reset_libinput_settings(device: &mut input::Device) {
+    if let Some(profile) = device.config_accel_default_profile() {
+        let _ = device.config_accel_set_profile(profile);
+    }
+    let _ = device.config_accel_set_speed(device.config_accel_default_speed());
+    if let Some(matrix) = device.config_calibration_default_matrix() {
+        let _ = device.config_calibration_set_matrix(matrix);
+    }
+    if let Some(method) = device.config_click_default_method() {
+        let _ = device.config_click_set_method(method);
+    }
+    let _ = device.config_dwt_set_enabled(device.config_dwt_default_enabled());
+    let _ = device.config_left_handed_set(device.config_left_handed_default());
+    let _ = device
+        .config_middle_emulation_set_enabled(device.config_middle_emulation_default_enabled());
+    let _ = device.config_rotation_set_angle(device.config_rotation_default_angle());
+    let _ = device.config_scroll_set_button(device.config_scroll_default_button());
+    let _ = device.config_scroll_set_button_lock(device.config_scroll_default_button_lock());
+    if let Some(method) = device.config_scroll_default_method() {
+        let _ = device.config_scroll_set_method(method);
+    }
+    let _ = device.config_scroll_set_natural_scroll_enabled(
+        device.config_scroll_default_natural_scroll_enabled(),
+    );
+    if let Some(map) = device.config_tap_default_button_map() {
+        let _ = device.config_tap_set_button_map(map);
+    }
+    let _ = device.config_tap_set_drag_enabled(device.config_tap_default_drag_enabled());
+    let _ = device.config_tap_set_drag_lock_enabled(device.config_tap_default_drag_lock_enabled());
+    let _ = device.config_tap_set_enabled(device.config_tap_default_enabled());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_matches_names() {
+        assert!(glob_matches("*Touchpad*", "SYNA2393:00 06CB:7A13 Touchpad"));
+        assert!(glob_matches("Logitech ??? Mouse", "Logitech MX3 Mouse"));
+        assert!(glob_matches("*", ""));
+        assert!(glob_matches("a*b*c", "aXbYbZc"));
+        assert!(!glob_matches(
+            "*Touchpad",
+            "SYNA2393:00 06CB:7A13 Touchpad Pen"
+        ));
+        assert!(!glob_matches("Mouse?", "Mouse"));
+    }
+}
@@ -0,0 +1,256 @@
+//! Mouse keys.
+//!
+//! With mouse keys enabled, the numpad controls the pointer instead of typing. The keys around
+//! 5 move the pointer, speeding up the longer they're held. 5 clicks, + double clicks, 0 holds
+//! the button down and . lets it go. /, * and - pick the left, middle and right button.
+//!
+//! Keys are matched by keycode, so this works the same whether num lock is on or not.
+
+use std::time::{Duration, Instant};
+
+use smithay::{
+    backend::input::{ButtonState, KeyState, Keycode},
+    reexports::calloop::{
+        timer::{TimeoutAction, Timer},
+        RegistrationToken,
+    },
+    utils::{Logical, Point},
+};
+use tracing::error;
+
+use crate::state::State;
+
+use super::remap::XKB_KEYCODE_OFFSET;
+
+const BTN_LEFT: u32 = 0x110;
+const BTN_RIGHT: u32 = 0x111;
+const BTN_MIDDLE: u32 = 0x112;
+
+/// How often the pointer moves while a movement key is held.
+const MOUSE_KEYS_INTERVAL: Duration = Duration::from_millis(10);
+/// How far the pointer moves as soon as a movement key is pressed.
+const MOUSE_KEYS_FIRST_STEP: f64 = 4.0;
+/// The pointer's speed when it starts moving, in pixels per second.
+const MOUSE_KEYS_MIN_SPEED: f64 = 200.0;
+/// The pointer's speed after speeding up, in pixels per second.
+const MOUSE_KEYS_MAX_SPEED: f64 = 1500.0;
+/// How long the pointer takes to speed up.
+const MOUSE_KEYS_ACCEL_TIME: Duration = Duration::from_millis(1500);
+
+/// What a numpad key does with mouse keys enabled.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MouseKey {
+    /// Move the pointer in this direction
+    Move(f64, f64),
+    Click,
+    DoubleClick,
+    Press,
+    Release,
+    /// Make clicks use this button
+    SelectButton(u32),
+}
+
+/// Get what `keycode` does with mouse keys enabled, if it's a mouse key.
+fn mouse_key(keycode: Keycode) -> Option<MouseKey> {
+    let key = match keycode.raw().checked_sub(XKB_KEYCODE_OFFSET)? {
+        71 => MouseKey::Move(-1.0, -1.0),
+        72 => MouseKey::Move(0.0, -1.0),
+        73 => MouseKey::Move(1.0, -1.0),
+        75 => MouseKey::Move(-1.0, 0.0),
+        77 => MouseKey::Move(1.0, 0.0),
+        79 => MouseKey::Move(-1.0, 1.0),
+        80 => MouseKey::Move(0.0, 1.0),
+        81 => MouseKey::Move(1.0, 1.0),
+        76 => MouseKey::Click,
+        78 => MouseKey::DoubleClick,
+        82 => MouseKey::Press,
+        83 => MouseKey::Release,
+        98 => MouseKey::SelectButton(BTN_LEFT),
+        55 => MouseKey::SelectButton(BTN_MIDDLE),
+        74 => MouseKey::SelectButton(BTN_RIGHT),
+        _ => return None,
+    };
+
+    Some(key)
+}
+
+/// Get the pointer's speed in pixels per second after moving for `elapsed`.
+fn mouse_keys_speed(elapsed: Duration) -> f64 {
+    let progress = (elapsed.as_secs_f64() / MOUSE_KEYS_ACCEL_TIME.as_secs_f64()).min(1.0);
+    MOUSE_KEYS_MIN_SPEED + (MOUSE_KEYS_MAX_SPEED - MOUSE_KEYS_MIN_SPEED) * progress
+}
+
+/// Get the direction the pointer moves in with `keys` held down, with a length of 1,
+/// or `None` if it doesn't move.
+fn mouse_keys_direction(keys: impl IntoIterator<Item = Keycode>) -> Option<Point<f64, Logical>> {
+    let (x, y) = keys
+        .into_iter()
+        .filter_map(|key| match mouse_key(key)? {
+            MouseKey::Move(x, y) => Some((x, y)),
+            _ => None,
+        })
+        .fold((0.0, 0.0), |(acc_x, acc_y), (x, y)| (acc_x + x, acc_y + y));
+
+    let length = x.hypot(y);
+    (length > 0.0).then(|| Point::from((x / length, y / length)))
+}
+
+/// Mouse keys settings and state.
+#[derive(Debug)]
+pub struct MouseKeys {
+    pub enabled: bool,
+    /// The button mouse keys click
+    button: u32,
+    /// Held down mouse keys, so their releases are kept from clients
+    held: Vec<Keycode>,
+    /// The timer moving the pointer while movement keys are held
+    motion_timer: Option<RegistrationToken>,
+}
+
+impl Default for MouseKeys {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            button: BTN_LEFT,
+            held: Vec::new(),
+            motion_timer: None,
+        }
+    }
+}
+
+impl State {
+    /// Handle a press or release of `keycode` if it's a mouse key.
+    ///
+    /// Returns whether it was, in which case it shouldn't be handled as a key.
+    pub(super) fn mouse_key(&mut self, keycode: Keycode, press_state: KeyState, time: u32) -> bool {
+        let mouse_keys = &mut self.pinnacle.input_state.mouse_keys;
+
+        if press_state == KeyState::Released {
+            let Some(pos) = mouse_keys.held.iter().position(|key| *key == keycode) else {
+                return false;
+            };
+            mouse_keys.held.remove(pos);
+
+            if mouse_keys_direction(mouse_keys.held.iter().copied()).is_none() {
+                if let Some(timer) = mouse_keys.motion_timer.take() {
+                    self.pinnacle.loop_handle.remove(timer);
+                }
+            }
+            return true;
+        }
+
+        if !mouse_keys.enabled {
+            return false;
+        }
+        let Some(key) = mouse_key(keycode) else {
+            return false;
+        };
+        mouse_keys.held.push(keycode);
+
+        let button = mouse_keys.button;
+        match key {
+            MouseKey::Move(x, y) => {
+                self.move_pointer_by(Point::from((x, y)).upscale(MOUSE_KEYS_FIRST_STEP));
+                self.start_mouse_keys_motion();
+            }
+            MouseKey::Click => {
+                self.button(button, ButtonState::Pressed, time);
+                self.button(button, ButtonState::Released, time);
+            }
+            MouseKey::DoubleClick => {
+                for _ in 0..2 {
+                    self.button(button, ButtonState::Pressed, time);
+                    self.button(button, ButtonState::Released, time);
+                }
+            }
+            MouseKey::Press => self.button(button, ButtonState::Pressed, time),
+            MouseKey::Release => self.button(button, ButtonState::Released, time),
+            MouseKey::SelectButton(button) => {
+                self.pinnacle.input_state.mouse_keys.button = button;
+            }
+        }
+
+        true
+    }
+
+    /// Start moving the pointer while movement keys are held, if it isn't already.
+    fn start_mouse_keys_motion(&mut self) {
+        if self.pinnacle.input_state.mouse_keys.motion_timer.is_some() {
+            return;
+        }
+
+        let start = Instant::now();
+
+        let res = self.pinnacle.loop_handle.insert_source(
+            Timer::from_duration(MOUSE_KEYS_INTERVAL),
+            move |_, _, state| {
+                let held = state.pinnacle.input_state.mouse_keys.held.clone();
+                let Some(direction) = mouse_keys_direction(held) else {
+                    state.pinnacle.input_state.mouse_keys.motion_timer = None;
+                    return TimeoutAction::Drop;
+                };
+
+                let distance =
+                    mouse_keys_speed(start.elapsed()) * MOUSE_KEYS_INTERVAL.as_secs_f64();
+                state.move_pointer_by(direction.upscale(distance));
+
+                TimeoutAction::ToDuration(MOUSE_KEYS_INTERVAL)
+            },
+        );
+
+        match res {
+            Ok(token) => self.pinnacle.input_state.mouse_keys.motion_timer = Some(token),
+            Err(err) => error!("Failed to insert mouse keys timer: {err}"),
+        }
+    }
+
+    /// Move the pointer by `delta`.
+    fn move_pointer_by(&mut self, delta: Point<f64, Logical>) {
+        let Some(pointer) = self.pinnacle.seat.get_pointer() else {
+            return;
+        };
+
+        self.warp_pointer(pointer.current_location() + delta);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keycode(evdev: u32) -> Keycode {
+        Keycode::new(evdev + XKB_KEYCODE_OFFSET)
+    }
+
+    #[test]
+    fn speed_ramps_up_then_levels_off() {
+        assert_eq!(mouse_keys_speed(Duration::ZERO), MOUSE_KEYS_MIN_SPEED);
+        assert!(mouse_keys_speed(MOUSE_KEYS_ACCEL_TIME / 2) > MOUSE_KEYS_MIN_SPEED);
+        assert_eq!(
+            mouse_keys_speed(MOUSE_KEYS_ACCEL_TIME),
+            MOUSE_KEYS_MAX_SPEED
+        );
+        assert_eq!(
+            mouse_keys_speed(MOUSE_KEYS_ACCEL_TIME * 2),
+            MOUSE_KEYS_MAX_SPEED
+        );
+    }
+
+    #[test]
+    fn held_keys_combine_into_one_direction() {
+        assert_eq!(mouse_keys_direction([]), None);
+        // Click keys don't move
+        assert_eq!(mouse_keys_direction([keycode(76)]), None);
+        // Left and right cancel out
+        assert_eq!(mouse_keys_direction([keycode(75), keycode(77)]), None);
+
+        assert_eq!(
+            mouse_keys_direction([keycode(72)]),
+            Some(Point::from((0.0, -1.0)))
+        );
+
+        let diagonal = mouse_keys_direction([keycode(77), keycode(80)]).unwrap();
+        assert!((diagonal.x - diagonal.y).abs() < f64::EPSILON);
+        assert!((diagonal.x.hypot(diagonal.y) - 1.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,110 @@
+//! Key remapping.
+//!
+//! Remaps turn one key into another before keybinds and clients see it, like making caps lock
+//! a control key. Keys are either evdev keycodes or keysyms, which stand for the key that
+//! types them without modifiers in the active layout. Remaps can be limited to keyboards
+//! matching a [`DeviceFilter`], and a later remap of the same key overrides an earlier one.
+//!
+//! A key is always released as the key it was pressed as, so changing remaps while a key is
+//! held down doesn't leave it stuck.
+
+use smithay::backend::input::{Device, KeyState, Keycode};
+use xkbcommon::xkb::Keysym;
+
+use crate::state::State;
+
+use super::libinput::DeviceFilter;
+
+/// How far xkb keycodes are offset from evdev keycodes.
+pub const XKB_KEYCODE_OFFSET: u32 = 8;
+/// The highest evdev keycode.
+const EVDEV_KEY_MAX: u32 = 0x2ff;
+
+/// A key in a remap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemapKey {
+    /// An evdev keycode, like 58 for caps lock
+    Keycode(u32),
+    /// The key that types this keysym without modifiers in the active layout
+    Keysym(Keysym),
+}
+
+/// A key turned into another on keyboards matching `filter`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyRemap {
+    pub from: RemapKey,
+    pub to: RemapKey,
+    pub filter: DeviceFilter,
+}
+
+impl State {
+    /// Get the xkb keycode of `key`, or `None` if it's a keysym no key types.
+    fn remap_keycode_of(&mut self, key: RemapKey) -> Option<Keycode> {
+        match key {
+            RemapKey::Keycode(code) => Some(Keycode::new(code + XKB_KEYCODE_OFFSET)),
+            RemapKey::Keysym(keysym) => {
+                let keyboard = self.pinnacle.seat.get_keyboard()?;
+                keyboard.with_xkb_state(self, |context| {
+                    let xkb = context.xkb().lock().unwrap();
+                    let layout = xkb.active_layout();
+                    (XKB_KEYCODE_OFFSET..=EVDEV_KEY_MAX + XKB_KEYCODE_OFFSET)
+                        .map(Keycode::new)
+                        .find(|code| {
+                            xkb.raw_syms_for_key_in_layout(*code, layout)
+                                .contains(&keysym)
+                        })
+                })
+            }
+        }
+    }
+
+    /// Get the keycode `keycode` from `device` is remapped to.
+    pub(super) fn remap_keycode<D: Device>(
+        &mut self,
+        device: &D,
+        keycode: Keycode,
+        press_state: KeyState,
+    ) -> Keycode {
+        if press_state == KeyState::Released {
+            let remapped_keys = &mut self.pinnacle.input_state.remapped_keys;
+            return match remapped_keys.iter().position(|(from, _)| *from == keycode) {
+                Some(pos) => remapped_keys.remove(pos).1,
+                None => keycode,
+            };
+        }
+
+        if self.pinnacle.input_state.key_remaps.is_empty() {
+            return keycode;
+        }
+
+        let libinput_device = self.pinnacle.input_state.libinput_device(&device.id());
+
+        let remaps = self
+            .pinnacle
+            .input_state
+            .key_remaps
+            .iter()
+            .rev()
+            .filter(|remap| remap.filter.matches_device(libinput_device))
+            .map(|remap| (remap.from, remap.to))
+            .collect::<Vec<_>>();
+
+        for (from, to) in remaps {
+            if self.remap_keycode_of(from) != Some(keycode) {
+                continue;
+            }
+
+            let Some(remapped) = self.remap_keycode_of(to) else {
+                continue;
+            };
+
+            self.pinnacle
+                .input_state
+                .remapped_keys
+                .push((keycode, remapped));
+            return remapped;
+        }
+
+        keycode
+    }
+}
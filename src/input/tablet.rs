@@ -0,0 +1,355 @@
+//! Drawing tablets.
+//!
+//! Tablets are sent to clients through the tablet protocol, and their pens move the pointer
+//! along with them. A tablet covers the first output unless it's mapped to another output or
+//! to a region of the global space. Pen pressure can be run through a curve to make the pen
+//! feel softer or firmer, and pen buttons can be remapped to other buttons.
+//!
+//! Settings can be limited to tablets matching a [`DeviceFilter`], and a later setting
+//! overrides an earlier one it matches the same tablet as.
+
+use smithay::{
+    backend::input::{
+        ButtonState, Device, DeviceCapability, Event, InputBackend, ProximityState,
+        TabletToolButtonEvent, TabletToolEvent, TabletToolProximityEvent, TabletToolTipEvent,
+        TabletToolTipState,
+    },
+    input::pointer::MotionEvent,
+    reexports::wayland_server::protocol::wl_surface::WlSurface,
+    utils::{Logical, Point, Rectangle, SERIAL_COUNTER},
+    wayland::{
+        seat::WaylandFocus,
+        tablet_manager::{TabletDescriptor, TabletSeatTrait},
+    },
+};
+
+use crate::{output::OutputName, state::State};
+
+use super::libinput::DeviceFilter;
+
+/// What part of the global space a tablet's area is mapped to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TabletArea {
+    Output(OutputName),
+    Region(Rectangle<i32, Logical>),
+}
+
+/// A cubic bezier curve from (0, 0) to (1, 1) that pen pressure is mapped through.
+///
+/// The two fields are its control points, so a curve with control points (0, 0) and (1, 1)
+/// leaves pressure as is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PressureCurve {
+    pub control_1: (f64, f64),
+    pub control_2: (f64, f64),
+}
+
+impl PressureCurve {
+    /// Map `pressure`, from 0 to 1, through the curve.
+    fn apply(&self, pressure: f64) -> f64 {
+        let pressure = pressure.clamp(0.0, 1.0);
+        let bezier = |t: f64, p1: f64, p2: f64| {
+            let u = 1.0 - t;
+            3.0 * u * u * t * p1 + 3.0 * u * t * t * p2 + t * t * t
+        };
+
+        // Control points have x between 0 and 1, so x grows with t
+        // and the t where it equals the pressure can be bisected for
+        let (mut low, mut high) = (0.0, 1.0);
+        for _ in 0..32 {
+            let mid = (low + high) / 2.0;
+            if bezier(mid, self.control_1.0, self.control_2.0) < pressure {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+
+        let t = (low + high) / 2.0;
+        bezier(t, self.control_1.1, self.control_2.1).clamp(0.0, 1.0)
+    }
+}
+
+/// A tablet setting.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TabletConfig {
+    Area(TabletArea),
+    PressureCurve(PressureCurve),
+    /// Turn a pen button into another
+    ButtonRemap {
+        from: u32,
+        to: u32,
+    },
+}
+
+/// A tablet setting and the tablets it applies to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TabletSetting {
+    pub filter: DeviceFilter,
+    pub config: TabletConfig,
+}
+
+impl State {
+    /// Get the settings that apply to `device`, the latest first.
+    fn tablet_configs<D: Device>(&self, device: &D) -> impl Iterator<Item = &TabletConfig> {
+        let input_state = &self.pinnacle.input_state;
+        let libinput_device = input_state.libinput_device(&device.id());
+
+        input_state
+            .tablet_settings
+            .iter()
+            .rev()
+            .filter(move |setting| setting.filter.matches_device(libinput_device))
+            .map(|setting| &setting.config)
+    }
+
+    /// Get the location in the global space a tablet tool event happened at.
+    fn tablet_tool_location<I: InputBackend, E: TabletToolEvent<I>>(
+        &self,
+        event: &E,
+    ) -> Option<Point<f64, Logical>> {
+        let area = self
+            .tablet_configs(&event.device())
+            .find_map(|config| match config {
+                TabletConfig::Area(area) => Some(area.clone()),
+                _ => None,
+            });
+
+        let output = match area {
+            Some(TabletArea::Region(region)) => {
+                return Some(event.position_transformed(region.size) + region.loc.to_f64());
+            }
+            Some(TabletArea::Output(output_name)) => output_name.output(&self.pinnacle),
+            None => None,
+        };
+
+        let output = output.or_else(|| self.pinnacle.space.outputs().next().cloned())?;
+        self.absolute_location_on(&output, |size| event.position_transformed(size))
+    }
+
+    /// Move the pointer along with a tablet tool to `location`.
+    ///
+    /// Returns the surface under it along with its origin in the global space.
+    fn move_pointer_with_tablet_tool(
+        &mut self,
+        location: Point<f64, Logical>,
+        time: u32,
+    ) -> Option<(WlSurface, Point<i32, Logical>)> {
+        let pointer = self.pinnacle.seat.get_pointer()?;
+
+        if let Some(output) = self.pinnacle.space.output_under(location).next().cloned() {
+            self.pinnacle.output_focus_stack.set_focus(output);
+        }
+
+        let focus = self.pointer_focus_target_under(location);
+
+        pointer.motion(
+            self,
+            focus.clone(),
+            &MotionEvent {
+                location,
+                serial: SERIAL_COUNTER.next_serial(),
+                time,
+            },
+        );
+        pointer.frame(self);
+
+        focus.and_then(|(target, loc)| Some((target.wl_surface()?, loc)))
+    }
+
+    /// Add a newly connected device to the tablet seat if it's a tablet.
+    pub(super) fn tablet_device_added<D: Device>(&mut self, device: &D) {
+        if device.has_capability(DeviceCapability::TabletTool) {
+            self.pinnacle.seat.tablet_seat().add_tablet::<Self>(
+                &self.pinnacle.display_handle,
+                &TabletDescriptor::from(device),
+            );
+        }
+    }
+
+    /// Remove a disconnected device from the tablet seat if it's a tablet.
+    pub(super) fn tablet_device_removed<D: Device>(&mut self, device: &D) {
+        if device.has_capability(DeviceCapability::TabletTool) {
+            let tablet_seat = self.pinnacle.seat.tablet_seat();
+            tablet_seat.remove_tablet(&TabletDescriptor::from(device));
+
+            // Tools can't be used without a tablet
+            if tablet_seat.count_tablets() == 0 {
+                tablet_seat.clear_tools();
+            }
+        }
+    }
+
+    pub(super) fn tablet_tool_proximity<I: InputBackend>(
+        &mut self,
+        event: I::TabletToolProximityEvent,
+    ) {
+        let tablet_seat = self.pinnacle.seat.tablet_seat();
+        let tool = tablet_seat.add_tool::<Self>(&self.pinnacle.display_handle, &event.tool());
+
+        if event.state() == ProximityState::Out {
+            tool.proximity_out(event.time_msec());
+            return;
+        }
+
+        let Some(location) = self.tablet_tool_location::<I, _>(&event) else {
+            return;
+        };
+        let focus = self.move_pointer_with_tablet_tool(location, event.time_msec());
+
+        let tablet = tablet_seat.get_tablet(&TabletDescriptor::from(&event.device()));
+        if let (Some(tablet), Some(focus)) = (tablet, focus) {
+            tool.proximity_in(
+                location,
+                focus,
+                &tablet,
+                SERIAL_COUNTER.next_serial(),
+                event.time_msec(),
+            );
+        }
+    }
+
+    pub(super) fn tablet_tool_axis<I: InputBackend>(&mut self, event: I::TabletToolAxisEvent) {
+        let Some(location) = self.tablet_tool_location::<I, _>(&event) else {
+            return;
+        };
+        let focus = self.move_pointer_with_tablet_tool(location, event.time_msec());
+
+        let tablet_seat = self.pinnacle.seat.tablet_seat();
+        let (Some(tablet), Some(tool)) = (
+            tablet_seat.get_tablet(&TabletDescriptor::from(&event.device())),
+            tablet_seat.get_tool(&event.tool()),
+        ) else {
+            return;
+        };
+
+        if event.pressure_has_changed() {
+            let pressure =
+                match self
+                    .tablet_configs(&event.device())
+                    .find_map(|config| match config {
+                        TabletConfig::PressureCurve(curve) => Some(*curve),
+                        _ => None,
+                    }) {
+                    Some(curve) => curve.apply(event.pressure()),
+                    None => event.pressure(),
+                };
+            tool.pressure(pressure);
+        }
+        if event.distance_has_changed() {
+            tool.distance(event.distance());
+        }
+        if event.tilt_has_changed() {
+            tool.tilt(event.tilt());
+        }
+        if event.slider_has_changed() {
+            tool.slider_position(event.slider_position());
+        }
+        if event.rotation_has_changed() {
+            tool.rotation(event.rotation());
+        }
+        if event.wheel_has_changed() {
+            tool.wheel(event.wheel_delta(), event.wheel_delta_discrete());
+        }
+
+        tool.motion(
+            location,
+            focus,
+            &tablet,
+            SERIAL_COUNTER.next_serial(),
+            event.time_msec(),
+        );
+    }
+
+    pub(super) fn tablet_tool_tip<I: InputBackend>(&mut self, event: I::TabletToolTipEvent) {
+        let Some(tool) = self.pinnacle.seat.tablet_seat().get_tool(&event.tool()) else {
+            return;
+        };
+
+        match event.tip_state() {
+            TabletToolTipState::Down => {
+                let serial = SERIAL_COUNTER.next_serial();
+                tool.tip_down(serial, event.time_msec());
+
+                // Touching a window with the pen focuses it like a click
+                if let Some(pointer) = self.pinnacle.seat.get_pointer() {
+                    self.focus_under(pointer.current_location(), serial);
+                }
+            }
+            TabletToolTipState::Up => tool.tip_up(event.time_msec()),
+        }
+    }
+
+    pub(super) fn tablet_tool_button<I: InputBackend>(&mut self, event: I::TabletToolButtonEvent) {
+        let Some(tool) = self.pinnacle.seat.tablet_seat().get_tool(&event.tool()) else {
+            return;
+        };
+
+        let button = event.button();
+        let button_state = event.button_state();
+
+        // A button is always released as the button it was pressed as
+        let remapped = match button_state {
+            ButtonState::Pressed => {
+                let remapped = self
+                    .tablet_configs(&event.device())
+                    .find_map(|config| match config {
+                        TabletConfig::ButtonRemap { from, to } if *from == button => Some(*to),
+                        _ => None,
+                    })
+                    .unwrap_or(button);
+                self.pinnacle
+                    .input_state
+                    .remapped_tablet_buttons
+                    .push((button, remapped));
+                remapped
+            }
+            ButtonState::Released => {
+                let remapped_buttons = &mut self.pinnacle.input_state.remapped_tablet_buttons;
+                match remapped_buttons
+                    .iter()
+                    .position(|(from, _)| *from == button)
+                {
+                    Some(pos) => remapped_buttons.remove(pos).1,
+                    None => button,
+                }
+            }
+        };
+
+        tool.button(
+            remapped,
+            button_state,
+            SERIAL_COUNTER.next_serial(),
+            event.time_msec(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linear_curve_keeps_pressure() {
+        let curve = PressureCurve {
+            control_1: (0.0, 0.0),
+            control_2: (1.0, 1.0),
+        };
+
+        for pressure in [0.0, 0.25, 0.5, 0.75, 1.0] {
+            assert!((curve.apply(pressure) - pressure).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn soft_curve_raises_pressure() {
+        let curve = PressureCurve {
+            control_1: (0.0, 0.5),
+            control_2: (0.5, 1.0),
+        };
+
+        assert!(curve.apply(0.0).abs() < 1e-6);
+        assert!(curve.apply(0.5) > 0.5);
+        assert!((curve.apply(1.0) - 1.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,125 @@
+//! Touchscreens.
+//!
+//! Touches are sent to the surface under them, and touching a window focuses it like a click.
+//! A touchscreen covers the first output unless it's mapped to another one, so a screen
+//! attached to a secondary monitor can be mapped to it. Touches follow the output's transform,
+//! so rotated panels register them where they were made. Touchscreens that aren't quite
+//! aligned with their panel can be corrected with libinput's calibration matrix.
+
+use smithay::{
+    backend::input::{AbsolutePositionEvent, Device, Event, InputBackend, TouchEvent},
+    input::touch::{DownEvent, MotionEvent, UpEvent},
+    output::Output,
+    utils::{Logical, Point, SERIAL_COUNTER},
+};
+
+use crate::{output::OutputName, state::State};
+
+use super::libinput::DeviceFilter;
+
+/// An output touchscreens matching `filter` are mapped to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TouchOutputMapping {
+    pub filter: DeviceFilter,
+    pub output: OutputName,
+}
+
+impl State {
+    /// Get the output touches from `device` land on.
+    ///
+    /// Later mappings override earlier ones. Touchscreens that aren't mapped, or are mapped
+    /// to an output that isn't connected, cover the first output.
+    fn touch_output<D: Device>(&self, device: &D) -> Option<Output> {
+        let input_state = &self.pinnacle.input_state;
+        let libinput_device = input_state.libinput_device(&device.id());
+
+        input_state
+            .touch_output_mappings
+            .iter()
+            .rev()
+            .find(|mapping| mapping.filter.matches_device(libinput_device))
+            .and_then(|mapping| mapping.output.output(&self.pinnacle))
+            .or_else(|| self.pinnacle.space.outputs().next().cloned())
+    }
+
+    /// Get the location in the global space a touch event happened at.
+    fn touch_location<I: InputBackend, E: AbsolutePositionEvent<I>>(
+        &self,
+        event: &E,
+    ) -> Option<Point<f64, Logical>> {
+        let output = self.touch_output(&event.device())?;
+        self.absolute_location_on(&output, |size| event.position_transformed(size))
+    }
+
+    pub(super) fn touch_down<I: InputBackend>(&mut self, event: I::TouchDownEvent) {
+        let Some(touch) = self.pinnacle.seat.get_touch() else {
+            return;
+        };
+        let Some(location) = self.touch_location::<I, _>(&event) else {
+            return;
+        };
+
+        let serial = SERIAL_COUNTER.next_serial();
+
+        self.focus_under(location, serial);
+
+        let focus = self.pointer_focus_target_under(location);
+        touch.down(
+            self,
+            focus,
+            &DownEvent {
+                slot: event.slot(),
+                location,
+                serial,
+                time: event.time_msec(),
+            },
+        );
+    }
+
+    pub(super) fn touch_motion<I: InputBackend>(&mut self, event: I::TouchMotionEvent) {
+        let Some(touch) = self.pinnacle.seat.get_touch() else {
+            return;
+        };
+        let Some(location) = self.touch_location::<I, _>(&event) else {
+            return;
+        };
+
+        let focus = self.pointer_focus_target_under(location);
+        touch.motion(
+            self,
+            focus,
+            &MotionEvent {
+                slot: event.slot(),
+                location,
+                time: event.time_msec(),
+            },
+        );
+    }
+
+    pub(super) fn touch_up<I: InputBackend>(&mut self, event: I::TouchUpEvent) {
+        let Some(touch) = self.pinnacle.seat.get_touch() else {
+            return;
+        };
+
+        touch.up(
+            self,
+            &UpEvent {
+                slot: event.slot(),
+                serial: SERIAL_COUNTER.next_serial(),
+                time: event.time_msec(),
+            },
+        );
+    }
+
+    pub(super) fn touch_frame<I: InputBackend>(&mut self, _event: I::TouchFrameEvent) {
+        if let Some(touch) = self.pinnacle.seat.get_touch() {
+            touch.frame(self);
+        }
+    }
+
+    pub(super) fn touch_cancel<I: InputBackend>(&mut self, _event: I::TouchCancelEvent) {
+        if let Some(touch) = self.pinnacle.seat.get_touch() {
+            touch.cancel(self);
+        }
+    }
+}
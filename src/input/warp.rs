@@ -0,0 +1,95 @@
+//! Pointer warping.
+//!
+//! The pointer can be moved to a point in the global space or to the center of a window or
+//! output. Warping the pointer on output focus changes moves it to the center of a newly
+//! focused output when focus moves there without the pointer, like from a keybind.
+
+use std::time::Duration;
+
+use smithay::{
+    input::pointer::MotionEvent,
+    utils::{Logical, Point, Rectangle, SERIAL_COUNTER},
+};
+
+use crate::state::State;
+
+/// Get the center of `rect`.
+pub fn center(rect: Rectangle<i32, Logical>) -> Point<f64, Logical> {
+    let rect = rect.to_f64();
+    Point::from((
+        rect.loc.x + rect.size.w / 2.0,
+        rect.loc.y + rect.size.h / 2.0,
+    ))
+}
+
+impl State {
+    /// Move the pointer to `location`, clamped inside outputs.
+    ///
+    /// The output it moves to gets focused.
+    pub fn warp_pointer(&mut self, location: Point<f64, Logical>) {
+        let Some(pointer) = self.pinnacle.seat.get_pointer() else {
+            return;
+        };
+
+        let old_output = self
+            .pinnacle
+            .space
+            .output_under(pointer.current_location())
+            .next()
+            .cloned();
+
+        let location = self.clamp_coords(location);
+
+        let new_output = self.pinnacle.space.output_under(location).next().cloned();
+        if let Some(output) = new_output.as_ref() {
+            self.pinnacle.output_focus_stack.set_focus(output.clone());
+        }
+        // Focus moved here with the pointer, so there's nothing to warp for
+        self.pinnacle.input_state.warp_output = new_output.clone();
+
+        let focus = self.pointer_focus_target_under(location);
+
+        pointer.motion(
+            self,
+            focus,
+            &MotionEvent {
+                location,
+                serial: SERIAL_COUNTER.next_serial(),
+                time: Duration::from(self.pinnacle.clock.now()).as_millis() as u32,
+            },
+        );
+        pointer.frame(self);
+
+        for output in [old_output, new_output].into_iter().flatten() {
+            self.schedule_render(&output);
+        }
+    }
+
+    /// Warp the pointer to the center of a newly focused output if warping on output focus
+    /// is enabled and the pointer isn't already on it.
+    pub fn update_pointer_warp(&mut self) {
+        let focused = self.pinnacle.focused_output().cloned();
+        if focused == self.pinnacle.input_state.warp_output {
+            return;
+        }
+        self.pinnacle.input_state.warp_output = focused.clone();
+
+        if !self.pinnacle.input_state.warp_pointer_on_output_focus {
+            return;
+        }
+
+        let Some(output_geo) = focused.and_then(|op| self.pinnacle.space.output_geometry(&op))
+        else {
+            return;
+        };
+        let Some(pointer) = self.pinnacle.seat.get_pointer() else {
+            return;
+        };
+
+        if output_geo.to_f64().contains(pointer.current_location()) {
+            return;
+        }
+
+        self.warp_pointer(center(output_geo));
+    }
+}
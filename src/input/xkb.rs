@@ -0,0 +1,199 @@
+//! Keyboard layout switching and lock keys.
+//!
+//! The active layout is checked every event loop iteration so switches from xkb options
+//! like `grp:alt_shift_toggle` are noticed along with ones from the API. With per-window
+//! layouts enabled, the focused window remembers the active layout and it's switched back
+//! to when the window is focused again. Windows that were never focused keep whatever
+//! layout is active.
+//!
+//! Num lock and caps lock can be turned on or off from the API, like to start with num lock
+//! on. Their leds are kept in sync on every keyboard, including ones plugged in later and
+//! after switching back from another VT.
+
+use pinnacle_api_defs::pinnacle::signal::v0alpha1::InputXkbLayoutResponse;
+use smithay::input::keyboard::Layout;
+
+use crate::{
+    focus::keyboard::KeyboardFocusTarget,
+    state::{State, WithState},
+};
+
+/// The layouts of the keyboard and which one is active.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct XkbLayouts {
+    pub names: Vec<String>,
+    pub active: u32,
+}
+
+/// Add the xkb option for `compose_key`, like `ralt` or `menu`, to `options`.
+pub fn options_with_compose_key(
+    options: Option<String>,
+    compose_key: Option<String>,
+) -> Option<String> {
+    let Some(compose_key) = compose_key.filter(|key| !key.is_empty()) else {
+        return options;
+    };
+    let compose = format!("compose:{compose_key}");
+
+    match options.filter(|options| !options.is_empty()) {
+        Some(options) => Some(format!("{options},{compose}")),
+        None => Some(compose),
+    }
+}
+
+/// Get the index `offset` layouts away from `active` out of `count`, wrapping around.
+pub fn relative_layout(active: u32, offset: i32, count: u32) -> u32 {
+    if count == 0 {
+        return 0;
+    }
+
+    (active as i64 + offset as i64).rem_euclid(count as i64) as u32
+}
+
+impl State {
+    /// Get the keyboard's layouts and which one is active.
+    pub fn xkb_layouts(&mut self) -> XkbLayouts {
+        let Some(keyboard) = self.pinnacle.seat.get_keyboard() else {
+            return XkbLayouts::default();
+        };
+
+        keyboard.with_xkb_state(self, |context| {
+            let xkb = context.xkb().lock().unwrap();
+            XkbLayouts {
+                names: xkb
+                    .layouts()
+                    .map(|layout| xkb.layout_name(layout).to_string())
+                    .collect(),
+                active: xkb.active_layout().0,
+            }
+        })
+    }
+
+    /// Get the index of the keyboard's active layout.
+    fn active_xkb_layout(&mut self) -> Option<u32> {
+        let keyboard = self.pinnacle.seat.get_keyboard()?;
+        Some(keyboard.with_xkb_state(self, |context| {
+            context.xkb().lock().unwrap().active_layout().0
+        }))
+    }
+
+    /// Switch the keyboard to the layout at `index`.
+    ///
+    /// Indices past the last layout are ignored.
+    pub fn set_xkb_layout(&mut self, index: u32) {
+        let Some(keyboard) = self.pinnacle.seat.get_keyboard() else {
+            return;
+        };
+
+        if index as usize >= self.xkb_layouts().names.len() {
+            return;
+        }
+
+        keyboard.with_xkb_state(self, |mut context| context.set_layout(Layout(index)));
+        self.update_xkb_layout();
+    }
+
+    /// Switch the keyboard `offset` layouts forward, or backward if negative, wrapping around.
+    pub fn switch_relative_xkb_layout(&mut self, offset: i32) {
+        let layouts = self.xkb_layouts();
+        let index = relative_layout(layouts.active, offset, layouts.names.len() as u32);
+        self.set_xkb_layout(index);
+    }
+
+    /// Turn num lock and caps lock on or off, leaving ones that are `None` as they are.
+    pub fn set_lock_keys(&mut self, num_lock: Option<bool>, caps_lock: Option<bool>) {
+        let Some(keyboard) = self.pinnacle.seat.get_keyboard() else {
+            return;
+        };
+
+        let mut modifiers = keyboard.modifier_state();
+        if let Some(num_lock) = num_lock {
+            modifiers.num_lock = num_lock;
+        }
+        if let Some(caps_lock) = caps_lock {
+            modifiers.caps_lock = caps_lock;
+        }
+        keyboard.set_modifier_state(modifiers);
+
+        self.update_keyboard_leds();
+    }
+
+    /// Signal a change of the active layout and, with per-window layouts enabled,
+    /// switch to the layout of a newly focused window.
+    pub fn update_xkb_layout(&mut self) {
+        let Some(keyboard) = self.pinnacle.seat.get_keyboard() else {
+            return;
+        };
+
+        let focused_window = match keyboard.current_focus() {
+            Some(KeyboardFocusTarget::Window(window)) => Some(window),
+            _ => None,
+        };
+
+        let per_window = self.pinnacle.input_state.per_window_xkb_layout;
+        if focused_window != self.pinnacle.input_state.xkb_layout_window {
+            self.pinnacle.input_state.xkb_layout_window = focused_window.clone();
+
+            let saved = focused_window
+                .as_ref()
+                .and_then(|window| window.with_state(|state| state.xkb_layout));
+            if let Some(layout) = saved.filter(|_| per_window) {
+                keyboard.with_xkb_state(self, |mut context| context.set_layout(Layout(layout)));
+            }
+        }
+
+        let Some(active) = self.active_xkb_layout() else {
+            return;
+        };
+
+        if per_window {
+            if let Some(window) = focused_window.as_ref() {
+                window.with_state_mut(|state| state.xkb_layout = Some(active));
+            }
+        }
+
+        if self.pinnacle.input_state.xkb_layout == Some(active) {
+            return;
+        }
+        self.pinnacle.input_state.xkb_layout = Some(active);
+
+        let name = self.xkb_layouts().names.get(active as usize).cloned();
+        self.pinnacle.signal_state.input_xkb_layout.signal(|buf| {
+            buf.push_back(InputXkbLayoutResponse {
+                index: Some(active),
+                name,
+            })
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_layout_wraps() {
+        assert_eq!(relative_layout(0, 1, 3), 1);
+        assert_eq!(relative_layout(2, 1, 3), 0);
+        assert_eq!(relative_layout(0, -1, 3), 2);
+        assert_eq!(relative_layout(1, -4, 3), 0);
+        assert_eq!(relative_layout(0, 1, 0), 0);
+    }
+
+    #[test]
+    fn compose_key_is_added_to_options() {
+        assert_eq!(
+            options_with_compose_key(None, Some("ralt".into())),
+            Some("compose:ralt".into())
+        );
+        assert_eq!(
+            options_with_compose_key(Some("caps:escape".into()), Some("menu".into())),
+            Some("caps:escape,compose:menu".into())
+        );
+        assert_eq!(
+            options_with_compose_key(Some("caps:escape".into()), None),
+            Some("caps:escape".into())
+        );
+        assert_eq!(options_with_compose_key(None, Some("".into())), None);
+    }
+}
@@ -1,26 +1,40 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+pub mod fallback;
+pub mod gaps;
+pub mod insert;
+pub mod transaction;
+
 use std::{
     collections::{HashMap, HashSet},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
-use pinnacle_api_defs::pinnacle::layout::v0alpha1::{layout_request::Geometries, LayoutResponse};
+use pinnacle_api_defs::pinnacle::layout::v0alpha1::{
+    layout_request::Geometries, LayoutResponse, TileResize,
+};
 use smithay::{
     desktop::{layer_map_for_output, WindowSurface},
     output::Output,
-    utils::{Logical, Point, Rectangle, Serial},
+    reexports::calloop::timer::{TimeoutAction, Timer},
+    utils::{Logical, Rectangle, Serial},
     wayland::{compositor, shell::xdg::XdgToplevelSurfaceData},
 };
 use tokio::sync::mpsc::UnboundedSender;
 use tonic::Status;
-use tracing::warn;
+use tracing::{debug, warn};
 
 use crate::{
+    layout::{
+        fallback::FallbackLayout,
+        gaps::Gaps,
+        insert::InsertPosition,
+        transaction::{LayoutTransaction, TRANSACTION_TIMEOUT},
+    },
     output::OutputName,
     state::{Pinnacle, State, WithState},
     window::{
-        window_state::{FloatingOrTiled, FullscreenOrMaximized},
+        window_state::{FloatingOrTiled, FullscreenOrMaximized, WindowId},
         WindowElement,
     },
 };
@@ -43,6 +57,9 @@ impl Pinnacle {
                 .collect::<Vec<_>>()
         });
 
+        self.start_geometry_animations(&windows_on_foc_tags);
+
+        // Windows hidden in a group share the tile of the group's active window
         let tiled_windows = windows_on_foc_tags
             .iter()
             .filter(|win| {
@@ -50,6 +67,7 @@ impl Pinnacle {
                     state.floating_or_tiled.is_tiled() && state.fullscreen_or_maximized.is_neither()
                 })
             })
+            .filter(|win| !win.is_hidden_in_group())
             .cloned();
 
         let output_geo = self.space.output_geometry(output).expect("no output geo");
@@ -59,13 +77,31 @@ impl Pinnacle {
             map.non_exclusive_zone()
         };
 
-        let mut zipped = tiled_windows.zip(geometries.into_iter().map(|mut geo| {
+        let gaps = self.output_gaps(output);
+        let layout_area = gaps.layout_area(non_exclusive_geo.size);
+
+        let mut zipped = tiled_windows.zip(geometries.into_iter().map(|geo| {
+            let mut geo = gaps.apply(geo, layout_area);
             geo.loc += output_geo.loc + non_exclusive_geo.loc;
             geo
         }));
 
         for (win, geo) in zipped.by_ref() {
-            win.change_geometry(geo);
+            let Some(group) = win.group() else {
+                win.change_geometry(geo);
+                continue;
+            };
+
+            // Leave room for the group's indicator above the windows
+            let indicator_height = group.indicator_height().min(geo.size.h - 1);
+            let geo = Rectangle::from_loc_and_size(
+                (geo.loc.x, geo.loc.y + indicator_height),
+                (geo.size.w, geo.size.h - indicator_height),
+            );
+
+            for member in group.windows().iter().filter_map(|id| id.window(self)) {
+                member.change_geometry(geo);
+            }
         }
 
         let (remaining_wins, _remaining_geos) = zipped.unzip::<_, _, Vec<_>, Vec<_>>();
@@ -96,57 +132,162 @@ impl Pinnacle {
             }
         }
 
+        let mut locations = Vec::new();
         let mut pending_wins = Vec::<(WindowElement, Serial)>::new();
-        let mut non_pending_wins = Vec::<(Point<i32, Logical>, WindowElement)>::new();
 
         for win in windows_on_foc_tags.iter() {
-            if win.with_state(|state| state.target_loc.is_some()) {
-                match win.underlying_surface() {
-                    WindowSurface::Wayland(toplevel) => {
-                        let pending = compositor::with_states(toplevel.wl_surface(), |states| {
-                            states
-                                .data_map
-                                .get::<XdgToplevelSurfaceData>()
-                                .expect("XdgToplevelSurfaceData wasn't in surface's data map")
-                                .lock()
-                                .expect("Failed to lock Mutex<XdgToplevelSurfaceData>")
-                                .has_pending_changes()
-                        });
-
-                        if pending {
-                            pending_wins.push((win.clone(), toplevel.send_configure()))
-                        } else {
-                            let loc = win.with_state_mut(|state| state.target_loc.take());
-                            if let Some(loc) = loc {
-                                non_pending_wins.push((loc, win.clone()));
-                            }
-                        }
-                    }
-                    WindowSurface::X11(_) => {
-                        let loc = win.with_state_mut(|state| state.target_loc.take());
-                        if let Some(loc) = loc {
-                            self.space.map_element(win.clone(), loc, false);
-                        }
-                    }
+            let Some(loc) = win.with_state_mut(|state| state.target_loc.take()) else {
+                continue;
+            };
+
+            if let WindowSurface::Wayland(toplevel) = win.underlying_surface() {
+                let pending = compositor::with_states(toplevel.wl_surface(), |states| {
+                    states
+                        .data_map
+                        .get::<XdgToplevelSurfaceData>()
+                        .expect("XdgToplevelSurfaceData wasn't in surface's data map")
+                        .lock()
+                        .expect("Failed to lock Mutex<XdgToplevelSurfaceData>")
+                        .has_pending_changes()
+                });
+
+                if pending {
+                    pending_wins.push((win.clone(), toplevel.send_configure()));
                 }
             }
-        }
 
-        for (loc, window) in non_pending_wins {
-            self.space.map_element(window, loc, false);
+            locations.push((win.clone(), loc));
         }
 
-        // FIXME:
-        // We are sending frames here to get offscreen windows to commit and map.
-        // Obviously this is a bad way to do this but its a bandaid solution
-        // until decent transactional layout applications are implemented.
-        for (win, _serial) in pending_wins {
+        // Windows that aren't drawn, like new windows mapped offscreen, don't get frame
+        // callbacks, so send them one to get them to commit.
+        for (win, _serial) in pending_wins.iter() {
             win.send_frame(output, self.clock.now(), Some(Duration::ZERO), |_, _| {
                 Some(output.clone())
             });
         }
 
-        self.fixup_z_layering();
+        self.start_layout_transaction(output, LayoutTransaction::new(locations, pending_wins));
+    }
+
+    /// Start a transaction that moves windows on `output` once they've committed their
+    /// new sizes.
+    ///
+    /// If the output already has a pending transaction, the new one is merged into it.
+    fn start_layout_transaction(&mut self, output: &Output, transaction: LayoutTransaction) {
+        let transaction = match self.layout_state.transactions.remove(output) {
+            Some(mut current) => {
+                current.merge(transaction);
+                current
+            }
+            None => transaction,
+        };
+        self.layout_state
+            .transactions
+            .insert(output.clone(), transaction);
+
+        // Other outputs' transactions may resolve here too, but this one still needs a timeout
+        if self.update_layout_transactions().contains(output) {
+            return;
+        }
+
+        let res = self.loop_handle.insert_source(
+            Timer::from_duration(TRANSACTION_TIMEOUT),
+            |_, _, state| {
+                for output in state.pinnacle.update_layout_transactions() {
+                    state.schedule_render(&output);
+                }
+                TimeoutAction::Drop
+            },
+        );
+
+        if let Err(err) = res {
+            warn!("Failed to insert layout transaction timer: {err}");
+        }
+    }
+
+    /// Apply every layout transaction whose windows have all committed or that timed out.
+    ///
+    /// Returns the outputs whose transactions were applied.
+    pub fn update_layout_transactions(&mut self) -> Vec<Output> {
+        let now = Instant::now();
+        let ready = self
+            .layout_state
+            .transactions
+            .iter_mut()
+            .filter_map(|(output, transaction)| transaction.is_ready(now).then(|| output.clone()))
+            .collect::<Vec<_>>();
+
+        for output in ready.iter() {
+            if let Some(transaction) = self.layout_state.transactions.remove(output) {
+                transaction.apply(&mut self.space);
+            }
+        }
+
+        if !ready.is_empty() {
+            self.fixup_z_layering();
+        }
+
+        ready
+    }
+
+    /// Get the windows on `output` that are laid out, in layout order.
+    ///
+    /// These are the tiled windows on the output's focused tags that aren't
+    /// hidden in a group.
+    pub fn layout_windows(&self, output: &Output) -> Vec<WindowElement> {
+        output.with_state(|state| {
+            let focused_tags = state.focused_tags().collect::<Vec<_>>();
+            self.windows
+                .iter()
+                .filter(|win| !win.is_x11_override_redirect())
+                .filter(|win| {
+                    win.with_state(|state| {
+                        state.tags.iter().any(|tg| focused_tags.contains(&tg))
+                            && state.floating_or_tiled.is_tiled()
+                            && state.fullscreen_or_maximized.is_neither()
+                    })
+                })
+                .filter(|win| !win.is_hidden_in_group())
+                .cloned()
+                .collect()
+        })
+    }
+
+    /// Focus and raise the window `offset` windows after the focused one on `output`,
+    /// in layout order, wrapping around.
+    ///
+    /// Returns the newly focused window.
+    pub fn cycle_tiled_focus(&mut self, output: &Output, offset: isize) -> Option<WindowElement> {
+        let windows = self.layout_windows(output);
+        if windows.is_empty() {
+            return None;
+        }
+
+        let next = match self
+            .focused_window(output)
+            .and_then(|focused| windows.iter().position(|win| win == &focused))
+        {
+            Some(index) => (index as isize + offset).rem_euclid(windows.len() as isize) as usize,
+            None => 0,
+        };
+        let next = windows[next].clone();
+
+        output.with_state_mut(|state| state.focus_stack.set_focus(next.clone()));
+        self.raise_window(next.clone(), true);
+        Some(next)
+    }
+
+    /// Get the gaps tiled windows on `output` have.
+    ///
+    /// If multiple tags are active, the largest of their gaps are used.
+    fn output_gaps(&self, output: &Output) -> Gaps {
+        output.with_state(|state| {
+            state
+                .focused_tags()
+                .map(|tag| tag.gaps())
+                .fold(Gaps::default(), Gaps::max)
+        })
     }
 
     /// Swaps two windows in the main window vec and updates all windows.
@@ -172,44 +313,111 @@ pub struct LayoutRequestId(pub u32);
 pub struct LayoutState {
     pub layout_request_sender: Option<UnboundedSender<Result<LayoutResponse, Status>>>,
     pub pending_swap: bool,
+    /// The layout used when no client is connected to the layout service.
+    pub fallback_layout: FallbackLayout,
+    /// Where new windows are inserted.
+    pub insert_position: InsertPosition,
+    /// A window the next new window is inserted right after, overriding the insert position once.
+    pub insert_marker: Option<WindowId>,
+    /// Whether outputs laid out with a monocle layout show which window of how many is shown.
+    pub monocle_indicator: bool,
     id_maps: HashMap<Output, LayoutRequestId>,
     pending_requests: HashMap<Output, Vec<(LayoutRequestId, Vec<WindowElement>)>>,
     old_requests: HashMap<Output, HashSet<LayoutRequestId>>,
+    transactions: HashMap<Output, LayoutTransaction>,
+}
+
+impl LayoutState {
+    /// Forget the layout client, falling back to compositor-side layouts.
+    fn remove_client(&mut self) {
+        debug!("Layout client disconnected, using the fallback layout");
+        self.layout_request_sender = None;
+        self.pending_requests.clear();
+    }
+
+    /// Returns whether `window` is waiting on a layout transaction to be moved.
+    pub fn is_in_transaction(&self, window: &WindowElement) -> bool {
+        self.transactions
+            .values()
+            .any(|transaction| transaction.contains(window))
+    }
 }
 
 impl Pinnacle {
+    /// Request a layout for `output` from the layout client.
+    ///
+    /// If no client is connected, the output is laid out with the fallback layout instead.
     pub fn request_layout(&mut self, output: &Output) {
-        let Some(sender) = self.layout_state.layout_request_sender.as_ref() else {
-            warn!("Layout requested but no client has connected to the layout service");
+        self.request_layout_with_resize(output, None);
+    }
+
+    /// Ask the layout client to resize the tiled `window` by the given amounts,
+    /// in logical pixels.
+    ///
+    /// The client adjusts its layout's split ratios and responds with a new layout.
+    /// Fallback layouts have fixed ratios, so this does nothing without a layout client.
+    pub fn resize_tile(&mut self, window: &WindowElement, width_delta: i32, height_delta: i32) {
+        let is_tiled = window.with_state(|state| {
+            state.floating_or_tiled.is_tiled() && state.fullscreen_or_maximized.is_neither()
+        });
+        if !is_tiled || !window.is_visible() || self.layout_state.layout_request_sender.is_none() {
+            return;
+        }
+
+        let Some(output) = window.output(self) else {
             return;
         };
 
-        let windows_on_foc_tags = output.with_state(|state| {
-            let focused_tags = state.focused_tags().collect::<Vec<_>>();
-            self.windows
-                .iter()
-                .filter(|win| !win.is_x11_override_redirect())
-                .filter(|win| {
-                    win.with_state(|state| state.tags.iter().any(|tg| focused_tags.contains(&tg)))
-                })
-                .cloned()
-                .collect::<Vec<_>>()
-        });
+        // Windows in a group are laid out as the group's active window
+        let window_id = match window.group() {
+            Some(group) => group.active(),
+            None => window.with_state(|state| state.id),
+        };
 
-        let windows = windows_on_foc_tags
-            .iter()
-            .filter(|win| {
-                win.with_state(|state| {
-                    state.floating_or_tiled.is_tiled() && state.fullscreen_or_maximized.is_neither()
-                })
-            })
-            .cloned()
-            .collect::<Vec<_>>();
+        self.request_layout_with_resize(
+            &output,
+            Some(TileResize {
+                window_id: Some(window_id.0),
+                width_delta: Some(width_delta),
+                height_delta: Some(height_delta),
+            }),
+        );
+    }
+
+    fn request_layout_with_resize(&mut self, output: &Output, resize: Option<TileResize>) {
+        if self
+            .layout_state
+            .layout_request_sender
+            .as_ref()
+            .is_some_and(|sender| sender.is_closed())
+        {
+            self.layout_state.remove_client();
+        }
+
+        let windows = self.layout_windows(output);
 
         let (output_width, output_height) = {
             let map = layer_map_for_output(output);
-            let zone = map.non_exclusive_zone();
-            (zone.size.w, zone.size.h)
+            let area = self
+                .output_gaps(output)
+                .layout_area(map.non_exclusive_zone().size);
+            (area.w, area.h)
+        };
+
+        let Some(sender) = self.layout_state.layout_request_sender.as_ref() else {
+            output.with_state_mut(|state| state.layout_name = None);
+            let geometries = self
+                .layout_state
+                .fallback_layout
+                .geometries(windows.len(), (output_width, output_height).into());
+            self.update_windows_with_geometries(output, geometries);
+            self.layout_state.pending_swap = false;
+
+            let output = output.clone();
+            self.loop_handle.insert_idle(move |state| {
+                state.schedule_render(&output);
+            });
+            return;
         };
 
         let window_ids = windows
@@ -217,8 +425,11 @@ impl Pinnacle {
             .map(|win| win.with_state(|state| state.id.0))
             .collect::<Vec<_>>();
 
-        let tag_ids =
-            output.with_state(|state| state.focused_tags().map(|tag| tag.id().0).collect());
+        let (tag_ids, layout) = output.with_state(|state| {
+            let tag_ids = state.focused_tags().map(|tag| tag.id().0).collect();
+            let layout = state.focused_tags().next().and_then(|tag| tag.layout());
+            (tag_ids, layout)
+        });
 
         let id = self
             .layout_state
@@ -240,6 +451,8 @@ impl Pinnacle {
             tag_ids,
             output_width: Some(output_width as u32),
             output_height: Some(output_height as u32),
+            layout,
+            resize,
         }));
 
         *id = LayoutRequestId(id.0 + 1);
@@ -247,11 +460,39 @@ impl Pinnacle {
 }
 
 impl State {
+    /// Lay out every output again.
+    ///
+    /// Call this when a layout client connects or disconnects so it takes over
+    /// from the previous one.
+    pub fn relayout_all(&mut self) {
+        for output in self.pinnacle.space.outputs().cloned().collect::<Vec<_>>() {
+            self.pinnacle.request_layout(&output);
+        }
+    }
+
+    /// Fall back to compositor-side layouts after the layout client with `sender`
+    /// disconnects, unless another client has taken over since.
+    pub fn layout_client_disconnected(
+        &mut self,
+        sender: &UnboundedSender<Result<LayoutResponse, Status>>,
+    ) {
+        let layout_state = &mut self.pinnacle.layout_state;
+        if layout_state
+            .layout_request_sender
+            .as_ref()
+            .is_some_and(|current| current.same_channel(sender))
+        {
+            layout_state.remove_client();
+            self.relayout_all();
+        }
+    }
+
     pub fn apply_layout(&mut self, geometries: Geometries) -> anyhow::Result<()> {
         let Geometries {
             request_id: Some(request_id),
             output_name: Some(output_name),
             geometries,
+            layout_name,
         } = geometries
         else {
             anyhow::bail!("One or more `geometries` fields were None");
@@ -315,6 +556,8 @@ impl State {
         self.pinnacle
             .update_windows_with_geometries(&output, geometries);
 
+        output.with_state_mut(|state| state.layout_name = layout_name);
+
         self.schedule_render(&output);
 
         self.pinnacle.layout_state.pending_swap = false;
@@ -0,0 +1,186 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Compositor-side layouts.
+//!
+//! These are used when no client is connected to the layout service,
+//! such as before the config starts or after it crashes.
+
+use smithay::utils::{Logical, Rectangle, Size};
+
+/// A layout the compositor uses when no layout client is connected.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FallbackLayout {
+    /// One master window on the left and the rest stacked on the right.
+    #[default]
+    MasterStack,
+    /// Each window takes half of the space left by the previous one,
+    /// alternating between splitting horizontally and vertically.
+    Dwindle,
+    /// Windows are arranged in a grid of equally sized cells.
+    Grid,
+}
+
+impl FallbackLayout {
+    /// Lay out `count` windows in an area of `size`.
+    ///
+    /// Returned geometries are relative to the area's top left corner.
+    pub fn geometries(
+        self,
+        count: usize,
+        size: Size<i32, Logical>,
+    ) -> Vec<Rectangle<i32, Logical>> {
+        match self {
+            FallbackLayout::MasterStack => master_stack(count, size),
+            FallbackLayout::Dwindle => dwindle(count, size),
+            FallbackLayout::Grid => grid(count, size),
+        }
+    }
+}
+
+/// Split `len` into `count` parts as evenly as possible.
+///
+/// Returns the offset and length of each part.
+fn split(len: i32, count: usize) -> impl Iterator<Item = (i32, i32)> {
+    let count = count.max(1) as i32;
+    (0..count).map(move |i| {
+        let start = len * i / count;
+        let end = len * (i + 1) / count;
+        (start, end - start)
+    })
+}
+
+fn master_stack(count: usize, size: Size<i32, Logical>) -> Vec<Rectangle<i32, Logical>> {
+    if count <= 1 {
+        return vec![Rectangle::from_loc_and_size((0, 0), size); count];
+    }
+
+    let master_width = size.w / 2;
+    let mut geos = vec![Rectangle::from_loc_and_size((0, 0), (master_width, size.h))];
+
+    geos.extend(
+        split(size.h, count - 1).map(|(y, h)| {
+            Rectangle::from_loc_and_size((master_width, y), (size.w - master_width, h))
+        }),
+    );
+
+    geos
+}
+
+fn dwindle(count: usize, size: Size<i32, Logical>) -> Vec<Rectangle<i32, Logical>> {
+    let mut remaining = Rectangle::from_loc_and_size((0, 0), size);
+    let mut geos = Vec::with_capacity(count);
+
+    for i in 0..count {
+        if i == count - 1 {
+            geos.push(remaining);
+            break;
+        }
+
+        if i % 2 == 0 {
+            let w = remaining.size.w / 2;
+            geos.push(Rectangle::from_loc_and_size(
+                remaining.loc,
+                (w, remaining.size.h),
+            ));
+            remaining.loc.x += w;
+            remaining.size.w -= w;
+        } else {
+            let h = remaining.size.h / 2;
+            geos.push(Rectangle::from_loc_and_size(
+                remaining.loc,
+                (remaining.size.w, h),
+            ));
+            remaining.loc.y += h;
+            remaining.size.h -= h;
+        }
+    }
+
+    geos
+}
+
+fn grid(count: usize, size: Size<i32, Logical>) -> Vec<Rectangle<i32, Logical>> {
+    if count == 0 {
+        return Vec::new();
+    }
+
+    let cols = (count as f64).sqrt().ceil() as usize;
+    let rows = count.div_ceil(cols);
+
+    split(size.h, rows)
+        .enumerate()
+        .flat_map(|(row, (y, h))| {
+            // The last row may have fewer windows; they share its whole width
+            let cols_in_row = if row == rows - 1 {
+                count - cols * (rows - 1)
+            } else {
+                cols
+            };
+            split(size.w, cols_in_row)
+                .map(move |(x, w)| Rectangle::from_loc_and_size((x, y), (w, h)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn size() -> Size<i32, Logical> {
+        Size::from((1000, 600))
+    }
+
+    fn rect(x: i32, y: i32, w: i32, h: i32) -> Rectangle<i32, Logical> {
+        Rectangle::from_loc_and_size((x, y), (w, h))
+    }
+
+    #[test]
+    fn no_windows_no_geometries() {
+        for layout in [
+            FallbackLayout::MasterStack,
+            FallbackLayout::Dwindle,
+            FallbackLayout::Grid,
+        ] {
+            assert!(layout.geometries(0, size()).is_empty());
+        }
+    }
+
+    #[test]
+    fn master_stack_splits_the_stack_evenly() {
+        assert_eq!(
+            FallbackLayout::MasterStack.geometries(3, size()),
+            [
+                rect(0, 0, 500, 600),
+                rect(500, 0, 500, 300),
+                rect(500, 300, 500, 300),
+            ]
+        );
+    }
+
+    #[test]
+    fn dwindle_alternates_split_direction() {
+        assert_eq!(
+            FallbackLayout::Dwindle.geometries(3, size()),
+            [
+                rect(0, 0, 500, 600),
+                rect(500, 0, 500, 300),
+                rect(500, 300, 500, 300),
+            ]
+        );
+        assert_eq!(
+            FallbackLayout::Dwindle.geometries(4, size())[3],
+            rect(750, 300, 250, 300)
+        );
+    }
+
+    #[test]
+    fn grid_fills_the_last_row() {
+        assert_eq!(
+            FallbackLayout::Grid.geometries(3, size()),
+            [
+                rect(0, 0, 500, 300),
+                rect(500, 0, 500, 300),
+                rect(0, 300, 1000, 300),
+            ]
+        );
+    }
+}
@@ -0,0 +1,108 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Gaps between tiled windows.
+//!
+//! Layouts are computed in an area inset from the output's non-exclusive zone by the outer gaps.
+//! Window edges that don't touch that area's boundary are then pulled in by half of the inner
+//! gap, so neighboring windows end up the full inner gap apart.
+
+use smithay::utils::{Logical, Rectangle, Size};
+
+/// Gaps around tiled windows, in logical pixels.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Gaps {
+    /// The gap between neighboring windows.
+    pub inner: u32,
+    /// The gap between windows and the edges of the layout area.
+    pub outer: u32,
+}
+
+impl Gaps {
+    /// Get gaps that are at least as large as both `self` and `other`.
+    pub fn max(self, other: Gaps) -> Gaps {
+        Gaps {
+            inner: self.inner.max(other.inner),
+            outer: self.outer.max(other.outer),
+        }
+    }
+
+    /// Get the size of the area windows are laid out in for a non-exclusive zone of `zone_size`.
+    pub fn layout_area(&self, zone_size: Size<i32, Logical>) -> Size<i32, Logical> {
+        let outer = self.outer as i32;
+        Size::from((
+            i32::max(zone_size.w - outer * 2, 1),
+            i32::max(zone_size.h - outer * 2, 1),
+        ))
+    }
+
+    /// Apply these gaps to a window geometry computed for a layout area of `area_size`.
+    ///
+    /// The returned geometry is relative to the non-exclusive zone instead of the layout area.
+    pub fn apply(
+        &self,
+        geo: Rectangle<i32, Logical>,
+        area_size: Size<i32, Logical>,
+    ) -> Rectangle<i32, Logical> {
+        let outer = self.outer as i32;
+        // Split odd gaps so that the two sides facing each other still add up to the whole gap
+        let leading = self.inner as i32 / 2;
+        let trailing = self.inner as i32 - leading;
+        let inset = |touches_edge: bool, amount: i32| if touches_edge { 0 } else { amount };
+
+        let left = inset(geo.loc.x <= 0, leading);
+        let top = inset(geo.loc.y <= 0, leading);
+        let right = inset(geo.loc.x + geo.size.w >= area_size.w, trailing);
+        let bottom = inset(geo.loc.y + geo.size.h >= area_size.h, trailing);
+
+        Rectangle::from_loc_and_size(
+            (geo.loc.x + outer + left, geo.loc.y + outer + top),
+            (
+                i32::max(geo.size.w - left - right, 1),
+                i32::max(geo.size.h - top - bottom, 1),
+            ),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: i32, y: i32, w: i32, h: i32) -> Rectangle<i32, Logical> {
+        Rectangle::from_loc_and_size((x, y), (w, h))
+    }
+
+    #[test]
+    fn layout_area_is_inset_by_outer_gaps() {
+        let gaps = Gaps {
+            inner: 8,
+            outer: 10,
+        };
+        assert_eq!(
+            gaps.layout_area(Size::from((1000, 600))),
+            Size::from((980, 580))
+        );
+    }
+
+    #[test]
+    fn neighbors_are_inner_gap_apart() {
+        let gaps = Gaps {
+            inner: 8,
+            outer: 10,
+        };
+        let area = Size::from((980, 580));
+
+        let left = gaps.apply(rect(0, 0, 490, 580), area);
+        let right = gaps.apply(rect(490, 0, 490, 580), area);
+
+        assert_eq!(left, rect(10, 10, 486, 580));
+        assert_eq!(right, rect(504, 10, 486, 580));
+        assert_eq!(right.loc.x - (left.loc.x + left.size.w), 8);
+    }
+
+    #[test]
+    fn no_gaps_leave_geometry_unchanged() {
+        let geo = rect(100, 50, 300, 200);
+        assert_eq!(Gaps::default().apply(geo, Size::from((1000, 600))), geo);
+    }
+}
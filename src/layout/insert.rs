@@ -0,0 +1,92 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Where new windows are inserted into the window list.
+//!
+//! Layouts receive windows in the order of the window list, so this decides
+//! where a new tiled window ends up in the layout.
+
+use smithay::output::Output;
+
+use crate::{state::Pinnacle, window::WindowElement};
+
+/// Where new windows are inserted.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum InsertPosition {
+    /// After every other window.
+    #[default]
+    End,
+    /// Right after the focused window.
+    AfterFocused,
+    /// Before every other window, making it the master window in master-stack layouts.
+    Master,
+}
+
+impl InsertPosition {
+    /// Get the index in `windows` a new window should be inserted at.
+    ///
+    /// If `focused` isn't in `windows`, [`InsertPosition::AfterFocused`] inserts at the end.
+    pub fn index<T: PartialEq>(self, windows: &[T], focused: Option<&T>) -> usize {
+        match self {
+            InsertPosition::End => windows.len(),
+            InsertPosition::AfterFocused => focused
+                .and_then(|focused| windows.iter().position(|win| win == focused))
+                .map_or(windows.len(), |index| index + 1),
+            InsertPosition::Master => 0,
+        }
+    }
+}
+
+impl Pinnacle {
+    /// Add a new window to the window list.
+    ///
+    /// The window goes right after the window marked with
+    /// [`LayoutState::insert_marker`][crate::layout::LayoutState::insert_marker], if any,
+    /// consuming the marker. Otherwise it goes where the insert position puts it,
+    /// using the focused window on `output`.
+    pub fn insert_new_window(&mut self, window: WindowElement, output: Option<&Output>) {
+        let marked = self
+            .layout_state
+            .insert_marker
+            .take()
+            .and_then(|id| id.window(self));
+
+        let index = match marked {
+            Some(marked) => InsertPosition::AfterFocused.index(&self.windows, Some(&marked)),
+            None => {
+                let focused = output.and_then(|op| self.focused_window(op));
+                self.layout_state
+                    .insert_position
+                    .index(&self.windows, focused.as_ref())
+            }
+        };
+
+        self.windows.insert(index, window);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn end_appends() {
+        assert_eq!(InsertPosition::End.index(&[1, 2, 3], Some(&1)), 3);
+    }
+
+    #[test]
+    fn after_focused_follows_the_focused_window() {
+        assert_eq!(InsertPosition::AfterFocused.index(&[1, 2, 3], Some(&2)), 2);
+        assert_eq!(InsertPosition::AfterFocused.index(&[1, 2, 3], Some(&3)), 3);
+    }
+
+    #[test]
+    fn after_focused_without_focus_appends() {
+        assert_eq!(InsertPosition::AfterFocused.index(&[1, 2, 3], None), 3);
+        assert_eq!(InsertPosition::AfterFocused.index(&[1, 2, 3], Some(&4)), 3);
+    }
+
+    #[test]
+    fn master_goes_first() {
+        assert_eq!(InsertPosition::Master.index(&[1, 2, 3], Some(&2)), 0);
+    }
+}
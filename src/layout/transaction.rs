@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Layout transactions.
+//!
+//! When the layout changes, windows are sent configures with their new sizes. Moving them
+//! to their new locations right away would show stale buffers at the new locations until
+//! the clients catch up, so the new locations are gathered into a transaction instead.
+//! The transaction is applied all at once after every window has acked and committed
+//! its configure, or after [`TRANSACTION_TIMEOUT`] passes so unresponsive clients can't
+//! hold up the layout.
+
+use std::time::{Duration, Instant};
+
+use smithay::{
+    desktop::Space,
+    utils::{IsAlive, Logical, Point, Serial},
+};
+
+use crate::{state::WithState, window::WindowElement};
+
+/// How long a transaction waits for windows to commit before being applied anyway.
+pub const TRANSACTION_TIMEOUT: Duration = Duration::from_millis(150);
+
+/// New window locations waiting on windows to commit their configures.
+#[derive(Debug)]
+pub struct LayoutTransaction {
+    /// The locations windows will be mapped at.
+    locations: Vec<(WindowElement, Point<i32, Logical>)>,
+    /// Windows that haven't committed the configure with the given serial yet.
+    pending: Vec<(WindowElement, Serial)>,
+    start: Instant,
+}
+
+impl LayoutTransaction {
+    pub fn new(
+        locations: Vec<(WindowElement, Point<i32, Logical>)>,
+        pending: Vec<(WindowElement, Serial)>,
+    ) -> Self {
+        Self {
+            locations,
+            pending,
+            start: Instant::now(),
+        }
+    }
+
+    /// Fold a newer transaction into this one.
+    ///
+    /// Locations and serials from `newer` replace those of the same windows. The start time
+    /// is kept so constant relayouts can't postpone the transaction past the timeout.
+    pub fn merge(&mut self, newer: LayoutTransaction) {
+        self.locations
+            .retain(|(win, _)| !newer.locations.iter().any(|(new_win, _)| new_win == win));
+        self.locations.extend(newer.locations);
+
+        self.pending
+            .retain(|(win, _)| !newer.pending.iter().any(|(new_win, _)| new_win == win));
+        self.pending.extend(newer.pending);
+    }
+
+    /// Returns whether `window` will be moved when this transaction is applied.
+    pub fn contains(&self, window: &WindowElement) -> bool {
+        self.locations.iter().any(|(win, _)| win == window)
+    }
+
+    /// Returns whether every window has committed its configure or the transaction timed out.
+    pub fn is_ready(&mut self, now: Instant) -> bool {
+        self.pending
+            .retain(|(win, serial)| win.alive() && !win.has_committed(*serial));
+
+        self.pending.is_empty() || now.duration_since(self.start) >= TRANSACTION_TIMEOUT
+    }
+
+    /// Map every window at its new location.
+    ///
+    /// Windows being dragged to swap tiles are left where they are.
+    pub fn apply(self, space: &mut Space<WindowElement>) {
+        for (win, loc) in self.locations {
+            if win.alive() && !win.with_state(|state| state.swap_ghost) {
+                space.map_element(win, loc, false);
+            }
+        }
+    }
+}
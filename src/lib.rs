@@ -1,5 +1,6 @@
 #![warn(clippy::unwrap_used)]
 
+pub mod animation;
 pub mod api;
 pub mod backend;
 pub mod cli;
@@ -10,9 +11,13 @@ pub mod grab;
 pub mod handlers;
 pub mod input;
 pub mod layout;
+pub mod night_light;
 pub mod output;
 pub mod protocol;
+pub mod recording;
 pub mod render;
+pub mod screenshot;
+pub mod session;
 pub mod state;
 pub mod tag;
 pub mod window;
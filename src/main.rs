@@ -16,7 +16,9 @@ use std::io::{BufRead, BufReader};
 use anyhow::Context;
 use nix::unistd::Uid;
 use pinnacle::{
-    backend::{udev::setup_udev, winit::setup_winit},
+    backend::{
+        headless::setup_headless, nested::setup_nested, udev::setup_udev, winit::setup_winit,
+    },
     cli::{self, Cli},
 };
 use tracing::{error, info, warn};
@@ -127,6 +129,24 @@ async fn main() -> anyhow::Result<()> {
                 setup_udev(cli.no_config, cli.config_dir)?
             }
         }
+        (Some(cli::Backend::Headless), _) => {
+            let output_sizes = if cli.headless_output.is_empty() {
+                vec![(1920, 1080).into()]
+            } else {
+                cli.headless_output.into_iter().map(Into::into).collect()
+            };
+            info!("Starting headless backend");
+            setup_headless(cli.no_config, cli.config_dir, output_sizes)?
+        }
+        (Some(cli::Backend::Nested), _) => {
+            if std::env::var("WAYLAND_DISPLAY").is_err() {
+                warn!("WAYLAND_DISPLAY is not set.");
+                warn!("The nested backend needs to run inside another Wayland compositor.");
+                return Ok(());
+            }
+            info!("Starting nested backend");
+            setup_nested(cli.no_config, cli.config_dir)?
+        }
     };
 
     event_loop.run(None, &mut state, |state| {
@@ -134,6 +154,13 @@ async fn main() -> anyhow::Result<()> {
         state.pinnacle.fixup_z_layering();
         state.pinnacle.space.refresh();
         state.pinnacle.popup_manager.cleanup();
+        state.pinnacle.update_tag_history();
+        state.pinnacle.update_tag_occupancy();
+        state.pinnacle.update_dynamic_tags();
+        state.update_xkb_layout();
+        state.update_pointer_warp();
+        state.pinnacle.update_ext_workspaces();
+        state.pinnacle.update_ext_foreign_toplevels();
 
         state
             .pinnacle
@@ -0,0 +1,330 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Night light.
+//!
+//! While an output's night light is on, its colors are shifted towards a warmer color
+//! temperature. This is done through the crtc's gamma ramp, so it only works on the udev
+//! backend. Gamma ramps set by gamma control clients like gammastep are shifted as well.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Local, NaiveTime, TimeZone, Timelike, Utc};
+use smithay::{
+    output::Output,
+    reexports::calloop::timer::{TimeoutAction, Timer},
+};
+use tracing::{error, warn};
+
+use crate::{
+    backend::Backend,
+    state::{State, WithState},
+};
+
+/// The color temperature that leaves colors unchanged, in Kelvin.
+pub const NEUTRAL_TEMPERATURE: u32 = 6500;
+/// The lowest and highest allowed color temperatures, in Kelvin.
+pub const TEMPERATURE_RANGE: std::ops::RangeInclusive<u32> = 1000..=10000;
+
+/// How long the night light takes to fade in and out.
+const TRANSITION: Duration = Duration::from_secs(30 * 60);
+/// How often the night light is updated to follow its schedule.
+const UPDATE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// When a night light is on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NightLightSchedule {
+    /// Always on.
+    Always,
+    /// On between two local times.
+    Fixed { from: NaiveTime, to: NaiveTime },
+    /// On from sunset to sunrise at a location.
+    Sun { latitude: f64, longitude: f64 },
+}
+
+/// An output's night light.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NightLight {
+    /// The color temperature while the night light is on, in Kelvin.
+    pub temperature: u32,
+    pub schedule: NightLightSchedule,
+}
+
+impl NightLight {
+    /// How far the night light is on at `now`, from 0.0 (off) to 1.0 (fully on).
+    pub fn strength(&self, now: DateTime<Utc>) -> f64 {
+        match self.schedule {
+            NightLightSchedule::Always => 1.0,
+            NightLightSchedule::Fixed { from, to } => {
+                window_strength(now.with_timezone(&Local).time(), from, to)
+            }
+            NightLightSchedule::Sun {
+                latitude,
+                longitude,
+            } => match sun_times(now, latitude, longitude) {
+                SunTimes::PolarNight => 1.0,
+                SunTimes::PolarDay => 0.0,
+                SunTimes::Normal { sunrise, sunset } => window_strength(
+                    now.with_timezone(&Local).time(),
+                    sunset.with_timezone(&Local).time(),
+                    sunrise.with_timezone(&Local).time(),
+                ),
+            },
+        }
+    }
+
+    /// The color temperature the output should have at `now`, in Kelvin.
+    pub fn current_temperature(&self, now: DateTime<Utc>) -> u32 {
+        let strength = self.strength(now);
+        let neutral = f64::from(NEUTRAL_TEMPERATURE);
+        let target = f64::from(self.temperature);
+        (neutral + (target - neutral) * strength).round() as u32
+    }
+}
+
+/// How far a night light on from `from` to `to` is on at `now`, fading in after `from`
+/// and out before `to`.
+fn window_strength(now: NaiveTime, from: NaiveTime, to: NaiveTime) -> f64 {
+    const DAY: f64 = 24.0 * 60.0 * 60.0;
+
+    let seconds = |time: NaiveTime| f64::from(time.num_seconds_from_midnight());
+
+    let length = (seconds(to) - seconds(from)).rem_euclid(DAY);
+    let elapsed = (seconds(now) - seconds(from)).rem_euclid(DAY);
+
+    if elapsed >= length {
+        return 0.0;
+    }
+
+    // Short windows never reach full strength
+    let transition = TRANSITION.as_secs_f64();
+    (elapsed / transition)
+        .min((length - elapsed) / transition)
+        .min(1.0)
+}
+
+/// When the sun rises and sets on a day.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SunTimes {
+    Normal {
+        sunrise: DateTime<Utc>,
+        sunset: DateTime<Utc>,
+    },
+    /// The sun doesn't rise.
+    PolarNight,
+    /// The sun doesn't set.
+    PolarDay,
+}
+
+/// Calculate when the sun rises and sets on the day of `now` with the sunrise equation.
+fn sun_times(now: DateTime<Utc>, latitude: f64, longitude: f64) -> SunTimes {
+    const J2000: f64 = 2451545.0;
+    const UNIX_EPOCH_JULIAN: f64 = 2440587.5;
+    const SECONDS_PER_DAY: f64 = 86400.0;
+
+    let julian_date = now.timestamp() as f64 / SECONDS_PER_DAY + UNIX_EPOCH_JULIAN;
+    // Julian dates start at noon, so this is the start of the UTC day
+    let midnight = (julian_date - 0.5).floor() + 0.5;
+    let day = (midnight - J2000 + 0.0008).ceil();
+
+    let mean_solar_time = day - longitude / 360.0;
+    let mean_anomaly = (357.5291 + 0.98560028 * mean_solar_time).rem_euclid(360.0);
+    let m = mean_anomaly.to_radians();
+    let center = 1.9148 * m.sin() + 0.02 * (2.0 * m).sin() + 0.0003 * (3.0 * m).sin();
+    let ecliptic_longitude = (mean_anomaly + center + 180.0 + 102.9372)
+        .rem_euclid(360.0)
+        .to_radians();
+    let transit =
+        J2000 + mean_solar_time + 0.0053 * m.sin() - 0.0069 * (2.0 * ecliptic_longitude).sin();
+
+    let declination = (ecliptic_longitude.sin() * 23.4397f64.to_radians().sin()).asin();
+    let latitude = latitude.to_radians();
+    let cos_hour_angle = ((-0.833f64).to_radians().sin() - latitude.sin() * declination.sin())
+        / (latitude.cos() * declination.cos());
+
+    if cos_hour_angle > 1.0 {
+        return SunTimes::PolarNight;
+    }
+    if cos_hour_angle < -1.0 {
+        return SunTimes::PolarDay;
+    }
+
+    let hour_angle = cos_hour_angle.acos().to_degrees();
+
+    let to_utc = |julian_date: f64| {
+        let timestamp = (julian_date - UNIX_EPOCH_JULIAN) * SECONDS_PER_DAY;
+        Utc.timestamp_opt(timestamp as i64, 0)
+            .single()
+            .unwrap_or(now)
+    };
+
+    SunTimes::Normal {
+        sunrise: to_utc(transit - hour_angle / 360.0),
+        sunset: to_utc(transit + hour_angle / 360.0),
+    }
+}
+
+/// Get what the red, green, and blue channels are multiplied by for a color temperature.
+///
+/// This uses Tanner Helland's approximation of the color of a black body,
+/// scaled so that [`NEUTRAL_TEMPERATURE`] is white.
+pub fn whitepoint(temperature: u32) -> [f64; 3] {
+    fn rgb(temperature: u32) -> [f64; 3] {
+        let temp = f64::from(temperature) / 100.0;
+
+        let red = if temp <= 66.0 {
+            255.0
+        } else {
+            329.698727446 * (temp - 60.0).powf(-0.1332047592)
+        };
+        let green = if temp <= 66.0 {
+            99.4708025861 * temp.ln() - 161.1195681661
+        } else {
+            288.1221695283 * (temp - 60.0).powf(-0.0755148492)
+        };
+        let blue = if temp >= 66.0 {
+            255.0
+        } else if temp <= 19.0 {
+            0.0
+        } else {
+            138.5177312231 * (temp - 10.0).ln() - 305.0447927307
+        };
+
+        [red, green, blue].map(|channel| channel.clamp(0.0, 255.0) / 255.0)
+    }
+
+    let neutral = rgb(NEUTRAL_TEMPERATURE);
+    let [red, green, blue] = rgb(temperature);
+
+    [red / neutral[0], green / neutral[1], blue / neutral[2]].map(|channel| channel.min(1.0))
+}
+
+impl State {
+    /// Set or remove an output's night light.
+    pub fn set_night_light(&mut self, output: &Output, night_light: Option<NightLight>) {
+        if !self.backend.is_udev() && night_light.is_some() {
+            warn!("Night light is only supported on the udev backend");
+        }
+
+        let timer = output.with_state_mut(|state| {
+            state.night_light = night_light;
+            state.night_light_timer.take()
+        });
+
+        if let Some(timer) = timer {
+            self.pinnacle.loop_handle.remove(timer);
+        }
+
+        self.apply_night_light(output);
+
+        if night_light.is_none() {
+            return;
+        }
+
+        let timer_output = output.clone();
+        let res = self.pinnacle.loop_handle.insert_source(
+            Timer::from_duration(UPDATE_INTERVAL),
+            move |_, _, state| {
+                let output = &timer_output;
+
+                let exists = state.pinnacle.space.outputs().any(|op| op == output)
+                    || state.pinnacle.disabled_outputs.contains(output);
+                if !exists {
+                    return TimeoutAction::Drop;
+                }
+
+                state.apply_night_light(output);
+                TimeoutAction::ToDuration(UPDATE_INTERVAL)
+            },
+        );
+
+        match res {
+            Ok(token) => output.with_state_mut(|state| state.night_light_timer = Some(token)),
+            Err(err) => error!("Failed to insert night light timer: {err}"),
+        }
+    }
+
+    /// Update the gamma of an output to match its night light's schedule.
+    fn apply_night_light(&mut self, output: &Output) {
+        let Backend::Udev(udev) = &mut self.backend else {
+            return;
+        };
+
+        let whitepoint = output
+            .with_state(|state| state.night_light)
+            .map(|night_light| whitepoint(night_light.current_temperature(Utc::now())));
+
+        if let Err(err) = udev.set_night_light(output, whitepoint) {
+            warn!(
+                "Failed to set night light for output {}: {err}",
+                output.name()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn neutral_whitepoint_is_white() {
+        assert_eq!(whitepoint(NEUTRAL_TEMPERATURE), [1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn warm_whitepoint_has_less_blue() {
+        let [red, green, blue] = whitepoint(3000);
+        assert_eq!(red, 1.0);
+        assert!(green < red);
+        assert!(blue < green);
+    }
+
+    #[test]
+    fn window_strength_wraps_around_midnight() {
+        let (from, to) = (time(21, 0), time(7, 0));
+
+        assert_eq!(window_strength(time(12, 0), from, to), 0.0);
+        assert_eq!(window_strength(time(23, 0), from, to), 1.0);
+        assert_eq!(window_strength(time(3, 0), from, to), 1.0);
+        assert_eq!(window_strength(time(21, 15), from, to), 0.5);
+        assert_eq!(window_strength(time(6, 45), from, to), 0.5);
+        assert_eq!(window_strength(time(7, 0), from, to), 0.0);
+    }
+
+    #[test]
+    fn sun_times_at_equinox_on_equator() {
+        let noon = NaiveDate::from_ymd_opt(2024, 3, 20)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_utc();
+
+        let SunTimes::Normal { sunrise, sunset } = sun_times(noon, 0.0, 0.0) else {
+            panic!("sun should rise and set");
+        };
+
+        let expected_sunrise = noon.date_naive().and_hms_opt(6, 4, 0).unwrap().and_utc();
+        let expected_sunset = noon.date_naive().and_hms_opt(18, 10, 0).unwrap().and_utc();
+
+        assert!((sunrise - expected_sunrise).num_minutes().abs() < 10);
+        assert!((sunset - expected_sunset).num_minutes().abs() < 10);
+    }
+
+    #[test]
+    fn sun_times_in_arctic_winter() {
+        let noon = NaiveDate::from_ymd_opt(2024, 12, 21)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_utc();
+
+        assert_eq!(sun_times(noon, 80.0, 0.0), SunTimes::PolarNight);
+        assert_eq!(sun_times(noon, -80.0, 0.0), SunTimes::PolarDay);
+    }
+}
@@ -1,20 +1,37 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use std::{cell::RefCell, num::NonZeroU32};
+pub mod profile;
+
+use std::{cell::RefCell, num::NonZeroU32, str::FromStr, time::Duration};
+
+use anyhow::Context;
 
 use pinnacle_api_defs::pinnacle::signal::v0alpha1::{OutputMoveResponse, OutputResizeResponse};
 use smithay::{
     desktop::layer_map_for_output,
     output::{Mode, Output, Scale},
-    utils::{Logical, Point, Transform},
+    reexports::{calloop::RegistrationToken, wayland_server::protocol::wl_surface::WlSurface},
+    utils::{Logical, Physical, Point, Rectangle, Size, Transform},
+    wayland::{compositor::SurfaceData, fractional_scale::with_fractional_scale},
 };
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::{
+    backend::BackendData,
     focus::WindowKeyboardFocusStack,
+    input::LidSwitchPolicy,
+    night_light::NightLight,
     protocol::screencopy::Screencopy,
-    state::{Pinnacle, WithState},
-    tag::Tag,
+    render::{
+        color_filter::ColorFilter,
+        damage_debug::DamageDebug,
+        monocle_indicator::MonocleIndicator,
+        perf_overlay::{FrameStats, PerfOverlay},
+        wallpaper::Wallpaper,
+    },
+    state::{Pinnacle, State, WithState},
+    tag::{Tag, TagHistory},
+    window::window_state::FloatingOrTiled,
 };
 
 /// A unique identifier for an output.
@@ -35,13 +52,422 @@ impl OutputName {
     }
 }
 
+/// Whether an output should use variable refresh rate.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Vrr {
+    #[default]
+    Off,
+    On,
+    /// Only enable VRR while a fullscreen game or video is being scanned out.
+    OnDemand,
+}
+
+/// When an output starts rendering a frame.
+///
+/// Rendering as late as possible before vblank shortens the time between input
+/// and it showing up on screen, at the risk of missing the vblank.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MaxRenderTime {
+    /// Render right after vblank.
+    #[default]
+    Off,
+    /// Start rendering this long before the next vblank.
+    Fixed(Duration),
+    /// Start rendering based on how long recent frames took to render.
+    Adaptive,
+}
+
+impl MaxRenderTime {
+    /// How much longer than the 99th percentile frame time to allow frames to take
+    /// in [`MaxRenderTime::Adaptive`].
+    const ADAPTIVE_SLACK: Duration = Duration::from_millis(1);
+
+    /// Get how long before vblank to start rendering, or `None` to render right away.
+    pub fn render_time(&self, frame_stats: &FrameStats) -> Option<Duration> {
+        match self {
+            MaxRenderTime::Off => None,
+            MaxRenderTime::Fixed(time) => Some(*time),
+            MaxRenderTime::Adaptive => {
+                let frame_time = frame_stats.frame_time(0.99);
+                // Without any measurements there is nothing to go off of
+                (!frame_time.is_zero()).then_some(frame_time + Self::ADAPTIVE_SLACK)
+            }
+        }
+    }
+}
+
+/// Get how many refresh cycles each frame should be shown for so an output refreshing at
+/// `refresh_millihz` shows at most `max_fps` frames per second.
+pub fn refreshes_per_frame(refresh_millihz: i32, max_fps: Option<NonZeroU32>) -> u32 {
+    let Some(max_fps) = max_fps else {
+        return 1;
+    };
+
+    let ratio = f64::from(refresh_millihz) / 1000.0 / f64::from(max_fps.get());
+    // Modes are rarely exactly their nominal refresh rate; a 60.01 Hz mode capped
+    // to 60 fps shouldn't drop to 30
+    (ratio - 0.01).ceil().max(1.0) as u32
+}
+
+/// Where to place an output next to another one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
+    TopAlignLeft,
+    TopAlignCenter,
+    TopAlignRight,
+    BottomAlignLeft,
+    BottomAlignCenter,
+    BottomAlignRight,
+    LeftAlignTop,
+    LeftAlignCenter,
+    LeftAlignBottom,
+    RightAlignTop,
+    RightAlignCenter,
+    RightAlignBottom,
+}
+
+impl Alignment {
+    /// Get the location of an output of `size` placed next to `other` with this alignment.
+    pub fn loc_adjacent_to(
+        self,
+        size: Size<i32, Logical>,
+        other: Rectangle<i32, Logical>,
+    ) -> Point<i32, Logical> {
+        use Alignment::*;
+
+        let start_x = other.loc.x;
+        let center_x = other.loc.x + (other.size.w - size.w) / 2;
+        let end_x = other.loc.x + other.size.w - size.w;
+        let start_y = other.loc.y;
+        let center_y = other.loc.y + (other.size.h - size.h) / 2;
+        let end_y = other.loc.y + other.size.h - size.h;
+
+        let above = other.loc.y - size.h;
+        let below = other.loc.y + other.size.h;
+        let left = other.loc.x - size.w;
+        let right = other.loc.x + other.size.w;
+
+        match self {
+            TopAlignLeft => (start_x, above),
+            TopAlignCenter => (center_x, above),
+            TopAlignRight => (end_x, above),
+            BottomAlignLeft => (start_x, below),
+            BottomAlignCenter => (center_x, below),
+            BottomAlignRight => (end_x, below),
+            LeftAlignTop => (left, start_y),
+            LeftAlignCenter => (left, center_y),
+            LeftAlignBottom => (left, end_y),
+            RightAlignTop => (right, start_y),
+            RightAlignCenter => (right, center_y),
+            RightAlignBottom => (right, end_y),
+        }
+        .into()
+    }
+}
+
+/// Custom mode timings for an output, in the same order as an X11 modeline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Modeline {
+    /// The pixel clock in kilohertz
+    pub clock: f32,
+    pub hdisplay: u16,
+    pub hsync_start: u16,
+    pub hsync_end: u16,
+    pub htotal: u16,
+    pub vdisplay: u16,
+    pub vsync_start: u16,
+    pub vsync_end: u16,
+    pub vtotal: u16,
+    pub hsync_pos: bool,
+    pub vsync_pos: bool,
+}
+
+impl Modeline {
+    /// The refresh rate of this modeline in millihertz.
+    pub fn refresh_millihz(&self) -> u32 {
+        let pixels_per_frame = self.htotal as f64 * self.vtotal as f64;
+        if pixels_per_frame == 0.0 {
+            return 0;
+        }
+        (self.clock as f64 * 1_000_000.0 / pixels_per_frame).round() as u32
+    }
+
+    /// The [`Mode`] this modeline produces.
+    pub fn mode(&self) -> Mode {
+        Mode {
+            size: (self.hdisplay as i32, self.vdisplay as i32).into(),
+            refresh: self.refresh_millihz() as i32,
+        }
+    }
+
+    /// Generate a modeline with VESA Coordinated Video Timings, like the `cvt` utility.
+    ///
+    /// `reduced_blanking` uses CVT-RB timings, which need a lower pixel clock and are
+    /// meant for digital displays.
+    pub fn cvt(width: u16, height: u16, refresh_hz: f64, reduced_blanking: bool) -> Self {
+        const H_GRANULARITY: u32 = 8;
+        const MIN_V_PORCH: u32 = 3;
+        const MIN_V_BPORCH: u32 = 6;
+        const CLOCK_STEP: u32 = 250;
+
+        let hdisplay = width as u32 - width as u32 % H_GRANULARITY;
+        let vdisplay = height as u32;
+
+        // The vsync width encodes the aspect ratio
+        let vsync = match (hdisplay, vdisplay) {
+            (h, v) if v % 3 == 0 && v * 4 / 3 == h => 4,
+            (h, v) if v % 9 == 0 && v * 16 / 9 == h => 5,
+            (h, v) if v % 10 == 0 && v * 16 / 10 == h => 6,
+            (h, v) if v % 4 == 0 && v * 5 / 4 == h => 7,
+            (h, v) if v % 9 == 0 && v * 15 / 9 == h => 7,
+            _ => 10,
+        };
+
+        if reduced_blanking {
+            const MIN_VBLANK: f64 = 460.0;
+            const H_SYNC: u32 = 32;
+            const H_BLANK: u32 = 160;
+            const V_FPORCH: u32 = 3;
+
+            let hperiod = (1_000_000.0 / refresh_hz - MIN_VBLANK) / vdisplay as f64;
+            let vblank_lines =
+                ((MIN_VBLANK / hperiod) as u32 + 1).max(V_FPORCH + vsync + MIN_V_BPORCH);
+
+            let vtotal = vdisplay + vblank_lines;
+            let htotal = hdisplay + H_BLANK;
+
+            let clock = (htotal as f64 * refresh_hz * vtotal as f64 / 1000.0) as u32;
+            let clock = clock - clock % CLOCK_STEP;
+
+            let hsync_end = hdisplay + H_BLANK / 2;
+            let vsync_start = vdisplay + V_FPORCH;
+
+            Self {
+                clock: clock as f32,
+                hdisplay: hdisplay as u16,
+                hsync_start: (hsync_end - H_SYNC) as u16,
+                hsync_end: hsync_end as u16,
+                htotal: htotal as u16,
+                vdisplay: vdisplay as u16,
+                vsync_start: vsync_start as u16,
+                vsync_end: (vsync_start + vsync) as u16,
+                vtotal: vtotal as u16,
+                hsync_pos: true,
+                vsync_pos: false,
+            }
+        } else {
+            const MIN_VSYNC_BP: f64 = 550.0;
+            const HSYNC_PERCENTAGE: u32 = 8;
+            const M_PRIME: f64 = 300.0;
+            const C_PRIME: f64 = 30.0;
+
+            let hperiod =
+                (1_000_000.0 / refresh_hz - MIN_VSYNC_BP) / (vdisplay + MIN_V_PORCH) as f64;
+            let vsync_and_back_porch =
+                ((MIN_VSYNC_BP / hperiod) as u32 + 1).max(vsync + MIN_V_PORCH);
+
+            let vtotal = vdisplay + vsync_and_back_porch + MIN_V_PORCH;
+
+            let hblank_percentage = (C_PRIME - M_PRIME * hperiod / 1000.0).max(20.0);
+            let hblank = (hdisplay as f64 * hblank_percentage / (100.0 - hblank_percentage)) as u32;
+            let hblank = hblank - hblank % (2 * H_GRANULARITY);
+            let htotal = hdisplay + hblank;
+
+            let clock = (htotal as f64 * 1000.0 / hperiod) as u32;
+            let clock = clock - clock % CLOCK_STEP;
+
+            let hsync_end = hdisplay + hblank / 2;
+            let hsync_start = hsync_end - htotal * HSYNC_PERCENTAGE / 100;
+            let hsync_start = hsync_start + H_GRANULARITY - hsync_start % H_GRANULARITY;
+            let vsync_start = vdisplay + MIN_V_PORCH;
+
+            Self {
+                clock: clock as f32,
+                hdisplay: hdisplay as u16,
+                hsync_start: hsync_start as u16,
+                hsync_end: hsync_end as u16,
+                htotal: htotal as u16,
+                vdisplay: vdisplay as u16,
+                vsync_start: vsync_start as u16,
+                vsync_end: (vsync_start + vsync) as u16,
+                vtotal: vtotal as u16,
+                hsync_pos: false,
+                vsync_pos: true,
+            }
+        }
+    }
+}
+
+impl FromStr for Modeline {
+    type Err = anyhow::Error;
+
+    /// Parse an X11 modeline, with or without the leading `Modeline "name"`.
+    ///
+    /// As in X11, the pixel clock is in megahertz.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let s = s
+            .strip_prefix("Modeline")
+            .or_else(|| s.strip_prefix("modeline"))
+            .unwrap_or(s)
+            .trim_start();
+        let s = match s.strip_prefix('"') {
+            Some(rest) => rest
+                .split_once('"')
+                .map(|(_, rest)| rest)
+                .context("unterminated modeline name")?,
+            None => s,
+        };
+
+        let mut args = s.split_whitespace();
+
+        let clock = args
+            .next()
+            .context("no pixel clock")?
+            .parse::<f32>()
+            .context("invalid pixel clock")?
+            * 1000.0;
+
+        let mut timings = [0u16; 8];
+        for timing in timings.iter_mut() {
+            *timing = args
+                .next()
+                .context("not enough timings")?
+                .parse()
+                .context("invalid timing")?;
+        }
+        let [hdisplay, hsync_start, hsync_end, htotal, vdisplay, vsync_start, vsync_end, vtotal] =
+            timings;
+
+        let mut hsync_pos = false;
+        let mut vsync_pos = false;
+        for flag in args {
+            match flag.to_ascii_lowercase().as_str() {
+                "+hsync" => hsync_pos = true,
+                "-hsync" => hsync_pos = false,
+                "+vsync" => vsync_pos = true,
+                "-vsync" => vsync_pos = false,
+                flag => anyhow::bail!("unsupported modeline flag `{flag}`"),
+            }
+        }
+
+        Ok(Self {
+            clock,
+            hdisplay,
+            hsync_start,
+            hsync_end,
+            htotal,
+            vdisplay,
+            vsync_start,
+            vsync_end,
+            vtotal,
+            hsync_pos,
+            vsync_pos,
+        })
+    }
+}
+
+/// Find the mode in `modes` with the given size.
+///
+/// If `refresh_millihz` is specified, the mode with the closest refresh rate within 1 Hz of it
+/// is returned. Otherwise, the mode with the highest refresh rate is returned.
+pub fn find_mode(
+    modes: &[Mode],
+    size: Size<i32, Physical>,
+    refresh_millihz: Option<u32>,
+) -> Option<Mode> {
+    let modes = modes.iter().filter(|mode| mode.size == size).copied();
+
+    match refresh_millihz {
+        Some(refresh) => modes
+            .map(|mode| (mode, mode.refresh.abs_diff(refresh as i32)))
+            .filter(|(_, diff)| *diff < 1000)
+            .min_by_key(|(_, diff)| *diff)
+            .map(|(mode, _)| mode),
+        None => modes.max_by_key(|mode| mode.refresh),
+    }
+}
+
+/// Register a custom mode on an output so it can be selected like any other mode.
+pub fn add_custom_mode(output: &Output, modeline: Modeline) {
+    let mode = modeline.mode();
+    output.with_state_mut(|state| {
+        state.custom_modes.retain(|custom| custom.mode() != mode);
+        state.custom_modes.push(modeline);
+    });
+    output.add_mode(mode);
+}
+
 /// The state of an output
-#[derive(Default, Debug)]
+#[derive(Debug)]
 pub struct OutputState {
     pub tags: Vec<Tag>,
     pub focus_stack: WindowKeyboardFocusStack,
     pub screencopy: Option<Screencopy>,
     pub serial: Option<NonZeroU32>,
+    pub vrr: Vrr,
+    pub max_render_time: MaxRenderTime,
+    /// The most frames per second this output shows, if capped.
+    pub max_fps: Option<NonZeroU32>,
+    /// Whether this output is part of the layout.
+    pub enabled: bool,
+    /// Whether the monitor is turned on.
+    pub powered: bool,
+    /// Tags that were moved to another output when this one was disabled.
+    pub migrated_tags: Vec<Tag>,
+    /// Whether this output was disabled because the laptop lid was closed.
+    pub disabled_by_lid: bool,
+    /// Modes added by the config that the connector doesn't advertise.
+    pub custom_modes: Vec<Modeline>,
+    /// The color filter everything on this output is drawn with.
+    pub color_filter: Option<ColorFilter>,
+    /// This output's night light.
+    pub night_light: Option<NightLight>,
+    /// The timer that updates the night light to follow its schedule.
+    pub night_light_timer: Option<RegistrationToken>,
+    /// Statistics about recently rendered frames.
+    pub frame_stats: FrameStats,
+    /// The performance overlay's elements, created the first time it is shown.
+    pub perf_overlay: Option<PerfOverlay>,
+    /// The damage visualization's state, created when it is enabled.
+    pub damage_debug: Option<DamageDebug>,
+    /// What is drawn beneath everything else on this output.
+    pub wallpaper: Option<Wallpaper>,
+    /// The name of the layout this output's windows were last laid out with, if it has one.
+    pub layout_name: Option<String>,
+    /// The monocle indicator's elements, created the first time it is shown.
+    pub monocle_indicator: Option<MonocleIndicator>,
+    /// The tags that were active on this output before.
+    pub tag_history: TagHistory,
+}
+
+impl Default for OutputState {
+    fn default() -> Self {
+        Self {
+            tags: Vec::new(),
+            focus_stack: WindowKeyboardFocusStack::default(),
+            screencopy: None,
+            serial: None,
+            vrr: Vrr::default(),
+            max_render_time: MaxRenderTime::default(),
+            max_fps: None,
+            enabled: true,
+            powered: true,
+            migrated_tags: Vec::new(),
+            disabled_by_lid: false,
+            custom_modes: Vec::new(),
+            color_filter: None,
+            night_light: None,
+            night_light_timer: None,
+            frame_stats: FrameStats::default(),
+            perf_overlay: None,
+            damage_debug: None,
+            wallpaper: None,
+            layout_name: None,
+            monocle_indicator: None,
+            tag_history: TagHistory::default(),
+        }
+    }
 }
 
 impl WithState for Output {
@@ -74,9 +500,306 @@ impl OutputState {
     pub fn focused_tags(&self) -> impl Iterator<Item = &Tag> {
         self.tags.iter().filter(|tag| tag.active())
     }
+
+    /// Whether this output should be rendered to.
+    pub fn is_displaying(&self) -> bool {
+        self.enabled && self.powered
+    }
+}
+
+impl Pinnacle {
+    /// Get the output with the given name, including disabled outputs.
+    pub fn output_including_disabled(&self, name: &OutputName) -> Option<Output> {
+        name.output(self).or_else(|| {
+            self.disabled_outputs
+                .iter()
+                .find(|op| op.name() == name.0)
+                .cloned()
+        })
+    }
+}
+
+/// Whether the output is a laptop's built-in panel.
+pub fn is_internal(output: &Output) -> bool {
+    let name = output.name();
+    ["eDP", "LVDS", "DSI"]
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+}
+
+impl Pinnacle {
+    /// Reapply output profiles, the lid switch policy, and the X11 primary output once
+    /// the current event is done being handled.
+    ///
+    /// Call this after an output is connected or disconnected.
+    pub fn queue_outputs_changed(&self) {
+        self.loop_handle.insert_idle(|state| {
+            state.apply_output_profile();
+            state.update_lid_outputs();
+            state.pinnacle.update_xwayland_primary_output();
+        });
+    }
+}
+
+impl State {
+    /// Disable or re-enable internal panels according to the lid state and
+    /// [`LidSwitchPolicy`].
+    pub fn update_lid_outputs(&mut self) {
+        let clamshell = self.pinnacle.input_state.lid_closed
+            && self.pinnacle.input_state.lid_switch_policy == LidSwitchPolicy::Clamshell
+            && self.pinnacle.space.outputs().any(|op| !is_internal(op));
+
+        if clamshell {
+            let internal_outputs = self
+                .pinnacle
+                .space
+                .outputs()
+                .filter(|op| is_internal(op))
+                .cloned()
+                .collect::<Vec<_>>();
+
+            for output in internal_outputs {
+                self.disable_output(&output);
+                if !output.with_state(|state| state.enabled) {
+                    output.with_state_mut(|state| state.disabled_by_lid = true);
+                }
+            }
+        } else {
+            let lid_disabled_outputs = self
+                .pinnacle
+                .disabled_outputs
+                .iter()
+                .filter(|op| op.with_state(|state| state.disabled_by_lid))
+                .cloned()
+                .collect::<Vec<_>>();
+
+            for output in lid_disabled_outputs {
+                self.enable_output(&output);
+            }
+        }
+    }
+
+    /// Turn the given output's monitor on or off.
+    ///
+    /// A powered off output stays in the layout.
+    pub fn set_output_powered(&mut self, output: &Output, powered: bool) {
+        if output.with_state(|state| state.powered) == powered {
+            return;
+        }
+
+        output.with_state_mut(|state| state.powered = powered);
+
+        if output.with_state(|state| state.enabled) {
+            self.backend.set_output_powered(output, powered);
+        }
+
+        if powered {
+            self.schedule_render(output);
+        }
+    }
+
+    /// Remove the given output from the layout and turn it off.
+    ///
+    /// Its tags, along with their windows, are moved to the focused output
+    /// until this output is enabled again. Clients stop seeing the output while it's disabled.
+    pub fn disable_output(&mut self, output: &Output) {
+        if !output.with_state(|state| state.enabled) {
+            return;
+        }
+
+        let Some((target, tags)) = self.pinnacle.migrate_tags(output) else {
+            warn!(
+                "Not disabling {}: it is the only enabled output",
+                output.name()
+            );
+            return;
+        };
+
+        if let (Some(from), Some(to)) = (
+            self.pinnacle.space.output_geometry(output),
+            self.pinnacle.space.output_geometry(&target),
+        ) {
+            self.pinnacle.move_floating_windows(&tags, from, to);
+        }
+
+        output.with_state_mut(|state| {
+            state.enabled = false;
+            state.migrated_tags = tags;
+        });
+
+        for layer in layer_map_for_output(output).layers() {
+            layer.layer_surface().send_close();
+        }
+
+        self.pinnacle.space.unmap_output(output);
+        self.pinnacle.output_focus_stack.remove(output);
+        self.pinnacle.disabled_outputs.push(output.clone());
+        self.set_output_global_enabled(output, false);
+
+        if output.with_state(|state| state.powered) {
+            self.backend.set_output_powered(output, false);
+        }
+
+        self.pinnacle.request_layout(&target);
+        self.schedule_render(&target);
+    }
+
+    /// Add a disabled output back to the layout at its previous location,
+    /// taking back the tags it had.
+    pub fn enable_output(&mut self, output: &Output) {
+        if output.with_state(|state| state.enabled) {
+            return;
+        }
+
+        self.pinnacle.disabled_outputs.retain(|op| op != output);
+
+        let migrated_tags = output.with_state_mut(|state| {
+            state.enabled = true;
+            state.disabled_by_lid = false;
+            std::mem::take(&mut state.migrated_tags)
+        });
+
+        // Where the tags' floating windows are now, to move them back
+        let tag_geometries = migrated_tags
+            .iter()
+            .filter_map(|tag| {
+                let geo = self
+                    .pinnacle
+                    .space
+                    .output_geometry(&tag.output(&self.pinnacle)?)?;
+                Some((tag.clone(), geo))
+            })
+            .collect::<Vec<_>>();
+
+        let tags = self.pinnacle.reclaim_tags(&migrated_tags);
+        output.with_state_mut(|state| state.tags = tags);
+
+        self.set_output_global_enabled(output, true);
+        self.pinnacle.change_output_state(
+            output,
+            None,
+            None,
+            None,
+            Some(output.current_location()),
+        );
+
+        if let Some(to) = self.pinnacle.space.output_geometry(output) {
+            for (tag, from) in tag_geometries {
+                self.pinnacle.move_floating_windows(&[tag], from, to);
+            }
+        }
+
+        if output.with_state(|state| state.powered) {
+            self.backend.set_output_powered(output, true);
+        }
+
+        for op in self.pinnacle.space.outputs().cloned().collect::<Vec<_>>() {
+            self.pinnacle.request_layout(&op);
+            self.schedule_render(&op);
+        }
+    }
 }
 
 impl Pinnacle {
+    /// Get the primary output, if one is set and it's connected and enabled.
+    pub fn primary_output(&self) -> Option<Output> {
+        self.primary_output_name
+            .as_ref()
+            .and_then(|name| name.output(self))
+    }
+
+    /// Get the output new windows should open on.
+    ///
+    /// This is the primary output, falling back to the focused output.
+    pub fn new_window_output(&self) -> Option<Output> {
+        self.primary_output()
+            .or_else(|| self.focused_output().cloned())
+    }
+
+    /// Make the output with the given name the primary output.
+    ///
+    /// The name is kept if the output disconnects, so it becomes primary again on replug.
+    pub fn set_primary_output(&mut self, name: OutputName) {
+        self.primary_output_name = Some(name);
+        self.update_xwayland_primary_output();
+    }
+
+    /// Tell Xwayland which output is primary so X11 clients place dialogs and
+    /// panels on it.
+    pub fn update_xwayland_primary_output(&self) {
+        let (Some(x11_worker), Some(output)) = (self.x11_worker.as_ref(), self.primary_output())
+        else {
+            return;
+        };
+
+        // Xwayland doesn't expose the RandR primary output to the compositor,
+        // so set it like any other X11 client would.
+        x11_worker.set_primary_output(output.name());
+    }
+
+    /// Move all of `output`'s tags, along with their windows, to the focused output
+    /// or any other output if `output` is focused.
+    ///
+    /// Returns the output the tags were moved to and the tags, or `None` if there is
+    /// no other output.
+    pub fn migrate_tags(&mut self, output: &Output) -> Option<(Output, Vec<Tag>)> {
+        let target = self
+            .focused_output()
+            .filter(|op| *op != output)
+            .or_else(|| self.space.outputs().find(|op| *op != output))
+            .cloned()?;
+
+        let tags = output.with_state_mut(|state| std::mem::take(&mut state.tags));
+        target.with_state_mut(|state| state.tags.extend(tags.iter().cloned()));
+
+        Some((target, tags))
+    }
+
+    /// Move floating windows with any of `tags` from the output at `from` to the output at `to`.
+    ///
+    /// Windows keep their position relative to the output, clamped so that they stay inside it.
+    pub fn move_floating_windows(
+        &self,
+        tags: &[Tag],
+        from: Rectangle<i32, Logical>,
+        to: Rectangle<i32, Logical>,
+    ) {
+        for window in self.windows.iter() {
+            window.with_state_mut(|state| {
+                if !state.tags.iter().any(|tag| tags.contains(tag)) {
+                    return;
+                }
+                if let FloatingOrTiled::Floating(rect) = &mut state.floating_or_tiled {
+                    let loc = rect.loc + (to.loc - from.loc);
+                    let max_x = to.loc.x + (to.size.w - rect.size.w).max(0);
+                    let max_y = to.loc.y + (to.size.h - rect.size.h).max(0);
+                    rect.loc = (loc.x.clamp(to.loc.x, max_x), loc.y.clamp(to.loc.y, max_y)).into();
+                }
+            });
+        }
+    }
+
+    /// Take back tags moved away with [`Pinnacle::migrate_tags`] from whichever outputs
+    /// they are on now, preserving their original order.
+    ///
+    /// Tags that were removed in the meantime stay removed.
+    pub fn reclaim_tags(&mut self, migrated_tags: &[Tag]) -> Vec<Tag> {
+        let mut tags = Vec::new();
+        for op in self.space.outputs() {
+            op.with_state_mut(|state| {
+                state.tags.retain(|tag| {
+                    let migrated = migrated_tags.contains(tag);
+                    if migrated {
+                        tags.push(tag.clone());
+                    }
+                    !migrated
+                });
+            });
+        }
+        tags.sort_by_key(|tag| migrated_tags.iter().position(|t| t == tag));
+        tags
+    }
+
     /// A wrapper around [`Output::change_current_state`] that additionally sends an output
     /// geometry signal.
     pub fn change_output_state(
@@ -87,17 +810,27 @@ impl Pinnacle {
         scale: Option<Scale>,
         location: Option<Point<i32, Logical>>,
     ) {
-        output.change_current_state(mode, transform, scale, location);
+        output.change_current_state(mode, transform, scale, None);
         if let Some(location) = location {
-            info!(?location);
-            self.space.map_output(output, location);
-            self.signal_state.output_move.signal(|buf| {
-                buf.push_back(OutputMoveResponse {
-                    output_name: Some(output.name()),
-                    x: Some(location.x),
-                    y: Some(location.y),
+            self.move_output(output, location);
+        }
+        if let Some(scale) = scale {
+            // Let clients start rendering at the new scale right away instead of waiting
+            // for the next repaint to update their preferred scale.
+            let scale = scale.fractional_scale();
+            let set_preferred_scale = |_: &WlSurface, states: &SurfaceData| {
+                with_fractional_scale(states, |fractional_scale| {
+                    fractional_scale.set_preferred_scale(scale);
                 });
-            });
+            };
+            for window in self.space.elements() {
+                if self.space.outputs_for_element(window).contains(output) {
+                    window.with_surfaces(set_preferred_scale);
+                }
+            }
+            for layer in layer_map_for_output(output).layers() {
+                layer.with_surfaces(set_preferred_scale);
+            }
         }
         if mode.is_some() || transform.is_some() || scale.is_some() {
             layer_map_for_output(output).arrange();
@@ -113,5 +846,131 @@ impl Pinnacle {
         if let Some(mode) = mode {
             output.set_preferred(mode);
         }
+        if mode.is_some() || transform.is_some() || scale.is_some() || location.is_some() {
+            self.update_relative_output_locs();
+            self.place_pip_windows(output);
+            self.place_edge_tiled_windows(output);
+        }
+    }
+
+    fn move_output(&mut self, output: &Output, location: Point<i32, Logical>) {
+        info!(?location);
+        output.change_current_state(None, None, None, Some(location));
+        self.space.map_output(output, location);
+        self.signal_state.output_move.signal(|buf| {
+            buf.push_back(OutputMoveResponse {
+                output_name: Some(output.name()),
+                x: Some(location.x),
+                y: Some(location.y),
+            });
+        });
+    }
+
+    /// Move outputs placed relative to other outputs back next to them.
+    pub fn update_relative_output_locs(&mut self) {
+        let relative_locs = self.config.relative_output_locs.clone();
+
+        // Outputs can be placed relative to outputs that are themselves placed relatively,
+        // so keep going until nothing moves. Cycles give up after every output had a turn.
+        for _ in 0..relative_locs.len() {
+            let mut moved = false;
+
+            for (name, (relative_to, alignment)) in relative_locs.iter() {
+                let (Some(output), Some(other)) = (name.output(self), relative_to.output(self))
+                else {
+                    continue;
+                };
+                let (Some(geo), Some(other_geo)) = (
+                    self.space.output_geometry(&output),
+                    self.space.output_geometry(&other),
+                ) else {
+                    continue;
+                };
+
+                let loc = alignment.loc_adjacent_to(geo.size, other_geo);
+                if loc != geo.loc {
+                    self.move_output(&output, loc);
+                    self.request_layout(&output);
+                    moved = true;
+                }
+            }
+
+            if !moved {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cvt_matches_cvt_utility() {
+        // `cvt 1920 1080 60`
+        assert_eq!(
+            Modeline::cvt(1920, 1080, 60.0, false),
+            "173.00 1920 2048 2248 2576 1080 1083 1088 1120 -hsync +vsync"
+                .parse::<Modeline>()
+                .unwrap()
+        );
+        // `cvt -r 1920 1080 60`
+        assert_eq!(
+            Modeline::cvt(1920, 1080, 60.0, true),
+            "138.50 1920 1968 2000 2080 1080 1083 1088 1111 +hsync -vsync"
+                .parse::<Modeline>()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_modeline_with_name() -> anyhow::Result<()> {
+        let modeline: Modeline = r#"Modeline "2560x1440_60.00"  312.25  2560 2752 3024 3488  1440 1443 1448 1493 -hsync +vsync"#.parse()?;
+
+        assert_eq!(modeline.clock, 312_250.0);
+        assert_eq!(modeline.mode().size, (2560, 1440).into());
+        assert_eq!(modeline.refresh_millihz(), 59_961);
+        assert!(!modeline.hsync_pos);
+        assert!(modeline.vsync_pos);
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_modeline_rejects_missing_timings() {
+        assert!("173.00 1920 2048 2248 2576 1080"
+            .parse::<Modeline>()
+            .is_err());
+    }
+
+    #[test]
+    fn adaptive_max_render_time_follows_frame_times() {
+        let mut stats = FrameStats::default();
+        assert_eq!(MaxRenderTime::Adaptive.render_time(&stats), None);
+
+        let now = std::time::Instant::now();
+        for _ in 0..10 {
+            stats.record(now, Duration::from_millis(3), false, 0.0);
+        }
+        assert_eq!(
+            MaxRenderTime::Adaptive.render_time(&stats),
+            Some(Duration::from_millis(4))
+        );
+
+        assert_eq!(MaxRenderTime::Off.render_time(&stats), None);
+        assert_eq!(
+            MaxRenderTime::Fixed(Duration::from_millis(7)).render_time(&stats),
+            Some(Duration::from_millis(7))
+        );
+    }
+
+    #[test]
+    fn refreshes_per_frame_caps_fps() {
+        assert_eq!(refreshes_per_frame(240_000, None), 1);
+        assert_eq!(refreshes_per_frame(240_000, NonZeroU32::new(120)), 2);
+        assert_eq!(refreshes_per_frame(144_000, NonZeroU32::new(60)), 3);
+        assert_eq!(refreshes_per_frame(60_010, NonZeroU32::new(60)), 1);
+        assert_eq!(refreshes_per_frame(60_000, NonZeroU32::new(144)), 1);
     }
 }
@@ -0,0 +1,251 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Output profiles.
+//!
+//! A profile records how every output was set up for a given set of connected monitors
+//! and is re-applied whenever that exact set of monitors is connected again.
+
+use std::path::PathBuf;
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use smithay::output::{Output, Scale};
+use tracing::{error, info};
+use xdg::BaseDirectories;
+
+use crate::state::{Pinnacle, State, WithState};
+
+use super::find_mode;
+
+const PROFILES_FILE: &str = "output_profiles.toml";
+
+/// Identifies a physical monitor.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MonitorId {
+    pub make: String,
+    pub model: String,
+    pub serial: Option<u32>,
+    /// The connector the monitor was plugged into.
+    ///
+    /// This is only used to tell apart monitors without a serial number.
+    pub connector: String,
+}
+
+impl MonitorId {
+    pub fn from_output(output: &Output) -> Self {
+        let props = output.physical_properties();
+        Self {
+            make: props.make,
+            model: props.model,
+            serial: output.with_state(|state| state.serial.map(|serial| serial.get())),
+            connector: output.name(),
+        }
+    }
+
+    pub fn matches(&self, other: &MonitorId) -> bool {
+        self.make == other.make
+            && self.model == other.model
+            && self.serial == other.serial
+            && (self.serial.is_some() || self.connector == other.connector)
+    }
+}
+
+/// The configuration of one output in a profile.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfileOutput {
+    pub monitor: MonitorId,
+    pub enabled: bool,
+    /// (width, height, refresh rate in millihertz)
+    pub mode: Option<(i32, i32, i32)>,
+    pub loc: (i32, i32),
+    pub scale: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    pub outputs: Vec<ProfileOutput>,
+}
+
+impl Profile {
+    /// Whether this profile is for exactly the given monitors.
+    fn matches(&self, monitors: &[MonitorId]) -> bool {
+        self.outputs.len() == monitors.len()
+            && monitors.iter().all(|monitor| {
+                self.outputs
+                    .iter()
+                    .any(|output| output.monitor.matches(monitor))
+            })
+    }
+}
+
+/// Saved output profiles, persisted to pinnacle's state directory.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct OutputProfiles {
+    profiles: Vec<Profile>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl OutputProfiles {
+    /// Load profiles from disk, or start with none if they can't be read.
+    pub fn load(xdg_base_dirs: &BaseDirectories) -> Self {
+        let path = xdg_base_dirs.get_state_home().join(PROFILES_FILE);
+
+        let mut profiles = match std::fs::read_to_string(&path) {
+            Ok(contents) => toml::from_str::<OutputProfiles>(&contents).unwrap_or_else(|err| {
+                error!("Failed to parse {}: {err}", path.display());
+                OutputProfiles::default()
+            }),
+            Err(_) => OutputProfiles::default(),
+        };
+
+        profiles.path = Some(path);
+        profiles
+    }
+
+    fn save(&self) -> anyhow::Result<()> {
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, toml::to_string(self)?)
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    fn find(&self, monitors: &[MonitorId]) -> Option<&Profile> {
+        self.profiles
+            .iter()
+            .find(|profile| profile.matches(monitors))
+    }
+}
+
+impl Pinnacle {
+    /// All connected outputs, including disabled ones.
+    fn connected_outputs(&self) -> Vec<Output> {
+        self.space
+            .outputs()
+            .chain(self.disabled_outputs.iter())
+            .cloned()
+            .collect()
+    }
+
+    /// Save the current output configuration as the profile for the connected monitors,
+    /// replacing any existing one.
+    pub fn save_output_profile(&mut self) {
+        let outputs = self.connected_outputs();
+        let monitors = outputs
+            .iter()
+            .map(MonitorId::from_output)
+            .collect::<Vec<_>>();
+
+        let profile = Profile {
+            outputs: outputs
+                .iter()
+                .zip(monitors.iter().cloned())
+                .map(|(output, monitor)| ProfileOutput {
+                    monitor,
+                    enabled: output.with_state(|state| state.enabled),
+                    mode: output
+                        .current_mode()
+                        .map(|mode| (mode.size.w, mode.size.h, mode.refresh)),
+                    loc: output.current_location().into(),
+                    scale: output.current_scale().fractional_scale(),
+                })
+                .collect(),
+        };
+
+        self.output_profiles
+            .profiles
+            .retain(|profile| !profile.matches(&monitors));
+        self.output_profiles.profiles.push(profile);
+
+        info!("Saved output profile for {} output(s)", monitors.len());
+
+        if let Err(err) = self.output_profiles.save() {
+            error!("Failed to save output profiles: {err}");
+        }
+    }
+
+    /// Remove the profile for the connected monitors, or every profile if `all` is true.
+    pub fn clear_output_profiles(&mut self, all: bool) {
+        if all {
+            self.output_profiles.profiles.clear();
+        } else {
+            let monitors = self
+                .connected_outputs()
+                .iter()
+                .map(MonitorId::from_output)
+                .collect::<Vec<_>>();
+            self.output_profiles
+                .profiles
+                .retain(|profile| !profile.matches(&monitors));
+        }
+
+        if let Err(err) = self.output_profiles.save() {
+            error!("Failed to save output profiles: {err}");
+        }
+    }
+}
+
+impl State {
+    /// Apply the profile for the connected monitors, if there is one.
+    pub fn apply_output_profile(&mut self) {
+        let outputs = self.pinnacle.connected_outputs();
+        let monitors = outputs
+            .iter()
+            .map(MonitorId::from_output)
+            .collect::<Vec<_>>();
+
+        let Some(profile) = self.pinnacle.output_profiles.find(&monitors).cloned() else {
+            return;
+        };
+
+        info!("Applying output profile for {} output(s)", monitors.len());
+
+        let configs = outputs
+            .iter()
+            .zip(monitors.iter())
+            .filter_map(|(output, monitor)| {
+                profile
+                    .outputs
+                    .iter()
+                    .find(|config| config.monitor.matches(monitor))
+                    .map(|config| (output.clone(), config.clone()))
+            });
+
+        // Enable outputs first so that disabling others always has somewhere to move tags to
+        let (enabled, disabled): (Vec<_>, Vec<_>) = configs.partition(|(_, config)| config.enabled);
+
+        for (output, config) in enabled {
+            self.enable_output(&output);
+
+            if let Some((w, h, refresh)) = config.mode {
+                let size = (w, h).into();
+                let mode = find_mode(&output.modes(), size, Some(refresh as u32));
+                if let Some(mode) = mode.filter(|mode| Some(*mode) != output.current_mode()) {
+                    self.resize_output(&output, mode);
+                }
+            }
+
+            self.pinnacle.change_output_state(
+                &output,
+                None,
+                None,
+                Some(Scale::Fractional(config.scale)),
+                Some(config.loc.into()),
+            );
+        }
+
+        for (output, _) in disabled {
+            self.disable_output(&output);
+        }
+
+        for output in self.pinnacle.space.outputs().cloned().collect::<Vec<_>>() {
+            self.pinnacle.request_layout(&output);
+            self.schedule_render(&output);
+        }
+    }
+}
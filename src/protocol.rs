@@ -1,2 +1,8 @@
+pub mod alpha_modifier;
+pub mod drm_syncobj;
+pub mod ext_foreign_toplevel_list;
+pub mod ext_workspace;
 pub mod gamma_control;
+pub mod image_copy_capture;
 pub mod screencopy;
+pub mod xdg_toplevel_drag;
@@ -0,0 +1,237 @@
+//! An implementation of `alpha-modifier-v1`.
+//!
+//! The multiplier is stored as double-buffered surface state, see
+//! [`AlphaModifierSurfaceCachedState`].
+
+use std::sync::Mutex;
+
+use smithay::{
+    reexports::wayland_server::{
+        self, protocol::wl_surface::WlSurface, Client, DataInit, Dispatch, DisplayHandle,
+        GlobalDispatch, Resource, Weak,
+    },
+    wayland::compositor::{self, Cacheable},
+};
+
+use self::generated::{
+    wp_alpha_modifier_surface_v1::{self, WpAlphaModifierSurfaceV1},
+    wp_alpha_modifier_v1::{self, WpAlphaModifierV1},
+};
+
+#[allow(missing_docs)]
+pub mod generated {
+    #![allow(dead_code, non_camel_case_types, unused_unsafe, unused_variables)]
+    #![allow(non_upper_case_globals, non_snake_case, unused_imports)]
+    #![allow(clippy::all)]
+
+    use smithay::reexports::wayland_server;
+    use smithay::reexports::wayland_server::protocol::*;
+
+    pub mod __interfaces {
+        use smithay::reexports::wayland_server::protocol::__interfaces::*;
+        wayland_scanner::generate_interfaces!("resources/protocols/alpha-modifier-v1.xml");
+    }
+    use self::__interfaces::*;
+
+    wayland_scanner::generate_server_code!("resources/protocols/alpha-modifier-v1.xml");
+}
+
+const VERSION: u32 = 1;
+
+pub struct AlphaModifierState;
+
+pub struct AlphaModifierGlobalData {
+    filter: Box<dyn Fn(&Client) -> bool + Send + Sync>,
+}
+
+/// Marks a surface as already having an alpha modifier object.
+#[derive(Default)]
+struct AlphaModifierSurfaceData {
+    constructed: Mutex<bool>,
+}
+
+/// The double-buffered alpha multiplier of a surface.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AlphaModifierSurfaceCachedState {
+    multiplier: Option<u32>,
+}
+
+impl AlphaModifierSurfaceCachedState {
+    /// The alpha multiplier as a float between 0.0 and 1.0.
+    pub fn multiplier(&self) -> f32 {
+        self.multiplier
+            .map_or(1.0, |multiplier| multiplier as f32 / u32::MAX as f32)
+    }
+}
+
+impl Cacheable for AlphaModifierSurfaceCachedState {
+    fn commit(&mut self, _dh: &DisplayHandle) -> Self {
+        *self
+    }
+
+    fn merge_into(self, into: &mut Self, _dh: &DisplayHandle) {
+        *into = self;
+    }
+}
+
+impl AlphaModifierState {
+    pub fn new<D, F>(display: &DisplayHandle, filter: F) -> Self
+    where
+        D: GlobalDispatch<WpAlphaModifierV1, AlphaModifierGlobalData>
+            + Dispatch<WpAlphaModifierV1, ()>
+            + Dispatch<WpAlphaModifierSurfaceV1, Weak<WlSurface>>
+            + 'static,
+        F: Fn(&Client) -> bool + Send + Sync + 'static,
+    {
+        let global_data = AlphaModifierGlobalData {
+            filter: Box::new(filter),
+        };
+        display.create_global::<D, WpAlphaModifierV1, _>(VERSION, global_data);
+        Self
+    }
+}
+
+/// Get the current alpha multiplier of a surface as a float between 0.0 and 1.0.
+pub fn surface_alpha_multiplier(surface: &WlSurface) -> f32 {
+    compositor::with_states(surface, |states| {
+        states
+            .cached_state
+            .current::<AlphaModifierSurfaceCachedState>()
+            .multiplier()
+    })
+}
+
+impl<D> GlobalDispatch<WpAlphaModifierV1, AlphaModifierGlobalData, D> for AlphaModifierState
+where
+    D: GlobalDispatch<WpAlphaModifierV1, AlphaModifierGlobalData>
+        + Dispatch<WpAlphaModifierV1, ()>
+        + Dispatch<WpAlphaModifierSurfaceV1, Weak<WlSurface>>
+        + 'static,
+{
+    fn bind(
+        _state: &mut D,
+        _handle: &DisplayHandle,
+        _client: &Client,
+        resource: wayland_server::New<WpAlphaModifierV1>,
+        _global_data: &AlphaModifierGlobalData,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        data_init.init(resource, ());
+    }
+
+    fn can_view(client: Client, global_data: &AlphaModifierGlobalData) -> bool {
+        (global_data.filter)(&client)
+    }
+}
+
+impl<D> Dispatch<WpAlphaModifierV1, (), D> for AlphaModifierState
+where
+    D: Dispatch<WpAlphaModifierV1, ()> + Dispatch<WpAlphaModifierSurfaceV1, Weak<WlSurface>>,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        manager: &WpAlphaModifierV1,
+        request: <WpAlphaModifierV1 as Resource>::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        let (id, surface) = match request {
+            wp_alpha_modifier_v1::Request::GetSurface { id, surface } => (id, surface),
+            wp_alpha_modifier_v1::Request::Destroy => return,
+            _ => unreachable!(),
+        };
+
+        let already_constructed = compositor::with_states(&surface, |states| {
+            states
+                .data_map
+                .insert_if_missing_threadsafe(AlphaModifierSurfaceData::default);
+            let data = states
+                .data_map
+                .get::<AlphaModifierSurfaceData>()
+                .expect("data was just inserted");
+            let mut constructed = data
+                .constructed
+                .lock()
+                .expect("alpha modifier mutex was poisoned");
+            std::mem::replace(&mut *constructed, true)
+        });
+
+        if already_constructed {
+            manager.post_error(
+                wp_alpha_modifier_v1::Error::AlreadyConstructed,
+                "surface already has an alpha modifier object",
+            );
+            return;
+        }
+
+        data_init.init(id, surface.downgrade());
+    }
+}
+
+impl<D> Dispatch<WpAlphaModifierSurfaceV1, Weak<WlSurface>, D> for AlphaModifierState
+where
+    D: Dispatch<WpAlphaModifierSurfaceV1, Weak<WlSurface>>,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        resource: &WpAlphaModifierSurfaceV1,
+        request: <WpAlphaModifierSurfaceV1 as Resource>::Request,
+        data: &Weak<WlSurface>,
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        let multiplier = match request {
+            wp_alpha_modifier_surface_v1::Request::SetMultiplier { factor } => Some(factor),
+            // Destroying is the same as resetting the multiplier
+            wp_alpha_modifier_surface_v1::Request::Destroy => None,
+            _ => unreachable!(),
+        };
+
+        let Ok(surface) = data.upgrade() else {
+            if multiplier.is_some() {
+                resource.post_error(
+                    wp_alpha_modifier_surface_v1::Error::NoSurface,
+                    "the surface was destroyed",
+                );
+            }
+            return;
+        };
+
+        compositor::with_states(&surface, |states| {
+            states
+                .cached_state
+                .pending::<AlphaModifierSurfaceCachedState>()
+                .multiplier = multiplier;
+
+            if multiplier.is_none() {
+                if let Some(data) = states.data_map.get::<AlphaModifierSurfaceData>() {
+                    *data
+                        .constructed
+                        .lock()
+                        .expect("alpha modifier mutex was poisoned") = false;
+                }
+            }
+        });
+    }
+}
+
+#[allow(missing_docs)]
+#[macro_export]
+macro_rules! delegate_alpha_modifier {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        smithay::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::protocol::alpha_modifier::generated::wp_alpha_modifier_v1::WpAlphaModifierV1: $crate::protocol::alpha_modifier::AlphaModifierGlobalData
+        ] => $crate::protocol::alpha_modifier::AlphaModifierState);
+
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::protocol::alpha_modifier::generated::wp_alpha_modifier_v1::WpAlphaModifierV1: ()
+        ] => $crate::protocol::alpha_modifier::AlphaModifierState);
+
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::protocol::alpha_modifier::generated::wp_alpha_modifier_surface_v1::WpAlphaModifierSurfaceV1: smithay::reexports::wayland_server::Weak<smithay::reexports::wayland_server::protocol::wl_surface::WlSurface>
+        ] => $crate::protocol::alpha_modifier::AlphaModifierState);
+    };
+}
@@ -0,0 +1,696 @@
+//! An implementation of `linux-drm-syncobj-v1`.
+//!
+//! Acquire points are waited on through a [`Blocker`] so that the commit is only applied once
+//! the client's rendering has finished. Release points are signaled once the buffer they were
+//! committed with is no longer used, see [`DrmReleasePoint`].
+//!
+//! The Smithay revision pinned here has no `drm_syncobj` module, and the drm-ffi it resolves
+//! doesn't wrap `DRM_IOCTL_SYNCOBJ_EVENTFD`, so that ioctl is issued directly in `ioctl`.
+//! The global is only created once [`supports_eventfd`] has confirmed the kernel has it.
+
+use std::{
+    os::fd::{AsFd, BorrowedFd},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use nix::sys::eventfd::{EfdFlags, EventFd};
+use smithay::{
+    backend::{allocator::dmabuf, drm::DrmDeviceFd},
+    reexports::{
+        calloop::{
+            generic::Generic, EventSource, Interest, Mode, Poll, PostAction, Readiness, Token,
+            TokenFactory,
+        },
+        drm::control::{syncobj, Device as ControlDevice},
+        wayland_server::{
+            self,
+            protocol::{wl_buffer::WlBuffer, wl_surface::WlSurface},
+            Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, Resource, Weak,
+        },
+    },
+    wayland::compositor::{
+        self, Blocker, BlockerState, BufferAssignment, Cacheable, SurfaceAttributes, SurfaceData,
+    },
+};
+use tracing::warn;
+
+use self::generated::{
+    wp_linux_drm_syncobj_manager_v1::{self, WpLinuxDrmSyncobjManagerV1},
+    wp_linux_drm_syncobj_surface_v1::{self, WpLinuxDrmSyncobjSurfaceV1},
+    wp_linux_drm_syncobj_timeline_v1::{self, WpLinuxDrmSyncobjTimelineV1},
+};
+
+#[allow(missing_docs)]
+pub mod generated {
+    #![allow(dead_code, non_camel_case_types, unused_unsafe, unused_variables)]
+    #![allow(non_upper_case_globals, non_snake_case, unused_imports)]
+    #![allow(clippy::all)]
+
+    use smithay::reexports::wayland_server;
+    use smithay::reexports::wayland_server::protocol::*;
+
+    pub mod __interfaces {
+        use smithay::reexports::wayland_server::protocol::__interfaces::*;
+        wayland_scanner::generate_interfaces!("resources/protocols/linux-drm-syncobj-v1.xml");
+    }
+    use self::__interfaces::*;
+
+    wayland_scanner::generate_server_code!("resources/protocols/linux-drm-syncobj-v1.xml");
+}
+
+const VERSION: u32 = 1;
+
+/// Ioctls that drm-ffi doesn't wrap yet.
+mod ioctl {
+    use std::os::fd::{AsFd, AsRawFd, BorrowedFd};
+
+    use smithay::backend::drm::DrmDeviceFd;
+
+    nix::ioctl_readwrite!(
+        raw_syncobj_eventfd,
+        drm_ffi::DRM_IOCTL_BASE,
+        0xCF,
+        drm_ffi::drm_syncobj_eventfd
+    );
+
+    /// `DRM_IOCTL_SYNCOBJ_EVENTFD`: make `eventfd` readable once `point` on the syncobj
+    /// `handle` has been signaled. Without an `eventfd`, this only checks that the ioctl
+    /// exists.
+    pub fn syncobj_eventfd(
+        device: &DrmDeviceFd,
+        handle: u32,
+        point: u64,
+        eventfd: Option<BorrowedFd<'_>>,
+    ) -> nix::Result<()> {
+        let mut args = drm_ffi::drm_syncobj_eventfd {
+            handle,
+            flags: 0,
+            point,
+            fd: eventfd.map_or(-1, |fd| fd.as_raw_fd()),
+            pad: 0,
+        };
+
+        // SAFETY:
+        // - The request code is the kernel's `DRM_IOCTL_SYNCOBJ_EVENTFD`, an `_IOWR` on the
+        //   `drm_syncobj_eventfd` struct that drm-sys generates from the kernel headers,
+        //   so the kernel reads and writes exactly `args`, which outlives the call.
+        // - `device` and `eventfd` are borrowed for the call, so their fds stay open.
+        //   The kernel takes its own reference to `eventfd` if it keeps it, and rejects an fd
+        //   of -1 or an invalid handle with an error instead of touching any other memory.
+        // - Kernels without this ioctl fail it with `ENOTTY`/`EINVAL`.
+        unsafe { raw_syncobj_eventfd(device.as_fd().as_raw_fd(), &mut args) }.map(|_| ())
+    }
+}
+
+/// Whether the kernel supports waiting on syncobj timeline points with an eventfd,
+/// which is needed to wait on acquire points.
+pub fn supports_eventfd(device: &DrmDeviceFd) -> bool {
+    // The ioctl exists if it fails on the invalid syncobj handle
+    ioctl::syncobj_eventfd(device, 0, 0, None) == Err(nix::errno::Errno::ENOENT)
+}
+
+/// A DRM syncobj timeline imported from a client.
+#[derive(Debug)]
+pub struct DrmTimeline {
+    device: DrmDeviceFd,
+    handle: syncobj::Handle,
+}
+
+impl DrmTimeline {
+    /// Import a syncobj timeline from a file descriptor.
+    pub fn import(device: &DrmDeviceFd, fd: BorrowedFd<'_>) -> std::io::Result<Self> {
+        let handle = device.fd_to_syncobj(fd, false)?;
+        Ok(Self {
+            device: device.clone(),
+            handle,
+        })
+    }
+}
+
+impl Drop for DrmTimeline {
+    fn drop(&mut self) {
+        if let Err(err) = self.device.destroy_syncobj(self.handle) {
+            warn!("Failed to destroy syncobj: {err}");
+        }
+    }
+}
+
+/// A point on a [`DrmTimeline`].
+#[derive(Debug, Clone)]
+pub struct DrmSyncPoint {
+    timeline: Arc<DrmTimeline>,
+    point: u64,
+}
+
+impl DrmSyncPoint {
+    /// Create an eventfd that becomes readable once this point has been signaled.
+    fn eventfd(&self) -> std::io::Result<EventFd> {
+        let eventfd = EventFd::from_flags(EfdFlags::EFD_CLOEXEC | EfdFlags::EFD_NONBLOCK)?;
+
+        ioctl::syncobj_eventfd(
+            &self.timeline.device,
+            self.timeline.handle.into(),
+            self.point,
+            Some(eventfd.as_fd()),
+        )?;
+
+        Ok(eventfd)
+    }
+
+    /// Signal this point.
+    pub fn signal(&self) -> std::io::Result<()> {
+        self.timeline
+            .device
+            .syncobj_timeline_signal(&[self.timeline.handle], &[self.point])
+    }
+
+    /// Create a [`Blocker`] that is released once this point is signaled.
+    ///
+    /// The returned source fires once the blocker has been released and should be
+    /// inserted into the event loop. It removes itself afterwards.
+    pub fn generate_blocker(&self) -> std::io::Result<(DrmSyncPointBlocker, DrmSyncPointSource)> {
+        let eventfd = self.eventfd()?;
+        let signaled = Arc::new(AtomicBool::new(false));
+
+        let source = DrmSyncPointSource {
+            source: Generic::new(eventfd, Interest::READ, Mode::OneShot),
+            signaled: signaled.clone(),
+        };
+
+        Ok((DrmSyncPointBlocker { signaled }, source))
+    }
+
+    fn is_same_timeline(&self, other: &DrmSyncPoint) -> bool {
+        Arc::ptr_eq(&self.timeline, &other.timeline)
+    }
+}
+
+/// A [`Blocker`] waiting on an acquire point.
+#[derive(Debug)]
+pub struct DrmSyncPointBlocker {
+    signaled: Arc<AtomicBool>,
+}
+
+impl Blocker for DrmSyncPointBlocker {
+    fn state(&self) -> BlockerState {
+        if self.signaled.load(Ordering::Acquire) {
+            BlockerState::Released
+        } else {
+            BlockerState::Pending
+        }
+    }
+}
+
+/// An event source that fires once the [`DrmSyncPoint`] of a [`DrmSyncPointBlocker`]
+/// has been signaled.
+pub struct DrmSyncPointSource {
+    source: Generic<EventFd>,
+    signaled: Arc<AtomicBool>,
+}
+
+impl EventSource for DrmSyncPointSource {
+    type Event = ();
+    type Metadata = ();
+    type Ret = ();
+    type Error = std::io::Error;
+
+    fn process_events<F>(
+        &mut self,
+        readiness: Readiness,
+        token: Token,
+        mut callback: F,
+    ) -> Result<PostAction, Self::Error>
+    where
+        F: FnMut(Self::Event, &mut Self::Metadata) -> Self::Ret,
+    {
+        self.source.process_events(readiness, token, |_, _| {
+            self.signaled.store(true, Ordering::Release);
+            callback((), &mut ());
+            Ok(PostAction::Remove)
+        })
+    }
+
+    fn register(
+        &mut self,
+        poll: &mut Poll,
+        token_factory: &mut TokenFactory,
+    ) -> smithay::reexports::calloop::Result<()> {
+        self.source.register(poll, token_factory)
+    }
+
+    fn reregister(
+        &mut self,
+        poll: &mut Poll,
+        token_factory: &mut TokenFactory,
+    ) -> smithay::reexports::calloop::Result<()> {
+        self.source.reregister(poll, token_factory)
+    }
+
+    fn unregister(&mut self, poll: &mut Poll) -> smithay::reexports::calloop::Result<()> {
+        self.source.unregister(poll)
+    }
+}
+
+/// A release point that is signaled when dropped.
+///
+/// The surface holds onto the release point of its current buffer. Backends that read
+/// from the buffer asynchronously clone the [`Arc`] from [`release_point`] and hold it
+/// until they're done, so the point is signaled once the last user lets go of it.
+#[derive(Debug)]
+pub struct DrmReleasePoint(DrmSyncPoint);
+
+impl Drop for DrmReleasePoint {
+    fn drop(&mut self) {
+        if let Err(err) = self.0.signal() {
+            warn!("Failed to signal release point: {err}");
+        }
+    }
+}
+
+/// The double-buffered sync points of a surface.
+#[derive(Debug, Default)]
+pub struct DrmSyncobjCachedState {
+    pub acquire_point: Option<DrmSyncPoint>,
+    pub release_point: Option<DrmSyncPoint>,
+}
+
+impl Cacheable for DrmSyncobjCachedState {
+    fn commit(&mut self, _dh: &DisplayHandle) -> Self {
+        Self {
+            acquire_point: self.acquire_point.take(),
+            release_point: self.release_point.take(),
+        }
+    }
+
+    fn merge_into(self, into: &mut Self, _dh: &DisplayHandle) {
+        // Sync points only apply to the buffer they were committed with
+        *into = self;
+    }
+}
+
+/// The syncobj surface object of a surface, if one exists.
+#[derive(Default)]
+struct DrmSyncobjSurfaceData {
+    syncobj_surface: Mutex<Option<WpLinuxDrmSyncobjSurfaceV1>>,
+    /// The release point of the currently attached buffer.
+    release_point: Mutex<Option<Arc<DrmReleasePoint>>>,
+}
+
+pub struct DrmSyncobjState {
+    device: DrmDeviceFd,
+}
+
+pub struct DrmSyncobjGlobalData {
+    filter: Box<dyn Fn(&Client) -> bool + Send + Sync>,
+}
+
+impl DrmSyncobjState {
+    /// Create the `linux-drm-syncobj-v1` global.
+    ///
+    /// Timelines are imported on the given device, which should support syncobj timelines.
+    pub fn new<D, F>(display: &DisplayHandle, device: DrmDeviceFd, filter: F) -> Self
+    where
+        D: GlobalDispatch<WpLinuxDrmSyncobjManagerV1, DrmSyncobjGlobalData>
+            + Dispatch<WpLinuxDrmSyncobjManagerV1, ()>
+            + Dispatch<WpLinuxDrmSyncobjTimelineV1, Arc<DrmTimeline>>
+            + Dispatch<WpLinuxDrmSyncobjSurfaceV1, Weak<WlSurface>>
+            + DrmSyncobjHandler
+            + 'static,
+        F: Fn(&Client) -> bool + Send + Sync + 'static,
+    {
+        let global_data = DrmSyncobjGlobalData {
+            filter: Box::new(filter),
+        };
+        display.create_global::<D, WpLinuxDrmSyncobjManagerV1, _>(VERSION, global_data);
+        Self { device }
+    }
+}
+
+pub trait DrmSyncobjHandler {
+    fn drm_syncobj_state(&mut self) -> &mut DrmSyncobjState;
+}
+
+/// Validate the pending sync points of a surface and return the acquire point, if any.
+///
+/// This should be called in a pre-commit hook. Protocol errors are posted here.
+pub fn pending_acquire_point(surface: &WlSurface) -> Option<DrmSyncPoint> {
+    compositor::with_states(surface, |states| {
+        let syncobj_surface = states
+            .data_map
+            .get::<DrmSyncobjSurfaceData>()?
+            .syncobj_surface
+            .lock()
+            .expect("syncobj mutex was poisoned")
+            .clone()?;
+
+        let buffer = match &states.cached_state.pending::<SurfaceAttributes>().buffer {
+            Some(BufferAssignment::NewBuffer(buffer)) => Some(buffer.clone()),
+            _ => None,
+        };
+        let mut pending = states.cached_state.pending::<DrmSyncobjCachedState>();
+
+        let Some(buffer) = buffer else {
+            if pending.acquire_point.is_some() || pending.release_point.is_some() {
+                syncobj_surface.post_error(
+                    wp_linux_drm_syncobj_surface_v1::Error::NoBuffer,
+                    "sync points were set without a buffer",
+                );
+            }
+            return None;
+        };
+
+        let error = validate_points(&buffer, &pending);
+        if let Some((error, message)) = error {
+            syncobj_surface.post_error(error, message);
+            pending.acquire_point = None;
+            pending.release_point = None;
+            return None;
+        }
+
+        pending.acquire_point.clone()
+    })
+}
+
+fn validate_points(
+    buffer: &WlBuffer,
+    pending: &DrmSyncobjCachedState,
+) -> Option<(wp_linux_drm_syncobj_surface_v1::Error, &'static str)> {
+    let (Some(acquire), Some(release)) = (&pending.acquire_point, &pending.release_point) else {
+        return if pending.acquire_point.is_none() {
+            Some((
+                wp_linux_drm_syncobj_surface_v1::Error::NoAcquirePoint,
+                "a buffer was attached without an acquire point",
+            ))
+        } else {
+            Some((
+                wp_linux_drm_syncobj_surface_v1::Error::NoReleasePoint,
+                "a buffer was attached without a release point",
+            ))
+        };
+    };
+
+    if dmabuf::get_dmabuf(buffer).is_err() {
+        return Some((
+            wp_linux_drm_syncobj_surface_v1::Error::UnsupportedBuffer,
+            "explicit sync is only supported for dmabufs",
+        ));
+    }
+
+    if acquire.is_same_timeline(release) && acquire.point >= release.point {
+        return Some((
+            wp_linux_drm_syncobj_surface_v1::Error::ConflictingPoints,
+            "the acquire point must come before the release point",
+        ));
+    }
+
+    None
+}
+
+/// Swap in the release point of the buffer that was just committed.
+///
+/// This should be called on commit before the buffer is taken out of the surface's
+/// [`SurfaceAttributes`]. The release point of the replaced buffer is signaled once
+/// nothing holds onto it anymore.
+pub fn update_release_point(surface: &WlSurface) {
+    compositor::with_states(surface, |states| {
+        let Some(data) = states.data_map.get::<DrmSyncobjSurfaceData>() else {
+            return;
+        };
+
+        let new_buffer = matches!(
+            states.cached_state.current::<SurfaceAttributes>().buffer,
+            Some(BufferAssignment::NewBuffer(_)) | Some(BufferAssignment::Removed)
+        );
+
+        let new_release_point = states
+            .cached_state
+            .current::<DrmSyncobjCachedState>()
+            .release_point
+            .take()
+            .map(|point| Arc::new(DrmReleasePoint(point)));
+
+        if !new_buffer && new_release_point.is_none() {
+            return;
+        }
+
+        *data
+            .release_point
+            .lock()
+            .expect("syncobj mutex was poisoned") = new_release_point;
+    })
+}
+
+/// Get the release point of a surface's current buffer.
+///
+/// Hold onto it while the buffer may still be read from after the surface moves on
+/// to another buffer.
+pub fn release_point(states: &SurfaceData) -> Option<Arc<DrmReleasePoint>> {
+    states
+        .data_map
+        .get::<DrmSyncobjSurfaceData>()?
+        .release_point
+        .lock()
+        .expect("syncobj mutex was poisoned")
+        .clone()
+}
+
+impl<D> GlobalDispatch<WpLinuxDrmSyncobjManagerV1, DrmSyncobjGlobalData, D> for DrmSyncobjState
+where
+    D: GlobalDispatch<WpLinuxDrmSyncobjManagerV1, DrmSyncobjGlobalData>
+        + Dispatch<WpLinuxDrmSyncobjManagerV1, ()>
+        + Dispatch<WpLinuxDrmSyncobjTimelineV1, Arc<DrmTimeline>>
+        + Dispatch<WpLinuxDrmSyncobjSurfaceV1, Weak<WlSurface>>
+        + DrmSyncobjHandler
+        + 'static,
+{
+    fn bind(
+        _state: &mut D,
+        _handle: &DisplayHandle,
+        _client: &Client,
+        resource: wayland_server::New<WpLinuxDrmSyncobjManagerV1>,
+        _global_data: &DrmSyncobjGlobalData,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        data_init.init(resource, ());
+    }
+
+    fn can_view(client: Client, global_data: &DrmSyncobjGlobalData) -> bool {
+        (global_data.filter)(&client)
+    }
+}
+
+impl<D> Dispatch<WpLinuxDrmSyncobjManagerV1, (), D> for DrmSyncobjState
+where
+    D: Dispatch<WpLinuxDrmSyncobjManagerV1, ()>
+        + Dispatch<WpLinuxDrmSyncobjTimelineV1, Arc<DrmTimeline>>
+        + Dispatch<WpLinuxDrmSyncobjSurfaceV1, Weak<WlSurface>>
+        + DrmSyncobjHandler
+        + 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        manager: &WpLinuxDrmSyncobjManagerV1,
+        request: <WpLinuxDrmSyncobjManagerV1 as Resource>::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            wp_linux_drm_syncobj_manager_v1::Request::GetSurface { id, surface } => {
+                let already_exists = compositor::with_states(&surface, |states| {
+                    states
+                        .data_map
+                        .insert_if_missing_threadsafe(DrmSyncobjSurfaceData::default);
+                    states
+                        .data_map
+                        .get::<DrmSyncobjSurfaceData>()
+                        .expect("data was just inserted")
+                        .syncobj_surface
+                        .lock()
+                        .expect("syncobj mutex was poisoned")
+                        .is_some()
+                });
+
+                if already_exists {
+                    manager.post_error(
+                        wp_linux_drm_syncobj_manager_v1::Error::SurfaceExists,
+                        "surface already has a syncobj surface",
+                    );
+                    return;
+                }
+
+                let syncobj_surface = data_init.init(id, surface.downgrade());
+
+                compositor::with_states(&surface, |states| {
+                    if let Some(data) = states.data_map.get::<DrmSyncobjSurfaceData>() {
+                        *data
+                            .syncobj_surface
+                            .lock()
+                            .expect("syncobj mutex was poisoned") = Some(syncobj_surface);
+                    }
+                });
+            }
+            wp_linux_drm_syncobj_manager_v1::Request::ImportTimeline { id, fd } => {
+                match DrmTimeline::import(&state.drm_syncobj_state().device, fd.as_fd()) {
+                    Ok(timeline) => {
+                        data_init.init(id, Arc::new(timeline));
+                    }
+                    Err(err) => {
+                        manager.post_error(
+                            wp_linux_drm_syncobj_manager_v1::Error::InvalidTimeline,
+                            format!("failed to import timeline: {err}"),
+                        );
+                    }
+                }
+            }
+            wp_linux_drm_syncobj_manager_v1::Request::Destroy => (),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<WpLinuxDrmSyncobjTimelineV1, Arc<DrmTimeline>, D> for DrmSyncobjState
+where
+    D: Dispatch<WpLinuxDrmSyncobjTimelineV1, Arc<DrmTimeline>>,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _resource: &WpLinuxDrmSyncobjTimelineV1,
+        request: <WpLinuxDrmSyncobjTimelineV1 as Resource>::Request,
+        _data: &Arc<DrmTimeline>,
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            // Points that were already set keep the timeline alive
+            wp_linux_drm_syncobj_timeline_v1::Request::Destroy => (),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<WpLinuxDrmSyncobjSurfaceV1, Weak<WlSurface>, D> for DrmSyncobjState
+where
+    D: Dispatch<WpLinuxDrmSyncobjSurfaceV1, Weak<WlSurface>>,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        resource: &WpLinuxDrmSyncobjSurfaceV1,
+        request: <WpLinuxDrmSyncobjSurfaceV1 as Resource>::Request,
+        data: &Weak<WlSurface>,
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        let Ok(surface) = data.upgrade() else {
+            if !matches!(request, wp_linux_drm_syncobj_surface_v1::Request::Destroy) {
+                resource.post_error(
+                    wp_linux_drm_syncobj_surface_v1::Error::NoSurface,
+                    "the surface was destroyed",
+                );
+            }
+            return;
+        };
+
+        match request {
+            wp_linux_drm_syncobj_surface_v1::Request::SetAcquirePoint {
+                timeline,
+                point_hi,
+                point_lo,
+            } => {
+                let Some(sync_point) = sync_point(&timeline, point_hi, point_lo) else {
+                    return;
+                };
+                compositor::with_states(&surface, |states| {
+                    states
+                        .cached_state
+                        .pending::<DrmSyncobjCachedState>()
+                        .acquire_point = Some(sync_point);
+                });
+            }
+            wp_linux_drm_syncobj_surface_v1::Request::SetReleasePoint {
+                timeline,
+                point_hi,
+                point_lo,
+            } => {
+                let Some(sync_point) = sync_point(&timeline, point_hi, point_lo) else {
+                    return;
+                };
+                compositor::with_states(&surface, |states| {
+                    states
+                        .cached_state
+                        .pending::<DrmSyncobjCachedState>()
+                        .release_point = Some(sync_point);
+                });
+            }
+            wp_linux_drm_syncobj_surface_v1::Request::Destroy => {
+                compositor::with_states(&surface, |states| {
+                    *states.cached_state.pending::<DrmSyncobjCachedState>() =
+                        DrmSyncobjCachedState::default();
+                    if let Some(data) = states.data_map.get::<DrmSyncobjSurfaceData>() {
+                        *data
+                            .syncobj_surface
+                            .lock()
+                            .expect("syncobj mutex was poisoned") = None;
+                    }
+                });
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+fn sync_point(
+    timeline: &WpLinuxDrmSyncobjTimelineV1,
+    point_hi: u32,
+    point_lo: u32,
+) -> Option<DrmSyncPoint> {
+    let timeline = timeline.data::<Arc<DrmTimeline>>()?.clone();
+    Some(DrmSyncPoint {
+        timeline,
+        point: ((point_hi as u64) << 32) | point_lo as u64,
+    })
+}
+
+#[allow(missing_docs)]
+#[macro_export]
+macro_rules! delegate_drm_syncobj {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        smithay::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::protocol::drm_syncobj::generated::wp_linux_drm_syncobj_manager_v1::WpLinuxDrmSyncobjManagerV1: $crate::protocol::drm_syncobj::DrmSyncobjGlobalData
+        ] => $crate::protocol::drm_syncobj::DrmSyncobjState);
+
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::protocol::drm_syncobj::generated::wp_linux_drm_syncobj_manager_v1::WpLinuxDrmSyncobjManagerV1: ()
+        ] => $crate::protocol::drm_syncobj::DrmSyncobjState);
+
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::protocol::drm_syncobj::generated::wp_linux_drm_syncobj_timeline_v1::WpLinuxDrmSyncobjTimelineV1: std::sync::Arc<$crate::protocol::drm_syncobj::DrmTimeline>
+        ] => $crate::protocol::drm_syncobj::DrmSyncobjState);
+
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::protocol::drm_syncobj::generated::wp_linux_drm_syncobj_surface_v1::WpLinuxDrmSyncobjSurfaceV1: smithay::reexports::wayland_server::Weak<smithay::reexports::wayland_server::protocol::wl_surface::WlSurface>
+        ] => $crate::protocol::drm_syncobj::DrmSyncobjState);
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn syncobj_eventfd_request_code_matches_kernel() {
+        // DRM_IOCTL_SYNCOBJ_EVENTFD in the kernel's drm.h
+        assert_eq!(
+            nix::request_code_readwrite!(
+                drm_ffi::DRM_IOCTL_BASE,
+                0xCF,
+                std::mem::size_of::<drm_ffi::drm_syncobj_eventfd>()
+            ),
+            0xC018_64CF
+        );
+    }
+}
@@ -0,0 +1,283 @@
+//! An implementation of `ext-foreign-toplevel-list-v1`.
+//!
+//! This is a read-only list of windows. Each window is identified by its [`WindowId`],
+//! which is never reused.
+
+use std::collections::HashMap;
+
+use smithay::reexports::{
+    wayland_protocols::ext::foreign_toplevel_list::v1::server::{
+        ext_foreign_toplevel_handle_v1::{self, ExtForeignToplevelHandleV1},
+        ext_foreign_toplevel_list_v1::{self, ExtForeignToplevelListV1},
+    },
+    wayland_server::{
+        self, backend::ClientId, Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch,
+        Resource,
+    },
+};
+use tracing::warn;
+
+use crate::window::window_state::WindowId;
+
+const VERSION: u32 = 1;
+
+/// A snapshot of a toplevel to advertise to clients.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForeignToplevel {
+    pub id: WindowId,
+    pub title: String,
+    pub app_id: String,
+}
+
+/// A bound `ext_foreign_toplevel_list_v1` and the handles created for it.
+struct ListInstance {
+    list: ExtForeignToplevelListV1,
+    handles: HashMap<WindowId, ExtForeignToplevelHandleV1>,
+}
+
+pub struct ExtForeignToplevelListState {
+    display_handle: DisplayHandle,
+    instances: Vec<ListInstance>,
+    /// The last advertised toplevels.
+    toplevels: Vec<ForeignToplevel>,
+}
+
+pub struct ExtForeignToplevelListGlobalData {
+    filter: Box<dyn Fn(&Client) -> bool + Send + Sync>,
+}
+
+impl ExtForeignToplevelListState {
+    pub fn new<D, F>(display: &DisplayHandle, filter: F) -> Self
+    where
+        D: GlobalDispatch<ExtForeignToplevelListV1, ExtForeignToplevelListGlobalData>
+            + Dispatch<ExtForeignToplevelListV1, ()>
+            + Dispatch<ExtForeignToplevelHandleV1, WindowId>
+            + ExtForeignToplevelListHandler
+            + 'static,
+        F: Fn(&Client) -> bool + Send + Sync + 'static,
+    {
+        let global_data = ExtForeignToplevelListGlobalData {
+            filter: Box::new(filter),
+        };
+        display.create_global::<D, ExtForeignToplevelListV1, _>(VERSION, global_data);
+        Self {
+            display_handle: display.clone(),
+            instances: Vec::new(),
+            toplevels: Vec::new(),
+        }
+    }
+
+    /// Diff the given toplevels against the ones last advertised and
+    /// send any changes to clients.
+    pub fn update<D>(&mut self, toplevels: Vec<ForeignToplevel>)
+    where
+        D: Dispatch<ExtForeignToplevelHandleV1, WindowId> + 'static,
+    {
+        if toplevels == self.toplevels {
+            return;
+        }
+
+        let old_toplevels = self
+            .toplevels
+            .iter()
+            .map(|toplevel| (toplevel.id, toplevel))
+            .collect::<HashMap<_, _>>();
+
+        for instance in self.instances.iter_mut() {
+            instance.handles.retain(|id, handle| {
+                let still_exists = toplevels.iter().any(|toplevel| &toplevel.id == id);
+                if !still_exists {
+                    handle.closed();
+                }
+                still_exists
+            });
+
+            for toplevel in toplevels.iter() {
+                let Some(handle) = instance.handles.get(&toplevel.id) else {
+                    instance.create_handle::<D>(&self.display_handle, toplevel);
+                    continue;
+                };
+
+                let Some(old_toplevel) = old_toplevels.get(&toplevel.id) else {
+                    continue;
+                };
+
+                if old_toplevel == &toplevel {
+                    continue;
+                }
+
+                if old_toplevel.title != toplevel.title {
+                    handle.title(toplevel.title.clone());
+                }
+                if old_toplevel.app_id != toplevel.app_id {
+                    handle.app_id(toplevel.app_id.clone());
+                }
+                handle.done();
+            }
+        }
+
+        self.toplevels = toplevels;
+    }
+}
+
+impl ListInstance {
+    /// Create a new toplevel handle and send its initial state.
+    fn create_handle<D>(&mut self, display_handle: &DisplayHandle, toplevel: &ForeignToplevel)
+    where
+        D: Dispatch<ExtForeignToplevelHandleV1, WindowId> + 'static,
+    {
+        let Ok(client) = display_handle.get_client(self.list.id()) else {
+            return;
+        };
+
+        let handle = match client.create_resource::<ExtForeignToplevelHandleV1, _, D>(
+            display_handle,
+            self.list.version(),
+            toplevel.id,
+        ) {
+            Ok(handle) => handle,
+            Err(err) => {
+                warn!("Failed to create foreign toplevel handle: {err}");
+                return;
+            }
+        };
+
+        self.list.toplevel(&handle);
+        handle.identifier(toplevel.id.0.to_string());
+        handle.title(toplevel.title.clone());
+        handle.app_id(toplevel.app_id.clone());
+        handle.done();
+
+        self.handles.insert(toplevel.id, handle);
+    }
+}
+
+pub trait ExtForeignToplevelListHandler {
+    fn ext_foreign_toplevel_list_state(&mut self) -> &mut ExtForeignToplevelListState;
+}
+
+impl<D> GlobalDispatch<ExtForeignToplevelListV1, ExtForeignToplevelListGlobalData, D>
+    for ExtForeignToplevelListState
+where
+    D: GlobalDispatch<ExtForeignToplevelListV1, ExtForeignToplevelListGlobalData>
+        + Dispatch<ExtForeignToplevelListV1, ()>
+        + Dispatch<ExtForeignToplevelHandleV1, WindowId>
+        + ExtForeignToplevelListHandler
+        + 'static,
+{
+    fn bind(
+        state: &mut D,
+        handle: &DisplayHandle,
+        _client: &Client,
+        resource: wayland_server::New<ExtForeignToplevelListV1>,
+        _global_data: &ExtForeignToplevelListGlobalData,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        let list = data_init.init(resource, ());
+
+        let mut instance = ListInstance {
+            list,
+            handles: HashMap::new(),
+        };
+
+        let list_state = state.ext_foreign_toplevel_list_state();
+
+        for toplevel in list_state.toplevels.iter() {
+            instance.create_handle::<D>(handle, toplevel);
+        }
+
+        list_state.instances.push(instance);
+    }
+
+    fn can_view(client: Client, global_data: &ExtForeignToplevelListGlobalData) -> bool {
+        (global_data.filter)(&client)
+    }
+}
+
+impl<D> Dispatch<ExtForeignToplevelListV1, (), D> for ExtForeignToplevelListState
+where
+    D: Dispatch<ExtForeignToplevelListV1, ()> + ExtForeignToplevelListHandler + 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        resource: &ExtForeignToplevelListV1,
+        request: <ExtForeignToplevelListV1 as Resource>::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            ext_foreign_toplevel_list_v1::Request::Stop => {
+                state
+                    .ext_foreign_toplevel_list_state()
+                    .instances
+                    .retain(|instance| &instance.list != resource);
+                resource.finished();
+            }
+            ext_foreign_toplevel_list_v1::Request::Destroy => (),
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(
+        state: &mut D,
+        _client: ClientId,
+        resource: &ExtForeignToplevelListV1,
+        _data: &(),
+    ) {
+        state
+            .ext_foreign_toplevel_list_state()
+            .instances
+            .retain(|instance| &instance.list != resource);
+    }
+}
+
+impl<D> Dispatch<ExtForeignToplevelHandleV1, WindowId, D> for ExtForeignToplevelListState
+where
+    D: Dispatch<ExtForeignToplevelHandleV1, WindowId> + ExtForeignToplevelListHandler + 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _resource: &ExtForeignToplevelHandleV1,
+        request: <ExtForeignToplevelHandleV1 as Resource>::Request,
+        _data: &WindowId,
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            ext_foreign_toplevel_handle_v1::Request::Destroy => (),
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(
+        state: &mut D,
+        _client: ClientId,
+        resource: &ExtForeignToplevelHandleV1,
+        _data: &WindowId,
+    ) {
+        for instance in state.ext_foreign_toplevel_list_state().instances.iter_mut() {
+            instance.handles.retain(|_, handle| handle != resource);
+        }
+    }
+}
+
+#[allow(missing_docs)]
+#[macro_export]
+macro_rules! delegate_ext_foreign_toplevel_list {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        smithay::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::ext::foreign_toplevel_list::v1::server::ext_foreign_toplevel_list_v1::ExtForeignToplevelListV1: $crate::protocol::ext_foreign_toplevel_list::ExtForeignToplevelListGlobalData
+        ] => $crate::protocol::ext_foreign_toplevel_list::ExtForeignToplevelListState);
+
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::ext::foreign_toplevel_list::v1::server::ext_foreign_toplevel_list_v1::ExtForeignToplevelListV1: ()
+        ] => $crate::protocol::ext_foreign_toplevel_list::ExtForeignToplevelListState);
+
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::ext::foreign_toplevel_list::v1::server::ext_foreign_toplevel_handle_v1::ExtForeignToplevelHandleV1: $crate::window::window_state::WindowId
+        ] => $crate::protocol::ext_foreign_toplevel_list::ExtForeignToplevelListState);
+    };
+}
@@ -0,0 +1,538 @@
+//! An implementation of `ext-workspace-v1`.
+//!
+//! Pinnacle doesn't have workspaces, so this maps tags onto them instead.
+//! Every output is a workspace group, and every tag on that output is a workspace in that group.
+
+use std::collections::HashMap;
+
+use smithay::{
+    output::Output,
+    reexports::wayland_server::{
+        self, backend::ClientId, Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch,
+        Resource,
+    },
+};
+use tracing::warn;
+
+use crate::tag::TagId;
+
+use self::generated::{
+    ext_workspace_group_handle_v1::{self, ExtWorkspaceGroupHandleV1, GroupCapabilities},
+    ext_workspace_handle_v1::{self, ExtWorkspaceHandleV1, WorkspaceCapabilities},
+    ext_workspace_manager_v1::{self, ExtWorkspaceManagerV1},
+};
+
+#[allow(missing_docs)]
+pub mod generated {
+    #![allow(dead_code, non_camel_case_types, unused_unsafe, unused_variables)]
+    #![allow(non_upper_case_globals, non_snake_case, unused_imports)]
+    #![allow(clippy::all)]
+
+    use smithay::reexports::wayland_server;
+    use smithay::reexports::wayland_server::protocol::*;
+
+    pub mod __interfaces {
+        use smithay::reexports::wayland_server::protocol::__interfaces::*;
+        wayland_scanner::generate_interfaces!("resources/protocols/ext-workspace-v1.xml");
+    }
+    use self::__interfaces::*;
+
+    wayland_scanner::generate_server_code!("resources/protocols/ext-workspace-v1.xml");
+}
+
+const VERSION: u32 = 1;
+
+/// A snapshot of a workspace group to advertise to clients.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkspaceGroup {
+    /// The output this group is assigned to.
+    pub output: Output,
+    /// The workspaces in this group, in order.
+    pub workspaces: Vec<Workspace>,
+}
+
+/// A snapshot of a workspace to advertise to clients.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Workspace {
+    pub id: TagId,
+    pub name: String,
+    pub active: bool,
+}
+
+impl Workspace {
+    fn state(&self) -> ext_workspace_handle_v1::State {
+        if self.active {
+            ext_workspace_handle_v1::State::Active
+        } else {
+            ext_workspace_handle_v1::State::empty()
+        }
+    }
+}
+
+/// An action a client requested on a workspace, applied once the client commits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WorkspaceAction {
+    Activate(TagId),
+    Deactivate(TagId),
+}
+
+/// A bound `ext_workspace_manager_v1` and the objects created for it.
+struct ManagerInstance {
+    manager: ExtWorkspaceManagerV1,
+    groups: HashMap<Output, ExtWorkspaceGroupHandleV1>,
+    workspaces: HashMap<TagId, ExtWorkspaceHandleV1>,
+    pending_actions: Vec<WorkspaceAction>,
+}
+
+pub struct ExtWorkspaceManagerState {
+    display_handle: DisplayHandle,
+    instances: Vec<ManagerInstance>,
+    /// The last advertised workspace groups.
+    groups: Vec<WorkspaceGroup>,
+    /// Whether events were sent without a following `done`.
+    done_pending: bool,
+}
+
+pub struct ExtWorkspaceGlobalData {
+    filter: Box<dyn Fn(&Client) -> bool + Send + Sync>,
+}
+
+impl ExtWorkspaceManagerState {
+    pub fn new<D, F>(display: &DisplayHandle, filter: F) -> Self
+    where
+        D: GlobalDispatch<ExtWorkspaceManagerV1, ExtWorkspaceGlobalData>
+            + Dispatch<ExtWorkspaceManagerV1, ()>
+            + Dispatch<ExtWorkspaceGroupHandleV1, Output>
+            + Dispatch<ExtWorkspaceHandleV1, TagId>
+            + ExtWorkspaceHandler
+            + 'static,
+        F: Fn(&Client) -> bool + Send + Sync + 'static,
+    {
+        let global_data = ExtWorkspaceGlobalData {
+            filter: Box::new(filter),
+        };
+        display.create_global::<D, ExtWorkspaceManagerV1, _>(VERSION, global_data);
+        Self {
+            display_handle: display.clone(),
+            instances: Vec::new(),
+            groups: Vec::new(),
+            done_pending: false,
+        }
+    }
+
+    /// Send a workspace's new active state to all clients.
+    ///
+    /// A `done` event will be sent on the next call to [`Self::update`].
+    pub fn set_workspace_active(&mut self, id: TagId, active: bool) {
+        let Some(workspace) = self
+            .groups
+            .iter_mut()
+            .flat_map(|group| group.workspaces.iter_mut())
+            .find(|ws| ws.id == id)
+        else {
+            return;
+        };
+
+        if workspace.active == active {
+            return;
+        }
+
+        workspace.active = active;
+
+        for instance in self.instances.iter() {
+            if let Some(handle) = instance.workspaces.get(&id) {
+                handle.state(workspace.state());
+            }
+        }
+
+        self.done_pending = true;
+    }
+
+    /// Diff the given workspace groups against the ones last advertised and
+    /// send any changes to clients.
+    pub fn update<D>(&mut self, groups: Vec<WorkspaceGroup>)
+    where
+        D: Dispatch<ExtWorkspaceGroupHandleV1, Output>
+            + Dispatch<ExtWorkspaceHandleV1, TagId>
+            + 'static,
+    {
+        if groups == self.groups {
+            if self.done_pending {
+                for instance in self.instances.iter() {
+                    instance.manager.done();
+                }
+                self.done_pending = false;
+            }
+            return;
+        }
+
+        let old_workspaces = self
+            .groups
+            .iter()
+            .flat_map(|group| {
+                group
+                    .workspaces
+                    .iter()
+                    .enumerate()
+                    .map(|(i, ws)| (ws.id, (ws, &group.output, i)))
+            })
+            .collect::<HashMap<_, _>>();
+
+        for instance in self.instances.iter_mut() {
+            // Workspaces must leave their group before the group can be removed
+            instance.workspaces.retain(|id, handle| {
+                let still_exists = groups
+                    .iter()
+                    .any(|group| group.workspaces.iter().any(|ws| &ws.id == id));
+                if !still_exists {
+                    if let Some(group_handle) = old_workspaces
+                        .get(id)
+                        .and_then(|(_, output, _)| instance.groups.get(*output))
+                    {
+                        group_handle.workspace_leave(handle);
+                    }
+                    handle.removed();
+                }
+                still_exists
+            });
+
+            instance.groups.retain(|output, handle| {
+                let still_exists = groups.iter().any(|group| &group.output == output);
+                if !still_exists {
+                    handle.removed();
+                }
+                still_exists
+            });
+
+            for group in groups.iter() {
+                if !instance.groups.contains_key(&group.output) {
+                    instance.create_group::<D>(&self.display_handle, &group.output);
+                }
+
+                let Some(group_handle) = instance.groups.get(&group.output).cloned() else {
+                    continue;
+                };
+
+                for (i, workspace) in group.workspaces.iter().enumerate() {
+                    let Some(handle) = instance.workspaces.get(&workspace.id) else {
+                        if let Some(handle) =
+                            instance.create_workspace::<D>(&self.display_handle, workspace, i)
+                        {
+                            group_handle.workspace_enter(&handle);
+                        }
+                        continue;
+                    };
+
+                    let Some((old_workspace, old_output, old_index)) =
+                        old_workspaces.get(&workspace.id)
+                    else {
+                        continue;
+                    };
+
+                    if old_workspace.name != workspace.name {
+                        handle.name(workspace.name.clone());
+                    }
+                    if old_workspace.active != workspace.active {
+                        handle.state(workspace.state());
+                    }
+                    if *old_index != i {
+                        handle.coordinates(coordinates(i));
+                    }
+                    if *old_output != &group.output {
+                        if let Some(old_group_handle) = instance.groups.get(*old_output) {
+                            old_group_handle.workspace_leave(handle);
+                        }
+                        group_handle.workspace_enter(handle);
+                    }
+                }
+            }
+
+            instance.manager.done();
+        }
+
+        self.groups = groups;
+        self.done_pending = false;
+    }
+}
+
+impl ManagerInstance {
+    /// Create a new group handle for the given output and send its initial state.
+    fn create_group<D>(&mut self, display_handle: &DisplayHandle, output: &Output)
+    where
+        D: Dispatch<ExtWorkspaceGroupHandleV1, Output> + 'static,
+    {
+        let Ok(client) = display_handle.get_client(self.manager.id()) else {
+            return;
+        };
+
+        let handle = match client.create_resource::<ExtWorkspaceGroupHandleV1, _, D>(
+            display_handle,
+            self.manager.version(),
+            output.clone(),
+        ) {
+            Ok(handle) => handle,
+            Err(err) => {
+                warn!("Failed to create workspace group handle: {err}");
+                return;
+            }
+        };
+
+        self.manager.workspace_group(&handle);
+        handle.capabilities(GroupCapabilities::empty());
+
+        // TODO: send `output_enter` for wl_outputs the client binds after this
+        for wl_output in output.client_outputs(&client) {
+            handle.output_enter(&wl_output);
+        }
+
+        self.groups.insert(output.clone(), handle);
+    }
+
+    /// Create a new workspace handle and send its initial state.
+    ///
+    /// This does not assign the workspace to a group.
+    fn create_workspace<D>(
+        &mut self,
+        display_handle: &DisplayHandle,
+        workspace: &Workspace,
+        index: usize,
+    ) -> Option<ExtWorkspaceHandleV1>
+    where
+        D: Dispatch<ExtWorkspaceHandleV1, TagId> + 'static,
+    {
+        let client = display_handle.get_client(self.manager.id()).ok()?;
+
+        let handle = match client.create_resource::<ExtWorkspaceHandleV1, _, D>(
+            display_handle,
+            self.manager.version(),
+            workspace.id,
+        ) {
+            Ok(handle) => handle,
+            Err(err) => {
+                warn!("Failed to create workspace handle: {err}");
+                return None;
+            }
+        };
+
+        self.manager.workspace(&handle);
+        handle.id(workspace.id.0.to_string());
+        handle.name(workspace.name.clone());
+        handle.coordinates(coordinates(index));
+        handle.state(workspace.state());
+        handle.capabilities(WorkspaceCapabilities::Activate | WorkspaceCapabilities::Deactivate);
+
+        self.workspaces.insert(workspace.id, handle.clone());
+
+        Some(handle)
+    }
+}
+
+/// Encode a 1D coordinate for the `coordinates` event.
+fn coordinates(index: usize) -> Vec<u8> {
+    (index as u32).to_ne_bytes().to_vec()
+}
+
+pub trait ExtWorkspaceHandler {
+    fn ext_workspace_manager_state(&mut self) -> &mut ExtWorkspaceManagerState;
+    /// A client committed a request to activate the workspace with the given id.
+    fn activate_workspace(&mut self, id: TagId);
+    /// A client committed a request to deactivate the workspace with the given id.
+    fn deactivate_workspace(&mut self, id: TagId);
+}
+
+impl<D> GlobalDispatch<ExtWorkspaceManagerV1, ExtWorkspaceGlobalData, D>
+    for ExtWorkspaceManagerState
+where
+    D: GlobalDispatch<ExtWorkspaceManagerV1, ExtWorkspaceGlobalData>
+        + Dispatch<ExtWorkspaceManagerV1, ()>
+        + Dispatch<ExtWorkspaceGroupHandleV1, Output>
+        + Dispatch<ExtWorkspaceHandleV1, TagId>
+        + ExtWorkspaceHandler
+        + 'static,
+{
+    fn bind(
+        state: &mut D,
+        handle: &DisplayHandle,
+        _client: &Client,
+        resource: wayland_server::New<ExtWorkspaceManagerV1>,
+        _global_data: &ExtWorkspaceGlobalData,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        let manager = data_init.init(resource, ());
+
+        let mut instance = ManagerInstance {
+            manager,
+            groups: HashMap::new(),
+            workspaces: HashMap::new(),
+            pending_actions: Vec::new(),
+        };
+
+        let workspace_state = state.ext_workspace_manager_state();
+
+        for group in workspace_state.groups.iter() {
+            instance.create_group::<D>(handle, &group.output);
+            let Some(group_handle) = instance.groups.get(&group.output).cloned() else {
+                continue;
+            };
+            for (i, workspace) in group.workspaces.iter().enumerate() {
+                if let Some(workspace_handle) = instance.create_workspace::<D>(handle, workspace, i)
+                {
+                    group_handle.workspace_enter(&workspace_handle);
+                }
+            }
+        }
+
+        instance.manager.done();
+
+        workspace_state.instances.push(instance);
+    }
+
+    fn can_view(client: Client, global_data: &ExtWorkspaceGlobalData) -> bool {
+        (global_data.filter)(&client)
+    }
+}
+
+impl<D> Dispatch<ExtWorkspaceManagerV1, (), D> for ExtWorkspaceManagerState
+where
+    D: Dispatch<ExtWorkspaceManagerV1, ()> + ExtWorkspaceHandler + 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        resource: &ExtWorkspaceManagerV1,
+        request: <ExtWorkspaceManagerV1 as Resource>::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            ext_workspace_manager_v1::Request::Commit => {
+                let actions = state
+                    .ext_workspace_manager_state()
+                    .instances
+                    .iter_mut()
+                    .find(|instance| &instance.manager == resource)
+                    .map(|instance| std::mem::take(&mut instance.pending_actions))
+                    .unwrap_or_default();
+
+                for action in actions {
+                    match action {
+                        WorkspaceAction::Activate(id) => state.activate_workspace(id),
+                        WorkspaceAction::Deactivate(id) => state.deactivate_workspace(id),
+                    }
+                }
+            }
+            ext_workspace_manager_v1::Request::Stop => {
+                state
+                    .ext_workspace_manager_state()
+                    .instances
+                    .retain(|instance| &instance.manager != resource);
+                resource.finished();
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(state: &mut D, _client: ClientId, resource: &ExtWorkspaceManagerV1, _data: &()) {
+        state
+            .ext_workspace_manager_state()
+            .instances
+            .retain(|instance| &instance.manager != resource);
+    }
+}
+
+impl<D> Dispatch<ExtWorkspaceGroupHandleV1, Output, D> for ExtWorkspaceManagerState
+where
+    D: Dispatch<ExtWorkspaceGroupHandleV1, Output> + ExtWorkspaceHandler + 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _resource: &ExtWorkspaceGroupHandleV1,
+        request: <ExtWorkspaceGroupHandleV1 as Resource>::Request,
+        _data: &Output,
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            // We don't advertise the `create_workspace` capability
+            ext_workspace_group_handle_v1::Request::CreateWorkspace { .. } => (),
+            ext_workspace_group_handle_v1::Request::Destroy => (),
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(
+        state: &mut D,
+        _client: ClientId,
+        resource: &ExtWorkspaceGroupHandleV1,
+        _data: &Output,
+    ) {
+        for instance in state.ext_workspace_manager_state().instances.iter_mut() {
+            instance.groups.retain(|_, handle| handle != resource);
+        }
+    }
+}
+
+impl<D> Dispatch<ExtWorkspaceHandleV1, TagId, D> for ExtWorkspaceManagerState
+where
+    D: Dispatch<ExtWorkspaceHandleV1, TagId> + ExtWorkspaceHandler + 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        resource: &ExtWorkspaceHandleV1,
+        request: <ExtWorkspaceHandleV1 as Resource>::Request,
+        data: &TagId,
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        let action = match request {
+            ext_workspace_handle_v1::Request::Activate => WorkspaceAction::Activate(*data),
+            ext_workspace_handle_v1::Request::Deactivate => WorkspaceAction::Deactivate(*data),
+            // We don't advertise the `assign` or `remove` capabilities
+            ext_workspace_handle_v1::Request::Assign { .. }
+            | ext_workspace_handle_v1::Request::Remove
+            | ext_workspace_handle_v1::Request::Destroy => return,
+            _ => unreachable!(),
+        };
+
+        // Requests on removed workspaces are ignored
+        if let Some(instance) = state
+            .ext_workspace_manager_state()
+            .instances
+            .iter_mut()
+            .find(|instance| instance.workspaces.get(data) == Some(resource))
+        {
+            instance.pending_actions.push(action);
+        }
+    }
+
+    fn destroyed(state: &mut D, _client: ClientId, resource: &ExtWorkspaceHandleV1, _data: &TagId) {
+        for instance in state.ext_workspace_manager_state().instances.iter_mut() {
+            instance.workspaces.retain(|_, handle| handle != resource);
+        }
+    }
+}
+
+#[allow(missing_docs)]
+#[macro_export]
+macro_rules! delegate_ext_workspace {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        smithay::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::protocol::ext_workspace::generated::ext_workspace_manager_v1::ExtWorkspaceManagerV1: $crate::protocol::ext_workspace::ExtWorkspaceGlobalData
+        ] => $crate::protocol::ext_workspace::ExtWorkspaceManagerState);
+
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::protocol::ext_workspace::generated::ext_workspace_manager_v1::ExtWorkspaceManagerV1: ()
+        ] => $crate::protocol::ext_workspace::ExtWorkspaceManagerState);
+
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::protocol::ext_workspace::generated::ext_workspace_group_handle_v1::ExtWorkspaceGroupHandleV1: smithay::output::Output
+        ] => $crate::protocol::ext_workspace::ExtWorkspaceManagerState);
+
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::protocol::ext_workspace::generated::ext_workspace_handle_v1::ExtWorkspaceHandleV1: $crate::tag::TagId
+        ] => $crate::protocol::ext_workspace::ExtWorkspaceManagerState);
+    };
+}
@@ -0,0 +1,670 @@
+//! An implementation of `ext-image-capture-source-v1` and `ext-image-copy-capture-v1`.
+//!
+//! These let clients like xdg-desktop-portal backends capture outputs and single
+//! toplevels into their own buffers. Toplevels are identified through the handles
+//! of `ext-foreign-toplevel-list-v1`.
+//!
+//! Only shm buffers are supported. Cursor sessions never produce frames; clients that
+//! want the cursor should ask for it to be painted onto captured frames instead.
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::UNIX_EPOCH,
+};
+
+use smithay::{
+    output::Output,
+    reexports::wayland_server::{
+        self,
+        backend::ClientId,
+        protocol::{wl_buffer::WlBuffer, wl_output::Transform, wl_shm},
+        Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, Resource, WEnum,
+    },
+    utils::{Physical, Size},
+};
+use tracing::trace;
+
+use crate::window::window_state::WindowId;
+
+use self::generated::{
+    capture_source::{
+        ext_foreign_toplevel_image_capture_source_manager_v1::{
+            self, ExtForeignToplevelImageCaptureSourceManagerV1,
+        },
+        ext_image_capture_source_v1::{self, ExtImageCaptureSourceV1},
+        ext_output_image_capture_source_manager_v1::{self, ExtOutputImageCaptureSourceManagerV1},
+    },
+    copy_capture::{
+        ext_image_copy_capture_cursor_session_v1::{self, ExtImageCopyCaptureCursorSessionV1},
+        ext_image_copy_capture_frame_v1::{self, ExtImageCopyCaptureFrameV1},
+        ext_image_copy_capture_manager_v1::{self, ExtImageCopyCaptureManagerV1},
+        ext_image_copy_capture_session_v1::{self, ExtImageCopyCaptureSessionV1},
+    },
+};
+
+#[allow(missing_docs)]
+pub mod generated {
+    pub mod capture_source {
+        #![allow(dead_code, non_camel_case_types, unused_unsafe, unused_variables)]
+        #![allow(non_upper_case_globals, non_snake_case, unused_imports)]
+        #![allow(clippy::all)]
+
+        use smithay::reexports::wayland_protocols::ext::foreign_toplevel_list::v1::server::*;
+        use smithay::reexports::wayland_server;
+        use smithay::reexports::wayland_server::protocol::*;
+
+        pub mod __interfaces {
+            use smithay::reexports::wayland_protocols::ext::foreign_toplevel_list::v1::server::__interfaces::*;
+            use smithay::reexports::wayland_server::protocol::__interfaces::*;
+            wayland_scanner::generate_interfaces!(
+                "resources/protocols/ext-image-capture-source-v1.xml"
+            );
+        }
+        use self::__interfaces::*;
+
+        wayland_scanner::generate_server_code!(
+            "resources/protocols/ext-image-capture-source-v1.xml"
+        );
+    }
+
+    pub mod copy_capture {
+        #![allow(dead_code, non_camel_case_types, unused_unsafe, unused_variables)]
+        #![allow(non_upper_case_globals, non_snake_case, unused_imports)]
+        #![allow(clippy::all)]
+
+        use super::capture_source::*;
+        use smithay::reexports::wayland_server;
+        use smithay::reexports::wayland_server::protocol::*;
+
+        pub mod __interfaces {
+            use super::super::capture_source::__interfaces::*;
+            use smithay::reexports::wayland_server::protocol::__interfaces::*;
+            wayland_scanner::generate_interfaces!(
+                "resources/protocols/ext-image-copy-capture-v1.xml"
+            );
+        }
+        use self::__interfaces::*;
+
+        wayland_scanner::generate_server_code!("resources/protocols/ext-image-copy-capture-v1.xml");
+    }
+}
+
+const VERSION: u32 = 1;
+
+/// The shm formats captured frames can be copied into.
+pub const SHM_FORMATS: [wl_shm::Format; 2] = [wl_shm::Format::Argb8888, wl_shm::Format::Xrgb8888];
+
+/// Something a client can capture.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImageCaptureSource {
+    Output(Output),
+    Toplevel(WindowId),
+    /// The source's output or toplevel was already gone when it was created.
+    Invalid,
+}
+
+pub struct ImageCopyCaptureState;
+
+pub struct ImageCopyCaptureGlobalData {
+    filter: Box<dyn Fn(&Client) -> bool + Send + Sync>,
+}
+
+impl ImageCopyCaptureState {
+    pub fn new<D, F>(display: &DisplayHandle, filter: F) -> Self
+    where
+        D: GlobalDispatch<ExtOutputImageCaptureSourceManagerV1, ImageCopyCaptureGlobalData>
+            + GlobalDispatch<
+                ExtForeignToplevelImageCaptureSourceManagerV1,
+                ImageCopyCaptureGlobalData,
+            > + GlobalDispatch<ExtImageCopyCaptureManagerV1, ImageCopyCaptureGlobalData>
+            + Dispatch<ExtOutputImageCaptureSourceManagerV1, ()>
+            + Dispatch<ExtForeignToplevelImageCaptureSourceManagerV1, ()>
+            + Dispatch<ExtImageCaptureSourceV1, ImageCaptureSource>
+            + Dispatch<ExtImageCopyCaptureManagerV1, ()>
+            + Dispatch<ExtImageCopyCaptureSessionV1, ImageCopyCaptureSessionData>
+            + Dispatch<ExtImageCopyCaptureCursorSessionV1, AtomicBool>
+            + Dispatch<ExtImageCopyCaptureFrameV1, ImageCopyCaptureFrameData>
+            + ImageCopyCaptureHandler
+            + 'static,
+        F: Fn(&Client) -> bool + Send + Sync + Clone + 'static,
+    {
+        display.create_global::<D, ExtOutputImageCaptureSourceManagerV1, _>(
+            VERSION,
+            ImageCopyCaptureGlobalData {
+                filter: Box::new(filter.clone()),
+            },
+        );
+        display.create_global::<D, ExtForeignToplevelImageCaptureSourceManagerV1, _>(
+            VERSION,
+            ImageCopyCaptureGlobalData {
+                filter: Box::new(filter.clone()),
+            },
+        );
+        display.create_global::<D, ExtImageCopyCaptureManagerV1, _>(
+            VERSION,
+            ImageCopyCaptureGlobalData {
+                filter: Box::new(filter),
+            },
+        );
+        Self
+    }
+}
+
+pub trait ImageCopyCaptureHandler {
+    /// Get the size frames captured from `source` currently have,
+    /// or `None` if it can't be captured anymore.
+    fn capture_source_size(&mut self, source: &ImageCaptureSource) -> Option<Size<i32, Physical>>;
+
+    /// Capture a frame into its attached buffer.
+    fn capture_frame(&mut self, frame: ImageCopyFrame);
+}
+
+pub struct ImageCopyCaptureSessionData {
+    /// `None` for the sessions of cursor sessions, which never produce frames.
+    source: Option<ImageCaptureSource>,
+    paint_cursors: bool,
+    /// The buffer size last advertised to the client.
+    size: Mutex<Size<i32, Physical>>,
+    has_frame: AtomicBool,
+}
+
+pub struct ImageCopyCaptureFrameData {
+    session: ExtImageCopyCaptureSessionV1,
+    buffer: Mutex<Option<WlBuffer>>,
+    captured: AtomicBool,
+}
+
+/// Send the buffer constraints of a session for frames of the given size.
+fn send_constraints(session: &ExtImageCopyCaptureSessionV1, size: Size<i32, Physical>) {
+    session.buffer_size(size.w as u32, size.h as u32);
+    for format in SHM_FORMATS {
+        session.shm_format(format);
+    }
+    session.done();
+}
+
+impl<D, M> GlobalDispatch<M, ImageCopyCaptureGlobalData, D> for ImageCopyCaptureState
+where
+    M: Resource,
+    D: GlobalDispatch<M, ImageCopyCaptureGlobalData> + Dispatch<M, ()> + 'static,
+{
+    fn bind(
+        _state: &mut D,
+        _handle: &DisplayHandle,
+        _client: &Client,
+        resource: wayland_server::New<M>,
+        _global_data: &ImageCopyCaptureGlobalData,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        data_init.init(resource, ());
+    }
+
+    fn can_view(client: Client, global_data: &ImageCopyCaptureGlobalData) -> bool {
+        (global_data.filter)(&client)
+    }
+}
+
+impl<D> Dispatch<ExtOutputImageCaptureSourceManagerV1, (), D> for ImageCopyCaptureState
+where
+    D: Dispatch<ExtOutputImageCaptureSourceManagerV1, ()>
+        + Dispatch<ExtImageCaptureSourceV1, ImageCaptureSource>
+        + 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _resource: &ExtOutputImageCaptureSourceManagerV1,
+        request: <ExtOutputImageCaptureSourceManagerV1 as Resource>::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            ext_output_image_capture_source_manager_v1::Request::CreateSource {
+                source,
+                output,
+            } => {
+                let source_data = Output::from_resource(&output)
+                    .map_or(ImageCaptureSource::Invalid, ImageCaptureSource::Output);
+                data_init.init(source, source_data);
+            }
+            ext_output_image_capture_source_manager_v1::Request::Destroy => (),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<ExtForeignToplevelImageCaptureSourceManagerV1, (), D> for ImageCopyCaptureState
+where
+    D: Dispatch<ExtForeignToplevelImageCaptureSourceManagerV1, ()>
+        + Dispatch<ExtImageCaptureSourceV1, ImageCaptureSource>
+        + 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _resource: &ExtForeignToplevelImageCaptureSourceManagerV1,
+        request: <ExtForeignToplevelImageCaptureSourceManagerV1 as Resource>::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            ext_foreign_toplevel_image_capture_source_manager_v1::Request::CreateSource {
+                source,
+                toplevel_handle,
+            } => {
+                let source_data = toplevel_handle
+                    .data::<WindowId>()
+                    .map_or(ImageCaptureSource::Invalid, |id| {
+                        ImageCaptureSource::Toplevel(*id)
+                    });
+                data_init.init(source, source_data);
+            }
+            ext_foreign_toplevel_image_capture_source_manager_v1::Request::Destroy => (),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<ExtImageCaptureSourceV1, ImageCaptureSource, D> for ImageCopyCaptureState
+where
+    D: Dispatch<ExtImageCaptureSourceV1, ImageCaptureSource> + 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _resource: &ExtImageCaptureSourceV1,
+        request: <ExtImageCaptureSourceV1 as Resource>::Request,
+        _data: &ImageCaptureSource,
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            ext_image_capture_source_v1::Request::Destroy => (),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<ExtImageCopyCaptureManagerV1, (), D> for ImageCopyCaptureState
+where
+    D: Dispatch<ExtImageCopyCaptureManagerV1, ()>
+        + Dispatch<ExtImageCopyCaptureSessionV1, ImageCopyCaptureSessionData>
+        + Dispatch<ExtImageCopyCaptureCursorSessionV1, AtomicBool>
+        + ImageCopyCaptureHandler
+        + 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        resource: &ExtImageCopyCaptureManagerV1,
+        request: <ExtImageCopyCaptureManagerV1 as Resource>::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            ext_image_copy_capture_manager_v1::Request::CreateSession {
+                session,
+                source,
+                options,
+            } => {
+                let paint_cursors = match options {
+                    WEnum::Value(options) => {
+                        options.contains(ext_image_copy_capture_manager_v1::Options::PaintCursors)
+                    }
+                    WEnum::Unknown(_) => {
+                        resource.post_error(
+                            ext_image_copy_capture_manager_v1::Error::InvalidOption,
+                            "invalid options",
+                        );
+                        return;
+                    }
+                };
+
+                let source = source
+                    .data::<ImageCaptureSource>()
+                    .cloned()
+                    .unwrap_or(ImageCaptureSource::Invalid);
+                let size = state.capture_source_size(&source);
+
+                let session = data_init.init(
+                    session,
+                    ImageCopyCaptureSessionData {
+                        source: Some(source),
+                        paint_cursors,
+                        size: Mutex::new(size.unwrap_or_default()),
+                        has_frame: AtomicBool::new(false),
+                    },
+                );
+
+                match size {
+                    Some(size) => send_constraints(&session, size),
+                    None => {
+                        trace!("Image copy capture client requested an invalid source");
+                        session.stopped();
+                    }
+                }
+            }
+            ext_image_copy_capture_manager_v1::Request::CreatePointerCursorSession {
+                session,
+                ..
+            } => {
+                data_init.init(session, AtomicBool::new(false));
+            }
+            ext_image_copy_capture_manager_v1::Request::Destroy => (),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<ExtImageCopyCaptureCursorSessionV1, AtomicBool, D> for ImageCopyCaptureState
+where
+    D: Dispatch<ExtImageCopyCaptureCursorSessionV1, AtomicBool>
+        + Dispatch<ExtImageCopyCaptureSessionV1, ImageCopyCaptureSessionData>
+        + 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        resource: &ExtImageCopyCaptureCursorSessionV1,
+        request: <ExtImageCopyCaptureCursorSessionV1 as Resource>::Request,
+        has_session: &AtomicBool,
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            ext_image_copy_capture_cursor_session_v1::Request::GetCaptureSession { session } => {
+                if has_session.swap(true, Ordering::SeqCst) {
+                    resource.post_error(
+                        ext_image_copy_capture_cursor_session_v1::Error::DuplicateSession,
+                        "get_capture_session was already sent",
+                    );
+                    return;
+                }
+
+                let session = data_init.init(
+                    session,
+                    ImageCopyCaptureSessionData {
+                        source: None,
+                        paint_cursors: false,
+                        size: Mutex::new(Size::default()),
+                        has_frame: AtomicBool::new(false),
+                    },
+                );
+                session.stopped();
+            }
+            ext_image_copy_capture_cursor_session_v1::Request::Destroy => (),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<ExtImageCopyCaptureSessionV1, ImageCopyCaptureSessionData, D>
+    for ImageCopyCaptureState
+where
+    D: Dispatch<ExtImageCopyCaptureSessionV1, ImageCopyCaptureSessionData>
+        + Dispatch<ExtImageCopyCaptureFrameV1, ImageCopyCaptureFrameData>
+        + 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        resource: &ExtImageCopyCaptureSessionV1,
+        request: <ExtImageCopyCaptureSessionV1 as Resource>::Request,
+        data: &ImageCopyCaptureSessionData,
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            ext_image_copy_capture_session_v1::Request::CreateFrame { frame } => {
+                if data.has_frame.swap(true, Ordering::SeqCst) {
+                    resource.post_error(
+                        ext_image_copy_capture_session_v1::Error::DuplicateFrame,
+                        "the previous frame was not destroyed",
+                    );
+                    return;
+                }
+
+                data_init.init(
+                    frame,
+                    ImageCopyCaptureFrameData {
+                        session: resource.clone(),
+                        buffer: Mutex::new(None),
+                        captured: AtomicBool::new(false),
+                    },
+                );
+            }
+            ext_image_copy_capture_session_v1::Request::Destroy => (),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<ExtImageCopyCaptureFrameV1, ImageCopyCaptureFrameData, D> for ImageCopyCaptureState
+where
+    D: Dispatch<ExtImageCopyCaptureFrameV1, ImageCopyCaptureFrameData>
+        + ImageCopyCaptureHandler
+        + 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        resource: &ExtImageCopyCaptureFrameV1,
+        request: <ExtImageCopyCaptureFrameV1 as Resource>::Request,
+        data: &ImageCopyCaptureFrameData,
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        if matches!(request, ext_image_copy_capture_frame_v1::Request::Destroy) {
+            return;
+        }
+
+        if data.captured.load(Ordering::SeqCst) {
+            resource.post_error(
+                ext_image_copy_capture_frame_v1::Error::AlreadyCaptured,
+                "capture was already requested",
+            );
+            return;
+        }
+
+        match request {
+            ext_image_copy_capture_frame_v1::Request::AttachBuffer { buffer } => {
+                *data.buffer.lock().unwrap() = Some(buffer);
+            }
+            ext_image_copy_capture_frame_v1::Request::DamageBuffer {
+                x,
+                y,
+                width,
+                height,
+            } => {
+                // Frames are always copied in full, so buffer damage is only validated
+                if x < 0 || y < 0 || width <= 0 || height <= 0 {
+                    resource.post_error(
+                        ext_image_copy_capture_frame_v1::Error::InvalidBufferDamage,
+                        "invalid buffer damage",
+                    );
+                }
+            }
+            ext_image_copy_capture_frame_v1::Request::Capture => {
+                let Some(buffer) = data.buffer.lock().unwrap().clone() else {
+                    resource.post_error(
+                        ext_image_copy_capture_frame_v1::Error::NoBuffer,
+                        "no buffer was attached",
+                    );
+                    return;
+                };
+
+                data.captured.store(true, Ordering::SeqCst);
+
+                let Some(session_data) = data.session.data::<ImageCopyCaptureSessionData>() else {
+                    resource.failed(ext_image_copy_capture_frame_v1::FailureReason::Unknown);
+                    return;
+                };
+
+                let Some(source) = session_data.source.clone() else {
+                    resource.failed(ext_image_copy_capture_frame_v1::FailureReason::Stopped);
+                    return;
+                };
+
+                state.capture_frame(ImageCopyFrame {
+                    frame: resource.clone(),
+                    session: data.session.clone(),
+                    buffer,
+                    source,
+                    paint_cursors: session_data.paint_cursors,
+                    size: *session_data.size.lock().unwrap(),
+                    finished: false,
+                });
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(
+        _state: &mut D,
+        _client: ClientId,
+        _resource: &ExtImageCopyCaptureFrameV1,
+        data: &ImageCopyCaptureFrameData,
+    ) {
+        if let Some(session_data) = data.session.data::<ImageCopyCaptureSessionData>() {
+            session_data.has_frame.store(false, Ordering::SeqCst);
+        }
+    }
+}
+
+#[allow(missing_docs)]
+#[macro_export]
+macro_rules! delegate_image_copy_capture {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        smithay::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::protocol::image_copy_capture::generated::capture_source::ext_output_image_capture_source_manager_v1::ExtOutputImageCaptureSourceManagerV1: $crate::protocol::image_copy_capture::ImageCopyCaptureGlobalData
+        ] => $crate::protocol::image_copy_capture::ImageCopyCaptureState);
+
+        smithay::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::protocol::image_copy_capture::generated::capture_source::ext_foreign_toplevel_image_capture_source_manager_v1::ExtForeignToplevelImageCaptureSourceManagerV1: $crate::protocol::image_copy_capture::ImageCopyCaptureGlobalData
+        ] => $crate::protocol::image_copy_capture::ImageCopyCaptureState);
+
+        smithay::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::protocol::image_copy_capture::generated::copy_capture::ext_image_copy_capture_manager_v1::ExtImageCopyCaptureManagerV1: $crate::protocol::image_copy_capture::ImageCopyCaptureGlobalData
+        ] => $crate::protocol::image_copy_capture::ImageCopyCaptureState);
+
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::protocol::image_copy_capture::generated::capture_source::ext_output_image_capture_source_manager_v1::ExtOutputImageCaptureSourceManagerV1: ()
+        ] => $crate::protocol::image_copy_capture::ImageCopyCaptureState);
+
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::protocol::image_copy_capture::generated::capture_source::ext_foreign_toplevel_image_capture_source_manager_v1::ExtForeignToplevelImageCaptureSourceManagerV1: ()
+        ] => $crate::protocol::image_copy_capture::ImageCopyCaptureState);
+
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::protocol::image_copy_capture::generated::capture_source::ext_image_capture_source_v1::ExtImageCaptureSourceV1: $crate::protocol::image_copy_capture::ImageCaptureSource
+        ] => $crate::protocol::image_copy_capture::ImageCopyCaptureState);
+
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::protocol::image_copy_capture::generated::copy_capture::ext_image_copy_capture_manager_v1::ExtImageCopyCaptureManagerV1: ()
+        ] => $crate::protocol::image_copy_capture::ImageCopyCaptureState);
+
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::protocol::image_copy_capture::generated::copy_capture::ext_image_copy_capture_session_v1::ExtImageCopyCaptureSessionV1: $crate::protocol::image_copy_capture::ImageCopyCaptureSessionData
+        ] => $crate::protocol::image_copy_capture::ImageCopyCaptureState);
+
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::protocol::image_copy_capture::generated::copy_capture::ext_image_copy_capture_cursor_session_v1::ExtImageCopyCaptureCursorSessionV1: std::sync::atomic::AtomicBool
+        ] => $crate::protocol::image_copy_capture::ImageCopyCaptureState);
+
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::protocol::image_copy_capture::generated::copy_capture::ext_image_copy_capture_frame_v1::ExtImageCopyCaptureFrameV1: $crate::protocol::image_copy_capture::ImageCopyCaptureFrameData
+        ] => $crate::protocol::image_copy_capture::ImageCopyCaptureState);
+    };
+}
+
+/// A frame waiting to be captured.
+///
+/// If it is dropped without being submitted, the client is told that the capture failed.
+#[derive(Debug)]
+pub struct ImageCopyFrame {
+    frame: ExtImageCopyCaptureFrameV1,
+    session: ExtImageCopyCaptureSessionV1,
+    buffer: WlBuffer,
+    source: ImageCaptureSource,
+    paint_cursors: bool,
+    size: Size<i32, Physical>,
+    finished: bool,
+}
+
+impl Drop for ImageCopyFrame {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.frame
+                .failed(ext_image_copy_capture_frame_v1::FailureReason::Unknown);
+        }
+    }
+}
+
+impl ImageCopyFrame {
+    pub fn buffer(&self) -> &WlBuffer {
+        &self.buffer
+    }
+
+    pub fn source(&self) -> &ImageCaptureSource {
+        &self.source
+    }
+
+    pub fn paint_cursors(&self) -> bool {
+        self.paint_cursors
+    }
+
+    /// Get the buffer size last advertised to the client.
+    pub fn size(&self) -> Size<i32, Physical> {
+        self.size
+    }
+
+    /// Tell the client that the source changed size and fail this frame.
+    pub fn resize(mut self, size: Size<i32, Physical>) {
+        if let Some(session_data) = self.session.data::<ImageCopyCaptureSessionData>() {
+            *session_data.size.lock().unwrap() = size;
+        }
+        send_constraints(&self.session, size);
+
+        self.frame
+            .failed(ext_image_copy_capture_frame_v1::FailureReason::BufferConstraints);
+        self.finished = true;
+    }
+
+    /// Tell the client that the source can't be captured anymore and fail this frame.
+    pub fn stop(mut self) {
+        self.session.stopped();
+        self.frame
+            .failed(ext_image_copy_capture_frame_v1::FailureReason::Stopped);
+        self.finished = true;
+    }
+
+    /// Fail this frame because the attached buffer doesn't fit the buffer constraints.
+    pub fn reject_buffer(mut self) {
+        self.frame
+            .failed(ext_image_copy_capture_frame_v1::FailureReason::BufferConstraints);
+        self.finished = true;
+    }
+
+    /// Submit the copied content.
+    pub fn submit(mut self) {
+        self.frame.transform(Transform::Normal);
+        self.frame.damage(0, 0, self.size.w, self.size.h);
+
+        let time = UNIX_EPOCH
+            .elapsed()
+            .expect("failed to get time since UNIX_EPOCH");
+        let tv_sec_hi = (time.as_secs() >> 32) as u32;
+        let tv_sec_lo = (time.as_secs() & 0xFFFFFFFF) as u32;
+        self.frame
+            .presentation_time(tv_sec_hi, tv_sec_lo, time.subsec_nanos());
+
+        self.frame.ready();
+        self.finished = true;
+    }
+}
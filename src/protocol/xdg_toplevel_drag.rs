@@ -0,0 +1,246 @@
+//! An implementation of `xdg-toplevel-drag-v1`.
+//!
+//! This only keeps track of which toplevel is attached to which drag-and-drop data source.
+//! Moving the toplevel along with the pointer is handled by the compositor.
+
+use std::sync::Mutex;
+
+use smithay::{
+    reexports::{
+        wayland_protocols::xdg::shell::server::xdg_toplevel::XdgToplevel,
+        wayland_server::{
+            self, backend::ClientId, protocol::wl_data_source::WlDataSource, Client, DataInit,
+            Dispatch, DisplayHandle, GlobalDispatch, Resource,
+        },
+    },
+    utils::{Logical, Point},
+};
+
+use self::generated::{
+    xdg_toplevel_drag_manager_v1::{self, XdgToplevelDragManagerV1},
+    xdg_toplevel_drag_v1::{self, XdgToplevelDragV1},
+};
+
+#[allow(missing_docs)]
+pub mod generated {
+    #![allow(dead_code, non_camel_case_types, unused_unsafe, unused_variables)]
+    #![allow(non_upper_case_globals, non_snake_case, unused_imports)]
+    #![allow(clippy::all)]
+
+    use smithay::reexports::wayland_protocols::xdg::shell::server::*;
+    use smithay::reexports::wayland_server;
+    use smithay::reexports::wayland_server::protocol::*;
+
+    pub mod __interfaces {
+        use smithay::reexports::wayland_protocols::xdg::shell::server::__interfaces::*;
+        use smithay::reexports::wayland_server::protocol::__interfaces::*;
+        wayland_scanner::generate_interfaces!("resources/protocols/xdg-toplevel-drag-v1.xml");
+    }
+    use self::__interfaces::*;
+
+    wayland_scanner::generate_server_code!("resources/protocols/xdg-toplevel-drag-v1.xml");
+}
+
+const VERSION: u32 = 1;
+
+pub struct XdgToplevelDragManagerState {
+    drags: Vec<XdgToplevelDragV1>,
+}
+
+pub struct XdgToplevelDragManagerGlobalData {
+    filter: Box<dyn Fn(&Client) -> bool + Send + Sync>,
+}
+
+pub struct XdgToplevelDragData {
+    data_source: WlDataSource,
+    /// The attached toplevel along with its offset from the pointer.
+    toplevel: Mutex<Option<(XdgToplevel, Point<i32, Logical>)>>,
+}
+
+impl XdgToplevelDragManagerState {
+    pub fn new<D, F>(display: &DisplayHandle, filter: F) -> Self
+    where
+        D: GlobalDispatch<XdgToplevelDragManagerV1, XdgToplevelDragManagerGlobalData>
+            + Dispatch<XdgToplevelDragManagerV1, ()>
+            + Dispatch<XdgToplevelDragV1, XdgToplevelDragData>
+            + XdgToplevelDragHandler
+            + 'static,
+        F: Fn(&Client) -> bool + Send + Sync + 'static,
+    {
+        let global_data = XdgToplevelDragManagerGlobalData {
+            filter: Box::new(filter),
+        };
+        display.create_global::<D, XdgToplevelDragManagerV1, _>(VERSION, global_data);
+        Self { drags: Vec::new() }
+    }
+
+    /// Get the toplevel attached to the drag started with the given data source,
+    /// along with its offset from the pointer.
+    pub fn attached_toplevel(
+        &self,
+        data_source: &WlDataSource,
+    ) -> Option<(XdgToplevel, Point<i32, Logical>)> {
+        self.drags.iter().find_map(|drag| {
+            let data = drag.data::<XdgToplevelDragData>()?;
+            if &data.data_source != data_source {
+                return None;
+            }
+            data.toplevel
+                .lock()
+                .expect("toplevel drag mutex was poisoned")
+                .clone()
+                .filter(|(toplevel, _)| toplevel.is_alive())
+        })
+    }
+}
+
+pub trait XdgToplevelDragHandler {
+    fn xdg_toplevel_drag_manager_state(&mut self) -> &mut XdgToplevelDragManagerState;
+}
+
+impl<D> GlobalDispatch<XdgToplevelDragManagerV1, XdgToplevelDragManagerGlobalData, D>
+    for XdgToplevelDragManagerState
+where
+    D: GlobalDispatch<XdgToplevelDragManagerV1, XdgToplevelDragManagerGlobalData>
+        + Dispatch<XdgToplevelDragManagerV1, ()>
+        + Dispatch<XdgToplevelDragV1, XdgToplevelDragData>
+        + XdgToplevelDragHandler
+        + 'static,
+{
+    fn bind(
+        _state: &mut D,
+        _handle: &DisplayHandle,
+        _client: &Client,
+        resource: wayland_server::New<XdgToplevelDragManagerV1>,
+        _global_data: &XdgToplevelDragManagerGlobalData,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        data_init.init(resource, ());
+    }
+
+    fn can_view(client: Client, global_data: &XdgToplevelDragManagerGlobalData) -> bool {
+        (global_data.filter)(&client)
+    }
+}
+
+impl<D> Dispatch<XdgToplevelDragManagerV1, (), D> for XdgToplevelDragManagerState
+where
+    D: Dispatch<XdgToplevelDragManagerV1, ()>
+        + Dispatch<XdgToplevelDragV1, XdgToplevelDragData>
+        + XdgToplevelDragHandler
+        + 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        manager: &XdgToplevelDragManagerV1,
+        request: <XdgToplevelDragManagerV1 as Resource>::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        let (id, data_source) = match request {
+            xdg_toplevel_drag_manager_v1::Request::GetXdgToplevelDrag { id, data_source } => {
+                (id, data_source)
+            }
+            xdg_toplevel_drag_manager_v1::Request::Destroy => return,
+            _ => unreachable!(),
+        };
+
+        let drag_state = state.xdg_toplevel_drag_manager_state();
+
+        if drag_state.drags.iter().any(|drag| {
+            drag.data::<XdgToplevelDragData>()
+                .is_some_and(|data| data.data_source == data_source)
+        }) {
+            manager.post_error(
+                xdg_toplevel_drag_manager_v1::Error::InvalidSource,
+                "data source is already used for a toplevel drag",
+            );
+            return;
+        }
+
+        let drag = data_init.init(
+            id,
+            XdgToplevelDragData {
+                data_source,
+                toplevel: Mutex::new(None),
+            },
+        );
+
+        drag_state.drags.push(drag);
+    }
+}
+
+impl<D> Dispatch<XdgToplevelDragV1, XdgToplevelDragData, D> for XdgToplevelDragManagerState
+where
+    D: Dispatch<XdgToplevelDragV1, XdgToplevelDragData> + XdgToplevelDragHandler + 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        resource: &XdgToplevelDragV1,
+        request: <XdgToplevelDragV1 as Resource>::Request,
+        data: &XdgToplevelDragData,
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            xdg_toplevel_drag_v1::Request::Attach {
+                toplevel,
+                x_offset,
+                y_offset,
+            } => {
+                let mut attached = data
+                    .toplevel
+                    .lock()
+                    .expect("toplevel drag mutex was poisoned");
+
+                if attached
+                    .as_ref()
+                    .is_some_and(|(toplevel, _)| toplevel.is_alive())
+                {
+                    resource.post_error(
+                        xdg_toplevel_drag_v1::Error::ToplevelAttached,
+                        "a toplevel is already attached to this drag",
+                    );
+                    return;
+                }
+
+                *attached = Some((toplevel, Point::from((x_offset, y_offset))));
+            }
+            xdg_toplevel_drag_v1::Request::Destroy => (),
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(
+        state: &mut D,
+        _client: ClientId,
+        resource: &XdgToplevelDragV1,
+        _data: &XdgToplevelDragData,
+    ) {
+        state
+            .xdg_toplevel_drag_manager_state()
+            .drags
+            .retain(|drag| drag != resource);
+    }
+}
+
+#[allow(missing_docs)]
+#[macro_export]
+macro_rules! delegate_xdg_toplevel_drag {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        smithay::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::protocol::xdg_toplevel_drag::generated::xdg_toplevel_drag_manager_v1::XdgToplevelDragManagerV1: $crate::protocol::xdg_toplevel_drag::XdgToplevelDragManagerGlobalData
+        ] => $crate::protocol::xdg_toplevel_drag::XdgToplevelDragManagerState);
+
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::protocol::xdg_toplevel_drag::generated::xdg_toplevel_drag_manager_v1::XdgToplevelDragManagerV1: ()
+        ] => $crate::protocol::xdg_toplevel_drag::XdgToplevelDragManagerState);
+
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::protocol::xdg_toplevel_drag::generated::xdg_toplevel_drag_v1::XdgToplevelDragV1: $crate::protocol::xdg_toplevel_drag::XdgToplevelDragData
+        ] => $crate::protocol::xdg_toplevel_drag::XdgToplevelDragManagerState);
+    };
+}
@@ -0,0 +1,275 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Screen recording.
+//!
+//! A recording renders its target offscreen at a fixed framerate, like a screenshot, and pipes
+//! the raw frames into an `ffmpeg` process that encodes them to a file. The output being
+//! recorded shows a red square in its top-right corner, which isn't part of the recording.
+
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::mpsc::{self, Receiver, SyncSender, TrySendError},
+    time::Duration,
+};
+
+use anyhow::{anyhow, ensure, Context};
+use smithay::{
+    backend::renderer::{
+        element::{self, solid::SolidColorRenderElement, Id},
+        utils::CommitCounter,
+    },
+    output::Output,
+    reexports::calloop::{
+        timer::{TimeoutAction, Timer},
+        RegistrationToken,
+    },
+    utils::{Logical, Point, Rectangle},
+};
+use tracing::{debug, error, info, warn};
+
+use crate::{
+    screenshot::{region_output, ScreenshotTarget},
+    state::{Pinnacle, State},
+};
+
+/// The framerate recordings use if none is given.
+pub const DEFAULT_FRAMERATE: u32 = 30;
+
+/// How many frames can wait to be encoded before new frames are dropped.
+const FRAME_QUEUE_LEN: usize = 8;
+
+/// The size of the recording indicator, in logical pixels.
+const INDICATOR_SIZE: i32 = 12;
+/// The distance of the recording indicator from the output's top-right corner.
+const INDICATOR_MARGIN: i32 = 8;
+
+/// An ongoing recording.
+#[derive(Debug)]
+pub struct Recording {
+    target: ScreenshotTarget,
+    include_cursor: bool,
+    /// The size of every frame. Recordings stop if their target changes size.
+    size: (u32, u32),
+    /// Sends frames to the thread writing them to `ffmpeg`.
+    frames: SyncSender<Vec<u8>>,
+    timer: RegistrationToken,
+    indicator_id: Id,
+}
+
+impl Recording {
+    /// The output this recording's indicator is shown on.
+    fn output(&self, pinnacle: &Pinnacle) -> Option<Output> {
+        match &self.target {
+            ScreenshotTarget::Output(output) => Some(output.clone()),
+            ScreenshotTarget::Region(region) => region_output(pinnacle, *region),
+            ScreenshotTarget::Window(window) | ScreenshotTarget::WindowThumbnail { window, .. } => {
+                window.output(pinnacle)
+            }
+        }
+    }
+}
+
+/// Get the recording indicator for an output, if it is being recorded.
+pub fn recording_indicator_element(
+    pinnacle: &Pinnacle,
+    output: &Output,
+) -> Option<SolidColorRenderElement> {
+    let recording = pinnacle.recording.as_ref()?;
+    if recording.output(pinnacle).as_ref() != Some(output) {
+        return None;
+    }
+
+    let output_size = pinnacle.space.output_geometry(output)?.size;
+    let scale = output.current_scale().fractional_scale();
+
+    let loc = Point::<i32, Logical>::from((
+        output_size.w - INDICATOR_MARGIN - INDICATOR_SIZE,
+        INDICATOR_MARGIN,
+    ));
+    let geometry = Rectangle::from_loc_and_size(loc, (INDICATOR_SIZE, INDICATOR_SIZE))
+        .to_physical_precise_round(scale);
+
+    Some(SolidColorRenderElement::new(
+        recording.indicator_id.clone(),
+        geometry,
+        CommitCounter::default(),
+        [0.9, 0.1, 0.1, 1.0],
+        element::Kind::Unspecified,
+    ))
+}
+
+/// Spawn `ffmpeg` to encode raw RGBA frames of the given size to `path`.
+///
+/// The container and codec are chosen from the file extension.
+fn spawn_ffmpeg(path: &Path, (width, height): (u32, u32), framerate: u32) -> anyhow::Result<Child> {
+    Command::new("ffmpeg")
+        .args(["-y", "-loglevel", "error"])
+        .args(["-f", "rawvideo", "-pixel_format", "rgba"])
+        .args(["-video_size", &format!("{width}x{height}")])
+        .args(["-framerate", &framerate.to_string()])
+        .args(["-i", "-"])
+        // Most encoders need even dimensions for yuv420p
+        .args(["-vf", "pad=ceil(iw/2)*2:ceil(ih/2)*2", "-pix_fmt", "yuv420p"])
+        .arg(path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .context("failed to spawn ffmpeg; is it installed?")
+}
+
+/// Write frames to `ffmpeg` until the recording stops, then wait for it to finish the file.
+fn write_frames(frames: Receiver<Vec<u8>>, mut stdin: ChildStdin, mut ffmpeg: Child) {
+    for frame in frames {
+        if let Err(err) = stdin.write_all(&frame) {
+            error!("Failed to write frame to ffmpeg: {err}");
+            break;
+        }
+    }
+
+    // Closing stdin tells ffmpeg that the recording is over
+    drop(stdin);
+
+    match ffmpeg.wait() {
+        Ok(status) if status.success() => info!("Recording finished"),
+        Ok(status) => warn!("ffmpeg exited with {status}"),
+        Err(err) => error!("Failed to wait for ffmpeg: {err}"),
+    }
+}
+
+impl State {
+    /// Start recording `target` to a video file at `path`.
+    ///
+    /// Only one recording can run at a time.
+    pub fn start_recording(
+        &mut self,
+        target: ScreenshotTarget,
+        path: PathBuf,
+        framerate: u32,
+        include_cursor: bool,
+    ) -> anyhow::Result<()> {
+        ensure!(self.pinnacle.recording.is_none(), "already recording");
+        ensure!(framerate > 0, "framerate must be positive");
+
+        let first_frame = self.screenshot(&target, include_cursor)?;
+        let size = first_frame.size();
+
+        let mut ffmpeg = spawn_ffmpeg(&path, size, framerate)?;
+        let stdin = ffmpeg.stdin.take().context("ffmpeg has no stdin")?;
+
+        let (frames, receiver) = mpsc::sync_channel(FRAME_QUEUE_LEN);
+        std::thread::Builder::new()
+            .name("recording".to_string())
+            .spawn(move || write_frames(receiver, stdin, ffmpeg))?;
+
+        // This can't fail, the queue is empty
+        let _ = frames.try_send(first_frame.into_raw());
+
+        let interval = Duration::from_secs_f64(1.0 / f64::from(framerate));
+        let timer = self
+            .pinnacle
+            .loop_handle
+            .insert_source(Timer::from_duration(interval), move |_, _, state| {
+                if state.record_frame() {
+                    TimeoutAction::ToDuration(interval)
+                } else {
+                    state.finish_recording();
+                    TimeoutAction::Drop
+                }
+            })
+            .map_err(|err| anyhow!("failed to insert recording timer: {err}"))?;
+
+        self.pinnacle.recording = Some(Recording {
+            target,
+            include_cursor,
+            size,
+            frames,
+            timer,
+            indicator_id: Id::new(),
+        });
+
+        if let Some(output) = self
+            .pinnacle
+            .recording
+            .as_ref()
+            .and_then(|rec| rec.output(&self.pinnacle))
+        {
+            self.schedule_render(&output);
+        }
+
+        info!("Started recording to {}", path.display());
+
+        Ok(())
+    }
+
+    /// Stop the current recording.
+    ///
+    /// Returns whether there was a recording.
+    pub fn stop_recording(&mut self) -> bool {
+        let Some(recording) = self.finish_recording() else {
+            return false;
+        };
+
+        self.pinnacle.loop_handle.remove(recording.timer);
+        true
+    }
+
+    /// Remove the current recording and its indicator.
+    ///
+    /// The recording's file is finished once all queued frames are encoded.
+    fn finish_recording(&mut self) -> Option<Recording> {
+        let recording = self.pinnacle.recording.take()?;
+
+        if let Some(output) = recording.output(&self.pinnacle) {
+            self.schedule_render(&output);
+        }
+
+        Some(recording)
+    }
+
+    /// Capture a frame of the current recording.
+    ///
+    /// Returns `false` if the recording has to stop.
+    fn record_frame(&mut self) -> bool {
+        let Some(Recording {
+            target,
+            include_cursor,
+            size,
+            ..
+        }) = self.pinnacle.recording.as_ref()
+        else {
+            return false;
+        };
+        let (target, include_cursor, size) = (target.clone(), *include_cursor, *size);
+
+        let frame = match self.screenshot(&target, include_cursor) {
+            Ok(frame) => frame,
+            Err(err) => {
+                warn!("Failed to capture frame, stopping recording: {err}");
+                return false;
+            }
+        };
+
+        if frame.size() != size {
+            warn!("Recorded area changed size, stopping recording");
+            return false;
+        }
+
+        let Some(recording) = self.pinnacle.recording.as_ref() else {
+            return false;
+        };
+
+        match recording.frames.try_send(frame.into_raw()) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                debug!("Encoding is falling behind, dropped a frame");
+                true
+            }
+            Err(TrySendError::Disconnected(_)) => {
+                warn!("ffmpeg stopped, stopping recording");
+                false
+            }
+        }
+    }
+}
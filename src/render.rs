@@ -1,14 +1,19 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use std::{ops::Deref, sync::Mutex};
+use std::sync::{Arc, Mutex};
 
 use smithay::{
     backend::renderer::{
         element::{
-            surface::WaylandSurfaceRenderElement,
+            self,
+            memory::MemoryRenderBufferRenderElement,
+            solid::SolidColorRenderElement,
+            surface::{render_elements_from_surface_tree, WaylandSurfaceRenderElement},
             utils::{CropRenderElement, RelocateRenderElement, RescaleRenderElement},
             AsRenderElements, RenderElementStates, Wrap,
         },
+        gles::{GlesFrame, GlesRenderer, GlesTexProgram},
+        pixman::PixmanRenderer,
         ImportAll, ImportMem, Renderer, Texture,
     },
     desktop::{
@@ -18,25 +23,118 @@ use smithay::{
             surface_presentation_feedback_flags_from_states, surface_primary_scanout_output,
             OutputPresentationFeedback,
         },
-        Space,
+        PopupManager, Space, WindowSurface,
     },
     input::pointer::{CursorImageAttributes, CursorImageStatus},
     output::Output,
     reexports::wayland_server::protocol::wl_surface::WlSurface,
     render_elements,
-    utils::{Logical, Physical, Point, Scale},
-    wayland::{compositor, shell::wlr_layer},
+    utils::{Logical, Physical, Point, Rectangle, Scale},
+    wayland::{compositor, content_type::ContentType, seat::WaylandFocus, shell::wlr_layer},
 };
 
 use crate::{
+    animation::AnimatedGeometry,
     backend::Backend,
+    grab::tile_swap_grab::SWAP_GHOST_ALPHA,
+    protocol::{
+        alpha_modifier::surface_alpha_multiplier,
+        drm_syncobj::{self, DrmReleasePoint},
+    },
     state::{State, WithState},
-    window::WindowElement,
+    window::{unresponsive::UNRESPONSIVE_BRIGHTNESS, WindowElement},
 };
 
-use self::pointer::{PointerElement, PointerRenderElement};
+use self::{
+    blur::{blur_id, blurred_background, BlurElement, BlurSettings, BlurredBackground},
+    color_filter::{color_filter_element, ColorFilterElement},
+    dim::{dim_program, DimElement},
+    group_indicator::group_indicator_elements,
+    pointer::{PointerElement, PointerRenderElement},
+    rounded_corners::{rounded_corner_program, RoundedCornerElement},
+    wallpaper::wallpaper_elements,
+};
 
+pub mod blur;
+pub mod color_filter;
+pub mod damage_debug;
+pub mod dim;
+pub mod edge_tile_preview;
+pub mod group_indicator;
+pub mod monocle_indicator;
+pub mod perf_overlay;
 pub mod pointer;
+pub mod rounded_corners;
+pub mod snap_guides;
+pub mod wallpaper;
+pub mod window_switcher;
+
+/// A renderer that can draw everything pinnacle renders.
+///
+/// Effects that need custom shaders are only drawn when there is a GLES renderer underneath.
+pub trait PinnacleRenderer: Renderer + ImportAll + ImportMem {
+    /// Get the underlying GLES renderer, if any.
+    fn gles_renderer(&mut self) -> Option<&mut GlesRenderer>;
+
+    /// Get the underlying GLES frame of a frame from this renderer, if any.
+    fn gles_frame<'a, 'frame>(
+        frame: &'a mut Self::Frame<'frame>,
+    ) -> Option<&'a mut GlesFrame<'frame>>
+    where
+        Self: 'frame;
+}
+
+impl PinnacleRenderer for GlesRenderer {
+    fn gles_renderer(&mut self) -> Option<&mut GlesRenderer> {
+        Some(self)
+    }
+
+    fn gles_frame<'a, 'frame>(
+        frame: &'a mut Self::Frame<'frame>,
+    ) -> Option<&'a mut GlesFrame<'frame>>
+    where
+        Self: 'frame,
+    {
+        Some(frame)
+    }
+}
+
+impl PinnacleRenderer for PixmanRenderer {
+    fn gles_renderer(&mut self) -> Option<&mut GlesRenderer> {
+        None
+    }
+
+    fn gles_frame<'a, 'frame>(
+        _frame: &'a mut Self::Frame<'frame>,
+    ) -> Option<&'a mut GlesFrame<'frame>>
+    where
+        Self: 'frame,
+    {
+        None
+    }
+}
+
+/// Render settings set by the config.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RenderSettings {
+    /// The corner radius of windows, in logical pixels.
+    pub corner_radius: f64,
+    /// How the background behind windows and layer surfaces is blurred.
+    pub blur: BlurSettings,
+    /// How much unfocused windows are darkened, from 0.0 (not at all) to 1.0 (black).
+    pub dim_inactive: f32,
+    /// Whether windows of clients that stopped answering pings are darkened.
+    pub darken_unresponsive: bool,
+}
+
+/// Developer settings that draw debug information on top of outputs.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DebugSettings {
+    /// Whether every output shows its frame statistics.
+    pub perf_overlay: bool,
+    /// Whether damaged regions flash and render elements are outlined.
+    pub damage_visualization: bool,
+}
 
 render_elements! {
     pub TransformRenderElement<R, E>;
@@ -46,11 +144,19 @@ render_elements! {
 }
 
 render_elements! {
-    pub OutputRenderElement<R, E> where R: ImportAll + ImportMem;
+    pub OutputRenderElement<R, E> where R: PinnacleRenderer;
     Custom = Wrap<E>,
     Surface = WaylandSurfaceRenderElement<R>,
     Pointer = PointerRenderElement<R>,
     Transform = TransformRenderElement<R, E>,
+    RoundedSurface = RoundedCornerElement<WaylandSurfaceRenderElement<R>>,
+    RoundedTransform = RoundedCornerElement<RescaleRenderElement<WaylandSurfaceRenderElement<R>>>,
+    Blur = BlurElement,
+    DimSurface = DimElement<WaylandSurfaceRenderElement<R>>,
+    DimTransform = DimElement<RescaleRenderElement<WaylandSurfaceRenderElement<R>>>,
+    ColorFilter = ColorFilterElement,
+    SolidColor = SolidColorRenderElement,
+    Memory = MemoryRenderBufferRenderElement<R>,
 }
 
 impl<R> AsRenderElements<R> for WindowElement
@@ -67,25 +173,173 @@ where
         scale: Scale<f64>,
         alpha: f32,
     ) -> Vec<C> {
-        self.deref()
-            .render_elements(renderer, location, scale, alpha)
+        // Videos may be placed on overlay planes; everything else gets composited
+        let kind = if self.content_type() == ContentType::Video {
+            element::Kind::ScanoutCandidate
+        } else {
+            element::Kind::Unspecified
+        };
+
+        self.render_elements_with_kind(renderer, location, scale, alpha, kind)
+    }
+}
+
+impl WindowElement {
+    /// Get render elements for this window, with its surfaces marked as the given element kind.
+    ///
+    /// Popups are always [`element::Kind::Unspecified`].
+    pub fn render_elements_with_kind<R, C>(
+        &self,
+        renderer: &mut R,
+        location: Point<i32, Physical>,
+        scale: Scale<f64>,
+        alpha: f32,
+        kind: element::Kind,
+    ) -> Vec<C>
+    where
+        R: Renderer + ImportAll,
+        <R as Renderer>::TextureId: Clone + 'static,
+        C: From<WaylandSurfaceRenderElement<R>>,
+    {
+        let mut render_elements = self.popup_render_elements(renderer, location, scale, alpha);
+        render_elements
+            .extend(self.surface_render_elements(renderer, location, scale, alpha, kind));
+        render_elements
+    }
+
+    /// Get render elements for this window's popups.
+    pub fn popup_render_elements<R, C>(
+        &self,
+        renderer: &mut R,
+        location: Point<i32, Physical>,
+        scale: Scale<f64>,
+        alpha: f32,
+    ) -> Vec<C>
+    where
+        R: Renderer + ImportAll,
+        <R as Renderer>::TextureId: Clone + 'static,
+        C: From<WaylandSurfaceRenderElement<R>>,
+    {
+        let Some(surface) = self.wl_surface() else {
+            return Vec::new();
+        };
+
+        // The multiplier of the root surface is applied to the whole window
+        let alpha = alpha * surface_alpha_multiplier(&surface);
+
+        let mut render_elements = Vec::new();
+
+        if let WindowSurface::Wayland(_) = self.underlying_surface() {
+            for (popup, popup_offset) in PopupManager::popups_for_surface(&surface) {
+                let offset = (self.geometry().loc + popup_offset - popup.geometry().loc)
+                    .to_physical_precise_round(scale);
+                render_elements.extend(render_elements_from_surface_tree(
+                    renderer,
+                    popup.wl_surface(),
+                    location + offset,
+                    scale,
+                    alpha,
+                    element::Kind::Unspecified,
+                ));
+            }
+        }
+
+        render_elements
+    }
+
+    /// Get render elements for this window's surface tree, without popups.
+    pub fn surface_render_elements<R, C>(
+        &self,
+        renderer: &mut R,
+        location: Point<i32, Physical>,
+        scale: Scale<f64>,
+        alpha: f32,
+        kind: element::Kind,
+    ) -> Vec<C>
+    where
+        R: Renderer + ImportAll,
+        <R as Renderer>::TextureId: Clone + 'static,
+        C: From<WaylandSurfaceRenderElement<R>>,
+    {
+        let Some(surface) = self.wl_surface() else {
+            return Vec::new();
+        };
+
+        // The multiplier of the root surface is applied to the whole window
+        let alpha = alpha * surface_alpha_multiplier(&surface);
+
+        render_elements_from_surface_tree(renderer, &surface, location, scale, alpha, kind)
+    }
+
+    /// The corner radius this window is drawn with, in logical pixels.
+    ///
+    /// A window rule's radius is used first, then that of the first of the window's active tags
+    /// that sets one, then the global one. Fullscreen windows are never rounded.
+    ///
+    /// RefCell Safety: This uses `with_state` on this window.
+    pub fn corner_radius(&self, settings: &RenderSettings) -> f64 {
+        self.with_state(|state| {
+            if state.fullscreen_or_maximized.is_fullscreen() {
+                0.0
+            } else {
+                state
+                    .corner_radius
+                    .or_else(|| {
+                        state
+                            .tags
+                            .iter()
+                            .filter(|tag| tag.active())
+                            .find_map(|tag| tag.corner_radius())
+                    })
+                    .unwrap_or(settings.corner_radius)
+            }
+        })
+    }
+
+    /// What the color of this window is multiplied by, from 0.0 (black) to 1.0 (not dimmed).
+    ///
+    /// Windows other than the focused one are dimmed unless a window rule turned it off
+    /// or they're showing a game or video. Unresponsive windows are darkened further if enabled.
+    ///
+    /// RefCell Safety: This uses `with_state` on this window.
+    pub fn brightness(&self, settings: &RenderSettings, focused: Option<&WindowElement>) -> f32 {
+        let brightness = if focused == Some(self)
+            || !self.with_state(|state| state.dim)
+            || self.is_game_or_video()
+        {
+            1.0
+        } else {
+            (1.0 - settings.dim_inactive).clamp(0.0, 1.0)
+        };
+
+        if settings.darken_unresponsive && self.with_state(|state| state.unresponsive) {
+            brightness.min(UNRESPONSIVE_BRIGHTNESS)
+        } else {
+            brightness
+        }
     }
 }
 
-struct LayerRenderElements<R: Renderer> {
-    background: Vec<WaylandSurfaceRenderElement<R>>,
-    bottom: Vec<WaylandSurfaceRenderElement<R>>,
-    top: Vec<WaylandSurfaceRenderElement<R>>,
-    overlay: Vec<WaylandSurfaceRenderElement<R>>,
+struct LayerRenderElements<R>
+where
+    R: PinnacleRenderer,
+    <R as Renderer>::TextureId: Clone + 'static,
+{
+    background: Vec<OutputRenderElement<R, WaylandSurfaceRenderElement<R>>>,
+    bottom: Vec<OutputRenderElement<R, WaylandSurfaceRenderElement<R>>>,
+    top: Vec<OutputRenderElement<R, WaylandSurfaceRenderElement<R>>>,
+    overlay: Vec<OutputRenderElement<R, WaylandSurfaceRenderElement<R>>>,
 }
 
 fn layer_render_elements<R>(
     output: &Output,
     renderer: &mut R,
     scale: Scale<f64>,
+    blur: Option<&BlurredBackground>,
+    blur_settings: &BlurSettings,
 ) -> LayerRenderElements<R>
 where
-    R: Renderer + ImportAll,
+    R: PinnacleRenderer,
     <R as Renderer>::TextureId: Clone + 'static,
 {
     let layer_map = layer_map_for_output(output);
@@ -96,15 +350,27 @@ where
 
     let layer_elements = layer_map
         .layers()
-        .filter_map(|surface| {
-            layer_map
-                .layer_geometry(surface)
-                .map(|geo| (surface, geo.loc))
-        })
-        .map(|(surface, loc)| {
-            let loc = loc.to_physical_precise_round(scale);
-            let render_elements = surface
-                .render_elements::<WaylandSurfaceRenderElement<R>>(renderer, loc, scale, 1.0);
+        .filter_map(|surface| layer_map.layer_geometry(surface).map(|geo| (surface, geo)))
+        .map(|(surface, geo)| {
+            let loc = geo.loc.to_physical_precise_round(scale);
+            let mut render_elements = surface
+                .render_elements::<WaylandSurfaceRenderElement<R>>(renderer, loc, scale, 1.0)
+                .into_iter()
+                .map(OutputRenderElement::from)
+                .collect::<Vec<_>>();
+
+            if let Some(blur) =
+                blur.filter(|_| blur_settings.blurs_layer(surface.layer(), surface.namespace()))
+            {
+                render_elements.push(OutputRenderElement::from(BlurElement::new(
+                    blur_id(surface.user_data()),
+                    blur.clone(),
+                    geo.to_physical_precise_round(scale),
+                    None,
+                    1.0,
+                )));
+            }
+
             (surface.layer(), render_elements)
         });
 
@@ -125,7 +391,7 @@ where
     }
 }
 
-/// Get render elements for windows on active tags.
+/// Get render elements for windows on active tags and windows still fading out.
 ///
 /// ret.1 contains render elements for the windows at and above the first fullscreen window.
 /// ret.2 contains the rest.
@@ -136,37 +402,157 @@ fn window_render_elements<R>(
     space: &Space<WindowElement>,
     renderer: &mut R,
     scale: Scale<f64>,
+    settings: &RenderSettings,
+    blur: Option<&BlurredBackground>,
 ) -> (
     Vec<OutputRenderElement<R, WaylandSurfaceRenderElement<R>>>,
     Vec<OutputRenderElement<R, WaylandSurfaceRenderElement<R>>>,
 )
 where
-    R: Renderer + ImportAll + ImportMem,
+    R: PinnacleRenderer,
     <R as Renderer>::TextureId: Clone + 'static,
 {
     let mut last_fullscreen_split_at = 0;
 
+    let focused = output.with_state(|state| state.focus_stack.current_focus().cloned());
+
     let mut fullscreen_and_up = windows
         .iter()
         .rev() // rev because I treat the focus stack backwards vs how the renderer orders it
-        .filter(|win| win.is_on_active_tag())
+        .filter(|win| win.is_visible() || win.is_animating_out())
         .enumerate()
         .flat_map(|(i, win)| {
             if win.with_state(|state| state.fullscreen_or_maximized.is_fullscreen()) {
                 last_fullscreen_split_at = i + 1;
             }
 
+            let target = Rectangle::from_loc_and_size(
+                space.element_location(win).unwrap_or((0, 0).into()),
+                win.geometry().size,
+            );
+
             // subtract win.geometry().loc to align decorations correctly
-            let loc = (
-                space.element_location(win) .unwrap_or((0, 0).into())
-                    - win.geometry().loc
-                    - output.current_location()
-                )
+            let loc = (target.loc - win.geometry().loc - output.current_location())
                 .to_physical_precise_round(scale);
 
-            win.render_elements::<WaylandSurfaceRenderElement<R>>(renderer, loc, scale, 1.0)
-                .into_iter()
-                .map(OutputRenderElement::from)
+            let animated = win.animated_geometry(target);
+            let alpha = animated.map_or(1.0, |anim| anim.alpha)
+                * if win.with_state(|state| state.swap_ghost) {
+                    SWAP_GHOST_ALPHA
+                } else {
+                    1.0
+                };
+
+            // Scale the window around its actual location. The offset to where it's drawn
+            // is applied before scaling so it ends up unscaled.
+            let origin = (target.loc - output.current_location()).to_physical_precise_round(scale);
+            let (rescale, offset) = match animated {
+                Some(AnimatedGeometry { geometry, .. }) => {
+                    let rescale = Scale {
+                        x: (geometry.size.w / f64::from(target.size.w.max(1))).max(0.01),
+                        y: (geometry.size.h / f64::from(target.size.h.max(1))).max(0.01),
+                    };
+                    let offset = geometry.loc - target.loc.to_f64();
+                    let offset =
+                        Point::<f64, Logical>::from((offset.x / rescale.x, offset.y / rescale.y))
+                            .to_physical_precise_round(scale);
+                    (Some(rescale), offset)
+                }
+                None => (None, (0, 0).into()),
+            };
+            let loc = loc + offset;
+
+            let corner_radius = win.corner_radius(settings) * scale.x;
+            let program = (corner_radius > 0.0)
+                .then(|| renderer.gles_renderer().and_then(rounded_corner_program))
+                .flatten();
+
+            let brightness = win.brightness(settings, focused.as_ref());
+            let dim = (brightness < 1.0)
+                .then(|| renderer.gles_renderer().and_then(dim_program))
+                .flatten()
+                .map(|program| (program, brightness));
+            let rescale = rescale.map(|rescale| (origin, rescale));
+
+            // The window geometry as it's drawn, which the surfaces get clipped to
+            let drawn_geometry = animated.map_or(target.to_f64(), |anim| anim.geometry);
+            let drawn_geometry = Rectangle::from_loc_and_size(
+                drawn_geometry.loc - output.current_location().to_f64(),
+                drawn_geometry.size,
+            )
+            .to_physical_precise_round(scale);
+
+            // This is drawn beneath the window, so it goes after the window's elements
+            let blur_element = blur
+                .filter(|_| win.with_state(|state| state.blur))
+                .map(|blur| {
+                    OutputRenderElement::from(BlurElement::new(
+                        blur_id(win.user_data()),
+                        blur.clone(),
+                        drawn_geometry,
+                        program
+                            .clone()
+                            .map(|program| (program, corner_radius as f32)),
+                        alpha,
+                    ))
+                });
+
+            let indicator = group_indicator_elements(win, drawn_geometry, scale);
+
+            let Some(program) = program else {
+                let window_elements = win
+                    .render_elements::<WaylandSurfaceRenderElement<R>>(renderer, loc, scale, alpha);
+                let mut elements = indicator;
+                elements.extend(unclipped_window_elements(window_elements, rescale, dim));
+                elements.extend(blur_element);
+                return elements;
+            };
+
+            // Popups are usually outside of the window geometry, so they aren't clipped
+            let popups = win.popup_render_elements::<R, WaylandSurfaceRenderElement<R>>(
+                renderer, loc, scale, alpha,
+            );
+            let surfaces = win.surface_render_elements::<R, WaylandSurfaceRenderElement<R>>(
+                renderer,
+                loc,
+                scale,
+                alpha,
+                element::Kind::Unspecified,
+            );
+
+            // The rounded corner shader also dims the surfaces
+            let mut elements = indicator;
+            elements.extend(unclipped_window_elements(popups, rescale, dim));
+
+            match rescale {
+                Some((origin, rescale)) => {
+                    elements.extend(surfaces.into_iter().map(|elem| {
+                        let elem = RescaleRenderElement::from_element(elem, origin, rescale);
+                        OutputRenderElement::from(RoundedCornerElement::new(
+                            elem,
+                            drawn_geometry,
+                            corner_radius as f32,
+                            brightness,
+                            program.clone(),
+                        ))
+                    }));
+                }
+                None => {
+                    elements.extend(surfaces.into_iter().map(|elem| {
+                        OutputRenderElement::from(RoundedCornerElement::new(
+                            elem,
+                            drawn_geometry,
+                            corner_radius as f32,
+                            brightness,
+                            program.clone(),
+                        ))
+                    }));
+                }
+            }
+
+            elements.extend(blur_element);
+
+            elements
         }).collect::<Vec<_>>();
 
     let rest = fullscreen_and_up.split_off(last_fullscreen_split_at);
@@ -174,6 +560,36 @@ where
     (fullscreen_and_up, rest)
 }
 
+/// Wrap window elements that aren't clipped to the window geometry, rescaling them around
+/// `rescale.0` if the window is animating and dimming them if the window is dimmed.
+fn unclipped_window_elements<R>(
+    elements: Vec<WaylandSurfaceRenderElement<R>>,
+    rescale: Option<(Point<i32, Physical>, Scale<f64>)>,
+    dim: Option<(GlesTexProgram, f32)>,
+) -> Vec<OutputRenderElement<R, WaylandSurfaceRenderElement<R>>>
+where
+    R: PinnacleRenderer,
+    <R as Renderer>::TextureId: Clone + 'static,
+{
+    elements
+        .into_iter()
+        .map(|elem| match (rescale, dim.as_ref()) {
+            (Some((origin, rescale)), Some((program, brightness))) => {
+                let elem = RescaleRenderElement::from_element(elem, origin, rescale);
+                OutputRenderElement::from(DimElement::new(elem, *brightness, program.clone()))
+            }
+            (Some((origin, rescale)), None) => {
+                let elem = RescaleRenderElement::from_element(elem, origin, rescale);
+                OutputRenderElement::from(TransformRenderElement::from(elem))
+            }
+            (None, Some((program, brightness))) => {
+                OutputRenderElement::from(DimElement::new(elem, *brightness, program.clone()))
+            }
+            (None, None) => OutputRenderElement::from(elem),
+        })
+        .collect()
+}
+
 pub fn pointer_render_elements<R>(
     output: &Output,
     renderer: &mut R,
@@ -237,14 +653,42 @@ where
 ///
 /// Render elements will be pulled from the provided windows,
 /// with the first window being at the top and subsequent ones beneath.
+///
+/// If the output has a color filter, this is a single element drawing the filtered output.
 pub fn output_render_elements<R, T>(
     output: &Output,
     renderer: &mut R,
     space: &Space<WindowElement>,
     windows: &[WindowElement],
+    settings: &RenderSettings,
 ) -> Vec<OutputRenderElement<R, WaylandSurfaceRenderElement<R>>>
 where
-    R: Renderer<TextureId = T> + ImportAll + ImportMem,
+    R: PinnacleRenderer<TextureId = T>,
+    <R as Renderer>::TextureId: 'static,
+    T: Texture + Clone,
+{
+    if let Some(filter) = output.with_state(|state| state.color_filter) {
+        let element = renderer.gles_renderer().and_then(|renderer| {
+            color_filter_element(renderer, output, space, windows, settings, filter)
+        });
+
+        if let Some(element) = element {
+            return vec![OutputRenderElement::from(element)];
+        }
+    }
+
+    unfiltered_output_render_elements(output, renderer, space, windows, settings)
+}
+
+fn unfiltered_output_render_elements<R, T>(
+    output: &Output,
+    renderer: &mut R,
+    space: &Space<WindowElement>,
+    windows: &[WindowElement],
+    settings: &RenderSettings,
+) -> Vec<OutputRenderElement<R, WaylandSurfaceRenderElement<R>>>
+where
+    R: PinnacleRenderer<TextureId = T>,
     <R as Renderer>::TextureId: 'static,
     T: Texture + Clone,
 {
@@ -289,28 +733,124 @@ where
     // |     base it on if it's a descendant or not
     output_render_elements.extend(o_r_elements.map(OutputRenderElement::from));
 
+    let blur = needs_blur(output, &windows, &settings.blur)
+        .then(|| {
+            renderer
+                .gles_renderer()
+                .and_then(|renderer| blurred_background(renderer, output, &settings.blur))
+        })
+        .flatten();
+
     let LayerRenderElements {
         background,
         bottom,
         top,
         overlay,
-    } = layer_render_elements(output, renderer, scale);
+    } = layer_render_elements(output, renderer, scale, blur.as_ref(), &settings.blur);
+
+    // Only render what's visible over a fullscreen window so that it can be scanned out directly
+    if let Some(window) = override_redirect_windows
+        .is_empty()
+        .then(|| fullscreen_scanout_window(output, space))
+        .flatten()
+        .filter(|window| renders_alone(output, window, &windows, settings))
+    {
+        let loc = (space.element_location(&window).unwrap_or((0, 0).into())
+            - window.geometry().loc
+            - output.current_location())
+        .to_physical_precise_round(scale);
+
+        output_render_elements.extend(overlay);
+        output_render_elements.extend(
+            window
+                .render_elements_with_kind::<R, WaylandSurfaceRenderElement<R>>(
+                    renderer,
+                    loc,
+                    scale,
+                    1.0,
+                    element::Kind::ScanoutCandidate,
+                )
+                .into_iter()
+                .map(OutputRenderElement::from),
+        );
+
+        return output_render_elements;
+    }
 
-    let (fullscreen_and_up_elements, rest_of_window_elements) =
-        window_render_elements::<R>(output, &windows, space, renderer, scale);
+    let (fullscreen_and_up_elements, rest_of_window_elements) = window_render_elements::<R>(
+        output,
+        &windows,
+        space,
+        renderer,
+        scale,
+        settings,
+        blur.as_ref(),
+    );
 
     // Elements render from top to bottom
 
-    output_render_elements.extend(overlay.into_iter().map(OutputRenderElement::from));
+    output_render_elements.extend(overlay);
     output_render_elements.extend(fullscreen_and_up_elements);
-    output_render_elements.extend(top.into_iter().map(OutputRenderElement::from));
+    output_render_elements.extend(top);
     output_render_elements.extend(rest_of_window_elements);
-    output_render_elements.extend(bottom.into_iter().map(OutputRenderElement::from));
-    output_render_elements.extend(background.into_iter().map(OutputRenderElement::from));
+    output_render_elements.extend(bottom);
+    output_render_elements.extend(background);
+    output_render_elements.extend(wallpaper_elements(renderer, output, scale));
 
     output_render_elements
 }
 
+/// Whether any visible window or layer surface on this output has blur behind it.
+fn needs_blur(output: &Output, windows: &[WindowElement], settings: &BlurSettings) -> bool {
+    windows.iter().any(|win| {
+        (win.is_visible() || win.is_animating_out()) && win.with_state(|state| state.blur)
+    }) || layer_map_for_output(output)
+        .layers()
+        .any(|layer| settings.blurs_layer(layer.layer(), layer.namespace()))
+}
+
+/// Whether `window`, the fullscreen scanout window of `output`, can be rendered on its own.
+///
+/// Windows fading out, group indicators, and dimming are only drawn when rendering
+/// all windows, so the window can't be rendered alone while any of those are visible.
+fn renders_alone(
+    output: &Output,
+    window: &WindowElement,
+    windows: &[WindowElement],
+    settings: &RenderSettings,
+) -> bool {
+    let focused = output.with_state(|state| state.focus_stack.current_focus().cloned());
+
+    window.group().is_none()
+        && window.brightness(settings, focused.as_ref()) >= 1.0
+        && !windows.iter().any(|win| win.is_animating_out())
+}
+
+/// Get the window that is a candidate for direct scanout on the given output.
+///
+/// This is the topmost window on the output if it's fullscreen and covers the whole output.
+pub fn fullscreen_scanout_window(
+    output: &Output,
+    space: &Space<WindowElement>,
+) -> Option<WindowElement> {
+    let output_geo = space.output_geometry(output)?;
+
+    let window = space
+        .elements()
+        .rev()
+        .filter(|win| win.is_visible())
+        .find(|win| {
+            space
+                .element_geometry(win)
+                .is_some_and(|geo| geo.overlaps(output_geo))
+        })?;
+
+    let is_fullscreen = window.with_state(|state| state.fullscreen_or_maximized.is_fullscreen());
+
+    (is_fullscreen && !window.is_animating() && space.element_geometry(window) == Some(output_geo))
+        .then(|| window.clone())
+}
+
 // TODO: docs
 pub fn take_presentation_feedback(
     output: &Output,
@@ -345,6 +885,37 @@ pub fn take_presentation_feedback(
     output_presentation_feedback
 }
 
+/// Get the release points of the explicitly synced buffers of windows and layer surfaces
+/// that were used to render a frame on `output`.
+pub fn release_points(
+    output: &Output,
+    space: &Space<WindowElement>,
+    render_element_states: &RenderElementStates,
+) -> Vec<Arc<DrmReleasePoint>> {
+    let mut release_points = Vec::new();
+    let mut push_release_point = |surface: &WlSurface, states: &compositor::SurfaceData| {
+        if render_element_states
+            .element_render_state(surface)
+            .is_some()
+        {
+            release_points.extend(drm_syncobj::release_point(states));
+        }
+    };
+
+    space.elements().for_each(|window| {
+        if space.outputs_for_element(window).contains(output) {
+            window.with_surfaces(&mut push_release_point);
+        }
+    });
+
+    let map = smithay::desktop::layer_map_for_output(output);
+    for layer_surface in map.layers() {
+        layer_surface.with_surfaces(&mut push_release_point);
+    }
+
+    release_points
+}
+
 impl State {
     /// Schedule a new render. This does nothing on the winit backend.
     pub fn schedule_render(&mut self, output: &Output) {
@@ -0,0 +1,430 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Background blur.
+//!
+//! The background and bottom layer surfaces of an output are rendered into a texture and
+//! blurred with the dual kawase algorithm. Windows and layer surfaces that want blur get the
+//! part of that texture behind them drawn underneath them.
+//!
+//! Only the background is blurred, not windows beneath other windows. This lets the blurred
+//! texture be reused until the background changes instead of being redone every frame.
+
+use std::cell::RefCell;
+
+use smithay::{
+    backend::{
+        allocator::Fourcc,
+        renderer::{
+            element::{
+                surface::WaylandSurfaceRenderElement, AsRenderElements, Element, Id, RenderElement,
+            },
+            gles::{
+                GlesError, GlesRenderer, GlesTexProgram, GlesTexture, Uniform, UniformName,
+                UniformType,
+            },
+            utils::CommitCounter,
+            Bind, Frame, Offscreen, Renderer, Unbind,
+        },
+    },
+    desktop::layer_map_for_output,
+    output::Output,
+    utils::{user_data::UserDataMap, Buffer, Physical, Rectangle, Scale, Size, Transform},
+    wayland::shell::wlr_layer,
+};
+use tracing::{error, warn};
+
+use super::{wallpaper::wallpaper_elements, OutputRenderElement, PinnacleRenderer};
+
+const DOWN_SHADER: &str = include_str!("shaders/blur_down.frag");
+const UP_SHADER: &str = include_str!("shaders/blur_up.frag");
+
+/// The most blur passes allowed. Each pass halves the size of the texture.
+pub const MAX_PASSES: u32 = 8;
+
+/// Blur settings set by the config.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlurSettings {
+    /// How many times the background is downsampled. More passes blur more.
+    pub passes: u32,
+    /// How far apart the samples of each pass are.
+    pub offset: f32,
+    /// Namespaces of top and overlay layer surfaces that get blur.
+    pub layer_namespaces: Vec<String>,
+}
+
+impl Default for BlurSettings {
+    fn default() -> Self {
+        Self {
+            passes: 2,
+            offset: 3.0,
+            layer_namespaces: Vec::new(),
+        }
+    }
+}
+
+impl BlurSettings {
+    /// Whether the layer surface with this namespace on this layer gets blur.
+    pub fn blurs_layer(&self, layer: wlr_layer::Layer, namespace: &str) -> bool {
+        matches!(layer, wlr_layer::Layer::Top | wlr_layer::Layer::Overlay)
+            && self.layer_namespaces.iter().any(|ns| ns == namespace)
+    }
+}
+
+/// The compiled blur shaders, stored in the renderer's EGL context.
+///
+/// These are `None` if the shaders failed to compile so that they aren't recompiled every frame.
+struct BlurPrograms(Option<(GlesTexProgram, GlesTexProgram)>);
+
+fn blur_programs(renderer: &mut GlesRenderer) -> Option<(GlesTexProgram, GlesTexProgram)> {
+    if let Some(programs) = renderer.egl_context().user_data().get::<BlurPrograms>() {
+        return programs.0.clone();
+    }
+
+    let uniforms = [
+        UniformName::new("half_pixel", UniformType::_2f),
+        UniformName::new("offset", UniformType::_1f),
+    ];
+
+    let programs = renderer
+        .compile_custom_texture_shader(DOWN_SHADER, &uniforms)
+        .and_then(|down| {
+            let up = renderer.compile_custom_texture_shader(UP_SHADER, &uniforms)?;
+            Ok((down, up))
+        })
+        .map_err(|err| error!("Failed to compile blur shaders: {err}"))
+        .ok();
+
+    renderer
+        .egl_context()
+        .user_data()
+        .insert_if_missing(|| BlurPrograms(programs.clone()));
+
+    programs
+}
+
+/// The id of the blur element drawn behind a window or layer surface.
+struct BlurId(Id);
+
+/// Get the id of the blur element for the window or layer surface with this user data.
+pub fn blur_id(user_data: &UserDataMap) -> Id {
+    user_data.insert_if_missing(|| BlurId(Id::new()));
+    user_data.get::<BlurId>().expect("inserted above").0.clone()
+}
+
+/// What the blurred texture of an output was last rendered from.
+#[derive(Debug, PartialEq)]
+struct BlurKey {
+    size: Size<i32, Physical>,
+    scale: f64,
+    passes: u32,
+    offset: f32,
+    elements: Vec<(Id, CommitCounter)>,
+}
+
+/// The blurred background of an output, stored in its user data.
+#[derive(Debug, Default)]
+struct BlurCache {
+    key: Option<BlurKey>,
+    texture: Option<GlesTexture>,
+    commit: CommitCounter,
+}
+
+/// The blurred background of an output.
+#[derive(Debug, Clone)]
+pub struct BlurredBackground {
+    texture: GlesTexture,
+    size: Size<i32, Physical>,
+    commit: CommitCounter,
+}
+
+/// Get the blurred background of an output, only blurring it again if it changed.
+pub fn blurred_background(
+    renderer: &mut GlesRenderer,
+    output: &Output,
+    settings: &BlurSettings,
+) -> Option<BlurredBackground> {
+    let mode = output.current_mode()?;
+    let size = output.current_transform().transform_size(mode.size);
+    let scale = output.current_scale().fractional_scale();
+
+    let elements = background_elements(renderer, output, Scale::from(scale));
+
+    let key = BlurKey {
+        size,
+        scale,
+        passes: settings.passes.clamp(1, MAX_PASSES),
+        offset: settings.offset,
+        elements: elements
+            .iter()
+            .map(|elem| (elem.id().clone(), elem.current_commit()))
+            .collect(),
+    };
+
+    output
+        .user_data()
+        .insert_if_missing(RefCell::<BlurCache>::default);
+    let mut cache = output
+        .user_data()
+        .get::<RefCell<BlurCache>>()
+        .expect("inserted above")
+        .borrow_mut();
+
+    if cache.key.as_ref() != Some(&key) {
+        let (down, up) = blur_programs(renderer)?;
+
+        let texture = render_blur(renderer, &elements, &key, &down, &up);
+        if let Err(err) = renderer.unbind() {
+            warn!("Failed to unbind blur texture: {err}");
+        }
+
+        match texture {
+            Ok(texture) => {
+                cache.texture = Some(texture);
+                cache.commit.increment();
+            }
+            Err(err) => {
+                error!("Failed to blur background of {}: {err}", output.name());
+                cache.texture = None;
+            }
+        }
+        cache.key = Some(key);
+    }
+
+    Some(BlurredBackground {
+        texture: cache.texture.clone()?,
+        size,
+        commit: cache.commit,
+    })
+}
+
+/// Get render elements for the background and bottom layer surfaces of an output,
+/// from top to bottom.
+fn background_elements(
+    renderer: &mut GlesRenderer,
+    output: &Output,
+    scale: Scale<f64>,
+) -> Vec<OutputRenderElement<GlesRenderer, WaylandSurfaceRenderElement<GlesRenderer>>> {
+    let layer_map = layer_map_for_output(output);
+
+    let mut elements = Vec::new();
+
+    for layer in [wlr_layer::Layer::Bottom, wlr_layer::Layer::Background] {
+        for surface in layer_map.layers_on(layer).rev() {
+            let Some(geo) = layer_map.layer_geometry(surface) else {
+                continue;
+            };
+            let loc = geo.loc.to_physical_precise_round(scale);
+            elements.extend(
+                surface
+                    .render_elements::<WaylandSurfaceRenderElement<GlesRenderer>>(
+                        renderer, loc, scale, 1.0,
+                    )
+                    .into_iter()
+                    .map(OutputRenderElement::from),
+            );
+        }
+    }
+
+    elements.extend(wallpaper_elements(renderer, output, scale));
+
+    elements
+}
+
+fn render_blur(
+    renderer: &mut GlesRenderer,
+    elements: &[OutputRenderElement<GlesRenderer, WaylandSurfaceRenderElement<GlesRenderer>>],
+    key: &BlurKey,
+    down: &GlesTexProgram,
+    up: &GlesTexProgram,
+) -> Result<GlesTexture, GlesError> {
+    let scale = Scale::from(key.scale);
+
+    let mut sizes = vec![key.size];
+    for _ in 0..key.passes {
+        let last = sizes[sizes.len() - 1];
+        sizes.push(Size::from(((last.w / 2).max(1), (last.h / 2).max(1))));
+    }
+
+    let textures = sizes
+        .iter()
+        .map(|size| {
+            renderer.create_buffer(
+                Fourcc::Abgr8888,
+                size.to_logical(1).to_buffer(1, Transform::Normal),
+            )
+        })
+        .collect::<Result<Vec<GlesTexture>, _>>()?;
+
+    // Draw the background
+    renderer.bind(textures[0].clone())?;
+    {
+        let full = Rectangle::from_loc_and_size((0, 0), key.size);
+        let mut frame = renderer.render(key.size, Transform::Normal)?;
+        frame.clear([0.0, 0.0, 0.0, 1.0], &[full])?;
+        for elem in elements.iter().rev() {
+            let dst = elem.geometry(scale);
+            let damage = Rectangle::from_loc_and_size((0, 0), dst.size);
+            elem.draw(&mut frame, elem.src(), dst, &[damage])?;
+        }
+        frame.finish()?;
+    }
+
+    // Downsample into smaller and smaller textures, then upsample back into the first one
+    let passes = (0..sizes.len() - 1)
+        .map(|i| (i, i + 1, down))
+        .chain((1..sizes.len()).rev().map(|i| (i, i - 1, up)));
+
+    for (from, to, program) in passes {
+        let src_size = sizes[from];
+        let dst_size = sizes[to];
+
+        renderer.bind(textures[to].clone())?;
+
+        let mut frame = renderer.render(dst_size, Transform::Normal)?;
+        let dst = Rectangle::from_loc_and_size((0, 0), dst_size);
+        frame.override_default_tex_program(
+            program.clone(),
+            vec![
+                Uniform::new(
+                    "half_pixel",
+                    (0.5 / dst_size.w as f32, 0.5 / dst_size.h as f32),
+                ),
+                Uniform::new("offset", key.offset),
+            ],
+        );
+        frame.render_texture_from_to(
+            &textures[from],
+            Rectangle::from_loc_and_size(
+                (0.0, 0.0),
+                src_size
+                    .to_f64()
+                    .to_logical(1.0)
+                    .to_buffer(1.0, Transform::Normal),
+            ),
+            dst,
+            &[dst],
+            Transform::Normal,
+            1.0,
+        )?;
+        frame.clear_tex_program_override();
+        frame.finish()?;
+    }
+
+    Ok(textures[0].clone())
+}
+
+/// Draws the blurred background behind a window or layer surface.
+#[derive(Debug)]
+pub struct BlurElement {
+    id: Id,
+    background: BlurredBackground,
+    /// The area to blur, relative to the output.
+    geometry: Rectangle<i32, Physical>,
+    /// The rounded corner shader and the corner radius in physical pixels, if the
+    /// blur should have rounded corners.
+    rounded: Option<(GlesTexProgram, f32)>,
+    alpha: f32,
+}
+
+impl BlurElement {
+    pub fn new(
+        id: Id,
+        background: BlurredBackground,
+        geometry: Rectangle<i32, Physical>,
+        rounded: Option<(GlesTexProgram, f32)>,
+        alpha: f32,
+    ) -> Self {
+        Self {
+            id,
+            background,
+            geometry,
+            rounded,
+            alpha,
+        }
+    }
+}
+
+impl Element for BlurElement {
+    fn id(&self) -> &Id {
+        &self.id
+    }
+
+    fn current_commit(&self) -> CommitCounter {
+        self.background.commit
+    }
+
+    // The blurred texture covers the whole output with one pixel per physical pixel
+    fn src(&self) -> Rectangle<f64, Buffer> {
+        Rectangle::from_loc_and_size(
+            (
+                f64::from(self.geometry.loc.x),
+                f64::from(self.geometry.loc.y),
+            ),
+            (
+                f64::from(self.geometry.size.w),
+                f64::from(self.geometry.size.h),
+            ),
+        )
+    }
+
+    fn geometry(&self, _scale: Scale<f64>) -> Rectangle<i32, Physical> {
+        self.geometry
+    }
+}
+
+impl<R: PinnacleRenderer> RenderElement<R> for BlurElement {
+    fn draw(
+        &self,
+        frame: &mut R::Frame<'_>,
+        src: Rectangle<f64, Buffer>,
+        dst: Rectangle<i32, Physical>,
+        damage: &[Rectangle<i32, Physical>],
+    ) -> Result<(), R::Error> {
+        let Some(frame) = R::gles_frame(frame) else {
+            return Ok(());
+        };
+
+        // Round the blur like the window on top of it. The texture covers the whole output,
+        // so the shader is told that the "element" is the output.
+        if let Some((program, radius)) = self.rounded.as_ref() {
+            let size = self.background.size;
+            let max_radius = self.geometry.size.w.min(self.geometry.size.h) as f32 / 2.0;
+            frame.override_default_tex_program(
+                program.clone(),
+                vec![
+                    Uniform::new(
+                        "geo_size",
+                        (self.geometry.size.w as f32, self.geometry.size.h as f32),
+                    ),
+                    Uniform::new(
+                        "elem_loc",
+                        (-self.geometry.loc.x as f32, -self.geometry.loc.y as f32),
+                    ),
+                    Uniform::new("elem_size", (size.w as f32, size.h as f32)),
+                    Uniform::new("corner_radius", radius.min(max_radius)),
+                    Uniform::new("brightness", 1.0),
+                ],
+            );
+        }
+
+        let res = frame.render_texture_from_to(
+            &self.background.texture,
+            src,
+            dst,
+            damage,
+            Transform::Normal,
+            self.alpha,
+        );
+
+        if self.rounded.is_some() {
+            frame.clear_tex_program_override();
+        }
+
+        // Failing to blur shouldn't stop the rest of the frame from rendering
+        if let Err(err) = res {
+            warn!("Failed to draw blur: {err}");
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,374 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Full-output color filters.
+//!
+//! When an output has a color filter, everything except the cursor is rendered into a texture
+//! first. That texture is then drawn with a shader that transforms every color with a matrix.
+//! This needs a GLES renderer; with other renderers, color filters do nothing.
+
+use std::cell::RefCell;
+
+use smithay::{
+    backend::{
+        allocator::Fourcc,
+        renderer::{
+            damage::OutputDamageTracker,
+            element::{Element, Id, RenderElement, RenderElementStates},
+            gles::{
+                GlesRenderer, GlesTexProgram, GlesTexture, Uniform, UniformName, UniformType,
+                UniformValue,
+            },
+            utils::CommitCounter,
+            Bind, Frame, Offscreen, Unbind,
+        },
+    },
+    desktop::Space,
+    output::Output,
+    utils::{Buffer, Physical, Rectangle, Scale, Size, Transform},
+};
+use tracing::{error, warn};
+
+use crate::window::WindowElement;
+
+use super::{unfiltered_output_render_elements, PinnacleRenderer, RenderSettings};
+
+const SHADER: &str = include_str!("shaders/color_filter.frag");
+
+/// A transformation applied to every color on an output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorFilter {
+    /// Show everything in shades of gray.
+    Grayscale,
+    /// Invert all colors.
+    Invert,
+    /// Simulate protanopia (no red cones).
+    Protanopia,
+    /// Simulate deuteranopia (no green cones).
+    Deuteranopia,
+    /// Simulate tritanopia (no blue cones).
+    Tritanopia,
+    /// Shift colors so they are easier to tell apart with protanopia.
+    ProtanopiaCorrection,
+    /// Shift colors so they are easier to tell apart with deuteranopia.
+    DeuteranopiaCorrection,
+    /// Shift colors so they are easier to tell apart with tritanopia.
+    TritanopiaCorrection,
+}
+
+type Matrix = [[f32; 3]; 3];
+
+const IDENTITY: Matrix = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+// Simulation matrices for full severity from Machado, Oliveira and Fernandes (2009)
+const PROTANOPIA: Matrix = [
+    [0.152286, 1.052583, -0.204868],
+    [0.114503, 0.786281, 0.099216],
+    [-0.003882, -0.048116, 1.051998],
+];
+const DEUTERANOPIA: Matrix = [
+    [0.367322, 0.860646, -0.227968],
+    [0.280085, 0.672501, 0.047413],
+    [-0.011820, 0.042940, 0.968881],
+];
+const TRITANOPIA: Matrix = [
+    [1.255528, -0.076749, -0.178779],
+    [-0.078411, 0.930809, 0.147602],
+    [0.004733, 0.691367, 0.303900],
+];
+
+// Where the color information lost to each kind of color blindness is moved to
+const RED_GREEN_SHIFT: Matrix = [[0.0, 0.0, 0.0], [0.7, 1.0, 0.0], [0.7, 0.0, 1.0]];
+const BLUE_SHIFT: Matrix = [[1.0, 0.0, 0.7], [0.0, 1.0, 0.7], [0.0, 0.0, 0.0]];
+
+fn mul(a: Matrix, b: Matrix) -> Matrix {
+    let mut out = [[0.0; 3]; 3];
+    for (row, out_row) in out.iter_mut().enumerate() {
+        for (col, out) in out_row.iter_mut().enumerate() {
+            *out = (0..3).map(|i| a[row][i] * b[i][col]).sum();
+        }
+    }
+    out
+}
+
+fn add(a: Matrix, b: Matrix, factor: f32) -> Matrix {
+    let mut out = a;
+    for (out_row, b_row) in out.iter_mut().zip(b) {
+        for (out, b) in out_row.iter_mut().zip(b_row) {
+            *out += b * factor;
+        }
+    }
+    out
+}
+
+/// Daltonize: add the difference between the original and simulated colors,
+/// shifted to channels that can still be seen.
+fn correction(simulation: Matrix, shift: Matrix) -> Matrix {
+    add(IDENTITY, mul(shift, add(IDENTITY, simulation, -1.0)), 1.0)
+}
+
+impl ColorFilter {
+    /// The row-major matrix and the offset that transform a color with this filter.
+    fn transformation(self) -> (Matrix, [f32; 3]) {
+        match self {
+            ColorFilter::Grayscale => {
+                // Rec. 709 luma
+                let luma = [0.2126, 0.7152, 0.0722];
+                ([luma, luma, luma], [0.0; 3])
+            }
+            ColorFilter::Invert => (add([[0.0; 3]; 3], IDENTITY, -1.0), [1.0; 3]),
+            ColorFilter::Protanopia => (PROTANOPIA, [0.0; 3]),
+            ColorFilter::Deuteranopia => (DEUTERANOPIA, [0.0; 3]),
+            ColorFilter::Tritanopia => (TRITANOPIA, [0.0; 3]),
+            ColorFilter::ProtanopiaCorrection => {
+                (correction(PROTANOPIA, RED_GREEN_SHIFT), [0.0; 3])
+            }
+            ColorFilter::DeuteranopiaCorrection => {
+                (correction(DEUTERANOPIA, RED_GREEN_SHIFT), [0.0; 3])
+            }
+            ColorFilter::TritanopiaCorrection => (correction(TRITANOPIA, BLUE_SHIFT), [0.0; 3]),
+        }
+    }
+
+    fn uniforms(self) -> Vec<Uniform<'static>> {
+        let (matrix, offset) = self.transformation();
+
+        // GLES 2 can't transpose matrices itself, and wants them column-major
+        let mut columns = [[0.0; 3]; 3];
+        for (row, matrix_row) in matrix.iter().enumerate() {
+            for (col, value) in matrix_row.iter().enumerate() {
+                columns[col][row] = *value;
+            }
+        }
+
+        vec![
+            Uniform::new(
+                "color_matrix",
+                UniformValue::Matrix3x3 {
+                    matrices: vec![columns],
+                    transpose: false,
+                },
+            ),
+            Uniform::new("color_offset", (offset[0], offset[1], offset[2])),
+        ]
+    }
+}
+
+/// The compiled shader, stored in the renderer's EGL context.
+///
+/// This is `None` if the shader failed to compile so that it isn't recompiled every frame.
+struct ColorFilterProgram(Option<GlesTexProgram>);
+
+fn color_filter_program(renderer: &mut GlesRenderer) -> Option<GlesTexProgram> {
+    if let Some(program) = renderer
+        .egl_context()
+        .user_data()
+        .get::<ColorFilterProgram>()
+    {
+        return program.0.clone();
+    }
+
+    let program = renderer
+        .compile_custom_texture_shader(
+            SHADER,
+            &[
+                UniformName::new("color_matrix", UniformType::Matrix3x3),
+                UniformName::new("color_offset", UniformType::_3f),
+            ],
+        )
+        .map_err(|err| error!("Failed to compile color filter shader: {err}"))
+        .ok();
+
+    renderer
+        .egl_context()
+        .user_data()
+        .insert_if_missing(|| ColorFilterProgram(program.clone()));
+
+    program
+}
+
+/// The texture an output is rendered into before being filtered, stored in its user data.
+#[derive(Debug)]
+struct FilterTarget {
+    texture: GlesTexture,
+    size: Size<i32, Physical>,
+    scale: f64,
+    damage_tracker: OutputDamageTracker,
+    /// Whether the texture has been rendered into yet.
+    rendered: bool,
+    id: Id,
+    commit: CommitCounter,
+    /// The render element states of the last render into the texture.
+    states: Option<RenderElementStates>,
+}
+
+/// Render the output into a texture and get an element that draws it with `filter`.
+///
+/// This returns `None` if anything fails, in which case the output should be drawn unfiltered.
+pub fn color_filter_element(
+    renderer: &mut GlesRenderer,
+    output: &Output,
+    space: &Space<WindowElement>,
+    windows: &[WindowElement],
+    settings: &RenderSettings,
+    filter: ColorFilter,
+) -> Option<ColorFilterElement> {
+    let program = color_filter_program(renderer)?;
+
+    let mode = output.current_mode()?;
+    let size = output.current_transform().transform_size(mode.size);
+    let scale = output.current_scale().fractional_scale();
+
+    // This may render blur, so it has to happen before the texture is bound
+    let elements = unfiltered_output_render_elements(output, renderer, space, windows, settings);
+
+    output
+        .user_data()
+        .insert_if_missing(RefCell::<Option<FilterTarget>>::default);
+    let mut target = output
+        .user_data()
+        .get::<RefCell<Option<FilterTarget>>>()
+        .expect("inserted above")
+        .borrow_mut();
+
+    if target
+        .as_ref()
+        .map_or(true, |target| target.size != size || target.scale != scale)
+    {
+        let texture = match renderer.create_buffer(
+            Fourcc::Abgr8888,
+            size.to_logical(1).to_buffer(1, Transform::Normal),
+        ) {
+            Ok(texture) => texture,
+            Err(err) => {
+                error!("Failed to create color filter texture: {err}");
+                return None;
+            }
+        };
+
+        *target = Some(FilterTarget {
+            texture,
+            size,
+            scale,
+            damage_tracker: OutputDamageTracker::new(size, scale, Transform::Normal),
+            rendered: false,
+            id: Id::new(),
+            commit: CommitCounter::default(),
+            states: None,
+        });
+    }
+
+    let target = target.as_mut()?;
+
+    if let Err(err) = renderer.bind(target.texture.clone()) {
+        error!("Failed to bind color filter texture: {err}");
+        return None;
+    }
+
+    // The texture keeps its contents, so only what changed since the last frame is redrawn
+    let age = if target.rendered { 1 } else { 0 };
+    let result =
+        target
+            .damage_tracker
+            .render_output(renderer, age, &elements, [0.0, 0.0, 0.0, 1.0]);
+
+    let result = match result {
+        Ok(result) => {
+            if result.damage.is_some() {
+                target.commit.increment();
+            }
+            target.rendered = true;
+            target.states = Some(result.states);
+            Some(())
+        }
+        Err(err) => {
+            warn!("Failed to render output for color filter: {err}");
+            None
+        }
+    };
+
+    if let Err(err) = renderer.unbind() {
+        warn!("Failed to unbind color filter texture: {err}");
+    }
+
+    result?;
+
+    Some(ColorFilterElement {
+        id: target.id.clone(),
+        texture: target.texture.clone(),
+        size,
+        commit: target.commit,
+        filter,
+        program,
+    })
+}
+
+/// Take the render element states of the last time this output was rendered for a
+/// color filter.
+///
+/// Surfaces are rendered into the filter's texture and not the output itself, so these
+/// states are the ones that say which surfaces were visible.
+pub fn take_filtered_render_element_states(output: &Output) -> Option<RenderElementStates> {
+    output
+        .user_data()
+        .get::<RefCell<Option<FilterTarget>>>()?
+        .borrow_mut()
+        .as_mut()?
+        .states
+        .take()
+}
+
+/// Draws a filtered output.
+#[derive(Debug)]
+pub struct ColorFilterElement {
+    id: Id,
+    texture: GlesTexture,
+    size: Size<i32, Physical>,
+    commit: CommitCounter,
+    filter: ColorFilter,
+    program: GlesTexProgram,
+}
+
+impl Element for ColorFilterElement {
+    fn id(&self) -> &Id {
+        &self.id
+    }
+
+    fn current_commit(&self) -> CommitCounter {
+        self.commit
+    }
+
+    fn src(&self) -> Rectangle<f64, Buffer> {
+        Rectangle::from_loc_and_size((0.0, 0.0), (f64::from(self.size.w), f64::from(self.size.h)))
+    }
+
+    fn geometry(&self, _scale: Scale<f64>) -> Rectangle<i32, Physical> {
+        Rectangle::from_loc_and_size((0, 0), self.size)
+    }
+}
+
+impl<R: PinnacleRenderer> RenderElement<R> for ColorFilterElement {
+    fn draw(
+        &self,
+        frame: &mut R::Frame<'_>,
+        src: Rectangle<f64, Buffer>,
+        dst: Rectangle<i32, Physical>,
+        damage: &[Rectangle<i32, Physical>],
+    ) -> Result<(), R::Error> {
+        let Some(frame) = R::gles_frame(frame) else {
+            return Ok(());
+        };
+
+        frame.override_default_tex_program(self.program.clone(), self.filter.uniforms());
+
+        let res =
+            frame.render_texture_from_to(&self.texture, src, dst, damage, Transform::Normal, 1.0);
+
+        frame.clear_tex_program_override();
+
+        if let Err(err) = res {
+            warn!("Failed to draw color filter: {err}");
+        }
+
+        Ok(())
+    }
+}
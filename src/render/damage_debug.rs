@@ -0,0 +1,175 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Damage visualization.
+//!
+//! While it is enabled, regions that were damaged flash red and fade out,
+//! and every render element is outlined in yellow.
+//!
+//! Damage is computed with a separate damage tracker that only sees the scene,
+//! so the visualization doesn't damage itself.
+
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use smithay::{
+    backend::renderer::{
+        damage::OutputDamageTracker,
+        element::{
+            self, solid::SolidColorRenderElement, surface::WaylandSurfaceRenderElement, Element, Id,
+        },
+        utils::CommitCounter,
+        Renderer, Texture,
+    },
+    output::Output,
+    utils::{Physical, Rectangle, Scale},
+};
+
+use crate::state::{Pinnacle, WithState};
+
+use super::{OutputRenderElement, PinnacleRenderer};
+
+/// How long damaged regions take to fade out.
+const FLASH_DURATION: Duration = Duration::from_millis(300);
+const FLASH_COLOR: [f32; 3] = [0.9, 0.1, 0.1];
+const FLASH_ALPHA: f32 = 0.4;
+
+const OUTLINE_COLOR: [f32; 4] = [0.95, 0.85, 0.1, 1.0];
+/// The width of element outlines in physical pixels.
+const OUTLINE_WIDTH: i32 = 1;
+
+/// A damaged region that is fading out.
+#[derive(Debug)]
+struct Flash {
+    id: Id,
+    commit: CommitCounter,
+    rect: Rectangle<i32, Physical>,
+    start: Instant,
+}
+
+/// The damage visualization state of an output.
+#[derive(Debug)]
+pub struct DamageDebug {
+    damage_tracker: OutputDamageTracker,
+    flashes: Vec<Flash>,
+    /// The elements each scene element is outlined with.
+    ///
+    /// These are kept across frames so outlines only damage what moved.
+    outlines: HashMap<Id, [Id; 4]>,
+}
+
+impl DamageDebug {
+    fn new(output: &Output) -> Self {
+        Self {
+            damage_tracker: OutputDamageTracker::from_output(output),
+            flashes: Vec::new(),
+            outlines: HashMap::new(),
+        }
+    }
+
+    /// Whether damaged regions are still fading out.
+    pub fn is_flashing(&self) -> bool {
+        !self.flashes.is_empty()
+    }
+}
+
+/// Get the elements visualizing damage and element bounds of `elements` for an output
+/// if damage visualization is enabled.
+///
+/// These should be placed on top of `elements`.
+pub fn damage_debug_elements<R>(
+    pinnacle: &Pinnacle,
+    output: &Output,
+    elements: &[OutputRenderElement<R, WaylandSurfaceRenderElement<R>>],
+) -> Vec<OutputRenderElement<R, WaylandSurfaceRenderElement<R>>>
+where
+    R: PinnacleRenderer,
+    <R as Renderer>::TextureId: Texture + Clone + 'static,
+{
+    if !pinnacle.debug_settings.damage_visualization {
+        output.with_state_mut(|state| state.damage_debug = None);
+        return Vec::new();
+    }
+
+    let scale = Scale::from(output.current_scale().fractional_scale());
+    let now = Instant::now();
+
+    output.with_state_mut(|state| {
+        let debug = state
+            .damage_debug
+            .get_or_insert_with(|| DamageDebug::new(output));
+
+        if let Ok((Some(damage), _)) = debug.damage_tracker.damage_output(1, elements) {
+            debug.flashes.extend(damage.iter().map(|rect| Flash {
+                id: Id::new(),
+                commit: CommitCounter::default(),
+                rect: *rect,
+                start: now,
+            }));
+        }
+
+        debug
+            .flashes
+            .retain(|flash| now.duration_since(flash.start) < FLASH_DURATION);
+
+        let mut debug_elements = Vec::new();
+
+        for flash in debug.flashes.iter_mut() {
+            let progress =
+                now.duration_since(flash.start).as_secs_f32() / FLASH_DURATION.as_secs_f32();
+            let [r, g, b] = FLASH_COLOR;
+            let alpha = FLASH_ALPHA * (1.0 - progress);
+
+            // Solid color elements aren't damaged when their color changes
+            flash.commit.increment();
+
+            debug_elements.push(OutputRenderElement::from(SolidColorRenderElement::new(
+                flash.id.clone(),
+                flash.rect,
+                flash.commit,
+                [r * alpha, g * alpha, b * alpha, alpha],
+                element::Kind::Unspecified,
+            )));
+        }
+
+        let mut outlines = HashMap::new();
+
+        for elem in elements {
+            let geo = elem.geometry(scale);
+            let ids = debug
+                .outlines
+                .remove(elem.id())
+                .unwrap_or_else(|| std::array::from_fn(|_| Id::new()));
+
+            let edges = [
+                Rectangle::from_loc_and_size(geo.loc, (geo.size.w, OUTLINE_WIDTH)),
+                Rectangle::from_loc_and_size(
+                    (geo.loc.x, geo.loc.y + geo.size.h - OUTLINE_WIDTH),
+                    (geo.size.w, OUTLINE_WIDTH),
+                ),
+                Rectangle::from_loc_and_size(geo.loc, (OUTLINE_WIDTH, geo.size.h)),
+                Rectangle::from_loc_and_size(
+                    (geo.loc.x + geo.size.w - OUTLINE_WIDTH, geo.loc.y),
+                    (OUTLINE_WIDTH, geo.size.h),
+                ),
+            ];
+
+            for (id, edge) in ids.iter().zip(edges) {
+                debug_elements.push(OutputRenderElement::from(SolidColorRenderElement::new(
+                    id.clone(),
+                    edge,
+                    CommitCounter::default(),
+                    OUTLINE_COLOR,
+                    element::Kind::Unspecified,
+                )));
+            }
+
+            outlines.insert(elem.id().clone(), ids);
+        }
+
+        debug.outlines = outlines;
+
+        debug_elements
+    })
+}
@@ -0,0 +1,120 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Dimming unfocused windows.
+//!
+//! Window elements are drawn with a custom texture shader that multiplies their color.
+//! Rounded windows are dimmed by the rounded corner shader instead, as only one
+//! shader can be used at a time. This needs a GLES renderer; with other renderers,
+//! windows are never dimmed.
+
+use smithay::{
+    backend::renderer::{
+        element::{Element, Id, RenderElement},
+        gles::{GlesRenderer, GlesTexProgram, Uniform, UniformName, UniformType},
+        utils::CommitCounter,
+    },
+    utils::{Buffer, Physical, Point, Rectangle, Scale, Transform},
+};
+use tracing::error;
+
+use super::PinnacleRenderer;
+
+const SHADER: &str = include_str!("shaders/dim.frag");
+
+/// The compiled shader, stored in the renderer's EGL context.
+///
+/// This is `None` if the shader failed to compile so that it isn't recompiled every frame.
+struct DimProgram(Option<GlesTexProgram>);
+
+/// Get the dim shader for this renderer, compiling it the first time.
+pub fn dim_program(renderer: &mut GlesRenderer) -> Option<GlesTexProgram> {
+    if let Some(program) = renderer.egl_context().user_data().get::<DimProgram>() {
+        return program.0.clone();
+    }
+
+    let program = renderer
+        .compile_custom_texture_shader(SHADER, &[UniformName::new("brightness", UniformType::_1f)])
+        .map_err(|err| error!("Failed to compile dim shader: {err}"))
+        .ok();
+
+    renderer
+        .egl_context()
+        .user_data()
+        .insert_if_missing(|| DimProgram(program.clone()));
+
+    program
+}
+
+/// A render element with its color multiplied to darken it.
+#[derive(Debug)]
+pub struct DimElement<E> {
+    inner: E,
+    /// What the color is multiplied by, from 0.0 (black) to 1.0 (unchanged).
+    brightness: f32,
+    program: GlesTexProgram,
+}
+
+impl<E> DimElement<E> {
+    pub fn new(inner: E, brightness: f32, program: GlesTexProgram) -> Self {
+        Self {
+            inner,
+            brightness,
+            program,
+        }
+    }
+}
+
+impl<E: Element> Element for DimElement<E> {
+    fn id(&self) -> &Id {
+        self.inner.id()
+    }
+
+    fn current_commit(&self) -> CommitCounter {
+        self.inner.current_commit()
+    }
+
+    fn location(&self, scale: Scale<f64>) -> Point<i32, Physical> {
+        self.inner.location(scale)
+    }
+
+    fn src(&self) -> Rectangle<f64, Buffer> {
+        self.inner.src()
+    }
+
+    fn transform(&self) -> Transform {
+        self.inner.transform()
+    }
+
+    fn geometry(&self, scale: Scale<f64>) -> Rectangle<i32, Physical> {
+        self.inner.geometry(scale)
+    }
+}
+
+impl<R, E> RenderElement<R> for DimElement<E>
+where
+    R: PinnacleRenderer,
+    E: RenderElement<R>,
+{
+    fn draw(
+        &self,
+        frame: &mut R::Frame<'_>,
+        src: Rectangle<f64, Buffer>,
+        dst: Rectangle<i32, Physical>,
+        damage: &[Rectangle<i32, Physical>],
+    ) -> Result<(), R::Error> {
+        if let Some(frame) = R::gles_frame(frame) {
+            frame.override_default_tex_program(
+                self.program.clone(),
+                vec![Uniform::new("brightness", self.brightness)],
+            );
+        }
+
+        let res = self.inner.draw(frame, src, dst, damage);
+
+        if let Some(frame) = R::gles_frame(frame) {
+            frame.clear_tex_program_override();
+        }
+
+        res
+    }
+}
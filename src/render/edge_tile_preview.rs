@@ -0,0 +1,35 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! The drop-zone preview shown while a floating window is dragged to an output edge.
+
+use smithay::{
+    backend::renderer::{element::surface::WaylandSurfaceRenderElement, Renderer, Texture},
+    output::Output,
+    utils::Scale,
+};
+
+use crate::state::Pinnacle;
+
+use super::{perf_overlay::solid_color, OutputRenderElement, PinnacleRenderer};
+
+const PREVIEW_COLOR: [f32; 4] = [0.4, 0.7, 1.0, 0.25];
+
+/// Get the element of the edge tile preview on an output, if it's on the output.
+pub fn edge_tile_preview_element<R>(
+    pinnacle: &Pinnacle,
+    output: &Output,
+) -> Option<OutputRenderElement<R, WaylandSurfaceRenderElement<R>>>
+where
+    R: PinnacleRenderer,
+    <R as Renderer>::TextureId: Texture + Clone + 'static,
+{
+    let output_geo = pinnacle.space.output_geometry(output)?;
+    let preview = &pinnacle.edge_tile_preview;
+
+    let mut rect = preview.rect?.intersection(output_geo)?;
+    rect.loc -= output_geo.loc;
+
+    let scale = Scale::from(output.current_scale().fractional_scale());
+
+    Some(solid_color(preview.id.clone(), rect, scale, PREVIEW_COLOR))
+}
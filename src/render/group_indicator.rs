@@ -0,0 +1,90 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Tab and stack indicators of window groups.
+
+use smithay::{
+    backend::renderer::{
+        element::{self, solid::SolidColorRenderElement, surface::WaylandSurfaceRenderElement},
+        Renderer,
+    },
+    utils::{Physical, Rectangle, Scale},
+};
+
+use crate::{
+    state::WithState,
+    window::{
+        group::{GroupMode, INDICATOR_ROW_HEIGHT},
+        WindowElement,
+    },
+};
+
+use super::{OutputRenderElement, PinnacleRenderer};
+
+const ACTIVE_COLOR: [f32; 4] = [0.4, 0.6, 0.9, 1.0];
+const INACTIVE_COLOR: [f32; 4] = [0.3, 0.3, 0.35, 1.0];
+/// The space between tabs in logical pixels.
+const TAB_SPACING: i32 = 2;
+
+/// Get the indicator drawn above `window` if it's the active window of a group.
+///
+/// `drawn_geometry` is where the window is drawn on the output.
+pub fn group_indicator_elements<R>(
+    window: &WindowElement,
+    drawn_geometry: Rectangle<i32, Physical>,
+    scale: Scale<f64>,
+) -> Vec<OutputRenderElement<R, WaylandSurfaceRenderElement<R>>>
+where
+    R: PinnacleRenderer,
+    <R as Renderer>::TextureId: Clone + 'static,
+{
+    let Some(group) = window.group() else {
+        return Vec::new();
+    };
+
+    let windows = group.windows();
+    let active = window.with_state(|state| state.id);
+    let (ids, commit) = group.indicator_ids();
+
+    let to_physical = |size: i32| (f64::from(size) * scale.y).round() as i32;
+    let row_height = to_physical(INDICATOR_ROW_HEIGHT);
+    let spacing = to_physical(TAB_SPACING);
+    let height = to_physical(group.indicator_height());
+
+    let x = drawn_geometry.loc.x;
+    let y = drawn_geometry.loc.y - height;
+    let width = drawn_geometry.size.w;
+    let count = windows.len() as i32;
+
+    windows
+        .iter()
+        .zip(ids)
+        .enumerate()
+        .map(|(i, (id, elem_id))| {
+            let i = i as i32;
+            let rect = match group.mode() {
+                GroupMode::Tabbed => {
+                    let start = width * i / count;
+                    let end = width * (i + 1) / count;
+                    let gap = if i == count - 1 { 0 } else { spacing };
+                    Rectangle::from_loc_and_size(
+                        (x + start, y),
+                        ((end - start - gap).max(1), row_height),
+                    )
+                }
+                GroupMode::Stacked => {
+                    Rectangle::from_loc_and_size((x, y + row_height * i), (width, row_height))
+                }
+            };
+
+            let color = if *id == active { ACTIVE_COLOR } else { INACTIVE_COLOR };
+
+            OutputRenderElement::from(SolidColorRenderElement::new(
+                elem_id,
+                rect,
+                commit,
+                color,
+                element::Kind::Unspecified,
+            ))
+        })
+        .collect()
+}
@@ -0,0 +1,176 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! The monocle indicator.
+//!
+//! Monocle layouts stack every window on top of each other, so while the indicator
+//! is enabled, outputs laid out with one show which window of how many is shown
+//! at the top of the output, like "2/5".
+
+use smithay::{
+    backend::renderer::{
+        element::{
+            self, memory::MemoryRenderBufferRenderElement, surface::WaylandSurfaceRenderElement, Id,
+        },
+        Renderer, Texture,
+    },
+    output::Output,
+    utils::{Logical, Point, Rectangle, Scale, Size},
+};
+use tracing::warn;
+
+use crate::state::{Pinnacle, WithState};
+
+use super::{
+    perf_overlay::{solid_color, DigitSlot, DIGIT_SIZE},
+    OutputRenderElement, PinnacleRenderer,
+};
+
+/// The name layouts report to get the indicator.
+pub const MONOCLE_LAYOUT_NAME: &str = "monocle";
+
+/// How many digits each number has room for.
+const DIGITS_PER_NUMBER: usize = 2;
+/// How many steps the slash is drawn with.
+const SLASH_STEPS: usize = 7;
+const SLASH_STEP_WIDTH: i32 = 4;
+const SLASH_WIDTH: i32 = SLASH_STEP_WIDTH + (SLASH_STEPS as i32 - 1) * 2;
+
+const MARGIN: i32 = 8;
+const PADDING: i32 = 6;
+const GAP: i32 = 4;
+
+const BACKGROUND_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.7];
+const SLASH_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+/// The elements an output's monocle indicator is drawn with.
+#[derive(Debug)]
+pub struct MonocleIndicator {
+    background: Id,
+    slash: [Id; SLASH_STEPS],
+    /// The slots of the shown window's position followed by those of the window count.
+    digits: Vec<DigitSlot>,
+}
+
+impl Default for MonocleIndicator {
+    fn default() -> Self {
+        Self {
+            background: Id::new(),
+            slash: std::array::from_fn(|_| Id::new()),
+            digits: (0..DIGITS_PER_NUMBER * 2)
+                .map(|_| DigitSlot::new())
+                .collect(),
+        }
+    }
+}
+
+/// Get the elements of the monocle indicator for an output if it should be shown.
+///
+/// The indicator is shown when it is enabled, the output was laid out with a layout named
+/// [`MONOCLE_LAYOUT_NAME`], and more than one window is laid out.
+pub fn monocle_indicator_elements<R>(
+    renderer: &mut R,
+    pinnacle: &Pinnacle,
+    output: &Output,
+) -> Vec<OutputRenderElement<R, WaylandSurfaceRenderElement<R>>>
+where
+    R: PinnacleRenderer,
+    <R as Renderer>::TextureId: Texture + Clone + 'static,
+{
+    if !pinnacle.layout_state.monocle_indicator
+        || output.with_state(|state| state.layout_name.as_deref() != Some(MONOCLE_LAYOUT_NAME))
+    {
+        return Vec::new();
+    }
+
+    let windows = pinnacle.layout_windows(output);
+    if windows.len() < 2 {
+        return Vec::new();
+    }
+
+    let Some(output_geo) = pinnacle.space.output_geometry(output) else {
+        return Vec::new();
+    };
+
+    // The shown window is the one on top.
+    let position = pinnacle
+        .space
+        .elements()
+        .rev()
+        .find_map(|win| windows.iter().position(|w| w == win))
+        .unwrap_or_default()
+        + 1;
+
+    let max = 10usize.pow(DIGITS_PER_NUMBER as u32) - 1;
+    let position = position.min(max).to_string();
+    let count = windows.len().min(max).to_string();
+
+    let scale = Scale::from(output.current_scale().fractional_scale());
+    let (digit_w, digit_h) = DIGIT_SIZE;
+
+    let width =
+        PADDING * 2 + (position.len() + count.len()) as i32 * digit_w + GAP * 2 + SLASH_WIDTH;
+    let left = (output_geo.size.w - width) / 2;
+    let top = MARGIN;
+
+    output.with_state_mut(|state| {
+        let indicator = state.monocle_indicator.get_or_insert_with(Default::default);
+        let (position_slots, count_slots) = indicator.digits.split_at_mut(DIGITS_PER_NUMBER);
+
+        let mut elements = Vec::new();
+        let mut x = left + PADDING;
+        let y = top + PADDING;
+
+        let mut push_digits = |x: &mut i32, number: &str, slots: &mut [DigitSlot]| {
+            for (digit, slot) in number.bytes().zip(slots.iter_mut()) {
+                slot.set_digit(digit - b'0');
+
+                let loc = Point::<i32, Logical>::from((*x, y)).to_physical_precise_round(scale);
+
+                match MemoryRenderBufferRenderElement::from_buffer(
+                    renderer,
+                    loc.to_f64(),
+                    slot.buffer(),
+                    None,
+                    None,
+                    Some(Size::from(DIGIT_SIZE)),
+                    element::Kind::Unspecified,
+                ) {
+                    Ok(elem) => elements.push(OutputRenderElement::from(elem)),
+                    Err(err) => warn!("Failed to import monocle indicator digit: {err:?}"),
+                }
+
+                *x += digit_w;
+            }
+        };
+
+        push_digits(&mut x, &position, position_slots);
+        x += GAP;
+        let slash_x = x;
+        x += SLASH_WIDTH + GAP;
+        push_digits(&mut x, &count, count_slots);
+
+        // The slash leans right, so its top step is the rightmost.
+        let step_h = digit_h / SLASH_STEPS as i32;
+        for (step, id) in indicator.slash.iter().enumerate() {
+            let step_x = slash_x + (SLASH_STEPS - 1 - step) as i32 * 2;
+            let rect = Rectangle::<i32, Logical>::from_loc_and_size(
+                (step_x, y + step as i32 * step_h),
+                (SLASH_STEP_WIDTH, step_h),
+            );
+            elements.push(solid_color(id.clone(), rect, scale, SLASH_COLOR));
+        }
+
+        let background = Rectangle::<i32, Logical>::from_loc_and_size(
+            (left, top),
+            (width, PADDING * 2 + digit_h),
+        );
+        elements.push(solid_color(
+            indicator.background.clone(),
+            background,
+            scale,
+            BACKGROUND_COLOR,
+        ));
+
+        elements
+    })
+}
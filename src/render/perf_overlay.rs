@@ -0,0 +1,419 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! The performance overlay.
+//!
+//! While it is enabled, every output shows its frame statistics in its top-left corner.
+//! From top to bottom, the rows are frames per second, the median and 99th percentile
+//! time spent rendering a frame in milliseconds, and how much of the output the last
+//! frame damaged in percent. The bar below them is green while a client's buffer is
+//! scanned out directly and orange while the output is composited.
+
+use std::{
+    collections::VecDeque,
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+
+use image::RgbaImage;
+use smithay::{
+    backend::{
+        allocator::Fourcc,
+        renderer::{
+            element::{
+                self,
+                memory::{MemoryRenderBuffer, MemoryRenderBufferRenderElement},
+                solid::SolidColorRenderElement,
+                surface::WaylandSurfaceRenderElement,
+                Id,
+            },
+            utils::CommitCounter,
+            Renderer, Texture,
+        },
+    },
+    output::Output,
+    utils::{Buffer, Logical, Point, Rectangle, Scale, Size, Transform},
+};
+use tracing::warn;
+
+use crate::state::{Pinnacle, WithState};
+
+use super::{OutputRenderElement, PinnacleRenderer};
+
+/// How many frame times percentiles are taken over.
+const FRAME_TIME_HISTORY: usize = 120;
+
+/// The size of a digit in `numbers.png`.
+pub(super) const DIGIT_SIZE: (i32, i32) = (22, 35);
+/// How many digits each row has room for.
+const DIGITS_PER_ROW: usize = 4;
+const ROWS: usize = 4;
+
+const MARGIN: i32 = 8;
+const PADDING: i32 = 6;
+const SWATCH_WIDTH: i32 = 6;
+const GAP: i32 = 4;
+const BAR_HEIGHT: i32 = 6;
+
+const BACKGROUND_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.7];
+/// The colors next to the frames per second, median frame time, 99th percentile frame time,
+/// and damage rows.
+const SWATCH_COLORS: [[f32; 4]; ROWS] = [
+    [1.0, 1.0, 1.0, 1.0],
+    [0.3, 0.8, 0.95, 1.0],
+    [0.95, 0.8, 0.2, 1.0],
+    [0.9, 0.3, 0.9, 1.0],
+];
+const SCANOUT_COLOR: [f32; 4] = [0.3, 0.9, 0.3, 1.0];
+const COMPOSITED_COLOR: [f32; 4] = [0.95, 0.5, 0.2, 1.0];
+
+/// Statistics about the frames an output recently rendered.
+#[derive(Debug, Default)]
+pub struct FrameStats {
+    /// How long recent frames took to render, oldest first.
+    frame_times: VecDeque<Duration>,
+    /// When frames in the last second were rendered, oldest first.
+    presented: VecDeque<Instant>,
+    /// Whether the last frame was scanned out directly.
+    scanout: bool,
+    /// The fraction of the output the last frame damaged.
+    damage: f64,
+}
+
+impl FrameStats {
+    /// Record a rendered frame.
+    pub fn record(&mut self, now: Instant, render_time: Duration, scanout: bool, damage: f64) {
+        if self.frame_times.len() == FRAME_TIME_HISTORY {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(render_time);
+
+        self.presented.push_back(now);
+        while self
+            .presented
+            .front()
+            .is_some_and(|time| now.duration_since(*time) > Duration::from_secs(1))
+        {
+            self.presented.pop_front();
+        }
+
+        self.scanout = scanout;
+        self.damage = damage;
+    }
+
+    /// How many frames were rendered in the second before `now`.
+    pub fn fps(&self, now: Instant) -> usize {
+        self.presented
+            .iter()
+            .filter(|time| now.duration_since(**time) <= Duration::from_secs(1))
+            .count()
+    }
+
+    /// Get a percentile of recent frame times, with `percentile` from 0.0 to 1.0.
+    pub fn frame_time(&self, percentile: f64) -> Duration {
+        let mut frame_times = self.frame_times.iter().copied().collect::<Vec<_>>();
+        frame_times.sort_unstable();
+
+        let Some(last) = frame_times.len().checked_sub(1) else {
+            return Duration::ZERO;
+        };
+        let index = (last as f64 * percentile.clamp(0.0, 1.0)).round() as usize;
+        frame_times[index]
+    }
+}
+
+/// Get the fraction of an area of `size` covered by `damage`.
+///
+/// Overlapping damage is counted more than once.
+pub fn damage_fraction<'a, Kind: 'a>(
+    damage: impl IntoIterator<Item = &'a Rectangle<i32, Kind>>,
+    size: Size<i32, Kind>,
+) -> f64 {
+    let area = f64::from(size.w) * f64::from(size.h);
+    if area <= 0.0 {
+        return 0.0;
+    }
+
+    let damaged = damage
+        .into_iter()
+        .map(|rect| f64::from(rect.size.w) * f64::from(rect.size.h))
+        .sum::<f64>();
+
+    (damaged / area).min(1.0)
+}
+
+/// The digits in `numbers.png`.
+fn digit_atlas() -> &'static RgbaImage {
+    static ATLAS: OnceLock<RgbaImage> = OnceLock::new();
+    ATLAS.get_or_init(|| {
+        image::load_from_memory(include_bytes!("../../resources/numbers.png"))
+            .expect("numbers.png is a valid png")
+            .to_rgba8()
+    })
+}
+
+/// Get the location of a digit in `numbers.png`.
+fn digit_location(digit: u8) -> (i32, i32) {
+    let (w, h) = DIGIT_SIZE;
+    match digit {
+        9 => (0, 0),
+        6 => (w, 0),
+        3 => (w * 2, 0),
+        1 => (w * 3, 0),
+        8 => (0, h),
+        0 => (w, h),
+        2 => (w * 2, h),
+        7 => (0, h * 2),
+        4 => (w, h * 2),
+        5 => (w * 2, h * 2),
+        _ => unreachable!("not a digit"),
+    }
+}
+
+/// One place a digit can be drawn at.
+///
+/// Each slot has its own buffer so changing a digit damages only that slot.
+#[derive(Debug)]
+pub(super) struct DigitSlot {
+    buffer: MemoryRenderBuffer,
+    digit: Option<u8>,
+}
+
+impl DigitSlot {
+    pub(super) fn new() -> Self {
+        Self {
+            buffer: MemoryRenderBuffer::new(
+                Fourcc::Abgr8888,
+                DIGIT_SIZE,
+                1,
+                Transform::Normal,
+                None,
+            ),
+            digit: None,
+        }
+    }
+
+    pub(super) fn set_digit(&mut self, digit: u8) {
+        if self.digit == Some(digit) {
+            return;
+        }
+        self.digit = Some(digit);
+
+        let atlas = digit_atlas();
+        let (x, y) = digit_location(digit);
+        let (w, h) = DIGIT_SIZE;
+
+        let res = self.buffer.render().draw(|pixels| {
+            for (row, dst) in pixels.chunks_exact_mut(w as usize * 4).enumerate() {
+                for (col, pixel) in dst.chunks_exact_mut(4).enumerate() {
+                    let src = atlas.get_pixel(x as u32 + col as u32, y as u32 + row as u32);
+                    pixel.copy_from_slice(&src.0);
+                }
+            }
+
+            Result::<_, ()>::Ok(vec![Rectangle::<i32, Buffer>::from_loc_and_size(
+                (0, 0),
+                (w, h),
+            )])
+        });
+
+        if res.is_err() {
+            warn!("Failed to draw performance overlay digit");
+        }
+    }
+
+    /// The buffer the digit is drawn in.
+    pub(super) fn buffer(&self) -> &MemoryRenderBuffer {
+        &self.buffer
+    }
+}
+
+/// The elements an output's performance overlay is drawn with.
+#[derive(Debug)]
+pub struct PerfOverlay {
+    background: Id,
+    swatches: [Id; ROWS],
+    /// The bar's elements for scanout and composition.
+    ///
+    /// Solid color elements aren't damaged when their color changes,
+    /// so each color gets its own element.
+    scanout_bar: Id,
+    composited_bar: Id,
+    digits: Vec<DigitSlot>,
+}
+
+impl Default for PerfOverlay {
+    fn default() -> Self {
+        Self {
+            background: Id::new(),
+            swatches: std::array::from_fn(|_| Id::new()),
+            scanout_bar: Id::new(),
+            composited_bar: Id::new(),
+            digits: (0..ROWS * DIGITS_PER_ROW)
+                .map(|_| DigitSlot::new())
+                .collect(),
+        }
+    }
+}
+
+/// Get the elements of the performance overlay for an output if it is enabled.
+pub fn perf_overlay_elements<R>(
+    renderer: &mut R,
+    pinnacle: &Pinnacle,
+    output: &Output,
+) -> Vec<OutputRenderElement<R, WaylandSurfaceRenderElement<R>>>
+where
+    R: PinnacleRenderer,
+    <R as Renderer>::TextureId: Texture + Clone + 'static,
+{
+    if !pinnacle.debug_settings.perf_overlay {
+        return Vec::new();
+    }
+
+    let scale = Scale::from(output.current_scale().fractional_scale());
+    let now = Instant::now();
+
+    output.with_state_mut(|state| {
+        let stats = &state.frame_stats;
+        let values = [
+            stats.fps(now) as u64,
+            stats.frame_time(0.5).as_millis() as u64,
+            stats.frame_time(0.99).as_millis() as u64,
+            (stats.damage * 100.0).round() as u64,
+        ];
+        let scanout = stats.scanout;
+
+        let overlay = state.perf_overlay.get_or_insert_with(Default::default);
+
+        let (digit_w, digit_h) = DIGIT_SIZE;
+        let mut elements = Vec::new();
+
+        for (row, value) in values.into_iter().enumerate() {
+            let y = MARGIN + PADDING + row as i32 * (digit_h + GAP);
+            let digits_x = MARGIN + PADDING + SWATCH_WIDTH + GAP;
+
+            let value = value.min(10u64.pow(DIGITS_PER_ROW as u32) - 1).to_string();
+            let slots = &mut overlay.digits[row * DIGITS_PER_ROW..][..DIGITS_PER_ROW];
+
+            for (col, (digit, slot)) in value.bytes().zip(slots.iter_mut()).enumerate() {
+                slot.set_digit(digit - b'0');
+
+                let loc = Point::<i32, Logical>::from((digits_x + col as i32 * digit_w, y))
+                    .to_physical_precise_round(scale);
+
+                match MemoryRenderBufferRenderElement::from_buffer(
+                    renderer,
+                    loc.to_f64(),
+                    &slot.buffer,
+                    None,
+                    None,
+                    Some(Size::from(DIGIT_SIZE)),
+                    element::Kind::Unspecified,
+                ) {
+                    Ok(elem) => elements.push(OutputRenderElement::from(elem)),
+                    Err(err) => warn!("Failed to import performance overlay digit: {err:?}"),
+                }
+            }
+
+            let swatch = Rectangle::<i32, Logical>::from_loc_and_size(
+                (MARGIN + PADDING, y),
+                (SWATCH_WIDTH, digit_h),
+            );
+            elements.push(solid_color(
+                overlay.swatches[row].clone(),
+                swatch,
+                scale,
+                SWATCH_COLORS[row],
+            ));
+        }
+
+        let width = PADDING * 2 + SWATCH_WIDTH + GAP + digit_w * DIGITS_PER_ROW as i32;
+        let rows_height = ROWS as i32 * digit_h + (ROWS as i32 - 1) * GAP;
+
+        let bar = Rectangle::<i32, Logical>::from_loc_and_size(
+            (MARGIN + PADDING, MARGIN + PADDING + rows_height + GAP),
+            (width - PADDING * 2, BAR_HEIGHT),
+        );
+        let (bar_id, bar_color) = if scanout {
+            (overlay.scanout_bar.clone(), SCANOUT_COLOR)
+        } else {
+            (overlay.composited_bar.clone(), COMPOSITED_COLOR)
+        };
+        elements.push(solid_color(bar_id, bar, scale, bar_color));
+
+        let background = Rectangle::<i32, Logical>::from_loc_and_size(
+            (MARGIN, MARGIN),
+            (width, PADDING * 2 + rows_height + GAP + BAR_HEIGHT),
+        );
+        elements.push(solid_color(
+            overlay.background.clone(),
+            background,
+            scale,
+            BACKGROUND_COLOR,
+        ));
+
+        elements
+    })
+}
+
+pub(super) fn solid_color<R>(
+    id: Id,
+    rect: Rectangle<i32, Logical>,
+    scale: Scale<f64>,
+    color: [f32; 4],
+) -> OutputRenderElement<R, WaylandSurfaceRenderElement<R>>
+where
+    R: PinnacleRenderer,
+    <R as Renderer>::TextureId: Texture + Clone + 'static,
+{
+    OutputRenderElement::from(SolidColorRenderElement::new(
+        id,
+        rect.to_physical_precise_round(scale),
+        CommitCounter::default(),
+        color,
+        element::Kind::Unspecified,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_time_percentiles() {
+        let mut stats = FrameStats::default();
+        let now = Instant::now();
+        for ms in 1..=100 {
+            stats.record(now, Duration::from_millis(ms), false, 0.0);
+        }
+
+        assert_eq!(stats.frame_time(0.5), Duration::from_millis(51));
+        assert_eq!(stats.frame_time(0.99), Duration::from_millis(99));
+        assert_eq!(stats.frame_time(1.0), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn fps_only_counts_the_last_second() {
+        let mut stats = FrameStats::default();
+        let start = Instant::now();
+        for frame in 0..120 {
+            stats.record(
+                start + Duration::from_millis(frame * 10),
+                Duration::ZERO,
+                false,
+                0.0,
+            );
+        }
+
+        assert_eq!(stats.fps(start + Duration::from_millis(1190)), 101);
+    }
+
+    #[test]
+    fn damage_fraction_is_clamped() {
+        let size = Size::<i32, Logical>::from((100, 100));
+        let half = Rectangle::from_loc_and_size((0, 0), (100, 50));
+
+        assert_eq!(damage_fraction(&[half], size), 0.5);
+        assert_eq!(damage_fraction(&[half, half, half], size), 1.0);
+        assert_eq!(damage_fraction(&[], size), 0.0);
+    }
+}
@@ -0,0 +1,161 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Rounded window corners.
+//!
+//! Window surfaces are drawn with a custom texture shader that cuts off their corners and
+//! anything outside the window geometry. This needs a GLES renderer; with other renderers,
+//! windows are drawn with square corners.
+
+use smithay::{
+    backend::renderer::{
+        element::{Element, Id, RenderElement},
+        gles::{GlesRenderer, GlesTexProgram, Uniform, UniformName, UniformType},
+        utils::CommitCounter,
+    },
+    utils::{Buffer, Physical, Point, Rectangle, Scale, Transform},
+};
+use tracing::error;
+
+use super::PinnacleRenderer;
+
+const SHADER: &str = include_str!("shaders/rounded_corners.frag");
+
+/// The compiled shader, stored in the renderer's EGL context.
+///
+/// This is `None` if the shader failed to compile so that it isn't recompiled every frame.
+struct RoundedCornerProgram(Option<GlesTexProgram>);
+
+/// Get the rounded corner shader for this renderer, compiling it the first time.
+pub fn rounded_corner_program(renderer: &mut GlesRenderer) -> Option<GlesTexProgram> {
+    if let Some(program) = renderer
+        .egl_context()
+        .user_data()
+        .get::<RoundedCornerProgram>()
+    {
+        return program.0.clone();
+    }
+
+    let program = renderer
+        .compile_custom_texture_shader(
+            SHADER,
+            &[
+                UniformName::new("geo_size", UniformType::_2f),
+                UniformName::new("elem_loc", UniformType::_2f),
+                UniformName::new("elem_size", UniformType::_2f),
+                UniformName::new("corner_radius", UniformType::_1f),
+                UniformName::new("brightness", UniformType::_1f),
+            ],
+        )
+        .map_err(|err| error!("Failed to compile rounded corner shader: {err}"))
+        .ok();
+
+    renderer
+        .egl_context()
+        .user_data()
+        .insert_if_missing(|| RoundedCornerProgram(program.clone()));
+
+    program
+}
+
+/// A render element clipped to a window's geometry with rounded corners.
+///
+/// Popups shouldn't be wrapped in this, as they are usually outside the window geometry.
+#[derive(Debug)]
+pub struct RoundedCornerElement<E> {
+    inner: E,
+    /// The window geometry relative to the output.
+    geometry: Rectangle<i32, Physical>,
+    /// The corner radius in physical pixels.
+    radius: f32,
+    /// What the color is multiplied by to dim the window.
+    brightness: f32,
+    program: GlesTexProgram,
+}
+
+impl<E> RoundedCornerElement<E> {
+    pub fn new(
+        inner: E,
+        geometry: Rectangle<i32, Physical>,
+        radius: f32,
+        brightness: f32,
+        program: GlesTexProgram,
+    ) -> Self {
+        Self {
+            inner,
+            geometry,
+            radius,
+            brightness,
+            program,
+        }
+    }
+
+    fn uniforms(&self, dst: Rectangle<i32, Physical>) -> Vec<Uniform<'static>> {
+        let elem_loc = dst.loc - self.geometry.loc;
+        // Don't let the corners overlap on small windows
+        let max_radius = self.geometry.size.w.min(self.geometry.size.h) as f32 / 2.0;
+
+        vec![
+            Uniform::new(
+                "geo_size",
+                (self.geometry.size.w as f32, self.geometry.size.h as f32),
+            ),
+            Uniform::new("elem_loc", (elem_loc.x as f32, elem_loc.y as f32)),
+            Uniform::new("elem_size", (dst.size.w as f32, dst.size.h as f32)),
+            Uniform::new("corner_radius", self.radius.min(max_radius)),
+            Uniform::new("brightness", self.brightness),
+        ]
+    }
+}
+
+// Opaque regions are left as the default of none, as the corners are now transparent
+impl<E: Element> Element for RoundedCornerElement<E> {
+    fn id(&self) -> &Id {
+        self.inner.id()
+    }
+
+    fn current_commit(&self) -> CommitCounter {
+        self.inner.current_commit()
+    }
+
+    fn location(&self, scale: Scale<f64>) -> Point<i32, Physical> {
+        self.inner.location(scale)
+    }
+
+    fn src(&self) -> Rectangle<f64, Buffer> {
+        self.inner.src()
+    }
+
+    fn transform(&self) -> Transform {
+        self.inner.transform()
+    }
+
+    fn geometry(&self, scale: Scale<f64>) -> Rectangle<i32, Physical> {
+        self.inner.geometry(scale)
+    }
+}
+
+impl<R, E> RenderElement<R> for RoundedCornerElement<E>
+where
+    R: PinnacleRenderer,
+    E: RenderElement<R>,
+{
+    fn draw(
+        &self,
+        frame: &mut R::Frame<'_>,
+        src: Rectangle<f64, Buffer>,
+        dst: Rectangle<i32, Physical>,
+        damage: &[Rectangle<i32, Physical>],
+    ) -> Result<(), R::Error> {
+        if let Some(frame) = R::gles_frame(frame) {
+            frame.override_default_tex_program(self.program.clone(), self.uniforms(dst));
+        }
+
+        let res = self.inner.draw(frame, src, dst, damage);
+
+        if let Some(frame) = R::gles_frame(frame) {
+            frame.clear_tex_program_override();
+        }
+
+        res
+    }
+}
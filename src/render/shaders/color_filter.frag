@@ -0,0 +1,49 @@
+#version 100
+
+//_DEFINES_
+
+#if defined(EXTERNAL)
+#extension GL_OES_EGL_image_external : require
+#endif
+
+precision highp float;
+
+#if defined(EXTERNAL)
+uniform samplerExternalOES tex;
+#else
+uniform sampler2D tex;
+#endif
+
+uniform float alpha;
+varying vec2 v_coords;
+
+#if defined(DEBUG_FLAGS)
+uniform float tint;
+#endif
+
+// The color is transformed with `color_matrix * rgb + color_offset`
+uniform mat3 color_matrix;
+uniform vec3 color_offset;
+
+void main() {
+    vec4 color = texture2D(tex, v_coords);
+#if defined(NO_ALPHA)
+    color = vec4(color.rgb, 1.0);
+#endif
+
+    // Colors are premultiplied, so unpremultiply them before transforming them
+    if (color.a > 0.0) {
+        color.rgb = color.rgb / color.a;
+    }
+    color.rgb = clamp(color_matrix * color.rgb + color_offset, 0.0, 1.0);
+    color.rgb = color.rgb * color.a;
+
+    color = color * alpha;
+
+#if defined(DEBUG_FLAGS)
+    if (tint == 1.0)
+        color = vec4(0.0, 0.3, 0.0, 0.2) + color * 0.8;
+#endif
+
+    gl_FragColor = color;
+}
@@ -0,0 +1,77 @@
+#version 100
+
+//_DEFINES_
+
+#if defined(EXTERNAL)
+#extension GL_OES_EGL_image_external : require
+#endif
+
+precision highp float;
+
+#if defined(EXTERNAL)
+uniform samplerExternalOES tex;
+#else
+uniform sampler2D tex;
+#endif
+
+uniform float alpha;
+varying vec2 v_coords;
+
+#if defined(DEBUG_FLAGS)
+uniform float tint;
+#endif
+
+// The size of the window geometry
+uniform vec2 geo_size;
+// The location and size of this element relative to the window geometry
+uniform vec2 elem_loc;
+uniform vec2 elem_size;
+uniform float corner_radius;
+// What the color is multiplied by to dim the window
+uniform float brightness;
+
+float rounding_alpha(vec2 coords) {
+    vec2 center;
+
+    if (coords.x < corner_radius && coords.y < corner_radius) {
+        center = vec2(corner_radius, corner_radius);
+    } else if (coords.x > geo_size.x - corner_radius && coords.y < corner_radius) {
+        center = vec2(geo_size.x - corner_radius, corner_radius);
+    } else if (coords.x > geo_size.x - corner_radius && coords.y > geo_size.y - corner_radius) {
+        center = geo_size - vec2(corner_radius, corner_radius);
+    } else if (coords.x < corner_radius && coords.y > geo_size.y - corner_radius) {
+        center = vec2(corner_radius, geo_size.y - corner_radius);
+    } else {
+        return 1.0;
+    }
+
+    float dist = distance(coords, center);
+    return 1.0 - smoothstep(corner_radius - 0.5, corner_radius + 0.5, dist);
+}
+
+void main() {
+    vec2 coords = elem_loc + v_coords * elem_size;
+
+    vec4 color;
+
+    // Clip anything outside the window geometry, like client-side shadows
+    if (coords.x < 0.0 || coords.y < 0.0 || coords.x > geo_size.x || coords.y > geo_size.y) {
+        color = vec4(0.0);
+    } else {
+        color = texture2D(tex, v_coords);
+#if defined(NO_ALPHA)
+        color = vec4(color.rgb, 1.0);
+#endif
+        color = vec4(color.rgb * brightness, color.a);
+        color = color * rounding_alpha(coords);
+    }
+
+    color = color * alpha;
+
+#if defined(DEBUG_FLAGS)
+    if (tint == 1.0)
+        color = vec4(0.0, 0.3, 0.0, 0.2) + color * 0.8;
+#endif
+
+    gl_FragColor = color;
+}
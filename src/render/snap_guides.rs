@@ -0,0 +1,43 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Guide lines shown along the edges a moving floating window snapped to.
+
+use smithay::{
+    backend::renderer::{element::surface::WaylandSurfaceRenderElement, Renderer, Texture},
+    output::Output,
+    utils::Scale,
+};
+
+use crate::state::Pinnacle;
+
+use super::{perf_overlay::solid_color, OutputRenderElement, PinnacleRenderer};
+
+const GUIDE_COLOR: [f32; 4] = [0.4, 0.7, 1.0, 0.9];
+
+/// Get the elements of the snap guides that cross an output.
+pub fn snap_guide_elements<R>(
+    pinnacle: &Pinnacle,
+    output: &Output,
+) -> Vec<OutputRenderElement<R, WaylandSurfaceRenderElement<R>>>
+where
+    R: PinnacleRenderer,
+    <R as Renderer>::TextureId: Texture + Clone + 'static,
+{
+    let Some(output_geo) = pinnacle.space.output_geometry(output) else {
+        return Vec::new();
+    };
+
+    let scale = Scale::from(output.current_scale().fractional_scale());
+    let guides = &pinnacle.snap_guides;
+
+    guides
+        .lines
+        .iter()
+        .zip(guides.ids.iter())
+        .filter_map(|(line, id)| {
+            let mut line = line.as_ref()?.intersection(output_geo)?;
+            line.loc -= output_geo.loc;
+            Some(solid_color(id.clone(), line, scale, GUIDE_COLOR))
+        })
+        .collect()
+}
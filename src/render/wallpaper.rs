@@ -0,0 +1,284 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Built-in wallpapers.
+//!
+//! An output's wallpaper is drawn beneath everything else on it, including
+//! background layer surfaces.
+
+use std::path::Path;
+
+use anyhow::Context;
+use image::RgbaImage;
+use smithay::{
+    backend::{
+        allocator::Fourcc,
+        renderer::{
+            element::{
+                self,
+                memory::{MemoryRenderBuffer, MemoryRenderBufferRenderElement},
+                solid::SolidColorRenderElement,
+                surface::WaylandSurfaceRenderElement,
+                Id,
+            },
+            utils::CommitCounter,
+            Renderer, Texture,
+        },
+    },
+    output::Output,
+    utils::{Buffer, Logical, Physical, Point, Rectangle, Scale, Size, Transform},
+};
+use tracing::warn;
+
+use crate::state::WithState;
+
+use super::{OutputRenderElement, PinnacleRenderer};
+
+/// The color shown around images that don't cover the whole output.
+const LETTERBOX_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+
+/// How an image is fit onto an output.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum WallpaperMode {
+    /// Scale the image to cover the output, cropping what doesn't fit.
+    #[default]
+    Fill,
+    /// Scale the image to fit inside the output, showing bars around it.
+    Fit,
+    /// Stretch the image to the output's size.
+    Stretch,
+}
+
+/// A decoded wallpaper image.
+#[derive(Debug, Clone)]
+pub struct WallpaperImage {
+    buffer: MemoryRenderBuffer,
+    size: Size<i32, Buffer>,
+}
+
+impl WallpaperImage {
+    /// Decode a PNG image.
+    ///
+    /// This can take a while for large images, so avoid doing it on the compositor's thread.
+    pub fn decode(path: &Path) -> anyhow::Result<RgbaImage> {
+        Ok(image::open(path)
+            .with_context(|| format!("failed to open {}", path.display()))?
+            .into_rgba8())
+    }
+
+    /// Create a wallpaper image from a decoded image.
+    pub fn new(image: &RgbaImage) -> Self {
+        let size = Size::from((image.width() as i32, image.height() as i32));
+        let opaque = image.pixels().all(|pixel| pixel.0[3] == u8::MAX);
+
+        let buffer = MemoryRenderBuffer::from_slice(
+            image.as_raw(),
+            Fourcc::Abgr8888,
+            size,
+            1,
+            Transform::Normal,
+            opaque.then(|| vec![Rectangle::from_loc_and_size((0, 0), size)]),
+        );
+
+        Self { buffer, size }
+    }
+}
+
+/// An output's wallpaper.
+#[derive(Debug, Clone)]
+pub enum Wallpaper {
+    /// A solid color.
+    Color { id: Id, color: [f32; 4] },
+    /// An image fit onto the output.
+    Image {
+        image: WallpaperImage,
+        mode: WallpaperMode,
+        /// The id of the bars around images that don't cover the whole output.
+        letterbox: Id,
+    },
+}
+
+impl Wallpaper {
+    /// Create a solid color wallpaper from red, green, and blue components from 0.0 to 1.0.
+    pub fn color(r: f32, g: f32, b: f32) -> Self {
+        Self::Color {
+            id: Id::new(),
+            color: [r.clamp(0.0, 1.0), g.clamp(0.0, 1.0), b.clamp(0.0, 1.0), 1.0],
+        }
+    }
+
+    /// Create an image wallpaper.
+    pub fn image(image: WallpaperImage, mode: WallpaperMode) -> Self {
+        Self::Image {
+            image,
+            mode,
+            letterbox: Id::new(),
+        }
+    }
+}
+
+/// Get where an image of `image_size` is drawn on an output of `output_size` and which part
+/// of it is shown.
+///
+/// Returns the destination rectangle and the source rectangle in the image.
+fn image_placement(
+    image_size: Size<f64, Logical>,
+    output_size: Size<f64, Logical>,
+    mode: WallpaperMode,
+) -> (Rectangle<f64, Logical>, Rectangle<f64, Logical>) {
+    let full_image = Rectangle::from_loc_and_size((0.0, 0.0), image_size);
+    let full_output = Rectangle::from_loc_and_size((0.0, 0.0), output_size);
+
+    let scale_x = output_size.w / image_size.w;
+    let scale_y = output_size.h / image_size.h;
+
+    match mode {
+        WallpaperMode::Stretch => (full_output, full_image),
+        WallpaperMode::Fill => {
+            let scale = scale_x.max(scale_y);
+            let src_size = Size::from((output_size.w / scale, output_size.h / scale));
+            let src_loc = Point::from((
+                (image_size.w - src_size.w) / 2.0,
+                (image_size.h - src_size.h) / 2.0,
+            ));
+            (full_output, Rectangle::from_loc_and_size(src_loc, src_size))
+        }
+        WallpaperMode::Fit => {
+            let scale = scale_x.min(scale_y);
+            let dst_size = Size::from((image_size.w * scale, image_size.h * scale));
+            let dst_loc = Point::from((
+                (output_size.w - dst_size.w) / 2.0,
+                (output_size.h - dst_size.h) / 2.0,
+            ));
+            (Rectangle::from_loc_and_size(dst_loc, dst_size), full_image)
+        }
+    }
+}
+
+/// Get the elements of an output's wallpaper, if it has one.
+///
+/// These go beneath every other element on the output.
+pub fn wallpaper_elements<R>(
+    renderer: &mut R,
+    output: &Output,
+    scale: Scale<f64>,
+) -> Vec<OutputRenderElement<R, WaylandSurfaceRenderElement<R>>>
+where
+    R: PinnacleRenderer,
+    <R as Renderer>::TextureId: Texture + Clone + 'static,
+{
+    let Some(wallpaper) = output.with_state(|state| state.wallpaper.clone()) else {
+        return Vec::new();
+    };
+
+    let Some(output_mode) = output.current_mode() else {
+        return Vec::new();
+    };
+    let output_geo = Rectangle::<i32, Physical>::from_loc_and_size(
+        (0, 0),
+        output.current_transform().transform_size(output_mode.size),
+    );
+    let output_size = output_geo.size.to_f64().to_logical(scale);
+
+    match wallpaper {
+        Wallpaper::Color { id, color } => {
+            vec![OutputRenderElement::from(SolidColorRenderElement::new(
+                id,
+                output_geo,
+                CommitCounter::default(),
+                color,
+                element::Kind::Unspecified,
+            ))]
+        }
+        Wallpaper::Image {
+            image,
+            mode,
+            letterbox,
+        } => {
+            let image_size = image.size.to_logical(1, Transform::Normal).to_f64();
+            let (dst, src) = image_placement(image_size, output_size, mode);
+
+            let mut elements = Vec::new();
+
+            match MemoryRenderBufferRenderElement::from_buffer(
+                renderer,
+                dst.loc.to_physical(scale),
+                &image.buffer,
+                None,
+                Some(src),
+                Some(dst.size.to_i32_round()),
+                element::Kind::Unspecified,
+            ) {
+                Ok(elem) => elements.push(OutputRenderElement::from(elem)),
+                Err(err) => warn!("Failed to import wallpaper: {err:?}"),
+            }
+
+            if mode == WallpaperMode::Fit {
+                elements.push(OutputRenderElement::from(SolidColorRenderElement::new(
+                    letterbox,
+                    output_geo,
+                    CommitCounter::default(),
+                    LETTERBOX_COLOR,
+                    element::Kind::Unspecified,
+                )));
+            }
+
+            elements
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fill_crops_to_the_center() {
+        let (dst, src) = image_placement(
+            Size::from((2000.0, 1000.0)),
+            Size::from((1000.0, 1000.0)),
+            WallpaperMode::Fill,
+        );
+        assert_eq!(
+            dst,
+            Rectangle::from_loc_and_size((0.0, 0.0), (1000.0, 1000.0))
+        );
+        assert_eq!(
+            src,
+            Rectangle::from_loc_and_size((500.0, 0.0), (1000.0, 1000.0))
+        );
+    }
+
+    #[test]
+    fn fit_centers_with_bars() {
+        let (dst, src) = image_placement(
+            Size::from((2000.0, 1000.0)),
+            Size::from((1000.0, 1000.0)),
+            WallpaperMode::Fit,
+        );
+        assert_eq!(
+            dst,
+            Rectangle::from_loc_and_size((0.0, 250.0), (1000.0, 500.0))
+        );
+        assert_eq!(
+            src,
+            Rectangle::from_loc_and_size((0.0, 0.0), (2000.0, 1000.0))
+        );
+    }
+
+    #[test]
+    fn stretch_uses_the_whole_image() {
+        let (dst, src) = image_placement(
+            Size::from((2000.0, 1000.0)),
+            Size::from((1000.0, 1000.0)),
+            WallpaperMode::Stretch,
+        );
+        assert_eq!(
+            dst,
+            Rectangle::from_loc_and_size((0.0, 0.0), (1000.0, 1000.0))
+        );
+        assert_eq!(
+            src,
+            Rectangle::from_loc_and_size((0.0, 0.0), (2000.0, 1000.0))
+        );
+    }
+}
@@ -0,0 +1,161 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! The window switcher overlay.
+//!
+//! Windows are shown as a grid of cells in the middle of the output the switcher was opened
+//! on, most recently focused first. The selected cell is outlined, and cells show
+//! a thumbnail of their window unless thumbnails are disabled.
+
+use smithay::{
+    backend::renderer::{
+        element::{
+            surface::WaylandSurfaceRenderElement, utils::RescaleRenderElement, AsRenderElements,
+        },
+        Renderer, Texture,
+    },
+    desktop::space::SpaceElement,
+    output::Output,
+    utils::{IsAlive, Logical, Point, Rectangle, Scale},
+};
+
+use crate::state::Pinnacle;
+
+use super::{
+    perf_overlay::solid_color, OutputRenderElement, PinnacleRenderer, TransformRenderElement,
+};
+
+const CELL_WIDTH: i32 = 200;
+const CELL_HEIGHT: i32 = 130;
+const MARGIN: i32 = 32;
+const PADDING: i32 = 12;
+const GAP: i32 = 12;
+const HIGHLIGHT_WIDTH: i32 = 3;
+
+const BACKGROUND_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.7];
+const CELL_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.08];
+const HIGHLIGHT_COLOR: [f32; 4] = [0.4, 0.7, 1.0, 0.9];
+
+/// Get the elements of the window switcher overlay if it's open on `output`.
+pub fn window_switcher_elements<R>(
+    renderer: &mut R,
+    pinnacle: &Pinnacle,
+    output: &Output,
+) -> Vec<OutputRenderElement<R, WaylandSurfaceRenderElement<R>>>
+where
+    R: PinnacleRenderer,
+    <R as Renderer>::TextureId: Texture + Clone + 'static,
+{
+    let Some(switcher) = pinnacle
+        .window_switcher
+        .as_ref()
+        .filter(|switcher| &switcher.output == output)
+    else {
+        return Vec::new();
+    };
+
+    let Some(output_geo) = pinnacle.space.output_geometry(output) else {
+        return Vec::new();
+    };
+
+    let count = switcher.windows.len() as i32;
+    if count == 0 {
+        return Vec::new();
+    }
+
+    let scale = Scale::from(output.current_scale().fractional_scale());
+
+    let max_columns = (output_geo.size.w - MARGIN * 2 - PADDING * 2 + GAP) / (CELL_WIDTH + GAP);
+    let columns = count.min(max_columns.max(1));
+    let rows = (count + columns - 1) / columns;
+
+    let width = PADDING * 2 + columns * CELL_WIDTH + (columns - 1) * GAP;
+    let height = PADDING * 2 + rows * CELL_HEIGHT + (rows - 1) * GAP;
+    let panel = Rectangle::<i32, Logical>::from_loc_and_size(
+        (
+            (output_geo.size.w - width) / 2,
+            (output_geo.size.h - height) / 2,
+        ),
+        (width, height),
+    );
+
+    let cell_geo = |index: i32| {
+        Rectangle::<i32, Logical>::from_loc_and_size(
+            (
+                panel.loc.x + PADDING + (index % columns) * (CELL_WIDTH + GAP),
+                panel.loc.y + PADDING + (index / columns) * (CELL_HEIGHT + GAP),
+            ),
+            (CELL_WIDTH, CELL_HEIGHT),
+        )
+    };
+
+    let mut thumbnails = Vec::new();
+    let mut cells = Vec::new();
+
+    for (index, (window, id)) in switcher.windows.iter().zip(&switcher.cells).enumerate() {
+        let cell = cell_geo(index as i32);
+        cells.push(solid_color(id.clone(), cell, scale, CELL_COLOR));
+
+        if !pinnacle.config.switcher.thumbnails || !window.alive() {
+            continue;
+        }
+
+        let size = window.geometry().size;
+        if size.w <= 0 || size.h <= 0 {
+            continue;
+        }
+
+        let factor = (CELL_WIDTH as f64 / size.w as f64)
+            .min(CELL_HEIGHT as f64 / size.h as f64)
+            .min(1.0);
+        let thumb_size = size.to_f64().upscale(factor).to_i32_round::<i32>();
+        let loc = Point::<i32, Logical>::from((
+            cell.loc.x + (CELL_WIDTH - thumb_size.w) / 2,
+            cell.loc.y + (CELL_HEIGHT - thumb_size.h) / 2,
+        ));
+
+        let origin = loc.to_physical_precise_round(scale);
+        thumbnails.extend(
+            window
+                .render_elements::<WaylandSurfaceRenderElement<R>>(
+                    renderer,
+                    (loc - window.geometry().loc).to_physical_precise_round(scale),
+                    scale,
+                    1.0,
+                )
+                .into_iter()
+                .map(|elem| {
+                    let elem = RescaleRenderElement::from_element(elem, origin, factor);
+                    OutputRenderElement::from(TransformRenderElement::from(elem))
+                }),
+        );
+    }
+
+    let mut elements = thumbnails;
+    elements.extend(cells);
+
+    let selected = cell_geo(switcher.selected as i32);
+    let highlight = Rectangle::from_loc_and_size(
+        (
+            selected.loc.x - HIGHLIGHT_WIDTH,
+            selected.loc.y - HIGHLIGHT_WIDTH,
+        ),
+        (
+            selected.size.w + HIGHLIGHT_WIDTH * 2,
+            selected.size.h + HIGHLIGHT_WIDTH * 2,
+        ),
+    );
+    elements.push(solid_color(
+        switcher.highlight.clone(),
+        highlight,
+        scale,
+        HIGHLIGHT_COLOR,
+    ));
+    elements.push(solid_color(
+        switcher.background.clone(),
+        panel,
+        scale,
+        BACKGROUND_COLOR,
+    ));
+
+    elements
+}
@@ -0,0 +1,426 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Screenshots.
+//!
+//! Screenshots are rendered into a new buffer instead of being read back from an output's
+//! framebuffer, so they work the same way on every backend and can capture single windows.
+
+use std::{io::Cursor, path::Path};
+
+use anyhow::{anyhow, ensure, Context};
+use image::{ImageFormat, RgbaImage};
+use smithay::{
+    backend::{
+        allocator::Fourcc,
+        renderer::{
+            damage::OutputDamageTracker,
+            element::{self, surface::WaylandSurfaceRenderElement},
+            gles::GlesRenderbuffer,
+            Bind, ExportMem, Offscreen, Renderer, Texture, TextureMapping, Unbind,
+        },
+    },
+    desktop::space::SpaceElement,
+    output::Output,
+    utils::{Logical, Physical, Point, Rectangle, Scale, Size, Transform},
+};
+use tracing::warn;
+
+use crate::{
+    backend::Backend,
+    render::{
+        output_render_elements, pointer::PointerElement, pointer_render_elements,
+        OutputRenderElement, PinnacleRenderer,
+    },
+    state::{Pinnacle, State},
+    window::WindowElement,
+};
+
+/// What to take a screenshot of.
+#[derive(Debug, Clone)]
+pub enum ScreenshotTarget {
+    /// An entire output.
+    Output(Output),
+    /// A region of the global space.
+    ///
+    /// Only the part of the region on the output containing its top-left corner is captured.
+    Region(Rectangle<i32, Logical>),
+    /// A single window, without anything above or below it.
+    Window(WindowElement),
+    /// A single window without its popups, scaled down to fit in a maximum size.
+    ///
+    /// This is meant for previews like taskbar thumbnails.
+    WindowThumbnail {
+        window: WindowElement,
+        max_size: Size<i32, Physical>,
+    },
+}
+
+/// A rendered screenshot.
+#[derive(Debug)]
+pub struct Screenshot {
+    image: RgbaImage,
+}
+
+impl Screenshot {
+    /// The size of this screenshot in pixels.
+    pub fn size(&self) -> (u32, u32) {
+        self.image.dimensions()
+    }
+
+    /// Get the RGBA pixels of this screenshot, row by row.
+    pub fn into_raw(self) -> Vec<u8> {
+        self.image.into_raw()
+    }
+
+    /// Encode this screenshot as a PNG.
+    pub fn encode_png(&self) -> anyhow::Result<Vec<u8>> {
+        let mut png = Vec::new();
+        self.image
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+        Ok(png)
+    }
+
+    /// Write this screenshot to a PNG file.
+    pub fn save_png(&self, path: &Path) -> anyhow::Result<()> {
+        self.image.save_with_format(path, ImageFormat::Png)?;
+        Ok(())
+    }
+}
+
+/// The area a screenshot is rendered at and the part of it that is kept.
+struct Capture {
+    size: Size<i32, Physical>,
+    scale: Scale<f64>,
+    region: Rectangle<i32, Physical>,
+    clear_color: [f32; 4],
+}
+
+/// Render a screenshot into a new buffer of type `B`.
+pub fn render_screenshot<R, B>(
+    renderer: &mut R,
+    pinnacle: &mut Pinnacle,
+    target: &ScreenshotTarget,
+    include_cursor: bool,
+) -> anyhow::Result<Screenshot>
+where
+    R: PinnacleRenderer + Offscreen<B> + Bind<B> + Unbind + ExportMem,
+    <R as Renderer>::TextureId: Texture + Clone + 'static,
+    <R as Renderer>::Error: Send + Sync + 'static,
+{
+    let (capture, elements) = match target {
+        ScreenshotTarget::Output(output) => {
+            let capture = output_capture(output, None)?;
+            let elements = output_elements(renderer, pinnacle, output, include_cursor);
+            (capture, elements)
+        }
+        ScreenshotTarget::Region(region) => {
+            let output = region_output(pinnacle, *region).context("region is not on an output")?;
+            let capture = output_capture(&output, Some(*region))?;
+            let elements = output_elements(renderer, pinnacle, &output, include_cursor);
+            (capture, elements)
+        }
+        ScreenshotTarget::Window(window) => {
+            let scale = window_scale(pinnacle, window);
+            window_capture(renderer, window, scale, true)
+        }
+        ScreenshotTarget::WindowThumbnail { window, max_size } => {
+            let scale = thumbnail_scale(pinnacle, window, *max_size);
+            window_capture(renderer, window, scale, false)
+        }
+    };
+
+    ensure!(
+        capture.region.size.w > 0 && capture.region.size.h > 0,
+        "nothing to capture"
+    );
+
+    // Blurring renders into other textures, so the buffer is bound
+    // only after all render elements are created.
+    let buffer = renderer
+        .create_buffer(
+            Fourcc::Abgr8888,
+            capture.size.to_logical(1).to_buffer(1, Transform::Normal),
+        )
+        .context("failed to create screenshot buffer")?;
+    renderer
+        .bind(buffer)
+        .context("failed to bind screenshot buffer")?;
+
+    let pixels = render_and_read(renderer, &capture, &elements);
+
+    if let Err(err) = renderer.unbind() {
+        warn!("Failed to unbind screenshot buffer: {err}");
+    }
+
+    let image = RgbaImage::from_raw(
+        capture.region.size.w as u32,
+        capture.region.size.h as u32,
+        pixels?,
+    )
+    .context("screenshot has the wrong size")?;
+
+    Ok(Screenshot { image })
+}
+
+impl ScreenshotTarget {
+    /// The size screenshots of this target currently have, in physical pixels.
+    pub fn size(&self, pinnacle: &Pinnacle) -> anyhow::Result<Size<i32, Physical>> {
+        let size = match self {
+            ScreenshotTarget::Output(output) => output_capture(output, None)?.region.size,
+            ScreenshotTarget::Region(region) => {
+                let output =
+                    region_output(pinnacle, *region).context("region is not on an output")?;
+                output_capture(&output, Some(*region))?.region.size
+            }
+            ScreenshotTarget::Window(window) => window
+                .geometry()
+                .size
+                .to_physical_precise_round(window_scale(pinnacle, window)),
+            ScreenshotTarget::WindowThumbnail { window, max_size } => window
+                .geometry()
+                .size
+                .to_physical_precise_round(thumbnail_scale(pinnacle, window, *max_size)),
+        };
+
+        Ok(size)
+    }
+}
+
+/// The output containing the top-left corner of a region.
+pub fn region_output(pinnacle: &Pinnacle, region: Rectangle<i32, Logical>) -> Option<Output> {
+    pinnacle
+        .space
+        .outputs()
+        .find(|op| {
+            pinnacle
+                .space
+                .output_geometry(op)
+                .is_some_and(|geo| geo.contains(region.loc))
+        })
+        .cloned()
+}
+
+/// The scale of the output a window is on.
+fn window_scale(pinnacle: &Pinnacle, window: &WindowElement) -> Scale<f64> {
+    Scale::from(
+        window
+            .output(pinnacle)
+            .map(|op| op.current_scale().fractional_scale())
+            .unwrap_or(1.0),
+    )
+}
+
+/// The scale that fits a window into a thumbnail's maximum size.
+fn thumbnail_scale(
+    pinnacle: &Pinnacle,
+    window: &WindowElement,
+    max_size: Size<i32, Physical>,
+) -> Scale<f64> {
+    let size = window.geometry().size.to_f64();
+    // Thumbnails are never larger than the window itself
+    let scale = (f64::from(max_size.w) / size.w)
+        .min(f64::from(max_size.h) / size.h)
+        .min(window_scale(pinnacle, window).x);
+    Scale::from(scale)
+}
+
+/// Get the capture of a window's geometry and the elements to render it,
+/// leaving out anything outside its geometry like client-side shadows.
+fn window_capture<R>(
+    renderer: &mut R,
+    window: &WindowElement,
+    scale: Scale<f64>,
+    with_popups: bool,
+) -> (
+    Capture,
+    Vec<OutputRenderElement<R, WaylandSurfaceRenderElement<R>>>,
+)
+where
+    R: PinnacleRenderer,
+    <R as Renderer>::TextureId: Texture + Clone + 'static,
+{
+    let geometry = window.geometry();
+    let size = geometry.size.to_physical_precise_round(scale);
+    let location = Point::<i32, Logical>::from((-geometry.loc.x, -geometry.loc.y))
+        .to_physical_precise_round(scale);
+
+    let elements = if with_popups {
+        window.render_elements_with_kind::<R, WaylandSurfaceRenderElement<R>>(
+            renderer,
+            location,
+            scale,
+            1.0,
+            element::Kind::Unspecified,
+        )
+    } else {
+        window.surface_render_elements::<R, WaylandSurfaceRenderElement<R>>(
+            renderer,
+            location,
+            scale,
+            1.0,
+            element::Kind::Unspecified,
+        )
+    };
+
+    let capture = Capture {
+        size,
+        scale,
+        region: Rectangle::from_loc_and_size((0, 0), size),
+        clear_color: [0.0, 0.0, 0.0, 0.0],
+    };
+
+    (
+        capture,
+        elements
+            .into_iter()
+            .map(OutputRenderElement::from)
+            .collect(),
+    )
+}
+
+/// Get the capture of an output, or of a region in the global space on it.
+fn output_capture(
+    output: &Output,
+    region: Option<Rectangle<i32, Logical>>,
+) -> anyhow::Result<Capture> {
+    let mode = output.current_mode().context("output has no mode")?;
+    let size = output.current_transform().transform_size(mode.size);
+    let scale = Scale::from(output.current_scale().fractional_scale());
+    let output_rect = Rectangle::from_loc_and_size((0, 0), size);
+
+    let region = match region {
+        Some(region) => {
+            let local =
+                Rectangle::from_loc_and_size(region.loc - output.current_location(), region.size);
+            local
+                .to_physical_precise_round(scale)
+                .intersection(output_rect)
+                .context("region is not on the output")?
+        }
+        None => output_rect,
+    };
+
+    Ok(Capture {
+        size,
+        scale,
+        region,
+        clear_color: [0.6, 0.6, 0.6, 1.0],
+    })
+}
+
+fn output_elements<R>(
+    renderer: &mut R,
+    pinnacle: &mut Pinnacle,
+    output: &Output,
+    include_cursor: bool,
+) -> Vec<OutputRenderElement<R, WaylandSurfaceRenderElement<R>>>
+where
+    R: PinnacleRenderer,
+    <R as Renderer>::TextureId: Texture + Clone + 'static,
+{
+    let mut elements = Vec::new();
+
+    if include_cursor {
+        // Like on offscreen outputs, only cursor surfaces are drawn
+        let mut pointer_element = PointerElement::<R::TextureId>::new();
+        pointer_element.set_status(pinnacle.cursor_status.clone());
+
+        let pointer_location = pinnacle
+            .seat
+            .get_pointer()
+            .map(|ptr| ptr.current_location())
+            .unwrap_or((0.0, 0.0).into());
+
+        elements.extend(pointer_render_elements(
+            output,
+            renderer,
+            &pinnacle.space,
+            pointer_location,
+            &mut pinnacle.cursor_status,
+            pinnacle.dnd_icon.as_ref(),
+            &pointer_element,
+        ));
+    }
+
+    let windows = pinnacle.space.elements().cloned().collect::<Vec<_>>();
+
+    elements.extend(output_render_elements(
+        output,
+        renderer,
+        &pinnacle.space,
+        &windows,
+        &pinnacle.config.render_settings,
+    ));
+
+    elements
+}
+
+/// Render elements into the bound buffer and read back the captured region as RGBA pixels.
+fn render_and_read<R>(
+    renderer: &mut R,
+    capture: &Capture,
+    elements: &[OutputRenderElement<R, WaylandSurfaceRenderElement<R>>],
+) -> anyhow::Result<Vec<u8>>
+where
+    R: PinnacleRenderer + ExportMem,
+    <R as Renderer>::TextureId: Texture + Clone + 'static,
+    <R as Renderer>::Error: Send + Sync + 'static,
+{
+    let mut damage_tracker =
+        OutputDamageTracker::new(capture.size, capture.scale, Transform::Normal);
+    damage_tracker
+        .render_output(renderer, 0, elements, capture.clear_color)
+        .map_err(|err| anyhow!("failed to render screenshot: {err:?}"))?;
+
+    let region =
+        capture
+            .region
+            .to_logical(1)
+            .to_buffer(1, Transform::Normal, &capture.size.to_logical(1));
+    let mapping = renderer.copy_framebuffer(region, Fourcc::Abgr8888)?;
+    let flipped = mapping.flipped();
+    let mut pixels = renderer.map_texture(&mapping)?.to_vec();
+
+    if flipped {
+        let row_len = capture.region.size.w as usize * 4;
+        pixels = pixels
+            .chunks_exact(row_len)
+            .rev()
+            .flatten()
+            .copied()
+            .collect();
+    }
+
+    Ok(pixels)
+}
+
+impl State {
+    /// Take a screenshot with the backend's renderer.
+    pub fn screenshot(
+        &mut self,
+        target: &ScreenshotTarget,
+        include_cursor: bool,
+    ) -> anyhow::Result<Screenshot> {
+        match &mut self.backend {
+            Backend::Winit(winit) => render_screenshot::<_, GlesRenderbuffer>(
+                winit.backend.renderer(),
+                &mut self.pinnacle,
+                target,
+                include_cursor,
+            ),
+            Backend::Udev(udev) => udev.screenshot(&mut self.pinnacle, target, include_cursor),
+            Backend::Headless(headless) => {
+                headless
+                    .renderer
+                    .screenshot(&mut self.pinnacle, target, include_cursor)
+            }
+            Backend::Nested(nested) => {
+                nested
+                    .renderer
+                    .screenshot(&mut self.pinnacle, target, include_cursor)
+            }
+            #[cfg(feature = "testing")]
+            Backend::Dummy(_) => Err(anyhow!("the dummy backend can't take screenshots")),
+        }
+    }
+}
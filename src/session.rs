@@ -0,0 +1,292 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Session persistence.
+//!
+//! While enabled, the names and active state of every output's tags and the tags each app's
+//! windows are on are saved to pinnacle's state directory as they change. Apps are keyed by
+//! app id or class, and tags by name, since neither windows nor tag ids survive a restart.
+//!
+//! Changes are saved shortly after they're made, so a burst of them is written once.
+//!
+//! Restoring a session recreates saved tags that are missing, activates the saved tags on
+//! every output, and moves open windows back to their app's tags. Windows that open while
+//! persistence is enabled are put on their app's saved tags unless a window rule places them.
+
+use std::{collections::BTreeMap, path::PathBuf, time::Duration};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use smithay::reexports::calloop::{
+    timer::{TimeoutAction, Timer},
+    RegistrationToken,
+};
+use tracing::error;
+use xdg::BaseDirectories;
+
+use crate::{
+    output::OutputName,
+    state::{Pinnacle, State, WithState},
+    tag::Tag,
+    window::WindowElement,
+};
+
+const SESSION_FILE: &str = "session.toml";
+/// How long to wait after a change before saving the session.
+const SAVE_DELAY: Duration = Duration::from_secs(1);
+
+/// Session persistence settings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SessionSettings {
+    /// Whether the session is saved and new windows are put on their app's saved tags.
+    pub persist: bool,
+}
+
+/// A saved tag.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedTag {
+    pub name: String,
+    pub active: bool,
+}
+
+/// The tags an app's windows were last on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedWindow {
+    /// The name of the output the tags are on.
+    pub output: String,
+    pub tags: Vec<String>,
+}
+
+/// Saved tags by output name and window placements by app id, persisted to
+/// pinnacle's state directory.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Session {
+    outputs: BTreeMap<String, Vec<SavedTag>>,
+    windows: BTreeMap<String, SavedWindow>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    /// The timer that saves the session, set while there are unsaved changes.
+    #[serde(skip)]
+    save_timer: Option<RegistrationToken>,
+}
+
+impl Session {
+    /// Load the session from disk, or start with an empty one if it can't be read.
+    pub fn load(xdg_base_dirs: &BaseDirectories) -> Self {
+        let path = xdg_base_dirs.get_state_home().join(SESSION_FILE);
+
+        let mut session = match std::fs::read_to_string(&path) {
+            Ok(contents) => toml::from_str::<Session>(&contents).unwrap_or_else(|err| {
+                error!("Failed to parse {}: {err}", path.display());
+                Session::default()
+            }),
+            Err(_) => Session::default(),
+        };
+
+        session.path = Some(path);
+        session
+    }
+
+    fn save(&self) -> anyhow::Result<()> {
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, toml::to_string(self)?)
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+}
+
+impl Pinnacle {
+    /// Save the session shortly if session persistence is enabled.
+    ///
+    /// This should be called whenever tags or the tags of windows change.
+    pub fn session_changed(&mut self) {
+        if !self.config.session.persist || self.session.save_timer.is_some() {
+            return;
+        }
+
+        let timer = Timer::from_duration(SAVE_DELAY);
+        let res = self.loop_handle.insert_source(timer, |_, _, state| {
+            state.pinnacle.session.save_timer = None;
+            state.pinnacle.save_session();
+            TimeoutAction::Drop
+        });
+
+        match res {
+            Ok(token) => self.session.save_timer = Some(token),
+            Err(err) => error!("Failed to insert session save timer: {err}"),
+        }
+    }
+
+    /// Save changes to the session that are waiting on their timer right away.
+    pub fn flush_session(&mut self) {
+        if let Some(token) = self.session.save_timer.take() {
+            self.loop_handle.remove(token);
+            self.save_session();
+        }
+    }
+
+    /// Save the current tags and window placements if session persistence is enabled
+    /// and they changed.
+    ///
+    /// Outputs without tags and windows on tags that aren't on an output, like while
+    /// the config is reloading, keep what was saved for them before.
+    fn save_session(&mut self) {
+        if !self.config.session.persist {
+            return;
+        }
+
+        let mut outputs = self.session.outputs.clone();
+        for output in self.space.outputs() {
+            let tags = output.with_state(|state| {
+                state
+                    .tags
+                    .iter()
+                    .map(|tag| SavedTag {
+                        name: tag.name(),
+                        active: tag.active(),
+                    })
+                    .collect::<Vec<_>>()
+            });
+
+            if !tags.is_empty() {
+                outputs.insert(output.name(), tags);
+            }
+        }
+
+        let mut windows = self.session.windows.clone();
+        for window in self.windows.iter() {
+            let Some(app_id) = window.class() else {
+                continue;
+            };
+
+            let tags = window.with_state(|state| state.tags.clone());
+            let Some(output) = tags.first().and_then(|tag| tag.output(self)) else {
+                continue;
+            };
+            if tags
+                .iter()
+                .any(|tag| tag.output(self).as_ref() != Some(&output))
+            {
+                continue;
+            }
+
+            windows.insert(
+                app_id,
+                SavedWindow {
+                    output: output.name(),
+                    tags: tags.iter().map(|tag| tag.name()).collect(),
+                },
+            );
+        }
+
+        if outputs == self.session.outputs && windows == self.session.windows {
+            return;
+        }
+
+        self.session.outputs = outputs;
+        self.session.windows = windows;
+
+        if let Err(err) = self.session.save() {
+            error!("Failed to save session: {err}");
+        }
+    }
+
+    /// Get the tags the app of `window` was last on, if they're still there.
+    fn saved_window_tags(&self, window: &WindowElement) -> Option<Vec<Tag>> {
+        let saved = self.session.windows.get(&window.class()?)?;
+        let output = OutputName(saved.output.clone()).output(self)?;
+
+        let tags = output.with_state(|state| {
+            saved
+                .tags
+                .iter()
+                .filter_map(|name| state.tags.iter().find(|tag| &tag.name() == name).cloned())
+                .collect::<Vec<_>>()
+        });
+
+        (!tags.is_empty()).then_some(tags)
+    }
+
+    /// Put a new `window` on its app's saved tags if session persistence is enabled.
+    ///
+    /// This is applied before window rules so they can override it.
+    pub fn restore_window_session(&self, window: &WindowElement) {
+        if !self.config.session.persist {
+            return;
+        }
+
+        if let Some(tags) = self.saved_window_tags(window) {
+            window.with_state_mut(|state| state.tags = tags);
+        }
+    }
+}
+
+impl State {
+    /// Restore the saved session.
+    ///
+    /// Saved tags that an output doesn't have, like ones that were renamed or added at runtime,
+    /// are added to it. Outputs then activate the tags that were active on them, and open
+    /// windows are moved back to their app's saved tags.
+    pub fn restore_session(&mut self) {
+        let outputs = self.pinnacle.space.outputs().cloned().collect::<Vec<_>>();
+
+        for output in outputs.iter() {
+            let Some(saved) = self.pinnacle.session.outputs.get(&output.name()).cloned() else {
+                continue;
+            };
+
+            let missing_tags = output.with_state(|state| {
+                saved
+                    .iter()
+                    .filter(|saved| !state.tags.iter().any(|tag| tag.name() == saved.name))
+                    .map(|saved| Tag::new(saved.name.clone()))
+                    .collect::<Vec<_>>()
+            });
+
+            if !missing_tags.is_empty() {
+                self.pinnacle
+                    .config
+                    .connector_saved_states
+                    .entry(OutputName(output.name()))
+                    .or_default()
+                    .tags
+                    .extend(missing_tags.clone());
+                output.with_state_mut(|state| state.tags.extend(missing_tags));
+            }
+
+            let active = saved
+                .iter()
+                .filter(|tag| tag.active)
+                .map(|tag| tag.name.clone())
+                .collect::<Vec<_>>();
+
+            let tags = output.with_state(|state| state.tags.clone());
+            if !tags.iter().any(|tag| active.contains(&tag.name())) {
+                continue;
+            }
+
+            for tag in tags {
+                let name = tag.name();
+                tag.set_active(active.contains(&name), self);
+            }
+        }
+
+        for window in self.pinnacle.windows.clone() {
+            if let Some(tags) = self.pinnacle.saved_window_tags(&window) {
+                window.with_state_mut(|state| state.tags = tags);
+            }
+        }
+
+        self.pinnacle.fixup_xwayland_window_layering();
+
+        for output in outputs {
+            self.pinnacle.request_layout(&output);
+            self.update_focus(&output);
+            self.schedule_render(&output);
+        }
+    }
+}
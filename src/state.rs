@@ -5,32 +5,51 @@ use crate::{
     backend::Backend,
     config::Config,
     cursor::Cursor,
-    focus::OutputFocusStack,
-    grab::resize_grab::ResizeSurfaceState,
+    focus::{switcher::WindowSwitcher, OutputFocusStack},
+    grab::{resize_grab::ResizeSurfaceState, snap::SnapGuides},
+    handlers::xwayland::x11_worker::X11Worker,
     layout::LayoutState,
-    protocol::{gamma_control::GammaControlManagerState, screencopy::ScreencopyManagerState},
-    window::WindowElement,
+    output::{profile::OutputProfiles, OutputName},
+    protocol::{
+        alpha_modifier::AlphaModifierState, drm_syncobj::DrmSyncobjState,
+        ext_foreign_toplevel_list::ExtForeignToplevelListState,
+        ext_workspace::ExtWorkspaceManagerState, gamma_control::GammaControlManagerState,
+        image_copy_capture::ImageCopyCaptureState, screencopy::ScreencopyManagerState,
+        xdg_toplevel_drag::XdgToplevelDragManagerState,
+    },
+    recording::Recording,
+    render::DebugSettings,
+    session::Session,
+    window::{
+        edge_tile::EdgeTilePreview, placement::SpawnPlacement,
+        remembered_geometry::RememberedGeometries, unresponsive::PendingPing, WindowElement,
+    },
 };
 use anyhow::Context;
 use pinnacle_api_defs::pinnacle::v0alpha1::ShutdownWatchResponse;
 use smithay::{
     desktop::{PopupManager, Space},
     input::{keyboard::XkbConfig, pointer::CursorImageStatus, Seat, SeatState},
+    output::Output,
     reexports::{
         calloop::{generic::Generic, Interest, LoopHandle, LoopSignal, Mode, PostAction},
         wayland_server::{
             backend::{ClientData, ClientId, DisconnectReason},
-            protocol::wl_surface::WlSurface,
-            Display, DisplayHandle,
+            protocol::{wl_data_source::WlDataSource, wl_surface::WlSurface},
+            Client, Display, DisplayHandle,
         },
     },
-    utils::{Clock, Monotonic, Point, Size},
+    utils::{Clock, Monotonic},
     wayland::{
         compositor::{self, CompositorClientState, CompositorState},
+        content_type::ContentTypeState,
+        cursor_shape::CursorShapeManagerState,
         dmabuf::DmabufFeedback,
         fractional_scale::FractionalScaleManagerState,
         output::OutputManagerState,
+        pointer_gestures::PointerGesturesState,
         relative_pointer::RelativePointerManagerState,
+        security_context::{SecurityContext, SecurityContextState},
         selection::{
             data_device::DataDeviceState, primary_selection::PrimarySelectionState,
             wlr_data_control::DataControlState,
@@ -38,11 +57,13 @@ use smithay::{
         shell::{wlr_layer::WlrLayerShellState, xdg::XdgShellState},
         shm::ShmState,
         socket::ListeningSocketSource,
+        tablet_manager::TabletManagerState,
         viewporter::ViewporterState,
+        xdg_activation::XdgActivationState,
     },
     xwayland::{X11Wm, XWayland, XWaylandEvent},
 };
-use std::{cell::RefCell, path::PathBuf, sync::Arc, time::Duration};
+use std::{cell::RefCell, collections::HashMap, path::PathBuf, sync::Arc};
 use sysinfo::{ProcessRefreshKind, RefreshKind};
 use tracing::{error, info, warn};
 use xdg::BaseDirectories;
@@ -82,17 +103,55 @@ pub struct Pinnacle {
     pub screencopy_manager_state: ScreencopyManagerState,
     pub gamma_control_manager_state: GammaControlManagerState,
     pub relative_pointer_manager_state: RelativePointerManagerState,
+    pub pointer_gestures_state: PointerGesturesState,
+    pub content_type_state: ContentTypeState,
+    pub cursor_shape_manager_state: CursorShapeManagerState,
+    pub tablet_manager_state: TabletManagerState,
+    pub ext_workspace_manager_state: ExtWorkspaceManagerState,
+    pub security_context_state: SecurityContextState,
+    pub xdg_activation_state: XdgActivationState,
+    pub xdg_toplevel_drag_manager_state: XdgToplevelDragManagerState,
+    pub alpha_modifier_state: AlphaModifierState,
+    pub ext_foreign_toplevel_list_state: ExtForeignToplevelListState,
+    pub image_copy_capture_state: ImageCopyCaptureState,
+    /// The explicit sync global; only created on the udev backend when supported.
+    pub drm_syncobj_state: Option<DrmSyncobjState>,
 
     /// The state of key and mousebinds along with libinput settings
     pub input_state: InputState,
 
     pub output_focus_stack: OutputFocusStack,
+    /// Windows in the order they were focused, the most recent last.
+    pub focus_history: Vec<WindowElement>,
+    /// The open window switcher, if any.
+    pub window_switcher: Option<WindowSwitcher>,
+    /// Where to place the first window of spawned processes, by process id.
+    pub spawn_placements: HashMap<u32, SpawnPlacement>,
+    /// Pings sent to clients that they haven't answered yet.
+    pub pending_pings: Vec<PendingPing>,
+    /// Outputs that have been removed from the layout with [`State::disable_output`].
+    ///
+    /// These are not in `space`.
+    pub disabled_outputs: Vec<Output>,
+    pub output_profiles: OutputProfiles,
+    pub remembered_geometries: RememberedGeometries,
+    /// Saved tags and window placements, see [`crate::session`].
+    pub session: Session,
+    /// The output new windows open on, set with [`Pinnacle::set_primary_output`].
+    pub primary_output_name: Option<OutputName>,
     pub z_index_stack: Vec<WindowElement>,
 
     pub popup_manager: PopupManager,
 
     pub cursor_status: CursorImageStatus,
+    /// The cursor theme and size cursor images are drawn with
+    pub cursor: Cursor,
+    /// The cursor theme and size from the environment Pinnacle was started in,
+    /// which config reloads reset the cursor to
+    pub startup_cursor_theme: (String, u32),
     pub dnd_icon: Option<WlSurface>,
+    /// The data source of the currently ongoing client drag-and-drop, if any
+    pub dnd_source: Option<WlDataSource>,
 
     /// The main window vec
     pub windows: Vec<WindowElement>,
@@ -104,6 +163,8 @@ pub struct Pinnacle {
     pub xwayland: XWayland,
     pub xwm: Option<X11Wm>,
     pub xdisplay: Option<u32>,
+    /// Makes X11 requests [`X11Wm`] doesn't, without blocking the event loop
+    pub x11_worker: Option<X11Worker>,
 
     pub system_processes: sysinfo::System,
 
@@ -115,6 +176,16 @@ pub struct Pinnacle {
     pub signal_state: SignalState,
 
     pub layout_state: LayoutState,
+
+    /// Guide lines for the floating window being snapped, if any.
+    pub snap_guides: SnapGuides,
+    /// The drop-zone preview for a window being dragged to an output edge.
+    pub edge_tile_preview: EdgeTilePreview,
+
+    /// The ongoing screen recording, if any.
+    pub recording: Option<Recording>,
+
+    pub debug_settings: DebugSettings,
 }
 
 impl State {
@@ -181,6 +252,7 @@ impl State {
 
         let mut seat = seat_state.new_wl_seat(&display_handle, backend.seat_name());
         seat.add_pointer();
+        seat.add_touch();
 
         seat.add_keyboard(XkbConfig::default(), 500, 25)?;
 
@@ -195,7 +267,7 @@ impl State {
                     client_fd: _,
                     display,
                 } => {
-                    let mut wm = X11Wm::start_wm(
+                    let wm = X11Wm::start_wm(
                         state.pinnacle.loop_handle.clone(),
                         dh_clone.clone(),
                         connection,
@@ -203,22 +275,21 @@ impl State {
                     )
                     .expect("failed to attach x11wm");
 
-                    let cursor = Cursor::load();
-                    let image = cursor.get_image(1, Duration::ZERO);
-                    wm.set_cursor(
-                        &image.pixels_rgba,
-                        Size::from((image.width as u16, image.height as u16)),
-                        Point::from((image.xhot as u16, image.yhot as u16)),
-                    )
-                    .expect("failed to set xwayland default cursor");
-
                     tracing::debug!("setting xwm and xdisplay");
 
                     state.pinnacle.xwm = Some(wm);
                     state.pinnacle.xdisplay = Some(display);
 
+                    state.pinnacle.update_xwayland_cursor();
+
                     std::env::set_var("DISPLAY", format!(":{display}"));
 
+                    match X11Worker::spawn(display, &state.pinnacle.loop_handle) {
+                        Ok(worker) => state.pinnacle.x11_worker = Some(worker),
+                        Err(err) => warn!("Failed to spawn the X11 worker thread: {err}"),
+                    }
+                    state.pinnacle.update_xwayland_primary_output();
+
                     if let Err(err) = state.pinnacle.start_config(Some(
                         state.pinnacle.config.dir(&state.pinnacle.xdg_base_dirs),
                     )) {
@@ -227,6 +298,7 @@ impl State {
                 }
                 XWaylandEvent::Exited => {
                     state.pinnacle.xwm.take();
+                    state.pinnacle.x11_worker.take();
                 }
             });
             if let Err(err) = res {
@@ -241,10 +313,15 @@ impl State {
         let data_control_state = DataControlState::new::<Self, _>(
             &display_handle,
             Some(&primary_selection_state),
-            |_| true,
+            client_is_unrestricted,
         );
 
-        let state = Self {
+        let xdg_base_dirs = BaseDirectories::with_prefix("pinnacle")
+            .context("couldn't create xdg BaseDirectories")?;
+
+        let startup_cursor_theme = crate::cursor::theme_from_env();
+
+        let mut state = Self {
             backend,
 
             pinnacle: Pinnacle {
@@ -258,6 +335,8 @@ impl State {
                 shm_state: ShmState::new::<Self>(&display_handle, vec![]),
                 space: Space::<WindowElement>::default(),
                 cursor_status: CursorImageStatus::default_named(),
+                cursor: Cursor::load(),
+                startup_cursor_theme,
                 output_manager_state: OutputManagerState::new_with_xdg_output::<Self>(
                     &display_handle,
                 ),
@@ -271,19 +350,54 @@ impl State {
                 data_control_state,
                 screencopy_manager_state: ScreencopyManagerState::new::<Self, _>(
                     &display_handle,
-                    |_| true,
+                    client_is_unrestricted,
                 ),
                 gamma_control_manager_state: GammaControlManagerState::new::<Self, _>(
                     &display_handle,
-                    |_| true,
+                    client_is_unrestricted,
                 ),
                 relative_pointer_manager_state: RelativePointerManagerState::new::<Self>(
                     &display_handle,
                 ),
+                pointer_gestures_state: PointerGesturesState::new::<Self>(&display_handle),
+                content_type_state: ContentTypeState::new::<Self>(&display_handle),
+                cursor_shape_manager_state: CursorShapeManagerState::new::<Self>(&display_handle),
+                tablet_manager_state: TabletManagerState::new::<Self>(&display_handle),
+                // Workspaces can be activated and removed through this
+                ext_workspace_manager_state: ExtWorkspaceManagerState::new::<Self, _>(
+                    &display_handle,
+                    client_is_unrestricted,
+                ),
+                // Sandboxed clients shouldn't be able to nest security contexts
+                security_context_state: SecurityContextState::new::<Self, _>(
+                    &display_handle,
+                    client_is_unrestricted,
+                ),
+                xdg_activation_state: XdgActivationState::new::<Self>(&display_handle),
+                xdg_toplevel_drag_manager_state: XdgToplevelDragManagerState::new::<Self, _>(
+                    &display_handle,
+                    |_| true,
+                ),
+                alpha_modifier_state: AlphaModifierState::new::<Self, _>(&display_handle, |_| true),
+                ext_foreign_toplevel_list_state: ExtForeignToplevelListState::new::<Self, _>(
+                    &display_handle,
+                    client_is_unrestricted,
+                ),
+                image_copy_capture_state: ImageCopyCaptureState::new::<Self, _>(
+                    &display_handle,
+                    client_is_unrestricted,
+                ),
+                drm_syncobj_state: None,
 
                 input_state: InputState::new(),
 
                 output_focus_stack: OutputFocusStack::default(),
+                focus_history: Vec::new(),
+                window_switcher: None,
+                spawn_placements: HashMap::new(),
+                pending_pings: Vec::new(),
+                disabled_outputs: Vec::new(),
+                primary_output_name: None,
                 z_index_stack: Vec::new(),
 
                 config: Config::new(no_config, config_dir),
@@ -291,6 +405,7 @@ impl State {
                 seat,
 
                 dnd_icon: None,
+                dnd_source: None,
 
                 popup_manager: PopupManager::default(),
 
@@ -300,6 +415,7 @@ impl State {
                 xwayland,
                 xwm: None,
                 xdisplay: None,
+                x11_worker: None,
 
                 system_processes: sysinfo::System::new_with_specifics(
                     RefreshKind::new().with_processes(ProcessRefreshKind::new()),
@@ -307,15 +423,26 @@ impl State {
 
                 grpc_server_join_handle: None,
 
-                xdg_base_dirs: BaseDirectories::with_prefix("pinnacle")
-                    .context("couldn't create xdg BaseDirectories")?,
+                output_profiles: OutputProfiles::load(&xdg_base_dirs),
+                remembered_geometries: RememberedGeometries::load(&xdg_base_dirs),
+                session: Session::load(&xdg_base_dirs),
+                xdg_base_dirs,
 
                 signal_state: SignalState::default(),
 
                 layout_state: LayoutState::default(),
+
+                snap_guides: SnapGuides::default(),
+                edge_tile_preview: EdgeTilePreview::default(),
+
+                recording: None,
+
+                debug_settings: DebugSettings::default(),
             },
         };
 
+        state.start_pinging_clients();
+
         Ok(state)
     }
 }
@@ -340,6 +467,7 @@ impl Pinnacle {
 
     pub fn shutdown(&mut self) {
         info!("Shutting down Pinnacle");
+        self.pinnacle.flush_session();
         self.loop_signal.stop();
         if let Some(join_handle) = self.config.config_join_handle.take() {
             join_handle.abort();
@@ -355,6 +483,28 @@ impl Pinnacle {
 #[derive(Default)]
 pub struct ClientState {
     pub compositor_state: CompositorClientState,
+    /// The security context this client connected through, if any.
+    ///
+    /// Clients with a security context are sandboxed (e.g. Flatpak apps) and
+    /// don't get access to privileged globals.
+    pub security_context: Option<SecurityContext>,
+}
+
+impl ClientState {
+    /// Returns whether this client connected through a security context.
+    pub fn is_sandboxed(&self) -> bool {
+        self.security_context.is_some()
+    }
+}
+
+/// Returns whether the given client is allowed to see privileged globals like
+/// screencopy, image copy capture, data control, and gamma control.
+///
+/// Clients that aren't a [`ClientState`], like Xwayland, are unrestricted.
+pub fn client_is_unrestricted(client: &Client) -> bool {
+    client
+        .get_data::<ClientState>()
+        .map_or(true, |client_state| !client_state.is_sandboxed())
 }
 
 impl ClientData for ClientState {
@@ -1,5 +1,11 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+//! Tags.
+//!
+//! In dynamic mode, every output keeps one empty tag after the last tag that has windows
+//! or is active, adding a new one when that tag gets a window. Any other empty tags at the
+//! end are hidden: relative switching skips them and they aren't advertised as workspaces.
+
 use std::{
     cell::RefCell,
     hash::Hash,
@@ -7,9 +13,18 @@ use std::{
     sync::atomic::{AtomicU32, Ordering},
 };
 
+use pinnacle_api_defs::pinnacle::signal::v0alpha1::TagOccupiedResponse;
 use smithay::output::Output;
 
-use crate::state::{Pinnacle, State, WithState};
+use crate::{
+    layout::gaps::Gaps,
+    output::OutputName,
+    state::{Pinnacle, State, WithState},
+    window::window_state::FloatingOrTiled,
+};
+
+/// How many previously active sets of tags each output remembers.
+const TAG_HISTORY_LEN: usize = 32;
 
 static TAG_ID_COUNTER: AtomicU32 = AtomicU32::new(0);
 
@@ -49,6 +64,18 @@ struct TagInner {
     name: String,
     /// Whether this tag is active or not.
     active: bool,
+    /// The gaps around tiled windows while this tag is active.
+    gaps: Gaps,
+    /// The name of the layout last used on this tag.
+    ///
+    /// Layout clients pick what this means; the compositor only remembers it.
+    layout: Option<String>,
+    /// The corner radius of windows while this tag is active, overriding the global one.
+    corner_radius: Option<f64>,
+    /// Whether any window had this tag when occupancy was last signaled.
+    occupied: bool,
+    /// Whether this is an empty trailing tag hidden in dynamic mode.
+    hidden: bool,
 }
 
 impl PartialEq for TagInner {
@@ -76,12 +103,48 @@ impl Tag {
         self.0.borrow().name.clone()
     }
 
+    pub fn set_name(&self, name: String) {
+        self.0.borrow_mut().name = name;
+    }
+
     pub fn active(&self) -> bool {
         self.0.borrow().active
     }
 
+    pub fn gaps(&self) -> Gaps {
+        self.0.borrow().gaps
+    }
+
+    pub fn set_gaps(&self, gaps: Gaps) {
+        self.0.borrow_mut().gaps = gaps;
+    }
+
+    pub fn layout(&self) -> Option<String> {
+        self.0.borrow().layout.clone()
+    }
+
+    pub fn set_layout(&self, layout: Option<String>) {
+        self.0.borrow_mut().layout = layout;
+    }
+
+    pub fn hidden(&self) -> bool {
+        self.0.borrow().hidden
+    }
+
+    pub fn corner_radius(&self) -> Option<f64> {
+        self.0.borrow().corner_radius
+    }
+
+    pub fn set_corner_radius(&self, corner_radius: Option<f64>) {
+        self.0.borrow_mut().corner_radius = corner_radius;
+    }
+
+    /// Activate or deactivate this tag.
+    ///
+    /// Activating it shows its windows, so they stop being urgent.
     pub fn set_active(&self, active: bool, state: &mut State) {
         self.0.borrow_mut().active = active;
+        state.pinnacle.session_changed();
 
         state.pinnacle.signal_state.tag_active.signal(|buf| {
             buf.push_back(
@@ -90,7 +153,26 @@ impl Tag {
                     active: Some(self.active()),
                 },
             );
-        })
+        });
+
+        state
+            .pinnacle
+            .ext_workspace_manager_state
+            .set_workspace_active(self.id(), active);
+
+        if active {
+            let urgent_windows = state
+                .pinnacle
+                .windows
+                .iter()
+                .filter(|win| win.with_state(|state| state.urgent && state.tags.contains(self)))
+                .cloned()
+                .collect::<Vec<_>>();
+
+            for window in urgent_windows {
+                state.pinnacle.set_window_urgent(&window, false);
+            }
+        }
     }
 }
 
@@ -100,6 +182,11 @@ impl Tag {
             id: TagId::next(),
             name,
             active: false,
+            gaps: Gaps::default(),
+            layout: None,
+            corner_radius: None,
+            occupied: false,
+            hidden: false,
         })))
     }
 
@@ -114,3 +201,476 @@ impl Tag {
             .cloned()
     }
 }
+
+/// Dynamic tag settings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DynamicTagSettings {
+    /// Whether outputs keep one empty tag at the end and hide any other empty trailing tags.
+    pub enabled: bool,
+}
+
+/// Get whether each tag is hidden in dynamic mode, given whether each has windows or is active.
+///
+/// Tags after the last used one are hidden, except for the first of them.
+fn hidden_trailing_tags(used: &[bool]) -> Vec<bool> {
+    let first_trailing = used
+        .iter()
+        .rposition(|used| *used)
+        .map_or(0, |last| last + 1);
+    (0..used.len()).map(|i| i > first_trailing).collect()
+}
+
+/// Get a name for a tag added in dynamic mode.
+///
+/// This is one more than the largest numeric name, or one more than the number of tags
+/// if none are numeric.
+fn next_dynamic_tag_name(names: &[String]) -> String {
+    names
+        .iter()
+        .filter_map(|name| name.parse::<u32>().ok())
+        .max()
+        .map_or(names.len() + 1, |max| max as usize + 1)
+        .to_string()
+}
+
+/// The sets of tags that were active on an output, most recent last.
+#[derive(Debug, Default)]
+pub struct TagHistory {
+    /// The tags active when the history was last updated.
+    current: Vec<Tag>,
+    /// Previously active sets of tags, without duplicates or the current set.
+    previous: Vec<Vec<Tag>>,
+}
+
+impl TagHistory {
+    /// Record `active` as the current set of tags.
+    ///
+    /// If it changed, the old set moves to the top of the history. An empty set is never
+    /// recorded so switching through no tags doesn't fill the history with it.
+    fn update(&mut self, active: Vec<Tag>) {
+        if active == self.current {
+            return;
+        }
+
+        let old = std::mem::replace(&mut self.current, active);
+        self.previous
+            .retain(|tags| tags != &old && tags != &self.current);
+        if !old.is_empty() {
+            self.previous.push(old);
+        }
+
+        if self.previous.len() > TAG_HISTORY_LEN {
+            self.previous.remove(0);
+        }
+    }
+
+    /// Forget every tag not in `tags`, dropping sets that end up empty or duplicated.
+    fn retain_tags(&mut self, tags: &[Tag]) {
+        for mut set in std::mem::take(&mut self.previous) {
+            set.retain(|tag| tags.contains(tag));
+            if set.is_empty() || set == self.current {
+                continue;
+            }
+            self.previous.retain(|prev| prev != &set);
+            self.previous.push(set);
+        }
+    }
+
+    /// Get previously active sets of tags, most recent first.
+    pub fn previous(&self) -> impl Iterator<Item = &Vec<Tag>> {
+        self.previous.iter().rev()
+    }
+}
+
+impl Pinnacle {
+    /// Update every output's tag history with the tags currently active on it.
+    ///
+    /// Tags no longer on an output are forgotten by its history.
+    pub fn update_tag_history(&mut self) {
+        for output in self.space.outputs() {
+            output.with_state_mut(|state| {
+                let active = state.focused_tags().cloned().collect::<Vec<_>>();
+                state.tag_history.retain_tags(&state.tags);
+                state.tag_history.update(active);
+            });
+        }
+    }
+
+    /// Returns whether any window has `tag`.
+    pub fn tag_is_occupied(&self, tag: &Tag) -> bool {
+        self.windows
+            .iter()
+            .any(|win| win.with_state(|state| state.tags.contains(tag)))
+    }
+
+    /// Move `tag` to `index` among the tags on its output.
+    ///
+    /// Indices past the last tag move it to the end.
+    pub fn set_tag_index(&self, tag: &Tag, index: usize) {
+        let Some(output) = tag.output(self) else {
+            return;
+        };
+
+        output.with_state_mut(|state| {
+            let Some(current) = state.tags.iter().position(|tg| tg == tag) else {
+                return;
+            };
+            let tag = state.tags.remove(current);
+            let index = index.min(state.tags.len());
+            state.tags.insert(index, tag);
+        });
+    }
+
+    /// Keep an empty tag at the end of every output and hide other empty trailing tags
+    /// if dynamic tags are enabled, or unhide every tag if not.
+    pub fn update_dynamic_tags(&mut self) {
+        let outputs = self.space.outputs().cloned().collect::<Vec<_>>();
+
+        for output in outputs {
+            let tags = output.with_state(|state| state.tags.clone());
+
+            if !self.config.dynamic_tags.enabled {
+                for tag in tags {
+                    tag.0.borrow_mut().hidden = false;
+                }
+                continue;
+            }
+
+            // Outputs without tags are left for the config to set up
+            let Some(last) = tags.last() else {
+                continue;
+            };
+
+            if self.tag_is_occupied(last) {
+                let names = tags.iter().map(|tag| tag.name()).collect::<Vec<_>>();
+                let new_tag = Tag::new(next_dynamic_tag_name(&names));
+
+                self.config
+                    .connector_saved_states
+                    .entry(OutputName(output.name()))
+                    .or_default()
+                    .tags
+                    .push(new_tag.clone());
+                output.with_state_mut(|state| state.tags.push(new_tag));
+                self.session_changed();
+            }
+
+            let tags = output.with_state(|state| state.tags.clone());
+            let used = tags
+                .iter()
+                .map(|tag| tag.active() || self.tag_is_occupied(tag))
+                .collect::<Vec<_>>();
+
+            for (tag, hidden) in tags.iter().zip(hidden_trailing_tags(&used)) {
+                tag.0.borrow_mut().hidden = hidden;
+            }
+        }
+    }
+
+    /// Signal every tag that gained its first window or lost its last one since this was
+    /// last called.
+    pub fn update_tag_occupancy(&mut self) {
+        let tags = self
+            .space
+            .outputs()
+            .flat_map(|op| op.with_state(|state| state.tags.clone()))
+            .collect::<Vec<_>>();
+
+        for tag in tags {
+            let occupied = self.tag_is_occupied(&tag);
+            if tag.0.borrow().occupied == occupied {
+                continue;
+            }
+            tag.0.borrow_mut().occupied = occupied;
+
+            self.signal_state.tag_occupied.signal(|buf| {
+                buf.push_back(TagOccupiedResponse {
+                    tag_id: Some(tag.id().0),
+                    occupied: Some(occupied),
+                })
+            });
+        }
+    }
+}
+
+impl State {
+    /// Activate the `nth` most recent set of tags that was active on `output`, deactivating
+    /// its other tags.
+    ///
+    /// The most recent set is at index 0, so switching to it repeatedly
+    /// flips between the two most recently active sets.
+    pub fn switch_to_previous_tags(&mut self, output: &Output, nth: usize) {
+        let Some(tags) = output.with_state(|state| state.tag_history.previous().nth(nth).cloned())
+        else {
+            return;
+        };
+
+        output.with_state_mut(|state| {
+            for tag in state.tags.iter() {
+                tag.set_active(tags.contains(tag), self);
+            }
+        });
+
+        self.pinnacle.fixup_xwayland_window_layering();
+
+        self.pinnacle.request_layout(output);
+        self.update_focus(output);
+        self.schedule_render(output);
+    }
+
+    /// Switch `output` to the tag `offset` places away from its first active tag in tag order,
+    /// wrapping around the ends.
+    ///
+    /// If no tag is active, positive offsets count from before the first tag and negative
+    /// ones from after the last. Hidden tags are skipped.
+    pub fn switch_to_relative_tag(&mut self, output: &Output, offset: i32) {
+        let tags = output.with_state(|state| {
+            state
+                .tags
+                .iter()
+                .filter(|tag| !tag.hidden())
+                .cloned()
+                .collect::<Vec<_>>()
+        });
+        if tags.is_empty() {
+            return;
+        }
+
+        let current = match tags.iter().position(|tag| tag.active()) {
+            Some(current) => current as i64,
+            None if offset > 0 => -1,
+            None => tags.len() as i64,
+        };
+        let target = (current + offset as i64).rem_euclid(tags.len() as i64) as usize;
+
+        for (i, tag) in tags.iter().enumerate() {
+            tag.set_active(i == target, self);
+        }
+
+        self.pinnacle.fixup_xwayland_window_layering();
+
+        self.pinnacle.request_layout(output);
+        self.update_focus(output);
+        self.schedule_render(output);
+    }
+
+    /// Remove `tags` from their outputs.
+    ///
+    /// Windows left without tags are moved to `fallback` if it's given and isn't being
+    /// removed. Otherwise they're moved to the first active tag left on their output,
+    /// or its first tag if none are active. Outputs left without an active tag
+    /// activate the tag windows would be moved to.
+    pub fn remove_tags(&mut self, tags: Vec<Tag>, fallback: Option<Tag>) {
+        let fallback = fallback.filter(|tag| !tags.contains(tag));
+
+        let affected_outputs = tags
+            .iter()
+            .filter_map(|tag| tag.output(&self.pinnacle))
+            .fold(Vec::<Output>::new(), |mut outputs, output| {
+                if !outputs.contains(&output) {
+                    outputs.push(output);
+                }
+                outputs
+            });
+
+        let orphaned_windows = self
+            .pinnacle
+            .windows
+            .iter()
+            .filter(|win| win.with_state(|state| state.tags.iter().any(|tg| tags.contains(tg))))
+            .map(|win| (win.clone(), win.output(&self.pinnacle)))
+            .collect::<Vec<_>>();
+
+        for output in self.pinnacle.space.outputs() {
+            output.with_state_mut(|state| state.tags.retain(|tag| !tags.contains(tag)));
+        }
+
+        for conn_saved_state in self.pinnacle.config.connector_saved_states.values_mut() {
+            conn_saved_state.tags.retain(|tag| !tags.contains(tag));
+        }
+        self.pinnacle.session_changed();
+
+        let output_fallback = |output: &Output| {
+            output.with_state(|state| {
+                state
+                    .focused_tags()
+                    .next()
+                    .or_else(|| state.tags.first())
+                    .cloned()
+            })
+        };
+
+        for output in affected_outputs.iter() {
+            if output.with_state(|state| state.focused_tags().next().is_some()) {
+                continue;
+            }
+
+            let tag = fallback
+                .clone()
+                .filter(|tag| tag.output(&self.pinnacle).as_ref() == Some(output))
+                .or_else(|| output_fallback(output));
+
+            if let Some(tag) = tag {
+                tag.set_active(true, self);
+            }
+        }
+
+        for (window, output) in orphaned_windows {
+            let new_tag = fallback
+                .clone()
+                .or_else(|| output.as_ref().and_then(output_fallback));
+
+            window.with_state_mut(|state| {
+                state.tags.retain(|tag| !tags.contains(tag));
+                if state.tags.is_empty() {
+                    state.tags.extend(new_tag);
+                }
+            });
+        }
+
+        self.pinnacle.fixup_xwayland_window_layering();
+
+        for output in self.pinnacle.space.outputs().cloned().collect::<Vec<_>>() {
+            self.pinnacle.request_layout(&output);
+            self.schedule_render(&output);
+        }
+    }
+
+    /// Move `tag` and its windows to `output`.
+    ///
+    /// Floating windows with the tag keep their position relative to the output.
+    /// If the tag's old output is left without an active tag, it activates its first tag.
+    pub fn move_tag_to_output(&mut self, tag: &Tag, output: &Output) {
+        let Some(old_output) = tag.output(&self.pinnacle) else {
+            return;
+        };
+
+        if &old_output == output {
+            return;
+        }
+
+        old_output.with_state_mut(|state| state.tags.retain(|tg| tg != tag));
+        output.with_state_mut(|state| state.tags.push(tag.clone()));
+
+        for conn_saved_state in self.pinnacle.config.connector_saved_states.values_mut() {
+            conn_saved_state.tags.retain(|tg| tg != tag);
+        }
+        self.pinnacle
+            .config
+            .connector_saved_states
+            .entry(OutputName(output.name()))
+            .or_default()
+            .tags
+            .push(tag.clone());
+        self.pinnacle.session_changed();
+
+        if let (Some(old_geo), Some(new_geo)) = (
+            self.pinnacle.space.output_geometry(&old_output),
+            self.pinnacle.space.output_geometry(output),
+        ) {
+            let offset = new_geo.loc - old_geo.loc;
+
+            for window in self.pinnacle.windows.iter() {
+                window.with_state_mut(|state| {
+                    if !state.tags.contains(tag) {
+                        return;
+                    }
+                    if let FloatingOrTiled::Floating(rect) = &mut state.floating_or_tiled {
+                        rect.loc += offset;
+                    }
+                });
+            }
+        }
+
+        if old_output.with_state(|state| state.focused_tags().next().is_none()) {
+            if let Some(first_tag) = old_output.with_state(|state| state.tags.first().cloned()) {
+                first_tag.set_active(true, self);
+            }
+        }
+
+        self.pinnacle.fixup_xwayland_window_layering();
+
+        for op in [old_output, output.clone()] {
+            self.pinnacle.request_layout(&op);
+            self.schedule_render(&op);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dynamic_mode_hides_all_but_one_trailing_tag() {
+        assert_eq!(
+            hidden_trailing_tags(&[true, false, true, false, false, false]),
+            vec![false, false, false, false, true, true]
+        );
+        assert_eq!(hidden_trailing_tags(&[false, false]), vec![false, true]);
+        assert_eq!(hidden_trailing_tags(&[true, true]), vec![false, false]);
+    }
+
+    #[test]
+    fn dynamic_tag_names_continue_numbering() {
+        let names = |names: &[&str]| {
+            names
+                .iter()
+                .map(|name| name.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(next_dynamic_tag_name(&names(&["1", "2", "5"])), "6");
+        assert_eq!(next_dynamic_tag_name(&names(&["web", "chat"])), "3");
+        assert_eq!(next_dynamic_tag_name(&names(&[])), "1");
+    }
+
+    #[test]
+    fn history_flips_between_recent_sets() {
+        let one = Tag::new("1".to_string());
+        let two = Tag::new("2".to_string());
+        let three = Tag::new("3".to_string());
+
+        let mut history = TagHistory::default();
+        history.update(vec![one.clone()]);
+        history.update(vec![two.clone()]);
+        history.update(vec![two.clone(), three.clone()]);
+
+        assert_eq!(
+            history.previous().cloned().collect::<Vec<_>>(),
+            vec![vec![two.clone()], vec![one.clone()]]
+        );
+
+        // Switching back to the previous set puts the current one on top
+        history.update(vec![two.clone()]);
+        assert_eq!(
+            history.previous().cloned().collect::<Vec<_>>(),
+            vec![vec![two.clone(), three.clone()], vec![one.clone()]]
+        );
+
+        history.update(Vec::new());
+        history.update(vec![one.clone()]);
+        assert_eq!(
+            history.previous().cloned().collect::<Vec<_>>(),
+            vec![vec![two.clone()], vec![two.clone(), three.clone()]]
+        );
+    }
+
+    #[test]
+    fn history_forgets_removed_tags() {
+        let one = Tag::new("1".to_string());
+        let two = Tag::new("2".to_string());
+        let three = Tag::new("3".to_string());
+
+        let mut history = TagHistory::default();
+        history.update(vec![one.clone()]);
+        history.update(vec![one.clone(), two.clone()]);
+        history.update(vec![three.clone()]);
+
+        history.retain_tags(&[two.clone(), three.clone()]);
+        assert_eq!(
+            history.previous().cloned().collect::<Vec<_>>(),
+            vec![vec![two.clone()]]
+        );
+    }
+}
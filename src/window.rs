@@ -1,6 +1,13 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+pub mod edge_tile;
+pub mod group;
+pub mod pip;
+pub mod placement;
+pub mod remembered_geometry;
 pub mod rules;
+pub mod unresponsive;
+pub mod urgency;
 
 use std::{cell::RefCell, ops::Deref};
 
@@ -8,11 +15,19 @@ use smithay::{
     desktop::{space::SpaceElement, Window, WindowSurface},
     output::Output,
     reexports::wayland_server::protocol::wl_surface::WlSurface,
-    utils::{IsAlive, Logical, Point, Rectangle},
-    wayland::{compositor, seat::WaylandFocus, shell::xdg::XdgToplevelSurfaceData},
+    utils::{IsAlive, Logical, Point, Rectangle, Serial},
+    wayland::{
+        compositor,
+        content_type::{ContentType, ContentTypeSurfaceCachedState},
+        seat::WaylandFocus,
+        shell::xdg::XdgToplevelSurfaceData,
+    },
 };
 
-use crate::state::{Pinnacle, WithState};
+use crate::{
+    state::{Pinnacle, State, WithState},
+    tag::Tag,
+};
 
 use self::window_state::WindowElementState;
 
@@ -60,6 +75,28 @@ impl WindowElement {
         });
     }
 
+    /// Returns whether this window has acked and committed the configure with `serial`
+    /// or a later one.
+    ///
+    /// Xwayland windows don't ack configures and always return `true`.
+    pub fn has_committed(&self, serial: Serial) -> bool {
+        match self.0.underlying_surface() {
+            WindowSurface::Wayland(toplevel) => {
+                compositor::with_states(toplevel.wl_surface(), |states| {
+                    states
+                        .data_map
+                        .get::<XdgToplevelSurfaceData>()
+                        .expect("XdgToplevelSurfaceData wasn't in surface's data map")
+                        .lock()
+                        .expect("Failed to lock Mutex<XdgToplevelSurfaceData>")
+                        .current_serial
+                        .is_some_and(|current| current.is_no_older_than(&serial))
+                })
+            }
+            WindowSurface::X11(_) => true,
+        }
+    }
+
     /// Get this window's class (app id in Wayland but hey old habits die hard).
     pub fn class(&self) -> Option<String> {
         match self.0.underlying_surface() {
@@ -98,6 +135,30 @@ impl WindowElement {
         }
     }
 
+    /// Get the content type this window's surface has hinted with `wp_content_type_v1`.
+    ///
+    /// Xwayland windows and windows that haven't set a content type return [`ContentType::None`].
+    pub fn content_type(&self) -> ContentType {
+        let Some(surface) = self.wl_surface() else {
+            return ContentType::None;
+        };
+
+        compositor::with_states(&surface, |states| {
+            *states
+                .cached_state
+                .current::<ContentTypeSurfaceCachedState>()
+                .content_type()
+        })
+    }
+
+    /// Returns whether this window has hinted that it's displaying a game or video.
+    ///
+    /// These windows aren't dimmed or animated, and on-demand VRR is enabled while one
+    /// is fullscreen.
+    pub fn is_game_or_video(&self) -> bool {
+        matches!(self.content_type(), ContentType::Game | ContentType::Video)
+    }
+
     /// Get the output this window is on.
     ///
     /// This method gets the first tag the window has and returns its output.
@@ -232,4 +293,56 @@ impl Pinnacle {
             .find(|&win| win.wl_surface().is_some_and(|surf| &surf == surface))
             .cloned()
     }
+
+    /// Returns the window attached to the ongoing drag-and-drop through `xdg-toplevel-drag`,
+    /// along with its offset from the pointer.
+    pub fn toplevel_drag_window(&self) -> Option<(WindowElement, Point<i32, Logical>)> {
+        let source = self.dnd_source.as_ref()?;
+        let (toplevel, offset) = self
+            .xdg_toplevel_drag_manager_state
+            .attached_toplevel(source)?;
+
+        self.windows
+            .iter()
+            .find(|win| {
+                win.toplevel()
+                    .is_some_and(|surface| surface.xdg_toplevel() == &toplevel)
+            })
+            .map(|win| (win.clone(), offset))
+    }
+}
+
+impl State {
+    /// Set the tags of `window`.
+    ///
+    /// A window shows up while any of its tags is active. Its tags stay on one output,
+    /// so tags on a different output than the first one are dropped. Both the output the
+    /// window was on and the one it's on now are laid out again.
+    pub fn set_window_tags(&mut self, window: &WindowElement, tags: Vec<Tag>) {
+        let old_output = window.output(&self.pinnacle);
+        let output = tags.first().and_then(|tag| tag.output(&self.pinnacle));
+
+        let mut new_tags = Vec::<Tag>::new();
+        for tag in tags {
+            if !new_tags.contains(&tag) && tag.output(&self.pinnacle) == output {
+                new_tags.push(tag);
+            }
+        }
+
+        window.with_state_mut(|state| state.tags = new_tags);
+        self.pinnacle.session_changed();
+
+        self.pinnacle.fixup_xwayland_window_layering();
+
+        let mut outputs = old_output.into_iter().collect::<Vec<_>>();
+        if let Some(output) = output.filter(|output| !outputs.contains(output)) {
+            outputs.push(output);
+        }
+
+        for output in outputs {
+            self.pinnacle.request_layout(&output);
+            self.update_focus(&output);
+            self.schedule_render(&output);
+        }
+    }
 }
@@ -0,0 +1,284 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Tiling floating windows by dragging them to output edges.
+//!
+//! When the pointer gets close to an edge of an output while a floating window is being
+//! moved, a preview of the half or quarter of the output the window would be tiled to is
+//! shown. Dropping the window tiles it there. The window stays floating, but remembers
+//! which part of the output it covers so it can be put back when the output changes,
+//! and gets its old size back once it's dragged away.
+
+use smithay::{
+    backend::renderer::element::Id,
+    desktop::layer_map_for_output,
+    output::Output,
+    utils::{Logical, Point, Rectangle},
+};
+
+use crate::{
+    grab::move_grab::loc_keeping_pointer,
+    state::{Pinnacle, State, WithState},
+};
+
+use super::{window_state::FloatingOrTiled, WindowElement};
+
+/// How close the pointer has to get to an output edge to tile the window.
+const EDGE_TRIGGER: f64 = 8.0;
+/// How far from a corner along an edge the pointer tiles the window to a quarter.
+const CORNER_TRIGGER: f64 = 64.0;
+
+/// The part of an output a window was dragged to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeTile {
+    Left,
+    Right,
+    Top,
+    Bottom,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl EdgeTile {
+    /// Get the part of an output with geometry `output_geo` the pointer at `pointer`
+    /// would tile a window to, if any.
+    pub fn under_pointer(
+        pointer: Point<f64, Logical>,
+        output_geo: Rectangle<i32, Logical>,
+    ) -> Option<Self> {
+        let output_geo = output_geo.to_f64();
+
+        let left = pointer.x - output_geo.loc.x;
+        let top = pointer.y - output_geo.loc.y;
+        let right = output_geo.size.w - left;
+        let bottom = output_geo.size.h - top;
+
+        let near_left = left < EDGE_TRIGGER;
+        let near_right = right <= EDGE_TRIGGER;
+        let near_top = top < EDGE_TRIGGER;
+        let near_bottom = bottom <= EDGE_TRIGGER;
+
+        let in_top_corner = top < CORNER_TRIGGER;
+        let in_bottom_corner = bottom <= CORNER_TRIGGER;
+        let in_left_corner = left < CORNER_TRIGGER;
+        let in_right_corner = right <= CORNER_TRIGGER;
+
+        if (near_left && in_top_corner) || (near_top && in_left_corner) {
+            Some(EdgeTile::TopLeft)
+        } else if (near_right && in_top_corner) || (near_top && in_right_corner) {
+            Some(EdgeTile::TopRight)
+        } else if (near_left && in_bottom_corner) || (near_bottom && in_left_corner) {
+            Some(EdgeTile::BottomLeft)
+        } else if (near_right && in_bottom_corner) || (near_bottom && in_right_corner) {
+            Some(EdgeTile::BottomRight)
+        } else if near_left {
+            Some(EdgeTile::Left)
+        } else if near_right {
+            Some(EdgeTile::Right)
+        } else if near_top {
+            Some(EdgeTile::Top)
+        } else if near_bottom {
+            Some(EdgeTile::Bottom)
+        } else {
+            None
+        }
+    }
+
+    /// Get the geometry of this part of `area`.
+    pub fn geometry(&self, area: Rectangle<i32, Logical>) -> Rectangle<i32, Logical> {
+        let half_w = area.size.w / 2;
+        let half_h = area.size.h / 2;
+        let (x, y) = (area.loc.x, area.loc.y);
+        let (w, h) = (area.size.w, area.size.h);
+
+        let (loc, size) = match self {
+            EdgeTile::Left => ((x, y), (half_w, h)),
+            EdgeTile::Right => ((x + half_w, y), (w - half_w, h)),
+            EdgeTile::Top => ((x, y), (w, half_h)),
+            EdgeTile::Bottom => ((x, y + half_h), (w, h - half_h)),
+            EdgeTile::TopLeft => ((x, y), (half_w, half_h)),
+            EdgeTile::TopRight => ((x + half_w, y), (w - half_w, half_h)),
+            EdgeTile::BottomLeft => ((x, y + half_h), (half_w, h - half_h)),
+            EdgeTile::BottomRight => ((x + half_w, y + half_h), (w - half_w, h - half_h)),
+        };
+
+        Rectangle::from_loc_and_size(loc, size)
+    }
+}
+
+/// The drop-zone preview shown while a window is dragged to an output edge.
+#[derive(Debug)]
+pub struct EdgeTilePreview {
+    /// The area the window would be tiled to, in global coordinates.
+    pub rect: Option<Rectangle<i32, Logical>>,
+    pub id: Id,
+}
+
+impl Default for EdgeTilePreview {
+    fn default() -> Self {
+        Self {
+            rect: None,
+            id: Id::new(),
+        }
+    }
+}
+
+impl Pinnacle {
+    /// Get the geometry `tile` covers on `output`, leaving out layer-shell exclusive zones.
+    pub fn edge_tile_geometry(
+        &self,
+        tile: EdgeTile,
+        output: &Output,
+    ) -> Option<Rectangle<i32, Logical>> {
+        let output_geo = self.space.output_geometry(output)?;
+
+        let mut area = layer_map_for_output(output).non_exclusive_zone();
+        area.loc += output_geo.loc;
+
+        Some(tile.geometry(area))
+    }
+
+    /// Tile the floating `window` to part of `output`.
+    pub fn edge_tile_window(&mut self, window: &WindowElement, tile: EdgeTile, output: &Output) {
+        let Some(geo) = self.edge_tile_geometry(tile, output) else {
+            return;
+        };
+
+        let size = window.geometry().size;
+
+        window.with_state_mut(|state| {
+            let untiled_size = state
+                .edge_tile
+                .map_or(size, |(_, untiled_size)| untiled_size);
+            state.edge_tile = Some((tile, untiled_size));
+            state.floating_or_tiled = FloatingOrTiled::Floating(geo);
+        });
+        window.change_geometry(geo);
+
+        self.request_layout(output);
+    }
+
+    /// Give an edge-tiled `window` its size from before it was tiled back.
+    ///
+    /// The window is moved so the pointer at `pointer` stays at the same relative
+    /// position across it.
+    pub fn untile_from_edge(&mut self, window: &WindowElement, pointer: Point<f64, Logical>) {
+        let Some((_, untiled_size)) = window.with_state_mut(|state| state.edge_tile.take()) else {
+            return;
+        };
+
+        let Some(loc) = self.space.element_location(window) else {
+            return;
+        };
+        let size = window.geometry().size;
+
+        let new_loc = loc_keeping_pointer(loc, size, untiled_size, pointer);
+
+        let geo = Rectangle::from_loc_and_size(new_loc, untiled_size);
+        window.with_state_mut(|state| {
+            if state.floating_or_tiled.is_floating() {
+                state.floating_or_tiled = FloatingOrTiled::Floating(geo);
+            }
+        });
+        window.change_geometry(geo);
+        self.space.map_element(window.clone(), new_loc, false);
+    }
+
+    /// Put every edge-tiled window on `output` back into its part of the output.
+    pub fn place_edge_tiled_windows(&mut self, output: &Output) {
+        let windows = self
+            .windows
+            .iter()
+            .filter(|win| {
+                win.with_state(|state| {
+                    state.edge_tile.is_some()
+                        && state.floating_or_tiled.is_floating()
+                        && state.fullscreen_or_maximized.is_neither()
+                })
+            })
+            .filter(|win| win.output(self).as_ref() == Some(output))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for window in windows {
+            if let Some((tile, _)) = window.with_state(|state| state.edge_tile) {
+                self.edge_tile_window(&window, tile, output);
+            }
+        }
+    }
+}
+
+impl State {
+    /// Show the drop-zone preview at `rect`, or hide it if `None`.
+    pub fn set_edge_tile_preview(&mut self, rect: Option<Rectangle<i32, Logical>>) {
+        if self.pinnacle.edge_tile_preview.rect == rect {
+            return;
+        }
+
+        self.pinnacle.edge_tile_preview.rect = rect;
+
+        for output in self.pinnacle.space.outputs().cloned().collect::<Vec<_>>() {
+            self.schedule_render(&output);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output() -> Rectangle<i32, Logical> {
+        Rectangle::from_loc_and_size((1920, 0), (1920, 1080))
+    }
+
+    #[test]
+    fn edges_tile_to_halves() {
+        assert_eq!(
+            EdgeTile::under_pointer((1921.0, 500.0).into(), output()),
+            Some(EdgeTile::Left)
+        );
+        assert_eq!(
+            EdgeTile::under_pointer((3839.0, 500.0).into(), output()),
+            Some(EdgeTile::Right)
+        );
+        assert_eq!(
+            EdgeTile::under_pointer((2800.0, 0.0).into(), output()),
+            Some(EdgeTile::Top)
+        );
+        assert_eq!(
+            EdgeTile::under_pointer((2800.0, 500.0).into(), output()),
+            None
+        );
+    }
+
+    #[test]
+    fn corners_tile_to_quarters() {
+        assert_eq!(
+            EdgeTile::under_pointer((1925.0, 20.0).into(), output()),
+            Some(EdgeTile::TopLeft)
+        );
+        assert_eq!(
+            EdgeTile::under_pointer((3800.0, 1079.0).into(), output()),
+            Some(EdgeTile::BottomRight)
+        );
+    }
+
+    #[test]
+    fn tile_geometry() {
+        let area = Rectangle::from_loc_and_size((0, 30), (1921, 1050));
+
+        assert_eq!(
+            EdgeTile::Left.geometry(area),
+            Rectangle::from_loc_and_size((0, 30), (960, 1050))
+        );
+        assert_eq!(
+            EdgeTile::Right.geometry(area),
+            Rectangle::from_loc_and_size((960, 30), (961, 1050))
+        );
+        assert_eq!(
+            EdgeTile::BottomLeft.geometry(area),
+            Rectangle::from_loc_and_size((0, 555), (960, 525))
+        );
+    }
+}
@@ -0,0 +1,231 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Window groups.
+//!
+//! A group is a set of tiled windows that share one tile. Only the group's active window
+//! is shown; the others are hidden behind it until the group is cycled. A tab or stack
+//! indicator drawn above the active window shows the group's windows.
+
+use std::{cell::RefCell, rc::Rc};
+
+use smithay::backend::renderer::{element::Id, utils::CommitCounter};
+
+use crate::state::{Pinnacle, WithState};
+
+use super::{window_state::WindowId, WindowElement};
+
+/// The height of one row of a group's indicator, in logical pixels.
+pub const INDICATOR_ROW_HEIGHT: i32 = 6;
+
+/// How a group's indicator shows its windows.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GroupMode {
+    /// Windows are shown as tabs next to each other in one row.
+    #[default]
+    Tabbed,
+    /// Windows are shown as rows stacked on top of each other.
+    Stacked,
+}
+
+#[derive(Debug)]
+struct WindowGroupInner {
+    /// The windows in this group, in tab order.
+    windows: Vec<WindowId>,
+    /// The window that is shown.
+    active: WindowId,
+    mode: GroupMode,
+    /// The ids of the indicator's segments, one per window.
+    indicator_ids: Vec<Id>,
+    /// Incremented whenever the indicator changes.
+    indicator_commit: CommitCounter,
+}
+
+/// A group of windows sharing one tile.
+///
+/// Every window in the group holds a clone of this.
+#[derive(Debug, Clone)]
+pub struct WindowGroup(Rc<RefCell<WindowGroupInner>>);
+
+impl PartialEq for WindowGroup {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+// RefCell Safety: These methods should never panic because they are all self-contained.
+impl WindowGroup {
+    fn new(windows: Vec<WindowId>, active: WindowId) -> Self {
+        let group = Self(Rc::new(RefCell::new(WindowGroupInner {
+            windows,
+            active,
+            mode: GroupMode::default(),
+            indicator_ids: Vec::new(),
+            indicator_commit: CommitCounter::default(),
+        })));
+        group.changed();
+        group
+    }
+
+    /// The windows in this group, in tab order.
+    pub fn windows(&self) -> Vec<WindowId> {
+        self.0.borrow().windows.clone()
+    }
+
+    /// The window that is shown.
+    pub fn active(&self) -> WindowId {
+        self.0.borrow().active
+    }
+
+    pub fn mode(&self) -> GroupMode {
+        self.0.borrow().mode
+    }
+
+    pub fn set_mode(&self, mode: GroupMode) {
+        self.0.borrow_mut().mode = mode;
+        self.changed();
+    }
+
+    /// The height of the indicator above the active window, in logical pixels.
+    pub fn indicator_height(&self) -> i32 {
+        let inner = self.0.borrow();
+        match inner.mode {
+            GroupMode::Tabbed => INDICATOR_ROW_HEIGHT,
+            GroupMode::Stacked => INDICATOR_ROW_HEIGHT * inner.windows.len() as i32,
+        }
+    }
+
+    /// The ids of the indicator's segments, one per window, and the indicator's commit.
+    pub fn indicator_ids(&self) -> (Vec<Id>, CommitCounter) {
+        let inner = self.0.borrow();
+        (inner.indicator_ids.clone(), inner.indicator_commit)
+    }
+
+    fn set_active(&self, active: WindowId) {
+        self.0.borrow_mut().active = active;
+        self.changed();
+    }
+
+    /// Mark the indicator as changed so it gets redrawn.
+    fn changed(&self) {
+        let mut inner = self.0.borrow_mut();
+        let len = inner.windows.len();
+        inner.indicator_ids.resize_with(len, Id::new);
+        inner.indicator_commit.increment();
+    }
+}
+
+impl WindowElement {
+    /// Returns whether this window is shown: it's on an active tag and isn't hidden behind
+    /// another window in its group.
+    ///
+    /// RefCell Safety: This calls `with_state` on `self`.
+    pub fn is_visible(&self) -> bool {
+        self.is_on_active_tag() && !self.is_hidden_in_group()
+    }
+
+    /// Returns whether this window is in a group and isn't its active window.
+    ///
+    /// RefCell Safety: This calls `with_state` on `self`.
+    pub fn is_hidden_in_group(&self) -> bool {
+        self.with_state(|state| {
+            state
+                .group
+                .as_ref()
+                .is_some_and(|group| group.active() != state.id)
+        })
+    }
+
+    /// Get the group this window is in.
+    pub fn group(&self) -> Option<WindowGroup> {
+        self.with_state(|state| state.group.clone())
+    }
+}
+
+impl Pinnacle {
+    /// Add `window` to the group `target` is in, creating one if `target` isn't in one.
+    ///
+    /// `window` is removed from its current group first and becomes the active window.
+    pub fn add_window_to_group(&mut self, window: &WindowElement, target: &WindowElement) {
+        if window == target {
+            return;
+        }
+
+        self.remove_window_from_group(window);
+
+        let window_id = window.with_state(|state| state.id);
+
+        let group = match target.group() {
+            Some(group) => {
+                group.0.borrow_mut().windows.push(window_id);
+                group.set_active(window_id);
+                group
+            }
+            None => {
+                let target_id = target.with_state(|state| state.id);
+                let group = WindowGroup::new(vec![target_id, window_id], window_id);
+                target.with_state_mut(|state| state.group = Some(group.clone()));
+                group
+            }
+        };
+
+        window.with_state_mut(|state| state.group = Some(group));
+    }
+
+    /// Remove `window` from its group.
+    ///
+    /// If only one window is left in the group, the group is dissolved.
+    pub fn remove_window_from_group(&mut self, window: &WindowElement) {
+        let Some(group) = window.with_state_mut(|state| state.group.take()) else {
+            return;
+        };
+
+        let window_id = window.with_state(|state| state.id);
+        let remaining = {
+            let mut inner = group.0.borrow_mut();
+            let index = inner.windows.iter().position(|id| id == &window_id);
+            if let Some(index) = index {
+                inner.windows.remove(index);
+                if inner.active == window_id {
+                    let next = inner.windows.get(index).or(inner.windows.last()).copied();
+                    if let Some(next) = next {
+                        inner.active = next;
+                    }
+                }
+            }
+            inner.windows.clone()
+        };
+        group.changed();
+
+        if let [last] = remaining.as_slice() {
+            if let Some(last) = last.window(self) {
+                last.with_state_mut(|state| state.group = None);
+            }
+        }
+    }
+
+    /// Show the window `offset` windows after the active one in `window`'s group,
+    /// wrapping around.
+    ///
+    /// Returns the newly active window.
+    pub fn cycle_window_group(
+        &mut self,
+        window: &WindowElement,
+        offset: isize,
+    ) -> Option<WindowElement> {
+        let group = window.group()?;
+
+        let windows = group.windows();
+        let index = windows.iter().position(|id| id == &group.active())?;
+        let next = windows[(index as isize + offset).rem_euclid(windows.len() as isize) as usize];
+
+        group.set_active(next);
+        next.window(self)
+    }
+
+    /// Make `window` the active window of its group, if it's in one.
+    pub fn show_window_in_group(&mut self, window: &WindowElement) {
+        if let Some(group) = window.group() {
+            group.set_active(window.with_state(|state| state.id));
+        }
+    }
+}
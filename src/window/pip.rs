@@ -0,0 +1,209 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Picture-in-picture windows.
+//!
+//! Windows that look like picture-in-picture players, or that a window rule marks as one,
+//! are floated, kept above other windows, put on every tag of their output, and pinned
+//! to a corner of the output. They are moved back into their corner whenever the output's
+//! mode, scale, or transform changes.
+
+use smithay::{
+    desktop::layer_map_for_output,
+    output::Output,
+    utils::{Logical, Rectangle, Size},
+};
+
+use crate::state::{Pinnacle, WithState};
+
+use super::{
+    window_state::{FloatingOrTiled, WindowLayer},
+    WindowElement,
+};
+
+/// The corner of an output picture-in-picture windows are pinned to.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PipCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+/// Where picture-in-picture windows are placed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PipPlacement {
+    pub corner: PipCorner,
+    /// The size of picture-in-picture windows, shrunk to fit small outputs.
+    pub size: Size<i32, Logical>,
+    /// The gap between the window and the edges of the output.
+    pub margin: i32,
+}
+
+impl Default for PipPlacement {
+    fn default() -> Self {
+        Self {
+            corner: PipCorner::default(),
+            size: Size::from((480, 270)),
+            margin: 16,
+        }
+    }
+}
+
+impl PipPlacement {
+    /// Get the geometry of a picture-in-picture window in `area`.
+    pub fn geometry(&self, area: Rectangle<i32, Logical>) -> Rectangle<i32, Logical> {
+        let size = Size::from((
+            self.size.w.min(area.size.w - self.margin * 2).max(1),
+            self.size.h.min(area.size.h - self.margin * 2).max(1),
+        ));
+
+        let left = area.loc.x + self.margin;
+        let right = area.loc.x + area.size.w - self.margin - size.w;
+        let top = area.loc.y + self.margin;
+        let bottom = area.loc.y + area.size.h - self.margin - size.h;
+
+        let loc = match self.corner {
+            PipCorner::TopLeft => (left, top),
+            PipCorner::TopRight => (right, top),
+            PipCorner::BottomLeft => (left, bottom),
+            PipCorner::BottomRight => (right, bottom),
+        };
+
+        Rectangle::from_loc_and_size(loc, size)
+    }
+}
+
+/// Returns whether a window with the given title and class looks like a
+/// picture-in-picture player.
+///
+/// Firefox titles these windows "Picture-in-Picture" and Chromium "Picture in picture".
+pub fn looks_like_pip(title: Option<&str>, class: Option<&str>) -> bool {
+    let title_matches = title
+        .is_some_and(|title| title.trim().replace('-', " ").to_lowercase() == "picture in picture");
+
+    let class_matches = class.is_some_and(|class| {
+        let name = class.rsplit('.').next().unwrap_or(class).to_lowercase();
+        name == "pip" || name == "pictureinpicture"
+    });
+
+    title_matches || class_matches
+}
+
+impl WindowElement {
+    /// Returns whether this window looks like a picture-in-picture player.
+    ///
+    /// RefCell Safety: This method uses a [`RefCell`][std::cell::RefCell] on this window.
+    pub fn looks_like_pip(&self) -> bool {
+        looks_like_pip(self.title().as_deref(), self.class().as_deref())
+    }
+}
+
+impl Pinnacle {
+    /// Turn `window` into a picture-in-picture window on its output.
+    pub fn make_pip(&mut self, window: &WindowElement) {
+        if window.with_state(|state| state.floating_or_tiled.is_tiled()) {
+            window.toggle_floating();
+        }
+
+        window.with_state_mut(|state| {
+            state.pip = true;
+            state.layer = WindowLayer::Above;
+        });
+
+        if let Some(output) = window.output(self) {
+            self.place_pip_window(window, &output);
+        }
+    }
+
+    /// Put every picture-in-picture window on `output` back into its corner.
+    pub fn place_pip_windows(&mut self, output: &Output) {
+        let windows = self
+            .windows
+            .iter()
+            .filter(|win| win.with_state(|state| state.pip))
+            .filter(|win| win.output(self).as_ref() == Some(output))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if windows.is_empty() {
+            return;
+        }
+
+        for window in windows.iter() {
+            self.place_pip_window(window, output);
+        }
+
+        self.request_layout(output);
+    }
+
+    /// Pin `window` to the corner of `output` and put it on all of the output's tags.
+    fn place_pip_window(&self, window: &WindowElement, output: &Output) {
+        let Some(output_geo) = self.space.output_geometry(output) else {
+            return;
+        };
+
+        let mut area = layer_map_for_output(output).non_exclusive_zone();
+        area.loc += output_geo.loc;
+
+        let geo = self.config.pip_placement.geometry(area);
+        let tags = output.with_state(|state| state.tags.clone());
+
+        window.with_state_mut(|state| {
+            state.floating_or_tiled = FloatingOrTiled::Floating(geo);
+            if !tags.is_empty() {
+                state.tags = tags;
+            }
+        });
+        window.change_geometry(geo);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_browser_pip_titles() {
+        assert!(looks_like_pip(Some("Picture-in-Picture"), Some("firefox")));
+        assert!(looks_like_pip(Some("Picture in picture"), None));
+        assert!(!looks_like_pip(
+            Some("Picture in picture - Wikipedia"),
+            None
+        ));
+    }
+
+    #[test]
+    fn detects_pip_classes() {
+        assert!(looks_like_pip(None, Some("org.example.Pip")));
+        assert!(!looks_like_pip(None, Some("pipewire")));
+    }
+
+    #[test]
+    fn pins_to_corner() {
+        let area = Rectangle::from_loc_and_size((100, 0), (1920, 1080));
+        let placement = PipPlacement::default();
+
+        assert_eq!(
+            placement.geometry(area),
+            Rectangle::from_loc_and_size((100 + 1920 - 16 - 480, 1080 - 16 - 270), (480, 270))
+        );
+
+        let placement = PipPlacement {
+            corner: PipCorner::TopLeft,
+            ..placement
+        };
+        assert_eq!(
+            placement.geometry(area),
+            Rectangle::from_loc_and_size((116, 16), (480, 270))
+        );
+    }
+
+    #[test]
+    fn shrinks_to_fit_small_outputs() {
+        let area = Rectangle::from_loc_and_size((0, 0), (400, 200));
+        let geo = PipPlacement::default().geometry(area);
+
+        assert_eq!(geo.size, Size::from((368, 168)));
+    }
+}
@@ -0,0 +1,129 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Placing new windows on tags and outputs.
+//!
+//! Window rules and spawn placements can put a new window on a tag or output other than the
+//! focused one. The window opens there without switching to it unless it's followed, in which
+//! case its output switches to its tag and it gets focused.
+//!
+//! Spawn placements are matched to windows by process id. Apps often open their windows from
+//! a child process, so the process tree is searched up to the spawned process.
+
+use std::time::{Duration, Instant};
+
+use smithay::{reexports::wayland_server::Resource, wayland::seat::WaylandFocus};
+use sysinfo::Pid;
+
+use crate::state::{Pinnacle, State, WithState};
+
+use super::{rules::WindowRule, WindowElement};
+
+/// How long a spawn placement waits for the spawned process to open a window.
+const SPAWN_PLACEMENT_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// Where the first window of a spawned process is placed.
+#[derive(Debug)]
+pub struct SpawnPlacement {
+    /// The rule applied to the window, with only its output, tags, and follow set.
+    pub rule: WindowRule,
+    pub spawned_at: Instant,
+}
+
+impl SpawnPlacement {
+    pub fn new(rule: WindowRule) -> Self {
+        Self {
+            rule,
+            spawned_at: Instant::now(),
+        }
+    }
+}
+
+impl Pinnacle {
+    /// Get the id of the process that owns `window`.
+    pub fn window_pid(&self, window: &WindowElement) -> Option<u32> {
+        if let Some(surface) = window.x11_surface() {
+            return surface.pid();
+        }
+
+        let client = window.wl_surface()?.client()?;
+        let credentials = client.get_credentials(&self.display_handle).ok()?;
+        u32::try_from(credentials.pid).ok()
+    }
+
+    /// Take the spawn placement of the process that opened `window`, or of one of
+    /// its ancestors.
+    pub fn take_spawn_placement(&mut self, window: &WindowElement) -> Option<WindowRule> {
+        self.spawn_placements
+            .retain(|_, placement| placement.spawned_at.elapsed() < SPAWN_PLACEMENT_TIMEOUT);
+
+        if self.spawn_placements.is_empty() {
+            return None;
+        }
+
+        let compositor_pid = std::process::id();
+        let mut pid = self.window_pid(window)?;
+
+        loop {
+            if let Some(placement) = self.spawn_placements.remove(&pid) {
+                return Some(placement.rule);
+            }
+
+            self.system_processes.refresh_process(Pid::from_u32(pid));
+            let parent = self
+                .system_processes
+                .process(Pid::from_u32(pid))?
+                .parent()?
+                .as_u32();
+
+            if parent == compositor_pid || parent == pid {
+                return None;
+            }
+            pid = parent;
+        }
+    }
+}
+
+impl State {
+    /// Show and focus `window`.
+    ///
+    /// If it isn't on an active tag, its output switches to its first tag.
+    pub fn switch_to_window(&mut self, window: &WindowElement) {
+        let Some(output) = window.output(&self.pinnacle) else {
+            return;
+        };
+
+        if !window.is_on_active_tag() {
+            let Some(tag) = window.with_state(|state| state.tags.first().cloned()) else {
+                return;
+            };
+
+            output.with_state_mut(|op_state| {
+                for op_tag in op_state.tags.iter_mut() {
+                    op_tag.set_active(false, self);
+                }
+                tag.set_active(true, self);
+            });
+
+            self.pinnacle.fixup_xwayland_window_layering();
+            self.pinnacle.request_layout(&output);
+        }
+
+        if window.is_hidden_in_group() {
+            self.pinnacle.show_window_in_group(window);
+        }
+
+        self.pinnacle.output_focus_stack.set_focus(output.clone());
+        output.with_state_mut(|state| state.focus_stack.set_focus(window.clone()));
+        self.pinnacle.raise_window(window.clone(), true);
+
+        self.update_focus(&output);
+        self.schedule_render(&output);
+    }
+
+    /// Switch to a newly opened `window` if a window rule or spawn placement follows it.
+    pub fn follow_new_window(&mut self, window: &WindowElement) {
+        if window.with_state_mut(|state| std::mem::take(&mut state.follow_on_map)) {
+            self.switch_to_window(window);
+        }
+    }
+}
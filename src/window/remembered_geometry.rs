@@ -0,0 +1,175 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Remembered floating window geometry.
+//!
+//! When a floating window closes, its geometry is saved under its app id or class.
+//! The next window of that app that opens gets the same geometry, unless a window rule
+//! sets one. Geometries are persisted to pinnacle's state directory.
+
+use std::{collections::BTreeMap, path::PathBuf};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use smithay::utils::{Logical, Rectangle};
+use tracing::error;
+use xdg::BaseDirectories;
+
+use crate::state::{Pinnacle, WithState};
+
+use super::{window_state::FloatingOrTiled, WindowElement};
+
+const REMEMBERED_GEOMETRY_FILE: &str = "floating_geometry.toml";
+
+/// The geometry of a floating window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RememberedGeometry {
+    pub loc: (i32, i32),
+    pub size: (i32, i32),
+}
+
+impl From<Rectangle<i32, Logical>> for RememberedGeometry {
+    fn from(rect: Rectangle<i32, Logical>) -> Self {
+        Self {
+            loc: rect.loc.into(),
+            size: rect.size.into(),
+        }
+    }
+}
+
+impl From<RememberedGeometry> for Rectangle<i32, Logical> {
+    fn from(geo: RememberedGeometry) -> Self {
+        Rectangle::from_loc_and_size(geo.loc, geo.size)
+    }
+}
+
+/// Floating window geometries keyed by app id or class, persisted to pinnacle's state directory.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RememberedGeometries {
+    geometries: BTreeMap<String, RememberedGeometry>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl RememberedGeometries {
+    /// Load geometries from disk, or start with none if they can't be read.
+    pub fn load(xdg_base_dirs: &BaseDirectories) -> Self {
+        let path = xdg_base_dirs
+            .get_state_home()
+            .join(REMEMBERED_GEOMETRY_FILE);
+
+        let mut geometries = match std::fs::read_to_string(&path) {
+            Ok(contents) => {
+                toml::from_str::<RememberedGeometries>(&contents).unwrap_or_else(|err| {
+                    error!("Failed to parse {}: {err}", path.display());
+                    RememberedGeometries::default()
+                })
+            }
+            Err(_) => RememberedGeometries::default(),
+        };
+
+        geometries.path = Some(path);
+        geometries
+    }
+
+    fn save(&self) -> anyhow::Result<()> {
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, toml::to_string(self)?)
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// Get the remembered geometry of every app.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, RememberedGeometry)> {
+        self.geometries
+            .iter()
+            .map(|(app_id, geo)| (app_id.as_str(), *geo))
+    }
+}
+
+impl Pinnacle {
+    /// Remember the geometry of `window` for its app if it's an ordinary floating window.
+    ///
+    /// Picture-in-picture and edge-tiled windows are skipped, as their geometry
+    /// comes from their placement rather than from the user.
+    pub fn remember_floating_geometry(&mut self, window: &WindowElement) {
+        let Some(app_id) = window.class() else {
+            return;
+        };
+
+        let rect = window.with_state(|state| match state.floating_or_tiled {
+            FloatingOrTiled::Floating(rect)
+                if state.fullscreen_or_maximized.is_neither()
+                    && !state.pip
+                    && state.edge_tile.is_none() =>
+            {
+                Some(rect)
+            }
+            _ => None,
+        });
+
+        let Some(rect) = rect else {
+            return;
+        };
+
+        let geo = RememberedGeometry::from(rect);
+        if self.remembered_geometries.geometries.get(&app_id) == Some(&geo) {
+            return;
+        }
+
+        self.remembered_geometries.geometries.insert(app_id, geo);
+
+        if let Err(err) = self.remembered_geometries.save() {
+            error!("Failed to save remembered floating geometry: {err}");
+        }
+    }
+
+    /// Give a new `window` the remembered geometry of its app, if there is one and
+    /// it's still on an output.
+    ///
+    /// Tiled windows get it once they're floated.
+    pub fn restore_floating_geometry(&self, window: &WindowElement) {
+        let Some(rect) = window
+            .class()
+            .and_then(|app_id| self.remembered_geometries.geometries.get(&app_id).copied())
+            .map(Rectangle::from)
+        else {
+            return;
+        };
+
+        let on_output = self.space.outputs().any(|output| {
+            self.space
+                .output_geometry(output)
+                .is_some_and(|output_geo| output_geo.overlaps(rect))
+        });
+
+        if !on_output {
+            return;
+        }
+
+        window.with_state_mut(|state| {
+            state.floating_or_tiled = match state.floating_or_tiled {
+                FloatingOrTiled::Floating(_) => FloatingOrTiled::Floating(rect),
+                FloatingOrTiled::Tiled(_) => FloatingOrTiled::Tiled(Some(rect)),
+            };
+        });
+    }
+
+    /// Forget the remembered geometry of the app with `app_id`, or of every app if `None`.
+    pub fn forget_floating_geometry(&mut self, app_id: Option<&str>) {
+        match app_id {
+            Some(app_id) => {
+                self.remembered_geometries.geometries.remove(app_id);
+            }
+            None => self.remembered_geometries.geometries.clear(),
+        }
+
+        if let Err(err) = self.remembered_geometries.save() {
+            error!("Failed to save remembered floating geometry: {err}");
+        }
+    }
+}
@@ -1,6 +1,8 @@
+use regex::Regex;
 use smithay::{
-    desktop::space::SpaceElement,
-    utils::{Point, Rectangle},
+    desktop::{layer_map_for_output, space::SpaceElement},
+    reexports::wayland_protocols::xdg::shell::server::xdg_toplevel,
+    utils::{Logical, Point, Rectangle, Size},
 };
 
 use crate::{
@@ -10,139 +12,122 @@ use crate::{
 
 use super::WindowElement;
 
-use std::num::NonZeroU32;
+use std::{
+    num::NonZeroU32,
+    sync::atomic::{AtomicU32, Ordering},
+};
 
-use crate::{output::OutputName, tag::TagId, window::window_state::FullscreenOrMaximized};
+use crate::{
+    output::OutputName,
+    tag::TagId,
+    window::window_state::{FullscreenOrMaximized, WindowLayer},
+};
 
-#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Default)]
-pub struct WindowRuleCondition {
-    /// This condition is met when any of the conditions provided is met.
-    #[serde(default)]
-    pub cond_any: Option<Vec<WindowRuleCondition>>,
-    /// This condition is met when all of the conditions provided are met.
-    #[serde(default)]
-    pub cond_all: Option<Vec<WindowRuleCondition>>,
-    /// This condition is met when the class matches.
-    #[serde(default)]
-    pub class: Option<Vec<String>>,
-    /// This condition is met when the title matches.
-    #[serde(default)]
-    pub title: Option<Vec<String>>,
-    /// This condition is met when the tag matches.
-    #[serde(default)]
-    pub tag: Option<Vec<TagId>>,
+static WINDOW_RULE_ID_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+/// A unique id for an added window rule.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+pub struct WindowRuleId(pub u32);
+
+impl WindowRuleId {
+    /// Get the next available `WindowRuleId`.
+    pub fn next() -> Self {
+        Self(WINDOW_RULE_ID_COUNTER.fetch_add(1, Ordering::Relaxed))
+    }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum AllOrAny {
-    All,
-    Any,
+/// A matcher deciding which windows a window rule applies to.
+///
+/// Matchers are composed with [`WindowMatcher::All`], [`WindowMatcher::Any`],
+/// and [`WindowMatcher::Not`].
+#[derive(Debug, Clone)]
+pub enum WindowMatcher {
+    /// Matches windows whose app id matches the regex.
+    ///
+    /// Xwayland windows are matched on their `WM_CLASS` class.
+    AppId(Regex),
+    /// Matches windows whose title matches the regex.
+    Title(Regex),
+    /// Matches Xwayland windows whose `WM_CLASS` class or instance matches the regex.
+    X11Class(Regex),
+    /// Matches Xwayland windows whose `WM_WINDOW_ROLE` matches the regex.
+    X11Role(Regex),
+    /// Matches windows with the tag.
+    Tag(TagId),
+    /// Matches windows on the output.
+    Output(OutputName),
+    /// Matches floating windows if `true`, tiled ones if `false`.
+    Floating(bool),
+    /// Matches when all matchers match. Matches everything when empty.
+    All(Vec<WindowMatcher>),
+    /// Matches when at least one matcher matches. Matches nothing when empty.
+    Any(Vec<WindowMatcher>),
+    /// Matches when the matcher doesn't.
+    Not(Box<WindowMatcher>),
 }
 
-impl WindowRuleCondition {
-    /// RefCell Safety: This method uses RefCells on `window`.
-    pub fn is_met(&self, pinnacle: &Pinnacle, window: &WindowElement) -> bool {
-        Self::is_met_inner(self, pinnacle, window, AllOrAny::All)
+impl PartialEq for WindowMatcher {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::AppId(a), Self::AppId(b))
+            | (Self::Title(a), Self::Title(b))
+            | (Self::X11Class(a), Self::X11Class(b))
+            | (Self::X11Role(a), Self::X11Role(b)) => a.as_str() == b.as_str(),
+            (Self::Tag(a), Self::Tag(b)) => a == b,
+            (Self::Output(a), Self::Output(b)) => a == b,
+            (Self::Floating(a), Self::Floating(b)) => a == b,
+            (Self::All(a), Self::All(b)) | (Self::Any(a), Self::Any(b)) => a == b,
+            (Self::Not(a), Self::Not(b)) => a == b,
+            _ => false,
+        }
     }
+}
 
-    fn is_met_inner(
-        &self,
-        pinnacle: &Pinnacle,
-        window: &WindowElement,
-        all_or_any: AllOrAny,
-    ) -> bool {
-        tracing::debug!("{self:#?}");
-
-        let WindowRuleCondition {
-            cond_any,
-            cond_all,
-            class,
-            title,
-            tag,
-        } = self;
-
-        match all_or_any {
-            AllOrAny::All => {
-                let cond_any = if let Some(cond_any) = cond_any {
-                    cond_any
-                        .iter()
-                        .any(|cond| Self::is_met_inner(cond, pinnacle, window, AllOrAny::Any))
-                } else {
-                    true
-                };
-                let cond_all = if let Some(cond_all) = cond_all {
-                    cond_all
-                        .iter()
-                        .all(|cond| Self::is_met_inner(cond, pinnacle, window, AllOrAny::All))
-                } else {
-                    true
-                };
-                let classes = if let Some(classes) = class {
-                    classes
-                        .iter()
-                        .all(|class| window.class().as_ref() == Some(class))
-                } else {
-                    true
-                };
-                let titles = if let Some(titles) = title {
-                    titles
-                        .iter()
-                        .all(|title| window.title().as_ref() == Some(title))
-                } else {
-                    true
-                };
-                let tags = if let Some(tag_ids) = tag {
-                    let mut tags = tag_ids.iter().filter_map(|tag_id| tag_id.tag(pinnacle));
-                    tags.all(|tag| window.with_state(|state| state.tags.contains(&tag)))
-                } else {
-                    true
-                };
-
-                tracing::debug!("{cond_all} {cond_any} {classes} {titles} {tags}");
-                cond_all && cond_any && classes && titles && tags
+impl WindowMatcher {
+    /// Create a regex matching exactly `string`.
+    pub fn exact(string: &str) -> Regex {
+        Regex::new(&format!("^{}$", regex::escape(string))).expect("escaped regex was invalid")
+    }
+
+    /// RefCell Safety: This method uses RefCells on `window`.
+    pub fn matches(&self, pinnacle: &Pinnacle, window: &WindowElement) -> bool {
+        match self {
+            WindowMatcher::AppId(regex) => {
+                window.class().is_some_and(|class| regex.is_match(&class))
+            }
+            WindowMatcher::Title(regex) => {
+                window.title().is_some_and(|title| regex.is_match(&title))
             }
-            AllOrAny::Any => {
-                let cond_any = if let Some(cond_any) = cond_any {
-                    cond_any
-                        .iter()
-                        .any(|cond| Self::is_met_inner(cond, pinnacle, window, AllOrAny::Any))
-                } else {
-                    false
-                };
-                let cond_all = if let Some(cond_all) = cond_all {
-                    cond_all
-                        .iter()
-                        .all(|cond| Self::is_met_inner(cond, pinnacle, window, AllOrAny::All))
-                } else {
-                    false
-                };
-                let classes = if let Some(classes) = class {
-                    classes
-                        .iter()
-                        .any(|class| window.class().as_ref() == Some(class))
-                } else {
-                    false
-                };
-                let titles = if let Some(titles) = title {
-                    titles
-                        .iter()
-                        .any(|title| window.title().as_ref() == Some(title))
-                } else {
-                    false
-                };
-                let tags = if let Some(tag_ids) = tag {
-                    let mut tags = tag_ids.iter().filter_map(|tag_id| tag_id.tag(pinnacle));
-                    tags.any(|tag| window.with_state(|state| state.tags.contains(&tag)))
-                } else {
-                    false
-                };
-                cond_all || cond_any || classes || titles || tags
+            WindowMatcher::X11Class(regex) => window.x11_surface().is_some_and(|surface| {
+                regex.is_match(&surface.class()) || regex.is_match(&surface.instance())
+            }),
+            WindowMatcher::X11Role(regex) => window.with_state(|state| {
+                state
+                    .x11_role
+                    .as_ref()
+                    .is_some_and(|role| regex.is_match(role))
+            }),
+            WindowMatcher::Tag(tag_id) => tag_id
+                .tag(pinnacle)
+                .is_some_and(|tag| window.with_state(|state| state.tags.contains(&tag))),
+            WindowMatcher::Output(output_name) => window
+                .output(pinnacle)
+                .is_some_and(|output| &OutputName(output.name()) == output_name),
+            WindowMatcher::Floating(floating) => {
+                window.with_state(|state| state.floating_or_tiled.is_floating()) == *floating
             }
+            WindowMatcher::All(matchers) => matchers
+                .iter()
+                .all(|matcher| matcher.matches(pinnacle, window)),
+            WindowMatcher::Any(matchers) => matchers
+                .iter()
+                .any(|matcher| matcher.matches(pinnacle, window)),
+            WindowMatcher::Not(matcher) => !matcher.matches(pinnacle, window),
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, Default)]
 pub struct WindowRule {
     /// Set the output the window will open on.
     #[serde(default)]
@@ -161,8 +146,37 @@ pub struct WindowRule {
     pub size: Option<(NonZeroU32, NonZeroU32)>,
     /// Set the window's initial location. If the window is tiled, it will snap to this position
     /// when set to floating.
+    ///
+    /// With an `anchor`, this is an offset from the anchored location instead.
     #[serde(default)]
     pub location: Option<(i32, i32)>,
+    /// Place the floating window at this part of its output.
+    #[serde(default)]
+    pub anchor: Option<FloatingAnchor>,
+    /// Enable or disable animations for the window.
+    #[serde(default)]
+    pub animations: Option<bool>,
+    /// Set the window's corner radius, overriding the global one.
+    #[serde(default)]
+    pub corner_radius: Option<f64>,
+    /// Blur the background behind the window.
+    #[serde(default)]
+    pub blur: Option<bool>,
+    /// Set whether the window is dimmed when unfocused.
+    #[serde(default)]
+    pub dim: Option<bool>,
+    /// Set the layer the window is stacked in while floating.
+    #[serde(default)]
+    pub layer: Option<WindowLayer>,
+    /// Set whether the window is a picture-in-picture window, overriding detection.
+    #[serde(default)]
+    pub pip: Option<bool>,
+    /// Set whether fullscreen requests only tell the window it's fullscreen.
+    #[serde(default)]
+    pub fake_fullscreen: Option<bool>,
+    /// Switch to the window once it opens.
+    #[serde(default)]
+    pub follow: Option<bool>,
 }
 
 // TODO: just skip serializing fields on the other FloatingOrTiled
@@ -172,110 +186,352 @@ pub enum FloatingOrTiled {
     Tiled,
 }
 
+/// The part of an output a window rule places a floating window at.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum FloatingAnchor {
+    Center,
+    Top,
+    Bottom,
+    Left,
+    Right,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl FloatingAnchor {
+    /// Get the location of a window of `size` anchored to this part of `area`.
+    pub fn loc(
+        &self,
+        size: Size<i32, Logical>,
+        area: Rectangle<i32, Logical>,
+    ) -> Point<i32, Logical> {
+        let left = area.loc.x;
+        let center_x = area.loc.x + (area.size.w - size.w) / 2;
+        let right = area.loc.x + area.size.w - size.w;
+        let top = area.loc.y;
+        let center_y = area.loc.y + (area.size.h - size.h) / 2;
+        let bottom = area.loc.y + area.size.h - size.h;
+
+        let loc = match self {
+            FloatingAnchor::Center => (center_x, center_y),
+            FloatingAnchor::Top => (center_x, top),
+            FloatingAnchor::Bottom => (center_x, bottom),
+            FloatingAnchor::Left => (left, center_y),
+            FloatingAnchor::Right => (right, center_y),
+            FloatingAnchor::TopLeft => (left, top),
+            FloatingAnchor::TopRight => (right, top),
+            FloatingAnchor::BottomLeft => (left, bottom),
+            FloatingAnchor::BottomRight => (right, bottom),
+        };
+
+        loc.into()
+    }
+}
+
 impl Pinnacle {
+    /// Give a new `window` the floating state and size its rules ask for in its
+    /// initial configure, so it doesn't have to resize once it's mapped.
+    ///
+    /// Rules that match on tags or outputs don't apply here, as the window has neither yet.
+    pub fn configure_from_window_rules(&self, window: &WindowElement) {
+        let Some(toplevel) = window.toplevel() else {
+            return;
+        };
+
+        let mut floating = false;
+        let mut size = None;
+
+        for (_, rule) in self.matching_window_rules(window) {
+            if let Some(floating_or_tiled) = rule.floating_or_tiled {
+                floating = floating_or_tiled == FloatingOrTiled::Floating;
+            }
+            if let Some((w, h)) = rule.size {
+                size = Some(Size::<i32, Logical>::from((
+                    u32::from(w) as i32,
+                    u32::from(h) as i32,
+                )));
+            }
+        }
+
+        if !floating {
+            return;
+        }
+
+        toplevel.with_pending_state(|state| {
+            state.states.unset(xdg_toplevel::State::TiledTop);
+            state.states.unset(xdg_toplevel::State::TiledBottom);
+            state.states.unset(xdg_toplevel::State::TiledLeft);
+            state.states.unset(xdg_toplevel::State::TiledRight);
+            if let Some(size) = size {
+                state.size = Some(size);
+            }
+        });
+    }
+
     pub fn apply_window_rules(&mut self, window: &WindowElement) {
         tracing::debug!("Applying window rules");
-        for (cond, rule) in self.config.window_rules.iter() {
-            if cond.is_met(self, window) {
-                let WindowRule {
-                    output,
-                    tags,
-                    floating_or_tiled,
-                    fullscreen_or_maximized,
-                    size,
-                    location,
-                } = rule;
-
-                // TODO: If both `output` and `tags` are specified, `tags` will apply over
-                // |     `output`.
-
-                if let Some(output_name) = output {
-                    if let Some(output) = output_name.output(self) {
-                        let tags = output
-                            .with_state(|state| state.focused_tags().cloned().collect::<Vec<_>>());
-
-                        window.with_state_mut(|state| state.tags = tags.clone());
+
+        let looks_like_pip = window.looks_like_pip();
+        window.with_state_mut(|state| state.pip = looks_like_pip);
+
+        self.restore_floating_geometry(window);
+        self.restore_window_session(window);
+
+        let matched = self.matching_window_rules(window);
+        window.with_state_mut(|state| {
+            state.matched_rules = matched.iter().map(|(id, _)| *id).collect();
+        });
+
+        for (_, rule) in matched.iter() {
+            self.apply_window_rule(window, rule);
+        }
+
+        if let Some(rule) = self.take_spawn_placement(window) {
+            self.apply_window_rule(window, &rule);
+        }
+
+        if window.with_state(|state| state.pip) {
+            self.make_pip(window);
+        }
+
+        self.session_changed();
+    }
+
+    /// Apply the rules that started matching `window` since they were last checked.
+    ///
+    /// This is called when the window's title or app id changes, or once its X11 role is read.
+    pub fn reapply_window_rules(&mut self, window: &WindowElement) {
+        let matched = self.matching_window_rules(window);
+        let newly_matched = window.with_state_mut(|state| {
+            let newly_matched = matched
+                .iter()
+                .filter(|(id, _)| !state.matched_rules.contains(id))
+                .cloned()
+                .collect::<Vec<_>>();
+            state.matched_rules = matched.iter().map(|(id, _)| *id).collect();
+            newly_matched
+        });
+
+        if newly_matched.is_empty() {
+            return;
+        }
+
+        tracing::debug!("Applying window rules after a title, app id, or role change");
+
+        let old_output = window.output(self);
+        let was_pip = window.with_state(|state| state.pip);
+
+        for (_, rule) in newly_matched.iter() {
+            self.apply_window_rule(window, rule);
+        }
+
+        if !was_pip && window.with_state(|state| state.pip) {
+            self.make_pip(window);
+        }
+
+        self.session_changed();
+
+        let new_output = window.output(self);
+        if let Some(output) = old_output.as_ref() {
+            self.request_layout(output);
+        }
+        if let Some(output) = new_output.filter(|output| Some(output) != old_output.as_ref()) {
+            self.request_layout(&output);
+        }
+    }
+
+    /// Get the rules whose matchers match `window`, in the order they were added.
+    fn matching_window_rules(&self, window: &WindowElement) -> Vec<(WindowRuleId, WindowRule)> {
+        self.config
+            .window_rules
+            .iter()
+            .filter(|(_, matcher, _)| matcher.matches(self, window))
+            .map(|(id, _, rule)| (*id, rule.clone()))
+            .collect()
+    }
+
+    fn apply_window_rule(&mut self, window: &WindowElement, rule: &WindowRule) {
+        let WindowRule {
+            output,
+            tags,
+            floating_or_tiled,
+            fullscreen_or_maximized,
+            size,
+            location,
+            anchor,
+            animations,
+            corner_radius,
+            blur,
+            dim,
+            layer,
+            pip,
+            fake_fullscreen,
+            follow,
+        } = rule;
+
+        // TODO: If both `output` and `tags` are specified, `tags` will apply over
+        // |     `output`.
+
+        if let Some(output_name) = output {
+            if let Some(output) = output_name.output(self) {
+                let tags =
+                    output.with_state(|state| state.focused_tags().cloned().collect::<Vec<_>>());
+
+                window.with_state_mut(|state| state.tags = tags.clone());
+            }
+        }
+
+        if let Some(tag_ids) = tags {
+            let tags = tag_ids
+                .iter()
+                .filter_map(|tag_id| tag_id.tag(self))
+                .collect::<Vec<_>>();
+
+            window.with_state_mut(|state| state.tags = tags.clone());
+        }
+
+        if let Some(floating_or_tiled) = floating_or_tiled {
+            match floating_or_tiled {
+                FloatingOrTiled::Floating => {
+                    if window.with_state(|state| state.floating_or_tiled.is_tiled()) {
+                        window.toggle_floating();
+                    }
+                }
+                FloatingOrTiled::Tiled => {
+                    if window.with_state(|state| state.floating_or_tiled.is_floating()) {
+                        window.toggle_floating();
                     }
                 }
+            }
+        }
 
-                if let Some(tag_ids) = tags {
-                    let tags = tag_ids
-                        .iter()
-                        .filter_map(|tag_id| tag_id.tag(self))
-                        .collect::<Vec<_>>();
+        if let Some(fs_or_max) = fullscreen_or_maximized {
+            window.with_state_mut(|state| state.fullscreen_or_maximized = *fs_or_max);
+        }
 
-                    window.with_state_mut(|state| state.tags = tags.clone());
-                }
+        if let Some((w, h)) = size {
+            let mut window_size = window.geometry().size;
+            window_size.w = u32::from(*w) as i32;
+            window_size.h = u32::from(*h) as i32;
 
-                if let Some(floating_or_tiled) = floating_or_tiled {
-                    match floating_or_tiled {
-                        FloatingOrTiled::Floating => {
-                            if window.with_state(|state| state.floating_or_tiled.is_tiled()) {
-                                window.toggle_floating();
-                            }
-                        }
-                        FloatingOrTiled::Tiled => {
-                            if window.with_state(|state| state.floating_or_tiled.is_floating()) {
-                                window.toggle_floating();
-                            }
-                        }
+            match window.with_state(|state| state.floating_or_tiled) {
+                window_state::FloatingOrTiled::Floating(mut rect) => {
+                    rect.size = (u32::from(*w) as i32, u32::from(*h) as i32).into();
+                    window.with_state_mut(|state| {
+                        state.floating_or_tiled = window_state::FloatingOrTiled::Floating(rect)
+                    });
+                }
+                window_state::FloatingOrTiled::Tiled(mut rect) => {
+                    if let Some(rect) = rect.as_mut() {
+                        rect.size = (u32::from(*w) as i32, u32::from(*h) as i32).into();
                     }
+                    window.with_state_mut(|state| {
+                        state.floating_or_tiled = window_state::FloatingOrTiled::Tiled(rect)
+                    });
                 }
+            }
+        }
 
-                if let Some(fs_or_max) = fullscreen_or_maximized {
-                    window.with_state_mut(|state| state.fullscreen_or_maximized = *fs_or_max);
+        if let Some(loc) = location {
+            match window.with_state(|state| state.floating_or_tiled) {
+                window_state::FloatingOrTiled::Floating(mut rect) => {
+                    rect.loc = (*loc).into();
+                    window.with_state_mut(|state| {
+                        state.floating_or_tiled = window_state::FloatingOrTiled::Floating(rect)
+                    });
+                    self.space.map_element(window.clone(), *loc, false);
                 }
+                window_state::FloatingOrTiled::Tiled(rect) => {
+                    // If the window is tiled, don't set the size. Instead, set
+                    // what the size will be when it gets set to floating.
+                    let rect = rect.unwrap_or_else(|| {
+                        let size = window.geometry().size;
+                        Rectangle::from_loc_and_size(Point::from(*loc), size)
+                    });
 
-                if let Some((w, h)) = size {
-                    let mut window_size = window.geometry().size;
-                    window_size.w = u32::from(*w) as i32;
-                    window_size.h = u32::from(*h) as i32;
-
-                    match window.with_state(|state| state.floating_or_tiled) {
-                        window_state::FloatingOrTiled::Floating(mut rect) => {
-                            rect.size = (u32::from(*w) as i32, u32::from(*h) as i32).into();
-                            window.with_state_mut(|state| {
-                                state.floating_or_tiled =
-                                    window_state::FloatingOrTiled::Floating(rect)
-                            });
-                        }
-                        window_state::FloatingOrTiled::Tiled(mut rect) => {
-                            if let Some(rect) = rect.as_mut() {
-                                rect.size = (u32::from(*w) as i32, u32::from(*h) as i32).into();
-                            }
-                            window.with_state_mut(|state| {
-                                state.floating_or_tiled = window_state::FloatingOrTiled::Tiled(rect)
-                            });
-                        }
-                    }
+                    window.with_state_mut(|state| {
+                        state.floating_or_tiled = window_state::FloatingOrTiled::Tiled(Some(rect))
+                    });
                 }
+            }
+        }
 
-                if let Some(loc) = location {
-                    match window.with_state(|state| state.floating_or_tiled) {
-                        window_state::FloatingOrTiled::Floating(mut rect) => {
-                            rect.loc = (*loc).into();
-                            window.with_state_mut(|state| {
-                                state.floating_or_tiled =
-                                    window_state::FloatingOrTiled::Floating(rect)
-                            });
-                            self.space.map_element(window.clone(), *loc, false);
-                        }
-                        window_state::FloatingOrTiled::Tiled(rect) => {
-                            // If the window is tiled, don't set the size. Instead, set
-                            // what the size will be when it gets set to floating.
-                            let rect = rect.unwrap_or_else(|| {
-                                let size = window.geometry().size;
-                                Rectangle::from_loc_and_size(Point::from(*loc), size)
-                            });
-
-                            window.with_state_mut(|state| {
-                                state.floating_or_tiled =
-                                    window_state::FloatingOrTiled::Tiled(Some(rect))
-                            });
-                        }
-                    }
+        if let Some(anchor) = anchor {
+            if let window_state::FloatingOrTiled::Floating(mut rect) =
+                window.with_state(|state| state.floating_or_tiled)
+            {
+                if let Some(area) = window.output(self).and_then(|output| {
+                    let output_geo = self.space.output_geometry(&output)?;
+                    let mut area = layer_map_for_output(&output).non_exclusive_zone();
+                    area.loc += output_geo.loc;
+                    Some(area)
+                }) {
+                    let offset = Point::from(location.unwrap_or_default());
+                    rect.loc = anchor.loc(rect.size, area) + offset;
+                    window.with_state_mut(|state| {
+                        state.floating_or_tiled = window_state::FloatingOrTiled::Floating(rect)
+                    });
                 }
             }
         }
+
+        if let Some(animations) = animations {
+            window.with_state_mut(|state| state.animations.disabled = !animations);
+        }
+
+        if let Some(corner_radius) = corner_radius {
+            window.with_state_mut(|state| state.corner_radius = Some(*corner_radius));
+        }
+
+        if let Some(blur) = blur {
+            window.with_state_mut(|state| state.blur = *blur);
+        }
+
+        if let Some(dim) = dim {
+            window.with_state_mut(|state| state.dim = *dim);
+        }
+
+        if let Some(layer) = layer {
+            window.with_state_mut(|state| state.layer = *layer);
+        }
+
+        if let Some(pip) = pip {
+            window.with_state_mut(|state| state.pip = *pip);
+        }
+
+        if let Some(fake_fullscreen) = fake_fullscreen {
+            window.set_fake_fullscreen(*fake_fullscreen);
+        }
+
+        if let Some(follow) = follow {
+            window.with_state_mut(|state| state.follow_on_map = *follow);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn anchors_windows_in_area() {
+        let area = Rectangle::from_loc_and_size((1920, 30), (1920, 1050));
+        let size = Size::from((400, 300));
+
+        assert_eq!(
+            FloatingAnchor::Center.loc(size, area),
+            Point::from((2680, 405))
+        );
+        assert_eq!(FloatingAnchor::Top.loc(size, area), Point::from((2680, 30)));
+        assert_eq!(
+            FloatingAnchor::BottomRight.loc(size, area),
+            Point::from((3440, 780))
+        );
+        assert_eq!(
+            FloatingAnchor::Left.loc(size, area),
+            Point::from((1920, 405))
+        );
     }
 }
@@ -0,0 +1,198 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Unresponsive windows.
+//!
+//! Clients with xdg-shell windows are pinged every [`PING_INTERVAL`]. The windows of a client
+//! that hasn't answered a ping within [`PING_TIMEOUT`] are unresponsive until it does, and are
+//! darkened while the "not responding" overlay is enabled.
+//!
+//! Windows can also be closed with a kill timeout, in which case their process is killed
+//! if they're still open once the timeout passes. This works for Xwayland windows too.
+
+use std::time::{Duration, Instant};
+
+use nix::{
+    sys::signal::{self, Signal},
+    unistd::Pid,
+};
+use smithay::{
+    reexports::calloop::timer::{TimeoutAction, Timer},
+    utils::SERIAL_COUNTER,
+    wayland::shell::xdg::ShellClient,
+};
+use tracing::{error, warn};
+
+use crate::state::{Pinnacle, State, WithState};
+
+use super::WindowElement;
+
+/// How often clients are pinged.
+pub const PING_INTERVAL: Duration = Duration::from_secs(2);
+/// How long a client has to answer a ping before its windows are unresponsive.
+const PING_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long a window has to close before its process is killed, if no timeout is given.
+pub const DEFAULT_KILL_TIMEOUT: Duration = Duration::from_secs(5);
+/// The brightness of unresponsive windows while the overlay is enabled.
+pub const UNRESPONSIVE_BRIGHTNESS: f32 = 0.4;
+
+/// A ping a client hasn't answered yet.
+#[derive(Debug)]
+pub struct PendingPing {
+    pub client: ShellClient,
+    pub sent_at: Instant,
+}
+
+impl Pinnacle {
+    /// Set whether the windows of `client` are unresponsive, returning the ones that changed.
+    fn set_client_unresponsive(
+        &mut self,
+        client: &ShellClient,
+        unresponsive: bool,
+    ) -> Vec<WindowElement> {
+        self.windows
+            .iter()
+            .filter(|win| {
+                win.toplevel()
+                    .is_some_and(|toplevel| &toplevel.client() == client)
+            })
+            .filter(|win| {
+                win.with_state_mut(|state| {
+                    let changed = state.unresponsive != unresponsive;
+                    state.unresponsive = unresponsive;
+                    changed
+                })
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Kill the process that owns `window` with `SIGKILL`.
+    pub fn kill_window(&self, window: &WindowElement) {
+        let Some(pid) = self.window_pid(window) else {
+            warn!("Couldn't get the process id of a window to kill");
+            return;
+        };
+
+        if pid == std::process::id() {
+            return;
+        }
+
+        let Ok(raw_pid) = i32::try_from(pid) else {
+            return;
+        };
+
+        if let Err(err) = signal::kill(Pid::from_raw(raw_pid), Signal::SIGKILL) {
+            warn!("Failed to kill process {pid}: {err}");
+        }
+    }
+}
+
+impl State {
+    /// Ping every client with a window, marking the windows of clients that haven't
+    /// answered their last ping in time as unresponsive.
+    pub fn ping_clients(&mut self) {
+        let mut clients = Vec::<ShellClient>::new();
+        for toplevel in self
+            .pinnacle
+            .windows
+            .iter()
+            .filter_map(|win| win.toplevel())
+        {
+            let client = toplevel.client();
+            if !clients.contains(&client) {
+                clients.push(client);
+            }
+        }
+
+        self.pinnacle
+            .pending_pings
+            .retain(|ping| clients.contains(&ping.client));
+
+        let mut changed = Vec::new();
+
+        for client in clients {
+            let pending = self
+                .pinnacle
+                .pending_pings
+                .iter()
+                .find(|ping| ping.client == client);
+
+            match pending {
+                Some(ping) => {
+                    if ping.sent_at.elapsed() >= PING_TIMEOUT {
+                        changed.extend(self.pinnacle.set_client_unresponsive(&client, true));
+                    }
+                }
+                None => {
+                    if let Err(err) = client.send_ping(SERIAL_COUNTER.next_serial()) {
+                        warn!("Failed to ping client: {err:?}");
+                        continue;
+                    }
+                    self.pinnacle.pending_pings.push(PendingPing {
+                        client,
+                        sent_at: Instant::now(),
+                    });
+                }
+            }
+        }
+
+        self.render_unresponsive_windows(changed);
+    }
+
+    /// Handle `client` answering a ping, making its windows responsive again.
+    pub fn handle_pong(&mut self, client: &ShellClient) {
+        self.pinnacle
+            .pending_pings
+            .retain(|ping| &ping.client != client);
+
+        let changed = self.pinnacle.set_client_unresponsive(client, false);
+        self.render_unresponsive_windows(changed);
+    }
+
+    fn render_unresponsive_windows(&mut self, windows: Vec<WindowElement>) {
+        if !self.pinnacle.config.render_settings.darken_unresponsive {
+            return;
+        }
+
+        for window in windows {
+            for output in self.pinnacle.space.outputs_for_element(&window) {
+                self.schedule_render(&output);
+            }
+        }
+    }
+
+    /// Ask `window` to close, killing its process if it's still open after `timeout`.
+    pub fn close_or_kill_window(&mut self, window: &WindowElement, timeout: Duration) {
+        self.pinnacle.close_window(window);
+
+        let window = window.clone();
+        let res = self.pinnacle.loop_handle.insert_source(
+            Timer::from_duration(timeout),
+            move |_, _, state| {
+                if state.pinnacle.windows.contains(&window) {
+                    state.pinnacle.kill_window(&window);
+                }
+                TimeoutAction::Drop
+            },
+        );
+
+        if let Err(err) = res {
+            error!("Failed to insert kill timer: {err}");
+        }
+    }
+
+    /// Start pinging clients every [`PING_INTERVAL`].
+    pub fn start_pinging_clients(&mut self) {
+        let res = self.pinnacle.loop_handle.insert_source(
+            Timer::from_duration(PING_INTERVAL),
+            |_, _, state| {
+                state.ping_clients();
+                TimeoutAction::ToDuration(PING_INTERVAL)
+            },
+        );
+
+        if let Err(err) = res {
+            error!("Failed to insert ping timer: {err}");
+        }
+    }
+}
@@ -0,0 +1,98 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Urgent windows.
+//!
+//! A window becomes urgent when an X11 client sets the urgency hint on it, or when a Wayland
+//! client asks to activate it through xdg-activation while it isn't focused. Tags with an
+//! urgent window are urgent too. Windows stop being urgent once they're focused or one of
+//! their tags is activated.
+
+use pinnacle_api_defs::pinnacle::signal::v0alpha1::{TagUrgentResponse, WindowUrgentResponse};
+
+use crate::{
+    focus::keyboard::KeyboardFocusTarget,
+    state::{Pinnacle, State, WithState},
+    tag::Tag,
+};
+
+use super::WindowElement;
+
+impl Pinnacle {
+    /// Returns whether any window with `tag` is urgent.
+    pub fn tag_is_urgent(&self, tag: &Tag) -> bool {
+        self.windows
+            .iter()
+            .any(|win| win.with_state(|state| state.urgent && state.tags.contains(tag)))
+    }
+
+    /// Set whether `window` is urgent.
+    ///
+    /// This signals the change for the window and for each of its tags whose urgency changed.
+    pub fn set_window_urgent(&mut self, window: &WindowElement, urgent: bool) {
+        if window.with_state(|state| state.urgent) == urgent {
+            return;
+        }
+
+        let tags = window.with_state(|state| state.tags.clone());
+        let tags_were_urgent = tags
+            .iter()
+            .map(|tag| self.tag_is_urgent(tag))
+            .collect::<Vec<_>>();
+
+        window.with_state_mut(|state| state.urgent = urgent);
+
+        let window_id = Some(window.with_state(|state| state.id.0));
+        self.signal_state.window_urgent.signal(|buf| {
+            buf.push_back(WindowUrgentResponse {
+                window_id,
+                urgent: Some(urgent),
+            })
+        });
+
+        for (tag, was_urgent) in tags.iter().zip(tags_were_urgent) {
+            let is_urgent = self.tag_is_urgent(tag);
+            if is_urgent == was_urgent {
+                continue;
+            }
+
+            self.signal_state.tag_urgent.signal(|buf| {
+                buf.push_back(TagUrgentResponse {
+                    tag_id: Some(tag.id().0),
+                    urgent: Some(is_urgent),
+                })
+            });
+        }
+    }
+
+    /// Mark `window` as urgent unless it has keyboard focus.
+    pub fn mark_urgent_unless_focused(&mut self, window: &WindowElement) {
+        let focused = self
+            .seat
+            .get_keyboard()
+            .and_then(|keyboard| keyboard.current_focus())
+            .is_some_and(|focus| focus == KeyboardFocusTarget::Window(window.clone()));
+
+        if !focused {
+            self.set_window_urgent(window, true);
+        }
+    }
+}
+
+impl State {
+    /// Focus and raise the first urgent window.
+    ///
+    /// If it isn't on an active tag, its output switches to its first tag.
+    pub fn focus_urgent_window(&mut self) {
+        let Some(window) = self
+            .pinnacle
+            .windows
+            .iter()
+            .find(|win| win.with_state(|state| state.urgent))
+            .cloned()
+        else {
+            return;
+        };
+
+        self.switch_to_window(&window);
+    }
+}
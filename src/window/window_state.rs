@@ -5,15 +5,16 @@ use std::sync::atomic::{AtomicU32, Ordering};
 use smithay::{
     desktop::{space::SpaceElement, WindowSurface},
     reexports::wayland_protocols::xdg::shell::server::xdg_toplevel,
-    utils::{Logical, Point, Rectangle},
+    utils::{Logical, Point, Rectangle, Size},
 };
 
 use crate::{
+    animation::WindowAnimations,
     state::{Pinnacle, WithState},
     tag::Tag,
 };
 
-use super::WindowElement;
+use super::{edge_tile::EdgeTile, group::WindowGroup, rules::WindowRuleId, WindowElement};
 
 /// A unique identifier for each window.
 #[derive(Debug, Hash, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
@@ -47,6 +48,44 @@ pub struct WindowElementState {
     pub floating_or_tiled: FloatingOrTiled,
     pub fullscreen_or_maximized: FullscreenOrMaximized,
     pub target_loc: Option<Point<i32, Logical>>,
+    pub animations: WindowAnimations,
+    /// The corner radius set by a window rule, overriding the global one.
+    pub corner_radius: Option<f64>,
+    /// Whether the background behind the window is blurred.
+    pub blur: bool,
+    /// Whether the window is dimmed when it isn't focused.
+    pub dim: bool,
+    /// The group this window shares its tile with.
+    pub group: Option<WindowGroup>,
+    /// Whether this tiled window is being dragged to swap it with another tile.
+    ///
+    /// Layouts don't move it while it is.
+    pub swap_ghost: bool,
+    /// The layer this window is stacked in while it's floating.
+    pub layer: WindowLayer,
+    /// Whether this is a picture-in-picture window pinned to a corner of its output.
+    pub pip: bool,
+    /// The part of its output this floating window was dragged to,
+    /// and its size from before it was tiled there.
+    pub edge_tile: Option<(EdgeTile, Size<i32, Logical>)>,
+    /// Whether fullscreen requests from the client only tell it that it's fullscreen
+    /// without changing its geometry.
+    pub fake_fullscreen: bool,
+    /// Whether the client was told it's fullscreen because of a fake fullscreen.
+    pub client_fullscreen: bool,
+    /// Whether this window wants attention.
+    pub urgent: bool,
+    /// The window rules that matched this window when they were last checked.
+    pub matched_rules: Vec<WindowRuleId>,
+    /// The `WM_WINDOW_ROLE` of this Xwayland window, read when it was mapped.
+    pub x11_role: Option<String>,
+    /// Whether to switch to this window once it's mapped.
+    pub follow_on_map: bool,
+    /// Whether this window's client stopped answering pings.
+    pub unresponsive: bool,
+    /// The keyboard layout that was active while this window was last focused,
+    /// if per-window layouts are enabled.
+    pub xkb_layout: Option<u32>,
 }
 
 impl WindowElement {
@@ -167,14 +206,59 @@ impl WindowElement {
         }
     }
 
+    /// Set whether fullscreen requests from this window's client only tell it
+    /// that it's fullscreen instead of actually making the window fullscreen.
+    ///
+    /// If the client was told it's fullscreen, turning this off tells it it isn't anymore.
+    ///
+    /// RefCell Safety: This method uses a [`RefCell`] on this window.
+    pub fn set_fake_fullscreen(&self, fake_fullscreen: bool) {
+        let was_client_fullscreen = self.with_state_mut(|state| {
+            state.fake_fullscreen = fake_fullscreen;
+            let was_client_fullscreen = state.client_fullscreen;
+            if !fake_fullscreen {
+                state.client_fullscreen = false;
+            }
+            was_client_fullscreen
+        });
+
+        if fake_fullscreen || !was_client_fullscreen {
+            return;
+        }
+
+        match self.with_state(|state| (state.floating_or_tiled, state.fullscreen_or_maximized)) {
+            (FloatingOrTiled::Floating(_), FullscreenOrMaximized::Neither) => {
+                self.set_floating_states()
+            }
+            (FloatingOrTiled::Tiled(_), FullscreenOrMaximized::Neither) => self.set_tiled_states(),
+            _ => (),
+        }
+    }
+
+    /// Returns whether the client should be told this window is fullscreen
+    /// while it isn't actually fullscreen.
+    ///
+    /// RefCell Safety: This method uses a [`RefCell`] on this window.
+    fn is_fake_fullscreened(&self) -> bool {
+        self.with_state(|state| state.fake_fullscreen && state.client_fullscreen)
+    }
+
     /// Unsets maximized and fullscreen states for both wayland and xwayland windows
     /// and unsets tiled states for wayland windows.
+    ///
+    /// The fullscreen state is kept if the window is fake fullscreened.
     fn set_floating_states(&self) {
+        let fake_fullscreened = self.is_fake_fullscreened();
+
         match self.underlying_surface() {
             WindowSurface::Wayland(toplevel) => {
                 toplevel.with_pending_state(|state| {
                     state.states.unset(xdg_toplevel::State::Maximized);
-                    state.states.unset(xdg_toplevel::State::Fullscreen);
+                    if fake_fullscreened {
+                        state.states.set(xdg_toplevel::State::Fullscreen);
+                    } else {
+                        state.states.unset(xdg_toplevel::State::Fullscreen);
+                    }
                     state.states.unset(xdg_toplevel::State::TiledTop);
                     state.states.unset(xdg_toplevel::State::TiledLeft);
                     state.states.unset(xdg_toplevel::State::TiledBottom);
@@ -187,7 +271,7 @@ impl WindowElement {
                         .set_maximized(false)
                         .expect("failed to set x11 win to maximized");
                     surface
-                        .set_fullscreen(false)
+                        .set_fullscreen(fake_fullscreened)
                         .expect("failed to set x11 win to not fullscreen");
                 }
             }
@@ -196,12 +280,20 @@ impl WindowElement {
 
     /// Unsets maximized and fullscreen states for both wayland and xwayland windows
     /// and sets tiled states for wayland windows.
+    ///
+    /// The fullscreen state is kept if the window is fake fullscreened.
     fn set_tiled_states(&self) {
+        let fake_fullscreened = self.is_fake_fullscreened();
+
         match self.underlying_surface() {
             WindowSurface::Wayland(toplevel) => {
                 toplevel.with_pending_state(|state| {
                     state.states.unset(xdg_toplevel::State::Maximized);
-                    state.states.unset(xdg_toplevel::State::Fullscreen);
+                    if fake_fullscreened {
+                        state.states.set(xdg_toplevel::State::Fullscreen);
+                    } else {
+                        state.states.unset(xdg_toplevel::State::Fullscreen);
+                    }
                     state.states.set(xdg_toplevel::State::TiledTop);
                     state.states.set(xdg_toplevel::State::TiledLeft);
                     state.states.set(xdg_toplevel::State::TiledBottom);
@@ -214,7 +306,7 @@ impl WindowElement {
                         .set_maximized(false)
                         .expect("failed to set x11 win to maximized");
                     surface
-                        .set_fullscreen(false)
+                        .set_fullscreen(fake_fullscreened)
                         .expect("failed to set x11 win to not fullscreen");
                 }
             }
@@ -252,6 +344,31 @@ impl FloatingOrTiled {
     }
 }
 
+/// Where a floating window is stacked relative to other windows.
+///
+/// Layers are ordered from bottom to top.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub enum WindowLayer {
+    /// Always below windows in the normal layer.
+    Below,
+    /// Stacked with other windows.
+    #[default]
+    Normal,
+    /// Always above windows in the normal layer.
+    Above,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum FullscreenOrMaximized {
     Neither,
@@ -286,6 +403,16 @@ impl FullscreenOrMaximized {
 }
 
 impl WindowElementState {
+    /// Get the layer this window is actually stacked in.
+    ///
+    /// Only floating windows leave the normal layer.
+    pub fn stacking_layer(&self) -> WindowLayer {
+        match self.floating_or_tiled {
+            FloatingOrTiled::Floating(_) if self.fullscreen_or_maximized.is_neither() => self.layer,
+            _ => WindowLayer::Normal,
+        }
+    }
+
     #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
         Self {
@@ -295,6 +422,23 @@ impl WindowElementState {
             floating_or_tiled: FloatingOrTiled::Tiled(None),
             fullscreen_or_maximized: FullscreenOrMaximized::Neither,
             target_loc: None,
+            animations: WindowAnimations::default(),
+            corner_radius: None,
+            blur: false,
+            dim: true,
+            group: None,
+            swap_ghost: false,
+            layer: WindowLayer::Normal,
+            pip: false,
+            edge_tile: None,
+            fake_fullscreen: false,
+            client_fullscreen: false,
+            urgent: false,
+            matched_rules: Vec::new(),
+            x11_role: None,
+            follow_on_map: false,
+            unresponsive: false,
+            xkb_layout: None,
         }
     }
 }
@@ -57,6 +57,13 @@ where
         state.pinnacle.fixup_z_layering();
         state.pinnacle.space.refresh();
         state.pinnacle.popup_manager.cleanup();
+        state.pinnacle.update_tag_history();
+        state.pinnacle.update_tag_occupancy();
+        state.pinnacle.update_dynamic_tags();
+        state.update_xkb_layout();
+        state.update_pointer_warp();
+        state.pinnacle.update_ext_workspaces();
+        state.pinnacle.update_ext_foreign_toplevels();
 
         state
             .pinnacle
@@ -102,7 +102,7 @@ macro_rules! setup_lua {
 use pinnacle::{
     tag::TagId,
     window::{
-        rules::{WindowRule, WindowRuleCondition},
+        rules::{WindowMatcher, WindowRule},
         window_state::FullscreenOrMaximized,
     },
 };
@@ -202,6 +202,37 @@ mod window {
         })
     }
 
+    #[tokio::main]
+    #[self::test]
+    async fn set_focus_follows_mouse() -> anyhow::Result<()> {
+        test_api(|sender| {
+            run_lua! { |Pinnacle|
+                Pinnacle.window.set_focus_follows_mouse(true, 150)
+            }
+
+            sleep_secs(1);
+
+            with_state(&sender, |state| {
+                assert_eq!(
+                    state.pinnacle.config.focus.focus_follows_mouse,
+                    Some(std::time::Duration::from_millis(150))
+                );
+            });
+
+            run_lua! { |Pinnacle|
+                Pinnacle.window.set_focus_follows_mouse(false)
+            }
+
+            sleep_secs(1);
+
+            with_state(&sender, |state| {
+                assert_eq!(state.pinnacle.config.focus.focus_follows_mouse, None);
+            });
+
+            Ok(())
+        })
+    }
+
     #[tokio::main]
     #[self::test]
     async fn add_window_rule() -> anyhow::Result<()> {
@@ -218,18 +249,19 @@ mod window {
 
             with_state(&sender, |state| {
                 assert_eq!(state.pinnacle.config.window_rules.len(), 1);
+                let (_, matcher, rule) = &state.pinnacle.config.window_rules[0];
                 assert_eq!(
-                    state.pinnacle.config.window_rules[0],
-                    (
-                        WindowRuleCondition {
-                            class: Some(vec!["firefox".to_string()]),
-                            ..Default::default()
-                        },
-                        WindowRule {
-                            tags: Some(vec![TagId(0)]),
-                            ..Default::default()
-                        }
-                    )
+                    matcher,
+                    &WindowMatcher::All(vec![WindowMatcher::AppId(WindowMatcher::exact(
+                        "firefox"
+                    ))])
+                );
+                assert_eq!(
+                    rule,
+                    &WindowRule {
+                        tags: Some(vec![TagId(0)]),
+                        ..Default::default()
+                    }
                 );
             });
 
@@ -255,22 +287,21 @@ mod window {
 
             with_state(&sender, |state| {
                 assert_eq!(state.pinnacle.config.window_rules.len(), 2);
+                let (_, matcher, rule) = &state.pinnacle.config.window_rules[1];
                 assert_eq!(
-                    state.pinnacle.config.window_rules[1],
-                    (
-                        WindowRuleCondition {
-                            cond_all: Some(vec![WindowRuleCondition {
-                                class: Some(vec!["steam".to_string()]),
-                                tag: Some(vec![TagId(0), TagId(1)]),
-                                ..Default::default()
-                            }]),
-                            ..Default::default()
-                        },
-                        WindowRule {
-                            fullscreen_or_maximized: Some(FullscreenOrMaximized::Fullscreen),
-                            ..Default::default()
-                        }
-                    )
+                    matcher,
+                    &WindowMatcher::All(vec![WindowMatcher::All(vec![
+                        WindowMatcher::AppId(WindowMatcher::exact("steam")),
+                        WindowMatcher::Tag(TagId(0)),
+                        WindowMatcher::Tag(TagId(1)),
+                    ])])
+                );
+                assert_eq!(
+                    rule,
+                    &WindowRule {
+                        fullscreen_or_maximized: Some(FullscreenOrMaximized::Fullscreen),
+                        ..Default::default()
+                    }
                 );
             });
 
@@ -377,6 +408,39 @@ mod window {
                 Ok(())
             })
         }
+
+        #[tokio::main]
+        #[self::test]
+        async fn set_tags() -> anyhow::Result<()> {
+            test_api(|sender| {
+                run_lua! { |Pinnacle|
+                    local tags = Pinnacle.tag.add(Pinnacle.output.get_focused(), "1", "2", "3")
+                    tags[1]:set_active(true)
+                    Pinnacle.process.spawn("foot")
+                }
+
+                sleep_secs(1);
+
+                run_lua! { |Pinnacle|
+                    Pinnacle.window.get_all()[1]:set_tags({ Pinnacle.tag.get("2"), Pinnacle.tag.get("3") })
+                }
+
+                sleep_secs(1);
+
+                with_state(&sender, |state| {
+                    assert_eq!(
+                        state.pinnacle.windows[0].with_state(|st| st
+                            .tags
+                            .iter()
+                            .map(|tag| tag.name())
+                            .collect::<Vec<_>>()),
+                        vec!["2", "3"]
+                    );
+                });
+
+                Ok(())
+            })
+        }
     }
 }
 
@@ -434,6 +498,238 @@ mod tag {
                 Ok(())
             })
         }
+
+        #[tokio::main]
+        #[self::test]
+        async fn rename() -> anyhow::Result<()> {
+            test_api(|sender| {
+                run_lua! { |Pinnacle|
+                    Pinnacle.output.connect_for_all(function(op)
+                        Pinnacle.tag.add(op, "First")
+                    end)
+                }
+
+                sleep_secs(1);
+
+                run_lua! { |Pinnacle|
+                    Pinnacle.tag.get("First"):rename("Renamed")
+                }
+
+                sleep_secs(1);
+
+                with_state(&sender, |state| {
+                    let output = state.pinnacle.focused_output().unwrap();
+                    let names = output.with_state(|state| {
+                        state.tags.iter().map(|tag| tag.name()).collect::<Vec<_>>()
+                    });
+                    assert_eq!(names, vec!["Renamed".to_string()]);
+                });
+
+                Ok(())
+            })
+        }
+
+        #[tokio::main]
+        #[self::test]
+        async fn switch_to_previous() -> anyhow::Result<()> {
+            test_api(|sender| {
+                run_lua! { |Pinnacle|
+                    Pinnacle.output.connect_for_all(function(op)
+                        Pinnacle.tag.add(op, "1", "2", "3")
+                    end)
+                }
+
+                sleep_secs(1);
+
+                run_lua! { |Pinnacle|
+                    Pinnacle.tag.get("1"):switch_to()
+                }
+
+                sleep_secs(1);
+
+                run_lua! { |Pinnacle|
+                    Pinnacle.tag.get("2"):switch_to()
+                }
+
+                sleep_secs(1);
+
+                run_lua! { |Pinnacle|
+                    Pinnacle.tag.get("3"):switch_to()
+                }
+
+                sleep_secs(1);
+
+                run_lua! { |Pinnacle|
+                    Pinnacle.tag.switch_to_previous()
+                }
+
+                sleep_secs(1);
+
+                with_state(&sender, |state| {
+                    let output = state.pinnacle.focused_output().unwrap();
+                    let active = output.with_state(|state| {
+                        state
+                            .focused_tags()
+                            .map(|tag| tag.name())
+                            .collect::<Vec<_>>()
+                    });
+                    assert_eq!(active, vec!["2".to_string()]);
+                });
+
+                run_lua! { |Pinnacle|
+                    Pinnacle.tag.switch_to_previous(nil, 2)
+                }
+
+                sleep_secs(1);
+
+                with_state(&sender, |state| {
+                    let output = state.pinnacle.focused_output().unwrap();
+                    let active = output.with_state(|state| {
+                        state
+                            .focused_tags()
+                            .map(|tag| tag.name())
+                            .collect::<Vec<_>>()
+                    });
+                    assert_eq!(active, vec!["1".to_string()]);
+                });
+
+                Ok(())
+            })
+        }
+
+        #[tokio::main]
+        #[self::test]
+        async fn dynamic_tags() -> anyhow::Result<()> {
+            test_api(|sender| {
+                run_lua! { |Pinnacle|
+                    Pinnacle.tag.set_dynamic(true)
+                    local tags = Pinnacle.tag.add(Pinnacle.output.get_focused(), "1", "2", "3")
+                    tags[1]:set_active(true)
+                    Pinnacle.process.spawn("foot")
+                }
+
+                sleep_secs(1);
+
+                let tags = |state: &mut pinnacle::state::State| {
+                    let output = state.pinnacle.focused_output().unwrap();
+                    output.with_state(|state| {
+                        state
+                            .tags
+                            .iter()
+                            .map(|tag| (tag.name(), tag.hidden()))
+                            .collect::<Vec<_>>()
+                    })
+                };
+
+                with_state(&sender, move |state| {
+                    assert_eq!(
+                        tags(state),
+                        vec![
+                            ("1".to_string(), false),
+                            ("2".to_string(), false),
+                            ("3".to_string(), true),
+                        ]
+                    );
+                });
+
+                run_lua! { |Pinnacle|
+                    Pinnacle.window.get_all()[1]:move_to_tag(Pinnacle.tag.get("3"))
+                }
+
+                sleep_secs(1);
+
+                with_state(&sender, move |state| {
+                    assert_eq!(
+                        tags(state),
+                        vec![
+                            ("1".to_string(), false),
+                            ("2".to_string(), false),
+                            ("3".to_string(), false),
+                            ("4".to_string(), false),
+                        ]
+                    );
+                });
+
+                Ok(())
+            })
+        }
+
+        #[tokio::main]
+        #[self::test]
+        async fn reorder_and_switch_relative() -> anyhow::Result<()> {
+            test_api(|sender| {
+                run_lua! { |Pinnacle|
+                    Pinnacle.output.connect_for_all(function(op)
+                        Pinnacle.tag.add(op, "1", "2", "3")
+                    end)
+                }
+
+                sleep_secs(1);
+
+                run_lua! { |Pinnacle|
+                    Pinnacle.tag.get("3"):set_index(1)
+                    Pinnacle.tag.get("1"):move_right()
+                    Pinnacle.tag.get("3"):switch_to()
+                    Pinnacle.tag.switch_left()
+                }
+
+                sleep_secs(1);
+
+                with_state(&sender, |state| {
+                    let output = state.pinnacle.focused_output().unwrap();
+                    let (order, active) = output.with_state(|state| {
+                        (
+                            state.tags.iter().map(|tag| tag.name()).collect::<Vec<_>>(),
+                            state
+                                .focused_tags()
+                                .map(|tag| tag.name())
+                                .collect::<Vec<_>>(),
+                        )
+                    });
+                    assert_eq!(order, vec!["3", "2", "1"]);
+                    assert_eq!(active, vec!["1".to_string()]);
+                });
+
+                Ok(())
+            })
+        }
+
+        #[tokio::main]
+        #[self::test]
+        async fn remove_moves_windows_to_fallback() -> anyhow::Result<()> {
+            test_api(|sender| {
+                run_lua! { |Pinnacle|
+                    Pinnacle.output.connect_for_all(function(op)
+                        local tags = Pinnacle.tag.add(op, "1", "2")
+                        tags[1]:set_active(true)
+                    end)
+                }
+
+                sleep_secs(1);
+
+                run_lua! { |Pinnacle|
+                    Pinnacle.process.spawn("foot")
+                }
+
+                sleep_secs(1);
+
+                run_lua! { |Pinnacle|
+                    Pinnacle.tag.get("1"):remove()
+                }
+
+                sleep_secs(1);
+
+                with_state(&sender, |state| {
+                    assert_eq!(state.pinnacle.windows.len(), 1);
+                    let tags = state.pinnacle.windows[0].with_state(|state| state.tags.clone());
+                    assert_eq!(tags.len(), 1);
+                    assert_eq!(tags[0].name(), "2");
+                    assert!(tags[0].active());
+                });
+
+                Ok(())
+            })
+        }
     }
 }
 
@@ -917,3 +1213,389 @@ async fn spawned_window_has_correct_tags() -> anyhow::Result<()> {
         Ok(())
     })
 }
+
+// Input
+
+mod input {
+    use super::*;
+
+    #[tokio::main]
+    #[self::test]
+    async fn keybind_info() -> anyhow::Result<()> {
+        test_api(|sender| {
+            run_lua! { |Pinnacle|
+                Pinnacle.input.keybind({ "super" }, "a", function() end)
+                Pinnacle.input.keybind({ "super", "shift" }, "b", function() end, {
+                    group = "Window",
+                    description = "Do something",
+                })
+            }
+
+            sleep_secs(1);
+
+            with_state(&sender, |state| {
+                let info = state
+                    .pinnacle
+                    .input_state
+                    .keybind_info
+                    .iter()
+                    .map(|(_, info)| (info.group.clone(), info.description.clone()))
+                    .collect::<Vec<_>>();
+
+                assert_eq!(info.len(), 2);
+                assert!(info.contains(&(None, None)));
+                assert!(
+                    info.contains(&(Some("Window".to_string()), Some("Do something".to_string())))
+                );
+            });
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn keybind_flags() -> anyhow::Result<()> {
+        test_api(|sender| {
+            run_lua! { |Pinnacle|
+                Pinnacle.input.keybind({}, "a", function() end, { on_release = true })
+                Pinnacle.input.keybind({}, "b", function() end, { repeating = true })
+            }
+
+            sleep_secs(1);
+
+            with_state(&sender, |state| {
+                let mut flags = state
+                    .pinnacle
+                    .input_state
+                    .keybinds
+                    .iter()
+                    .map(|((_, keysym), bind)| (keysym.raw(), bind.on_release, bind.repeat))
+                    .collect::<Vec<_>>();
+                flags.sort();
+
+                assert_eq!(
+                    flags,
+                    [('a' as u32, true, false), ('b' as u32, false, true)]
+                );
+            });
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn keybind_priority() -> anyhow::Result<()> {
+        test_api(|sender| {
+            run_lua! { |Pinnacle|
+                Pinnacle.input.keybind({ "super" }, "a", function() end, { priority = 1 })
+                Pinnacle.input.keybind({ "super" }, "a", function() end, { description = "Lower" })
+                Pinnacle.input.keybind({ "super" }, "b", function() end)
+                Pinnacle.input.keybind({ "super" }, "b", function() end, { description = "Later" })
+            }
+
+            sleep_secs(1);
+
+            with_state(&sender, |state| {
+                use pinnacle::input::ModifierMask;
+                use xkbcommon::xkb::Keysym;
+
+                let input_state = &state.pinnacle.input_state;
+
+                let a = &input_state.keybinds[&(ModifierMask::SUPER, Keysym::a)];
+                assert_eq!(a.priority, 1);
+
+                let info = |keysym| {
+                    input_state
+                        .keybind_info
+                        .iter()
+                        .find(|(bind, _)| *bind == (ModifierMask::SUPER, keysym))
+                        .map(|(_, info)| info.description.clone())
+                        .unwrap()
+                };
+                assert_eq!(info(Keysym::a), None);
+                assert_eq!(info(Keysym::b), Some("Later".to_string()));
+            });
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn remap_key() -> anyhow::Result<()> {
+        test_api(|sender| {
+            run_lua! { |Pinnacle|
+                Pinnacle.input.remap_key({ keycode = 58 }, "Control_L")
+                Pinnacle.input.remap_key({ keycode = 1 }, { keycode = 58 }, { type = "keyboard" })
+            }
+
+            sleep_secs(1);
+
+            with_state(&sender, |state| {
+                use pinnacle::input::remap::RemapKey;
+
+                let remaps = &state.pinnacle.input_state.key_remaps;
+                assert_eq!(remaps.len(), 2);
+
+                assert_eq!(remaps[0].from, RemapKey::Keycode(58));
+                assert_eq!(
+                    remaps[0].to,
+                    RemapKey::Keysym(xkbcommon::xkb::Keysym::Control_L)
+                );
+                assert_eq!(remaps[0].filter, Default::default());
+
+                assert_eq!(remaps[1].to, RemapKey::Keycode(58));
+                assert_eq!(
+                    remaps[1].filter.device_type,
+                    Some(pinnacle::input::libinput::DeviceType::Keyboard)
+                );
+            });
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn map_touch_to_output() -> anyhow::Result<()> {
+        test_api(|sender| {
+            run_lua! { |Pinnacle|
+                Pinnacle.input.map_touch_to_output(Pinnacle.output.get_focused())
+                Pinnacle.input.map_touch_to_output({ name = "DP-2" }, { type = "touchscreen" })
+            }
+
+            sleep_secs(1);
+
+            with_state(&sender, |state| {
+                let focused_name = state.pinnacle.focused_output().unwrap().name();
+
+                let mappings = &state.pinnacle.input_state.touch_output_mappings;
+                assert_eq!(mappings.len(), 2);
+
+                assert_eq!(mappings[0].output.0, focused_name);
+                assert_eq!(mappings[0].filter, Default::default());
+
+                assert_eq!(mappings[1].output.0, "DP-2");
+                assert_eq!(
+                    mappings[1].filter.device_type,
+                    Some(pinnacle::input::libinput::DeviceType::Touchscreen)
+                );
+            });
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn set_scroll_factor() -> anyhow::Result<()> {
+        test_api(|sender| {
+            run_lua! { |Pinnacle|
+                Pinnacle.input.set_scroll_factor(0.5)
+                Pinnacle.input.set_scroll_factor(1.5, { type = "touchpad" })
+            }
+
+            sleep_secs(1);
+
+            with_state(&sender, |state| {
+                let factors = &state.pinnacle.input_state.scroll_factors;
+                assert_eq!(factors.len(), 2);
+
+                assert_eq!(factors[0].factor, 0.5);
+                assert_eq!(factors[0].filter, Default::default());
+
+                assert_eq!(factors[1].factor, 1.5);
+                assert_eq!(
+                    factors[1].filter.device_type,
+                    Some(pinnacle::input::libinput::DeviceType::Touchpad)
+                );
+            });
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn set_cursor_theme() -> anyhow::Result<()> {
+        test_api(|sender| {
+            run_lua! { |Pinnacle|
+                Pinnacle.input.set_cursor_theme("Adwaita", 32)
+                Pinnacle.input.set_cursor_theme(nil, 48)
+            }
+
+            sleep_secs(1);
+
+            with_state(&sender, |state| {
+                assert_eq!(state.pinnacle.cursor.theme_name(), "Adwaita");
+                assert_eq!(state.pinnacle.cursor.size(), 48);
+            });
+
+            assert_eq!(std::env::var("XCURSOR_THEME").as_deref(), Ok("Adwaita"));
+            assert_eq!(std::env::var("XCURSOR_SIZE").as_deref(), Ok("48"));
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn set_tablet_settings() -> anyhow::Result<()> {
+        test_api(|sender| {
+            run_lua! { |Pinnacle|
+                Pinnacle.input.set_tablet_settings({
+                    region = { x = 10, y = 20, width = 300, height = 200 },
+                    pressure_curve = { 0, 0.5, 0.5, 1 },
+                })
+                Pinnacle.input.set_tablet_settings(
+                    { button_remaps = { [0x14b] = 0x14c } },
+                    { name = "Wacom*" }
+                )
+            }
+
+            sleep_secs(1);
+
+            with_state(&sender, |state| {
+                use pinnacle::input::tablet::{PressureCurve, TabletArea, TabletConfig};
+                use smithay::utils::Rectangle;
+
+                let settings = &state.pinnacle.input_state.tablet_settings;
+                assert_eq!(settings.len(), 3);
+
+                assert_eq!(
+                    settings[0].config,
+                    TabletConfig::Area(TabletArea::Region(Rectangle::from_loc_and_size(
+                        (10, 20),
+                        (300, 200)
+                    )))
+                );
+                assert_eq!(
+                    settings[1].config,
+                    TabletConfig::PressureCurve(PressureCurve {
+                        control_1: (0.0, 0.5),
+                        control_2: (0.5, 1.0),
+                    })
+                );
+                assert_eq!(
+                    settings[2].config,
+                    TabletConfig::ButtonRemap {
+                        from: 0x14b,
+                        to: 0x14c
+                    }
+                );
+                assert_eq!(settings[2].filter.name.as_deref(), Some("Wacom*"));
+            });
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn set_num_lock() -> anyhow::Result<()> {
+        test_api(|sender| {
+            run_lua! { |Pinnacle|
+                Pinnacle.input.set_num_lock(true)
+            }
+
+            sleep_secs(1);
+
+            with_state(&sender, |state| {
+                let keyboard = state.pinnacle.seat.get_keyboard().unwrap();
+                assert!(keyboard.modifier_state().num_lock);
+                assert!(!keyboard.modifier_state().caps_lock);
+            });
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn accessibility_settings() -> anyhow::Result<()> {
+        test_api(|sender| {
+            run_lua! { |Pinnacle|
+                Pinnacle.input.set_sticky_keys(true)
+                Pinnacle.input.set_slow_keys(300)
+                Pinnacle.input.set_mouse_keys(true)
+            }
+
+            sleep_secs(1);
+
+            with_state(&sender, |state| {
+                let input_state = &state.pinnacle.input_state;
+                assert!(input_state.sticky_keys.enabled);
+                assert!(input_state.mouse_keys.enabled);
+                assert_eq!(
+                    input_state.slow_keys.delay,
+                    Some(std::time::Duration::from_millis(300))
+                );
+            });
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn switch_xkb_layout() -> anyhow::Result<()> {
+        test_api(|sender| {
+            run_lua! { |Pinnacle|
+                Pinnacle.input.set_xkb_config({ layout = "us,fr" })
+                Pinnacle.input.switch_xkb_layout(2)
+            }
+
+            sleep_secs(1);
+
+            with_state(&sender, |state| {
+                let layouts = state.xkb_layouts();
+                assert_eq!(layouts.names.len(), 2);
+                assert_eq!(layouts.active, 1);
+            });
+
+            run_lua! { |Pinnacle|
+                Pinnacle.input.next_xkb_layout()
+            }
+
+            sleep_secs(1);
+
+            with_state(&sender, |state| {
+                assert_eq!(state.xkb_layouts().active, 0);
+                assert_eq!(state.pinnacle.input_state.xkb_layout, Some(0));
+            });
+
+            Ok(())
+        })
+    }
+    #[tokio::main]
+    #[self::test]
+    async fn warp_pointer() -> anyhow::Result<()> {
+        test_api(|sender| {
+            run_lua! { |Pinnacle|
+                Pinnacle.input.warp_pointer(100, 200)
+            }
+
+            sleep_secs(1);
+
+            with_state(&sender, |state| {
+                let pointer = state.pinnacle.seat.get_pointer().unwrap();
+                assert_eq!(pointer.current_location(), (100.0, 200.0).into());
+            });
+
+            run_lua! { |Pinnacle|
+                Pinnacle.input.warp_pointer_to_output(Pinnacle.output.get_focused())
+            }
+
+            sleep_secs(1);
+
+            with_state(&sender, |state| {
+                let pointer = state.pinnacle.seat.get_pointer().unwrap();
+                assert_eq!(pointer.current_location(), (960.0, 540.0).into());
+            });
+
+            Ok(())
+        })
+    }
+}
@@ -317,7 +317,7 @@ mod output {
     }
 
     mod handle {
-        use pinnacle_api::output::Transform;
+        use pinnacle_api::output::{Alignment, Transform};
 
         use super::*;
 
@@ -359,5 +359,145 @@ mod output {
                 Ok(())
             })
         }
+
+        #[tokio::main]
+        #[self::test]
+        async fn set_enabled() -> anyhow::Result<()> {
+            test_api(|sender| {
+                with_state(&sender, |state| {
+                    state.pinnacle.new_output("Second", (300, 200).into());
+                });
+
+                sleep_secs(1);
+
+                run_rust(|api| {
+                    let second = api.output.get_by_name("Second").unwrap();
+                    api.tag.add(&second, ["A", "B"]);
+                    second.set_enabled(false);
+                });
+
+                sleep_secs(1);
+
+                with_state(&sender, |state| {
+                    let original_op = output_for_name(state, DUMMY_OUTPUT_NAME);
+                    assert!(state
+                        .pinnacle
+                        .space
+                        .outputs()
+                        .all(|op| op.name() != "Second"));
+                    assert_eq!(
+                        original_op.with_state(|state| state
+                            .tags
+                            .iter()
+                            .map(|t| t.name())
+                            .collect::<Vec<_>>()),
+                        vec!["A", "B"]
+                    );
+                });
+
+                run_rust(|api| {
+                    let second = api.output.get_by_name("Second").unwrap();
+                    assert_eq!(second.enabled(), Some(false));
+                    second.set_enabled(true);
+                });
+
+                sleep_secs(1);
+
+                with_state(&sender, |state| {
+                    let original_op = output_for_name(state, DUMMY_OUTPUT_NAME);
+                    let second_op = output_for_name(state, "Second");
+                    assert!(state.pinnacle.disabled_outputs.is_empty());
+                    assert!(original_op.with_state(|state| state.tags.is_empty()));
+                    assert_eq!(
+                        second_op.with_state(|state| state
+                            .tags
+                            .iter()
+                            .map(|t| t.name())
+                            .collect::<Vec<_>>()),
+                        vec!["A", "B"]
+                    );
+                });
+
+                Ok(())
+            })
+        }
+
+        #[tokio::main]
+        #[self::test]
+        async fn set_primary() -> anyhow::Result<()> {
+            test_api(|sender| {
+                with_state(&sender, |state| {
+                    state.pinnacle.new_output("Second", (300, 200).into());
+                });
+
+                sleep_secs(1);
+
+                run_rust(|api| {
+                    api.output.get_by_name("Second").unwrap().set_primary();
+                });
+
+                sleep_secs(1);
+
+                with_state(&sender, |state| {
+                    let second_op = output_for_name(state, "Second");
+                    assert_eq!(state.pinnacle.primary_output(), Some(second_op.clone()));
+                    assert_eq!(state.pinnacle.new_window_output(), Some(second_op));
+                });
+
+                run_rust(|api| {
+                    assert_eq!(
+                        api.output.get_by_name("Second").unwrap().primary(),
+                        Some(true)
+                    );
+                    assert_eq!(
+                        api.output.get_by_name(DUMMY_OUTPUT_NAME).unwrap().primary(),
+                        Some(false)
+                    );
+                });
+
+                Ok(())
+            })
+        }
+
+        #[tokio::main]
+        #[self::test]
+        async fn set_loc_adj_to_follows_resize() -> anyhow::Result<()> {
+            test_api(|sender| {
+                with_state(&sender, |state| {
+                    state.pinnacle.new_output("Second", (300, 200).into());
+                });
+
+                sleep_secs(1);
+
+                run_rust(|api| {
+                    let original = api.output.get_by_name(DUMMY_OUTPUT_NAME).unwrap();
+                    let second = api.output.get_by_name("Second").unwrap();
+                    second.set_loc_adj_to(&original, Alignment::BottomAlignCenter);
+                });
+
+                sleep_secs(1);
+
+                with_state(&sender, |state| {
+                    let second_op = output_for_name(state, "Second");
+                    assert_eq!(second_op.current_location(), (810, 1080).into());
+                });
+
+                run_rust(|api| {
+                    api.output
+                        .get_by_name(DUMMY_OUTPUT_NAME)
+                        .unwrap()
+                        .set_scale(2.0);
+                });
+
+                sleep_secs(1);
+
+                with_state(&sender, |state| {
+                    let second_op = output_for_name(state, "Second");
+                    assert_eq!(second_op.current_location(), (330, 540).into());
+                });
+
+                Ok(())
+            })
+        }
     }
 }